@@ -1,12 +1,13 @@
+use std::collections::HashMap;
 use std::fs;
 use std::io::{Cursor, self};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
-use image::{DynamicImage, GenericImageView, GrayImage};
+use image::{DynamicImage, GenericImageView, GrayImage, Rgb32FImage};
 use image::imageops::{self, FilterType};
-use ndarray::{Array, IxDyn};
+use ndarray::{Array, Axis, IxDyn};
 use ort::{Environment, Session, SessionBuilder, Value};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -16,6 +17,7 @@ use tokio::sync::Mutex as TokioMutex;
 use tokenizers::Tokenizer;
 
 use crate::file_management;
+use crate::image_processing::with_depth_of;
 
 const ENCODER_URL: &str = "https://huggingface.co/CyberTimon/RapidRAW-Models/resolve/main/vit_t_encoder.onnx?download=true";
 const DECODER_URL: &str = "https://huggingface.co/CyberTimon/RapidRAW-Models/resolve/main/vit_t_decoder.onnx?download=true";
@@ -30,17 +32,62 @@ const U2NETP_FILENAME: &str = "u2net.onnx";
 const U2NETP_INPUT_SIZE: u32 = 320;
 const U2NETP_SHA256: &str = "8d10d2f3bb75ae3b6d527c77944fc5e7dcd94b29809d47a739a7a728a912b491";
 
+const CUTOUT_WORKING_DIM: u32 = 2048;
+const CUTOUT_MATTE_LOW: f32 = 0.15;
+const CUTOUT_MATTE_HIGH: f32 = 0.85;
+const CUTOUT_GUIDED_EPSILON: f32 = 1e-3;
+
 const SKYSEG_URL: &str = "https://huggingface.co/CyberTimon/RapidRAW-Models/resolve/main/skyseg-u2net.onnx?download=true";
 const SKYSEG_FILENAME: &str = "skyseg_u2net.onnx";
 const SKYSEG_INPUT_SIZE: u32 = 320;
 const SKYSEG_SHA256: &str = "ab9c34c64c3d821220a2886a4a06da4642ffa14d5b30e8d5339056a089aa1d39";
 
+// Models without a published hash are pinned to the SHA-256 of their first complete download.
+const FACE_DETECTOR_URL: &str = "https://huggingface.co/CyberTimon/RapidRAW-Models/resolve/main/ultraface_rfb_320.onnx?download=true";
+const FACE_DETECTOR_FILENAME: &str = "ultraface_rfb_320.onnx";
+const FACE_DETECTOR_SHA256: &str = "";
+const FACE_INPUT_WIDTH: u32 = 320;
+const FACE_INPUT_HEIGHT: u32 = 240;
+const FACE_SCORE_THRESHOLD: f32 = 0.7;
+const FACE_NMS_IOU_THRESHOLD: f32 = 0.3;
+
 const CLIP_MODEL_URL: &str = "https://huggingface.co/CyberTimon/RapidRAW-Models/resolve/main/clip_model.onnx?download=true";
 const CLIP_MODEL_FILENAME: &str = "clip_model.onnx";
 const CLIP_TOKENIZER_URL: &str = "https://huggingface.co/CyberTimon/RapidRAW-Models/resolve/main/clip_tokenizer.json?download=true";
 const CLIP_TOKENIZER_FILENAME: &str = "clip_tokenizer.json";
 const CLIP_MODEL_SHA256: &str = "57879bb1c23cdeb350d23569dd251ed4b740a96d747c529e94a2bb8040ac5d00";
 
+const UPSCALE_MODEL_URL: &str = "https://huggingface.co/CyberTimon/RapidRAW-Models/resolve/main/realesrgan_x4plus.onnx?download=true";
+const UPSCALE_MODEL_FILENAME: &str = "realesrgan_x4plus.onnx";
+const UPSCALE_MODEL_SCALE: u32 = 4;
+const UPSCALE_MODEL_SHA256: &str = "";
+
+const DENOISE_MODEL_URL: &str = "https://huggingface.co/CyberTimon/RapidRAW-Models/resolve/main/nafnet_denoise.onnx?download=true";
+const DENOISE_MODEL_FILENAME: &str = "nafnet_denoise.onnx";
+const DENOISE_MODEL_SHA256: &str = "";
+
+const LAMA_MODEL_URL: &str = "https://huggingface.co/CyberTimon/RapidRAW-Models/resolve/main/lama_fp32.onnx?download=true";
+const LAMA_MODEL_FILENAME: &str = "lama_fp32.onnx";
+const LAMA_MODEL_SHA256: &str = "";
+const LAMA_INPUT_SIZE: u32 = 512;
+const LAMA_CONTEXT_MARGIN: f32 = 0.5;
+
+const EYE_STATE_MODEL_URL: &str = "https://huggingface.co/CyberTimon/RapidRAW-Models/resolve/main/open_closed_eye.onnx?download=true";
+const EYE_STATE_MODEL_FILENAME: &str = "open_closed_eye.onnx";
+const EYE_STATE_MODEL_SHA256: &str = "";
+const EYE_STATE_INPUT_SIZE: u32 = 32;
+const EYE_STATE_CLOSED_INDEX: usize = 0;
+const EYE_CLOSED_THRESHOLD: f32 = 0.5;
+
+const FACE_EMBEDDING_MODEL_URL: &str = "https://huggingface.co/CyberTimon/RapidRAW-Models/resolve/main/face_embedding.onnx?download=true";
+const FACE_EMBEDDING_MODEL_FILENAME: &str = "face_embedding.onnx";
+const FACE_EMBEDDING_MODEL_SHA256: &str = "";
+const FACE_EMBEDDING_INPUT_SIZE: u32 = 112;
+const FACE_EMBEDDING_CROP_SCALE: f32 = 1.2;
+
+const MODEL_TILE_SIZE: u32 = 256;
+const MODEL_TILE_PADDING: u32 = 16;
+
 pub struct AiModels {
     pub sam_encoder: Session,
     pub sam_decoder: Session,
@@ -57,9 +104,18 @@ pub struct ImageEmbeddings {
     pub original_size: (u32, u32),
 }
 
+#[derive(Clone)]
+pub struct DenoisedImage {
+    pub path_hash: String,
+    pub image: Arc<DynamicImage>,
+}
+
 pub struct AiState {
     pub models: Arc<AiModels>,
     pub embeddings: Option<ImageEmbeddings>,
+    // The decoder's low-resolution logits of the last prediction, keyed by image path hash and
+    // sub-mask id, so a refinement only starts from the candidates of the same mask.
+    pub sam_low_res_masks: HashMap<(String, String), Vec<Array<f32, IxDyn>>>,
 }
 
 fn get_models_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
@@ -73,24 +129,38 @@ fn get_models_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf> {
     Ok(models_dir)
 }
 
+// Error pages would otherwise be saved as the model, and an interrupted download would leave a
+// truncated file behind; both would look like a valid model to the exists() checks.
 async fn download_model(url: &str, dest: &Path) -> Result<()> {
-    let response = reqwest::get(url).await?;
-    let mut file = fs::File::create(dest)?;
+    let response = reqwest::get(url).await?.error_for_status()?;
     let mut content = Cursor::new(response.bytes().await?);
+    let file_name = dest.file_name().and_then(|n| n.to_str()).unwrap_or("model");
+    let temp_path = dest.with_file_name(format!("{}.part", file_name));
+    let mut file = fs::File::create(&temp_path)?;
     std::io::copy(&mut content, &mut file)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&temp_path, dest)?;
     Ok(())
 }
 
-fn verify_sha256(path: &Path, expected_hash: &str) -> Result<bool> {
-    if !path.exists() {
-        return Ok(false);
-    }
+fn sha256_hex(path: &Path) -> Result<String> {
     let mut file = fs::File::open(path)?;
     let mut hasher = Sha256::new();
     io::copy(&mut file, &mut hasher)?;
-    let hash = hasher.finalize();
-    let hex_hash = format!("{:x}", hash);
-    Ok(hex_hash == expected_hash)
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn verify_sha256(path: &Path, expected_hash: &str) -> Result<bool> {
+    if !path.exists() || expected_hash.is_empty() {
+        return Ok(false);
+    }
+    Ok(sha256_hex(path)? == expected_hash)
+}
+
+fn pinned_hash_path(dest: &Path) -> PathBuf {
+    let file_name = dest.file_name().and_then(|n| n.to_str()).unwrap_or("model");
+    dest.with_file_name(format!("{}.sha256", file_name))
 }
 
 async fn download_and_verify_model(
@@ -102,6 +172,13 @@ async fn download_and_verify_model(
     model_name: &str,
 ) -> Result<()> {
     let dest_path = models_dir.join(filename);
+    let pin_path = pinned_hash_path(&dest_path);
+    let expected_hash = if expected_hash.is_empty() {
+        fs::read_to_string(&pin_path).map(|h| h.trim().to_string()).unwrap_or_default()
+    } else {
+        expected_hash.to_string()
+    };
+    let expected_hash = expected_hash.as_str();
     let is_valid = verify_sha256(&dest_path, expected_hash)?;
 
     if !is_valid {
@@ -113,7 +190,9 @@ async fn download_and_verify_model(
         download_model(url, &dest_path).await?;
         let _ = app_handle.emit("ai-model-download-finish", model_name);
 
-        if !verify_sha256(&dest_path, expected_hash)? {
+        if expected_hash.is_empty() {
+            fs::write(&pin_path, sha256_hex(&dest_path)?)?;
+        } else if !verify_sha256(&dest_path, expected_hash)? {
             return Err(anyhow::anyhow!("Failed to verify model {} after download. Hash mismatch.", model_name));
         }
     }
@@ -160,6 +239,7 @@ pub async fn get_or_init_ai_models(
     download_and_verify_model(app_handle, &models_dir, U2NETP_FILENAME, U2NETP_URL, U2NETP_SHA256, "Foreground Model").await?;
     download_and_verify_model(app_handle, &models_dir, SKYSEG_FILENAME, SKYSEG_URL, SKYSEG_SHA256, "Sky Model").await?;
 
+
     let environment = Arc::new(Environment::builder().with_name("AI").build()?);
     let mut clip_model = None;
     let mut clip_tokenizer = None;
@@ -206,6 +286,7 @@ pub async fn get_or_init_ai_models(
     *ai_state_lock = Some(AiState {
         models: models.clone(),
         embeddings: None,
+        sam_low_res_masks: HashMap::new(),
     });
 
     Ok(models)
@@ -249,27 +330,53 @@ pub fn generate_image_embeddings(
     })
 }
 
+pub struct SamPrediction {
+    pub mask: GrayImage,
+    pub score: f32,
+    pub low_res_mask: Array<f32, IxDyn>,
+}
+
 pub fn run_sam_decoder(
     decoder: &Session,
     embeddings: &ImageEmbeddings,
-    start_point: (f64, f64),
-    end_point: (f64, f64),
-) -> Result<GrayImage> {
+    prompt_box: Option<((f64, f64), (f64, f64))>,
+    points: &[SamPoint],
+    previous_mask: Option<&Array<f32, IxDyn>>,
+) -> Result<Vec<SamPrediction>> {
     let (orig_width, orig_height) = embeddings.original_size;
 
     let long_side = orig_width.max(orig_height) as f64;
     let scale = SAM_INPUT_SIZE as f64 / long_side;
 
-    let x1 = start_point.0.min(end_point.0) * scale;
-    let y1 = start_point.1.min(end_point.1) * scale;
-    let x2 = start_point.0.max(end_point.0) * scale;
-    let y2 = start_point.1.max(end_point.1) * scale;
+    let mut coords: Vec<f32> = Vec::new();
+    let mut labels: Vec<f32> = Vec::new();
+    for point in points {
+        coords.extend([(point.x * scale) as f32, (point.y * scale) as f32]);
+        labels.push(if point.positive { 1.0 } else { 0.0 });
+    }
+    match prompt_box {
+        Some((start_point, end_point)) => {
+            let x1 = start_point.0.min(end_point.0) * scale;
+            let y1 = start_point.1.min(end_point.1) * scale;
+            let x2 = start_point.0.max(end_point.0) * scale;
+            let y2 = start_point.1.max(end_point.1) * scale;
+            coords.extend([x1 as f32, y1 as f32, x2 as f32, y2 as f32]);
+            labels.extend([2.0, 3.0]);
+        }
+        None => {
+            coords.extend([0.0, 0.0]);
+            labels.push(-1.0);
+        }
+    }
 
-    let point_coords = Array::from_shape_vec((1, 2, 2), vec![x1 as f32, y1 as f32, x2 as f32, y2 as f32])?.into_dyn();
-    let point_labels = Array::from_shape_vec((1, 2), vec![2.0f32, 3.0f32])?.into_dyn();
-    
-    let mask_input: Array<f32, IxDyn> = Array::zeros((1, 1, 256, 256)).into_dyn();
-    let has_mask_input = Array::from_elem((1,), 0.0f32).into_dyn();
+    let num_points = labels.len();
+    let point_coords = Array::from_shape_vec((1, num_points, 2), coords)?.into_dyn();
+    let point_labels = Array::from_shape_vec((1, num_points), labels)?.into_dyn();
+
+    let (mask_input, has_mask_input) = match previous_mask {
+        Some(mask) => (mask.clone(), Array::from_elem((1,), 1.0f32).into_dyn()),
+        None => (Array::zeros((1, 1, 256, 256)).into_dyn(), Array::from_elem((1,), 0.0f32).into_dyn()),
+    };
     let orig_im_size = Array::from_shape_vec((2,), vec![orig_height as f32, orig_width as f32])?.into_dyn();
 
     let embeddings_values = embeddings.embeddings.as_standard_layout();
@@ -290,22 +397,45 @@ pub fn run_sam_decoder(
 
     let outputs = decoder.run(inputs)?;
     let mask_tensor = outputs[0].try_extract::<f32>()?.view().to_owned();
-    
+    let scores: Vec<f32> = match outputs.get(1) {
+        Some(output) => output.try_extract::<f32>()?.view().iter().cloned().collect(),
+        None => Vec::new(),
+    };
+    let low_res_tensor = match outputs.get(2) {
+        Some(output) => Some(output.try_extract::<f32>()?.view().to_owned()),
+        None => None,
+    };
+
     let mask_dims = mask_tensor.shape();
+    let num_masks = mask_dims[1];
     let mask_height = mask_dims[2];
     let mask_width = mask_dims[3];
 
-    let mask_data: Vec<u8> = mask_tensor
-        .iter()
-        .map(|&val| if val > 0.0 { 255 } else { 0 })
-        .collect();
-
-    let gray_mask = GrayImage::from_raw(mask_width as u32, mask_height as u32, mask_data)
-        .ok_or_else(|| anyhow::anyhow!("Failed to create mask image from raw data"))?;
+    let mut predictions = Vec::with_capacity(num_masks);
+    for i in 0..num_masks {
+        let mask_data: Vec<u8> = mask_tensor
+            .index_axis(Axis(1), i)
+            .iter()
+            .map(|&val| if val > 0.0 { 255 } else { 0 })
+            .collect();
+
+        let gray_mask = GrayImage::from_raw(mask_width as u32, mask_height as u32, mask_data)
+            .ok_or_else(|| anyhow::anyhow!("Failed to create mask image from raw data"))?;
+
+        let low_res_mask = match &low_res_tensor {
+            Some(tensor) => tensor.index_axis(Axis(1), i).insert_axis(Axis(1)).to_owned(),
+            None => Array::zeros((1, 1, 256, 256)).into_dyn(),
+        };
+
+        predictions.push(SamPrediction {
+            mask: image::imageops::blur(&gray_mask, 3.0),
+            score: scores.get(i).copied().unwrap_or(0.0),
+            low_res_mask,
+        });
+    }
 
-    let feathered_mask = image::imageops::blur(&gray_mask, 3.0);
-    
-    Ok(feathered_mask)
+    predictions.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(predictions)
 }
 
 pub fn run_sky_seg_model(
@@ -438,6 +568,579 @@ pub fn run_u2netp_model(
     Ok(final_mask)
 }
 
+fn box_mean(data: &[f32], width: usize, height: usize, radius: usize) -> Vec<f32> {
+    let stride = width + 1;
+    let mut integral = vec![0.0f64; stride * (height + 1)];
+    for y in 0..height {
+        let mut row_sum = 0.0f64;
+        for x in 0..width {
+            row_sum += data[y * width + x] as f64;
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row_sum;
+        }
+    }
+
+    let mut output = vec![0.0f32; width * height];
+    for y in 0..height {
+        let (y0, y1) = (y.saturating_sub(radius), (y + radius + 1).min(height));
+        for x in 0..width {
+            let (x0, x1) = (x.saturating_sub(radius), (x + radius + 1).min(width));
+            let sum = integral[y1 * stride + x1] - integral[y0 * stride + x1] - integral[y1 * stride + x0]
+                + integral[y0 * stride + x0];
+            output[y * width + x] = (sum / ((x1 - x0) * (y1 - y0)) as f64) as f32;
+        }
+    }
+    output
+}
+
+// Tightens the low-resolution U2Net matte and snaps its edges to the image with a guided filter.
+pub fn refine_matte(image: &DynamicImage, mask: &GrayImage) -> GrayImage {
+    let (width, height) = mask.dimensions();
+    let (w, h) = (width as usize, height as usize);
+    let guide: Vec<f32> = image
+        .resize_exact(width, height, FilterType::Triangle)
+        .to_luma8()
+        .pixels()
+        .map(|p| p[0] as f32 / 255.0)
+        .collect();
+    let matte: Vec<f32> = mask
+        .pixels()
+        .map(|p| {
+            let t = ((p[0] as f32 / 255.0 - CUTOUT_MATTE_LOW) / (CUTOUT_MATTE_HIGH - CUTOUT_MATTE_LOW)).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        })
+        .collect();
+
+    let radius = (width.max(height) / 400).max(2) as usize;
+    let guide_sq: Vec<f32> = guide.iter().map(|v| v * v).collect();
+    let guide_matte: Vec<f32> = guide.iter().zip(&matte).map(|(i, p)| i * p).collect();
+
+    let mean_i = box_mean(&guide, w, h, radius);
+    let mean_p = box_mean(&matte, w, h, radius);
+    let mean_ii = box_mean(&guide_sq, w, h, radius);
+    let mean_ip = box_mean(&guide_matte, w, h, radius);
+
+    let mut a = vec![0.0f32; w * h];
+    let mut b = vec![0.0f32; w * h];
+    for i in 0..w * h {
+        let variance = mean_ii[i] - mean_i[i] * mean_i[i];
+        let covariance = mean_ip[i] - mean_i[i] * mean_p[i];
+        a[i] = covariance / (variance + CUTOUT_GUIDED_EPSILON);
+        b[i] = mean_p[i] - a[i] * mean_i[i];
+    }
+    let mean_a = box_mean(&a, w, h, radius);
+    let mean_b = box_mean(&b, w, h, radius);
+
+    GrayImage::from_fn(width, height, |x, y| {
+        let i = y as usize * w + x as usize;
+        let value = (mean_a[i] * guide[i] + mean_b[i]).clamp(0.0, 1.0);
+        image::Luma([(value * 255.0).round() as u8])
+    })
+}
+
+pub fn remove_background(image: &DynamicImage, u2netp_session: &Session) -> Result<DynamicImage> {
+    let (width, height) = image.dimensions();
+    let working = if width.max(height) > CUTOUT_WORKING_DIM {
+        image.thumbnail(CUTOUT_WORKING_DIM, CUTOUT_WORKING_DIM)
+    } else {
+        image.clone()
+    };
+
+    let mask = run_u2netp_model(&working, u2netp_session)?;
+    let matte = refine_matte(&working, &mask);
+    let matte = if matte.dimensions() != (width, height) {
+        imageops::resize(&matte, width, height, FilterType::Triangle)
+    } else {
+        matte
+    };
+
+    if matches!(image, DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)) {
+        let mut rgba = image.to_rgba32f();
+        for (pixel, alpha) in rgba.pixels_mut().zip(matte.pixels()) {
+            pixel[3] *= alpha[0] as f32 / 255.0;
+        }
+        return Ok(DynamicImage::ImageRgba32F(rgba));
+    }
+
+    let mut rgba = image.to_rgba16();
+    for (pixel, alpha) in rgba.pixels_mut().zip(matte.pixels()) {
+        pixel[3] = ((pixel[3] as u32 * alpha[0] as u32) / 255) as u16;
+    }
+    Ok(DynamicImage::ImageRgba16(rgba))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FaceDetection {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub score: f32,
+}
+
+impl FaceDetection {
+    fn area(&self) -> f32 {
+        (self.x2 - self.x1).max(0.0) * (self.y2 - self.y1).max(0.0)
+    }
+
+    pub fn iou(&self, other: &FaceDetection) -> f32 {
+        let ix = (self.x2.min(other.x2) - self.x1.max(other.x1)).max(0.0);
+        let iy = (self.y2.min(other.y2) - self.y1.max(other.y1)).max(0.0);
+        let intersection = ix * iy;
+        let union = self.area() + other.area() - intersection;
+        if union > 0.0 { intersection / union } else { 0.0 }
+    }
+}
+
+pub fn run_face_detector(
+    image: &DynamicImage,
+    face_detector_session: &Session,
+) -> Result<Vec<FaceDetection>> {
+    let resized_rgb = image
+        .resize_exact(FACE_INPUT_WIDTH, FACE_INPUT_HEIGHT, FilterType::Triangle)
+        .to_rgb8();
+
+    let mut input_tensor: Array<f32, _> = Array::zeros((1, 3, FACE_INPUT_HEIGHT as usize, FACE_INPUT_WIDTH as usize));
+    for y in 0..FACE_INPUT_HEIGHT {
+        for x in 0..FACE_INPUT_WIDTH {
+            let pixel = resized_rgb.get_pixel(x, y);
+            for c in 0..3 {
+                input_tensor[[0, c, y as usize, x as usize]] = (pixel[c] as f32 - 127.0) / 128.0;
+            }
+        }
+    }
+
+    let input_tensor_dyn = input_tensor.into_dyn();
+    let input_values = input_tensor_dyn.as_standard_layout();
+    let inputs = vec![Value::from_array(face_detector_session.allocator(), &input_values)?];
+
+    let outputs = face_detector_session.run(inputs)?;
+    let scores = outputs[0].try_extract::<f32>()?.view().to_owned();
+    let boxes = outputs[1].try_extract::<f32>()?.view().to_owned();
+
+    let scores: Vec<f32> = scores.iter().cloned().collect();
+    let boxes: Vec<f32> = boxes.iter().cloned().collect();
+
+    let mut candidates: Vec<FaceDetection> = scores
+        .chunks_exact(2)
+        .zip(boxes.chunks_exact(4))
+        .filter(|(score, _)| score[1] > FACE_SCORE_THRESHOLD)
+        .map(|(score, b)| FaceDetection {
+            x1: b[0].clamp(0.0, 1.0),
+            y1: b[1].clamp(0.0, 1.0),
+            x2: b[2].clamp(0.0, 1.0),
+            y2: b[3].clamp(0.0, 1.0),
+            score: score[1],
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut faces: Vec<FaceDetection> = Vec::new();
+    for candidate in candidates {
+        if faces.iter().all(|face| face.iou(&candidate) < FACE_NMS_IOU_THRESHOLD) {
+            faces.push(candidate);
+        }
+    }
+    faces.sort_by(|a, b| a.x1.partial_cmp(&b.x1).unwrap_or(std::cmp::Ordering::Equal));
+
+    Ok(faces)
+}
+
+fn skin_likelihood(pixel: &image::Rgb<u8>) -> f32 {
+    let (r, g, b) = (pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+    let cb = 128.0 - 0.168736 * r - 0.331264 * g + 0.5 * b;
+    let cr = 128.0 + 0.5 * r - 0.418688 * g - 0.081312 * b;
+    let cb_dist = ((cb - 102.0) / 25.0).abs();
+    let cr_dist = ((cr - 153.0) / 20.0).abs();
+    (1.0 - cb_dist.max(cr_dist)).clamp(0.0, 1.0).powf(0.5)
+}
+
+pub fn generate_face_mask(image: &DynamicImage, face: &FaceDetection, refine_skin: bool) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let cx = (face.x1 + face.x2) * 0.5 * width as f32;
+    let cy = (face.y1 + face.y2) * 0.5 * height as f32;
+    let rx = ((face.x2 - face.x1) * width as f32 * 0.6).max(1.0);
+    let ry = ((face.y2 - face.y1) * height as f32 * 0.65).max(1.0);
+
+    let rgb = if refine_skin { Some(image.to_rgb8()) } else { None };
+    let mut mask = GrayImage::new(width, height);
+
+    let x_start = (cx - rx).floor().max(0.0) as u32;
+    let x_end = ((cx + rx).ceil().max(0.0) as u32).min(width);
+    let y_start = (cy - ry).floor().max(0.0) as u32;
+    let y_end = ((cy + ry).ceil().max(0.0) as u32).min(height);
+
+    for y in y_start..y_end {
+        for x in x_start..x_end {
+            let dx = (x as f32 + 0.5 - cx) / rx;
+            let dy = (y as f32 + 0.5 - cy) / ry;
+            let dist = (dx * dx + dy * dy).sqrt();
+            let mut value = 1.0 - ((dist - 0.75) / 0.25).clamp(0.0, 1.0);
+            if value <= 0.0 {
+                continue;
+            }
+            if let Some(rgb) = &rgb {
+                value *= skin_likelihood(rgb.get_pixel(x, y));
+            }
+            mask.put_pixel(x, y, image::Luma([(value * 255.0) as u8]));
+        }
+    }
+
+    mask
+}
+
+async fn get_or_init_session(
+    app_handle: &tauri::AppHandle,
+    session_mutex: &Mutex<Option<Arc<Session>>>,
+    ai_init_lock: &TokioMutex<()>,
+    url: &str,
+    filename: &str,
+    expected_hash: &str,
+    name: &str,
+) -> Result<Arc<Session>> {
+    if let Some(session) = session_mutex.lock().unwrap().as_ref() {
+        return Ok(session.clone());
+    }
+
+    let _guard = ai_init_lock.lock().await;
+
+    if let Some(session) = session_mutex.lock().unwrap().as_ref() {
+        return Ok(session.clone());
+    }
+
+    let models_dir = get_models_dir(app_handle)?;
+    download_and_verify_model(app_handle, &models_dir, filename, url, expected_hash, name).await?;
+    let model_path = models_dir.join(filename);
+
+    let environment = Arc::new(Environment::builder().with_name(name).build()?);
+    let session = Arc::new(SessionBuilder::new(&environment)?.with_model_from_file(model_path)?);
+    *session_mutex.lock().unwrap() = Some(session.clone());
+
+    Ok(session)
+}
+
+// Only face masks, culling and people indexing need the face detector, so it is loaded on first use
+// instead of with the other mask models.
+pub async fn get_or_init_face_detector(
+    app_handle: &tauri::AppHandle,
+    face_detector_mutex: &Mutex<Option<Arc<Session>>>,
+    ai_init_lock: &TokioMutex<()>,
+) -> Result<Arc<Session>> {
+    get_or_init_session(app_handle, face_detector_mutex, ai_init_lock, FACE_DETECTOR_URL, FACE_DETECTOR_FILENAME, FACE_DETECTOR_SHA256, "Face Detection Model").await
+}
+
+pub async fn get_or_init_upscale_model(
+    app_handle: &tauri::AppHandle,
+    upscale_model_mutex: &Mutex<Option<Arc<Session>>>,
+    ai_init_lock: &TokioMutex<()>,
+) -> Result<Arc<Session>> {
+    get_or_init_session(app_handle, upscale_model_mutex, ai_init_lock, UPSCALE_MODEL_URL, UPSCALE_MODEL_FILENAME, UPSCALE_MODEL_SHA256, "Upscale Model").await
+}
+
+pub async fn get_or_init_denoise_model(
+    app_handle: &tauri::AppHandle,
+    denoise_model_mutex: &Mutex<Option<Arc<Session>>>,
+    ai_init_lock: &TokioMutex<()>,
+) -> Result<Arc<Session>> {
+    get_or_init_session(app_handle, denoise_model_mutex, ai_init_lock, DENOISE_MODEL_URL, DENOISE_MODEL_FILENAME, DENOISE_MODEL_SHA256, "Denoise Model").await
+}
+
+fn run_tiled_inference(image: &Rgb32FImage, session: &Session, model_scale: u32) -> Result<Rgb32FImage> {
+    let (width, height) = image.dimensions();
+    let mut result = Rgb32FImage::new(width * model_scale, height * model_scale);
+
+    for tile_y in (0..height).step_by(MODEL_TILE_SIZE as usize) {
+        for tile_x in (0..width).step_by(MODEL_TILE_SIZE as usize) {
+            let tile_w = MODEL_TILE_SIZE.min(width - tile_x);
+            let tile_h = MODEL_TILE_SIZE.min(height - tile_y);
+            let input_x = tile_x.saturating_sub(MODEL_TILE_PADDING);
+            let input_y = tile_y.saturating_sub(MODEL_TILE_PADDING);
+            let input_w = (tile_x + tile_w + MODEL_TILE_PADDING).min(width) - input_x;
+            let input_h = (tile_y + tile_h + MODEL_TILE_PADDING).min(height) - input_y;
+
+            let mut input_tensor: Array<f32, _> = Array::zeros((1, 3, input_h as usize, input_w as usize));
+            for y in 0..input_h {
+                for x in 0..input_w {
+                    let pixel = image.get_pixel(input_x + x, input_y + y);
+                    for c in 0..3 {
+                        input_tensor[[0, c, y as usize, x as usize]] = pixel[c].clamp(0.0, 1.0);
+                    }
+                }
+            }
+
+            let input_tensor_dyn = input_tensor.into_dyn();
+            let input_values = input_tensor_dyn.as_standard_layout();
+            let inputs = vec![Value::from_array(session.allocator(), &input_values)?];
+
+            let outputs = session.run(inputs)?;
+            let output_tensor = outputs[0].try_extract::<f32>()?.view().to_owned();
+            let output: Vec<f32> = output_tensor.iter().cloned().collect();
+
+            let output_w = (input_w * model_scale) as usize;
+            let plane = output_w * (input_h * model_scale) as usize;
+            if output.len() != 3 * plane {
+                return Err(anyhow::anyhow!("Unexpected output size from tiled model"));
+            }
+
+            let offset_x = (tile_x - input_x) * model_scale;
+            let offset_y = (tile_y - input_y) * model_scale;
+            for y in 0..tile_h * model_scale {
+                for x in 0..tile_w * model_scale {
+                    let index = (offset_y + y) as usize * output_w + (offset_x + x) as usize;
+                    let channel = |c: usize| output[c * plane + index].clamp(0.0, 1.0);
+                    result.put_pixel(
+                        tile_x * model_scale + x,
+                        tile_y * model_scale + y,
+                        image::Rgb([channel(0), channel(1), channel(2)]),
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn run_upscale_model(image: &DynamicImage, upscale_session: &Session, scale: u32) -> Result<DynamicImage> {
+    if scale != 2 && scale != UPSCALE_MODEL_SCALE {
+        return Err(anyhow::anyhow!("Unsupported upscale factor: {}x", scale));
+    }
+
+    let (width, height) = image.dimensions();
+    let source = image.to_rgb32f();
+    let mut upscaled = run_tiled_inference(&source, upscale_session, UPSCALE_MODEL_SCALE)?;
+    // The model only sees [0, 1]; whatever a wide-gamut render has outside that range is carried
+    // over with a plain resample.
+    if source.pixels().any(|p| p.0.iter().any(|v| !(0.0..=1.0).contains(v))) {
+        let residual = Rgb32FImage::from_fn(width, height, |x, y| {
+            image::Rgb(source.get_pixel(x, y).0.map(|v| v - v.clamp(0.0, 1.0)))
+        });
+        let residual = imageops::resize(&residual, upscaled.width(), upscaled.height(), FilterType::Triangle);
+        for (pixel, extra) in upscaled.pixels_mut().zip(residual.pixels()) {
+            for c in 0..3 {
+                pixel[c] += extra[c];
+            }
+        }
+    }
+    let mut upscaled_rgba = DynamicImage::ImageRgb32F(upscaled).to_rgba32f();
+    if image.color().has_alpha() {
+        let alpha = imageops::resize(&image.to_rgba32f(), upscaled_rgba.width(), upscaled_rgba.height(), FilterType::Triangle);
+        for (pixel, source_pixel) in upscaled_rgba.pixels_mut().zip(alpha.pixels()) {
+            pixel[3] = source_pixel[3];
+        }
+    }
+    let upscaled = with_depth_of(image, upscaled_rgba);
+    if scale == UPSCALE_MODEL_SCALE {
+        Ok(upscaled)
+    } else {
+        Ok(upscaled.resize_exact(width * scale, height * scale, FilterType::Lanczos3))
+    }
+}
+
+pub fn run_denoise_model(image: &DynamicImage, denoise_session: &Session) -> Result<DynamicImage> {
+    let denoised = run_tiled_inference(&image.to_rgb32f(), denoise_session, 1)?;
+    Ok(DynamicImage::ImageRgb32F(denoised))
+}
+
+pub fn blend_denoised(original: &DynamicImage, denoised: &DynamicImage, strength: f32) -> DynamicImage {
+    let strength = strength.clamp(0.0, 1.0);
+    let mut blended = original.to_rgb32f();
+    let denoised = denoised.to_rgb32f();
+    for (pixel, denoised_pixel) in blended.pixels_mut().zip(denoised.pixels()) {
+        for c in 0..3 {
+            let residual = denoised_pixel[c] - pixel[c].clamp(0.0, 1.0);
+            pixel[c] += residual * strength;
+        }
+    }
+    DynamicImage::ImageRgb32F(blended)
+}
+
+pub async fn get_or_init_inpaint_model(
+    app_handle: &tauri::AppHandle,
+    inpaint_model_mutex: &Mutex<Option<Arc<Session>>>,
+    ai_init_lock: &TokioMutex<()>,
+) -> Result<Arc<Session>> {
+    get_or_init_session(app_handle, inpaint_model_mutex, ai_init_lock, LAMA_MODEL_URL, LAMA_MODEL_FILENAME, LAMA_MODEL_SHA256, "Inpainting Model").await
+}
+
+fn mask_bounds(mask: &GrayImage) -> Option<(u32, u32, u32, u32)> {
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+    for (x, y, pixel) in mask.enumerate_pixels() {
+        if pixel[0] > 0 {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+    }
+    (min_x <= max_x).then_some((min_x, min_y, max_x, max_y))
+}
+
+pub fn run_lama_inpaint(image: &DynamicImage, mask: &GrayImage, inpaint_session: &Session) -> Result<image::RgbaImage> {
+    let mut result = image.to_rgba8();
+    let Some((min_x, min_y, max_x, max_y)) = mask_bounds(mask) else {
+        return Ok(result);
+    };
+
+    let (img_w, img_h) = image.dimensions();
+    let margin = (((max_x - min_x).max(max_y - min_y) as f32 * LAMA_CONTEXT_MARGIN) as u32).max(LAMA_INPUT_SIZE / 4);
+    let crop_x = min_x.saturating_sub(margin);
+    let crop_y = min_y.saturating_sub(margin);
+    let crop_w = (max_x + margin + 1).min(img_w) - crop_x;
+    let crop_h = (max_y + margin + 1).min(img_h) - crop_y;
+
+    let crop_image = image
+        .crop_imm(crop_x, crop_y, crop_w, crop_h)
+        .resize_exact(LAMA_INPUT_SIZE, LAMA_INPUT_SIZE, FilterType::Triangle)
+        .to_rgb8();
+    let crop_mask = imageops::resize(
+        &imageops::crop_imm(mask, crop_x, crop_y, crop_w, crop_h).to_image(),
+        LAMA_INPUT_SIZE,
+        LAMA_INPUT_SIZE,
+        FilterType::Nearest,
+    );
+
+    let size = LAMA_INPUT_SIZE as usize;
+    let mut image_tensor: Array<f32, _> = Array::zeros((1, 3, size, size));
+    let mut mask_tensor: Array<f32, _> = Array::zeros((1, 1, size, size));
+    for (x, y, pixel) in crop_image.enumerate_pixels() {
+        for c in 0..3 {
+            image_tensor[[0, c, y as usize, x as usize]] = pixel[c] as f32 / 255.0;
+        }
+        mask_tensor[[0, 0, y as usize, x as usize]] = if crop_mask.get_pixel(x, y)[0] > 0 { 1.0 } else { 0.0 };
+    }
+
+    let image_tensor_dyn = image_tensor.into_dyn();
+    let mask_tensor_dyn = mask_tensor.into_dyn();
+    let image_values = image_tensor_dyn.as_standard_layout();
+    let mask_values = mask_tensor_dyn.as_standard_layout();
+    let inputs = vec![
+        Value::from_array(inpaint_session.allocator(), &image_values)?,
+        Value::from_array(inpaint_session.allocator(), &mask_values)?,
+    ];
+
+    let outputs = inpaint_session.run(inputs)?;
+    let output_tensor = outputs[0].try_extract::<f32>()?.view().to_owned();
+    let output: Vec<f32> = output_tensor.iter().cloned().collect();
+    let plane = size * size;
+    if output.len() != 3 * plane {
+        return Err(anyhow::anyhow!("Unexpected output size from inpainting model"));
+    }
+
+    let inpainted = image::RgbImage::from_fn(LAMA_INPUT_SIZE, LAMA_INPUT_SIZE, |x, y| {
+        let index = y as usize * size + x as usize;
+        let channel = |c: usize| output[c * plane + index].clamp(0.0, 255.0).round() as u8;
+        image::Rgb([channel(0), channel(1), channel(2)])
+    });
+    let inpainted = imageops::resize(&inpainted, crop_w, crop_h, FilterType::Lanczos3);
+
+    for (x, y, pixel) in inpainted.enumerate_pixels() {
+        if mask.get_pixel(crop_x + x, crop_y + y)[0] > 0 {
+            result.put_pixel(crop_x + x, crop_y + y, image::Rgba([pixel[0], pixel[1], pixel[2], 255]));
+        }
+    }
+
+    Ok(result)
+}
+
+pub async fn get_or_init_eye_state_model(
+    app_handle: &tauri::AppHandle,
+    eye_state_model_mutex: &Mutex<Option<Arc<Session>>>,
+    ai_init_lock: &TokioMutex<()>,
+) -> Result<Arc<Session>> {
+    get_or_init_session(app_handle, eye_state_model_mutex, ai_init_lock, EYE_STATE_MODEL_URL, EYE_STATE_MODEL_FILENAME, EYE_STATE_MODEL_SHA256, "Eye State Model").await
+}
+
+fn eye_closed_probability(image: &DynamicImage, center: (f32, f32), size: f32, eye_state_session: &Session) -> Result<f32> {
+    let (width, height) = image.dimensions();
+    let half = (size * 0.5).max(1.0);
+    let x = (center.0 - half).max(0.0) as u32;
+    let y = (center.1 - half).max(0.0) as u32;
+    let w = ((size as u32).max(1)).min(width.saturating_sub(x)).max(1);
+    let h = ((size as u32).max(1)).min(height.saturating_sub(y)).max(1);
+
+    let eye = image
+        .crop_imm(x, y, w, h)
+        .resize_exact(EYE_STATE_INPUT_SIZE, EYE_STATE_INPUT_SIZE, FilterType::Triangle)
+        .to_rgb8();
+
+    let size = EYE_STATE_INPUT_SIZE as usize;
+    let mut input_tensor: Array<f32, _> = Array::zeros((1, 3, size, size));
+    for (x, y, pixel) in eye.enumerate_pixels() {
+        input_tensor[[0, 0, y as usize, x as usize]] = pixel[2] as f32;
+        input_tensor[[0, 1, y as usize, x as usize]] = pixel[1] as f32;
+        input_tensor[[0, 2, y as usize, x as usize]] = pixel[0] as f32;
+    }
+
+    let input_tensor_dyn = input_tensor.into_dyn();
+    let input_values = input_tensor_dyn.as_standard_layout();
+    let inputs = vec![Value::from_array(eye_state_session.allocator(), &input_values)?];
+
+    let outputs = eye_state_session.run(inputs)?;
+    let probabilities: Vec<f32> = outputs[0].try_extract::<f32>()?.view().iter().cloned().collect();
+    probabilities
+        .get(EYE_STATE_CLOSED_INDEX)
+        .copied()
+        .ok_or_else(|| anyhow::anyhow!("Unexpected output from eye state model"))
+}
+
+pub fn are_eyes_closed(image: &DynamicImage, face: &FaceDetection, eye_state_session: &Session) -> Result<bool> {
+    let (width, height) = image.dimensions();
+    let face_w = (face.x2 - face.x1) * width as f32;
+    let face_h = (face.y2 - face.y1) * height as f32;
+    let eye_y = face.y1 * height as f32 + face_h * 0.4;
+    let eye_size = face_w * 0.28;
+
+    let left = eye_closed_probability(image, (face.x1 * width as f32 + face_w * 0.3, eye_y), eye_size, eye_state_session)?;
+    let right = eye_closed_probability(image, (face.x1 * width as f32 + face_w * 0.7, eye_y), eye_size, eye_state_session)?;
+
+    Ok(left > EYE_CLOSED_THRESHOLD && right > EYE_CLOSED_THRESHOLD)
+}
+
+pub async fn get_or_init_face_embedding_model(
+    app_handle: &tauri::AppHandle,
+    face_embedding_model_mutex: &Mutex<Option<Arc<Session>>>,
+    ai_init_lock: &TokioMutex<()>,
+) -> Result<Arc<Session>> {
+    get_or_init_session(app_handle, face_embedding_model_mutex, ai_init_lock, FACE_EMBEDDING_MODEL_URL, FACE_EMBEDDING_MODEL_FILENAME, FACE_EMBEDDING_MODEL_SHA256, "Face Recognition Model").await
+}
+
+pub fn compute_face_embedding(image: &DynamicImage, face: &FaceDetection, face_embedding_session: &Session) -> Result<Vec<f32>> {
+    let (width, height) = image.dimensions();
+    let center_x = (face.x1 + face.x2) * 0.5 * width as f32;
+    let center_y = (face.y1 + face.y2) * 0.5 * height as f32;
+    let side = ((face.x2 - face.x1) * width as f32).max((face.y2 - face.y1) * height as f32) * FACE_EMBEDDING_CROP_SCALE;
+
+    let x = (center_x - side * 0.5).max(0.0) as u32;
+    let y = (center_y - side * 0.5).max(0.0) as u32;
+    let w = (side as u32).min(width.saturating_sub(x)).max(1);
+    let h = (side as u32).min(height.saturating_sub(y)).max(1);
+
+    let crop = image
+        .crop_imm(x, y, w, h)
+        .resize_exact(FACE_EMBEDDING_INPUT_SIZE, FACE_EMBEDDING_INPUT_SIZE, FilterType::Triangle)
+        .to_rgb8();
+
+    let size = FACE_EMBEDDING_INPUT_SIZE as usize;
+    let mut input_tensor: Array<f32, _> = Array::zeros((1, 3, size, size));
+    for (x, y, pixel) in crop.enumerate_pixels() {
+        for c in 0..3 {
+            input_tensor[[0, c, y as usize, x as usize]] = (pixel[c] as f32 - 127.5) / 127.5;
+        }
+    }
+
+    let input_tensor_dyn = input_tensor.into_dyn();
+    let input_values = input_tensor_dyn.as_standard_layout();
+    let inputs = vec![Value::from_array(face_embedding_session.allocator(), &input_values)?];
+
+    let outputs = face_embedding_session.run(inputs)?;
+    let embedding: Vec<f32> = outputs[0].try_extract::<f32>()?.view().iter().cloned().collect();
+    let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm <= f32::EPSILON {
+        return Err(anyhow::anyhow!("Face recognition model returned an empty embedding"));
+    }
+    Ok(embedding.into_iter().map(|v| v / norm).collect())
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct AiSubjectMaskParameters {
@@ -455,6 +1158,25 @@ pub struct AiSubjectMaskParameters {
     pub flip_vertical: Option<bool>,
     #[serde(default)]
     pub orientation_steps: Option<u8>,
+    #[serde(default)]
+    pub points: Option<Vec<SamPoint>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<AiMaskCandidate>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SamPoint {
+    pub x: f64,
+    pub y: f64,
+    pub positive: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AiMaskCandidate {
+    pub mask_data_base64: String,
+    pub score: f32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -485,4 +1207,21 @@ pub struct AiForegroundMaskParameters {
     pub flip_vertical: Option<bool>,
     #[serde(default)]
     pub orientation_steps: Option<u8>,
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AiFaceMaskParameters {
+    #[serde(default)]
+    pub mask_data_base64: Option<String>,
+    #[serde(default)]
+    pub face_box: Option<[f32; 4]>,
+    #[serde(default)]
+    pub rotation: Option<f32>,
+    #[serde(default)]
+    pub flip_horizontal: Option<bool>,
+    #[serde(default)]
+    pub flip_vertical: Option<bool>,
+    #[serde(default)]
+    pub orientation_steps: Option<u8>,
+}
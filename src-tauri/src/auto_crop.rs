@@ -0,0 +1,160 @@
+use image::GrayImage;
+use serde::Serialize;
+
+use crate::auto_straighten::inscribed_crop;
+use crate::image_processing::Crop;
+
+pub const ANALYSIS_DIM: u32 = 1024;
+const SCALE_STEPS: [f64; 6] = [1.0, 0.92, 0.85, 0.78, 0.7, 0.62];
+const POSITION_STEPS: usize = 24;
+const MIN_SUBJECT_MASS: f64 = 0.002;
+const COVERAGE_WEIGHT: f64 = 2.0;
+const COMPOSITION_WEIGHT: f64 = 1.0;
+const EDGE_CUT_WEIGHT: f64 = 3.0;
+const AREA_WEIGHT: f64 = 0.3;
+
+pub const DEFAULT_CROP_RATIOS: [(&str, f64); 5] =
+    [("1:1", 1.0), ("4:5", 4.0 / 5.0), ("9:16", 9.0 / 16.0), ("16:9", 16.0 / 9.0), ("3:2", 3.0 / 2.0)];
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CropSuggestion {
+    pub label: String,
+    pub aspect_ratio: f64,
+    pub crop: Crop,
+    pub score: f64,
+}
+
+// Summed-area tables for the mask mass and its first moments, so every candidate is scored in O(1).
+struct MaskIntegrals {
+    width: usize,
+    mass: Vec<f64>,
+    mass_x: Vec<f64>,
+    mass_y: Vec<f64>,
+}
+
+impl MaskIntegrals {
+    fn new(mask: &GrayImage) -> Self {
+        let (w, h) = (mask.width() as usize, mask.height() as usize);
+        let stride = w + 1;
+        let mut mass = vec![0.0; stride * (h + 1)];
+        let mut mass_x = vec![0.0; stride * (h + 1)];
+        let mut mass_y = vec![0.0; stride * (h + 1)];
+        for y in 0..h {
+            for x in 0..w {
+                let m = mask.get_pixel(x as u32, y as u32)[0] as f64 / 255.0;
+                let i = (y + 1) * stride + x + 1;
+                mass[i] = m + mass[i - 1] + mass[i - stride] - mass[i - stride - 1];
+                mass_x[i] = m * x as f64 + mass_x[i - 1] + mass_x[i - stride] - mass_x[i - stride - 1];
+                mass_y[i] = m * y as f64 + mass_y[i - 1] + mass_y[i - stride] - mass_y[i - stride - 1];
+            }
+        }
+        MaskIntegrals { width: w, mass, mass_x, mass_y }
+    }
+
+    fn sum(table: &[f64], stride: usize, x0: usize, y0: usize, x1: usize, y1: usize) -> f64 {
+        table[y1 * stride + x1] - table[y0 * stride + x1] - table[y1 * stride + x0] + table[y0 * stride + x0]
+    }
+
+    fn region(&self, x0: usize, y0: usize, x1: usize, y1: usize) -> (f64, f64, f64) {
+        let stride = self.width + 1;
+        (
+            Self::sum(&self.mass, stride, x0, y0, x1, y1),
+            Self::sum(&self.mass_x, stride, x0, y0, x1, y1),
+            Self::sum(&self.mass_y, stride, x0, y0, x1, y1),
+        )
+    }
+
+    fn total(&self) -> f64 {
+        *self.mass.last().unwrap_or(&0.0)
+    }
+}
+
+fn composition_score(cx: f64, cy: f64, width: f64, height: f64) -> f64 {
+    let thirds = [1.0 / 3.0, 2.0 / 3.0];
+    let nearest = thirds
+        .iter()
+        .flat_map(|tx| thirds.iter().map(move |ty| ((cx / width - tx).powi(2) + (cy / height - ty).powi(2)).sqrt()))
+        .fold(f64::MAX, f64::min);
+    let centered = ((cx / width - 0.5).powi(2) + (cy / height - 0.5).powi(2)).sqrt();
+    // Centered subjects are acceptable, thirds intersections are preferred.
+    (-(nearest / 0.12).powi(2)).exp().max(0.8 * (-(centered / 0.1).powi(2)).exp())
+}
+
+fn best_crop_for_ratio(integrals: &MaskIntegrals, bounds: &Crop, aspect_ratio: f64) -> Option<(Crop, f64)> {
+    let total = integrals.total();
+    let max_height = bounds.height.min(bounds.width / aspect_ratio);
+    let mut best: Option<(Crop, f64)> = None;
+
+    for scale in SCALE_STEPS {
+        let height = (max_height * scale).floor();
+        let width = (height * aspect_ratio).floor();
+        if width < 8.0 || height < 8.0 {
+            continue;
+        }
+        let free_x = bounds.width - width;
+        let free_y = bounds.height - height;
+
+        for iy in 0..=POSITION_STEPS {
+            for ix in 0..=POSITION_STEPS {
+                let x = (bounds.x + free_x * ix as f64 / POSITION_STEPS as f64).floor();
+                let y = (bounds.y + free_y * iy as f64 / POSITION_STEPS as f64).floor();
+                let (x0, y0) = (x as usize, y as usize);
+                let (x1, y1) = (x0 + width as usize, y0 + height as usize);
+
+                let (mass, mass_x, mass_y) = integrals.region(x0, y0, x1, y1);
+                if mass <= 0.0 {
+                    continue;
+                }
+                let coverage = mass / total;
+                let composition = composition_score(mass_x / mass - x, mass_y / mass - y, width, height);
+                let edge_mass = [
+                    integrals.region(x0, y0, x0 + 1, y1).0,
+                    integrals.region(x1 - 1, y0, x1, y1).0,
+                    integrals.region(x0, y0, x1, y0 + 1).0,
+                    integrals.region(x0, y1 - 1, x1, y1).0,
+                ];
+                let edge_cut = edge_mass[0] / height + edge_mass[1] / height + edge_mass[2] / width + edge_mass[3] / width;
+                let area = (width * height) / (bounds.width * bounds.height);
+
+                let score = COVERAGE_WEIGHT * coverage + COMPOSITION_WEIGHT * composition
+                    - EDGE_CUT_WEIGHT * edge_cut
+                    + AREA_WEIGHT * area;
+                if best.as_ref().map_or(true, |(_, best_score)| score > *best_score) {
+                    best = Some((Crop { x, y, width, height }, score));
+                }
+            }
+        }
+    }
+    best
+}
+
+pub fn suggest_crops(mask: &GrayImage, rotation_degrees: f32, ratios: &[(String, f64)]) -> Vec<CropSuggestion> {
+    let (width, height) = mask.dimensions();
+    let integrals = MaskIntegrals::new(mask);
+    let bounds = inscribed_crop(width, height, rotation_degrees, width as f64 / height as f64);
+    let (x, y) = (bounds.x.ceil(), bounds.y.ceil());
+    let bounds = Crop { x, y, width: (width as f64 - 2.0 * x).floor(), height: (height as f64 - 2.0 * y).floor() };
+
+    let has_subject = integrals.total() >= MIN_SUBJECT_MASS * (width as f64 * height as f64);
+
+    ratios
+        .iter()
+        .filter_map(|(label, aspect_ratio)| {
+            let (crop, score) = if has_subject {
+                best_crop_for_ratio(&integrals, &bounds, *aspect_ratio)?
+            } else {
+                let crop_height = bounds.height.min(bounds.width / aspect_ratio);
+                let crop_width = crop_height * aspect_ratio;
+                let crop = Crop {
+                    x: bounds.x + (bounds.width - crop_width) / 2.0,
+                    y: bounds.y + (bounds.height - crop_height) / 2.0,
+                    width: crop_width,
+                    height: crop_height,
+                };
+                (crop, 0.0)
+            };
+            Some(CropSuggestion { label: label.clone(), aspect_ratio: *aspect_ratio, crop, score })
+        })
+        .collect()
+}
@@ -0,0 +1,140 @@
+use image::{DynamicImage, GrayImage};
+use serde::Serialize;
+
+use crate::image_processing::Crop;
+
+const ANALYSIS_DIM: u32 = 800;
+const MAX_CORRECTION_DEGREES: f32 = 15.0;
+const ANGLE_STEP_DEGREES: f32 = 0.1;
+const EDGE_PERCENTILE: f32 = 0.92;
+const VERTICAL_LINE_WEIGHT: f32 = 0.5;
+const MIN_CONFIDENCE: f32 = 0.15;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoStraightenResult {
+    pub rotation: f32,
+    pub confidence: f32,
+    pub crop: Crop,
+}
+
+struct EdgePoint {
+    x: f32,
+    y: f32,
+    weight: f32,
+    horizontal: bool,
+}
+
+fn collect_edge_points(gray: &GrayImage) -> Vec<EdgePoint> {
+    let (width, height) = gray.dimensions();
+    if width < 3 || height < 3 {
+        return Vec::new();
+    }
+
+    let at = |x: u32, y: u32| gray.get_pixel(x, y)[0] as f32;
+    let mut gradients = Vec::with_capacity(((width - 2) * (height - 2)) as usize);
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let gx = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                - at(x - 1, y - 1) - 2.0 * at(x - 1, y) - at(x - 1, y + 1);
+            let gy = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                - at(x - 1, y - 1) - 2.0 * at(x, y - 1) - at(x + 1, y - 1);
+            gradients.push((x, y, gx, gy, (gx * gx + gy * gy).sqrt()));
+        }
+    }
+
+    let mut magnitudes: Vec<f32> = gradients.iter().map(|g| g.4).collect();
+    let index = ((magnitudes.len() as f32 * EDGE_PERCENTILE) as usize).min(magnitudes.len() - 1);
+    let threshold = *magnitudes
+        .select_nth_unstable_by(index, |a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .1;
+    if threshold <= 0.0 {
+        return Vec::new();
+    }
+
+    let tolerance = (MAX_CORRECTION_DEGREES + 5.0).to_radians().tan();
+    gradients
+        .into_iter()
+        .filter(|g| g.4 > threshold)
+        .filter_map(|(x, y, gx, gy, magnitude)| {
+            // A horizontal line has a mostly vertical gradient and vice versa.
+            let horizontal = if gx.abs() <= gy.abs() * tolerance {
+                true
+            } else if gy.abs() <= gx.abs() * tolerance {
+                false
+            } else {
+                return None;
+            };
+            Some(EdgePoint { x: x as f32, y: y as f32, weight: magnitude, horizontal })
+        })
+        .collect()
+}
+
+// Restricted Hough transform: for each candidate angle, lines that are straight at that angle
+// pile their votes into few accumulator bins, which the sum of squares rewards.
+fn angle_scores(points: &[EdgePoint], width: u32, height: u32) -> Vec<(f32, f32)> {
+    let steps = (2.0 * MAX_CORRECTION_DEGREES / ANGLE_STEP_DEGREES).round() as usize + 1;
+    let diagonal = ((width * width + height * height) as f32).sqrt();
+    let bins = (2.0 * diagonal).ceil() as usize + 1;
+
+    (0..steps)
+        .map(|step| {
+            let angle = -MAX_CORRECTION_DEGREES + step as f32 * ANGLE_STEP_DEGREES;
+            let (sin, cos) = angle.to_radians().sin_cos();
+            let mut horizontal = vec![0.0f32; bins];
+            let mut vertical = vec![0.0f32; bins];
+            for point in points {
+                if point.horizontal {
+                    let rho = point.y * cos - point.x * sin + diagonal;
+                    horizontal[(rho.max(0.0) as usize).min(bins - 1)] += point.weight;
+                } else {
+                    let rho = point.x * cos + point.y * sin + diagonal;
+                    vertical[(rho.max(0.0) as usize).min(bins - 1)] += point.weight;
+                }
+            }
+            let energy = |acc: &[f32]| acc.iter().map(|v| v * v).sum::<f32>();
+            (angle, energy(&horizontal) + VERTICAL_LINE_WEIGHT * energy(&vertical))
+        })
+        .collect()
+}
+
+pub fn detect_straighten_angle(image: &DynamicImage) -> Option<(f32, f32)> {
+    let gray = image.thumbnail(ANALYSIS_DIM, ANALYSIS_DIM).to_luma8();
+    let (width, height) = gray.dimensions();
+    let points = collect_edge_points(&gray);
+    if points.is_empty() {
+        return None;
+    }
+
+    let scores = angle_scores(&points, width, height);
+    let (best_angle, best_score) = scores
+        .iter()
+        .copied()
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))?;
+    let mean_score = scores.iter().map(|s| s.1).sum::<f32>() / scores.len() as f32;
+    if best_score <= 0.0 {
+        return None;
+    }
+
+    let confidence = (1.0 - mean_score / best_score).clamp(0.0, 1.0);
+    if confidence < MIN_CONFIDENCE {
+        return None;
+    }
+
+    // The detected line slopes by `best_angle` in image space; rotating by the opposite angle levels it.
+    Some((-best_angle, confidence))
+}
+
+pub fn inscribed_crop(width: u32, height: u32, rotation_degrees: f32, aspect_ratio: f64) -> Crop {
+    let (width, height) = (width as f64, height as f64);
+    let (sin, cos) = (rotation_degrees as f64).to_radians().abs().sin_cos();
+    let crop_height = (width / (aspect_ratio * cos + sin)).min(height / (aspect_ratio * sin + cos));
+    let crop_width = crop_height * aspect_ratio;
+
+    Crop {
+        x: ((width - crop_width) / 2.0).max(0.0),
+        y: ((height - crop_height) / 2.0).max(0.0),
+        width: crop_width.min(width),
+        height: crop_height.min(height),
+    }
+}
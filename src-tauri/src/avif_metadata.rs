@@ -0,0 +1,340 @@
+use crate::iptc_metadata::{read_metadata, xmp_packet};
+use crate::tiff_metadata::exif_payload;
+
+const XMP_CONTENT_TYPE: &str = "application/rdf+xml";
+const CONSTRUCTION_FILE_OFFSET: u8 = 0;
+const CONSTRUCTION_ITEM_OFFSET: u8 = 2;
+
+struct BoxRange {
+    kind: [u8; 4],
+    start: usize,
+    content_start: usize,
+    end: usize,
+}
+
+struct IlocItem {
+    id: u32,
+    construction_method: u8,
+    data_reference_index: u16,
+    extents: Vec<(u64, u64)>,
+}
+
+struct ItemReference {
+    kind: [u8; 4],
+    from: u32,
+    to: Vec<u32>,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let slice = self.bytes.get(self.pos..self.pos + n).ok_or("Exported AVIF is truncated.")?;
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn uint(&mut self, n: usize) -> Result<u64, String> {
+        Ok(self.take(n)?.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.uint(1)? as u8)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(self.uint(2)? as u16)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(self.uint(4)? as u32)
+    }
+}
+
+fn parse_boxes(bytes: &[u8], start: usize, end: usize) -> Result<Vec<BoxRange>, String> {
+    let mut boxes = Vec::new();
+    let mut pos = start;
+    while pos + 8 <= end {
+        let mut reader = Reader { bytes, pos };
+        let size = reader.u32()? as u64;
+        let kind: [u8; 4] = reader.take(4)?.try_into().unwrap();
+        let size = match size {
+            0 => (end - pos) as u64,
+            1 => reader.uint(8)?,
+            s => s,
+        };
+        let box_end = usize::try_from(size)
+            .ok()
+            .and_then(|size| pos.checked_add(size))
+            .filter(|&box_end| box_end <= end && box_end >= reader.pos)
+            .ok_or("Exported AVIF has a malformed box.")?;
+        boxes.push(BoxRange { kind, start: pos, content_start: reader.pos, end: box_end });
+        pos = box_end;
+    }
+    Ok(boxes)
+}
+
+fn make_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 8);
+    out.extend_from_slice(&(body.len() as u32 + 8).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(body);
+    out
+}
+
+fn full_box(kind: &[u8; 4], version: u8, body: &[u8]) -> Vec<u8> {
+    let mut content = vec![version, 0, 0, 0];
+    content.extend_from_slice(body);
+    make_box(kind, &content)
+}
+
+fn push_uint(out: &mut Vec<u8>, value: u64, n: usize) {
+    out.extend_from_slice(&value.to_be_bytes()[8 - n..]);
+}
+
+fn parse_iloc(bytes: &[u8]) -> Result<Vec<IlocItem>, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let version = reader.u8()?;
+    reader.take(3)?;
+    let sizes = reader.u8()?;
+    let (offset_size, length_size) = ((sizes >> 4) as usize, (sizes & 0xf) as usize);
+    let sizes = reader.u8()?;
+    let base_offset_size = (sizes >> 4) as usize;
+    let index_size = if version > 0 { (sizes & 0xf) as usize } else { 0 };
+    let count = if version < 2 { reader.u16()? as u32 } else { reader.u32()? };
+
+    let mut items = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let id = if version < 2 { reader.u16()? as u32 } else { reader.u32()? };
+        let construction_method = if version > 0 { (reader.u16()? & 0xf) as u8 } else { CONSTRUCTION_FILE_OFFSET };
+        if construction_method == CONSTRUCTION_ITEM_OFFSET || index_size > 0 {
+            return Err("Exported AVIF uses item offsets, which are not supported.".to_string());
+        }
+        let data_reference_index = reader.u16()?;
+        let base_offset = reader.uint(base_offset_size)?;
+        let extent_count = reader.u16()?;
+        let mut extents = Vec::with_capacity(extent_count as usize);
+        for _ in 0..extent_count {
+            let offset = reader.uint(offset_size)?;
+            let length = reader.uint(length_size)?;
+            extents.push((base_offset + offset, length));
+        }
+        items.push(IlocItem { id, construction_method, data_reference_index, extents });
+    }
+    Ok(items)
+}
+
+// Offsets are written absolute with no base offset, so every item keeps a single layout.
+fn write_iloc(items: &[IlocItem], wide_ids: bool, field_size: usize) -> Vec<u8> {
+    let mut body = vec![((field_size << 4) | field_size) as u8, 0];
+    if wide_ids {
+        body.extend_from_slice(&(items.len() as u32).to_be_bytes());
+    } else {
+        body.extend_from_slice(&(items.len() as u16).to_be_bytes());
+    }
+    for item in items {
+        if wide_ids {
+            body.extend_from_slice(&item.id.to_be_bytes());
+        } else {
+            body.extend_from_slice(&(item.id as u16).to_be_bytes());
+        }
+        body.extend_from_slice(&(item.construction_method as u16).to_be_bytes());
+        body.extend_from_slice(&item.data_reference_index.to_be_bytes());
+        body.extend_from_slice(&(item.extents.len() as u16).to_be_bytes());
+        for &(offset, length) in &item.extents {
+            push_uint(&mut body, offset, field_size);
+            push_uint(&mut body, length, field_size);
+        }
+    }
+    full_box(b"iloc", if wide_ids { 2 } else { 1 }, &body)
+}
+
+fn parse_iref(bytes: &[u8]) -> Result<Vec<ItemReference>, String> {
+    let version = *bytes.first().ok_or("Exported AVIF is truncated.")?;
+    let id_size = if version == 0 { 2 } else { 4 };
+    parse_boxes(bytes, 4, bytes.len())?
+        .iter()
+        .map(|b| {
+            let mut reader = Reader { bytes: &bytes[..b.end], pos: b.content_start };
+            let from = reader.uint(id_size)? as u32;
+            let count = reader.u16()?;
+            let to = (0..count).map(|_| Ok(reader.uint(id_size)? as u32)).collect::<Result<_, String>>()?;
+            Ok(ItemReference { kind: b.kind, from, to })
+        })
+        .collect()
+}
+
+fn write_iref(references: &[ItemReference], wide_ids: bool) -> Vec<u8> {
+    let id_size = if wide_ids { 4 } else { 2 };
+    let mut body = Vec::new();
+    for reference in references {
+        let mut content = Vec::new();
+        push_uint(&mut content, reference.from as u64, id_size);
+        content.extend_from_slice(&(reference.to.len() as u16).to_be_bytes());
+        reference.to.iter().for_each(|&id| push_uint(&mut content, id as u64, id_size));
+        body.extend(make_box(&reference.kind, &content));
+    }
+    full_box(b"iref", if wide_ids { 1 } else { 0 }, &body)
+}
+
+fn item_info_entry(id: u32, item_type: &[u8; 4], content_type: Option<&str>) -> Vec<u8> {
+    let wide = id > u16::MAX as u32;
+    let mut body = Vec::new();
+    if wide {
+        body.extend_from_slice(&id.to_be_bytes());
+    } else {
+        body.extend_from_slice(&(id as u16).to_be_bytes());
+    }
+    body.extend_from_slice(&0u16.to_be_bytes());
+    body.extend_from_slice(item_type);
+    body.push(0);
+    if let Some(content_type) = content_type {
+        body.extend_from_slice(content_type.as_bytes());
+        body.push(0);
+    }
+    full_box(b"infe", if wide { 3 } else { 2 }, &body)
+}
+
+fn primary_image_size(bytes: &[u8], meta_children: &[BoxRange]) -> Option<(u32, u32)> {
+    let iprp = meta_children.iter().find(|b| &b.kind == b"iprp")?;
+    let ipco = parse_boxes(bytes, iprp.content_start, iprp.end).ok()?.into_iter().find(|b| &b.kind == b"ipco")?;
+    let ispe = parse_boxes(bytes, ipco.content_start, ipco.end).ok()?.into_iter().find(|b| &b.kind == b"ispe")?;
+    let mut reader = Reader { bytes: &bytes[..ispe.end], pos: ispe.content_start + 4 };
+    Some((reader.u32().ok()?, reader.u32().ok()?))
+}
+
+// Exif and XMP go in as metadata items that describe the primary image (`cdsc`), with their
+// payloads in a trailing `mdat`, the layout AVIF readers expect.
+pub fn write_avif_metadata(image_bytes: &mut Vec<u8>, original_path_str: &str, strip_gps: bool) -> Result<(), String> {
+    let top_level = parse_boxes(image_bytes, 0, image_bytes.len())?;
+    let meta = top_level.iter().find(|b| &b.kind == b"meta").ok_or("Exported AVIF has no meta box.")?;
+    let meta_children = parse_boxes(image_bytes, meta.content_start + 4, meta.end)?;
+    let child = |kind: &[u8; 4]| meta_children.iter().find(|b| &b.kind == kind);
+
+    let (width, height) = primary_image_size(image_bytes, &meta_children).unwrap_or((0, 0));
+    let exif = exif_payload(original_path_str, width, height, strip_gps)?.map(|tiff| {
+        let mut data = 0u32.to_be_bytes().to_vec();
+        data.extend(tiff);
+        data
+    });
+    let xmp = xmp_packet(&read_metadata(original_path_str)).map(String::into_bytes);
+    if exif.is_none() && xmp.is_none() {
+        return Ok(());
+    }
+
+    let pitm = child(b"pitm").ok_or("Exported AVIF has no primary item.")?;
+    let mut reader = Reader { bytes: &image_bytes[..pitm.end], pos: pitm.content_start };
+    let pitm_version = reader.u8()?;
+    reader.take(3)?;
+    let primary_id = if pitm_version == 0 { reader.u16()? as u32 } else { reader.u32()? };
+
+    let iloc = child(b"iloc").ok_or("Exported AVIF has no item locations.")?;
+    let mut items = parse_iloc(&image_bytes[iloc.content_start..iloc.end])?;
+    let iinf = child(b"iinf").ok_or("Exported AVIF has no item info.")?;
+    let mut references = match child(b"iref") {
+        Some(iref) => parse_iref(&image_bytes[iref.content_start..iref.end])?,
+        None => Vec::new(),
+    };
+
+    let mut reader = Reader { bytes: &image_bytes[..iinf.end], pos: iinf.content_start };
+    let iinf_version = reader.u8()?;
+    reader.take(3)?;
+    let entry_count = if iinf_version == 0 { reader.u16()? as u32 } else { reader.u32()? };
+    let existing_entries = &image_bytes[reader.pos..iinf.end];
+
+    let mut next_id = items.iter().map(|item| item.id).max().unwrap_or(0).max(primary_id);
+    let mut new_items: Vec<(u32, Vec<u8>, Vec<u8>)> = Vec::new();
+    for (payload, item_type, content_type) in [(exif, b"Exif", None), (xmp, b"mime", Some(XMP_CONTENT_TYPE))] {
+        if let Some(payload) = payload {
+            next_id += 1;
+            new_items.push((next_id, item_info_entry(next_id, item_type, content_type), payload));
+            references.push(ItemReference { kind: *b"cdsc", from: next_id, to: vec![primary_id] });
+        }
+    }
+    let wide_ids = next_id > u16::MAX as u32;
+
+    let total_entries = entry_count + new_items.len() as u32;
+    let mut iinf_body = Vec::new();
+    if total_entries > u16::MAX as u32 {
+        iinf_body.extend_from_slice(&total_entries.to_be_bytes());
+    } else {
+        iinf_body.extend_from_slice(&(total_entries as u16).to_be_bytes());
+    }
+    iinf_body.extend_from_slice(existing_entries);
+    new_items.iter().for_each(|(_, entry, _)| iinf_body.extend_from_slice(entry));
+    let new_iinf = full_box(b"iinf", if total_entries > u16::MAX as u32 { 1 } else { 0 }, &iinf_body);
+    let new_iref = write_iref(&references, wide_ids);
+
+    let payload_len: usize = new_items.iter().map(|(_, _, payload)| payload.len()).sum();
+    let field_size = if image_bytes.len() as u64 + payload_len as u64 + (1 << 20) > u32::MAX as u64 { 8 } else { 4 };
+
+    // The last box may run to the end of the file, which would swallow the appended `mdat`.
+    if let Some(last) = top_level.last() {
+        if image_bytes[last.start..last.start + 4] == [0, 0, 0, 0] {
+            let size = u32::try_from(last.end - last.start).map_err(|_| "Exported AVIF is too large.".to_string())?;
+            image_bytes[last.start..last.start + 4].copy_from_slice(&size.to_be_bytes());
+        }
+    }
+
+    // The iloc has fixed-width fields, so a pass with unshifted offsets gives the final meta size.
+    let build_meta = |items: &[IlocItem]| {
+        let mut body = image_bytes[meta.content_start..meta.content_start + 4].to_vec();
+        for b in &meta_children {
+            match &b.kind {
+                b"iloc" => body.extend(write_iloc(items, wide_ids, field_size)),
+                b"iinf" => {
+                    body.extend_from_slice(&new_iinf);
+                    if child(b"iref").is_none() {
+                        body.extend_from_slice(&new_iref);
+                    }
+                }
+                b"iref" => body.extend_from_slice(&new_iref),
+                _ => body.extend_from_slice(&image_bytes[b.start..b.end]),
+            }
+        }
+        make_box(b"meta", &body)
+    };
+
+    let placeholder: Vec<IlocItem> = items
+        .iter()
+        .map(|item| IlocItem { extents: item.extents.clone(), ..*item })
+        .chain(new_items.iter().map(|(id, _, payload)| IlocItem {
+            id: *id,
+            construction_method: CONSTRUCTION_FILE_OFFSET,
+            data_reference_index: 0,
+            extents: vec![(0, payload.len() as u64)],
+        }))
+        .collect();
+    let shift = (build_meta(&placeholder).len() - (meta.end - meta.start)) as u64;
+
+    for item in items.iter_mut().filter(|item| item.construction_method == CONSTRUCTION_FILE_OFFSET) {
+        for extent in item.extents.iter_mut().filter(|(offset, _)| *offset >= meta.end as u64) {
+            extent.0 += shift;
+        }
+    }
+    let mut payload_offset = image_bytes.len() as u64 + shift + 8;
+    for (id, _, payload) in &new_items {
+        items.push(IlocItem {
+            id: *id,
+            construction_method: CONSTRUCTION_FILE_OFFSET,
+            data_reference_index: 0,
+            extents: vec![(payload_offset, payload.len() as u64)],
+        });
+        payload_offset += payload.len() as u64;
+    }
+    let new_meta = build_meta(&items);
+
+    let mut mdat_body = Vec::with_capacity(payload_len);
+    new_items.iter().for_each(|(_, _, payload)| mdat_body.extend_from_slice(payload));
+
+    let mut output = Vec::with_capacity(image_bytes.len() + new_meta.len() + payload_len + 8);
+    output.extend_from_slice(&image_bytes[..meta.start]);
+    output.extend(new_meta);
+    output.extend_from_slice(&image_bytes[meta.end..]);
+    output.extend(make_box(b"mdat", &mdat_body));
+    *image_bytes = output;
+    Ok(())
+}
@@ -0,0 +1,401 @@
+use std::fs;
+use std::io::ErrorKind;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+use crate::file_management::{import_file, ImportSettings};
+use crate::formats::is_supported_library_file;
+
+// Cameras (PTP) and phones (MTP) are reached through libgphoto2's command line client, which
+// handles both protocols on Linux and macOS without the device being mounted.
+const GPHOTO2: &str = "gphoto2";
+const STAGING_DIR_NAME: &str = ".rapidraw-camera-import";
+
+// Windows has no gphoto2. Cameras and phones show up under This PC as Windows Portable Devices and
+// are reached through the shell's COM automation from PowerShell; the device's shell path is the port.
+// Values go in through environment variables so names never need quoting.
+const POWERSHELL: &str = "powershell";
+const WPD_COPY_TIMEOUT: Duration = Duration::from_secs(300);
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+const WPD_PRELUDE: &str = r#"$ErrorActionPreference = 'Stop'
+$shell = New-Object -ComObject Shell.Application
+function Get-DeviceFolder {
+  $device = $shell.NameSpace(17).Items() | Where-Object { $_.Path -eq $env:RAPIDRAW_DEVICE } | Select-Object -First 1
+  if (-not $device) { throw 'The device is no longer connected.' }
+  $device.GetFolder
+}
+function Get-FileName($item) {
+  $name = $item.ExtendedProperty('System.FileName')
+  if ($name) { $name } else { $item.Name }
+}
+function Find-DeviceFile($folderPath, $name) {
+  $folder = Get-DeviceFolder
+  foreach ($part in $folderPath.Split('/', [StringSplitOptions]::RemoveEmptyEntries)) {
+    $child = $folder.Items() | Where-Object { $_.IsFolder -and $_.Name -eq $part } | Select-Object -First 1
+    if (-not $child) { throw "Folder $folderPath was not found on the device." }
+    $folder = $child.GetFolder
+  }
+  $item = $folder.Items() | Where-Object { -not $_.IsFolder -and (Get-FileName $_) -eq $name } | Select-Object -First 1
+  if (-not $item) { throw "$name was not found on the device." }
+  $item
+}
+"#;
+
+// Portable devices are the non-filesystem folders under This PC.
+const WPD_LIST_DEVICES: &str = r#"$shell.NameSpace(17).Items() | Where-Object { $_.IsFolder -and -not $_.IsFileSystem } |
+  ForEach-Object { "$($_.Name)`t$($_.Path)" }
+"#;
+
+const WPD_LIST_FILES: &str = r#"function Write-Files($folder, $path) {
+  foreach ($item in $folder.Items()) {
+    if ($item.IsFolder) { Write-Files $item.GetFolder "$path/$($item.Name)" }
+    else { "$path`t$(Get-FileName $item)`t$($item.ExtendedProperty('System.Size'))" }
+  }
+}
+Write-Files (Get-DeviceFolder) ''
+"#;
+
+// CopyHere returns before the copy is done, so wait until the file has its full size and the shell has
+// closed it. Flags: no progress window, yes to all, no directory confirmation, no error dialogs.
+const WPD_TRANSFER: &str = r#"$item = Find-DeviceFile $env:RAPIDRAW_FOLDER $env:RAPIDRAW_NAME
+$size = [uint64]$item.ExtendedProperty('System.Size')
+$target = Join-Path $env:RAPIDRAW_TARGET_DIR (Get-FileName $item)
+$shell.NameSpace($env:RAPIDRAW_TARGET_DIR).CopyHere($item, 4 + 16 + 512 + 1024)
+$deadline = (Get-Date).AddSeconds([int]$env:RAPIDRAW_TIMEOUT)
+while ($true) {
+  if ((Test-Path -LiteralPath $target) -and (Get-Item -LiteralPath $target).Length -ge $size) {
+    try { [IO.File]::Open($target, 'Open', 'Read', 'None').Close(); break } catch {}
+  }
+  if ((Get-Date) -gt $deadline) { throw "Timed out copying $($env:RAPIDRAW_NAME) from the device." }
+  Start-Sleep -Milliseconds 200
+}
+$size
+"#;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraDevice {
+    pub model: String,
+    pub port: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraFile {
+    pub number: u32,
+    pub folder: String,
+    pub name: String,
+    pub size_kb: Option<u64>,
+}
+
+fn run_gphoto2(args: &[&str]) -> Result<String, String> {
+    let output = Command::new(GPHOTO2).args(args).output().map_err(|e| {
+        if e.kind() == ErrorKind::NotFound {
+            "gphoto2 was not found. Install it to import from cameras and phones connected over USB.".to_string()
+        } else {
+            e.to_string()
+        }
+    })?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn run_wpd_script(script: &str, port: &str, vars: &[(&str, &str)]) -> Result<String, String> {
+    let mut command = Command::new(POWERSHELL);
+    command
+        .args(["-NoProfile", "-NonInteractive", "-ExecutionPolicy", "Bypass", "-Command"])
+        .arg(format!("{}{}", WPD_PRELUDE, script))
+        .env("RAPIDRAW_DEVICE", port)
+        .envs(vars.iter().copied());
+    #[cfg(target_os = "windows")]
+    command.creation_flags(CREATE_NO_WINDOW);
+    let output = command.output().map_err(|e| format!("Failed to run PowerShell: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse_wpd_devices(output: &str) -> Vec<CameraDevice> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (model, port) = line.trim_end().split_once('\t')?;
+            (!port.is_empty()).then(|| CameraDevice { model: model.to_string(), port: port.to_string() })
+        })
+        .collect()
+}
+
+// Lines are "<folder>\t<name>\t<size in bytes>". The numbers only identify files within one listing;
+// transfers look files up by folder and name.
+fn parse_wpd_files(output: &str) -> Vec<CameraFile> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let folder = fields.next()?;
+            let name = fields.next().filter(|name| !name.is_empty())?;
+            let size_kb = fields.next().and_then(|size| size.trim().parse::<u64>().ok()).map(|size| size / 1024);
+            Some((folder.to_string(), name.to_string(), size_kb))
+        })
+        .enumerate()
+        .map(|(i, (folder, name, size_kb))| CameraFile { number: i as u32 + 1, folder, name, size_kb })
+        .collect()
+}
+
+// The shell copies under the device's file name, so the copy lands in its own folder and is moved to
+// the target afterwards.
+fn transfer_wpd(port: &str, file: &CameraFile, target: &Path) -> Result<(), String> {
+    let copy_dir = target.with_file_name(format!(".copy-{}", Uuid::new_v4()));
+    fs::create_dir_all(&copy_dir).map_err(|e| e.to_string())?;
+    let result = (|| {
+        let copy_dir_str = copy_dir.to_string_lossy();
+        let timeout = WPD_COPY_TIMEOUT.as_secs().to_string();
+        let output = run_wpd_script(
+            WPD_TRANSFER,
+            port,
+            &[
+                ("RAPIDRAW_FOLDER", file.folder.as_str()),
+                ("RAPIDRAW_NAME", file.name.as_str()),
+                ("RAPIDRAW_TARGET_DIR", copy_dir_str.as_ref()),
+                ("RAPIDRAW_TIMEOUT", timeout.as_str()),
+            ],
+        )?;
+        let expected_size: u64 = output
+            .trim()
+            .parse()
+            .map_err(|_| format!("The device didn't report the size of {}.", file.name))?;
+        let copied = copy_dir.join(&file.name);
+        let size = fs::metadata(&copied).map(|m| m.len()).map_err(|_| format!("{} was not transferred.", file.name))?;
+        if size != expected_size {
+            return Err(format!("{} was transferred incompletely ({} of {} bytes).", file.name, size, expected_size));
+        }
+        fs::rename(&copied, target).map_err(|e| e.to_string())
+    })();
+    let _ = fs::remove_dir_all(&copy_dir);
+    result
+}
+
+// `--auto-detect` prints a "Model  Port" table below a dashed line; ports never contain spaces.
+fn parse_devices(output: &str) -> Vec<CameraDevice> {
+    output
+        .lines()
+        .skip_while(|line| !line.starts_with("---"))
+        .skip(1)
+        .filter_map(|line| {
+            let (model, port) = line.trim_end().rsplit_once(char::is_whitespace)?;
+            let (model, port) = (model.trim(), port.trim());
+            (!model.is_empty() && port.contains(':')).then(|| CameraDevice { model: model.to_string(), port: port.to_string() })
+        })
+        .collect()
+}
+
+// Permissions follow the name as "rd", with "-" for a missing one.
+fn strip_permission_flags(head: &str) -> &str {
+    let bytes = head.as_bytes();
+    let is_flags = bytes.len() > 2
+        && matches!(bytes[bytes.len() - 2], b'r' | b'-')
+        && matches!(bytes[bytes.len() - 1], b'd' | b'-');
+    if is_flags { &head[..head.len() - 2] } else { head }.trim_end()
+}
+
+// `--list-files` prints a header per folder followed by "#<n> <name>[flags] <size> KB <mime>". Names
+// are padded to a fixed width, can contain spaces and long ones run into the flags, so the name is
+// whatever precedes the flags and size. The numbers are what `--get-file` and `--delete-file` expect.
+fn parse_files(output: &str) -> Vec<CameraFile> {
+    let mut folder = String::new();
+    let mut files = Vec::new();
+    for line in output.lines() {
+        if let Some(start) = line.find("in folder '") {
+            let rest = &line[start + "in folder '".len()..];
+            folder = rest.rsplit_once('\'').map(|(f, _)| f.to_string()).unwrap_or_default();
+            continue;
+        }
+        let Some(entry) = line.strip_prefix('#') else { continue; };
+        let Some((number, rest)) = entry.split_once(char::is_whitespace) else { continue; };
+        let Ok(number) = number.parse() else { continue; };
+        let rest = rest.trim_start();
+        let (head, size_kb) = match rest.rfind(" KB") {
+            Some(kb) => {
+                let before = rest[..kb].trim_end();
+                match before.rsplit_once(char::is_whitespace) {
+                    Some((head, size)) => (head, size.parse().ok()),
+                    None => (before, None),
+                }
+            }
+            None => (
+                rest.rsplit_once(char::is_whitespace)
+                    .filter(|(_, last)| last.contains('/'))
+                    .map_or(rest, |(head, _)| head),
+                None,
+            ),
+        };
+        let name = strip_permission_flags(head.trim_end());
+        if name.is_empty() {
+            continue;
+        }
+        files.push(CameraFile { number, folder: folder.clone(), name: name.to_string(), size_kb });
+    }
+    files
+}
+
+// `--show-info` prints the exact size as "Size: <n> byte(s)" in the file section, which comes before
+// the thumbnail's.
+fn device_file_size(port: &str, file: &CameraFile) -> Result<u64, String> {
+    let output = run_gphoto2(&["--port", port, "--show-info", &file.number.to_string()])?;
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Size:"))
+        .and_then(|size| size.split_whitespace().next())
+        .and_then(|size| size.parse().ok())
+        .ok_or_else(|| format!("The device didn't report the size of {}.", file.name))
+}
+
+fn transfer(port: &str, file: &CameraFile, target: &Path) -> Result<(), String> {
+    if cfg!(windows) {
+        return transfer_wpd(port, file, target);
+    }
+    let expected_size = device_file_size(port, file)?;
+    let number = file.number.to_string();
+    let target_str = target.to_string_lossy();
+    run_gphoto2(&["--port", port, "--get-file", &number, "--filename", &target_str, "--force-overwrite"])?;
+
+    let size = fs::metadata(target).map(|m| m.len()).map_err(|_| format!("{} was not transferred.", file.name))?;
+    if size != expected_size {
+        return Err(format!("{} was transferred incompletely ({} of {} bytes).", file.name, size, expected_size));
+    }
+    Ok(())
+}
+
+fn same_contents(a: &Path, b: &Path) -> Result<bool, String> {
+    let hash = |path: &Path| fs::read(path).map(|bytes| blake3::hash(&bytes)).map_err(|e| e.to_string());
+    Ok(hash(a)? == hash(b)?)
+}
+
+// Reads the file from the device a second time and compares it with the imported copy, so a file
+// is only deleted from the device once a fresh read matches what was imported.
+fn verify_against_device(port: &str, file: &CameraFile, imported: &Path, staging: &Path) -> Result<(), String> {
+    let reread = staging.join(format!("verify-{}", file.name));
+    transfer(port, file, &reread)?;
+    let matches = same_contents(&reread, imported);
+    let _ = fs::remove_file(&reread);
+    if !matches? {
+        return Err(format!("{} doesn't match the copy on the device, nothing was deleted.", file.name));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_camera_devices() -> Result<Vec<CameraDevice>, String> {
+    tokio::task::spawn_blocking(|| {
+        if cfg!(windows) {
+            return run_wpd_script(WPD_LIST_DEVICES, "", &[]).map(|output| parse_wpd_devices(&output));
+        }
+        run_gphoto2(&["--auto-detect"]).map(|output| parse_devices(&output))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_camera_files(port: String) -> Result<Vec<CameraFile>, String> {
+    tokio::task::spawn_blocking(move || {
+        let files = if cfg!(windows) {
+            parse_wpd_files(&run_wpd_script(WPD_LIST_FILES, &port, &[])?)
+        } else {
+            parse_files(&run_gphoto2(&["--port", &port, "--list-files"])?)
+        };
+        Ok(files.into_iter().filter(|f| is_supported_library_file(&f.name)).collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Files are staged next to the destination, checked against the size the device reports, imported
+// with the regular import settings and compared byte for byte against the staged copy. Deleting
+// from the device only happens after every imported file matched a second read from the device.
+#[tauri::command]
+pub async fn import_from_camera(
+    port: String,
+    files: Vec<CameraFile>,
+    destination_folder: String,
+    settings: ImportSettings,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let total_files = files.len();
+    let _ = app_handle.emit(
+        "import-start",
+        serde_json::json!({ "total": total_files }),
+    );
+
+    tokio::task::spawn_blocking(move || {
+        let staging = Path::new(&destination_folder).join(STAGING_DIR_NAME);
+        let file_settings = ImportSettings { delete_after_import: false, ..settings.clone() };
+
+        let import_result: Result<(), String> = (|| {
+            // The shell can only delete from portable devices behind a confirmation dialog.
+            if settings.delete_after_import && cfg!(windows) {
+                return Err("Deleting from the device after import isn't supported on Windows. Import without \
+                            deleting and remove the files on the device afterwards."
+                    .to_string());
+            }
+            fs::create_dir_all(&staging).map_err(|e| format!("Failed to create staging folder: {}", e))?;
+            let mut imported_paths = Vec::with_capacity(files.len());
+            for (i, file) in files.iter().enumerate() {
+                let _ = app_handle.emit(
+                    "import-progress",
+                    serde_json::json!({ "current": i, "total": total_files, "path": file.name }),
+                );
+
+                let staged = staging.join(&file.name);
+                transfer(&port, file, &staged)?;
+                let imported = import_file(&staged.to_string_lossy(), &destination_folder, &file_settings, i + 1, total_files)?;
+                if !same_contents(&staged, &imported)? {
+                    let _ = fs::remove_file(&imported);
+                    return Err(format!("Verification failed for {}.", file.name));
+                }
+                fs::remove_file(&staged).map_err(|e| e.to_string())?;
+                imported_paths.push(imported);
+            }
+
+            if settings.delete_after_import {
+                for (file, imported) in files.iter().zip(&imported_paths) {
+                    verify_against_device(&port, file, imported, &staging)?;
+                }
+                // Deleting shifts the numbering of later files, so go from the highest number down.
+                let mut numbers: Vec<u32> = files.iter().map(|f| f.number).collect();
+                numbers.sort_unstable_by(|a, b| b.cmp(a));
+                for number in numbers {
+                    run_gphoto2(&["--port", &port, "--delete-file", &number.to_string()])?;
+                }
+            }
+            Ok(())
+        })();
+
+        let _ = fs::remove_dir_all(&staging);
+        if let Err(e) = import_result {
+            eprintln!("Failed to import from camera on {}: {}", port, e);
+            let _ = app_handle.emit("import-error", e);
+            return;
+        }
+
+        let _ = app_handle.emit(
+            "import-progress",
+            serde_json::json!({ "current": total_files, "total": total_files, "path": "" }),
+        );
+        let _ = app_handle.emit("import-complete", ());
+    });
+
+    Ok(())
+}
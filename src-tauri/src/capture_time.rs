@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use little_exif::exif_tag::ExifTag;
+use little_exif::metadata::Metadata;
+use rayon::prelude::*;
+use rusqlite::params;
+use tauri::AppHandle;
+
+use crate::catalog::{sync_folder, with_catalog};
+use crate::file_management::get_sidecar_path;
+use crate::iptc_metadata::read_metadata;
+
+pub const EXIF_DATE_FORMAT: &str = "%Y:%m:%d %H:%M:%S";
+
+fn exif_capture_time(path: &Path) -> Option<NaiveDateTime> {
+    let metadata = Metadata::new_from_path(path).ok()?;
+    let tag = metadata.get_tag(&ExifTag::DateTimeOriginal("".to_string())).next()?;
+    match tag {
+        ExifTag::DateTimeOriginal(dt_str) => NaiveDateTime::parse_from_str(dt_str.trim_end_matches('\0'), EXIF_DATE_FORMAT).ok(),
+        _ => None,
+    }
+}
+
+// Seconds added to the camera's DateTimeOriginal, e.g. to fix a clock left on the wrong time zone.
+pub fn capture_time_shift(path: &str) -> i64 {
+    read_metadata(path).capture_time_shift.unwrap_or(0)
+}
+
+pub fn shifted_capture_time(path: &Path) -> Option<NaiveDateTime> {
+    let shift = capture_time_shift(&path.to_string_lossy());
+    exif_capture_time(path).map(|dt| dt + Duration::seconds(shift))
+}
+
+// Date used for filename templates and date folders: the shifted capture time, falling back to the
+// file creation time for images without EXIF.
+pub fn file_date(path: &Path) -> DateTime<Utc> {
+    shifted_capture_time(path)
+        .map(|dt| DateTime::from_naive_utc_and_offset(dt, Utc))
+        .unwrap_or_else(|| {
+            fs::metadata(path)
+                .ok()
+                .and_then(|m| m.created().ok())
+                .map(DateTime::<Utc>::from)
+                .unwrap_or_else(Utc::now)
+        })
+}
+
+// Shifts are cumulative, so applying +1h twice moves the images by two hours.
+#[tauri::command]
+pub fn shift_capture_time_for_paths(paths: Vec<String>, offset_seconds: i64, app_handle: AppHandle) -> Result<(), String> {
+    if offset_seconds == 0 {
+        return Ok(());
+    }
+
+    paths.par_iter().try_for_each(|path| {
+        let mut metadata = read_metadata(path);
+        let shift = metadata.capture_time_shift.unwrap_or(0) + offset_seconds;
+        metadata.capture_time_shift = if shift == 0 { None } else { Some(shift) };
+        let json_string = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
+        fs::write(get_sidecar_path(path), json_string).map_err(|e| e.to_string())
+    })?;
+
+    let folders: HashSet<String> = paths
+        .iter()
+        .filter_map(|p| Path::new(p).parent().map(|f| f.to_string_lossy().into_owned()))
+        .collect();
+    with_catalog(&app_handle, |conn| {
+        // The catalog only re-reads EXIF when the image itself changes, so move the stored date here.
+        let modifier = format!("{:+} seconds", offset_seconds);
+        for path in &paths {
+            conn.execute(
+                "UPDATE images SET capture_date = datetime(capture_date, ?2) WHERE path = ?1 AND capture_date IS NOT NULL",
+                params![path, modifier],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        for folder in &folders {
+            sync_folder(conn, folder)?;
+        }
+        Ok(())
+    })
+}
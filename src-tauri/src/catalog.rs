@@ -0,0 +1,889 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use exif::{In, Tag};
+use rayon::prelude::*;
+use rusqlite::{params, params_from_iter, Connection, ToSql};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use walkdir::WalkDir;
+
+use crate::file_management::{get_sidecar_path, FilterCriteria};
+use crate::formats::{is_raw_file, is_supported_library_file};
+use crate::image_processing::{GpsLocation, ImageFlag, ImageMetadata};
+use crate::tagging::COLOR_TAG_PREFIX;
+use crate::AppState;
+
+const CATALOG_FILE_NAME: &str = "catalog.db";
+const CATALOG_SCHEMA_VERSION: i32 = 4;
+// Version 4 stores modification times in nanoseconds; earlier versions used whole seconds.
+const NANOSECOND_MODIFIED_VERSION: i32 = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogExif {
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens_model: Option<String>,
+    pub focal_length: Option<f64>,
+    pub iso: Option<i64>,
+    pub aperture: Option<f64>,
+    pub capture_date: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    pub path: String,
+    pub modified: u64,
+    pub is_edited: bool,
+    pub rating: u8,
+    pub color_label: Option<String>,
+    pub flag: Option<ImageFlag>,
+    pub tags: Option<Vec<String>>,
+    pub exif: CatalogExif,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogQuery {
+    pub folder: Option<String>,
+    #[serde(default)]
+    pub recursive: bool,
+    pub min_rating: Option<u8>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub color_label: Option<String>,
+    pub edited: Option<bool>,
+    pub search: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+struct FileState {
+    path: String,
+    file_name: String,
+    modified: u64,
+    sidecar_modified: u64,
+}
+
+#[derive(Default)]
+struct SidecarSummary {
+    rating: u8,
+    color_label: Option<String>,
+    tags: Option<Vec<String>>,
+    is_edited: bool,
+    gps: Option<GpsLocation>,
+    flag: Option<ImageFlag>,
+    capture_time_shift: i64,
+}
+
+struct IndexedFile {
+    state: FileState,
+    sidecar: SidecarSummary,
+    exif: Option<CatalogExif>,
+}
+
+// Nanoseconds, so edits saved within the same second as the previous sync still register.
+fn modified_nanos(path: &Path) -> u64 {
+    fs::metadata(path)
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+// face_scans and published_images survive schema bumps but still hold second timestamps from before
+// version 4. Rows whose files are unchanged at that resolution get the nanosecond value, so faces
+// aren't rescanned and images aren't republished just because of the upgrade.
+fn migrate_kept_modified_times(conn: &Connection) -> Result<(), String> {
+    let to_secs = |nanos: u64| nanos / 1_000_000_000;
+
+    let face_scans = conn
+        .prepare("SELECT path, modified FROM face_scans")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| e.to_string())?;
+    for (path, secs) in face_scans {
+        let modified = modified_nanos(Path::new(&path));
+        if to_secs(modified) == secs {
+            conn.execute("UPDATE face_scans SET modified = ?2 WHERE path = ?1", params![path, modified])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    let published = conn
+        .prepare("SELECT service_id, path, modified, sidecar_modified FROM published_images")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, u64>(2)?,
+                    row.get::<_, u64>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| e.to_string())?;
+    for (service_id, path, secs, sidecar_secs) in published {
+        let (modified, sidecar_modified) = file_fingerprint(&path);
+        if to_secs(modified) == secs && to_secs(sidecar_modified) == sidecar_secs {
+            conn.execute(
+                "UPDATE published_images SET modified = ?3, sidecar_modified = ?4 WHERE service_id = ?1 AND path = ?2",
+                params![service_id, path, modified, sidecar_modified],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+fn open_catalog(app_handle: &AppHandle) -> Result<Connection, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+    fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+
+    let conn = Connection::open(data_dir.join(CATALOG_FILE_NAME)).map_err(|e| e.to_string())?;
+    let stored_version: i32 = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    // Schema bumps rebuild the tables derived from the files. published_images records what was sent to
+    // publish services and face_clusters the names given to people, together with the faces that define
+    // them, so those are kept; change them with ALTER TABLE migrations instead. Foreign keys are still
+    // off here, so dropping images leaves the faces in place.
+    if stored_version != 0 && stored_version < CATALOG_SCHEMA_VERSION {
+        conn.execute_batch(
+            "DROP TABLE IF EXISTS image_embeddings; DROP TABLE IF EXISTS image_tags; DROP TABLE IF EXISTS images;",
+        )
+            .map_err(|e| e.to_string())?;
+    }
+    conn.execute_batch(
+        "PRAGMA journal_mode = WAL;
+         PRAGMA synchronous = NORMAL;
+         PRAGMA foreign_keys = ON;
+         CREATE TABLE IF NOT EXISTS images (
+             path TEXT PRIMARY KEY,
+             folder TEXT NOT NULL,
+             file_name TEXT NOT NULL,
+             modified INTEGER NOT NULL,
+             sidecar_modified INTEGER NOT NULL,
+             rating INTEGER NOT NULL DEFAULT 0,
+             color_label TEXT,
+             flag TEXT,
+             tags TEXT,
+             is_edited INTEGER NOT NULL DEFAULT 0,
+             camera_make TEXT,
+             camera_model TEXT,
+             lens_model TEXT,
+             focal_length REAL,
+             iso INTEGER,
+             aperture REAL,
+             capture_date TEXT,
+             exif_latitude REAL,
+             exif_longitude REAL,
+             gps_latitude REAL,
+             gps_longitude REAL
+         );
+         CREATE INDEX IF NOT EXISTS idx_images_folder ON images(folder);
+         CREATE INDEX IF NOT EXISTS idx_images_rating ON images(rating);
+         CREATE TABLE IF NOT EXISTS image_tags (
+             path TEXT NOT NULL REFERENCES images(path) ON DELETE CASCADE,
+             tag TEXT NOT NULL,
+             PRIMARY KEY (path, tag)
+         );
+         CREATE INDEX IF NOT EXISTS idx_image_tags_tag ON image_tags(tag);
+         CREATE TABLE IF NOT EXISTS image_embeddings (
+             path TEXT PRIMARY KEY REFERENCES images(path) ON DELETE CASCADE,
+             modified INTEGER NOT NULL,
+             embedding BLOB NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS face_clusters (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             name TEXT
+         );
+         CREATE TABLE IF NOT EXISTS face_scans (
+             path TEXT PRIMARY KEY REFERENCES images(path) ON DELETE CASCADE,
+             modified INTEGER NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS faces (
+             id INTEGER PRIMARY KEY AUTOINCREMENT,
+             path TEXT NOT NULL REFERENCES images(path) ON DELETE CASCADE,
+             x1 REAL NOT NULL,
+             y1 REAL NOT NULL,
+             x2 REAL NOT NULL,
+             y2 REAL NOT NULL,
+             embedding BLOB NOT NULL,
+             cluster_id INTEGER REFERENCES face_clusters(id) ON DELETE SET NULL
+         );
+         CREATE INDEX IF NOT EXISTS idx_faces_path ON faces(path);
+         CREATE INDEX IF NOT EXISTS idx_faces_cluster ON faces(cluster_id);
+         CREATE TABLE IF NOT EXISTS published_images (
+             service_id TEXT NOT NULL,
+             path TEXT NOT NULL,
+             modified INTEGER NOT NULL,
+             sidecar_modified INTEGER NOT NULL,
+             remote_id TEXT,
+             published_at INTEGER NOT NULL,
+             PRIMARY KEY (service_id, path)
+         );",
+    )
+    .map_err(|e| e.to_string())?;
+    if stored_version != 0 && stored_version < NANOSECOND_MODIFIED_VERSION {
+        migrate_kept_modified_times(&conn)?;
+    }
+    conn.pragma_update(None, "user_version", CATALOG_SCHEMA_VERSION)
+        .map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+pub fn with_catalog<T>(
+    app_handle: &AppHandle,
+    f: impl FnOnce(&mut Connection) -> Result<T, String>,
+) -> Result<T, String> {
+    let state = app_handle.state::<AppState>();
+    let mut catalog_lock = state.catalog.lock().unwrap();
+    if catalog_lock.is_none() {
+        *catalog_lock = Some(open_catalog(app_handle)?);
+    }
+    f(catalog_lock.as_mut().unwrap())
+}
+
+pub fn ascii_field(exif: &exif::Exif, tag: Tag) -> Option<String> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    match &field.value {
+        exif::Value::Ascii(values) => values
+            .first()
+            .map(|v| String::from_utf8_lossy(v).trim().trim_end_matches('\0').to_string())
+            .filter(|v| !v.is_empty()),
+        _ => None,
+    }
+}
+
+fn number_field(exif: &exif::Exif, tag: Tag) -> Option<f64> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    match &field.value {
+        exif::Value::Rational(values) => values.first().map(|v| v.to_f64()),
+        exif::Value::SRational(values) => values.first().map(|v| v.to_f64()),
+        exif::Value::Short(values) => values.first().map(|v| *v as f64),
+        exif::Value::Long(values) => values.first().map(|v| *v as f64),
+        _ => None,
+    }
+}
+
+fn gps_coordinate(exif: &exif::Exif, value_tag: Tag, ref_tag: Tag) -> Option<f64> {
+    let field = exif.get_field(value_tag, In::PRIMARY)?;
+    let exif::Value::Rational(parts) = &field.value else {
+        return None;
+    };
+    let degrees = parts.iter().take(3).zip([1.0, 60.0, 3600.0]).map(|(v, div)| v.to_f64() / div).sum::<f64>();
+    let negative = ascii_field(exif, ref_tag).map_or(false, |r| r.starts_with('S') || r.starts_with('W'));
+    let value = if negative { -degrees } else { degrees };
+    value.is_finite().then_some(value)
+}
+
+pub fn read_catalog_exif(path: &Path) -> CatalogExif {
+    let Ok(file) = fs::File::open(path) else {
+        return CatalogExif::default();
+    };
+    let Ok(exif) = exif::Reader::new().read_from_container(&mut BufReader::new(file)) else {
+        return CatalogExif::default();
+    };
+
+    let capture_date = ascii_field(&exif, Tag::DateTimeOriginal)
+        .or_else(|| ascii_field(&exif, Tag::DateTime))
+        .and_then(|d| chrono::NaiveDateTime::parse_from_str(&d, "%Y:%m:%d %H:%M:%S").ok())
+        .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string());
+
+    CatalogExif {
+        camera_make: ascii_field(&exif, Tag::Make),
+        camera_model: ascii_field(&exif, Tag::Model),
+        lens_model: ascii_field(&exif, Tag::LensModel),
+        focal_length: number_field(&exif, Tag::FocalLength),
+        iso: number_field(&exif, Tag::PhotographicSensitivity).map(|v| v as i64),
+        aperture: number_field(&exif, Tag::FNumber),
+        capture_date,
+        latitude: gps_coordinate(&exif, Tag::GPSLatitude, Tag::GPSLatitudeRef),
+        longitude: gps_coordinate(&exif, Tag::GPSLongitude, Tag::GPSLongitudeRef),
+    }
+}
+
+fn read_sidecar_summary(path: &str) -> SidecarSummary {
+    let sidecar_path = get_sidecar_path(path);
+    let metadata = fs::read_to_string(sidecar_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<ImageMetadata>(&content).ok());
+
+    let Some(metadata) = metadata else {
+        return SidecarSummary::default();
+    };
+    let is_edited = metadata.adjustments.as_object().map_or(false, |a| {
+        a.keys().len() > 1 || (a.keys().len() == 1 && !a.contains_key("rating"))
+    });
+    let color_label = metadata.tags.as_ref().and_then(|tags| {
+        tags.iter()
+            .find_map(|t| t.strip_prefix(COLOR_TAG_PREFIX).map(str::to_string))
+    });
+    SidecarSummary {
+        rating: metadata.rating,
+        color_label,
+        tags: metadata.tags,
+        is_edited,
+        gps: metadata.gps,
+        flag: metadata.flag,
+        capture_time_shift: metadata.capture_time_shift.unwrap_or(0),
+    }
+}
+
+fn scan_folder(folder: &Path) -> Result<Vec<FileState>, String> {
+    let files = fs::read_dir(folder)
+        .map_err(|e| e.to_string())?
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            !path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .map_or(false, |s| s.starts_with('.'))
+        })
+        .filter(|path| path.is_file())
+        .filter(|path| path.to_str().map_or(false, is_supported_library_file))
+        .map(|path| {
+            let path_str = path.to_string_lossy().into_owned();
+            FileState {
+                file_name: path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+                modified: modified_nanos(&path),
+                sidecar_modified: modified_nanos(&get_sidecar_path(&path_str)),
+                path: path_str,
+            }
+        })
+        .collect();
+    Ok(files)
+}
+
+pub fn sync_folder(conn: &mut Connection, folder: &str) -> Result<usize, String> {
+    let files = scan_folder(Path::new(folder))?;
+
+    let mut known: HashMap<String, (u64, u64)> = HashMap::new();
+    {
+        let mut stmt = conn
+            .prepare_cached("SELECT path, modified, sidecar_modified FROM images WHERE folder = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![folder], |row| Ok((row.get::<_, String>(0)?, (row.get::<_, u64>(1)?, row.get::<_, u64>(2)?))))
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let (path, times) = row.map_err(|e| e.to_string())?;
+            known.insert(path, times);
+        }
+    }
+
+    let present: HashSet<String> = files.iter().map(|f| f.path.clone()).collect();
+    let changed: Vec<IndexedFile> = files
+        .into_par_iter()
+        .filter_map(|file| {
+            let previous = known.get(&file.path).copied();
+            if previous == Some((file.modified, file.sidecar_modified)) {
+                return None;
+            }
+            let needs_exif = previous.map_or(true, |(modified, _)| modified != file.modified);
+            let sidecar = read_sidecar_summary(&file.path);
+            let exif = if needs_exif { Some(read_catalog_exif(Path::new(&file.path))) } else { None };
+            Some(IndexedFile { state: file, sidecar, exif })
+        })
+        .collect();
+
+    let removed: Vec<&String> = known.keys().filter(|p| !present.contains(*p)).collect();
+    let changed_count = changed.len() + removed.len();
+    if changed_count == 0 {
+        return Ok(0);
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    {
+        let mut upsert = tx
+            .prepare_cached(
+                "INSERT INTO images (path, folder, file_name, modified, sidecar_modified, rating, color_label, tags, is_edited,
+                                     camera_make, camera_model, lens_model, focal_length, iso, aperture, capture_date,
+                                     exif_latitude, exif_longitude, gps_latitude, gps_longitude, flag)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?18, ?19, ?20, ?21, ?22)
+                 ON CONFLICT(path) DO UPDATE SET
+                     modified = excluded.modified,
+                     sidecar_modified = excluded.sidecar_modified,
+                     rating = excluded.rating,
+                     color_label = excluded.color_label,
+                     tags = excluded.tags,
+                     is_edited = excluded.is_edited,
+                     gps_latitude = excluded.gps_latitude,
+                     gps_longitude = excluded.gps_longitude,
+                     flag = excluded.flag,
+                     camera_make = CASE WHEN ?17 THEN excluded.camera_make ELSE camera_make END,
+                     camera_model = CASE WHEN ?17 THEN excluded.camera_model ELSE camera_model END,
+                     lens_model = CASE WHEN ?17 THEN excluded.lens_model ELSE lens_model END,
+                     focal_length = CASE WHEN ?17 THEN excluded.focal_length ELSE focal_length END,
+                     iso = CASE WHEN ?17 THEN excluded.iso ELSE iso END,
+                     aperture = CASE WHEN ?17 THEN excluded.aperture ELSE aperture END,
+                     capture_date = CASE WHEN ?17 THEN excluded.capture_date ELSE capture_date END,
+                     exif_latitude = CASE WHEN ?17 THEN excluded.exif_latitude ELSE exif_latitude END,
+                     exif_longitude = CASE WHEN ?17 THEN excluded.exif_longitude ELSE exif_longitude END",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut clear_tags = tx
+            .prepare_cached("DELETE FROM image_tags WHERE path = ?1")
+            .map_err(|e| e.to_string())?;
+        let mut insert_tag = tx
+            .prepare_cached("INSERT OR IGNORE INTO image_tags (path, tag) VALUES (?1, ?2)")
+            .map_err(|e| e.to_string())?;
+        let mut delete = tx
+            .prepare_cached("DELETE FROM images WHERE path = ?1")
+            .map_err(|e| e.to_string())?;
+
+        for file in &changed {
+            let mut exif = file.exif.clone().unwrap_or_default();
+            if file.sidecar.capture_time_shift != 0 {
+                exif.capture_date = exif
+                    .capture_date
+                    .and_then(|d| chrono::NaiveDateTime::parse_from_str(&d, "%Y-%m-%d %H:%M:%S").ok())
+                    .map(|d| (d + chrono::Duration::seconds(file.sidecar.capture_time_shift)).format("%Y-%m-%d %H:%M:%S").to_string());
+            }
+            let tags_json = file.sidecar.tags.as_ref().and_then(|t| serde_json::to_string(t).ok());
+            upsert
+                .execute(params![
+                    file.state.path,
+                    folder,
+                    file.state.file_name,
+                    file.state.modified,
+                    file.state.sidecar_modified,
+                    file.sidecar.rating,
+                    file.sidecar.color_label,
+                    tags_json,
+                    file.sidecar.is_edited,
+                    exif.camera_make,
+                    exif.camera_model,
+                    exif.lens_model,
+                    exif.focal_length,
+                    exif.iso,
+                    exif.aperture,
+                    exif.capture_date,
+                    file.exif.is_some(),
+                    exif.latitude,
+                    exif.longitude,
+                    file.sidecar.gps.map(|g| g.latitude),
+                    file.sidecar.gps.map(|g| g.longitude),
+                    file.sidecar.flag.map(|f| f.as_str()),
+                ])
+                .map_err(|e| e.to_string())?;
+
+            clear_tags.execute(params![file.state.path]).map_err(|e| e.to_string())?;
+            for tag in file.sidecar.tags.iter().flatten() {
+                insert_tag.execute(params![file.state.path, tag]).map_err(|e| e.to_string())?;
+            }
+        }
+
+        for path in removed {
+            delete.execute(params![path]).map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(changed_count)
+}
+
+pub fn has_current_embedding(conn: &Connection, path: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM image_embeddings e JOIN images i ON i.path = e.path
+                        WHERE e.path = ?1 AND e.modified = i.modified)",
+        params![path],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+// Published state isn't tied to the images table, since albums can hold images from folders that were
+// never synced.
+#[derive(Debug, Clone)]
+pub struct PublishedImage {
+    pub modified: u64,
+    pub sidecar_modified: u64,
+    pub remote_id: Option<String>,
+}
+
+pub fn file_fingerprint(path: &str) -> (u64, u64) {
+    (modified_nanos(Path::new(path)), modified_nanos(&get_sidecar_path(path)))
+}
+
+pub fn load_published(conn: &Connection, service_id: &str) -> Result<HashMap<String, PublishedImage>, String> {
+    let mut stmt = conn
+        .prepare_cached("SELECT path, modified, sidecar_modified, remote_id FROM published_images WHERE service_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![service_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                PublishedImage {
+                    modified: row.get(1)?,
+                    sidecar_modified: row.get(2)?,
+                    remote_id: row.get(3)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<HashMap<_, _>>>().map_err(|e| e.to_string())
+}
+
+pub fn mark_published(
+    conn: &Connection,
+    service_id: &str,
+    path: &str,
+    (modified, sidecar_modified): (u64, u64),
+    remote_id: Option<&str>,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO published_images (service_id, path, modified, sidecar_modified, remote_id, published_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, strftime('%s', 'now'))
+         ON CONFLICT(service_id, path) DO UPDATE SET modified = excluded.modified,
+             sidecar_modified = excluded.sidecar_modified, remote_id = excluded.remote_id,
+             published_at = excluded.published_at",
+        params![service_id, path, modified, sidecar_modified, remote_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn embedding_to_blob(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub fn blob_to_embedding(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+pub fn store_embedding(conn: &Connection, path: &str, embedding: &[f32]) -> Result<(), String> {
+    let bytes = embedding_to_blob(embedding);
+    conn.execute(
+        "INSERT INTO image_embeddings (path, modified, embedding)
+         SELECT path, modified, ?2 FROM images WHERE path = ?1
+         ON CONFLICT(path) DO UPDATE SET modified = excluded.modified, embedding = excluded.embedding",
+        params![path, bytes],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn load_embeddings(conn: &Connection, folder: Option<&str>) -> Result<Vec<(String, Vec<f32>)>, String> {
+    let mut stmt = conn
+        .prepare_cached(
+            "SELECT e.path, e.embedding FROM image_embeddings e JOIN images i ON i.path = e.path
+             WHERE ?1 IS NULL OR i.folder = ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![folder], |row| {
+            let bytes: Vec<u8> = row.get(1)?;
+            Ok((row.get::<_, String>(0)?, blob_to_embedding(&bytes)))
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+pub fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<CatalogEntry> {
+    let tags: Option<String> = row.get("tags")?;
+    Ok(CatalogEntry {
+        path: row.get("path")?,
+        // Stored in nanoseconds, but the library lists modification times in seconds.
+        modified: row.get::<_, u64>("modified")? / 1_000_000_000,
+        is_edited: row.get("is_edited")?,
+        rating: row.get("rating")?,
+        color_label: row.get("color_label")?,
+        flag: row.get::<_, Option<String>>("flag")?.and_then(|f| ImageFlag::parse(&f)),
+        tags: tags.and_then(|t| serde_json::from_str(&t).ok()),
+        exif: CatalogExif {
+            camera_make: row.get("camera_make")?,
+            camera_model: row.get("camera_model")?,
+            lens_model: row.get("lens_model")?,
+            focal_length: row.get("focal_length")?,
+            iso: row.get("iso")?,
+            aperture: row.get("aperture")?,
+            capture_date: row.get("capture_date")?,
+            latitude: row.get::<_, Option<f64>>("gps_latitude")?.or(row.get("exif_latitude")?),
+            longitude: row.get::<_, Option<f64>>("gps_longitude")?.or(row.get("exif_longitude")?),
+        },
+    })
+}
+
+pub fn query_entries(conn: &Connection, query: &CatalogQuery) -> Result<Vec<CatalogEntry>, String> {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+    if let Some(folder) = &query.folder {
+        if query.recursive {
+            let prefix = format!("{}{}", folder.trim_end_matches(std::path::MAIN_SEPARATOR), std::path::MAIN_SEPARATOR);
+            conditions.push("(folder = ? OR folder LIKE ? ESCAPE '\\')".to_string());
+            values.push(Box::new(folder.clone()));
+            values.push(Box::new(format!("{}%", escape_like(&prefix))));
+        } else {
+            conditions.push("folder = ?".to_string());
+            values.push(Box::new(folder.clone()));
+        }
+    }
+    if let Some(min_rating) = query.min_rating {
+        conditions.push("rating >= ?".to_string());
+        values.push(Box::new(min_rating));
+    }
+    if let Some(color_label) = &query.color_label {
+        conditions.push("color_label = ?".to_string());
+        values.push(Box::new(color_label.clone()));
+    }
+    if let Some(edited) = query.edited {
+        conditions.push("is_edited = ?".to_string());
+        values.push(Box::new(edited));
+    }
+    if let Some(search) = query.search.as_ref().filter(|s| !s.is_empty()) {
+        conditions.push("file_name LIKE ? ESCAPE '\\'".to_string());
+        values.push(Box::new(format!("%{}%", escape_like(search))));
+    }
+    for tag in &query.tags {
+        conditions.push("EXISTS (SELECT 1 FROM image_tags t WHERE t.path = images.path AND t.tag = ?)".to_string());
+        values.push(Box::new(tag.clone()));
+    }
+
+    let mut sql = "SELECT * FROM images".to_string();
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+    }
+    sql.push_str(" ORDER BY path");
+    if let Some(limit) = query.limit {
+        sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, query.offset.unwrap_or(0)));
+    }
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), row_to_entry)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterOptions {
+    pub camera_models: Vec<String>,
+    pub lens_models: Vec<String>,
+    pub focal_length_range: Option<(f64, f64)>,
+    pub iso_range: Option<(i64, i64)>,
+    pub aperture_range: Option<(f64, f64)>,
+    pub capture_date_range: Option<(String, String)>,
+}
+
+fn push_in_condition(column: &str, items: &[String], conditions: &mut Vec<String>, values: &mut Vec<Box<dyn ToSql>>) {
+    if items.is_empty() {
+        return;
+    }
+    let placeholders = vec!["?"; items.len()].join(", ");
+    conditions.push(format!("{} IN ({})", column, placeholders));
+    for item in items {
+        values.push(Box::new(item.clone()));
+    }
+}
+
+fn push_range_condition<T: ToSql + Clone + 'static>(
+    column: &str,
+    min: Option<&T>,
+    max: Option<&T>,
+    conditions: &mut Vec<String>,
+    values: &mut Vec<Box<dyn ToSql>>,
+) {
+    if let Some(min) = min {
+        conditions.push(format!("{} >= ?", column));
+        values.push(Box::new(min.clone()));
+    }
+    if let Some(max) = max {
+        conditions.push(format!("{} <= ?", column));
+        values.push(Box::new(max.clone()));
+    }
+}
+
+pub fn filter_folder(conn: &Connection, folder: &str, criteria: &FilterCriteria) -> Result<Vec<String>, String> {
+    let mut conditions: Vec<String> = vec!["folder = ?".to_string()];
+    let mut values: Vec<Box<dyn ToSql>> = vec![Box::new(folder.to_string())];
+
+    if criteria.rating == 5 {
+        conditions.push("rating = 5".to_string());
+    } else if criteria.rating > 0 {
+        conditions.push("rating >= ?".to_string());
+        values.push(Box::new(criteria.rating));
+    }
+
+    if !criteria.colors.is_empty() {
+        let labels: Vec<String> = criteria.colors.iter().filter(|c| *c != "none").cloned().collect();
+        let mut color_conditions = Vec::new();
+        push_in_condition("color_label", &labels, &mut color_conditions, &mut values);
+        if criteria.colors.iter().any(|c| c == "none") {
+            color_conditions.push("color_label IS NULL".to_string());
+        }
+        conditions.push(format!("({})", color_conditions.join(" OR ")));
+    }
+
+    match criteria.flag_status.as_deref() {
+        Some("picked") => conditions.push("flag = 'pick'".to_string()),
+        Some("rejected") => conditions.push("flag = 'reject'".to_string()),
+        Some("unflagged") => conditions.push("flag IS NULL".to_string()),
+        _ => {}
+    }
+
+    push_in_condition("camera_model", &criteria.camera_models, &mut conditions, &mut values);
+    push_in_condition("lens_model", &criteria.lens_models, &mut conditions, &mut values);
+    push_range_condition(
+        "focal_length",
+        criteria.focal_length_min.as_ref(),
+        criteria.focal_length_max.as_ref(),
+        &mut conditions,
+        &mut values,
+    );
+    push_range_condition(
+        "iso",
+        criteria.iso_min.as_ref(),
+        criteria.iso_max.as_ref(),
+        &mut conditions,
+        &mut values,
+    );
+    push_range_condition(
+        "aperture",
+        criteria.aperture_min.as_ref(),
+        criteria.aperture_max.as_ref(),
+        &mut conditions,
+        &mut values,
+    );
+    push_range_condition(
+        "substr(capture_date, 1, 10)",
+        criteria.capture_date_from.as_ref().filter(|d| !d.is_empty()),
+        criteria.capture_date_to.as_ref().filter(|d| !d.is_empty()),
+        &mut conditions,
+        &mut values,
+    );
+
+    let sql = format!("SELECT path FROM images WHERE {} ORDER BY path", conditions.join(" AND "));
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    let paths = rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())?;
+
+    Ok(match criteria.raw_status.as_str() {
+        "rawOnly" => paths.into_iter().filter(|p| is_raw_file(p)).collect(),
+        "nonRawOnly" => paths.into_iter().filter(|p| !is_raw_file(p)).collect(),
+        _ => paths,
+    })
+}
+
+fn distinct_values(conn: &Connection, column: &str, folder: &str) -> Result<Vec<String>, String> {
+    let sql = format!(
+        "SELECT DISTINCT {0} FROM images WHERE folder = ?1 AND {0} IS NOT NULL ORDER BY {0} COLLATE NOCASE",
+        column
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![folder], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+pub fn list_folder(app_handle: &AppHandle, folder: &str) -> Result<Vec<CatalogEntry>, String> {
+    with_catalog(app_handle, |conn| {
+        sync_folder(conn, folder)?;
+        query_entries(
+            conn,
+            &CatalogQuery { folder: Some(folder.to_string()), ..Default::default() },
+        )
+    })
+}
+
+#[tauri::command]
+pub fn query_catalog(query: CatalogQuery, app_handle: AppHandle) -> Result<Vec<CatalogEntry>, String> {
+    with_catalog(&app_handle, |conn| query_entries(conn, &query))
+}
+
+#[tauri::command]
+pub fn filter_images(folder: String, criteria: FilterCriteria, app_handle: AppHandle) -> Result<Vec<String>, String> {
+    with_catalog(&app_handle, |conn| {
+        sync_folder(conn, &folder)?;
+        filter_folder(conn, &folder, &criteria)
+    })
+}
+
+#[tauri::command]
+pub fn get_filter_options(folder: String, app_handle: AppHandle) -> Result<FilterOptions, String> {
+    with_catalog(&app_handle, |conn| {
+        sync_folder(conn, &folder)?;
+        let (focal_length_range, iso_range, aperture_range, capture_date_range) = conn
+            .query_row(
+                "SELECT MIN(focal_length), MAX(focal_length), MIN(iso), MAX(iso), MIN(aperture), MAX(aperture),
+                        MIN(capture_date), MAX(capture_date)
+                 FROM images WHERE folder = ?1",
+                params![folder],
+                |row| {
+                    let pair = |a: Option<f64>, b: Option<f64>| a.zip(b);
+                    Ok((
+                        pair(row.get(0)?, row.get(1)?),
+                        row.get::<_, Option<i64>>(2)?.zip(row.get::<_, Option<i64>>(3)?),
+                        pair(row.get(4)?, row.get(5)?),
+                        row.get::<_, Option<String>>(6)?.zip(row.get::<_, Option<String>>(7)?),
+                    ))
+                },
+            )
+            .map_err(|e| e.to_string())?;
+
+        Ok(FilterOptions {
+            camera_models: distinct_values(conn, "camera_model", &folder)?,
+            lens_models: distinct_values(conn, "lens_model", &folder)?,
+            focal_length_range,
+            iso_range,
+            aperture_range,
+            capture_date_range,
+        })
+    })
+}
+
+#[tauri::command]
+pub async fn sync_catalog(root_path: String, app_handle: AppHandle) -> Result<usize, String> {
+    tokio::task::spawn_blocking(move || {
+        let folders: Vec<PathBuf> = WalkDir::new(&root_path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_dir())
+            .filter(|e| !e.file_name().to_str().map_or(false, |s| s.starts_with('.')))
+            .map(|e| e.into_path())
+            .collect();
+
+        let total = folders.len();
+        let mut updated = 0;
+        for (i, folder) in folders.iter().enumerate() {
+            let _ = app_handle.emit("catalog-sync-progress", serde_json::json!({ "current": i, "total": total }));
+            let folder_str = folder.to_string_lossy().into_owned();
+            match with_catalog(&app_handle, |conn| sync_folder(conn, &folder_str)) {
+                Ok(count) => updated += count,
+                Err(e) => eprintln!("Failed to index folder '{}': {}", folder_str, e),
+            }
+        }
+        let _ = app_handle.emit("catalog-sync-finished", serde_json::json!({ "updated": updated }));
+        Ok(updated)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
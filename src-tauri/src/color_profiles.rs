@@ -0,0 +1,663 @@
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::{DynamicImage, ImageBuffer, ImageEncoder, RgbImage, Rgba};
+use lcms2::{ColorSpaceSignature, Flags, InfoType, Intent, Locale, PixelFormat, Profile, ProfileClassSignature, Transform};
+use nalgebra::Matrix3;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+const SRGB_TO_XYZ_D65: [[f32; 3]; 3] = [
+    [0.4124564, 0.3575761, 0.1804375],
+    [0.2126729, 0.7151522, 0.0721750],
+    [0.0193339, 0.1191920, 0.9503041],
+];
+
+const ADOBE_RGB_TO_XYZ_D65: [[f32; 3]; 3] = [
+    [0.5767309, 0.1855540, 0.1881852],
+    [0.2973769, 0.6273491, 0.0752741],
+    [0.0270343, 0.0706872, 0.9911085],
+];
+
+const DISPLAY_P3_TO_XYZ_D65: [[f32; 3]; 3] = [
+    [0.4865709, 0.2656677, 0.1982173],
+    [0.2289746, 0.6917385, 0.0792869],
+    [0.0000000, 0.0451134, 1.0439444],
+];
+
+const PROPHOTO_TO_XYZ_D50: [[f32; 3]; 3] = [
+    [0.7976749, 0.1351917, 0.0313534],
+    [0.2880402, 0.7118741, 0.0000857],
+    [0.0000000, 0.0000000, 0.8252100],
+];
+
+const BRADFORD_D65_TO_D50: [[f32; 3]; 3] = [
+    [1.0478112, 0.0228866, -0.0501270],
+    [0.0295424, 0.9904844, -0.0170491],
+    [-0.0092345, 0.0150436, 0.7521316],
+];
+
+const D50_WHITE: [f32; 3] = [0.9642, 1.0, 0.8249];
+const ICC_CURVE_ENTRIES: usize = 1024;
+const PROFILE_MATCH_TOLERANCE: f32 = 0.01;
+const CICP_PRIMARIES_BT2020: u8 = 9;
+const CICP_TRANSFER_PQ: u8 = 16;
+const CICP_TRANSFER_HLG: u8 = 18;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputColorSpace {
+    #[default]
+    #[serde(rename = "sRGB")]
+    Srgb,
+    #[serde(rename = "AdobeRGB")]
+    AdobeRgb,
+    #[serde(rename = "ProPhoto")]
+    ProPhoto,
+    #[serde(rename = "DisplayP3")]
+    DisplayP3,
+}
+
+impl OutputColorSpace {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputColorSpace::Srgb => "sRGB IEC61966-2.1",
+            OutputColorSpace::AdobeRgb => "Adobe RGB (1998) compatible",
+            OutputColorSpace::ProPhoto => "ProPhoto RGB compatible",
+            OutputColorSpace::DisplayP3 => "Display P3",
+        }
+    }
+
+    fn to_xyz_d50(&self) -> Matrix3<f32> {
+        let bradford = Matrix3::from_fn(|i, j| BRADFORD_D65_TO_D50[i][j]);
+        let d65 = |m: [[f32; 3]; 3]| bradford * Matrix3::from_fn(|i, j| m[i][j]);
+        match self {
+            OutputColorSpace::Srgb => d65(SRGB_TO_XYZ_D65),
+            OutputColorSpace::AdobeRgb => d65(ADOBE_RGB_TO_XYZ_D65),
+            OutputColorSpace::DisplayP3 => d65(DISPLAY_P3_TO_XYZ_D65),
+            OutputColorSpace::ProPhoto => Matrix3::from_fn(|i, j| PROPHOTO_TO_XYZ_D50[i][j]),
+        }
+    }
+
+    fn encode(&self, linear: f32) -> f32 {
+        let c = linear.clamp(0.0, 1.0);
+        match self {
+            OutputColorSpace::Srgb | OutputColorSpace::DisplayP3 => {
+                if c <= 0.0031308 {
+                    c * 12.92
+                } else {
+                    1.055 * c.powf(1.0 / 2.4) - 0.055
+                }
+            }
+            OutputColorSpace::AdobeRgb => c.powf(256.0 / 563.0),
+            OutputColorSpace::ProPhoto => {
+                if c < 1.0 / 512.0 {
+                    c * 16.0
+                } else {
+                    c.powf(1.0 / 1.8)
+                }
+            }
+        }
+    }
+
+    fn decode(&self, encoded: f32) -> f32 {
+        let c = encoded.clamp(0.0, 1.0);
+        match self {
+            OutputColorSpace::Srgb | OutputColorSpace::DisplayP3 => {
+                if c <= 0.04045 {
+                    c / 12.92
+                } else {
+                    ((c + 0.055) / 1.055).powf(2.4)
+                }
+            }
+            OutputColorSpace::AdobeRgb => c.powf(563.0 / 256.0),
+            OutputColorSpace::ProPhoto => {
+                if c < 16.0 / 512.0 {
+                    c / 16.0
+                } else {
+                    c.powf(1.8)
+                }
+            }
+        }
+    }
+}
+
+// The sRGB transfer extended to negative and above-white values, which float renders use for colors
+// outside the sRGB gamut.
+fn decode_extended_srgb(encoded: f32) -> f32 {
+    let c = encoded.abs();
+    let linear = if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) };
+    linear.copysign(encoded)
+}
+
+fn encode_extended_srgb(linear: f32) -> f32 {
+    let c = linear.abs();
+    let encoded = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    encoded.copysign(linear)
+}
+
+// Converts a rendered, sRGB encoded image to the export color space. Float renders carry the colors
+// the wider spaces can hold beyond sRGB, 8 and 16-bit images are converted as they are.
+pub fn convert_to_output_color_space(image: DynamicImage, color_space: OutputColorSpace) -> DynamicImage {
+    if color_space == OutputColorSpace::Srgb {
+        return image;
+    }
+
+    let srgb_to_target = color_space
+        .to_xyz_d50()
+        .try_inverse()
+        .map(|inv| inv * OutputColorSpace::Srgb.to_xyz_d50())
+        .unwrap_or_else(Matrix3::identity);
+
+    let source = image.into_rgba32f();
+    let (width, height) = source.dimensions();
+    let mut output: ImageBuffer<Rgba<u16>, Vec<u16>> = ImageBuffer::new(width, height);
+
+    output
+        .par_chunks_mut(4)
+        .zip(source.par_chunks(4))
+        .for_each(|(dst, src)| {
+            let linear = nalgebra::Vector3::new(
+                decode_extended_srgb(src[0]),
+                decode_extended_srgb(src[1]),
+                decode_extended_srgb(src[2]),
+            );
+            let target = srgb_to_target * linear;
+            for c in 0..3 {
+                dst[c] = (color_space.encode(target[c]) * 65535.0).round() as u16;
+            }
+            dst[3] = (src[3].clamp(0.0, 1.0) * 65535.0).round() as u16;
+        });
+
+    DynamicImage::ImageRgba16(output)
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    data.get(pos..pos + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn icc_colorants(icc: &[u8]) -> Option<Matrix3<f32>> {
+    if icc.len() < 132 || &icc[36..40] != b"acsp" {
+        return None;
+    }
+    let tag_count = read_u32(icc, 128)? as usize;
+    let mut columns = [None; 3];
+    for i in 0..tag_count {
+        let base = 132 + i * 12;
+        let column = match icc.get(base..base + 4)? {
+            b"rXYZ" => 0,
+            b"gXYZ" => 1,
+            b"bXYZ" => 2,
+            _ => continue,
+        };
+        let offset = read_u32(icc, base + 4)? as usize;
+        if icc.get(offset..offset + 4)? != b"XYZ " {
+            return None;
+        }
+        let mut xyz = [0.0f32; 3];
+        for (c, value) in xyz.iter_mut().enumerate() {
+            *value = read_u32(icc, offset + 8 + c * 4)? as i32 as f32 / 65536.0;
+        }
+        columns[column] = Some(xyz);
+    }
+    let [Some(r), Some(g), Some(b)] = columns else { return None; };
+    Some(Matrix3::new(r[0], g[0], b[0], r[1], g[1], b[1], r[2], g[2], b[2]))
+}
+
+// The standard space an embedded profile describes. Matched on the colorants, since profile
+// descriptions differ between vendors.
+pub fn identify_icc_profile(icc: &[u8]) -> Option<OutputColorSpace> {
+    let colorants = icc_colorants(icc)?;
+    [OutputColorSpace::Srgb, OutputColorSpace::AdobeRgb, OutputColorSpace::DisplayP3, OutputColorSpace::ProPhoto]
+        .into_iter()
+        .find(|space| (space.to_xyz_d50() - colorants).amax() < PROFILE_MATCH_TOLERANCE)
+}
+
+// Decoded images are treated as sRGB by the pipeline, so files tagged with another RGB profile are
+// converted on load. The conversion goes through XYZ in float and keeps colors outside sRGB as negative
+// or above-white values; the shader linearizes those into its wide working space, so AdobeRGB and P3
+// sources keep their gamut through editing and export.
+pub fn convert_from_embedded_profile(image: DynamicImage, icc: &[u8]) -> DynamicImage {
+    if identify_icc_profile(icc) == Some(OutputColorSpace::Srgb) {
+        return image;
+    }
+    let has_alpha = match &image {
+        DynamicImage::ImageRgb8(_) | DynamicImage::ImageRgb16(_) => false,
+        DynamicImage::ImageRgba8(_) | DynamicImage::ImageRgba16(_) => true,
+        _ => return image,
+    };
+    let source = match Profile::new_icc(icc) {
+        Ok(profile) if profile.color_space() == ColorSpaceSignature::RgbData => profile,
+        _ => return image,
+    };
+    let transform: Transform<[f32; 3], [f32; 3]> = match Transform::new(
+        &source,
+        PixelFormat::RGB_FLT,
+        &Profile::new_xyz(),
+        PixelFormat::XYZ_FLT,
+        Intent::RelativeColorimetric,
+    ) {
+        Ok(transform) => transform,
+        Err(e) => {
+            eprintln!("Failed to create transform for embedded profile, assuming sRGB: {}", e);
+            return image;
+        }
+    };
+
+    let xyz_to_srgb = OutputColorSpace::Srgb.to_xyz_d50().try_inverse().unwrap_or_else(Matrix3::identity);
+    let mut rgba = image.to_rgba32f();
+    let pixels: Vec<[f32; 3]> = rgba.pixels().map(|p| [p[0], p[1], p[2]]).collect();
+    let mut converted = vec![[0f32; 3]; pixels.len()];
+    transform.transform_pixels(&pixels, &mut converted);
+    rgba.par_chunks_mut(4).zip(converted.par_iter()).for_each(|(pixel, xyz)| {
+        let linear = xyz_to_srgb * nalgebra::Vector3::new(xyz[0], xyz[1], xyz[2]);
+        for c in 0..3 {
+            pixel[c] = encode_extended_srgb(linear[c]);
+        }
+    });
+
+    if has_alpha {
+        DynamicImage::ImageRgba32F(rgba)
+    } else {
+        DynamicImage::ImageRgb32F(DynamicImage::ImageRgba32F(rgba).to_rgb32f())
+    }
+}
+
+fn push_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_s15_fixed16(buf: &mut Vec<u8>, value: f32) {
+    buf.extend_from_slice(&((value * 65536.0).round() as i32).to_be_bytes());
+}
+
+fn pad_to_four(buf: &mut Vec<u8>) {
+    while buf.len() % 4 != 0 {
+        buf.push(0);
+    }
+}
+
+fn xyz_tag(xyz: [f32; 3]) -> Vec<u8> {
+    let mut tag = b"XYZ ".to_vec();
+    push_u32(&mut tag, 0);
+    xyz.iter().for_each(|v| push_s15_fixed16(&mut tag, *v));
+    tag
+}
+
+fn text_tag(text: &str) -> Vec<u8> {
+    let mut tag = b"text".to_vec();
+    push_u32(&mut tag, 0);
+    tag.extend_from_slice(text.as_bytes());
+    tag.push(0);
+    tag
+}
+
+fn description_tag(text: &str) -> Vec<u8> {
+    let mut tag = b"desc".to_vec();
+    push_u32(&mut tag, 0);
+    push_u32(&mut tag, text.len() as u32 + 1);
+    tag.extend_from_slice(text.as_bytes());
+    tag.push(0);
+    push_u32(&mut tag, 0);
+    push_u32(&mut tag, 0);
+    tag.extend_from_slice(&[0u8; 3]);
+    tag.extend_from_slice(&[0u8; 67]);
+    tag
+}
+
+fn curve_tag(color_space: OutputColorSpace) -> Vec<u8> {
+    let mut tag = b"curv".to_vec();
+    push_u32(&mut tag, 0);
+    push_u32(&mut tag, ICC_CURVE_ENTRIES as u32);
+    for i in 0..ICC_CURVE_ENTRIES {
+        let encoded = i as f32 / (ICC_CURVE_ENTRIES - 1) as f32;
+        let linear = color_space.decode(encoded);
+        tag.extend_from_slice(&((linear * 65535.0).round() as u16).to_be_bytes());
+    }
+    tag
+}
+
+pub fn build_icc_profile(color_space: OutputColorSpace) -> Vec<u8> {
+    let to_xyz = color_space.to_xyz_d50();
+    let colorant = |col: usize| [to_xyz[(0, col)], to_xyz[(1, col)], to_xyz[(2, col)]];
+    let curve = curve_tag(color_space);
+
+    let tags: Vec<(&[u8; 4], Vec<u8>)> = vec![
+        (b"desc", description_tag(color_space.name())),
+        (b"cprt", text_tag("No copyright, use freely")),
+        (b"wtpt", xyz_tag(D50_WHITE)),
+        (b"rXYZ", xyz_tag(colorant(0))),
+        (b"gXYZ", xyz_tag(colorant(1))),
+        (b"bXYZ", xyz_tag(colorant(2))),
+        (b"rTRC", curve.clone()),
+        (b"gTRC", curve.clone()),
+        (b"bTRC", curve),
+    ];
+
+    let table_size = 4 + tags.len() * 12;
+    let mut table = Vec::with_capacity(table_size);
+    let mut data = Vec::new();
+    push_u32(&mut table, tags.len() as u32);
+    for (signature, content) in &tags {
+        pad_to_four(&mut data);
+        table.extend_from_slice(&signature[..]);
+        push_u32(&mut table, (128 + table_size + data.len()) as u32);
+        push_u32(&mut table, content.len() as u32);
+        data.extend_from_slice(content);
+    }
+    pad_to_four(&mut data);
+
+    let total_size = 128 + table.len() + data.len();
+    let mut header = Vec::with_capacity(128);
+    push_u32(&mut header, total_size as u32);
+    header.extend_from_slice(&[0u8; 4]);
+    push_u32(&mut header, 0x0210_0000);
+    header.extend_from_slice(b"mntr");
+    header.extend_from_slice(b"RGB ");
+    header.extend_from_slice(b"XYZ ");
+    header.extend_from_slice(&[0u8; 12]);
+    header.extend_from_slice(b"acsp");
+    header.extend_from_slice(&[0u8; 4]);
+    push_u32(&mut header, 0);
+    header.extend_from_slice(&[0u8; 8]);
+    header.extend_from_slice(&[0u8; 8]);
+    push_u32(&mut header, 0);
+    D50_WHITE.iter().for_each(|v| push_s15_fixed16(&mut header, *v));
+    header.extend_from_slice(&[0u8; 4]);
+    header.resize(128, 0);
+
+    let mut profile = header;
+    profile.extend_from_slice(&table);
+    profile.extend_from_slice(&data);
+    profile
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum RenderingIntent {
+    #[default]
+    Perceptual,
+    RelativeColorimetric,
+    Saturation,
+    AbsoluteColorimetric,
+}
+
+impl RenderingIntent {
+    fn to_lcms(self) -> Intent {
+        match self {
+            RenderingIntent::Perceptual => Intent::Perceptual,
+            RenderingIntent::RelativeColorimetric => Intent::RelativeColorimetric,
+            RenderingIntent::Saturation => Intent::Saturation,
+            RenderingIntent::AbsoluteColorimetric => Intent::AbsoluteColorimetric,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SoftProofSettings {
+    pub profile_path: String,
+    #[serde(default)]
+    pub rendering_intent: RenderingIntent,
+    #[serde(default)]
+    pub black_point_compensation: bool,
+    #[serde(default)]
+    pub simulate_paper: bool,
+    #[serde(default)]
+    pub gamut_warning: bool,
+}
+
+const GAMUT_WARNING_COLOR: [u8; 3] = [255, 0, 128];
+const GAMUT_WARNING_THRESHOLD: i32 = 6;
+
+fn round_trip_through_profile<T: Copy + Default>(
+    srgb: &Profile,
+    printer: &Profile,
+    device_format: PixelFormat,
+    intent: Intent,
+    flags: Flags,
+    pixels: &[[u8; 3]],
+) -> Result<Vec<[u8; 3]>, String> {
+    let to_device: Transform<[u8; 3], T> = Transform::new_flags(srgb, PixelFormat::RGB_8, printer, device_format, intent, flags)
+        .map_err(|e| format!("Failed to create printer transform: {}", e))?;
+    let from_device: Transform<T, [u8; 3]> = Transform::new_flags(printer, device_format, srgb, PixelFormat::RGB_8, Intent::RelativeColorimetric, flags)
+        .map_err(|e| format!("Failed to create printer transform: {}", e))?;
+
+    let mut device_pixels = vec![T::default(); pixels.len()];
+    to_device.transform_pixels(pixels, &mut device_pixels);
+    let mut round_trip = vec![[0u8; 3]; pixels.len()];
+    from_device.transform_pixels(&device_pixels, &mut round_trip);
+    Ok(round_trip)
+}
+
+pub fn soft_proof_image(image: &DynamicImage, settings: &SoftProofSettings) -> Result<RgbImage, String> {
+    let printer = Profile::new_file(&settings.profile_path)
+        .map_err(|e| format!("Failed to load printer profile {}: {}", settings.profile_path, e))?;
+    let srgb = Profile::new_srgb();
+
+    let mut flags = Flags::SOFT_PROOFING;
+    if settings.black_point_compensation {
+        flags = flags | Flags::BLACKPOINT_COMPENSATION;
+    }
+    let proofing_intent = if settings.simulate_paper {
+        Intent::AbsoluteColorimetric
+    } else {
+        Intent::RelativeColorimetric
+    };
+
+    let proof: Transform<[u8; 3], [u8; 3]> = Transform::new_proofing(
+        &srgb,
+        PixelFormat::RGB_8,
+        &srgb,
+        PixelFormat::RGB_8,
+        &printer,
+        settings.rendering_intent.to_lcms(),
+        proofing_intent,
+        flags,
+    )
+    .map_err(|e| format!("Failed to create soft proofing transform: {}", e))?;
+
+    let source = image.to_rgb8();
+    let (width, height) = source.dimensions();
+    let pixels: Vec<[u8; 3]> = source.as_raw().chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect();
+
+    let mut proofed = vec![[0u8; 3]; pixels.len()];
+    proof.transform_pixels(&pixels, &mut proofed);
+
+    if settings.gamut_warning {
+        let check_flags = if settings.black_point_compensation { Flags::BLACKPOINT_COMPENSATION } else { Flags::default() };
+        let round_trip = match printer.color_space() {
+            ColorSpaceSignature::CmykData => round_trip_through_profile::<[u16; 4]>(
+                &srgb, &printer, PixelFormat::CMYK_16, Intent::RelativeColorimetric, check_flags, &pixels,
+            )?,
+            ColorSpaceSignature::RgbData => round_trip_through_profile::<[u16; 3]>(
+                &srgb, &printer, PixelFormat::RGB_16, Intent::RelativeColorimetric, check_flags, &pixels,
+            )?,
+            other => return Err(format!("Unsupported printer profile color space: {:?}", other)),
+        };
+
+        proofed
+            .par_iter_mut()
+            .zip(pixels.par_iter().zip(round_trip.par_iter()))
+            .for_each(|(out, (original, returned))| {
+                let out_of_gamut = (0..3).any(|c| (original[c] as i32 - returned[c] as i32).abs() > GAMUT_WARNING_THRESHOLD);
+                if out_of_gamut {
+                    *out = GAMUT_WARNING_COLOR;
+                }
+            });
+    }
+
+    let raw: Vec<u8> = proofed.into_iter().flatten().collect();
+    RgbImage::from_raw(width, height, raw).ok_or_else(|| "Failed to assemble soft proof image.".to_string())
+}
+
+// The monitor profile the editor previews are converted to. Without one, previews are sent as sRGB.
+pub struct DisplayProfile {
+    pub path: String,
+    icc: Vec<u8>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayProfileInfo {
+    pub path: String,
+    pub description: String,
+}
+
+fn is_display_profile(profile: &Profile) -> bool {
+    profile.color_space() == ColorSpaceSignature::RgbData && profile.device_class() == ProfileClassSignature::DisplayClass
+}
+
+impl DisplayProfile {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let icc = std::fs::read(path).map_err(|e| format!("Failed to read display profile {}: {}", path, e))?;
+        let profile = Profile::new_icc(&icc).map_err(|e| format!("Invalid display profile {}: {}", path, e))?;
+        if !is_display_profile(&profile) {
+            return Err(format!("{} is not an RGB display profile.", path));
+        }
+        Ok(DisplayProfile { path: path.to_string(), icc })
+    }
+
+    fn convert(&self, image: &RgbImage) -> Result<RgbImage, String> {
+        let display = Profile::new_icc(&self.icc).map_err(|e| e.to_string())?;
+        let transform: Transform<[u8; 3], [u8; 3]> = Transform::new(
+            &Profile::new_srgb(),
+            PixelFormat::RGB_8,
+            &display,
+            PixelFormat::RGB_8,
+            Intent::Perceptual,
+        )
+        .map_err(|e| format!("Failed to create display transform: {}", e))?;
+
+        let pixels: Vec<[u8; 3]> = image.as_raw().chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect();
+        let mut converted = vec![[0u8; 3]; pixels.len()];
+        transform.transform_pixels(&pixels, &mut converted);
+        let raw: Vec<u8> = converted.into_iter().flatten().collect();
+        RgbImage::from_raw(image.width(), image.height(), raw).ok_or_else(|| "Failed to assemble display image.".to_string())
+    }
+}
+
+// Rendered images are sRGB. With a display profile they are converted to it and tagged with it, so a
+// webview that color manages on its own sees matching data and doesn't convert a second time.
+pub fn encode_preview_jpeg(image: &DynamicImage, quality: u8, display: Option<&DisplayProfile>) -> Result<Vec<u8>, String> {
+    let mut buf = Cursor::new(Vec::new());
+    let mut encoder = JpegEncoder::new_with_quality(&mut buf, quality);
+    let rgb = match display {
+        Some(display) => {
+            encoder.set_icc_profile(display.icc.clone()).map_err(|e| e.to_string())?;
+            display.convert(&image.to_rgb8())?
+        }
+        None => image.to_rgb8(),
+    };
+    rgb.write_with_encoder(encoder).map_err(|e| e.to_string())?;
+    Ok(buf.into_inner())
+}
+
+fn system_profile_dirs() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    if cfg!(target_os = "windows") {
+        let windir = std::env::var_os("WINDIR").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("C:\\Windows"));
+        vec![windir.join("System32").join("spool").join("drivers").join("color")]
+    } else if cfg!(target_os = "macos") {
+        let mut dirs = vec![PathBuf::from("/Library/ColorSync/Profiles"), PathBuf::from("/System/Library/ColorSync/Profiles")];
+        dirs.extend(home.map(|h| h.join("Library/ColorSync/Profiles")));
+        dirs
+    } else {
+        let mut dirs = vec![PathBuf::from("/usr/share/color/icc"), PathBuf::from("/var/lib/colord/icc")];
+        dirs.extend(home.into_iter().flat_map(|h| [h.join(".local/share/icc"), h.join(".local/share/color/icc")]));
+        dirs
+    }
+}
+
+// Display profiles installed in the usual system locations, which is where calibration tools and the
+// OS color settings put monitor profiles.
+pub fn find_display_profiles() -> Vec<DisplayProfileInfo> {
+    let mut profiles: Vec<DisplayProfileInfo> = system_profile_dirs()
+        .iter()
+        .filter(|dir| dir.is_dir())
+        .flat_map(|dir| WalkDir::new(dir).max_depth(3).into_iter().filter_map(|e| e.ok()))
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            path.extension()
+                .map(|ext| ext.eq_ignore_ascii_case("icc") || ext.eq_ignore_ascii_case("icm"))
+                .unwrap_or(false)
+        })
+        .filter_map(|path| {
+            let profile = Profile::new_file(&path).ok().filter(is_display_profile)?;
+            let description = profile
+                .info(InfoType::Description, Locale::none())
+                .filter(|d| !d.trim().is_empty())
+                .unwrap_or_else(|| Path::new(&path).file_name().unwrap_or_default().to_string_lossy().into_owned());
+            Some(DisplayProfileInfo { path: path.to_string_lossy().into_owned(), description })
+        })
+        .collect();
+    profiles.sort_by(|a, b| a.description.to_lowercase().cmp(&b.description.to_lowercase()));
+    profiles
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum HdrPreviewMode {
+    #[default]
+    Off,
+    Pq,
+    Hlg,
+}
+
+impl HdrPreviewMode {
+    // Matches the hdr_output values in shader.wgsl.
+    pub fn shader_value(&self) -> u32 {
+        match self {
+            HdrPreviewMode::Off => 0,
+            HdrPreviewMode::Pq => 1,
+            HdrPreviewMode::Hlg => 2,
+        }
+    }
+
+    fn cicp_transfer(&self) -> Option<u8> {
+        match self {
+            HdrPreviewMode::Off => None,
+            HdrPreviewMode::Pq => Some(CICP_TRANSFER_PQ),
+            HdrPreviewMode::Hlg => Some(CICP_TRANSFER_HLG),
+        }
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+// Encodes an HDR preview as a 16-bit PNG. The cICP chunk tells the webview the pixels are Rec.2020
+// with a PQ or HLG transfer, which browsers use to show the image in HDR on HDR displays.
+pub fn encode_hdr_png(image: &ImageBuffer<Rgba<u16>, Vec<u16>>, mode: HdrPreviewMode) -> Result<Vec<u8>, String> {
+    let transfer = mode.cicp_transfer().ok_or("HDR preview is disabled.")?;
+    let rgb = DynamicImage::ImageRgba16(image.clone()).into_rgb16();
+
+    let mut buf = Cursor::new(Vec::new());
+    let encoder = PngEncoder::new_with_quality(&mut buf, CompressionType::Fast, FilterType::Sub);
+    rgb.write_with_encoder(encoder).map_err(|e| e.to_string())?;
+    let mut png = buf.into_inner();
+
+    let mut chunk_body = b"cICP".to_vec();
+    chunk_body.extend_from_slice(&[CICP_PRIMARIES_BT2020, transfer, 0, 1]);
+    let mut chunk = 4u32.to_be_bytes().to_vec();
+    chunk.extend_from_slice(&chunk_body);
+    chunk.extend_from_slice(&crc32(&chunk_body).to_be_bytes());
+
+    // The chunk has to precede the image data; right after the signature and IHDR is always valid.
+    const IHDR_END: usize = 8 + 4 + 4 + 13 + 4;
+    if png.len() < IHDR_END {
+        return Err("Failed to encode HDR preview.".to_string());
+    }
+    png.splice(IHDR_END..IHDR_END, chunk);
+    Ok(png)
+}
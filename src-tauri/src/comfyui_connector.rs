@@ -1,36 +1,251 @@
 use anyhow::{anyhow, Result};
-use futures_util::StreamExt;
-use image::{DynamicImage, ImageFormat};
+use futures_util::future::join_all;
+use futures_util::{Stream, StreamExt};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GenericImageView, ImageBuffer, ImageFormat, Luma, Rgb32FImage, Rgba32FImage};
 use reqwest::multipart;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::fmt;
+use std::future::Future;
 use std::io::Cursor;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_tungstenite::{connect_async, tungstenite::{self, protocol::Message}};
 use uuid::Uuid;
 
+use crate::file_management::AppSettings;
+use crate::mask_generation::GenerationParameters;
+
 const WORKFLOWS_DIR: &str = "./workflows";
+const UPSCALE_WORKFLOW: &str = "upscale";
+const IMAGE_TRANSFORM_WORKFLOW: &str = "image_transform";
+const UPSCALE_TILE_SIZE: u32 = 1024;
+const UPSCALE_TILE_PADDING: u32 = 32;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+const GENERATION_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const HISTORY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_ATTEMPTS: u32 = 4;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ComfyUiErrorKind {
+    Unreachable,
+    Timeout,
+    ServerError,
+    Rejected,
+    ExecutionFailed,
+    InvalidResponse,
+}
+
+// Carried inside the anyhow errors of this module so callers can report what went wrong in a structured
+// way, see error_details.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ComfyUiError {
+    pub kind: ComfyUiErrorKind,
+    pub message: String,
+    pub prompt_id: Option<String>,
+}
+
+impl ComfyUiError {
+    fn new(kind: ComfyUiErrorKind, message: impl Into<String>) -> Self {
+        ComfyUiError { kind, message: message.into(), prompt_id: None }
+    }
+
+    fn for_prompt(mut self, prompt_id: &str) -> Self {
+        self.prompt_id = Some(prompt_id.to_string());
+        self
+    }
+
+    fn is_transient(&self) -> bool {
+        matches!(self.kind, ComfyUiErrorKind::Unreachable | ComfyUiErrorKind::Timeout | ComfyUiErrorKind::ServerError)
+    }
+}
+
+impl fmt::Display for ComfyUiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ComfyUiError {}
+
+impl From<&reqwest::Error> for ComfyUiError {
+    fn from(e: &reqwest::Error) -> Self {
+        let kind = if e.is_timeout() {
+            ComfyUiErrorKind::Timeout
+        } else if e.is_connect() || e.is_request() {
+            ComfyUiErrorKind::Unreachable
+        } else if e.status().is_some_and(|s| s.is_server_error()) {
+            ComfyUiErrorKind::ServerError
+        } else {
+            ComfyUiErrorKind::InvalidResponse
+        };
+        ComfyUiError::new(kind, format!("ComfyUI request failed: {}", e))
+    }
+}
+
+pub fn error_details(error: &anyhow::Error) -> ComfyUiError {
+    if let Some(e) = error.downcast_ref::<ComfyUiError>() {
+        return e.clone();
+    }
+    if let Some(e) = error.downcast_ref::<reqwest::Error>() {
+        return e.into();
+    }
+    ComfyUiError::new(ComfyUiErrorKind::InvalidResponse, error.to_string())
+}
+
+fn http_client(timeout: Duration) -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder().connect_timeout(CONNECT_TIMEOUT).timeout(timeout).build()?)
+}
+
+async fn status_error(operation: &str, response: reqwest::Response) -> anyhow::Error {
+    let status = response.status();
+    let error_text = response.text().await.unwrap_or_else(|_| "Could not read error body".to_string());
+    let kind = if status.is_server_error() { ComfyUiErrorKind::ServerError } else { ComfyUiErrorKind::Rejected };
+    ComfyUiError::new(kind, format!("ComfyUI {} failed with status {}: {}", operation, status, error_text)).into()
+}
+
+// Retries transient failures with an exponential backoff. Requests that aren't safe to repeat, like
+// queueing a prompt, are only retried when the server couldn't be reached at all.
+async fn with_retry<T, F, Fut>(operation: &str, idempotent: bool, mut request: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match request().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                let details = error_details(&e);
+                let retryable = if idempotent { details.is_transient() } else { details.kind == ComfyUiErrorKind::Unreachable };
+                if !retryable || attempt >= MAX_ATTEMPTS {
+                    return Err(e);
+                }
+                let delay = Duration::from_millis(500 * 2u64.pow(attempt));
+                eprintln!("ComfyUI {} failed (attempt {}), retrying in {:?}: {}", operation, attempt, delay, e);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+// A ComfyUI server. Servers are tried in order, so the first reachable one is used. A workflows
+// directory replaces the built-in workflow files of the same name, which have to keep their node IDs.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ComfyUiProfile {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub address: String,
+    pub workflows_dir: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileHealth {
+    pub id: String,
+    pub name: String,
+    pub address: String,
+    pub online: bool,
+    pub latency_ms: Option<u64>,
+}
 
 pub struct WorkflowInputs {
     pub source_image_node_id: String,
     pub mask_image_node_id: Option<String>,
     pub text_prompt_node_id: Option<String>,
+    pub negative_prompt_node_id: Option<String>,
+    pub checkpoint_node_id: Option<String>,
+    pub sampler_node_id: Option<String>,
+    pub upscale_model_node_id: Option<String>,
     pub final_output_node_id: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoraSelection {
+    pub name: String,
+    #[serde(default = "default_lora_strength")]
+    pub strength: f32,
+}
+
+fn default_lora_strength() -> f32 {
+    1.0
+}
+
+// Overrides for the models and sampler settings baked into a workflow; None keeps the workflow's own
+// checkpoint.
+#[derive(Debug, Clone, Default)]
+pub struct GenerationOptions {
+    pub checkpoint: Option<String>,
+    pub loras: Vec<LoraSelection>,
+    pub parameters: GenerationParameters,
+    pub upscale_model: Option<String>,
+}
+
+// Whole-image transformations, which re-render the full frame through an img2img workflow instead of
+// inpainting a selection.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageTransform {
+    StyleTransfer,
+    Relight,
+}
+
+impl ImageTransform {
+    // Relighting has to keep the scene intact, so it changes less of the image than a new style.
+    fn default_denoise(self) -> f32 {
+        match self {
+            ImageTransform::StyleTransfer => 0.6,
+            ImageTransform::Relight => 0.4,
+        }
+    }
+
+    fn prompt(self, prompt: &str) -> String {
+        match self {
+            ImageTransform::StyleTransfer => prompt.to_string(),
+            ImageTransform::Relight => format!("same scene, {}, lighting", prompt),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailableModels {
+    pub checkpoints: Vec<String>,
+    pub loras: Vec<String>,
+    pub samplers: Vec<String>,
+    pub upscale_models: Vec<String>,
+}
+
 async fn upload_image(address: &str, image: DynamicImage, form_name: &str) -> Result<String> {
     let mut image_bytes = Cursor::new(Vec::new());
     image.write_to(&mut image_bytes, ImageFormat::Png)?;
-    
-    let part = multipart::Part::bytes(image_bytes.into_inner())
-        .file_name(format!("{}.png", Uuid::new_v4()))
+    let image_bytes = image_bytes.into_inner();
+    let file_name = format!("{}.png", Uuid::new_v4());
+    with_retry("upload", true, || upload_bytes(address, image_bytes.clone(), &file_name, form_name)).await
+}
+
+async fn upload_bytes(address: &str, bytes: Vec<u8>, file_name: &str, form_name: &str) -> Result<String> {
+    let part = multipart::Part::bytes(bytes)
+        .file_name(file_name.to_string())
         .mime_str("image/png")?;
 
     let form = multipart::Form::new()
         .part(form_name.to_string(), part)
         .text("overwrite", "true");
 
-    let client = reqwest::Client::new();
+    let client = http_client(REQUEST_TIMEOUT)?;
     let response = client
         .post(format!("http://{}/upload/image", address))
         .multipart(form)
@@ -38,9 +253,7 @@ async fn upload_image(address: &str, image: DynamicImage, form_name: &str) -> Re
         .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_else(|_| "Could not read error body".to_string());
-        return Err(anyhow!("ComfyUI upload failed with status {}: {}", status, error_text));
+        return Err(status_error("upload", response).await);
     }
 
     let response_json = response.json::<Value>().await
@@ -58,17 +271,14 @@ async fn queue_prompt(address: &str, prompt: Value, client_id: &str) -> Result<S
         "client_id": client_id,
     });
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!("http://{}/prompt", address))
-        .json(&payload)
-        .send()
-        .await?;
+    let client = http_client(REQUEST_TIMEOUT)?;
+    let response = with_retry("queue_prompt", false, || async {
+        Ok::<_, anyhow::Error>(client.post(format!("http://{}/prompt", address)).json(&payload).send().await?)
+    })
+    .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_else(|_| "Could not read error body".to_string());
-        return Err(anyhow!("ComfyUI queue_prompt failed with status {}: {}", status, error_text));
+        return Err(status_error("queue_prompt", response).await);
     }
 
     let response_json = response.json::<Value>().await
@@ -82,19 +292,17 @@ async fn queue_prompt(address: &str, prompt: Value, client_id: &str) -> Result<S
 
 async fn get_history(address: &str, prompt_id: &str) -> Result<Value> {
     let url = format!("http://{}/history/{}", address, prompt_id);
-    let response = reqwest::get(&url).await?;
+    let response = http_client(REQUEST_TIMEOUT)?.get(&url).send().await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_else(|_| "Could not read error body".to_string());
-        return Err(anyhow!("ComfyUI get_history failed with status {}: {}", status, error_text));
+        return Err(status_error("get_history", response).await);
     }
 
     Ok(response.json::<Value>().await?)
 }
 
 async fn get_image(address: &str, filename: &str, subfolder: &str, folder_type: &str) -> Result<Vec<u8>> {
-    let client = reqwest::Client::new();
+    let client = http_client(REQUEST_TIMEOUT)?;
     let response = client.get(format!("http://{}/view", address))
         .query(&[
             ("filename", filename),
@@ -105,28 +313,186 @@ async fn get_image(address: &str, filename: &str, subfolder: &str, folder_type:
         .await?;
 
     if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_else(|_| "Could not read error body".to_string());
-        return Err(anyhow!("ComfyUI get_image failed with status {}: {}", status, error_text));
+        return Err(status_error("get_image", response).await);
     }
 
     Ok(response.bytes().await?.to_vec())
 }
 
 pub async fn ping_server(address: &str) -> Result<()> {
-    reqwest::get(format!("http://{}", address)).await?.error_for_status()?;
+    http_client(PING_TIMEOUT)?.get(format!("http://{}", address)).send().await?.error_for_status()?;
+    Ok(())
+}
+
+// The address from the main setting comes first, followed by the additional server profiles.
+pub fn configured_profiles(settings: &AppSettings) -> Vec<ComfyUiProfile> {
+    let default_profile = settings.comfyui_address.clone().map(|address| ComfyUiProfile {
+        id: "default".to_string(),
+        name: "Default".to_string(),
+        address,
+        workflows_dir: None,
+    });
+    default_profile
+        .into_iter()
+        .chain(settings.comfyui_profiles.clone().unwrap_or_default())
+        .filter(|p| !p.address.trim().is_empty())
+        .collect()
+}
+
+pub async fn check_profiles(profiles: &[ComfyUiProfile]) -> Vec<ProfileHealth> {
+    join_all(profiles.iter().map(|profile| async move {
+        let started = Instant::now();
+        let online = ping_server(&profile.address).await.is_ok();
+        ProfileHealth {
+            id: profile.id.clone(),
+            name: profile.name.clone(),
+            address: profile.address.clone(),
+            online,
+            latency_ms: online.then(|| started.elapsed().as_millis() as u64),
+        }
+    }))
+    .await
+}
+
+// Picks the first reachable server, failing over to the next profile when the preferred one is offline.
+pub async fn select_profile(settings: &AppSettings) -> Result<ComfyUiProfile, String> {
+    let profiles = configured_profiles(settings);
+    if profiles.is_empty() {
+        return Err("ComfyUI address is not configured in settings.".to_string());
+    }
+    for (i, profile) in profiles.iter().enumerate() {
+        match ping_server(&profile.address).await {
+            Ok(()) => {
+                if i > 0 {
+                    println!("ComfyUI server '{}' is offline, using '{}' instead.", profiles[0].name, profile.name);
+                }
+                return Ok(profile.clone());
+            }
+            Err(e) => eprintln!("ComfyUI server '{}' ({}) is not reachable: {}", profile.name, profile.address, e),
+        }
+    }
+    Err("None of the configured ComfyUI servers is reachable.".to_string())
+}
+
+fn workflow_path(profile: &ComfyUiProfile, workflow_name: &str) -> PathBuf {
+    let file_name = format!("{}.json", workflow_name);
+    profile
+        .workflows_dir
+        .as_deref()
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| Path::new(dir).join(&file_name))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| Path::new(WORKFLOWS_DIR).join(file_name))
+}
+
+// The choices of a combo input as reported by /object_info, e.g. the checkpoint files the loader node
+// can see.
+async fn node_input_options(address: &str, node_class: &str, input_name: &str) -> Result<Vec<String>> {
+    let url = format!("http://{}/object_info/{}", address, node_class);
+    let response = http_client(REQUEST_TIMEOUT)?.get(&url).send().await?;
+    if !response.status().is_success() {
+        return Err(status_error("object_info", response).await);
+    }
+    let info = response.json::<Value>().await?;
+    Ok(info[node_class]["input"]["required"][input_name][0]
+        .as_array()
+        .map(|options| options.iter().filter_map(Value::as_str).map(String::from).collect())
+        .unwrap_or_default())
+}
+
+pub async fn list_models(address: &str) -> Result<AvailableModels> {
+    Ok(AvailableModels {
+        checkpoints: node_input_options(address, "CheckpointLoaderSimple", "ckpt_name").await?,
+        loras: node_input_options(address, "LoraLoader", "lora_name").await?,
+        samplers: node_input_options(address, "KSampler", "sampler_name").await?,
+        upscale_models: node_input_options(address, "UpscaleModelLoader", "model_name").await?,
+    })
+}
+
+fn apply_sampler_parameters(workflow: &mut Value, sampler_node_id: &str, parameters: &GenerationParameters) -> Result<()> {
+    let inputs = workflow
+        .get_mut(sampler_node_id)
+        .and_then(|node| node.get_mut("inputs"))
+        .ok_or_else(|| anyhow!("Sampler node ID '{}' not found in workflow.", sampler_node_id))?;
+    if let Some(seed) = parameters.seed {
+        inputs["seed"] = json!(seed);
+    }
+    if let Some(steps) = parameters.steps {
+        inputs["steps"] = json!(steps);
+    }
+    if let Some(cfg) = parameters.cfg {
+        inputs["cfg"] = json!(cfg);
+    }
+    if let Some(denoise) = parameters.denoise {
+        inputs["denoise"] = json!(denoise);
+    }
+    if let Some(sampler) = parameters.sampler.as_deref().filter(|s| !s.is_empty()) {
+        inputs["sampler_name"] = json!(sampler);
+    }
+    Ok(())
+}
+
+// Swaps the checkpoint and chains LoraLoader nodes behind it. Every node that consumed the
+// checkpoint's model or clip output is rewired to the last LoRA, so the rest of the graph is untouched.
+fn apply_generation_options(workflow: &mut Value, checkpoint_node_id: &str, options: &GenerationOptions) -> Result<()> {
+    let checkpoint = workflow
+        .get_mut(checkpoint_node_id)
+        .ok_or_else(|| anyhow!("Checkpoint node ID '{}' not found in workflow.", checkpoint_node_id))?;
+    if let Some(name) = options.checkpoint.as_deref().filter(|n| !n.is_empty()) {
+        checkpoint["inputs"]["ckpt_name"] = json!(name);
+    }
+    if options.loras.is_empty() {
+        return Ok(());
+    }
+
+    let mut model_source = json!([checkpoint_node_id, 0]);
+    let mut clip_source = json!([checkpoint_node_id, 1]);
+    let last_lora_id = format!("rapidraw_lora_{}", options.loras.len() - 1);
+    let (last_model, last_clip) = (json!([last_lora_id, 0]), json!([last_lora_id, 1]));
+
+    let nodes = workflow.as_object_mut().ok_or_else(|| anyhow!("Workflow is not a JSON object."))?;
+    for inputs in nodes.values_mut().filter_map(|node| node.get_mut("inputs").and_then(Value::as_object_mut)) {
+        for value in inputs.values_mut() {
+            if *value == model_source {
+                *value = last_model.clone();
+            } else if *value == clip_source {
+                *value = last_clip.clone();
+            }
+        }
+    }
+
+    for (i, lora) in options.loras.iter().enumerate() {
+        let node_id = format!("rapidraw_lora_{}", i);
+        nodes.insert(
+            node_id.clone(),
+            json!({
+                "class_type": "LoraLoader",
+                "inputs": {
+                    "lora_name": lora.name,
+                    "strength_model": lora.strength,
+                    "strength_clip": lora.strength,
+                    "model": model_source,
+                    "clip": clip_source,
+                },
+            }),
+        );
+        model_source = json!([node_id, 0]);
+        clip_source = json!([node_id, 1]);
+    }
     Ok(())
 }
 
 pub async fn execute_workflow(
-    address: &str,
+    profile: &ComfyUiProfile,
     workflow_name: &str,
     inputs: WorkflowInputs,
     source_image: DynamicImage,
     mask_image: Option<DynamicImage>,
     text_prompt: Option<String>,
+    options: &GenerationOptions,
 ) -> Result<Vec<u8>> {
-    let workflow_path = Path::new(WORKFLOWS_DIR).join(format!("{}.json", workflow_name));
+    let address = profile.address.as_str();
+    let workflow_path = workflow_path(profile, workflow_name);
     let workflow_str = fs::read_to_string(&workflow_path)
         .map_err(|e| anyhow!("Failed to read workflow file at {:?}: {}", workflow_path, e))?;
     let mut workflow: Value = serde_json::from_str(&workflow_str)?;
@@ -157,34 +523,48 @@ pub async fn execute_workflow(
         }
     }
 
+    if let Some(checkpoint_node_id) = &inputs.checkpoint_node_id {
+        apply_generation_options(&mut workflow, checkpoint_node_id, options)?;
+    }
+    if let Some(sampler_node_id) = &inputs.sampler_node_id {
+        apply_sampler_parameters(&mut workflow, sampler_node_id, &options.parameters)?;
+    }
+    if let (Some(model), Some(node_id)) = (options.upscale_model.as_deref().filter(|m| !m.is_empty()), &inputs.upscale_model_node_id) {
+        let node = workflow
+            .get_mut(node_id)
+            .ok_or_else(|| anyhow!("Upscale model node ID '{}' not found in workflow.", node_id))?;
+        node["inputs"]["model_name"] = json!(model);
+    }
+    let negative_prompt = options.parameters.negative_prompt.trim();
+    if let (false, Some(node_id)) = (negative_prompt.is_empty(), &inputs.negative_prompt_node_id) {
+        let node = workflow
+            .get_mut(node_id)
+            .ok_or_else(|| anyhow!("Negative prompt node ID '{}' not found in workflow.", node_id))?;
+        node["inputs"]["text"] = json!(negative_prompt);
+    }
+
+    // The websocket only speeds up noticing that the job finished; without it the history is polled.
     let client_id = Uuid::new_v4().to_string();
     let ws_url = format!("ws://{}/ws?clientId={}", address, client_id);
-    let (ws_stream, _) = connect_async(&ws_url).await.map_err(|e| anyhow!("Failed to connect to WebSocket at {}: {}", ws_url, e))?;
-    let (_write, mut read) = ws_stream.split();
+    let ws_read = match tokio::time::timeout(CONNECT_TIMEOUT, connect_async(&ws_url)).await {
+        Ok(Ok((ws_stream, _))) => Some(ws_stream.split().1),
+        Ok(Err(e)) => {
+            eprintln!("Failed to connect to ComfyUI WebSocket at {}, polling instead: {}", ws_url, e);
+            None
+        }
+        Err(_) => {
+            eprintln!("Timed out connecting to ComfyUI WebSocket at {}, polling instead", ws_url);
+            None
+        }
+    };
 
     let prompt_id = queue_prompt(address, workflow, &client_id).await?;
-
-    loop {
-        match read.next().await {
-            Some(Ok(msg)) => {
-                if let Message::Text(text) = msg {
-                    if let Ok(v) = serde_json::from_str::<Value>(&text) {
-                        if v["type"] == "executing" && v["data"]["node"].is_null() && v["data"]["prompt_id"] == prompt_id {
-                            break;
-                        }
-                    }
-                }
-            }
-            Some(Err(e)) => return Err(anyhow!("WebSocket error: {}", e)),
-            None => return Err(anyhow!("WebSocket stream ended unexpectedly")),
-        }
+    let deadline = Instant::now() + GENERATION_TIMEOUT;
+    if let Some(mut read) = ws_read {
+        wait_for_websocket(&mut read, &prompt_id, deadline).await?;
     }
+    let outputs = wait_for_history(address, &prompt_id, deadline).await?;
 
-    let history = get_history(address, &prompt_id).await?;
-    let outputs = history.get(&prompt_id)
-        .and_then(|h| h.get("outputs"))
-        .ok_or_else(|| anyhow!("Could not find outputs for prompt_id {} in history", prompt_id))?;
-    
     let images = outputs.get(&inputs.final_output_node_id)
         .and_then(|n| n.get("images"))
         .and_then(|i| i.as_array())
@@ -199,5 +579,191 @@ pub async fn execute_workflow(
     let subfolder = first_image_info.get("subfolder").and_then(|s| s.as_str()).unwrap_or("");
     let folder_type = first_image_info.get("type").and_then(|t| t.as_str()).ok_or_else(|| anyhow!("Could not get type from output"))?;
 
-    get_image(address, final_filename, subfolder, folder_type).await
-}
\ No newline at end of file
+    with_retry("get_image", true, || get_image(address, final_filename, subfolder, folder_type)).await
+}
+
+fn execution_error_message(data: &Value) -> String {
+    let node = data["node_type"].as_str().unwrap_or("unknown node");
+    let message = data["exception_message"].as_str().unwrap_or("unknown error").trim();
+    format!("ComfyUI workflow failed in {}: {}", node, message)
+}
+
+// Returns once the server reports the prompt as done. A dropped connection isn't fatal, the job keeps
+// running on the server and wait_for_history picks up its result.
+async fn wait_for_websocket<S>(read: &mut S, prompt_id: &str, deadline: Instant) -> Result<()>
+where
+    S: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+{
+    loop {
+        let message = match tokio::time::timeout_at(deadline, read.next()).await {
+            Ok(message) => message,
+            Err(_) => {
+                return Err(ComfyUiError::new(ComfyUiErrorKind::Timeout, "ComfyUI generation timed out.").for_prompt(prompt_id).into());
+            }
+        };
+        match message {
+            Some(Ok(Message::Text(text))) => {
+                let Ok(v) = serde_json::from_str::<Value>(&text) else { continue; };
+                if v["data"]["prompt_id"] != prompt_id {
+                    continue;
+                }
+                if v["type"] == "executing" && v["data"]["node"].is_null() {
+                    return Ok(());
+                }
+                if v["type"] == "execution_error" {
+                    let message = execution_error_message(&v["data"]);
+                    return Err(ComfyUiError::new(ComfyUiErrorKind::ExecutionFailed, message).for_prompt(prompt_id).into());
+                }
+            }
+            Some(Ok(_)) => {}
+            Some(Err(e)) => {
+                eprintln!("ComfyUI WebSocket error, polling history for {}: {}", prompt_id, e);
+                return Ok(());
+            }
+            None => {
+                eprintln!("ComfyUI WebSocket closed, polling history for {}", prompt_id);
+                return Ok(());
+            }
+        }
+    }
+}
+
+// Polls the history until the prompt shows up as finished and returns its outputs.
+async fn wait_for_history(address: &str, prompt_id: &str, deadline: Instant) -> Result<Value> {
+    loop {
+        let history = with_retry("get_history", true, || get_history(address, prompt_id))
+            .await
+            .map_err(|e| anyhow::Error::from(error_details(&e).for_prompt(prompt_id)))?;
+        if let Some(entry) = history.get(prompt_id) {
+            let status = &entry["status"];
+            if status["status_str"] == "error" {
+                let message = status["messages"]
+                    .as_array()
+                    .and_then(|messages| messages.iter().find(|m| m[0] == "execution_error"))
+                    .map(|m| execution_error_message(&m[1]))
+                    .unwrap_or_else(|| "ComfyUI workflow failed.".to_string());
+                return Err(ComfyUiError::new(ComfyUiErrorKind::ExecutionFailed, message).for_prompt(prompt_id).into());
+            }
+            if status["completed"].as_bool().unwrap_or(true) {
+                if let Some(outputs) = entry.get("outputs") {
+                    return Ok(outputs.clone());
+                }
+            }
+        }
+        if Instant::now() >= deadline {
+            return Err(ComfyUiError::new(ComfyUiErrorKind::Timeout, "ComfyUI generation timed out.").for_prompt(prompt_id).into());
+        }
+        tokio::time::sleep(HISTORY_POLL_INTERVAL).await;
+    }
+}
+
+// Upscales in padded tiles so large exports don't have to fit into the server's memory at once. Each
+// tile goes through the upscale workflow and is resized to the exact target size, since the model's own
+// factor may differ from the requested one. Tiles are reassembled in float and the result gets the
+// source's color type back, with its alpha upscaled locally since the workflow only sees RGB.
+pub async fn upscale_image(profile: &ComfyUiProfile, image: &DynamicImage, scale: u32, model: Option<&str>) -> Result<DynamicImage> {
+    let source = image.to_rgb8();
+    let (width, height) = source.dimensions();
+    let mut result = Rgb32FImage::new(width * scale, height * scale);
+    let options = GenerationOptions { upscale_model: model.map(String::from), ..Default::default() };
+
+    for tile_y in (0..height).step_by(UPSCALE_TILE_SIZE as usize) {
+        for tile_x in (0..width).step_by(UPSCALE_TILE_SIZE as usize) {
+            let tile_w = UPSCALE_TILE_SIZE.min(width - tile_x);
+            let tile_h = UPSCALE_TILE_SIZE.min(height - tile_y);
+            let input_x = tile_x.saturating_sub(UPSCALE_TILE_PADDING);
+            let input_y = tile_y.saturating_sub(UPSCALE_TILE_PADDING);
+            let input_w = (tile_x + tile_w + UPSCALE_TILE_PADDING).min(width) - input_x;
+            let input_h = (tile_y + tile_h + UPSCALE_TILE_PADDING).min(height) - input_y;
+
+            let tile = imageops::crop_imm(&source, input_x, input_y, input_w, input_h).to_image();
+            let inputs = WorkflowInputs {
+                source_image_node_id: "1".to_string(),
+                mask_image_node_id: None,
+                text_prompt_node_id: None,
+                negative_prompt_node_id: None,
+                checkpoint_node_id: None,
+                sampler_node_id: None,
+                upscale_model_node_id: Some("2".to_string()),
+                final_output_node_id: "4".to_string(),
+            };
+            let bytes = execute_workflow(profile, UPSCALE_WORKFLOW, inputs, DynamicImage::ImageRgb8(tile), None, None, &options).await?;
+            let upscaled = image::load_from_memory(&bytes)?
+                .resize_exact(input_w * scale, input_h * scale, FilterType::Lanczos3)
+                .to_rgb32f();
+
+            let offset_x = (tile_x - input_x) * scale;
+            let offset_y = (tile_y - input_y) * scale;
+            let center = imageops::crop_imm(&upscaled, offset_x, offset_y, tile_w * scale, tile_h * scale).to_image();
+            imageops::replace(&mut result, &center, (tile_x * scale) as i64, (tile_y * scale) as i64);
+        }
+    }
+
+    Ok(match_color_type(result, image))
+}
+
+fn match_color_type(upscaled: Rgb32FImage, source: &DynamicImage) -> DynamicImage {
+    if !source.color().has_alpha() {
+        return match source {
+            DynamicImage::ImageRgb8(_) => DynamicImage::ImageRgb8(DynamicImage::ImageRgb32F(upscaled).to_rgb8()),
+            DynamicImage::ImageRgb16(_) => DynamicImage::ImageRgb16(DynamicImage::ImageRgb32F(upscaled).to_rgb16()),
+            _ => DynamicImage::ImageRgb32F(upscaled),
+        };
+    }
+
+    let (width, height) = upscaled.dimensions();
+    let source_alpha = source.to_luma_alpha32f();
+    let source_alpha = ImageBuffer::<Luma<f32>, Vec<f32>>::from_fn(source.width(), source.height(), |x, y| {
+        Luma([source_alpha.get_pixel(x, y).0[1]])
+    });
+    let alpha = imageops::resize(&source_alpha, width, height, FilterType::Lanczos3);
+    let rgba = Rgba32FImage::from_fn(width, height, |x, y| {
+        let [r, g, b] = upscaled.get_pixel(x, y).0;
+        image::Rgba([r, g, b, alpha.get_pixel(x, y).0[0].clamp(0.0, 1.0)])
+    });
+    match source {
+        DynamicImage::ImageRgba8(_) | DynamicImage::ImageLumaA8(_) => DynamicImage::ImageRgba8(DynamicImage::ImageRgba32F(rgba).to_rgba8()),
+        DynamicImage::ImageRgba16(_) | DynamicImage::ImageLumaA16(_) => DynamicImage::ImageRgba16(DynamicImage::ImageRgba32F(rgba).to_rgba16()),
+        _ => DynamicImage::ImageRgba32F(rgba),
+    }
+}
+
+// The workflow renders at about one megapixel; the result is scaled back to the source size so it can
+// be stored as a full-frame patch.
+pub async fn transform_image(
+    profile: &ComfyUiProfile,
+    image: DynamicImage,
+    transform: ImageTransform,
+    prompt: &str,
+    options: &GenerationOptions,
+) -> Result<DynamicImage> {
+    let (width, height) = image.dimensions();
+    let mut options = options.clone();
+    options.parameters.denoise = options.parameters.denoise.or(Some(transform.default_denoise()));
+    let inputs = WorkflowInputs {
+        source_image_node_id: "1".to_string(),
+        mask_image_node_id: None,
+        text_prompt_node_id: Some("6".to_string()),
+        negative_prompt_node_id: Some("7".to_string()),
+        checkpoint_node_id: Some("4".to_string()),
+        sampler_node_id: Some("3".to_string()),
+        upscale_model_node_id: None,
+        final_output_node_id: "9".to_string(),
+    };
+    let bytes = execute_workflow(
+        profile,
+        IMAGE_TRANSFORM_WORKFLOW,
+        inputs,
+        image,
+        None,
+        Some(transform.prompt(prompt)),
+        &options,
+    )
+    .await?;
+    Ok(image::load_from_memory(&bytes)?.resize_exact(width, height, FilterType::Lanczos3))
+}
+
+// For the export workers, which run outside the async runtime.
+pub fn upscale_image_blocking(profile: &ComfyUiProfile, image: &DynamicImage, scale: u32, model: Option<&str>) -> Result<DynamicImage> {
+    tokio::task::block_in_place(|| tauri::async_runtime::block_on(upscale_image(profile, image, scale, model)))
+}
@@ -0,0 +1,156 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use image::DynamicImage;
+use ort::Session;
+use rayon::prelude::*;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::ai_processing::{are_eyes_closed, get_or_init_eye_state_model, get_or_init_face_detector, run_face_detector};
+use crate::file_management::set_flag_for_paths;
+use crate::formats::is_video_file;
+use crate::image_loader::load_base_image_from_bytes;
+use crate::image_processing::ImageFlag;
+use crate::AppState;
+
+// Faces in group shots are too small in the 640px thumbnail for the eye-state model.
+const CULLING_ANALYSIS_DIM: u32 = 2048;
+const SHARPNESS_ANALYSIS_DIM: u32 = 1024;
+const BLUR_REJECT_RATIO: f32 = 0.35;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CullingResult {
+    pub path: String,
+    pub sharpness: f32,
+    pub face_count: usize,
+    pub closed_eye_faces: usize,
+    pub is_blurry: bool,
+    pub likely_reject: bool,
+}
+
+fn laplacian_variance(image: &DynamicImage) -> f32 {
+    let gray = image.thumbnail(SHARPNESS_ANALYSIS_DIM, SHARPNESS_ANALYSIS_DIM).to_luma8();
+    let (width, height) = gray.dimensions();
+    if width < 3 || height < 3 {
+        return 0.0;
+    }
+
+    let at = |x: u32, y: u32| gray.get_pixel(x, y)[0] as f32;
+    let mut sum = 0.0f64;
+    let mut sum_sq = 0.0f64;
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let laplacian = at(x - 1, y) + at(x + 1, y) + at(x, y - 1) + at(x, y + 1) - 4.0 * at(x, y);
+            sum += laplacian as f64;
+            sum_sq += (laplacian * laplacian) as f64;
+        }
+    }
+
+    let count = ((width - 2) * (height - 2)) as f64;
+    let mean = sum / count;
+    (sum_sq / count - mean * mean) as f32
+}
+
+fn analyze_image(
+    path: &str,
+    face_detector: &Session,
+    eye_state_model: &Session,
+) -> Result<CullingResult, String> {
+    let file_bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let image = load_base_image_from_bytes(&file_bytes, path, true)
+        .map_err(|e| e.to_string())?
+        .thumbnail(CULLING_ANALYSIS_DIM, CULLING_ANALYSIS_DIM);
+    let faces = run_face_detector(&image, face_detector).map_err(|e| e.to_string())?;
+
+    let mut closed_eye_faces = 0;
+    for face in &faces {
+        if are_eyes_closed(&image, face, eye_state_model).map_err(|e| e.to_string())? {
+            closed_eye_faces += 1;
+        }
+    }
+
+    Ok(CullingResult {
+        path: path.to_string(),
+        sharpness: laplacian_variance(&image),
+        face_count: faces.len(),
+        closed_eye_faces,
+        is_blurry: false,
+        likely_reject: false,
+    })
+}
+
+fn mark_rejects(results: &mut [CullingResult]) {
+    let mut sharpness: Vec<f32> = results.iter().map(|r| r.sharpness).collect();
+    sharpness.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let median = sharpness.get(sharpness.len() / 2).copied().unwrap_or(0.0);
+
+    for result in results.iter_mut() {
+        result.is_blurry = result.sharpness < median * BLUR_REJECT_RATIO;
+        let eyes_closed = result.face_count > 0 && result.closed_eye_faces == result.face_count;
+        result.likely_reject = result.is_blurry || eyes_closed;
+    }
+}
+
+#[tauri::command]
+pub async fn analyze_for_culling(
+    paths: Vec<String>,
+    flag_rejects: bool,
+    state: tauri::State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let paths: Vec<String> = paths.into_iter().filter(|path| !is_video_file(path)).collect();
+    let face_detector = get_or_init_face_detector(&app_handle, &state.face_detector, &state.ai_init_lock)
+        .await
+        .map_err(|e| e.to_string())?;
+    let eye_state_model = get_or_init_eye_state_model(&app_handle, &state.eye_state_model, &state.ai_init_lock)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tokio::spawn(async move {
+        let worker_handle = app_handle.clone();
+        let analysis = tokio::task::spawn_blocking(move || {
+            let app_handle = worker_handle;
+            let total = paths.len();
+            let completed = AtomicUsize::new(0);
+
+            let mut results: Vec<CullingResult> = paths
+                .par_iter()
+                .filter_map(|path| {
+                    let result = analyze_image(path, &face_detector, &eye_state_model);
+                    let current = completed.fetch_add(1, Ordering::SeqCst) + 1;
+                    let _ = app_handle.emit("culling-progress", serde_json::json!({ "current": current, "total": total, "path": path }));
+                    match result {
+                        Ok(result) => Some(result),
+                        Err(e) => {
+                            eprintln!("Failed to analyze {} for culling: {}", path, e);
+                            None
+                        }
+                    }
+                })
+                .collect();
+
+            mark_rejects(&mut results);
+
+            if flag_rejects {
+                let rejects: Vec<String> = results.iter().filter(|r| r.likely_reject).map(|r| r.path.clone()).collect();
+                if !rejects.is_empty() {
+                    let _ = set_flag_for_paths(rejects, Some(ImageFlag::Reject), app_handle.clone());
+                }
+            }
+            results
+        })
+        .await;
+
+        match analysis {
+            Ok(results) => {
+                let _ = app_handle.emit("culling-finished", results);
+            }
+            Err(e) => {
+                let _ = app_handle.emit("culling-error", e.to_string());
+            }
+        }
+    });
+
+    Ok(())
+}
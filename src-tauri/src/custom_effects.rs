@@ -0,0 +1,86 @@
+use tauri::{AppHandle, Manager};
+
+use crate::file_management::AppSettings;
+use crate::gpu_processing::{adjustments_shader_source, check_shader_compiles, get_or_init_gpu_context, PipelineKind};
+use crate::AppState;
+
+const MAX_SOURCE_LEN: usize = 16 * 1024;
+// Loops could hang the GPU; attributes, which would allow bindings and entry points, are rejected
+// separately.
+const FORBIDDEN_KEYWORDS: [&str; 5] = ["loop", "while", "for", "enable", "requires"];
+
+// A snippet has to define `fn custom_effect(color: vec3<f32>, uv: vec2<f32>) -> vec3<f32>` and may add
+// helper functions and constants. It only sees its arguments: the host shader's textures and
+// uniforms are out of reach because the snippet has to compile on its own first.
+fn check_source(source: &str) -> Result<(), String> {
+    if source.len() > MAX_SOURCE_LEN {
+        return Err(format!("The effect is longer than {} characters.", MAX_SOURCE_LEN));
+    }
+    // Block comments could hide code from the line comment stripping below.
+    if source.contains("/*") {
+        return Err("Use // comments in custom effects.".to_string());
+    }
+    let code = source.lines().map(|line| line.split("//").next().unwrap_or_default()).collect::<Vec<_>>().join("\n");
+    if code.contains('@') {
+        return Err("Attributes (@...) aren't allowed in custom effects.".to_string());
+    }
+    if let Some(keyword) = code
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .find(|word| FORBIDDEN_KEYWORDS.contains(word))
+    {
+        return Err(format!("'{}' isn't allowed in custom effects.", keyword));
+    }
+    if !code.contains("fn custom_effect(") {
+        return Err("The effect has to define fn custom_effect(color: vec3<f32>, uv: vec2<f32>) -> vec3<f32>.".to_string());
+    }
+    Ok(())
+}
+
+fn sandbox_source(source: &str) -> String {
+    format!(
+        "{}\n@compute @workgroup_size(1)\nfn main() {{\n    _ = custom_effect(vec3<f32>(0.5), vec2<f32>(0.5));\n}}\n",
+        source
+    )
+}
+
+#[tauri::command]
+pub fn validate_custom_effect(source: String, state: tauri::State<AppState>) -> Result<(), String> {
+    check_source(&source)?;
+    let context = get_or_init_gpu_context(&state)?;
+    check_shader_compiles(&context.device, &sandbox_source(&source))?;
+    check_shader_compiles(
+        &context.device,
+        &adjustments_shader_source(wgpu::TextureFormat::Rgba8Unorm, Some(&source)),
+    )
+}
+
+// Swaps the effect compiled into the adjustments pipelines. Snippets that fail the checks are
+// dropped; one that passes them but doesn't compile falls back to the built-in shader.
+pub fn apply_custom_effect(app_handle: &AppHandle, settings: &AppSettings) {
+    let source = settings
+        .custom_effect_shader
+        .clone()
+        .filter(|s| !s.trim().is_empty())
+        .filter(|s| match check_source(s) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Custom effect ignored: {}", e);
+                false
+            }
+        });
+
+    let state = app_handle.state::<AppState>();
+    {
+        let mut current = state.custom_effect.lock().unwrap();
+        if *current == source {
+            return;
+        }
+        *current = source;
+    }
+    if let Some(context) = state.gpu_context.lock().unwrap().as_ref() {
+        let mut pipelines = context.pipelines.lock().unwrap();
+        pipelines.remove(&PipelineKind::Adjustments);
+        pipelines.remove(&PipelineKind::AdjustmentsHdr);
+        pipelines.remove(&PipelineKind::AdjustmentsFloat);
+    }
+}
@@ -0,0 +1,106 @@
+struct DenoiseParams {
+    luma_strength: f32,
+    chroma_strength: f32,
+    detail: f32,
+    level: u32,
+    last_level: u32,
+    offset_x: u32,
+    offset_y: u32,
+    _pad1: u32,
+}
+
+// One level of an edge-aware a-trous wavelet decomposition. Level 0 reads the sRGB tile, later levels
+// the previous approximation in YCbCr. Every level adds its soft-thresholded detail band to the running
+// sum; the last level reconstructs the denoised tile and writes it back as sRGB.
+@group(0) @binding(0) var approx_in: texture_2d<f32>;
+@group(0) @binding(1) var sum_in: texture_2d<f32>;
+@group(0) @binding(2) var approx_out: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(3) var sum_out: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(4) var<uniform> params: DenoiseParams;
+
+const KERNEL = array<f32, 5>(0.0625, 0.25, 0.375, 0.25, 0.0625);
+// Thresholds of the coarser bands relative to the finest one. High ISO chroma noise is blotchy, so
+// the coarse chroma bands are cleaned much harder than the luma ones.
+const LUMA_BAND_WEIGHTS = array<f32, 5>(1.0, 0.5, 0.3, 0.18, 0.1);
+const CHROMA_BAND_WEIGHTS = array<f32, 5>(1.0, 0.85, 0.7, 0.55, 0.4);
+const MAX_LUMA_THRESHOLD: f32 = 0.06;
+const MAX_CHROMA_THRESHOLD: f32 = 0.08;
+
+fn rgb_to_ycbcr(c: vec3<f32>) -> vec3<f32> {
+    let y = dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+    return vec3<f32>(y, (c.b - y) / 1.8556, (c.r - y) / 1.5748);
+}
+
+fn ycbcr_to_rgb(c: vec3<f32>) -> vec3<f32> {
+    let r = c.x + 1.5748 * c.z;
+    let b = c.x + 1.8556 * c.y;
+    let g = (c.x - 0.2126 * r - 0.0722 * b) / 0.7152;
+    return vec3<f32>(r, g, b);
+}
+
+fn load_level(coords: vec2<i32>) -> vec3<f32> {
+    let color = textureLoad(approx_in, coords, 0).rgb;
+    if (params.level == 0u) {
+        return rgb_to_ycbcr(color);
+    }
+    return color;
+}
+
+fn soft_threshold(value: f32, threshold: f32) -> f32 {
+    return sign(value) * max(abs(value) - threshold, 0.0);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let out_dims = textureDimensions(approx_out);
+    if (id.x >= out_dims.x || id.y >= out_dims.y) {
+        return;
+    }
+
+    let coords = vec2<i32>(id.xy + vec2<u32>(params.offset_x, params.offset_y));
+    let max_coords = vec2<i32>(textureDimensions(approx_in) - 1u);
+    let spacing = i32(1u << params.level);
+    let center = load_level(coords);
+    let alpha = textureLoad(approx_in, coords, 0).a;
+
+    // Luma edges stop the smoothing, so they end up in the detail band instead of being blurred into
+    // the approximation. A higher detail setting narrows the range of what counts as noise.
+    let range_sigma = mix(0.2, 0.03, params.detail) * max(params.luma_strength, 0.1);
+    var kernel = KERNEL;
+    var approx = vec3<f32>(0.0);
+    var total_weight = 0.0;
+    for (var y = 0; y < 5; y = y + 1) {
+        for (var x = 0; x < 5; x = x + 1) {
+            let sample_coords = clamp(coords + vec2<i32>(x - 2, y - 2) * spacing, vec2<i32>(0), max_coords);
+            let neighbour = load_level(sample_coords);
+            let luma_diff = neighbour.x - center.x;
+            let weight = kernel[x] * kernel[y] * exp(-(luma_diff * luma_diff) / (2.0 * range_sigma * range_sigma));
+            approx += neighbour * weight;
+            total_weight += weight;
+        }
+    }
+    approx /= max(total_weight, 0.0001);
+
+    let band = min(params.level, 4u);
+    var luma_weights = LUMA_BAND_WEIGHTS;
+    var chroma_weights = CHROMA_BAND_WEIGHTS;
+    let luma_threshold = params.luma_strength * MAX_LUMA_THRESHOLD * luma_weights[band] * (1.0 - 0.6 * params.detail);
+    let chroma_threshold = params.chroma_strength * MAX_CHROMA_THRESHOLD * chroma_weights[band];
+    let detail = center - approx;
+    var sum = vec3<f32>(
+        soft_threshold(detail.x, luma_threshold),
+        soft_threshold(detail.y, chroma_threshold),
+        soft_threshold(detail.z, chroma_threshold),
+    );
+    if (params.level > 0u) {
+        sum += textureLoad(sum_in, coords, 0).rgb;
+    }
+
+    if (params.last_level == 1u) {
+        let rgb = max(ycbcr_to_rgb(approx + sum), vec3<f32>(0.0));
+        textureStore(approx_out, vec2<i32>(id.xy), vec4<f32>(rgb, alpha));
+        return;
+    }
+    textureStore(approx_out, vec2<i32>(id.xy), vec4<f32>(approx, alpha));
+    textureStore(sum_out, vec2<i32>(id.xy), vec4<f32>(sum, 0.0));
+}
@@ -0,0 +1,352 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::file_management::get_sidecar_path;
+use crate::formats::is_raw_file;
+use crate::image_loader::base_image_dimensions_from_bytes;
+use crate::iptc_metadata::read_metadata;
+use crate::raw_processing::{white_balance_from_kelvin, white_balance_from_multipliers};
+use crate::xmp_sidecar::{element_block, find_xmp_sidecar, images_under, read_property};
+
+// Modules darktable enables on its own for every raw; they have no RapidRAW counterpart to convert to
+// but aren't user edits either.
+const DARKTABLE_PIPELINE_OPS: [&str; 11] = [
+    "rawprepare",
+    "demosaic",
+    "colorin",
+    "colorout",
+    "gamma",
+    "highlights",
+    "dither",
+    "flip",
+    "channelmixerrgb",
+    "mask_manager",
+    "hotpixels",
+];
+
+// Capture One settings that are commonly edited but have no converter yet.
+const CAPTURE_ONE_UNCONVERTED: [&str; 7] = ["Clarity", "Structure", "Vignetting", "Curve", "ColorBalance", "Keystone", "Rotation"];
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DevelopSource {
+    Darktable,
+    CaptureOne,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnconvertedEdits {
+    pub path: String,
+    pub operations: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DevelopImportSummary {
+    pub updated: usize,
+    pub skipped: usize,
+    pub unconverted: Vec<UnconvertedEdits>,
+}
+
+// Crop edges as fractions of the image, so they can be scaled once the image size is known.
+#[derive(Debug, Clone, Copy)]
+struct RelativeCrop {
+    left: f64,
+    top: f64,
+    right: f64,
+    bottom: f64,
+}
+
+enum WhiteBalance {
+    Kelvin(f32),
+    Multipliers([f32; 3]),
+}
+
+#[derive(Default)]
+struct ConvertedEdits {
+    adjustments: Map<String, Value>,
+    rating: Option<u8>,
+    crop: Option<RelativeCrop>,
+    white_balance: Option<WhiteBalance>,
+    unconverted: Vec<String>,
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
+
+fn read_f32(params: &[u8], offset: usize) -> Option<f32> {
+    params.get(offset..offset + 4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+// darktable keeps the edit history as rdf:li entries carrying the module name, version and its
+// parameter struct as hex. Only entries below history_end are active; later entries win.
+fn convert_darktable(xml: &str) -> ConvertedEdits {
+    let mut edits = ConvertedEdits::default();
+    edits.rating = read_property(xml, "xmp:Rating")
+        .and_then(|r| r.parse::<i32>().ok())
+        .filter(|r| (1..=5).contains(r))
+        .map(|r| r as u8);
+
+    let Some(history) = element_block(xml, "darktable:history") else { return edits; };
+    let history_end: usize = read_property(xml, "darktable:history_end").and_then(|h| h.parse().ok()).unwrap_or(usize::MAX);
+    let mut entries: Vec<(String, String, &str)> = Vec::new();
+    for entry in history
+        .split("<rdf:li")
+        .skip(1)
+        .map(|entry| entry.split('>').next().unwrap_or_default())
+        .filter(|entry| entry.contains("darktable:operation="))
+        .take(history_end)
+    {
+        let Some(operation) = read_property(entry, "darktable:operation") else { continue; };
+        let instance = read_property(entry, "darktable:multi_priority").unwrap_or_default();
+        entries.retain(|(op, inst, _)| !(op == &operation && inst == &instance));
+        entries.push((operation, instance, entry));
+    }
+
+    for (operation, _, entry) in entries {
+        if read_property(entry, "darktable:enabled").as_deref() == Some("0") || DARKTABLE_PIPELINE_OPS.contains(&operation.as_str()) {
+            continue;
+        }
+        let version: u32 = read_property(entry, "darktable:modversion").and_then(|v| v.parse().ok()).unwrap_or(0);
+        // Large parameter blobs are stored gzip compressed with a "gz" prefix; those fail to decode and
+        // end up reported as unconverted.
+        let params = read_property(entry, "darktable:params").and_then(|p| decode_hex(&p));
+
+        let converted = params.as_deref().and_then(|params| match operation.as_str() {
+            "exposure" => {
+                let offset = if version >= 3 { 8 } else { 4 };
+                read_f32(params, offset).map(|ev| {
+                    edits.adjustments.insert("exposure".to_string(), json!(ev));
+                })
+            }
+            "crop" => read_f32(params, 12).map(|_| {
+                edits.crop = Some(RelativeCrop {
+                    left: read_f32(params, 0).unwrap_or(0.0) as f64,
+                    top: read_f32(params, 4).unwrap_or(0.0) as f64,
+                    right: read_f32(params, 8).unwrap_or(1.0) as f64,
+                    bottom: read_f32(params, 12).unwrap_or(1.0) as f64,
+                });
+            }),
+            // The legacy clipping module stores the angle first; negative edges mark a flip.
+            "clipping" => read_f32(params, 16).map(|_| {
+                edits.adjustments.insert("rotation".to_string(), json!(read_f32(params, 0).unwrap_or(0.0)));
+                edits.crop = Some(RelativeCrop {
+                    left: read_f32(params, 4).unwrap_or(0.0) as f64,
+                    top: read_f32(params, 8).unwrap_or(0.0) as f64,
+                    right: read_f32(params, 12).unwrap_or(1.0).abs() as f64,
+                    bottom: read_f32(params, 16).unwrap_or(1.0).abs() as f64,
+                });
+            }),
+            "temperature" => match (read_f32(params, 0), read_f32(params, 4), read_f32(params, 8)) {
+                (Some(r), Some(g), Some(b)) => {
+                    edits.white_balance = Some(WhiteBalance::Multipliers([r, g, b]));
+                    Some(())
+                }
+                _ => None,
+            },
+            _ => None,
+        });
+        if converted.is_none() && !edits.unconverted.contains(&operation) {
+            edits.unconverted.push(operation);
+        }
+    }
+    edits
+}
+
+fn capture_one_settings(xml: &str) -> Vec<(String, String)> {
+    xml.split("<E ")
+        .skip(1)
+        .filter_map(|entry| {
+            let tag = entry.split('>').next()?;
+            Some((read_property(tag, "K")?, read_property(tag, "V")?))
+        })
+        .collect()
+}
+
+// Capture One sessions keep one .cos file per image in CaptureOne/Settings<version>/, named after
+// the full image filename.
+fn find_capture_one_settings(image_path: &str) -> Option<PathBuf> {
+    let path = Path::new(image_path);
+    let settings_name = format!("{}.cos", path.file_name()?.to_string_lossy());
+    let capture_one_dir = path.parent()?.join("CaptureOne");
+    let mut candidates: Vec<PathBuf> = fs::read_dir(capture_one_dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.file_name().map_or(false, |n| n.to_string_lossy().starts_with("Settings")))
+        .map(|dir| dir.join(&settings_name))
+        .filter(|p| p.is_file())
+        .collect();
+    // Newer Capture One versions write higher numbered folders.
+    candidates.sort();
+    candidates.pop()
+}
+
+fn convert_capture_one(xml: &str) -> ConvertedEdits {
+    let mut edits = ConvertedEdits::default();
+    for (key, value) in capture_one_settings(xml) {
+        let number = value.parse::<f64>().ok();
+        match (key.as_str(), number) {
+            ("Exposure", Some(ev)) => {
+                edits.adjustments.insert("exposure".to_string(), json!(ev));
+            }
+            ("Contrast", Some(v)) => {
+                edits.adjustments.insert("contrast".to_string(), json!(v));
+            }
+            ("Saturation", Some(v)) => {
+                edits.adjustments.insert("saturation".to_string(), json!(v));
+            }
+            ("HighlightRecoveryEx", Some(v)) => {
+                edits.adjustments.insert("highlights".to_string(), json!(-v));
+            }
+            ("ShadowRecovery", Some(v)) => {
+                edits.adjustments.insert("shadows".to_string(), json!(v));
+            }
+            ("Rating", Some(v)) if (1.0..=5.0).contains(&v) => edits.rating = Some(v as u8),
+            ("WhiteBalanceTemperature", Some(kelvin)) => edits.white_balance = Some(WhiteBalance::Kelvin(kelvin as f32)),
+            // Stored as the crop center followed by its size, in pixels of the unrotated image.
+            ("Crop", _) => {
+                let parts: Vec<f64> = value.split(';').filter_map(|p| p.trim().parse().ok()).collect();
+                if let [cx, cy, w, h] = parts[..] {
+                    edits.adjustments.insert(
+                        "crop".to_string(),
+                        json!({ "x": cx - w / 2.0, "y": cy - h / 2.0, "width": w, "height": h }),
+                    );
+                } else {
+                    edits.unconverted.push(key);
+                }
+            }
+            _ if CAPTURE_ONE_UNCONVERTED.iter().any(|k| key.starts_with(k)) => {
+                if !edits.unconverted.contains(&key) {
+                    edits.unconverted.push(key);
+                }
+            }
+            _ => {}
+        }
+    }
+    edits
+}
+
+// Resolves the parts that need the image itself: crops given as fractions and white balance, which is
+// only meaningful relative to the camera's as-shot white balance.
+fn finalize(image_path: &str, edits: &mut ConvertedEdits) {
+    let needs_image = edits.crop.is_some() || edits.white_balance.is_some();
+    let Some(bytes) = needs_image.then(|| fs::read(image_path).ok()).flatten() else { return; };
+
+    if let Some(crop) = edits.crop.take() {
+        match base_image_dimensions_from_bytes(&bytes, image_path) {
+            Ok((w, h)) => {
+                let (w, h) = (w as f64, h as f64);
+                edits.adjustments.insert(
+                    "crop".to_string(),
+                    json!({
+                        "x": crop.left * w,
+                        "y": crop.top * h,
+                        "width": (crop.right - crop.left).max(0.0) * w,
+                        "height": (crop.bottom - crop.top).max(0.0) * h,
+                    }),
+                );
+            }
+            Err(_) => edits.unconverted.push("crop".to_string()),
+        }
+    }
+
+    if let Some(white_balance) = edits.white_balance.take() {
+        let converted = if is_raw_file(image_path) {
+            match white_balance {
+                WhiteBalance::Kelvin(kelvin) => white_balance_from_kelvin(&bytes, kelvin).ok(),
+                WhiteBalance::Multipliers(multipliers) => white_balance_from_multipliers(&bytes, multipliers).ok(),
+            }
+        } else {
+            None
+        };
+        match converted {
+            Some((temperature, tint)) => {
+                edits.adjustments.insert("temperature".to_string(), json!(temperature));
+                edits.adjustments.insert("tint".to_string(), json!(tint));
+            }
+            None => edits.unconverted.push("white balance".to_string()),
+        }
+    }
+}
+
+fn sidecar_for(image_path: &str, source: DevelopSource) -> Option<PathBuf> {
+    match source {
+        DevelopSource::Darktable => find_xmp_sidecar(image_path),
+        DevelopSource::CaptureOne => find_capture_one_settings(image_path),
+    }
+}
+
+// Returns the operations that could not be converted, or None when the image was left untouched.
+fn import_for_image(image_path: &str, source: DevelopSource) -> Result<Option<Vec<String>>, String> {
+    let Some(sidecar) = sidecar_for(image_path, source) else { return Ok(None); };
+    let mut metadata = read_metadata(image_path);
+    let has_edits = metadata.adjustments.as_object().map_or(false, |a| a.keys().any(|k| k != "rating"));
+    if has_edits {
+        return Ok(None);
+    }
+
+    let xml = fs::read_to_string(&sidecar).map_err(|e| e.to_string())?;
+    let mut edits = match source {
+        DevelopSource::Darktable => convert_darktable(&xml),
+        DevelopSource::CaptureOne => convert_capture_one(&xml),
+    };
+    finalize(image_path, &mut edits);
+    if edits.adjustments.is_empty() && edits.rating.is_none() {
+        return Ok(None);
+    }
+
+    let rating = if metadata.rating == 0 { edits.rating.unwrap_or(0) } else { metadata.rating };
+    let mut adjustments = edits.adjustments;
+    adjustments.insert("rating".to_string(), json!(rating));
+    metadata.rating = rating;
+    metadata.adjustments = Value::Object(adjustments);
+
+    let json_string = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
+    fs::write(get_sidecar_path(image_path), json_string).map_err(|e| e.to_string())?;
+    Ok(Some(edits.unconverted))
+}
+
+// Best-effort conversion of darktable history stacks or Capture One session settings into RapidRAW
+// adjustments. Images that already have RapidRAW edits are skipped.
+#[tauri::command]
+pub async fn import_develop_settings(root_path: String, source: DevelopSource) -> Result<DevelopImportSummary, String> {
+    tokio::task::spawn_blocking(move || import_develop_settings_blocking(&root_path, source))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn import_develop_settings_blocking(root_path: &str, source: DevelopSource) -> Result<DevelopImportSummary, String> {
+    let images = images_under(root_path)?;
+    let results: Vec<(String, Result<Option<Vec<String>>, String>)> = images
+        .par_iter()
+        .map(|path| (path.clone(), import_for_image(path, source)))
+        .collect();
+
+    let mut summary = DevelopImportSummary::default();
+    for (path, result) in results {
+        match result {
+            Ok(Some(operations)) => {
+                summary.updated += 1;
+                if !operations.is_empty() {
+                    summary.unconverted.push(UnconvertedEdits { path, operations });
+                }
+            }
+            Ok(None) => summary.skipped += 1,
+            Err(e) => {
+                eprintln!("Failed to import develop settings for '{}': {}", path, e);
+                summary.skipped += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
@@ -0,0 +1,209 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::catalog::with_catalog;
+use crate::file_management::{generate_thumbnails_progressive, sidecar_lock};
+use crate::{read_sidecar_metadata, write_sidecar_metadata};
+
+const MAX_HISTORY_ENTRIES: i64 = 50;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub seq: i64,
+    pub created_at: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EditHistory {
+    pub entries: Vec<HistoryEntry>,
+    pub current: Option<i64>,
+    pub can_undo: bool,
+    pub can_redo: bool,
+}
+
+fn ensure_tables(conn: &Connection) -> Result<(), String> {
+    // Histories from before edit versions were tracked become the history of an image without versions.
+    let has_version_column = conn.prepare("SELECT version FROM edit_history LIMIT 0").is_ok();
+    let has_old_table = conn
+        .query_row("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'edit_history'", [], |_| Ok(()))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if has_old_table && !has_version_column {
+        conn.execute_batch(
+            "ALTER TABLE edit_history RENAME TO edit_history_unversioned;
+             ALTER TABLE edit_history_cursor RENAME TO edit_history_cursor_unversioned;",
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS edit_history (
+             path TEXT NOT NULL,
+             version TEXT NOT NULL DEFAULT '',
+             seq INTEGER NOT NULL,
+             adjustments TEXT NOT NULL,
+             created_at INTEGER NOT NULL,
+             PRIMARY KEY (path, version, seq)
+         );
+         CREATE TABLE IF NOT EXISTS edit_history_cursor (
+             path TEXT NOT NULL,
+             version TEXT NOT NULL DEFAULT '',
+             seq INTEGER NOT NULL,
+             PRIMARY KEY (path, version)
+         );",
+    )
+    .map_err(|e| e.to_string())?;
+
+    if has_old_table && !has_version_column {
+        conn.execute_batch(
+            "INSERT INTO edit_history (path, seq, adjustments, created_at)
+                 SELECT path, seq, adjustments, created_at FROM edit_history_unversioned;
+             INSERT INTO edit_history_cursor (path, seq)
+                 SELECT path, seq FROM edit_history_cursor_unversioned;
+             DROP TABLE edit_history_unversioned;
+             DROP TABLE edit_history_cursor_unversioned;",
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Each edit version of an image has its own undo history.
+fn active_version(path: &str) -> String {
+    read_sidecar_metadata(path).active_version.unwrap_or_default()
+}
+
+fn current_seq(conn: &Connection, path: &str, version: &str) -> Result<Option<i64>, String> {
+    conn.query_row(
+        "SELECT seq FROM edit_history_cursor WHERE path = ?1 AND version = ?2",
+        params![path, version],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn snapshot_at(conn: &Connection, path: &str, version: &str, seq: i64) -> Result<Option<Value>, String> {
+    let snapshot: Option<String> = conn
+        .query_row(
+            "SELECT adjustments FROM edit_history WHERE path = ?1 AND version = ?2 AND seq = ?3",
+            params![path, version, seq],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(snapshot.and_then(|s| serde_json::from_str(&s).ok()))
+}
+
+fn set_cursor(conn: &Connection, path: &str, version: &str, seq: i64) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO edit_history_cursor (path, version, seq) VALUES (?1, ?2, ?3)
+         ON CONFLICT(path, version) DO UPDATE SET seq = excluded.seq",
+        params![path, version, seq],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn record_snapshot(app_handle: &AppHandle, path: &str, version: &str, adjustments: &Value) -> Result<(), String> {
+    with_catalog(app_handle, |conn| {
+        ensure_tables(conn)?;
+        let cursor = current_seq(conn, path, version)?;
+        if let Some(seq) = cursor {
+            if snapshot_at(conn, path, version, seq)?.as_ref() == Some(adjustments) {
+                return Ok(());
+            }
+        }
+
+        let next_seq = cursor.map_or(0, |seq| seq + 1);
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute(
+            "DELETE FROM edit_history WHERE path = ?1 AND version = ?2 AND seq >= ?3",
+            params![path, version, next_seq],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "INSERT INTO edit_history (path, version, seq, adjustments, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![path, version, next_seq, adjustments.to_string(), chrono::Utc::now().timestamp_millis()],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.execute(
+            "DELETE FROM edit_history WHERE path = ?1 AND version = ?2 AND seq <= ?3",
+            params![path, version, next_seq - MAX_HISTORY_ENTRIES],
+        )
+        .map_err(|e| e.to_string())?;
+        set_cursor(&tx, path, version, next_seq)?;
+        tx.commit().map_err(|e| e.to_string())
+    })
+}
+
+fn step_history(path: &str, app_handle: &AppHandle, forward: bool) -> Result<Value, String> {
+    let version = active_version(path);
+    let adjustments = with_catalog(app_handle, |conn| {
+        ensure_tables(conn)?;
+        let cursor = current_seq(conn, path, &version)?.ok_or("No edit history for this image.")?;
+        let sql = if forward {
+            "SELECT MIN(seq) FROM edit_history WHERE path = ?1 AND version = ?2 AND seq > ?3"
+        } else {
+            "SELECT MAX(seq) FROM edit_history WHERE path = ?1 AND version = ?2 AND seq < ?3"
+        };
+        let target: Option<i64> = conn
+            .query_row(sql, params![path, version, cursor], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        let target = target.ok_or(if forward { "Nothing to redo." } else { "Nothing to undo." })?;
+        let adjustments = snapshot_at(conn, path, &version, target)?.ok_or("Edit history entry is corrupted.")?;
+        set_cursor(conn, path, &version, target)?;
+        Ok(adjustments)
+    })?;
+
+    let lock = sidecar_lock(path);
+    let guard = lock.lock().unwrap();
+    let mut metadata = read_sidecar_metadata(path);
+    metadata.rating = adjustments["rating"].as_u64().unwrap_or(metadata.rating as u64) as u8;
+    metadata.adjustments = adjustments.clone();
+    write_sidecar_metadata(path, &metadata)?;
+    drop(guard);
+
+    let app_handle = app_handle.clone();
+    let paths = vec![path.to_string()];
+    std::thread::spawn(move || {
+        let _ = generate_thumbnails_progressive(paths, app_handle);
+    });
+    Ok(adjustments)
+}
+
+#[tauri::command]
+pub fn undo_edit(path: String, app_handle: AppHandle) -> Result<Value, String> {
+    step_history(&path, &app_handle, false)
+}
+
+#[tauri::command]
+pub fn redo_edit(path: String, app_handle: AppHandle) -> Result<Value, String> {
+    step_history(&path, &app_handle, true)
+}
+
+#[tauri::command]
+pub fn get_history(path: String, app_handle: AppHandle) -> Result<EditHistory, String> {
+    let version = active_version(&path);
+    with_catalog(&app_handle, |conn| {
+        ensure_tables(conn)?;
+        let current = current_seq(conn, &path, &version)?;
+        let mut stmt = conn
+            .prepare("SELECT seq, created_at FROM edit_history WHERE path = ?1 AND version = ?2 ORDER BY seq")
+            .map_err(|e| e.to_string())?;
+        let entries = stmt
+            .query_map(params![path, version], |row| Ok(HistoryEntry { seq: row.get(0)?, created_at: row.get(1)? }))
+            .map_err(|e| e.to_string())?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())?;
+
+        let can_undo = current.map_or(false, |c| entries.iter().any(|e| e.seq < c));
+        let can_redo = current.map_or(false, |c| entries.iter().any(|e| e.seq > c));
+        Ok(EditHistory { entries, current, can_undo, can_redo })
+    })
+}
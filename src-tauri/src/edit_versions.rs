@@ -0,0 +1,216 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::file_management::generate_thumbnails_progressive;
+use crate::image_processing::{EditVersion, ImageMetadata};
+use crate::{read_sidecar_metadata, write_sidecar_metadata};
+
+const DEFAULT_VERSION_NAME: &str = "Original";
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EditVersionSummary {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EditVersionsInfo {
+    pub active_version_id: String,
+    pub versions: Vec<EditVersionSummary>,
+}
+
+fn with_rating(adjustments: Value, rating: u8) -> Value {
+    let mut adjustments = if adjustments.is_object() { adjustments } else { json!({}) };
+    adjustments["rating"] = json!(rating);
+    adjustments
+}
+
+fn ensure_versions(metadata: &mut ImageMetadata) -> bool {
+    let active_is_valid = metadata
+        .active_version
+        .as_ref()
+        .map_or(false, |id| metadata.versions.iter().any(|v| &v.id == id));
+    if active_is_valid {
+        return false;
+    }
+
+    if metadata.versions.is_empty() {
+        let id = Uuid::new_v4().to_string();
+        metadata.versions.push(EditVersion {
+            id: id.clone(),
+            name: DEFAULT_VERSION_NAME.to_string(),
+            adjustments: metadata.adjustments.clone(),
+        });
+        metadata.active_version = Some(id);
+    } else {
+        metadata.active_version = Some(metadata.versions[0].id.clone());
+        metadata.adjustments = with_rating(metadata.versions[0].adjustments.clone(), metadata.rating);
+    }
+    true
+}
+
+fn store_active_adjustments(metadata: &mut ImageMetadata) {
+    let adjustments = metadata.adjustments.clone();
+    if let Some(active) = metadata
+        .active_version
+        .as_ref()
+        .and_then(|id| metadata.versions.iter_mut().find(|v| &v.id == id))
+    {
+        active.adjustments = adjustments;
+    }
+}
+
+fn activate_version(metadata: &mut ImageMetadata, version_id: &str) -> Result<(), String> {
+    let target = metadata
+        .versions
+        .iter()
+        .find(|v| v.id == version_id)
+        .ok_or_else(|| format!("Edit version '{}' not found.", version_id))?
+        .adjustments
+        .clone();
+    store_active_adjustments(metadata);
+    metadata.adjustments = with_rating(target, metadata.rating);
+    metadata.active_version = Some(version_id.to_string());
+    Ok(())
+}
+
+fn versions_info(metadata: &ImageMetadata) -> EditVersionsInfo {
+    EditVersionsInfo {
+        active_version_id: metadata.active_version.clone().unwrap_or_default(),
+        versions: metadata
+            .versions
+            .iter()
+            .map(|v| EditVersionSummary { id: v.id.clone(), name: v.name.clone() })
+            .collect(),
+    }
+}
+
+pub fn adjustments_for_version(metadata: &ImageMetadata, version_id: &str) -> Option<Value> {
+    if metadata.active_version.as_deref() == Some(version_id) {
+        return Some(metadata.adjustments.clone());
+    }
+    metadata
+        .versions
+        .iter()
+        .find(|v| v.id == version_id)
+        .map(|v| with_rating(v.adjustments.clone(), metadata.rating))
+}
+
+fn update_versions(
+    path: &str,
+    app_handle: &AppHandle,
+    f: impl FnOnce(&mut ImageMetadata) -> Result<bool, String>,
+) -> Result<EditVersionsInfo, String> {
+    let mut metadata = read_sidecar_metadata(path);
+    ensure_versions(&mut metadata);
+    let adjustments_changed = f(&mut metadata)?;
+    write_sidecar_metadata(path, &metadata)?;
+
+    if adjustments_changed {
+        let app_handle = app_handle.clone();
+        let paths = vec![path.to_string()];
+        std::thread::spawn(move || {
+            let _ = generate_thumbnails_progressive(paths, app_handle);
+        });
+    }
+    Ok(versions_info(&metadata))
+}
+
+#[tauri::command]
+pub fn list_edit_versions(path: String) -> Result<EditVersionsInfo, String> {
+    let mut metadata = read_sidecar_metadata(&path);
+    if ensure_versions(&mut metadata) {
+        write_sidecar_metadata(&path, &metadata)?;
+    }
+    Ok(versions_info(&metadata))
+}
+
+#[tauri::command]
+pub fn create_edit_version(path: String, name: String, app_handle: AppHandle) -> Result<EditVersionsInfo, String> {
+    update_versions(&path, &app_handle, |metadata| {
+        let id = Uuid::new_v4().to_string();
+        metadata.versions.push(EditVersion { id: id.clone(), name, adjustments: json!({}) });
+        activate_version(metadata, &id)?;
+        Ok(true)
+    })
+}
+
+#[tauri::command]
+pub fn duplicate_edit_version(
+    path: String,
+    version_id: String,
+    name: Option<String>,
+    app_handle: AppHandle,
+) -> Result<EditVersionsInfo, String> {
+    update_versions(&path, &app_handle, |metadata| {
+        store_active_adjustments(metadata);
+        let source = metadata
+            .versions
+            .iter()
+            .find(|v| v.id == version_id)
+            .ok_or_else(|| format!("Edit version '{}' not found.", version_id))?;
+        let copy = EditVersion {
+            id: Uuid::new_v4().to_string(),
+            name: name.unwrap_or_else(|| format!("{} Copy", source.name)),
+            adjustments: source.adjustments.clone(),
+        };
+        let id = copy.id.clone();
+        metadata.versions.push(copy);
+        activate_version(metadata, &id)?;
+        Ok(true)
+    })
+}
+
+#[tauri::command]
+pub fn switch_edit_version(path: String, version_id: String, app_handle: AppHandle) -> Result<EditVersionsInfo, String> {
+    update_versions(&path, &app_handle, |metadata| {
+        if metadata.active_version.as_deref() == Some(version_id.as_str()) {
+            return Ok(false);
+        }
+        activate_version(metadata, &version_id)?;
+        Ok(true)
+    })
+}
+
+#[tauri::command]
+pub fn rename_edit_version(
+    path: String,
+    version_id: String,
+    name: String,
+    app_handle: AppHandle,
+) -> Result<EditVersionsInfo, String> {
+    update_versions(&path, &app_handle, |metadata| {
+        let version = metadata
+            .versions
+            .iter_mut()
+            .find(|v| v.id == version_id)
+            .ok_or_else(|| format!("Edit version '{}' not found.", version_id))?;
+        version.name = name;
+        Ok(false)
+    })
+}
+
+#[tauri::command]
+pub fn delete_edit_version(path: String, version_id: String, app_handle: AppHandle) -> Result<EditVersionsInfo, String> {
+    update_versions(&path, &app_handle, |metadata| {
+        if metadata.versions.len() <= 1 {
+            return Err("Cannot delete the only edit version.".to_string());
+        }
+        let was_active = metadata.active_version.as_deref() == Some(version_id.as_str());
+        if was_active {
+            let fallback = metadata
+                .versions
+                .iter()
+                .find(|v| v.id != version_id)
+                .map(|v| v.id.clone())
+                .unwrap_or_default();
+            activate_version(metadata, &fallback)?;
+        }
+        metadata.versions.retain(|v| v.id != version_id);
+        Ok(was_active)
+    })
+}
@@ -0,0 +1,314 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use exif::{Context, Exif, Field, In, Tag, Value as ExifValue};
+use serde::Serialize;
+
+const GROUP_CAMERA: &str = "Camera";
+const GROUP_EXPOSURE: &str = "Exposure";
+const GROUP_LENS: &str = "Lens";
+const GROUP_IMAGE: &str = "Image";
+const GROUP_DATE: &str = "Date";
+const GROUP_GPS: &str = "GPS";
+const GROUP_OTHER: &str = "Other";
+const GROUP_ORDER: [&str; 7] = [GROUP_CAMERA, GROUP_EXPOSURE, GROUP_LENS, GROUP_IMAGE, GROUP_DATE, GROUP_GPS, GROUP_OTHER];
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum TypedValue {
+    Text(String),
+    Integer(i64),
+    Number(f64),
+    DateTime(String),
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExifEntry {
+    pub tag: String,
+    pub display: String,
+    pub value: TypedValue,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExifGroup {
+    pub name: String,
+    pub entries: Vec<ExifEntry>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MakerNoteInfo {
+    pub make: Option<String>,
+    pub focus_mode: Option<String>,
+    pub shutter_count: Option<u64>,
+    pub serial_number: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExifDetails {
+    pub groups: Vec<ExifGroup>,
+    pub maker_note: Option<MakerNoteInfo>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+fn group_for(tag: Tag) -> &'static str {
+    match tag {
+        Tag::Make | Tag::Model | Tag::BodySerialNumber | Tag::Software | Tag::Artist | Tag::Copyright
+        | Tag::CameraOwnerName => GROUP_CAMERA,
+        Tag::ExposureTime | Tag::FNumber | Tag::PhotographicSensitivity | Tag::ExposureProgram
+        | Tag::ExposureBiasValue | Tag::ExposureMode | Tag::MeteringMode | Tag::Flash | Tag::WhiteBalance
+        | Tag::ShutterSpeedValue | Tag::ApertureValue | Tag::BrightnessValue | Tag::SceneCaptureType => {
+            GROUP_EXPOSURE
+        }
+        Tag::LensModel | Tag::LensMake | Tag::LensSerialNumber | Tag::LensSpecification | Tag::FocalLength
+        | Tag::FocalLengthIn35mmFilm | Tag::MaxApertureValue | Tag::SubjectDistance => GROUP_LENS,
+        Tag::ImageWidth | Tag::ImageLength | Tag::PixelXDimension | Tag::PixelYDimension | Tag::Orientation
+        | Tag::ColorSpace | Tag::XResolution | Tag::YResolution | Tag::ResolutionUnit | Tag::BitsPerSample => {
+            GROUP_IMAGE
+        }
+        Tag::DateTime | Tag::DateTimeOriginal | Tag::DateTimeDigitized | Tag::OffsetTime
+        | Tag::OffsetTimeOriginal | Tag::SubSecTimeOriginal => GROUP_DATE,
+        _ if tag.context() == Context::Gps => GROUP_GPS,
+        _ => GROUP_OTHER,
+    }
+}
+
+fn typed_value(field: &Field) -> TypedValue {
+    let display = || field.display_value().to_string().trim_matches('"').to_string();
+    match (&field.value, field.tag) {
+        (ExifValue::Ascii(values), Tag::DateTime | Tag::DateTimeOriginal | Tag::DateTimeDigitized) => {
+            let raw = values.first().map(|v| String::from_utf8_lossy(v).to_string()).unwrap_or_default();
+            chrono::NaiveDateTime::parse_from_str(raw.trim(), "%Y:%m:%d %H:%M:%S")
+                .map(|d| TypedValue::DateTime(d.format("%Y-%m-%dT%H:%M:%S").to_string()))
+                .unwrap_or_else(|_| TypedValue::Text(display()))
+        }
+        (ExifValue::Byte(v), _) if v.len() == 1 => TypedValue::Integer(v[0] as i64),
+        (ExifValue::Short(v), _) if v.len() == 1 => TypedValue::Integer(v[0] as i64),
+        (ExifValue::Long(v), _) if v.len() == 1 => TypedValue::Integer(v[0] as i64),
+        (ExifValue::SShort(v), _) if v.len() == 1 => TypedValue::Integer(v[0] as i64),
+        (ExifValue::SLong(v), _) if v.len() == 1 => TypedValue::Integer(v[0] as i64),
+        (ExifValue::Rational(v), _) if v.len() == 1 && v[0].denom != 0 => TypedValue::Number(v[0].to_f64()),
+        (ExifValue::SRational(v), _) if v.len() == 1 && v[0].denom != 0 => TypedValue::Number(v[0].to_f64()),
+        (ExifValue::Float(v), _) if v.len() == 1 => TypedValue::Number(v[0] as f64),
+        (ExifValue::Double(v), _) if v.len() == 1 => TypedValue::Number(v[0]),
+        _ => TypedValue::Text(display()),
+    }
+}
+
+fn gps_coordinate(exif: &Exif, value_tag: Tag, ref_tag: Tag) -> Option<f64> {
+    let ExifValue::Rational(parts) = &exif.get_field(value_tag, In::PRIMARY)?.value else {
+        return None;
+    };
+    let degrees = parts.iter().take(3).zip([1.0, 60.0, 3600.0]).map(|(v, div)| v.to_f64() / div).sum::<f64>();
+    let negative = exif
+        .get_field(ref_tag, In::PRIMARY)
+        .map_or(false, |f| f.display_value().to_string().contains(['S', 'W']));
+    let value = if negative { -degrees } else { degrees };
+    value.is_finite().then_some(value)
+}
+
+// Value offsets inside the IFD are relative to the start of `data`.
+struct IfdReader<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+struct RawEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    value_pos: usize,
+}
+
+impl<'a> IfdReader<'a> {
+    fn u16_at(&self, pos: usize) -> Option<u16> {
+        let b: [u8; 2] = self.data.get(pos..pos + 2)?.try_into().ok()?;
+        Some(if self.little_endian { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+    }
+
+    fn u32_at(&self, pos: usize) -> Option<u32> {
+        let b: [u8; 4] = self.data.get(pos..pos + 4)?.try_into().ok()?;
+        Some(if self.little_endian { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    fn entries(&self, ifd_pos: usize) -> Vec<RawEntry> {
+        let Some(count) = self.u16_at(ifd_pos) else { return Vec::new(); };
+        (0..count as usize)
+            .filter_map(|i| {
+                let pos = ifd_pos + 2 + i * 12;
+                let tag = self.u16_at(pos)?;
+                let field_type = self.u16_at(pos + 2)?;
+                let count = self.u32_at(pos + 4)?;
+                let size = count as usize * type_size(field_type);
+                let value_pos = if size <= 4 { pos + 8 } else { self.u32_at(pos + 8)? as usize };
+                Some(RawEntry { tag, field_type, count, value_pos })
+            })
+            .collect()
+    }
+
+    fn integer(&self, entries: &[RawEntry], tag: u16, index: usize) -> Option<u64> {
+        let entry = entries.iter().find(|e| e.tag == tag)?;
+        if index >= entry.count as usize {
+            return None;
+        }
+        match entry.field_type {
+            1 | 7 => self.data.get(entry.value_pos + index).map(|b| *b as u64),
+            3 | 8 => self.u16_at(entry.value_pos + index * 2).map(|v| v as u64),
+            4 | 9 => self.u32_at(entry.value_pos + index * 4).map(|v| v as u64),
+            _ => None,
+        }
+    }
+
+    fn shorts(&self, entry: &RawEntry) -> Vec<u16> {
+        (0..entry.count as usize).filter_map(|i| self.u16_at(entry.value_pos + i * 2)).collect()
+    }
+
+    fn text(&self, entries: &[RawEntry], tag: u16) -> Option<String> {
+        let entry = entries.iter().find(|e| e.tag == tag && (e.field_type == 2 || e.field_type == 7))?;
+        let bytes = self.data.get(entry.value_pos..entry.value_pos + entry.count as usize)?;
+        let text = String::from_utf8_lossy(bytes).trim_end_matches('\0').trim().to_string();
+        (!text.is_empty()).then_some(text)
+    }
+}
+
+fn type_size(field_type: u16) -> usize {
+    match field_type {
+        3 | 8 => 2,
+        4 | 9 | 11 => 4,
+        5 | 10 | 12 => 8,
+        _ => 1,
+    }
+}
+
+// Canon maker notes are a plain IFD whose offsets are relative to the EXIF TIFF header.
+fn parse_canon(tiff: &[u8], note_pos: usize, little_endian: bool) -> MakerNoteInfo {
+    let reader = IfdReader { data: tiff, little_endian };
+    let entries = reader.entries(note_pos);
+    let camera_settings = entries.iter().find(|e| e.tag == 0x0001).map(|e| reader.shorts(e));
+    let focus_mode = camera_settings.and_then(|s| s.get(7).copied()).and_then(|mode| match mode {
+        0 => Some("One-Shot AF"),
+        1 => Some("AI Servo AF"),
+        2 => Some("AI Focus AF"),
+        3 | 6 => Some("Manual Focus"),
+        4 => Some("Single"),
+        5 => Some("Continuous"),
+        _ => None,
+    });
+    MakerNoteInfo {
+        make: Some("Canon".to_string()),
+        focus_mode: focus_mode.map(str::to_string),
+        shutter_count: None,
+        serial_number: reader.integer(&entries, 0x000c, 0).map(|s| s.to_string()),
+    }
+}
+
+// Nikon type 3 notes embed their own TIFF header 10 bytes in.
+fn parse_nikon(note: &[u8]) -> Option<MakerNoteInfo> {
+    let header = note.get(10..)?;
+    let little_endian = header.starts_with(b"II");
+    let reader = IfdReader { data: header, little_endian };
+    let entries = reader.entries(reader.u32_at(4)? as usize);
+    Some(MakerNoteInfo {
+        make: Some("Nikon".to_string()),
+        focus_mode: reader.text(&entries, 0x0007),
+        shutter_count: reader.integer(&entries, 0x00a7, 0),
+        serial_number: reader.text(&entries, 0x001d),
+    })
+}
+
+// Sony notes start with a 12 byte signature; offsets are relative to the EXIF TIFF header.
+fn parse_sony(tiff: &[u8], note_pos: usize, little_endian: bool) -> MakerNoteInfo {
+    let reader = IfdReader { data: tiff, little_endian };
+    let entries = reader.entries(note_pos + 12);
+    let focus_mode = reader.integer(&entries, 0xb042, 0).and_then(|mode| match mode {
+        0 => Some("Manual"),
+        2 => Some("AF-S"),
+        3 => Some("AF-C"),
+        4 => Some("AF-A"),
+        6 => Some("DMF"),
+        _ => None,
+    });
+    MakerNoteInfo {
+        make: Some("Sony".to_string()),
+        focus_mode: focus_mode.map(str::to_string),
+        shutter_count: None,
+        serial_number: None,
+    }
+}
+
+// Fujifilm notes are always little endian with offsets relative to the note itself.
+fn parse_fujifilm(note: &[u8]) -> Option<MakerNoteInfo> {
+    let reader = IfdReader { data: note, little_endian: true };
+    let entries = reader.entries(reader.u32_at(8)? as usize);
+    let focus_mode = reader.integer(&entries, 0x1021, 0).map(|mode| if mode == 0 { "Auto" } else { "Manual" });
+    Some(MakerNoteInfo {
+        make: Some("Fujifilm".to_string()),
+        focus_mode: focus_mode.map(str::to_string),
+        shutter_count: None,
+        serial_number: reader.text(&entries, 0x0010),
+    })
+}
+
+fn parse_maker_note(exif: &Exif) -> Option<MakerNoteInfo> {
+    let field = exif.get_field(Tag::MakerNote, In::PRIMARY)?;
+    let ExifValue::Undefined(note, note_pos) = &field.value else {
+        return None;
+    };
+    let note_pos = *note_pos as usize;
+    let make = exif
+        .get_field(Tag::Make, In::PRIMARY)
+        .map(|f| f.display_value().to_string().to_lowercase())
+        .unwrap_or_default();
+
+    if note.starts_with(b"Nikon\0") {
+        parse_nikon(note)
+    } else if note.starts_with(b"SONY DSC ") || note.starts_with(b"SONY CAM ") {
+        Some(parse_sony(exif.buf(), note_pos, exif.little_endian()))
+    } else if note.starts_with(b"FUJIFILM") {
+        parse_fujifilm(note)
+    } else if make.contains("canon") {
+        Some(parse_canon(exif.buf(), note_pos, exif.little_endian()))
+    } else {
+        None
+    }
+}
+
+pub fn read_exif_details(path: &str) -> Result<ExifDetails, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .map_err(|e| format!("No EXIF data in {}: {}", path, e))?;
+
+    let mut groups: Vec<ExifGroup> =
+        GROUP_ORDER.iter().map(|name| ExifGroup { name: name.to_string(), entries: Vec::new() }).collect();
+    for field in exif.fields().filter(|f| f.ifd_num == In::PRIMARY && f.tag != Tag::MakerNote) {
+        let entry = ExifEntry {
+            tag: field.tag.to_string(),
+            display: field.display_value().with_unit(&exif).to_string(),
+            value: typed_value(field),
+        };
+        let group = group_for(field.tag);
+        if let Some(target) = groups.iter_mut().find(|g| g.name == group) {
+            target.entries.push(entry);
+        }
+    }
+    groups.retain(|g| !g.entries.is_empty());
+
+    Ok(ExifDetails {
+        groups,
+        maker_note: parse_maker_note(&exif),
+        latitude: gps_coordinate(&exif, Tag::GPSLatitude, Tag::GPSLatitudeRef),
+        longitude: gps_coordinate(&exif, Tag::GPSLongitude, Tag::GPSLongitudeRef),
+    })
+}
+
+#[tauri::command]
+pub async fn get_exif_details(path: String) -> Result<ExifDetails, String> {
+    tauri::async_runtime::spawn_blocking(move || read_exif_details(&path))
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,142 @@
+use std::fs;
+use std::io::Read;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+use crate::iptc_metadata::read_metadata;
+
+// A script that hangs is killed rather than holding on to the export forever.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(300);
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+#[derive(Clone)]
+pub struct ExportedFile {
+    pub source_path: String,
+    pub output_path: PathBuf,
+    // The source's catalog fingerprint from before it was rendered, so edits made while the export
+    // ran aren't recorded as published.
+    pub source_fingerprint: (u64, u64),
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ExportManifest<'a> {
+    source_path: &'a str,
+    output_path: String,
+    format: &'a str,
+    index: usize,
+    total: usize,
+    rating: u8,
+    tags: Vec<String>,
+    export_settings: &'a Value,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ExportHookErrorPayload {
+    path: String,
+    error: String,
+}
+
+fn run_hook(hook: &str, output_path: &PathBuf, manifest: &ExportManifest) -> Result<(), String> {
+    let manifest_path = std::env::temp_dir().join(format!("rapidraw-export-{}.json", Uuid::new_v4()));
+    let json_string = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+    fs::write(&manifest_path, json_string).map_err(|e| e.to_string())?;
+
+    let mut command = Command::new(hook);
+    command.arg(output_path).arg(&manifest_path).stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    command.creation_flags(CREATE_NO_WINDOW);
+    let result = command
+        .spawn()
+        .map_err(|e| format!("Failed to run post-export script '{}': {}", hook, e))
+        .and_then(wait_with_timeout);
+    let _ = fs::remove_file(&manifest_path);
+    let (status, stdout, stderr) = result?;
+
+    if !stdout.trim().is_empty() {
+        println!("Post-export script: {}", stdout.trim());
+    }
+    if !status.success() {
+        return Err(format!("Post-export script exited with {}: {}", status, stderr.trim()));
+    }
+    Ok(())
+}
+
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut output);
+        }
+        output
+    })
+}
+
+// The pipes are drained on their own threads so a chatty script can't block on a full pipe.
+fn wait_with_timeout(mut child: Child) -> Result<(std::process::ExitStatus, String, String), String> {
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+    let deadline = Instant::now() + HOOK_TIMEOUT;
+
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("Post-export script timed out after {} seconds.", HOOK_TIMEOUT.as_secs()));
+            }
+            None => thread::sleep(HOOK_POLL_INTERVAL),
+        }
+    };
+    Ok((status, stdout.join().unwrap_or_default(), stderr.join().unwrap_or_default()))
+}
+
+// Runs the export's script once per exported file, in export order, as `<script> <exported file> <manifest>`.
+// The manifest is a temporary JSON file describing the source image and the export. A failing script is
+// reported but doesn't fail the export, since the files were already written. The scripts run on their
+// own thread, so the export is reported complete without waiting for them.
+pub fn run_post_export_hook(
+    hook: String,
+    files: Vec<ExportedFile>,
+    format: String,
+    export_settings: Value,
+    app_handle: AppHandle,
+) {
+    thread::spawn(move || run_hook_for_files(&hook, &files, &format, &export_settings, &app_handle));
+}
+
+fn run_hook_for_files(hook: &str, files: &[ExportedFile], format: &str, export_settings: &Value, app_handle: &AppHandle) {
+    for (i, file) in files.iter().enumerate() {
+        let metadata = read_metadata(&file.source_path);
+        let manifest = ExportManifest {
+            source_path: &file.source_path,
+            output_path: file.output_path.to_string_lossy().into_owned(),
+            format,
+            index: i + 1,
+            total: files.len(),
+            rating: metadata.rating,
+            tags: metadata.tags.unwrap_or_default(),
+            export_settings,
+        };
+        if let Err(e) = run_hook(hook, &file.output_path, &manifest) {
+            eprintln!("{}", e);
+            let _ = app_handle.emit(
+                "export-hook-error",
+                ExportHookErrorPayload { path: manifest.output_path.clone(), error: e },
+            );
+        }
+    }
+}
@@ -4,7 +4,7 @@ use std::io::Cursor;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 use anyhow::Result;
@@ -18,22 +18,67 @@ use tauri::{AppHandle, Emitter, Manager};
 use uuid::Uuid;
 use walkdir::WalkDir;
 use chrono::{DateTime, Utc};
-use little_exif::exif_tag::ExifTag;
-use little_exif::metadata::Metadata;
+use once_cell::sync::Lazy;
 
+use crate::capture_time;
+use crate::catalog;
+use crate::edit_history;
+use crate::stacking::{self, StackInfo};
 use crate::gpu_processing;
-use crate::formats::is_supported_image_file;
+use crate::memory_budget::thumbnail_thread_count;
+use crate::formats::{is_raw_file, is_supported_image_file, is_supported_library_file, is_video_file};
 use crate::image_processing::GpuContext;
 use crate::image_loader;
+use crate::raw_processing;
+use crate::iptc_metadata;
 use crate::image_processing::{
     apply_crop, apply_flip, apply_rotation, auto_results_to_json, get_all_adjustments_from_json,
-    perform_auto_analysis, Crop, ImageMetadata, apply_coarse_rotation,
+    lateral_ca_for, perform_auto_analysis, Crop, ImageFlag, ImageMetadata, OpticalCenter, apply_coarse_rotation,
 };
 use crate::tagging::COLOR_TAG_PREFIX;
+use crate::watch_import::{self, WatchFolder};
+use crate::custom_effects;
+use crate::http_api;
+use crate::publish_services::PublishService;
+use crate::color_profiles::HdrPreviewMode;
+use crate::comfyui_connector::ComfyUiProfile;
+use crate::upload_targets::UploadTarget;
+use crate::xmp_sidecar;
+use crate::video;
 use crate::mask_generation::{generate_mask_bitmap, MaskDefinition};
+use crate::lut_processing::get_lut_for_adjustments;
 use crate::AppState;
 
 const THUMBNAIL_WIDTH: u32 = 640;
+const LARGE_THUMBNAIL_WIDTH: u32 = 1280;
+
+// The grid shows the small rendition, the filmstrip loupe and the editor placeholder the large one.
+// Both are cached whenever an image is rendered, so switching between them never renders again.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ThumbnailRendition {
+    #[default]
+    Small,
+    Large,
+}
+
+impl ThumbnailRendition {
+    const ALL: [ThumbnailRendition; 2] = [ThumbnailRendition::Small, ThumbnailRendition::Large];
+
+    fn max_dimension(self) -> u32 {
+        match self {
+            ThumbnailRendition::Small => THUMBNAIL_WIDTH,
+            ThumbnailRendition::Large => LARGE_THUMBNAIL_WIDTH,
+        }
+    }
+
+    fn cache_filename(self, hash: &str) -> String {
+        match self {
+            ThumbnailRendition::Small => format!("{}.jpg", hash),
+            ThumbnailRendition::Large => format!("{}_large.jpg", hash),
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Preset {
@@ -61,6 +106,24 @@ pub struct PresetFile {
     pub presets: Vec<PresetItem>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPreset {
+    pub id: String,
+    pub name: String,
+    pub export_settings: Value,
+    pub file_format: String,
+    pub output_folder: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Album {
+    pub id: String,
+    pub name: String,
+    pub paths: Vec<String>,
+}
+
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -76,6 +139,28 @@ pub struct FilterCriteria {
     pub raw_status: String,
     #[serde(default)]
     pub colors: Vec<String>,
+    #[serde(default)]
+    pub flag_status: Option<String>,
+    #[serde(default)]
+    pub camera_models: Vec<String>,
+    #[serde(default)]
+    pub lens_models: Vec<String>,
+    #[serde(default)]
+    pub focal_length_min: Option<f64>,
+    #[serde(default)]
+    pub focal_length_max: Option<f64>,
+    #[serde(default)]
+    pub iso_min: Option<i64>,
+    #[serde(default)]
+    pub iso_max: Option<i64>,
+    #[serde(default)]
+    pub aperture_min: Option<f64>,
+    #[serde(default)]
+    pub aperture_max: Option<f64>,
+    #[serde(default)]
+    pub capture_date_from: Option<String>,
+    #[serde(default)]
+    pub capture_date_to: Option<String>,
 }
 
 impl Default for FilterCriteria {
@@ -84,6 +169,17 @@ impl Default for FilterCriteria {
             rating: 0,
             raw_status: "all".to_string(),
             colors: Vec::new(),
+            flag_status: None,
+            camera_models: Vec::new(),
+            lens_models: Vec::new(),
+            focal_length_min: None,
+            focal_length_max: None,
+            iso_min: None,
+            iso_max: None,
+            aperture_min: None,
+            aperture_max: None,
+            capture_date_from: None,
+            capture_date_to: None,
         }
     }
 }
@@ -100,12 +196,18 @@ pub struct LastFolderState {
 pub struct AppSettings {
     pub last_root_path: Option<String>,
     pub editor_preview_resolution: Option<u32>,
+    pub editor_prefetch_count: Option<u32>,
+    pub memory_budget_mb: Option<u32>,
+    pub export_worker_count: Option<u32>,
     pub sort_criteria: Option<SortCriteria>,
     pub filter_criteria: Option<FilterCriteria>,
     pub theme: Option<String>,
     pub transparent: Option<bool>,
     pub decorations: Option<bool>,
     pub comfyui_address: Option<String>,
+    pub comfyui_profiles: Option<Vec<ComfyUiProfile>>,
+    pub display_profile_path: Option<String>,
+    pub hdr_preview: Option<HdrPreviewMode>,
     pub last_folder_state: Option<LastFolderState>,
     pub adaptive_editor_theme: Option<bool>,
     pub ui_visibility: Option<Value>,
@@ -113,6 +215,16 @@ pub struct AppSettings {
     pub tagging_thread_count: Option<u32>,
     pub thumbnail_size: Option<String>,
     pub thumbnail_aspect_ratio: Option<String>,
+    pub camera_profiles: Option<HashMap<String, String>>,
+    pub write_xmp_sidecars: Option<bool>,
+    pub watch_folders: Option<Vec<WatchFolder>>,
+    pub enable_http_api: Option<bool>,
+    pub http_api_port: Option<u16>,
+    pub http_api_token: Option<String>,
+    pub upload_targets: Option<Vec<UploadTarget>>,
+    pub publish_services: Option<Vec<PublishService>>,
+    pub custom_effect_shader: Option<String>,
+    pub auto_hot_pixel_removal: Option<bool>,
 }
 
 impl Default for AppSettings {
@@ -120,6 +232,9 @@ impl Default for AppSettings {
         Self {
             last_root_path: None,
             editor_preview_resolution: Some(1920),
+            editor_prefetch_count: Some(1),
+            memory_budget_mb: Some(4096),
+            export_worker_count: Some(0),
             sort_criteria: None,
             filter_criteria: None,
             theme: Some("dark".to_string()),
@@ -129,6 +244,9 @@ impl Default for AppSettings {
             #[cfg(any(target_os = "windows", target_os = "macos"))]
             decorations: Some(false),
             comfyui_address: None,
+            comfyui_profiles: None,
+            display_profile_path: None,
+            hdr_preview: None,
             last_folder_state: None,
             adaptive_editor_theme: Some(false),
             ui_visibility: None,
@@ -136,6 +254,16 @@ impl Default for AppSettings {
             tagging_thread_count: Some(3),
             thumbnail_size: Some("medium".to_string()),
             thumbnail_aspect_ratio: Some("cover".to_string()),
+            camera_profiles: None,
+            write_xmp_sidecars: Some(false),
+            watch_folders: None,
+            enable_http_api: Some(false),
+            http_api_port: None,
+            http_api_token: None,
+            upload_targets: None,
+            publish_services: None,
+            custom_effect_shader: None,
+            auto_hot_pixel_removal: Some(false),
         }
     }
 }
@@ -147,9 +275,11 @@ pub struct ImageFile {
     modified: u64,
     is_edited: bool,
     tags: Option<Vec<String>>,
+    flag: Option<ImageFlag>,
+    stack: Option<StackInfo>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ImportSettings {
     pub filename_template: String,
@@ -159,7 +289,34 @@ pub struct ImportSettings {
 }
 
 #[tauri::command]
-pub fn list_images_in_dir(path: String) -> Result<Vec<ImageFile>, String> {
+pub fn list_images_in_dir(path: String, app_handle: AppHandle) -> Result<Vec<ImageFile>, String> {
+    match catalog::list_folder(&app_handle, &path) {
+        Ok(entries) => {
+            let stacks = catalog::with_catalog(&app_handle, |conn| stacking::stacks_for_folder(conn, &path))
+                .unwrap_or_else(|e| {
+                    eprintln!("Failed to compute stacks for '{}': {}", path, e);
+                    HashMap::new()
+                });
+            Ok(entries
+                .into_iter()
+                .map(|entry| ImageFile {
+                    stack: stacks.get(&entry.path).cloned(),
+                    path: entry.path,
+                    modified: entry.modified,
+                    is_edited: entry.is_edited,
+                    tags: entry.tags,
+                    flag: entry.flag,
+                })
+                .collect())
+        }
+        Err(e) => {
+            eprintln!("Catalog unavailable for '{}', scanning sidecars directly: {}", path, e);
+            scan_images_in_dir(path)
+        }
+    }
+}
+
+fn scan_images_in_dir(path: String) -> Result<Vec<ImageFile>, String> {
     let entries: Vec<ImageFile> = fs::read_dir(path)
         .map_err(|e| e.to_string())?
         .filter_map(std::result::Result::ok)
@@ -171,7 +328,7 @@ pub fn list_images_in_dir(path: String) -> Result<Vec<ImageFile>, String> {
                 .map_or(false, |s| s.starts_with('.'))
         })
         .filter(|path| path.is_file())
-        .filter(|path| path.to_str().map_or(false, is_supported_image_file))
+        .filter(|path| path.to_str().map_or(false, is_supported_library_file))
         .map(|path| {
             let path_str = path.to_string_lossy().into_owned();
             let modified = fs::metadata(&path)
@@ -182,22 +339,24 @@ pub fn list_images_in_dir(path: String) -> Result<Vec<ImageFile>, String> {
                 .unwrap_or(0);
             
             let sidecar_path = get_sidecar_path(&path_str);
-            let (is_edited, tags) = if sidecar_path.exists() {
+            let (is_edited, tags, flag) = if sidecar_path.exists() {
                 if let Ok(content) = fs::read_to_string(sidecar_path) {
                     if let Ok(metadata) = serde_json::from_str::<ImageMetadata>(&content) {
                         let edited = metadata.adjustments.as_object().map_or(false, |a| {
                             a.keys().len() > 1 || (a.keys().len() == 1 && !a.contains_key("rating"))
                         });
-                        (edited, metadata.tags)
-                    } else { (false, None) }
-                } else { (false, None) }
-            } else { (false, None) };
+                        (edited, metadata.tags, metadata.flag)
+                    } else { (false, None, None) }
+                } else { (false, None, None) }
+            } else { (false, None, None) };
 
             ImageFile {
                 path: path_str,
                 modified,
                 is_edited,
                 tags,
+                flag,
+                stack: None,
             }
         })
         .collect();
@@ -282,6 +441,14 @@ pub fn get_sidecar_path(image_path: &str) -> PathBuf {
     path.with_file_name(new_filename)
 }
 
+// Camera JPEGs embedded in RAWs are far cheaper to read than developing the RAW. Tiny embedded
+// thumbnails would look soft in the grid, so those still go through the full decode.
+fn embedded_thumbnail(path_str: &str) -> Option<DynamicImage> {
+    let file_bytes = fs::read(path_str).ok()?;
+    let preview = raw_processing::extract_embedded_preview(&file_bytes).ok()??;
+    (preview.width().max(preview.height()) >= THUMBNAIL_WIDTH).then_some(preview)
+}
+
 pub fn generate_thumbnail_data(
     path_str: &str,
     gpu_context: Option<&GpuContext>,
@@ -296,6 +463,16 @@ pub fn generate_thumbnail_data(
         .as_ref()
         .map_or(serde_json::Value::Null, |m| m.adjustments.clone());
 
+    if is_video_file(path_str) {
+        return video::extract_poster_frame(path_str).map_err(anyhow::Error::msg);
+    }
+
+    if preloaded_image.is_none() && adjustments.is_null() && is_raw_file(path_str) {
+        if let Some(preview) = embedded_thumbnail(path_str) {
+            return Ok(preview);
+        }
+    }
+
     let base_image = if let Some(img) = preloaded_image {
         image_loader::composite_patches_on_image(img, &adjustments)?
     } else {
@@ -329,6 +506,7 @@ pub fn generate_thumbnail_data(
                 .unwrap_or(false);
             let flip_vertical = meta.adjustments["flipVertical"].as_bool().unwrap_or(false);
 
+            let lateral_ca = lateral_ca_for(&processing_base, &meta.adjustments);
             let flipped_image = apply_flip(processing_base, flip_horizontal, flip_vertical);
             let rotated_image = apply_rotation(&flipped_image, rotation_degrees);
 
@@ -346,10 +524,17 @@ pub fn generate_thumbnail_data(
                 serde_json::Value::Null
             };
 
+            let (uncropped_w, uncropped_h) = rotated_image.dimensions();
             let cropped_preview = apply_crop(rotated_image, &scaled_crop_json);
             let (preview_w, preview_h) = cropped_preview.dimensions();
 
             let unscaled_crop_offset = crop_data.map_or((0.0, 0.0), |c| (c.x as f32, c.y as f32));
+            let optical_center = OpticalCenter::of_crop(
+                uncropped_w,
+                uncropped_h,
+                (unscaled_crop_offset.0 * scale_for_gpu, unscaled_crop_offset.1 * scale_for_gpu),
+            )
+            .with_lateral_ca(lateral_ca);
 
             let mask_definitions: Vec<MaskDefinition> = meta
                 .adjustments
@@ -369,17 +554,19 @@ pub fn generate_thumbnail_data(
                             unscaled_crop_offset.0 * scale_for_gpu,
                             unscaled_crop_offset.1 * scale_for_gpu,
                         ),
+                        Some(&cropped_preview),
                     )
                 })
                 .collect();
 
-            let gpu_adjustments = get_all_adjustments_from_json(&meta.adjustments);
+            let gpu_adjustments = get_all_adjustments_from_json(&meta.adjustments).with_optical_center(optical_center);
 
             if let Ok(processed_image) = gpu_processing::process_and_get_dynamic_image(
                 context,
                 &cropped_preview,
                 gpu_adjustments,
                 &mask_bitmaps,
+                get_lut_for_adjustments(&meta.adjustments).as_deref(),
             ) {
                 return Ok(processed_image);
             } else {
@@ -392,20 +579,38 @@ pub fn generate_thumbnail_data(
     Ok(apply_coarse_rotation(base_image, fallback_orientation_steps))
 }
 
-fn encode_thumbnail(image: &DynamicImage) -> Result<Vec<u8>> {
-    let thumbnail = image.thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_WIDTH);
+fn encode_thumbnail(image: &DynamicImage, rendition: ThumbnailRendition) -> Result<Vec<u8>> {
+    let max_dimension = rendition.max_dimension();
+    let thumbnail = image.thumbnail(max_dimension, max_dimension);
     let mut buf = Cursor::new(Vec::new());
     let mut encoder = JpegEncoder::new_with_quality(&mut buf, 75);
     encoder.encode_image(&thumbnail.to_rgba8())?;
     Ok(buf.into_inner())
 }
 
+// Writes every rendition of a freshly rendered thumbnail and returns the requested one.
+fn cache_thumbnail_renditions(
+    image: &DynamicImage,
+    thumb_cache_dir: &Path,
+    hash: &str,
+    requested: ThumbnailRendition,
+) -> Result<Vec<u8>> {
+    for rendition in ThumbnailRendition::ALL.into_iter().filter(|r| *r != requested) {
+        let thumb_data = encode_thumbnail(image, rendition)?;
+        let _ = fs::write(thumb_cache_dir.join(rendition.cache_filename(hash)), thumb_data);
+    }
+    let thumb_data = encode_thumbnail(image, requested)?;
+    let _ = fs::write(thumb_cache_dir.join(requested.cache_filename(hash)), &thumb_data);
+    Ok(thumb_data)
+}
+
 fn generate_single_thumbnail_and_cache(
     path_str: &str,
     thumb_cache_dir: &Path,
     gpu_context: Option<&GpuContext>,
     preloaded_image: Option<&DynamicImage>,
     force_regenerate: bool,
+    rendition: ThumbnailRendition,
 ) -> Option<(String, u8)> {
     let original_path = Path::new(path_str);
     let sidecar_path = get_sidecar_path(path_str);
@@ -438,9 +643,8 @@ fn generate_single_thumbnail_and_cache(
     hasher.update(path_str.as_bytes());
     hasher.update(&img_mod_time.to_le_bytes());
     hasher.update(&sidecar_mod_time.to_le_bytes());
-    let hash = hasher.finalize();
-    let cache_filename = format!("{}.jpg", hash.to_hex());
-    let cache_path = thumb_cache_dir.join(cache_filename);
+    let hash = hasher.finalize().to_hex().to_string();
+    let cache_path = thumb_cache_dir.join(rendition.cache_filename(&hash));
 
     if !force_regenerate && cache_path.exists() {
         if let Ok(data) = fs::read(&cache_path) {
@@ -450,8 +654,7 @@ fn generate_single_thumbnail_and_cache(
     }
 
     if let Ok(thumb_image) = generate_thumbnail_data(path_str, gpu_context, preloaded_image) {
-        if let Ok(thumb_data) = encode_thumbnail(&thumb_image) {
-            let _ = fs::write(&cache_path, &thumb_data);
+        if let Ok(thumb_data) = cache_thumbnail_renditions(&thumb_image, thumb_cache_dir, &hash, rendition) {
             let base64_str = general_purpose::STANDARD.encode(&thumb_data);
             return Some((format!("data:image/jpeg;base64,{}", base64_str), rating));
         }
@@ -462,8 +665,10 @@ fn generate_single_thumbnail_and_cache(
 #[tauri::command]
 pub async fn generate_thumbnails(
     paths: Vec<String>,
+    size: Option<ThumbnailRendition>,
     app_handle: tauri::AppHandle,
 ) -> Result<HashMap<String, String>, String> {
+    let rendition = size.unwrap_or_default();
     tauri::async_runtime::spawn_blocking(move || {
         let cache_dir = app_handle
             .path()
@@ -486,6 +691,7 @@ pub async fn generate_thumbnails(
                     gpu_context.as_ref(),
                     None,
                     false,
+                    rendition,
                 )
                 .map(|(data, _rating)| (path_str.clone(), data))
             })
@@ -518,14 +724,25 @@ pub fn generate_thumbnails_progressive(
     thread::spawn(move || {
         let state = app_handle.state::<AppState>();
         let gpu_context = gpu_processing::get_or_init_gpu_context(&state).ok();
+        let pool = match rayon::ThreadPoolBuilder::new()
+            .num_threads(thumbnail_thread_count(&app_handle))
+            .build()
+        {
+            Ok(pool) => pool,
+            Err(e) => {
+                eprintln!("Failed to create thumbnail thread pool: {}", e);
+                return;
+            }
+        };
 
-        paths.par_iter().for_each(|path_str| {
+        pool.install(|| paths.par_iter().for_each(|path_str| {
             let result = generate_single_thumbnail_and_cache(
                 path_str,
                 &thumb_cache_dir,
                 gpu_context.as_ref(),
                 None,
                 false,
+                ThumbnailRendition::Small,
             );
 
             if let Some((thumbnail_data, rating)) = result {
@@ -540,7 +757,7 @@ pub fn generate_thumbnails_progressive(
                 "thumbnail-progress",
                 serde_json::json!({ "completed": completed, "total": total_count }),
             );
-        });
+        }));
 
         let _ = app_handle_clone.emit("thumbnail-generation-complete", true);
     });
@@ -570,7 +787,7 @@ pub fn create_folder(path: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn rename_folder(path: String, new_name: String) -> Result<(), String> {
+pub fn rename_folder(path: String, new_name: String, app_handle: AppHandle) -> Result<(), String> {
     let p = Path::new(&path);
     if !p.is_dir() {
         return Err("Path is not a directory.".to_string());
@@ -586,15 +803,36 @@ pub fn rename_folder(path: String, new_name: String) -> Result<(), String> {
             }
         }
         let new_path = parent.join(&new_name);
-        fs::rename(p, new_path).map_err(|e| e.to_string())
+        fs::rename(p, &new_path).map_err(|e| e.to_string())?;
+        relocate_album_paths(&app_handle, |album_path| match Path::new(album_path).strip_prefix(p) {
+            Ok(relative) => Some(new_path.join(relative).to_string_lossy().into_owned()),
+            Err(_) => Some(album_path.to_string()),
+        });
+        Ok(())
     } else {
         Err("Could not determine parent directory.".to_string())
     }
 }
 
 #[tauri::command]
-pub fn delete_folder(path: String) -> Result<(), String> {
-    trash::delete(&path).map_err(|e| e.to_string())
+pub fn delete_folder(path: String, app_handle: AppHandle) -> Result<(), String> {
+    trash::delete(&path).map_err(|e| e.to_string())?;
+    let folder = Path::new(&path);
+    relocate_album_paths(&app_handle, |album_path| {
+        (!Path::new(album_path).starts_with(folder)).then(|| album_path.to_string())
+    });
+    Ok(())
+}
+
+// Merged results get a counter appended rather than replacing an earlier result and its edits.
+pub fn unique_output_path(dir: &Path, stem: &str, extension: &str) -> PathBuf {
+    let mut output_path = dir.join(format!("{}.{}", stem, extension));
+    let mut counter = 2;
+    while output_path.exists() || get_sidecar_path(&output_path.to_string_lossy()).exists() {
+        output_path = dir.join(format!("{}_{}.{}", stem, counter, extension));
+        counter += 1;
+    }
+    output_path
 }
 
 #[tauri::command]
@@ -680,7 +918,7 @@ pub fn copy_files(source_paths: Vec<String>, destination_folder: String) -> Resu
 }
 
 #[tauri::command]
-pub fn move_files(source_paths: Vec<String>, destination_folder: String) -> Result<(), String> {
+pub fn move_files(source_paths: Vec<String>, destination_folder: String, app_handle: AppHandle) -> Result<(), String> {
     let dest_path = Path::new(&destination_folder);
     if !dest_path.is_dir() {
         return Err(format!(
@@ -691,6 +929,7 @@ pub fn move_files(source_paths: Vec<String>, destination_folder: String) -> Resu
 
     let mut files_to_delete = Vec::new();
     let mut sidecars_to_delete = Vec::new();
+    let mut moved = HashMap::new();
 
     for source_str in &source_paths {
         let source_path = Path::new(source_str);
@@ -706,6 +945,7 @@ pub fn move_files(source_paths: Vec<String>, destination_folder: String) -> Resu
 
             fs::copy(&source_path, &dest_file_path).map_err(|e| e.to_string())?;
             files_to_delete.push(source_path.to_path_buf());
+            moved.insert(source_str.clone(), dest_file_path.to_string_lossy().into_owned());
 
             let sidecar_path = get_sidecar_path(source_str);
             if sidecar_path.exists() {
@@ -720,10 +960,21 @@ pub fn move_files(source_paths: Vec<String>, destination_folder: String) -> Resu
 
     trash::delete_all(&files_to_delete).map_err(|e| e.to_string())?;
     trash::delete_all(&sidecars_to_delete).map_err(|e| e.to_string())?;
+    relocate_album_paths(&app_handle, |path| Some(moved.get(path).cloned().unwrap_or_else(|| path.to_string())));
 
     Ok(())
 }
 
+static SIDECAR_LOCKS: Lazy<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Serializes read-modify-write cycles on one sidecar, so an edit saved while a batch operation
+// updates the same file isn't lost. Locks nobody holds are dropped on the next lookup.
+pub fn sidecar_lock(path: &str) -> Arc<Mutex<()>> {
+    let mut locks = SIDECAR_LOCKS.lock().unwrap();
+    locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+    locks.entry(get_sidecar_path(path)).or_default().clone()
+}
+
 #[tauri::command]
 pub fn save_metadata_and_update_thumbnail(
     path: String,
@@ -732,6 +983,8 @@ pub fn save_metadata_and_update_thumbnail(
     state: tauri::State<AppState>,
 ) -> Result<(), String> {
     let sidecar_path = get_sidecar_path(&path);
+    let lock = sidecar_lock(&path);
+    let guard = lock.lock().unwrap();
 
     let mut metadata: ImageMetadata = if sidecar_path.exists() {
         fs::read_to_string(&sidecar_path)
@@ -747,6 +1000,16 @@ pub fn save_metadata_and_update_thumbnail(
 
     let json_string = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
     std::fs::write(sidecar_path, json_string).map_err(|e| e.to_string())?;
+    drop(guard);
+
+    if let Err(e) = edit_history::record_snapshot(
+        &app_handle,
+        &path,
+        metadata.active_version.as_deref().unwrap_or_default(),
+        &metadata.adjustments,
+    ) {
+        eprintln!("Failed to record edit history for '{}': {}", path, e);
+    }
 
     let loaded_image_lock = state.original_image.lock().unwrap();
     let preloaded_image_option = if let Some(loaded_image) = loaded_image_lock.as_ref() {
@@ -780,8 +1043,9 @@ pub fn save_metadata_and_update_thumbnail(
             &path_clone,
             &thumb_cache_dir,
             gpu_context.as_ref(),
-            preloaded_image_option.as_ref(),
+            preloaded_image_option.as_deref(),
             true,
+            ThumbnailRendition::Small,
         );
 
         if let Some((thumbnail_data, rating)) = result {
@@ -809,6 +1073,8 @@ pub fn apply_adjustments_to_paths(
 ) -> Result<(), String> {
     paths.par_iter().for_each(|path| {
         let sidecar_path = get_sidecar_path(path);
+        let lock = sidecar_lock(path);
+        let _guard = lock.lock().unwrap();
 
         let mut existing_metadata: ImageMetadata = if sidecar_path.exists() {
             fs::read_to_string(&sidecar_path)
@@ -840,6 +1106,10 @@ pub fn apply_adjustments_to_paths(
         }
     });
 
+    if adjustments.get("rating").is_some() {
+        xmp_sidecar::sync_if_enabled(&app_handle, &paths);
+    }
+
     thread::spawn(move || {
         let _ = generate_thumbnails_progressive(paths, app_handle);
     });
@@ -854,6 +1124,8 @@ pub fn reset_adjustments_for_paths(
 ) -> Result<(), String> {
     paths.par_iter().for_each(|path| {
         let sidecar_path = get_sidecar_path(path);
+        let lock = sidecar_lock(path);
+        let _guard = lock.lock().unwrap();
 
         let mut existing_metadata: ImageMetadata = if sidecar_path.exists() {
             fs::read_to_string(&sidecar_path)
@@ -951,13 +1223,47 @@ pub fn apply_auto_adjustments_to_paths(
     Ok(())
 }
 
+#[tauri::command]
+pub fn set_flag_for_paths(
+    paths: Vec<String>,
+    flag: Option<ImageFlag>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    paths.par_iter().for_each(|path| {
+        let sidecar_path = get_sidecar_path(path);
+        let lock = sidecar_lock(path);
+        let _guard = lock.lock().unwrap();
+
+        let mut metadata: ImageMetadata = if sidecar_path.exists() {
+            fs::read_to_string(&sidecar_path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_default()
+        } else {
+            ImageMetadata::default()
+        };
+
+        metadata.flag = flag;
+
+        if let Ok(json_string) = serde_json::to_string_pretty(&metadata) {
+            let _ = std::fs::write(sidecar_path, json_string);
+        }
+    });
+
+    xmp_sidecar::sync_if_enabled(&app_handle, &paths);
+    Ok(())
+}
+
 #[tauri::command]
 pub fn set_color_label_for_paths(
     paths: Vec<String>,
     color: Option<String>,
+    app_handle: AppHandle,
 ) -> Result<(), String> {
     paths.par_iter().for_each(|path| {
         let sidecar_path = get_sidecar_path(path);
+        let lock = sidecar_lock(path);
+        let _guard = lock.lock().unwrap();
 
         let mut metadata: ImageMetadata = if sidecar_path.exists() {
             fs::read_to_string(&sidecar_path)
@@ -988,6 +1294,7 @@ pub fn set_color_label_for_paths(
         }
     });
 
+    xmp_sidecar::sync_if_enabled(&app_handle, &paths);
     Ok(())
 }
 
@@ -1033,6 +1340,215 @@ pub fn save_presets(presets: Vec<PresetItem>, app_handle: AppHandle) -> Result<(
     fs::write(path, json_string).map_err(|e| e.to_string())
 }
 
+fn get_export_presets_path(app_handle: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let presets_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("presets");
+
+    if !presets_dir.exists() {
+        fs::create_dir_all(&presets_dir).map_err(|e| e.to_string())?;
+    }
+
+    Ok(presets_dir.join("export_presets.json"))
+}
+
+#[tauri::command]
+pub fn load_export_presets(app_handle: AppHandle) -> Result<Vec<ExportPreset>, String> {
+    let path = get_export_presets_path(&app_handle)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_export_preset(mut preset: ExportPreset, app_handle: AppHandle) -> Result<Vec<ExportPreset>, String> {
+    let name = preset.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Export preset name cannot be empty.".to_string());
+    }
+    preset.name = name;
+
+    let mut presets = load_export_presets(app_handle.clone())?;
+
+    if let Some(existing) = presets.iter_mut().find(|p| (!preset.id.is_empty() && p.id == preset.id) || p.name == preset.name) {
+        preset.id = existing.id.clone();
+        *existing = preset;
+    } else {
+        if preset.id.is_empty() {
+            preset.id = Uuid::new_v4().to_string();
+        }
+        presets.push(preset);
+    }
+
+    let path = get_export_presets_path(&app_handle)?;
+    let json_string = serde_json::to_string_pretty(&presets).map_err(|e| e.to_string())?;
+    fs::write(path, json_string).map_err(|e| e.to_string())?;
+
+    Ok(presets)
+}
+
+#[tauri::command]
+pub fn delete_export_preset(id: String, app_handle: AppHandle) -> Result<Vec<ExportPreset>, String> {
+    let mut presets = load_export_presets(app_handle.clone())?;
+    presets.retain(|p| p.id != id);
+
+    let path = get_export_presets_path(&app_handle)?;
+    let json_string = serde_json::to_string_pretty(&presets).map_err(|e| e.to_string())?;
+    fs::write(path, json_string).map_err(|e| e.to_string())?;
+
+    Ok(presets)
+}
+
+fn get_albums_path(app_handle: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    if !data_dir.exists() {
+        fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    }
+
+    Ok(data_dir.join("albums.json"))
+}
+
+fn save_albums(albums: &[Album], app_handle: &AppHandle) -> Result<(), String> {
+    let path = get_albums_path(app_handle)?;
+    let json_string = serde_json::to_string_pretty(albums).map_err(|e| e.to_string())?;
+    fs::write(path, json_string).map_err(|e| e.to_string())
+}
+
+// Keeps album entries pointing at their files after file operations. `relocate` returns the new
+// path of an entry, or None when the file is gone. Album bookkeeping never fails the file operation.
+fn relocate_album_paths(app_handle: &AppHandle, relocate: impl Fn(&str) -> Option<String>) {
+    let result = load_albums(app_handle.clone()).and_then(|mut albums| {
+        let mut changed = false;
+        for album in albums.iter_mut() {
+            let mut seen = HashSet::new();
+            let paths: Vec<String> = album
+                .paths
+                .iter()
+                .filter_map(|path| relocate(path))
+                .filter(|path| seen.insert(path.clone()))
+                .collect();
+            if paths != album.paths {
+                album.paths = paths;
+                changed = true;
+            }
+        }
+        if changed { save_albums(&albums, app_handle) } else { Ok(()) }
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to update albums: {}", e);
+    }
+}
+
+fn update_album(
+    id: &str,
+    app_handle: &AppHandle,
+    update: impl FnOnce(&mut Album) -> Result<(), String>,
+) -> Result<Vec<Album>, String> {
+    let mut albums = load_albums(app_handle.clone())?;
+    let album = albums
+        .iter_mut()
+        .find(|a| a.id == id)
+        .ok_or_else(|| format!("Album not found: {}", id))?;
+    update(album)?;
+    save_albums(&albums, app_handle)?;
+    Ok(albums)
+}
+
+#[tauri::command]
+pub fn load_albums(app_handle: AppHandle) -> Result<Vec<Album>, String> {
+    let path = get_albums_path(&app_handle)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn create_album(name: String, paths: Option<Vec<String>>, app_handle: AppHandle) -> Result<Vec<Album>, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Album name cannot be empty.".to_string());
+    }
+
+    let mut albums = load_albums(app_handle.clone())?;
+    let mut unique_paths = Vec::new();
+    for path in paths.unwrap_or_default() {
+        if !unique_paths.contains(&path) {
+            unique_paths.push(path);
+        }
+    }
+    albums.push(Album {
+        id: Uuid::new_v4().to_string(),
+        name,
+        paths: unique_paths,
+    });
+    save_albums(&albums, &app_handle)?;
+    Ok(albums)
+}
+
+#[tauri::command]
+pub fn rename_album(id: String, name: String, app_handle: AppHandle) -> Result<Vec<Album>, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Album name cannot be empty.".to_string());
+    }
+    update_album(&id, &app_handle, |album| {
+        album.name = name;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn delete_album(id: String, app_handle: AppHandle) -> Result<Vec<Album>, String> {
+    let mut albums = load_albums(app_handle.clone())?;
+    albums.retain(|a| a.id != id);
+    save_albums(&albums, &app_handle)?;
+    Ok(albums)
+}
+
+#[tauri::command]
+pub fn add_paths_to_album(id: String, paths: Vec<String>, app_handle: AppHandle) -> Result<Vec<Album>, String> {
+    update_album(&id, &app_handle, |album| {
+        for path in paths {
+            if !album.paths.contains(&path) {
+                album.paths.push(path);
+            }
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn remove_paths_from_album(id: String, paths: Vec<String>, app_handle: AppHandle) -> Result<Vec<Album>, String> {
+    let to_remove: HashSet<String> = paths.into_iter().collect();
+    update_album(&id, &app_handle, |album| {
+        album.paths.retain(|p| !to_remove.contains(p));
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn reorder_album(id: String, paths: Vec<String>, app_handle: AppHandle) -> Result<Vec<Album>, String> {
+    update_album(&id, &app_handle, |album| {
+        let current: HashSet<&String> = album.paths.iter().collect();
+        let requested: HashSet<&String> = paths.iter().collect();
+        if current != requested || paths.len() != album.paths.len() {
+            return Err("The new order must contain exactly the album's current images.".to_string());
+        }
+        album.paths = paths;
+        Ok(())
+    })
+}
+
 fn get_settings_path(app_handle: &AppHandle) -> Result<std::path::PathBuf, String> {
     let settings_dir = app_handle
         .path()
@@ -1060,7 +1576,13 @@ pub fn load_settings(app_handle: AppHandle) -> Result<AppSettings, String> {
 pub fn save_settings(settings: AppSettings, app_handle: AppHandle) -> Result<(), String> {
     let path = get_settings_path(&app_handle)?;
     let json_string = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    fs::write(path, json_string).map_err(|e| e.to_string())
+    fs::write(path, json_string).map_err(|e| e.to_string())?;
+    http_api::apply_http_api(&app_handle, &settings);
+    crate::apply_display_profile(&app_handle, &settings);
+    custom_effects::apply_custom_effect(&app_handle, &settings);
+    raw_processing::apply_defect_pixel_settings(&settings);
+    watch_import::apply_watch_folders(&app_handle, settings.watch_folders.unwrap_or_default());
+    Ok(())
 }
 
 #[tauri::command]
@@ -1210,11 +1732,31 @@ pub fn show_in_finder(path: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn delete_files_from_disk(paths: Vec<String>) -> Result<(), String> {
+pub fn delete_files_from_disk(
+    paths: Vec<String>,
+    rejected_in_folder: Option<String>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let mut paths = paths;
+    if let Some(folder) = rejected_in_folder {
+        let rejected = scan_images_in_dir(folder)?
+            .into_iter()
+            .filter(|image| image.flag == Some(ImageFlag::Reject))
+            .map(|image| image.path)
+            .filter(|path| !paths.contains(path))
+            .collect::<Vec<_>>();
+        paths.extend(rejected);
+    }
+    if paths.is_empty() {
+        return Ok(());
+    }
+
     trash::delete_all(&paths).map_err(|e| e.to_string())?;
+    let deleted: HashSet<&str> = paths.iter().map(String::as_str).collect();
+    relocate_album_paths(&app_handle, |path| (!deleted.contains(path)).then(|| path.to_string()));
 
-    for path in paths {
-        let sidecar_path = get_sidecar_path(&path);
+    for path in &paths {
+        let sidecar_path = get_sidecar_path(path);
         if sidecar_path.exists() {
             let _ = trash::delete(&sidecar_path);
         }
@@ -1224,7 +1766,7 @@ pub fn delete_files_from_disk(paths: Vec<String>) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn delete_files_with_associated(paths: Vec<String>) -> Result<(), String> {
+pub fn delete_files_with_associated(paths: Vec<String>, app_handle: AppHandle) -> Result<(), String> {
     let mut files_to_delete = HashSet::new();
 
     for path_str in &paths {
@@ -1248,7 +1790,7 @@ pub fn delete_files_with_associated(paths: Vec<String>) -> Result<(), String> {
                 }
             }
         } else {
-            if is_supported_image_file(path_str) {
+            if is_supported_library_file(path_str) {
                 files_to_delete.insert(path_str.clone());
             }
         }
@@ -1260,6 +1802,8 @@ pub fn delete_files_with_associated(paths: Vec<String>) -> Result<(), String> {
     }
 
     trash::delete_all(&final_paths_to_delete).map_err(|e| e.to_string())?;
+    let deleted: HashSet<&str> = final_paths_to_delete.iter().map(String::as_str).collect();
+    relocate_album_paths(&app_handle, |path| (!deleted.contains(path)).then(|| path.to_string()));
 
     for path in final_paths_to_delete {
         let sidecar_path = get_sidecar_path(&path);
@@ -1324,8 +1868,7 @@ pub fn get_cached_or_generate_thumbnail_image(
         .map_err(|e| anyhow::anyhow!(e))?;
 
     if let Some(cache_hash) = get_cache_key_hash(path_str) {
-        let cache_filename = format!("{}.jpg", cache_hash);
-        let cache_path = thumb_cache_dir.join(cache_filename);
+        let cache_path = thumb_cache_dir.join(ThumbnailRendition::Small.cache_filename(&cache_hash));
 
         if cache_path.exists() {
             if let Ok(image) = image::open(&cache_path) {
@@ -1335,8 +1878,7 @@ pub fn get_cached_or_generate_thumbnail_image(
         }
 
         let thumb_image = generate_thumbnail_data(path_str, gpu_context, None)?;
-        let thumb_data = encode_thumbnail(&thumb_image)?;
-        fs::write(&cache_path, &thumb_data)?;
+        cache_thumbnail_renditions(&thumb_image, &thumb_cache_dir, &cache_hash, ThumbnailRendition::Small)?;
 
         Ok(thumb_image)
     } else {
@@ -1344,6 +1886,61 @@ pub fn get_cached_or_generate_thumbnail_image(
     }
 }
 
+// Copies one file into the destination using the import settings and returns where it ended up.
+pub fn import_file(
+    source_path_str: &str,
+    destination_folder: &str,
+    settings: &ImportSettings,
+    sequence: usize,
+    total: usize,
+) -> Result<PathBuf, String> {
+    let source_path = Path::new(source_path_str);
+    if !source_path.exists() {
+        return Err(format!("Source file not found: {}", source_path_str));
+    }
+
+    let file_date = capture_time::file_date(source_path);
+
+    let mut final_dest_folder = PathBuf::from(destination_folder);
+    if settings.organize_by_date {
+        let date_format_str = settings.date_folder_format
+            .replace("YYYY", "%Y")
+            .replace("MM", "%m")
+            .replace("DD", "%d");
+        let subfolder = file_date.format(&date_format_str).to_string();
+        final_dest_folder.push(subfolder);
+    }
+
+    fs::create_dir_all(&final_dest_folder).map_err(|e| format!("Failed to create destination folder: {}", e))?;
+
+    let new_stem = generate_filename_from_template(&settings.filename_template, source_path, sequence, total, &file_date);
+    let extension = source_path.extension().and_then(|s| s.to_str()).unwrap_or("");
+    let new_filename = format!("{}.{}", new_stem, extension);
+    let dest_file_path = final_dest_folder.join(new_filename);
+
+    if dest_file_path.exists() {
+        return Err(format!("File already exists at destination: {}", dest_file_path.display()));
+    }
+
+    fs::copy(source_path, &dest_file_path).map_err(|e| e.to_string())?;
+    let source_sidecar = get_sidecar_path(source_path_str);
+    if source_sidecar.exists() {
+        if let Some(dest_str) = dest_file_path.to_str() {
+            let dest_sidecar = get_sidecar_path(dest_str);
+            fs::copy(&source_sidecar, &dest_sidecar).map_err(|e| e.to_string())?;
+        }
+    }
+
+    if settings.delete_after_import {
+        trash::delete(source_path).map_err(|e| e.to_string())?;
+        if source_sidecar.exists() {
+            trash::delete(source_sidecar).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(dest_file_path)
+}
+
 #[tauri::command]
 pub async fn import_files(
     source_paths: Vec<String>,
@@ -1364,77 +1961,7 @@ pub async fn import_files(
                 serde_json::json!({ "current": i, "total": total_files, "path": source_path_str }),
             );
 
-            let import_result: Result<(), String> = (|| {
-                let source_path = Path::new(source_path_str);
-                if !source_path.exists() {
-                    return Err(format!("Source file not found: {}", source_path_str));
-                }
-
-                let file_date: DateTime<Utc> = Metadata::new_from_path(source_path)
-                    .ok()
-                    .and_then(|metadata| {
-                        metadata
-                            .get_tag(&ExifTag::DateTimeOriginal("".to_string()))
-                            .next()
-                            .and_then(|tag| {
-                                if let &ExifTag::DateTimeOriginal(ref dt_str) = tag {
-                                    chrono::NaiveDateTime::parse_from_str(dt_str, "%Y:%m:%d %H:%M:%S")
-                                        .ok()
-                                        .map(|dt| DateTime::from_naive_utc_and_offset(dt, Utc))
-                                } else {
-                                    None
-                                }
-                            })
-                    })
-                    .unwrap_or_else(|| {
-                        fs::metadata(source_path)
-                            .ok()
-                            .and_then(|m| m.created().ok())
-                            .map(DateTime::<Utc>::from)
-                            .unwrap_or_else(Utc::now)
-                    });
-
-                let mut final_dest_folder = PathBuf::from(&destination_folder);
-                if settings.organize_by_date {
-                    let date_format_str = settings.date_folder_format
-                        .replace("YYYY", "%Y")
-                        .replace("MM", "%m")
-                        .replace("DD", "%d");
-                    let subfolder = file_date.format(&date_format_str).to_string();
-                    final_dest_folder.push(subfolder);
-                }
-
-                fs::create_dir_all(&final_dest_folder).map_err(|e| format!("Failed to create destination folder: {}", e))?;
-
-                let new_stem = generate_filename_from_template(&settings.filename_template, source_path, i + 1, total_files, &file_date);
-                let extension = source_path.extension().and_then(|s| s.to_str()).unwrap_or("");
-                let new_filename = format!("{}.{}", new_stem, extension);
-                let dest_file_path = final_dest_folder.join(new_filename);
-
-                if dest_file_path.exists() {
-                    return Err(format!("File already exists at destination: {}", dest_file_path.display()));
-                }
-
-                fs::copy(source_path, &dest_file_path).map_err(|e| e.to_string())?;
-                let source_sidecar = get_sidecar_path(source_path_str);
-                if source_sidecar.exists() {
-                    if let Some(dest_str) = dest_file_path.to_str() {
-                        let dest_sidecar = get_sidecar_path(dest_str);
-                        fs::copy(&source_sidecar, &dest_sidecar).map_err(|e| e.to_string())?;
-                    }
-                }
-
-                if settings.delete_after_import {
-                    trash::delete(source_path).map_err(|e| e.to_string())?;
-                    if source_sidecar.exists() {
-                        trash::delete(source_sidecar).map_err(|e| e.to_string())?;
-                    }
-                }
-
-                Ok(())
-            })();
-
-            if let Err(e) = import_result {
+            if let Err(e) = import_file(source_path_str, &destination_folder, &settings, i + 1, total_files) {
                 eprintln!("Failed to import {}: {}", source_path_str, e);
                 let _ = app_handle.emit("import-error", e);
                 return;
@@ -1451,6 +1978,16 @@ pub async fn import_files(
     Ok(())
 }
 
+const EXIF_FILENAME_TOKENS: [&str; 4] = ["{camera}", "{lens}", "{iso}", "{focal}"];
+
+fn filename_safe(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '-' } else { c })
+        .collect()
+}
+
 pub fn generate_filename_from_template(
     template: &str,
     original_path: &std::path::Path,
@@ -1470,12 +2007,30 @@ pub fn generate_filename_from_template(
     result = result.replace("{DD}", &local_date.format("%d").to_string());
     result = result.replace("{hh}", &local_date.format("%H").to_string());
     result = result.replace("{mm}", &local_date.format("%M").to_string());
+    result = result.replace("{sequence_total}", &total.to_string());
+
+    // EXIF and the sidecar are only read when the template asks for them.
+    if EXIF_FILENAME_TOKENS.iter().any(|token| result.contains(token)) {
+        let exif = catalog::read_catalog_exif(original_path);
+        let text = |value: Option<&str>| value.map(filename_safe).filter(|v| !v.is_empty()).unwrap_or_else(|| "unknown".to_string());
+        result = result.replace("{camera}", &text(exif.camera_model.as_deref()));
+        result = result.replace("{lens}", &text(exif.lens_model.as_deref()));
+        result = result.replace("{iso}", &exif.iso.map(|iso| iso.to_string()).unwrap_or_else(|| "unknown".to_string()));
+        result = result.replace(
+            "{focal}",
+            &exif.focal_length.map(|f| format!("{}mm", f.round() as i64)).unwrap_or_else(|| "unknown".to_string()),
+        );
+    }
+    if result.contains("{rating}") {
+        let rating = iptc_metadata::read_metadata(&original_path.to_string_lossy()).rating;
+        result = result.replace("{rating}", &rating.to_string());
+    }
 
     result
 }
 
 #[tauri::command]
-pub fn rename_files(paths: Vec<String>, name_template: String) -> Result<Vec<String>, String> {
+pub fn rename_files(paths: Vec<String>, name_template: String, app_handle: AppHandle) -> Result<Vec<String>, String> {
     if paths.is_empty() {
         return Ok(Vec::new());
     }
@@ -1492,29 +2047,7 @@ pub fn rename_files(paths: Vec<String>, name_template: String) -> Result<Vec<Str
         let parent = original_path.parent().ok_or("Could not get parent directory")?;
         let extension = original_path.extension().and_then(|s| s.to_str()).unwrap_or("");
 
-        let file_date: DateTime<Utc> = Metadata::new_from_path(original_path)
-            .ok()
-            .and_then(|metadata| {
-                metadata
-                    .get_tag(&ExifTag::DateTimeOriginal("".to_string()))
-                    .next()
-                    .and_then(|tag| {
-                        if let &ExifTag::DateTimeOriginal(ref dt_str) = tag {
-                            chrono::NaiveDateTime::parse_from_str(dt_str, "%Y:%m:%d %H:%M:%S")
-                                .ok()
-                                .map(|dt| DateTime::from_naive_utc_and_offset(dt, Utc))
-                        } else {
-                            None
-                        }
-                    })
-            })
-            .unwrap_or_else(|| {
-                fs::metadata(original_path)
-                    .ok()
-                    .and_then(|m| m.created().ok())
-                    .map(DateTime::<Utc>::from)
-                    .unwrap_or_else(Utc::now)
-            });
+        let file_date = capture_time::file_date(original_path);
 
         let new_stem = generate_filename_from_template(&name_template, original_path, i + 1, paths.len(), &file_date);
         let new_filename = format!("{}.{}", new_stem, extension);
@@ -1527,8 +2060,13 @@ pub fn rename_files(paths: Vec<String>, name_template: String) -> Result<Vec<Str
         operations.push((original_path.to_path_buf(), new_path));
     }
 
+    let mut renamed = HashMap::new();
     for (original_path, new_path) in operations {
         fs::rename(&original_path, &new_path).map_err(|e| e.to_string())?;
+        renamed.insert(
+            original_path.to_string_lossy().into_owned(),
+            new_path.to_string_lossy().into_owned(),
+        );
 
         let original_sidecar = get_sidecar_path(original_path.to_str().unwrap());
         if original_sidecar.exists() {
@@ -1537,6 +2075,7 @@ pub fn rename_files(paths: Vec<String>, name_template: String) -> Result<Vec<Str
         }
         new_paths.push(new_path.to_string_lossy().into_owned());
     }
+    relocate_album_paths(&app_handle, |path| Some(renamed.get(path).cloned().unwrap_or_else(|| path.to_string())));
 
     Ok(new_paths)
 }
\ No newline at end of file
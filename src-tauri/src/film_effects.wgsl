@@ -0,0 +1,130 @@
+struct FilmEffectsParams {
+    halation_strength: f32,
+    halation_threshold: f32,
+    bloom_strength: f32,
+    radius: f32,
+    halation_tint_r: f32,
+    halation_tint_g: f32,
+    halation_tint_b: f32,
+    grain_size: f32,
+    grain_red: f32,
+    grain_green: f32,
+    grain_blue: f32,
+    pass_index: u32,
+    tile_offset_x: u32,
+    tile_offset_y: u32,
+    offset_x: u32,
+    offset_y: u32,
+}
+
+// Pass 0 extracts the highlights of the input tile and blurs them horizontally. Pass 1 blurs them
+// vertically, adds them back as tinted halation and neutral bloom, and adds per-channel grain.
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var blur_texture: texture_2d<f32>;
+@group(0) @binding(2) var output_texture: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(3) var<uniform> params: FilmEffectsParams;
+
+const BLUR_TAPS: i32 = 16;
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let cutoff = vec3<f32>(0.04045);
+    let a = vec3<f32>(0.055);
+    let higher = pow((c + a) / (1.0 + a), vec3<f32>(2.4));
+    let lower = c / 12.92;
+    return select(higher, lower, c <= cutoff);
+}
+
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let c_clamped = clamp(c, vec3<f32>(0.0), vec3<f32>(1.0));
+    let cutoff = vec3<f32>(0.0031308);
+    let a = vec3<f32>(0.055);
+    let higher = (1.0 + a) * pow(c_clamped, vec3<f32>(1.0 / 2.4)) - a;
+    let lower = c_clamped * 12.92;
+    return select(higher, lower, c_clamped <= cutoff);
+}
+
+fn get_luma(c: vec3<f32>) -> f32 {
+    return dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+fn highlights(coords: vec2<i32>) -> vec3<f32> {
+    let color = srgb_to_linear(textureLoad(source_texture, coords, 0).rgb);
+    let weight = max(get_luma(color) - params.halation_threshold, 0.0) / max(1.0 - params.halation_threshold, 0.001);
+    return color * weight;
+}
+
+fn blur_weight(tap: i32) -> f32 {
+    let x = f32(tap) / (f32(BLUR_TAPS) * 0.5);
+    return exp(-0.5 * x * x);
+}
+
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
+
+fn value_noise(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let top = mix(hash(i), hash(i + vec2<f32>(1.0, 0.0)), u.x);
+    let bottom = mix(hash(i + vec2<f32>(0.0, 1.0)), hash(i + vec2<f32>(1.0, 1.0)), u.x);
+    return mix(top, bottom, u.y);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let out_dims = textureDimensions(output_texture);
+    if (id.x >= out_dims.x || id.y >= out_dims.y) {
+        return;
+    }
+
+    let coords = vec2<i32>(id.xy + vec2<u32>(params.offset_x, params.offset_y));
+    let max_coords = vec2<i32>(textureDimensions(source_texture) - 1u);
+    let tap_spacing = params.radius / f32(BLUR_TAPS);
+
+    if (params.pass_index == 0u) {
+        var blurred = vec3<f32>(0.0);
+        var total_weight = 0.0;
+        for (var tap = -BLUR_TAPS; tap <= BLUR_TAPS; tap = tap + 1) {
+            let x = clamp(coords.x + i32(round(f32(tap) * tap_spacing)), 0, max_coords.x);
+            let weight = blur_weight(tap);
+            blurred += highlights(vec2<i32>(x, coords.y)) * weight;
+            total_weight += weight;
+        }
+        textureStore(output_texture, vec2<i32>(id.xy), vec4<f32>(blurred / total_weight, 1.0));
+        return;
+    }
+
+    var glow = vec3<f32>(0.0);
+    var total_weight = 0.0;
+    for (var tap = -BLUR_TAPS; tap <= BLUR_TAPS; tap = tap + 1) {
+        let y = clamp(coords.y + i32(round(f32(tap) * tap_spacing)), 0, max_coords.y);
+        let weight = blur_weight(tap);
+        glow += textureLoad(blur_texture, vec2<i32>(coords.x, y), 0).rgb * weight;
+        total_weight += weight;
+    }
+    glow /= total_weight;
+
+    let original = textureLoad(source_texture, coords, 0);
+    let tint = vec3<f32>(params.halation_tint_r, params.halation_tint_g, params.halation_tint_b);
+    var color = srgb_to_linear(original.rgb);
+    color += glow * (tint * params.halation_strength + vec3<f32>(params.bloom_strength));
+    var result = linear_to_srgb(color);
+
+    // Every dye layer gets its own grain, which is what makes film grain look colored.
+    let grain_amounts = vec3<f32>(params.grain_red, params.grain_green, params.grain_blue);
+    if (any(grain_amounts > vec3<f32>(0.0))) {
+        let absolute = vec2<f32>(id.xy + vec2<u32>(params.tile_offset_x, params.tile_offset_y));
+        let p = absolute / max(params.grain_size, 0.5);
+        let noise = vec3<f32>(
+            value_noise(p),
+            value_noise(p + vec2<f32>(17.3, 41.9)),
+            value_noise(p + vec2<f32>(59.1, 7.7)),
+        ) - 0.5;
+        let luma = get_luma(result);
+        let luma_mask = smoothstep(0.0, 0.15, luma) * (1.0 - smoothstep(0.6, 1.0, luma));
+        result += noise * grain_amounts * 0.3 * luma_mask;
+    }
+
+    textureStore(output_texture, vec2<i32>(id.xy), vec4<f32>(clamp(result, vec3<f32>(0.0), vec3<f32>(1.0)), original.a));
+}
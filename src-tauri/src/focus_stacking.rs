@@ -0,0 +1,202 @@
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use image::{GrayImage, ImageBuffer, Luma, Rgb32FImage};
+use imageproc::filter::gaussian_blur_f32;
+use nalgebra::Matrix3;
+use rayon::prelude::*;
+use tauri::{AppHandle, Emitter};
+
+use crate::hdr_merging::{find_alignment, warp_to_reference};
+use crate::panorama_utils::blending::{collapse, gaussian_pyramid, laplacian_pyramid, PyramidLevel};
+use crate::panorama_utils::processing;
+
+const MAX_BLEND_BANDS: usize = 7;
+const MIN_BAND_SIZE: f64 = 16.0;
+const SHARPNESS_SIGMA: f32 = 3.0;
+
+struct FocusFrame {
+    filename: String,
+    image: Rgb32FImage,
+}
+
+fn load_frame(path: &str) -> Result<FocusFrame, String> {
+    let file_bytes = fs::read(path).map_err(|e| format!("Failed to read image {}: {}", path, e))?;
+    let image = crate::image_loader::load_base_image_from_bytes(&file_bytes, path, false)
+        .map_err(|e| format!("Failed to load image {}: {}", path, e))?
+        .to_rgb32f();
+    Ok(FocusFrame { filename: path.to_string(), image })
+}
+
+fn luma(pixel: &[f32; 3]) -> f32 {
+    0.2126 * pixel[0] + 0.7152 * pixel[1] + 0.0722 * pixel[2]
+}
+
+fn to_gray(image: &Rgb32FImage) -> GrayImage {
+    let (w, h) = image.dimensions();
+    GrayImage::from_fn(w, h, |x, y| Luma([(luma(&image.get_pixel(x, y).0).clamp(0.0, 1.0) * 255.0).round() as u8]))
+}
+
+// Local focus measure: smoothed magnitude of the Laplacian. Pixels outside the aligned frame get -1
+// so they never win.
+fn sharpness_map(samples: &[Option<[f32; 3]>], width: u32, height: u32) -> Vec<f32> {
+    let (w, h) = (width as usize, height as usize);
+    let at = |x: usize, y: usize| samples[y * w + x].map(|p| luma(&p));
+    let laplacian: ImageBuffer<Luma<f32>, Vec<f32>> = ImageBuffer::from_fn(width, height, |x, y| {
+        let (x, y) = (x as usize, y as usize);
+        if x == 0 || y == 0 || x + 1 >= w || y + 1 >= h {
+            return Luma([0.0]);
+        }
+        match (at(x, y), at(x - 1, y), at(x + 1, y), at(x, y - 1), at(x, y + 1)) {
+            (Some(c), Some(l), Some(r), Some(t), Some(b)) => Luma([(l + r + t + b - 4.0 * c).abs()]),
+            _ => Luma([0.0]),
+        }
+    });
+    let smoothed = gaussian_blur_f32(&laplacian, SHARPNESS_SIGMA);
+    smoothed
+        .into_raw()
+        .into_iter()
+        .zip(samples.iter())
+        .map(|(value, sample)| if sample.is_some() { value } else { -1.0 })
+        .collect()
+}
+
+pub fn stack_focus_images(paths: Vec<String>, app_handle: AppHandle) -> Result<Rgb32FImage, String> {
+    if paths.len() < 2 {
+        return Err("At least two focus-bracketed images are required for focus stacking.".to_string());
+    }
+
+    let start_time = Instant::now();
+    let _ = app_handle.emit("focus-stack-progress", "Loading focus bracket...");
+    println!("Starting focus stacking for {} images...", paths.len());
+
+    let frames: Vec<FocusFrame> = paths
+        .par_iter()
+        .map(|p| {
+            let _ = app_handle.emit("focus-stack-progress", format!("Loading '{}'", Path::new(p).file_name().unwrap_or_default().to_string_lossy()));
+            load_frame(p)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let (width, height) = frames[0].image.dimensions();
+    if frames.iter().any(|f| f.image.dimensions() != (width, height)) {
+        return Err("All focus-bracketed images must have the same dimensions.".to_string());
+    }
+    println!("Loaded frames in {:.2?}", start_time.elapsed());
+
+    // Focus breathing changes the scale across the bracket, so align everything to the middle frame.
+    let reference_index = frames.len() / 2;
+    let brief_pairs = processing::generate_brief_pairs();
+    let reference_gray = to_gray(&frames[reference_index].image);
+
+    let aligned: Vec<Vec<Option<[f32; 3]>>> = frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            let _ = app_handle.emit("focus-stack-progress", format!("Aligning image {} of {}", i + 1, frames.len()));
+            let homography = if i == reference_index {
+                Matrix3::identity()
+            } else {
+                find_alignment(&reference_gray, &to_gray(&frame.image), &brief_pairs).unwrap_or_else(|| {
+                    let warning = format!("Could not align '{}', stacking without alignment.", Path::new(&frame.filename).file_name().unwrap_or_default().to_string_lossy());
+                    println!("{}", warning);
+                    let _ = app_handle.emit("focus-stack-warning", warning);
+                    Matrix3::identity()
+                })
+            };
+            warp_to_reference(&frame.image, &homography, width, height)
+        })
+        .collect();
+    drop(frames);
+
+    let _ = app_handle.emit("focus-stack-progress", "Measuring sharpness...");
+    let sharpness: Vec<Vec<f32>> = aligned.iter().map(|samples| sharpness_map(samples, width, height)).collect();
+
+    let (w, h) = (width as usize, height as usize);
+    let labels: Vec<usize> = (0..w * h)
+        .into_par_iter()
+        .map(|i| {
+            sharpness
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1[i].partial_cmp(&b.1[i]).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(frame_index, _)| frame_index)
+                .unwrap_or(reference_index)
+        })
+        .collect();
+    drop(sharpness);
+
+    // Blend the sharpest-frame selection over a Laplacian pyramid so the transitions between
+    // focus slices don't show as hard seams or halos.
+    let bands = (((w.min(h) as f64) / MIN_BAND_SIZE).log2().floor() as usize).clamp(1, MAX_BLEND_BANDS);
+    let align = 1usize << bands;
+    let padded_w = w.div_ceil(align) * align;
+    let padded_h = h.div_ceil(align) * align;
+
+    let mut blended: Vec<PyramidLevel<[f32; 3]>> =
+        (0..=bands).map(|level| PyramidLevel::new(padded_w >> level, padded_h >> level)).collect();
+    let mut weights: Vec<PyramidLevel<f32>> =
+        (0..=bands).map(|level| PyramidLevel::new(padded_w >> level, padded_h >> level)).collect();
+
+    for (frame_index, samples) in aligned.iter().enumerate() {
+        let _ = app_handle.emit("focus-stack-progress", format!("Blending image {} of {}", frame_index + 1, aligned.len()));
+        let mut color = PyramidLevel::<[f32; 3]>::new(padded_w, padded_h);
+        let mut mask = PyramidLevel::<f32>::new(padded_w, padded_h);
+        color.data.par_chunks_mut(padded_w)
+            .zip(mask.data.par_chunks_mut(padded_w))
+            .enumerate()
+            .for_each(|(y, (color_row, mask_row))| {
+                let sy = y.min(h - 1);
+                for x in 0..padded_w {
+                    let i = sy * w + x.min(w - 1);
+                    // Uncovered pixels borrow from the reference so the pyramid sees no black borders.
+                    color_row[x] = samples[i].or(aligned[reference_index][i]).unwrap_or([0.0; 3]);
+                    if labels[i] == frame_index {
+                        mask_row[x] = 1.0;
+                    }
+                }
+            });
+
+        let color_pyramid = laplacian_pyramid(color, bands);
+        let mask_pyramid = gaussian_pyramid(mask, bands);
+        for (level, (color_level, mask_level)) in color_pyramid.iter().zip(mask_pyramid.iter()).enumerate() {
+            blended[level].data.par_iter_mut()
+                .zip(weights[level].data.par_iter_mut())
+                .zip(color_level.data.par_iter().zip(mask_level.data.par_iter()))
+                .for_each(|((target, weight_sum), (c, weight))| {
+                    if *weight > 0.0 {
+                        target[0] += c[0] * weight;
+                        target[1] += c[1] * weight;
+                        target[2] += c[2] * weight;
+                        *weight_sum += weight;
+                    }
+                });
+        }
+    }
+
+    let _ = app_handle.emit("focus-stack-progress", "Collapsing blend pyramid...");
+    for (blend_level, weight_level) in blended.iter_mut().zip(weights.iter()) {
+        blend_level.data.par_iter_mut().zip(weight_level.data.par_iter()).for_each(|(pixel, weight)| {
+            if *weight > 1e-6 {
+                *pixel = [pixel[0] / weight, pixel[1] / weight, pixel[2] / weight];
+            } else {
+                *pixel = [0.0; 3];
+            }
+        });
+    }
+    let result = collapse(blended);
+
+    let merged: Vec<f32> = (0..w * h)
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            let (x, y) = (i % w, i / w);
+            result.data[y * result.width + x].map(|c| c.clamp(0.0, 1.0))
+        })
+        .collect();
+
+    println!("Focus stacking completed in {:.2?}", start_time.elapsed());
+    let _ = app_handle.emit("focus-stack-progress", "Finalizing focus stack...");
+
+    Rgb32FImage::from_raw(width, height, merged).ok_or_else(|| "Failed to assemble focus-stacked image.".to_string())
+}
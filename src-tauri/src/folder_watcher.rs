@@ -0,0 +1,105 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::catalog::{sync_folder, with_catalog};
+use crate::file_management::generate_thumbnails_progressive;
+use crate::formats::is_supported_library_file;
+use crate::AppState;
+
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderChangedPayload {
+    pub folder: String,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+pub struct FolderWatcher {
+    folder: String,
+    _watcher: RecommendedWatcher,
+}
+
+fn apply_changes(folder: &str, paths: Vec<String>, app_handle: &AppHandle) {
+    let (changed, removed): (Vec<String>, Vec<String>) = paths.into_iter().partition(|p| Path::new(p).exists());
+
+    if let Err(e) = with_catalog(app_handle, |conn| sync_folder(conn, folder)) {
+        eprintln!("Failed to update catalog for '{}': {}", folder, e);
+    }
+    if !changed.is_empty() {
+        if let Err(e) = generate_thumbnails_progressive(changed.clone(), app_handle.clone()) {
+            eprintln!("Failed to regenerate thumbnails for '{}': {}", folder, e);
+        }
+    }
+
+    let _ = app_handle.emit(
+        "folder-changed",
+        FolderChangedPayload { folder: folder.to_string(), changed, removed },
+    );
+}
+
+fn handle_events(folder: String, rx: Receiver<notify::Result<Event>>, app_handle: AppHandle) {
+    let mut pending: HashSet<String> = HashSet::new();
+    loop {
+        let received = if pending.is_empty() {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            rx.recv_timeout(DEBOUNCE_INTERVAL)
+        };
+
+        match received {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Access(_)) {
+                    continue;
+                }
+                pending.extend(
+                    event
+                        .paths
+                        .iter()
+                        .filter_map(|p| p.to_str())
+                        .filter(|p| is_supported_library_file(p))
+                        .map(String::from),
+                );
+            }
+            Ok(Err(e)) => eprintln!("Folder watcher error for '{}': {}", folder, e),
+            Err(RecvTimeoutError::Timeout) => apply_changes(&folder, pending.drain().collect(), &app_handle),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+#[tauri::command]
+pub fn watch_folder(path: String, state: tauri::State<AppState>, app_handle: AppHandle) -> Result<(), String> {
+    let mut current = state.folder_watcher.lock().unwrap();
+    if current.as_ref().map_or(false, |w| w.folder == path) {
+        return Ok(());
+    }
+    *current = None;
+
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        let _ = tx.send(res);
+    })
+    .map_err(|e| e.to_string())?;
+    watcher
+        .watch(Path::new(&path), RecursiveMode::NonRecursive)
+        .map_err(|e| e.to_string())?;
+
+    let folder = path.clone();
+    std::thread::spawn(move || handle_events(folder, rx, app_handle));
+
+    *current = Some(FolderWatcher { folder: path, _watcher: watcher });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unwatch_folder(state: tauri::State<AppState>) {
+    *state.folder_watcher.lock().unwrap() = None;
+}
@@ -112,4 +112,23 @@ pub fn is_supported_image_file(path: &str) -> bool {
     } else {
         false
     }
-}
\ No newline at end of file
+}
+
+// Videos are listed in the library so mixed shoots can be browsed and organized, but never edited.
+pub const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "m4v", "avi", "mkv", "mts", "m2ts", "webm", "3gp"];
+
+pub fn is_video_file(path: &str) -> bool {
+    if let Some(ext) = std::path::Path::new(path)
+        .extension()
+        .and_then(|s| s.to_str())
+    {
+        let lower_ext = ext.to_lowercase();
+        VIDEO_EXTENSIONS.iter().any(|video_ext| *video_ext == lower_ext)
+    } else {
+        false
+    }
+}
+
+pub fn is_supported_library_file(path: &str) -> bool {
+    is_supported_image_file(path) || is_video_file(path)
+}
@@ -0,0 +1,380 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::BufReader;
+use std::path::Path;
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, TimeZone, Utc};
+use exif::Tag;
+use rayon::prelude::*;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, ToSql};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::capture_time::{capture_time_shift, EXIF_DATE_FORMAT};
+use crate::catalog::{ascii_field, escape_like, sync_folder, with_catalog};
+use crate::file_management::get_sidecar_path;
+use crate::image_processing::{GpsLocation, ImageMetadata};
+
+const CLUSTER_CELLS_PER_TILE: f64 = 4.0;
+const MAX_CLUSTER_ZOOM: u8 = 20;
+const GEOCODE_CACHE_PRECISION: f64 = 100.0;
+const NOMINATIM_REVERSE_URL: &str = "https://nominatim.openstreetmap.org/reverse";
+const DEFAULT_MAX_TRACK_GAP_SECONDS: i64 = 300;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GeotaggedImage {
+    pub path: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GpsCluster {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub count: usize,
+    pub images: Vec<GeotaggedImage>,
+    pub location_name: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackMatch {
+    pub path: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub capture_time: Option<String>,
+}
+
+struct TrackPoint {
+    time: DateTime<Utc>,
+    latitude: f64,
+    longitude: f64,
+}
+
+fn ensure_geocode_table(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS geocode_cache (
+             lat_key INTEGER NOT NULL,
+             lon_key INTEGER NOT NULL,
+             name TEXT NOT NULL,
+             PRIMARY KEY (lat_key, lon_key)
+         );",
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn geocode_key(latitude: f64, longitude: f64) -> (i64, i64) {
+    (
+        (latitude * GEOCODE_CACHE_PRECISION).round() as i64,
+        (longitude * GEOCODE_CACHE_PRECISION).round() as i64,
+    )
+}
+
+fn cached_location_name(conn: &Connection, latitude: f64, longitude: f64) -> Result<Option<String>, String> {
+    let (lat_key, lon_key) = geocode_key(latitude, longitude);
+    conn.query_row(
+        "SELECT name FROM geocode_cache WHERE lat_key = ?1 AND lon_key = ?2",
+        params![lat_key, lon_key],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn query_geotagged(conn: &Connection, root_path: Option<&str>) -> Result<Vec<GeotaggedImage>, String> {
+    let mut sql = "SELECT path, COALESCE(gps_latitude, exif_latitude), COALESCE(gps_longitude, exif_longitude)
+                   FROM images
+                   WHERE COALESCE(gps_latitude, exif_latitude) IS NOT NULL
+                     AND COALESCE(gps_longitude, exif_longitude) IS NOT NULL"
+        .to_string();
+    let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+    if let Some(root) = root_path {
+        let prefix = format!("{}{}", root.trim_end_matches(std::path::MAIN_SEPARATOR), std::path::MAIN_SEPARATOR);
+        sql.push_str(" AND (folder = ? OR folder LIKE ? ESCAPE '\\')");
+        values.push(Box::new(root.to_string()));
+        values.push(Box::new(format!("{}%", escape_like(&prefix))));
+    }
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            Ok(GeotaggedImage {
+                path: row.get(0)?,
+                latitude: row.get(1)?,
+                longitude: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+fn cluster_images(images: Vec<GeotaggedImage>, zoom: u8) -> Vec<GpsCluster> {
+    let cell_size = 360.0 / (2f64.powi(zoom.min(MAX_CLUSTER_ZOOM) as i32) * CLUSTER_CELLS_PER_TILE);
+
+    let mut cells: BTreeMap<(i64, i64), Vec<GeotaggedImage>> = BTreeMap::new();
+    for image in images {
+        let key = (
+            (image.latitude / cell_size).floor() as i64,
+            (image.longitude / cell_size).floor() as i64,
+        );
+        cells.entry(key).or_default().push(image);
+    }
+
+    cells
+        .into_values()
+        .map(|images| {
+            let count = images.len();
+            let latitude = images.iter().map(|i| i.latitude).sum::<f64>() / count as f64;
+            let longitude = images.iter().map(|i| i.longitude).sum::<f64>() / count as f64;
+            GpsCluster { latitude, longitude, count, images, location_name: None }
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_geotagged_images(
+    root_path: Option<String>,
+    zoom: Option<u8>,
+    app_handle: AppHandle,
+) -> Result<Vec<GpsCluster>, String> {
+    with_catalog(&app_handle, |conn| {
+        ensure_geocode_table(conn)?;
+        let images = query_geotagged(conn, root_path.as_deref())?;
+        let mut clusters = cluster_images(images, zoom.unwrap_or(2));
+        for cluster in clusters.iter_mut() {
+            cluster.location_name = cached_location_name(conn, cluster.latitude, cluster.longitude)?;
+        }
+        Ok(clusters)
+    })
+}
+
+#[tauri::command]
+pub fn set_gps_for_paths(
+    paths: Vec<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let gps = match (latitude, longitude) {
+        (Some(latitude), Some(longitude)) => {
+            if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+                return Err(format!("Invalid coordinates: {}, {}", latitude, longitude));
+            }
+            Some(GpsLocation { latitude, longitude })
+        }
+        (None, None) => None,
+        _ => return Err("Latitude and longitude must be set together.".to_string()),
+    };
+
+    paths.par_iter().try_for_each(|path| write_sidecar_gps(path, gps))?;
+    sync_folders(&app_handle, &paths)
+}
+
+fn write_sidecar_gps(path: &str, gps: Option<GpsLocation>) -> Result<(), String> {
+    let sidecar_path = get_sidecar_path(path);
+    let mut metadata: ImageMetadata = fs::read_to_string(&sidecar_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    metadata.gps = gps;
+    let json_string = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
+    fs::write(sidecar_path, json_string).map_err(|e| e.to_string())
+}
+
+fn sync_folders(app_handle: &AppHandle, paths: &[String]) -> Result<(), String> {
+    let folders: HashSet<String> = paths
+        .iter()
+        .filter_map(|p| Path::new(p).parent().map(|f| f.to_string_lossy().into_owned()))
+        .collect();
+    with_catalog(app_handle, |conn| {
+        for folder in &folders {
+            sync_folder(conn, folder)?;
+        }
+        Ok(())
+    })
+}
+
+pub fn read_sidecar_gps(path: &str) -> Option<GpsLocation> {
+    fs::read_to_string(get_sidecar_path(path))
+        .ok()
+        .and_then(|content| serde_json::from_str::<ImageMetadata>(&content).ok())
+        .and_then(|metadata| metadata.gps)
+}
+
+// Degrees, minutes and seconds as EXIF rationals (numerator, denominator).
+pub fn to_dms_rationals(value: f64) -> [(u32, u32); 3] {
+    let value = value.abs();
+    let degrees = value.floor();
+    let minutes = ((value - degrees) * 60.0).floor();
+    let seconds = (value - degrees) * 3600.0 - minutes * 60.0;
+    [(degrees as u32, 1), (minutes as u32, 1), ((seconds * 10000.0).round() as u32, 10000)]
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!("{}=", name);
+    let start = tag.find(&pattern)? + pattern.len();
+    let quote = tag[start..].chars().next()?;
+    let end = tag[start + 1..].find(quote)?;
+    Some(tag[start + 1..start + 1 + end].to_string())
+}
+
+fn parse_gpx(xml: &str) -> Vec<TrackPoint> {
+    let mut points = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find("<trkpt") {
+        let Some(end) = rest[start..].find("</trkpt>").map(|i| start + i) else { break; };
+        let element = &rest[start..end];
+        rest = &rest[end..];
+
+        let open_tag = &element[..element.find('>').unwrap_or(element.len())];
+        let latitude = attribute(open_tag, "lat").and_then(|v| v.parse::<f64>().ok());
+        let longitude = attribute(open_tag, "lon").and_then(|v| v.parse::<f64>().ok());
+        let time = element
+            .find("<time>")
+            .and_then(|i| element[i + 6..].find("</time>").map(|j| &element[i + 6..i + 6 + j]))
+            .and_then(|t| DateTime::parse_from_rfc3339(t.trim()).ok());
+        if let (Some(latitude), Some(longitude), Some(time)) = (latitude, longitude, time) {
+            points.push(TrackPoint { time: time.with_timezone(&Utc), latitude, longitude });
+        }
+    }
+    points.sort_by_key(|p| p.time);
+    points
+}
+
+// EXIF capture times are camera-local. The offset tag is used when the camera wrote one, otherwise
+// the offset the user gave for the camera clock. A capture-time shift set in RapidRAW is applied on top.
+fn capture_time_utc(path: &str, default_offset: FixedOffset) -> Option<DateTime<Utc>> {
+    let file = fs::File::open(path).ok()?;
+    let exif = exif::Reader::new().read_from_container(&mut BufReader::new(file)).ok()?;
+    let local = ascii_field(&exif, Tag::DateTimeOriginal)
+        .or_else(|| ascii_field(&exif, Tag::DateTime))
+        .and_then(|d| NaiveDateTime::parse_from_str(&d, EXIF_DATE_FORMAT).ok())?
+        + Duration::seconds(capture_time_shift(path));
+    let offset = ascii_field(&exif, Tag::OffsetTimeOriginal)
+        .and_then(|o| DateTime::parse_from_str(&format!("2000-01-01T00:00:00{}", o), "%Y-%m-%dT%H:%M:%S%:z").ok())
+        .map(|d| *d.offset())
+        .unwrap_or(default_offset);
+    offset.from_local_datetime(&local).single().map(|t| t.with_timezone(&Utc))
+}
+
+// Linear interpolation between the surrounding track points; nothing if the photo was taken
+// further than `max_gap` from the track.
+fn locate(track: &[TrackPoint], time: DateTime<Utc>, max_gap: i64) -> Option<GpsLocation> {
+    let next = track.partition_point(|p| p.time < time);
+    let after = track.get(next);
+    let before = next.checked_sub(1).and_then(|i| track.get(i));
+    match (before, after) {
+        (Some(a), Some(b)) if (b.time - a.time).num_seconds() <= max_gap * 2 => {
+            let span = (b.time - a.time).num_milliseconds() as f64;
+            let t = if span > 0.0 { (time - a.time).num_milliseconds() as f64 / span } else { 0.0 };
+            Some(GpsLocation {
+                latitude: a.latitude + (b.latitude - a.latitude) * t,
+                longitude: a.longitude + (b.longitude - a.longitude) * t,
+            })
+        }
+        _ => [before, after]
+            .into_iter()
+            .flatten()
+            .filter(|p| (p.time - time).num_seconds().abs() <= max_gap)
+            .min_by_key(|p| (p.time - time).num_seconds().abs())
+            .map(|p| GpsLocation { latitude: p.latitude, longitude: p.longitude }),
+    }
+}
+
+#[tauri::command]
+pub fn geotag_from_gpx(
+    paths: Vec<String>,
+    gpx_path: String,
+    camera_utc_offset_minutes: Option<i32>,
+    max_gap_seconds: Option<i64>,
+    app_handle: AppHandle,
+) -> Result<Vec<TrackMatch>, String> {
+    let xml = fs::read_to_string(&gpx_path).map_err(|e| format!("Failed to read GPX file: {}", e))?;
+    let track = parse_gpx(&xml);
+    if track.is_empty() {
+        return Err("The GPX file contains no timestamped track points.".to_string());
+    }
+    let default_offset = FixedOffset::east_opt(camera_utc_offset_minutes.unwrap_or(0) * 60)
+        .ok_or("Invalid camera time offset.")?;
+    let max_gap = max_gap_seconds.unwrap_or(DEFAULT_MAX_TRACK_GAP_SECONDS);
+
+    let matches: Vec<TrackMatch> = paths
+        .par_iter()
+        .map(|path| {
+            let capture_time = capture_time_utc(path, default_offset);
+            let location = capture_time.and_then(|time| locate(&track, time, max_gap));
+            if let Some(location) = location {
+                if let Err(e) = write_sidecar_gps(path, Some(location)) {
+                    eprintln!("Failed to write GPS for '{}': {}", path, e);
+                }
+            }
+            TrackMatch {
+                path: path.clone(),
+                latitude: location.map(|l| l.latitude),
+                longitude: location.map(|l| l.longitude),
+                capture_time: capture_time.map(|t| t.to_rfc3339()),
+            }
+        })
+        .collect();
+
+    sync_folders(&app_handle, &paths)?;
+    Ok(matches)
+}
+
+#[tauri::command]
+pub async fn reverse_geocode(latitude: f64, longitude: f64, app_handle: AppHandle) -> Result<Option<String>, String> {
+    let cached = with_catalog(&app_handle, |conn| {
+        ensure_geocode_table(conn)?;
+        cached_location_name(conn, latitude, longitude)
+    })?;
+    if cached.is_some() {
+        return Ok(cached);
+    }
+
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("RapidRAW/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response: serde_json::Value = client
+        .get(NOMINATIM_REVERSE_URL)
+        .query(&[
+            ("format", "jsonv2".to_string()),
+            ("zoom", "10".to_string()),
+            ("lat", latitude.to_string()),
+            ("lon", longitude.to_string()),
+        ])
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .error_for_status()
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let address = &response["address"];
+    let locality = ["city", "town", "village", "municipality", "county", "state"]
+        .iter()
+        .find_map(|key| address[*key].as_str());
+    let name = match (locality, address["country"].as_str()) {
+        (Some(locality), Some(country)) => Some(format!("{}, {}", locality, country)),
+        (Some(locality), None) => Some(locality.to_string()),
+        (None, Some(country)) => Some(country.to_string()),
+        (None, None) => response["display_name"].as_str().map(str::to_string),
+    };
+
+    if let Some(name) = &name {
+        let (lat_key, lon_key) = geocode_key(latitude, longitude);
+        with_catalog(&app_handle, |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO geocode_cache (lat_key, lon_key, name) VALUES (?1, ?2, ?3)",
+                params![lat_key, lon_key, name],
+            )
+            .map_err(|e| e.to_string())
+        })?;
+    }
+    Ok(name)
+}
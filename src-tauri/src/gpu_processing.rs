@@ -1,11 +1,234 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 use bytemuck;
-use image::{DynamicImage, GenericImageView, ImageBuffer, Rgba, Luma};
+use image::{imageops, DynamicImage, GenericImageView, ImageBuffer, Rgba, Luma};
+use serde::Deserialize;
 use wgpu::util::{DeviceExt, TextureDataOrder};
 
 use crate::AppState;
-use crate::image_processing::{AllAdjustments, GpuContext};
+use crate::color_profiles::HdrPreviewMode;
+use crate::image_processing::{
+    AllAdjustments, DenoiseParams, FilmEffectsParams, GlobalAdjustments, GpuContext, LensBlurParams, MaskAdjustments,
+    OpticalCenter,
+};
+use crate::lut_processing::Lut;
+
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct ProcessingUniforms {
+    global: GlobalAdjustments,
+    mask_count: u32,
+    tile_offset_x: u32,
+    tile_offset_y: u32,
+    input_offset_x: u32,
+    input_offset_y: u32,
+    full_width: u32,
+    full_height: u32,
+    hdr_output: u32,
+    optical_center_x: f32,
+    optical_center_y: f32,
+    lateral_ca_r: f32,
+    lateral_ca_b: f32,
+}
+
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct ScopesParams {
+    core_x: u32,
+    core_y: u32,
+    core_width: u32,
+    core_height: u32,
+    column_offset: u32,
+    scope_width: u32,
+    _pad0: u32,
+    _pad1: u32,
+}
+
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct PanoramaWarpParams {
+    pub inverse_rows: [[f32; 4]; 3],
+    pub projection_mode: u32,
+    pub focal: f32,
+    pub center_x: f32,
+    pub center_y: f32,
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub tile_offset_x: u32,
+    pub tile_offset_y: u32,
+}
+
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct PanoramaBlendParams {
+    pub region_x: u32,
+    pub region_y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub orientation: u32,
+    pub use_seam: u32,
+    pub new_is_dominant: u32,
+    pub feather_width: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CompareLayout {
+    Split,
+    SideBySide,
+}
+
+pub struct CompareSide<'a> {
+    pub image: &'a DynamicImage,
+    pub adjustments: AllAdjustments,
+    pub mask_bitmaps: &'a [ImageBuffer<Luma<u8>, Vec<u8>>],
+    pub lut: Option<&'a Lut>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipelineKind {
+    Adjustments,
+    AdjustmentsHdr,
+    AdjustmentsFloat,
+    LensBlur,
+    Denoise,
+    FilmEffects,
+    Scopes,
+    PanoramaWarp,
+    PanoramaBlend,
+}
+
+pub struct CachedPipeline {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+}
+
+const TILE_SIZE: u32 = 2048;
+const TILE_OVERLAP: u32 = 32;
+const NEIGHBORHOOD_HALO: u32 = 32;
+const LENS_BLUR_MAX_RADIUS_FRACTION: f32 = 0.03;
+const DENOISE_LEVELS: u32 = 5;
+// Reach of the a-trous kernel summed over all levels: 2 * (1 + 2 + 4 + 8 + 16).
+const DENOISE_HALO: u32 = 64;
+const FILM_EFFECTS_RADIUS_FRACTION: f32 = 0.01;
+const COMPARE_GAP: u32 = 16;
+const COMPARE_DIVIDER_WIDTH: u32 = 2;
+pub const SCOPE_BINS: usize = 256;
+const SCOPE_COUNTS_LEN: usize = 4 * SCOPE_BINS + 5 * SCOPE_BINS * SCOPE_BINS;
+// The hdr_output value in shader.wgsl for float tiles.
+const EXTENDED_SRGB_OUTPUT: u32 = 3;
+
+// A channel value of a rendered tile. HDR tiles are rendered to half floats holding the encoded
+// PQ/HLG signal in 0..1 and are read back as 16-bit code values. Float tiles hold extended sRGB
+// values for wide gamut exports.
+trait TileSample: Copy + Default + Into<f32> {
+    const FORMAT: wgpu::TextureFormat;
+    const BYTES_PER_PIXEL: u32;
+    fn from_f32(value: f32) -> Self;
+    fn from_texture_data(data: Vec<u8>) -> Vec<Self>;
+}
+
+impl TileSample for u8 {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+    const BYTES_PER_PIXEL: u32 = 4;
+
+    fn from_f32(value: f32) -> Self {
+        value.round() as u8
+    }
+
+    fn from_texture_data(data: Vec<u8>) -> Vec<Self> {
+        data
+    }
+}
+
+impl TileSample for u16 {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+    const BYTES_PER_PIXEL: u32 = 8;
+
+    fn from_f32(value: f32) -> Self {
+        value.round() as u16
+    }
+
+    fn from_texture_data(data: Vec<u8>) -> Vec<Self> {
+        data.chunks_exact(2)
+            .map(|b| (f16_to_f32(u16::from_ne_bytes([b[0], b[1]])).clamp(0.0, 1.0) * 65535.0).round() as u16)
+            .collect()
+    }
+}
+
+impl TileSample for f32 {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+    const BYTES_PER_PIXEL: u32 = 16;
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+
+    fn from_texture_data(data: Vec<u8>) -> Vec<Self> {
+        data.chunks_exact(4).map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]])).collect()
+    }
+}
+
+// Pixels uploaded to the adjustments shader. Float sources keep their values above 1.0, which the
+// shader's tonemapper brings into range, instead of being clipped to 8 bits on upload.
+enum SourcePixels {
+    Unorm8(Vec<u8>),
+    Float(Vec<f32>),
+}
+
+impl SourcePixels {
+    fn from_image(image: &DynamicImage) -> Self {
+        match image {
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => Self::Float(image.to_rgba32f().into_raw()),
+            _ => Self::Unorm8(image.to_rgba8().into_raw()),
+        }
+    }
+
+    fn format(&self) -> wgpu::TextureFormat {
+        match self {
+            Self::Unorm8(_) => u8::FORMAT,
+            Self::Float(_) => f32::FORMAT,
+        }
+    }
+
+    fn bytes_per_pixel(&self) -> u32 {
+        match self {
+            Self::Unorm8(_) => u8::BYTES_PER_PIXEL,
+            Self::Float(_) => f32::BYTES_PER_PIXEL,
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Self::Unorm8(pixels) => pixels,
+            Self::Float(pixels) => bytemuck::cast_slice(pixels),
+        }
+    }
+}
+
+fn half_float_to_f32(data: &[u8]) -> Vec<f32> {
+    data.chunks_exact(2)
+        .map(|b| f16_to_f32(u16::from_ne_bytes([b[0], b[1]])))
+        .collect()
+}
+
+fn half_float_to_unorm8(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(2)
+        .map(|b| (f16_to_f32(u16::from_ne_bytes([b[0], b[1]])).clamp(0.0, 1.0) * 255.0).round() as u8)
+        .collect()
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1f => if mantissa == 0.0 { sign * f32::INFINITY } else { f32::NAN },
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
 
 pub fn get_or_init_gpu_context(state: &tauri::State<AppState>) -> Result<GpuContext, String> {
     let mut context_lock = state.gpu_context.lock().unwrap();
@@ -36,6 +259,8 @@ pub fn get_or_init_gpu_context(state: &tauri::State<AppState>) -> Result<GpuCont
         device: Arc::new(device),
         queue: Arc::new(queue),
         limits,
+        pipelines: Arc::new(Mutex::new(HashMap::new())),
+        custom_effect: state.custom_effect.clone(),
     };
     *context_lock = Some(new_context.clone());
     Ok(new_context)
@@ -46,8 +271,9 @@ fn read_texture_data(
     queue: &wgpu::Queue,
     texture: &wgpu::Texture,
     size: wgpu::Extent3d,
+    bytes_per_pixel: u32,
 ) -> Result<Vec<u8>, String> {
-    let unpadded_bytes_per_row = 4 * size.width;
+    let unpadded_bytes_per_row = bytes_per_pixel * size.width;
     let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
     let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) & !(align - 1);
     let output_buffer_size = (padded_bytes_per_row * size.height) as u64;
@@ -90,25 +316,55 @@ fn read_texture_data(
     }
 }
 
-pub fn run_gpu_processing(
-    context: &GpuContext,
-    image: &DynamicImage,
-    adjustments: AllAdjustments,
-    mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
-) -> Result<Vec<u8>, String> {
-    let device = &context.device;
-    let queue = &context.queue;
-    let (width, height) = image.dimensions();
-    let max_dim = context.limits.max_texture_dimension_2d;
-    const MAX_MASKS: u32 = 16;
-
-    if width > max_dim || height > max_dim {
-        return Err(format!("Image dimensions ({}x{}) exceed GPU limits ({}).", width, height, max_dim));
+pub fn adjustments_shader_source(output_format: wgpu::TextureFormat, custom_effect: Option<&str>) -> String {
+    let mut source = include_str!("shader.wgsl").to_string();
+    if output_format == wgpu::TextureFormat::Rgba16Float {
+        source = source.replace("texture_storage_2d<rgba8unorm, write>", "texture_storage_2d<rgba16float, write>");
+    } else if output_format == wgpu::TextureFormat::Rgba32Float {
+        source = source.replace("texture_storage_2d<rgba8unorm, write>", "texture_storage_2d<rgba32float, write>");
     }
+    let begin = source.find("// CUSTOM_EFFECT_BEGIN");
+    let end = source.find("// CUSTOM_EFFECT_END");
+    if let (Some(custom_effect), Some(begin), Some(end)) = (custom_effect, begin, end) {
+        source.replace_range(begin..end, custom_effect);
+    }
+    source
+}
 
+// Compiles a shader inside an error scope, so invalid code is reported instead of hitting the
+// device's uncaptured error handler.
+fn create_checked_shader_module(device: &wgpu::Device, label: &str, source: &str) -> Result<wgpu::ShaderModule, String> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
     let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("Image Processing Shader"),
-        source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+        label: Some(label),
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    });
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(error) => Err(error.to_string()),
+        None => Ok(shader_module),
+    }
+}
+
+pub fn check_shader_compiles(device: &wgpu::Device, source: &str) -> Result<(), String> {
+    create_checked_shader_module(device, "Shader Check", source).map(|_| ())
+}
+
+fn create_adjustments_pipeline(
+    device: &wgpu::Device,
+    output_format: wgpu::TextureFormat,
+    custom_effect: Option<&str>,
+) -> CachedPipeline {
+    let custom_module = custom_effect.and_then(|custom_effect| {
+        let source = adjustments_shader_source(output_format, Some(custom_effect));
+        create_checked_shader_module(device, "Image Processing Shader", &source)
+            .map_err(|e| eprintln!("Custom effect failed to compile, using the built-in shader: {}", e))
+            .ok()
+    });
+    let shader_module = custom_module.unwrap_or_else(|| {
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Image Processing Shader"),
+            source: wgpu::ShaderSource::Wgsl(adjustments_shader_source(output_format, None).into()),
+        })
     });
 
     let mut bind_group_layout_entries = vec![
@@ -123,7 +379,7 @@ pub fn run_gpu_processing(
             binding: 1, visibility: wgpu::ShaderStages::COMPUTE,
             ty: wgpu::BindingType::StorageTexture {
                 access: wgpu::StorageTextureAccess::WriteOnly,
-                format: wgpu::TextureFormat::Rgba8Unorm,
+                format: output_format,
                 view_dimension: wgpu::TextureViewDimension::D2,
             }, count: None,
         },
@@ -136,18 +392,38 @@ pub fn run_gpu_processing(
         },
     ];
 
-    for i in 0..MAX_MASKS {
-        bind_group_layout_entries.push(wgpu::BindGroupLayoutEntry {
-            binding: 3 + i,
-            visibility: wgpu::ShaderStages::COMPUTE,
-            ty: wgpu::BindingType::Texture {
-                sample_type: wgpu::TextureSampleType::Float { filterable: false },
-                view_dimension: wgpu::TextureViewDimension::D2,
-                multisampled: false,
-            },
-            count: None,
-        });
-    }
+    bind_group_layout_entries.push(wgpu::BindGroupLayoutEntry {
+        binding: 3,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    });
+
+    bind_group_layout_entries.push(wgpu::BindGroupLayoutEntry {
+        binding: 4,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D2Array,
+            multisampled: false,
+        },
+        count: None,
+    });
+
+    bind_group_layout_entries.push(wgpu::BindGroupLayoutEntry {
+        binding: 5,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D3,
+            multisampled: false,
+        },
+        count: None,
+    });
 
     let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
         label: Some("Dynamic Bind Group Layout"),
@@ -160,136 +436,1540 @@ pub fn run_gpu_processing(
         push_constant_ranges: &[],
     });
 
-    let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
         label: Some("Compute Pipeline"), layout: Some(&pipeline_layout),
         module: &shader_module, entry_point: "main",
     });
 
-    let img_rgba = image.to_rgba8();
-    let full_texture_size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+    CachedPipeline { bind_group_layout, pipeline }
+}
 
-    let input_texture = device.create_texture_with_data(
-        queue,
-        &wgpu::TextureDescriptor {
-            label: Some("Full Input Texture"), size: full_texture_size, mip_level_count: 1, sample_count: 1,
-            dimension: wgpu::TextureDimension::D2, format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST, view_formats: &[],
-        },
-        TextureDataOrder::MipMajor, &img_rgba,
-    );
-    let input_texture_view = input_texture.create_view(&Default::default());
+fn create_lens_blur_pipeline(device: &wgpu::Device) -> CachedPipeline {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Lens Blur Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("lens_blur.wgsl").into()),
+    });
 
-    let mut mask_views = Vec::new();
-    for mask_bitmap in mask_bitmaps.iter() {
-        let mask_texture = device.create_texture_with_data(
-            queue,
-            &wgpu::TextureDescriptor {
-                label: Some("Full Mask Texture"), size: full_texture_size, mip_level_count: 1, sample_count: 1,
-                dimension: wgpu::TextureDimension::D2, format: wgpu::TextureFormat::R8Unorm,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST, view_formats: &[],
+    let texture_entry = |binding: u32| wgpu::BindGroupLayoutEntry {
+        binding, visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D2, multisampled: false,
+        }, count: None,
+    };
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Lens Blur Bind Group Layout"),
+        entries: &[
+            texture_entry(0),
+            wgpu::BindGroupLayoutEntry {
+                binding: 1, visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture {
+                    access: wgpu::StorageTextureAccess::WriteOnly,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                }, count: None,
             },
-            TextureDataOrder::MipMajor, mask_bitmap,
-        );
-        mask_views.push(mask_texture.create_view(&Default::default()));
-    }
-
-    let dummy_mask_texture = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("Dummy Mask Texture"),
-        size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
-        mip_level_count: 1, sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::R8Unorm,
-        usage: wgpu::TextureUsages::TEXTURE_BINDING,
-        view_formats: &[],
-    });
-    let dummy_mask_view = dummy_mask_texture.create_view(&Default::default());
-
-    let tile_size = 2048;
-    let mut final_pixels = vec![0u8; (width * height * 4) as usize];
-    let tiles_x = (width + tile_size - 1) / tile_size;
-    let tiles_y = (height + tile_size - 1) / tile_size;
-
-    for tile_y in 0..tiles_y {
-        for tile_x in 0..tiles_x {
-            let x_start = tile_x * tile_size;
-            let y_start = tile_y * tile_size;
-            let tile_width = (width - x_start).min(tile_size);
-            let tile_height = (height - y_start).min(tile_size);
-            let tile_texture_size = wgpu::Extent3d { width: tile_width, height: tile_height, depth_or_array_layers: 1 };
-
-            let output_texture = device.create_texture(&wgpu::TextureDescriptor {
-                label: Some("Output Tile Texture"), size: tile_texture_size, mip_level_count: 1, sample_count: 1,
-                dimension: wgpu::TextureDimension::D2, format: wgpu::TextureFormat::Rgba8Unorm,
-                usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC, view_formats: &[],
-            });
-            // THIS IS THE FIX: Create the view and store it in a variable
-            let output_texture_view = output_texture.create_view(&Default::default());
+            wgpu::BindGroupLayoutEntry {
+                binding: 2, visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false, min_binding_size: None,
+                }, count: None,
+            },
+            texture_entry(3),
+        ],
+    });
 
-            let mut tile_adjustments = adjustments;
-            tile_adjustments.tile_offset_x = x_start;
-            tile_adjustments.tile_offset_y = y_start;
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Lens Blur Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
 
-            let adjustments_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Tile Adjustments Buffer"),
-                contents: bytemuck::bytes_of(&tile_adjustments),
-                usage: wgpu::BufferUsages::UNIFORM,
-            });
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Lens Blur Pipeline"), layout: Some(&pipeline_layout),
+        module: &shader_module, entry_point: "main",
+    });
 
-            let mut bind_group_entries = vec![
-                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&input_texture_view) },
-                // Use the new variable here
-                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&output_texture_view) },
-                wgpu::BindGroupEntry { binding: 2, resource: adjustments_buffer.as_entire_binding() },
-            ];
-
-            for i in 0..MAX_MASKS as usize {
-                let view = mask_views.get(i).unwrap_or(&dummy_mask_view);
-                bind_group_entries.push(wgpu::BindGroupEntry {
-                    binding: 3 + i as u32,
-                    resource: wgpu::BindingResource::TextureView(view),
-                });
-            }
+    CachedPipeline { bind_group_layout, pipeline }
+}
 
-            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Tile Bind Group"),
-                layout: &bind_group_layout,
-                entries: &bind_group_entries,
-            });
+fn create_denoise_pipeline(device: &wgpu::Device) -> CachedPipeline {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Denoise Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("denoise.wgsl").into()),
+    });
 
-            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Tile Encoder") });
-            {
-                let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
-                compute_pass.set_pipeline(&compute_pipeline);
-                compute_pass.set_bind_group(0, &bind_group, &[]);
-                compute_pass.dispatch_workgroups((tile_width + 7) / 8, (tile_height + 7) / 8, 1);
-            }
-            queue.submit(Some(encoder.finish()));
+    let texture_entry = |binding: u32| wgpu::BindGroupLayoutEntry {
+        binding, visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D2, multisampled: false,
+        }, count: None,
+    };
+    let storage_entry = |binding: u32| wgpu::BindGroupLayoutEntry {
+        binding, visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::StorageTexture {
+            access: wgpu::StorageTextureAccess::WriteOnly,
+            format: wgpu::TextureFormat::Rgba16Float,
+            view_dimension: wgpu::TextureViewDimension::D2,
+        }, count: None,
+    };
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Denoise Bind Group Layout"),
+        entries: &[
+            texture_entry(0),
+            texture_entry(1),
+            storage_entry(2),
+            storage_entry(3),
+            wgpu::BindGroupLayoutEntry {
+                binding: 4, visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false, min_binding_size: None,
+                }, count: None,
+            },
+        ],
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Denoise Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Denoise Pipeline"), layout: Some(&pipeline_layout),
+        module: &shader_module, entry_point: "main",
+    });
+
+    CachedPipeline { bind_group_layout, pipeline }
+}
+
+fn create_film_effects_pipeline(device: &wgpu::Device) -> CachedPipeline {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Film Effects Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("film_effects.wgsl").into()),
+    });
+
+    let texture_entry = |binding: u32| wgpu::BindGroupLayoutEntry {
+        binding, visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D2, multisampled: false,
+        }, count: None,
+    };
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Film Effects Bind Group Layout"),
+        entries: &[
+            texture_entry(0),
+            texture_entry(1),
+            wgpu::BindGroupLayoutEntry {
+                binding: 2, visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture {
+                    access: wgpu::StorageTextureAccess::WriteOnly,
+                    format: wgpu::TextureFormat::Rgba16Float,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                }, count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 3, visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false, min_binding_size: None,
+                }, count: None,
+            },
+        ],
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Film Effects Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Film Effects Pipeline"), layout: Some(&pipeline_layout),
+        module: &shader_module, entry_point: "main",
+    });
+
+    CachedPipeline { bind_group_layout, pipeline }
+}
+
+fn create_scopes_pipeline(device: &wgpu::Device) -> CachedPipeline {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Scopes Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("scopes.wgsl").into()),
+    });
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Scopes Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0, visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2, multisampled: false,
+                }, count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1, visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false, min_binding_size: None,
+                }, count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2, visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false, min_binding_size: None,
+                }, count: None,
+            },
+        ],
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Scopes Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Scopes Pipeline"), layout: Some(&pipeline_layout),
+        module: &shader_module, entry_point: "main",
+    });
+
+    CachedPipeline { bind_group_layout, pipeline }
+}
+
+fn create_panorama_warp_pipeline(device: &wgpu::Device) -> CachedPipeline {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Panorama Warp Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("panorama_warp.wgsl").into()),
+    });
+
+    let storage_entry = |binding: u32, format: wgpu::TextureFormat| wgpu::BindGroupLayoutEntry {
+        binding, visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::StorageTexture {
+            access: wgpu::StorageTextureAccess::WriteOnly,
+            format,
+            view_dimension: wgpu::TextureViewDimension::D2,
+        }, count: None,
+    };
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Panorama Warp Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0, visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2, multisampled: false,
+                }, count: None,
+            },
+            storage_entry(1, wgpu::TextureFormat::Rgba32Float),
+            storage_entry(2, wgpu::TextureFormat::R32Float),
+            wgpu::BindGroupLayoutEntry {
+                binding: 3, visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false, min_binding_size: None,
+                }, count: None,
+            },
+        ],
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Panorama Warp Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Panorama Warp Pipeline"), layout: Some(&pipeline_layout),
+        module: &shader_module, entry_point: "main",
+    });
+
+    CachedPipeline { bind_group_layout, pipeline }
+}
+
+fn create_panorama_blend_pipeline(device: &wgpu::Device) -> CachedPipeline {
+    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Panorama Blend Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("panorama_blend.wgsl").into()),
+    });
+
+    let storage_entry = |binding: u32, read_only: bool| wgpu::BindGroupLayoutEntry {
+        binding, visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false, min_binding_size: None,
+        }, count: None,
+    };
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Panorama Blend Bind Group Layout"),
+        entries: &[
+            storage_entry(0, false),
+            storage_entry(1, true),
+            storage_entry(2, true),
+            wgpu::BindGroupLayoutEntry {
+                binding: 3, visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false, min_binding_size: None,
+                }, count: None,
+            },
+        ],
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Panorama Blend Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Panorama Blend Pipeline"), layout: Some(&pipeline_layout),
+        module: &shader_module, entry_point: "main",
+    });
+
+    CachedPipeline { bind_group_layout, pipeline }
+}
+
+fn get_or_create_pipeline(context: &GpuContext, kind: PipelineKind) -> Arc<CachedPipeline> {
+    let custom_effect = context.custom_effect.lock().unwrap().clone();
+    let mut pipelines = context.pipelines.lock().unwrap();
+    pipelines
+        .entry(kind)
+        .or_insert_with(|| {
+            Arc::new(match kind {
+                PipelineKind::Adjustments => {
+                    create_adjustments_pipeline(&context.device, wgpu::TextureFormat::Rgba8Unorm, custom_effect.as_deref())
+                }
+                PipelineKind::AdjustmentsHdr => {
+                    create_adjustments_pipeline(&context.device, wgpu::TextureFormat::Rgba16Float, custom_effect.as_deref())
+                }
+                PipelineKind::AdjustmentsFloat => {
+                    create_adjustments_pipeline(&context.device, wgpu::TextureFormat::Rgba32Float, custom_effect.as_deref())
+                }
+                PipelineKind::LensBlur => create_lens_blur_pipeline(&context.device),
+                PipelineKind::Denoise => create_denoise_pipeline(&context.device),
+                PipelineKind::FilmEffects => create_film_effects_pipeline(&context.device),
+                PipelineKind::Scopes => create_scopes_pipeline(&context.device),
+                PipelineKind::PanoramaWarp => create_panorama_warp_pipeline(&context.device),
+                PipelineKind::PanoramaBlend => create_panorama_blend_pipeline(&context.device),
+            })
+        })
+        .clone()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TileRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl TileRegion {
+    pub fn full(width: u32, height: u32) -> TileRegion {
+        TileRegion { x: 0, y: 0, width, height }
+    }
+
+    fn expand(&self, before: u32, after: u32, bounds: TileRegion) -> TileRegion {
+        let x = self.x.saturating_sub(before).max(bounds.x);
+        let y = self.y.saturating_sub(before).max(bounds.y);
+        let right = (self.x + self.width + after).min(bounds.x + bounds.width);
+        let bottom = (self.y + self.height + after).min(bounds.y + bounds.height);
+        TileRegion { x, y, width: right.saturating_sub(x), height: bottom.saturating_sub(y) }
+    }
+
+    fn relative_to(&self, origin: TileRegion) -> TileRegion {
+        TileRegion { x: self.x - origin.x, y: self.y - origin.y, width: self.width, height: self.height }
+    }
+
+    fn extent(&self, layers: u32) -> wgpu::Extent3d {
+        wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: layers }
+    }
+}
 
-            let processed_tile_data = read_texture_data(device, queue, &output_texture, tile_texture_size)?;
+fn write_texture_region(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    layer: u32,
+    data: &[u8],
+    image_width: u32,
+    bytes_per_pixel: u32,
+    region: TileRegion,
+) {
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d { x: 0, y: 0, z: layer },
+            aspect: wgpu::TextureAspect::All,
+        },
+        data,
+        wgpu::ImageDataLayout {
+            offset: (region.y as u64 * image_width as u64 + region.x as u64) * bytes_per_pixel as u64,
+            bytes_per_row: Some(image_width * bytes_per_pixel),
+            rows_per_image: Some(region.height),
+        },
+        region.extent(1),
+    );
+}
+
+fn blend_tile<T: TileSample>(final_pixels: &mut [T], width: u32, tile_pixels: &[T], output: TileRegion, core: TileRegion) {
+    let ramp = |pos: u32, start: u32, core_start: u32| -> f32 {
+        if pos >= core_start {
+            1.0
+        } else {
+            ((pos - start) as f32 + 0.5) / (core_start - start) as f32
+        }
+    };
+
+    for row in 0..output.height {
+        let y = output.y + row;
+        let weight_y = ramp(y, output.y, core.y);
+        let final_row = (y as usize * width as usize + output.x as usize) * 4;
+        let tile_row = row as usize * output.width as usize * 4;
+        let blend_cols = if weight_y < 1.0 { output.width } else { core.x - output.x };
 
-            for row in 0..tile_height {
-                let final_y = y_start + row;
-                let final_row_offset = (final_y * width + x_start) as usize * 4;
-                let tile_row_offset = (row * tile_width) as usize * 4;
-                let copy_bytes = (tile_width * 4) as usize;
-                final_pixels[final_row_offset..final_row_offset + copy_bytes]
-                    .copy_from_slice(&processed_tile_data[tile_row_offset..tile_row_offset + copy_bytes]);
+        for col in 0..blend_cols {
+            let weight = weight_y * ramp(output.x + col, output.x, core.x);
+            let dst = final_row + col as usize * 4;
+            let src = tile_row + col as usize * 4;
+            for c in 0..4 {
+                let existing: f32 = final_pixels[dst + c].into();
+                let processed: f32 = tile_pixels[src + c].into();
+                final_pixels[dst + c] = T::from_f32(existing + (processed - existing) * weight);
             }
         }
-    }
 
-    Ok(final_pixels)
+        let copy_start = blend_cols as usize * 4;
+        let copy_end = output.width as usize * 4;
+        if copy_start < copy_end {
+            final_pixels[final_row + copy_start..final_row + copy_end]
+                .copy_from_slice(&tile_pixels[tile_row + copy_start..tile_row + copy_end]);
+        }
+    }
 }
 
-pub fn process_and_get_dynamic_image(
+fn process_in_tiles<T: TileSample>(
     context: &GpuContext,
-    base_image: &DynamicImage,
+    bounds: TileRegion,
+    image_width: u32,
+    image_height: u32,
+    halo: u32,
+    mut process_tile: impl FnMut(TileRegion, TileRegion, TileRegion) -> Result<Vec<T>, String>,
+) -> Result<Vec<T>, String> {
+    let max_dim = context.limits.max_texture_dimension_2d;
+    let margin = 2 * (TILE_OVERLAP + halo);
+    if margin >= max_dim {
+        return Err(format!("Processing radius ({}px) exceeds GPU texture limits ({}).", halo, max_dim));
+    }
+    let tile_size = TILE_SIZE.min(max_dim - margin);
+
+    let image = TileRegion::full(image_width, image_height);
+    let right = bounds.x + bounds.width;
+    let bottom = bounds.y + bounds.height;
+    let mut final_pixels = vec![T::default(); bounds.width as usize * bounds.height as usize * 4];
+    for y in (bounds.y..bottom).step_by(tile_size as usize) {
+        for x in (bounds.x..right).step_by(tile_size as usize) {
+            let core = TileRegion { x, y, width: tile_size.min(right - x), height: tile_size.min(bottom - y) };
+            let output = core.expand(TILE_OVERLAP, 0, bounds);
+            let input = output.expand(halo, halo, image);
+            let tile_pixels = process_tile(output, input, core)?;
+            blend_tile(
+                &mut final_pixels,
+                bounds.width,
+                &tile_pixels,
+                output.relative_to(bounds),
+                core.relative_to(bounds),
+            );
+        }
+    }
+    Ok(final_pixels)
+}
+
+pub struct ScopeCounts {
+    pub histogram: Vec<u32>,
+    pub waveform: Vec<u32>,
+    pub vectorscope: Vec<u32>,
+}
+
+struct ScopesAccumulator {
+    counts_buffer: wgpu::Buffer,
+    bounds: TileRegion,
+}
+
+impl ScopesAccumulator {
+    fn new(context: &GpuContext, bounds: TileRegion) -> Self {
+        let counts_buffer = context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Scope Counts Buffer"),
+            contents: bytemuck::cast_slice(&vec![0u32; SCOPE_COUNTS_LEN]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        });
+        Self { counts_buffer, bounds }
+    }
+
+    fn record(
+        &self,
+        context: &GpuContext,
+        encoder: &mut wgpu::CommandEncoder,
+        texture_view: &wgpu::TextureView,
+        output: TileRegion,
+        core: TileRegion,
+    ) {
+        let device = &context.device;
+        let cached = get_or_create_pipeline(context, PipelineKind::Scopes);
+        let CachedPipeline { bind_group_layout, pipeline } = cached.as_ref();
+
+        let params = ScopesParams {
+            core_x: core.x - output.x,
+            core_y: core.y - output.y,
+            core_width: core.width,
+            core_height: core.height,
+            column_offset: core.x - self.bounds.x,
+            scope_width: self.bounds.width,
+            _pad0: 0,
+            _pad1: 0,
+        };
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Scopes Params Buffer"),
+            contents: bytemuck::bytes_of(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Scopes Bind Group"),
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(texture_view) },
+                wgpu::BindGroupEntry { binding: 1, resource: params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: self.counts_buffer.as_entire_binding() },
+            ],
+        });
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: Some("Scopes Pass"), timestamp_writes: None });
+        compute_pass.set_pipeline(pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
+        compute_pass.dispatch_workgroups((core.width + 15) / 16, (core.height + 15) / 16, 1);
+    }
+
+    fn read(self, context: &GpuContext) -> Result<ScopeCounts, String> {
+        let device = &context.device;
+        let size = (SCOPE_COUNTS_LEN * std::mem::size_of::<u32>()) as u64;
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Scope Counts Readback Buffer"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Scopes Readback Encoder") });
+        encoder.copy_buffer_to_buffer(&self.counts_buffer, 0, &readback_buffer, 0, size);
+        context.queue.submit(Some(encoder.finish()));
+
+        let buffer_slice = readback_buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| { tx.send(result).unwrap(); });
+        device.poll(wgpu::Maintain::Wait);
+        rx.recv().unwrap().map_err(|e| e.to_string())?;
+
+        let counts: Vec<u32> = {
+            let data = buffer_slice.get_mapped_range();
+            bytemuck::cast_slice::<u8, u32>(&data).to_vec()
+        };
+        readback_buffer.unmap();
+
+        let waveform_end = 4 * SCOPE_BINS + 4 * SCOPE_BINS * SCOPE_BINS;
+        let waveform = counts[4 * SCOPE_BINS..waveform_end].to_vec();
+        let vectorscope = counts[waveform_end..].to_vec();
+        let mut histogram = counts;
+        histogram.truncate(4 * SCOPE_BINS);
+        Ok(ScopeCounts { histogram, waveform, vectorscope })
+    }
+}
+
+fn processing_halo(adjustments: &AllAdjustments, mask_count: usize) -> u32 {
+    let max_glow_radius = adjustments
+        .mask_adjustments
+        .iter()
+        .take(mask_count)
+        .map(|m| m.glow_radius)
+        .fold(adjustments.global.glow_radius, f32::max);
+    NEIGHBORHOOD_HALO.max(max_glow_radius.ceil() as u32 + 1)
+}
+
+fn film_effects_radius(width: u32, height: u32) -> f32 {
+    FILM_EFFECTS_RADIUS_FRACTION * width.max(height) as f32
+}
+
+fn film_effects_halo(width: u32, height: u32) -> u32 {
+    film_effects_radius(width, height).ceil() as u32 + 1
+}
+
+fn lens_blur_halo(params: &LensBlurParams, width: u32, height: u32) -> u32 {
+    let max_radius = params.amount * LENS_BLUR_MAX_RADIUS_FRACTION * width.max(height) as f32;
+    max_radius.ceil() as u32 + 1
+}
+
+pub fn run_gpu_processing(
+    context: &GpuContext,
+    image: &DynamicImage,
+    adjustments: AllAdjustments,
+    mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
+    lut: Option<&Lut>,
+) -> Result<Vec<u8>, String> {
+    let (width, height) = image.dimensions();
+    run_gpu_processing_region(context, image, adjustments, mask_bitmaps, lut, TileRegion::full(width, height))
+}
+
+pub fn run_gpu_processing_region(
+    context: &GpuContext,
+    image: &DynamicImage,
+    adjustments: AllAdjustments,
+    mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
+    lut: Option<&Lut>,
+    region: TileRegion,
+) -> Result<Vec<u8>, String> {
+    render_region(context, image, adjustments, mask_bitmaps, lut, region, None)
+}
+
+fn render_region(
+    context: &GpuContext,
+    image: &DynamicImage,
+    adjustments: AllAdjustments,
+    mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
+    lut: Option<&Lut>,
+    region: TileRegion,
+    scopes: Option<&ScopesAccumulator>,
+) -> Result<Vec<u8>, String> {
+    let (width, height) = image.dimensions();
+    let image_region = TileRegion::full(width, height);
+    let region = region.expand(0, 0, image_region);
+    if region.width == 0 || region.height == 0 {
+        return Err("Requested region lies outside the image.".to_string());
+    }
+    let film_effects = adjustments.film_effects;
+    if !film_effects.is_enabled() {
+        return render_lens_blurred(context, image, adjustments, mask_bitmaps, lut, region, scopes);
+    }
+
+    let film_halo = film_effects_halo(width, height);
+    let source_region = region.expand(film_halo, film_halo, image_region);
+    let pixels = render_lens_blurred(context, image, adjustments, mask_bitmaps, lut, source_region, None)?;
+    run_film_effects_pass(context, &pixels, source_region, region, width, height, film_effects, scopes)
+}
+
+// Renders the adjustments and lens blur of a region that already lies within the image.
+fn render_lens_blurred(
+    context: &GpuContext,
+    image: &DynamicImage,
+    adjustments: AllAdjustments,
+    mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
+    lut: Option<&Lut>,
+    region: TileRegion,
+    scopes: Option<&ScopesAccumulator>,
+) -> Result<Vec<u8>, String> {
+    let (width, height) = image.dimensions();
+    let image_region = TileRegion::full(width, height);
+    let lens_blur = adjustments.lens_blur;
+    if lens_blur.amount <= 0.0 {
+        return render_adjustments(context, image, adjustments, mask_bitmaps, lut, region, scopes, HdrPreviewMode::Off);
+    }
+
+    let lens_halo = lens_blur_halo(&lens_blur, width, height);
+    let source_region = region.expand(lens_halo, lens_halo, image_region);
+    let pixels = render_adjustments(context, image, adjustments, mask_bitmaps, lut, source_region, None, HdrPreviewMode::Off)?;
+    let depth_map = usize::try_from(lens_blur.depth_mask_index)
+        .ok()
+        .and_then(|i| mask_bitmaps.get(i));
+    run_lens_blur_pass(context, &pixels, source_region, region, width, height, lens_blur, depth_map, scopes)
+}
+
+// Runs the adjustments shader over a region that already lies within the image.
+#[allow(clippy::too_many_arguments)]
+fn render_adjustments<T: TileSample>(
+    context: &GpuContext,
+    image: &DynamicImage,
+    adjustments: AllAdjustments,
+    mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
+    lut: Option<&Lut>,
+    region: TileRegion,
+    scopes: Option<&ScopesAccumulator>,
+    hdr_mode: HdrPreviewMode,
+) -> Result<Vec<T>, String> {
+    let device = &context.device;
+    let queue = &context.queue;
+    let (width, height) = image.dimensions();
+
+    let optical_center = adjustments.optical_center.unwrap_or_else(|| OpticalCenter::of_crop(width, height, (0.0, 0.0)));
+
+    let (pipeline_kind, hdr_output) = match T::FORMAT {
+        wgpu::TextureFormat::Rgba8Unorm => (PipelineKind::Adjustments, hdr_mode.shader_value()),
+        wgpu::TextureFormat::Rgba16Float => (PipelineKind::AdjustmentsHdr, hdr_mode.shader_value()),
+        _ => (PipelineKind::AdjustmentsFloat, EXTENDED_SRGB_OUTPUT),
+    };
+    let cached = get_or_create_pipeline(context, pipeline_kind);
+    let CachedPipeline { bind_group_layout, pipeline: compute_pipeline } = cached.as_ref();
+
+    let input_texture_descriptor = |size: wgpu::Extent3d, label: &'static str, format: wgpu::TextureFormat| wgpu::TextureDescriptor {
+        label: Some(label), size, mip_level_count: 1, sample_count: 1,
+        dimension: wgpu::TextureDimension::D2, format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST, view_formats: &[],
+    };
+
+    let max_layers = context.limits.max_texture_array_layers as usize;
+    let mut mask_adjustments = adjustments.mask_adjustments.clone();
+    let mask_count = mask_adjustments.len().min(mask_bitmaps.len());
+    if mask_count > max_layers {
+        return Err(format!(
+            "This image has {} masks, but the GPU can apply at most {}. Remove or merge some masks.",
+            mask_count, max_layers
+        ));
+    }
+    mask_adjustments.truncate(mask_count);
+    if mask_adjustments.is_empty() {
+        mask_adjustments.push(MaskAdjustments::default());
+    }
+
+    let mask_adjustments_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Mask Adjustments Buffer"),
+        contents: bytemuck::cast_slice(&mask_adjustments),
+        usage: wgpu::BufferUsages::STORAGE,
+    });
+
+    let empty_mask_texture = device.create_texture(&input_texture_descriptor(
+        wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        "Empty Mask Array Texture",
+        wgpu::TextureFormat::R8Unorm,
+    ));
+
+    let (lut_size, lut_data) = match lut {
+        Some(lut) => (
+            lut.size,
+            lut.data.chunks_exact(3).flat_map(|c| [c[0], c[1], c[2], 1.0]).collect::<Vec<f32>>(),
+        ),
+        None => (1, vec![0.0f32; 4]),
+    };
+    let lut_texture = device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: Some("LUT Texture"),
+            size: wgpu::Extent3d { width: lut_size, height: lut_size, depth_or_array_layers: lut_size },
+            mip_level_count: 1, sample_count: 1,
+            dimension: wgpu::TextureDimension::D3,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        },
+        TextureDataOrder::MipMajor, bytemuck::cast_slice(&lut_data),
+    );
+    let lut_view = lut_texture.create_view(&Default::default());
+
+    let mut global = adjustments.global;
+    global.lut_size = if lut.is_some() { lut_size } else { 0 };
+
+    let halo = processing_halo(&adjustments, mask_count);
+    let image_pixels = SourcePixels::from_image(image);
+    let (source_pixels, source_region) = if adjustments.denoise.is_enabled() {
+        let source_region = region.expand(halo, halo, TileRegion::full(width, height));
+        let pixels = run_denoise_pass(context, &image_pixels, width, height, adjustments.denoise, source_region)?;
+        (pixels, source_region)
+    } else {
+        (image_pixels, TileRegion::full(width, height))
+    };
+
+    process_in_tiles(context, region, width, height, halo, |output, input, core| {
+        let input_texture = device.create_texture(&input_texture_descriptor(
+            input.extent(1),
+            "Input Tile Texture",
+            source_pixels.format(),
+        ));
+        write_texture_region(
+            queue,
+            &input_texture,
+            0,
+            source_pixels.bytes(),
+            source_region.width,
+            source_pixels.bytes_per_pixel(),
+            input.relative_to(source_region),
+        );
+        let input_texture_view = input_texture.create_view(&Default::default());
+
+        let mask_array_texture = if mask_count > 0 {
+            let texture = device.create_texture(&input_texture_descriptor(
+                input.extent(mask_count as u32),
+                "Mask Array Tile Texture",
+                wgpu::TextureFormat::R8Unorm,
+            ));
+            for (i, mask_bitmap) in mask_bitmaps.iter().take(mask_count).enumerate() {
+                write_texture_region(queue, &texture, i as u32, mask_bitmap, width, 1, input);
+            }
+            Some(texture)
+        } else {
+            None
+        };
+        let mask_array_view = mask_array_texture
+            .as_ref()
+            .unwrap_or(&empty_mask_texture)
+            .create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2Array),
+                ..Default::default()
+            });
+
+        let output_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Output Tile Texture"), size: output.extent(1), mip_level_count: 1, sample_count: 1,
+            dimension: wgpu::TextureDimension::D2, format: T::FORMAT,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let output_texture_view = output_texture.create_view(&Default::default());
+
+        let tile_adjustments = ProcessingUniforms {
+            global,
+            mask_count: mask_count as u32,
+            tile_offset_x: output.x,
+            tile_offset_y: output.y,
+            input_offset_x: input.x,
+            input_offset_y: input.y,
+            full_width: width,
+            full_height: height,
+            hdr_output,
+            optical_center_x: optical_center.x,
+            optical_center_y: optical_center.y,
+            lateral_ca_r: optical_center.lateral_ca[0],
+            lateral_ca_b: optical_center.lateral_ca[1],
+        };
+
+        let adjustments_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Tile Adjustments Buffer"),
+            contents: bytemuck::bytes_of(&tile_adjustments),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Tile Bind Group"),
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&input_texture_view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&output_texture_view) },
+                wgpu::BindGroupEntry { binding: 2, resource: adjustments_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: mask_adjustments_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::TextureView(&mask_array_view) },
+                wgpu::BindGroupEntry { binding: 5, resource: wgpu::BindingResource::TextureView(&lut_view) },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Tile Encoder") });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+            compute_pass.set_pipeline(compute_pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups((output.width + 7) / 8, (output.height + 7) / 8, 1);
+        }
+        if let Some(scopes) = scopes {
+            scopes.record(context, &mut encoder, &output_texture_view, output, core);
+        }
+        queue.submit(Some(encoder.finish()));
+
+        read_texture_data(device, queue, &output_texture, output.extent(1), T::BYTES_PER_PIXEL).map(T::from_texture_data)
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_lens_blur_pass(
+    context: &GpuContext,
+    pixels: &[u8],
+    source_region: TileRegion,
+    region: TileRegion,
+    width: u32,
+    height: u32,
+    params: LensBlurParams,
+    depth_map: Option<&ImageBuffer<Luma<u8>, Vec<u8>>>,
+    scopes: Option<&ScopesAccumulator>,
+) -> Result<Vec<u8>, String> {
+    let device = &context.device;
+    let queue = &context.queue;
+
+    let cached = get_or_create_pipeline(context, PipelineKind::LensBlur);
+    let CachedPipeline { bind_group_layout, pipeline: compute_pipeline } = cached.as_ref();
+
+    let input_texture_descriptor = |size: wgpu::Extent3d, label: &'static str, format: wgpu::TextureFormat| wgpu::TextureDescriptor {
+        label: Some(label), size, mip_level_count: 1, sample_count: 1,
+        dimension: wgpu::TextureDimension::D2, format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST, view_formats: &[],
+    };
+    let empty_depth_texture = device.create_texture(&input_texture_descriptor(
+        wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        "Empty Lens Blur Depth Texture",
+        wgpu::TextureFormat::R8Unorm,
+    ));
+
+    let halo = lens_blur_halo(&params, width, height);
+
+    process_in_tiles(context, region, width, height, halo, |output, input, core| {
+        let input_texture = device.create_texture(&input_texture_descriptor(
+            input.extent(1),
+            "Lens Blur Input Tile Texture",
+            wgpu::TextureFormat::Rgba8Unorm,
+        ));
+        write_texture_region(queue, &input_texture, 0, pixels, source_region.width, 4, input.relative_to(source_region));
+        let input_texture_view = input_texture.create_view(&Default::default());
+
+        let depth_texture = depth_map.map(|map| {
+            let texture = device.create_texture(&input_texture_descriptor(
+                input.extent(1),
+                "Lens Blur Depth Tile Texture",
+                wgpu::TextureFormat::R8Unorm,
+            ));
+            write_texture_region(queue, &texture, 0, map.as_raw(), width, 1, input);
+            texture
+        });
+        let depth_texture_view = depth_texture
+            .as_ref()
+            .unwrap_or(&empty_depth_texture)
+            .create_view(&Default::default());
+
+        let output_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Lens Blur Output Tile Texture"), size: output.extent(1), mip_level_count: 1, sample_count: 1,
+            dimension: wgpu::TextureDimension::D2, format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let output_texture_view = output_texture.create_view(&Default::default());
+
+        let mut tile_params = params;
+        tile_params.tile_offset_x = output.x;
+        tile_params.tile_offset_y = output.y;
+        tile_params.input_offset_x = input.x;
+        tile_params.input_offset_y = input.y;
+        tile_params.full_width = width;
+        tile_params.full_height = height;
+
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Lens Blur Params Buffer"),
+            contents: bytemuck::bytes_of(&tile_params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Lens Blur Bind Group"),
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&input_texture_view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&output_texture_view) },
+                wgpu::BindGroupEntry { binding: 2, resource: params_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&depth_texture_view) },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Lens Blur Encoder") });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+            compute_pass.set_pipeline(compute_pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups((output.width + 7) / 8, (output.height + 7) / 8, 1);
+        }
+        if let Some(scopes) = scopes {
+            scopes.record(context, &mut encoder, &output_texture_view, output, core);
+        }
+        queue.submit(Some(encoder.finish()));
+
+        read_texture_data(device, queue, &output_texture, output.extent(1), 4)
+    })
+}
+
+// Adds halation, bloom and color grain to already rendered pixels, see film_effects.wgsl.
+#[allow(clippy::too_many_arguments)]
+fn run_film_effects_pass(
+    context: &GpuContext,
+    pixels: &[u8],
+    source_region: TileRegion,
+    region: TileRegion,
+    width: u32,
+    height: u32,
+    params: FilmEffectsParams,
+    scopes: Option<&ScopesAccumulator>,
+) -> Result<Vec<u8>, String> {
+    let device = &context.device;
+    let queue = &context.queue;
+
+    let cached = get_or_create_pipeline(context, PipelineKind::FilmEffects);
+    let CachedPipeline { bind_group_layout, pipeline: compute_pipeline } = cached.as_ref();
+
+    let blur_texture_descriptor = |size: wgpu::Extent3d, label: &'static str, usage: wgpu::TextureUsages| wgpu::TextureDescriptor {
+        label: Some(label), size, mip_level_count: 1, sample_count: 1,
+        dimension: wgpu::TextureDimension::D2, format: wgpu::TextureFormat::Rgba16Float,
+        usage, view_formats: &[],
+    };
+    let unused_blur_texture = device.create_texture(&blur_texture_descriptor(
+        wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        "Unused Film Effects Blur Texture",
+        wgpu::TextureUsages::TEXTURE_BINDING,
+    ));
+    let unused_blur_view = unused_blur_texture.create_view(&Default::default());
+
+    let radius = film_effects_radius(width, height);
+    let halo = film_effects_halo(width, height);
+
+    process_in_tiles(context, region, width, height, halo, |output, input, core| {
+        let input_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Film Effects Input Tile Texture"), size: input.extent(1), mip_level_count: 1, sample_count: 1,
+            dimension: wgpu::TextureDimension::D2, format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST, view_formats: &[],
+        });
+        write_texture_region(queue, &input_texture, 0, pixels, source_region.width, 4, input.relative_to(source_region));
+        let input_view = input_texture.create_view(&Default::default());
+
+        let blur_view = device
+            .create_texture(&blur_texture_descriptor(
+                input.extent(1),
+                "Film Effects Blur Texture",
+                wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            ))
+            .create_view(&Default::default());
+        let output_texture = device.create_texture(&blur_texture_descriptor(
+            output.extent(1),
+            "Film Effects Output Tile Texture",
+            wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+        ));
+        let output_view = output_texture.create_view(&Default::default());
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Film Effects Encoder") });
+        for pass_index in 0..2u32 {
+            let (blur_in, target, dispatch) = if pass_index == 0 {
+                (&unused_blur_view, &blur_view, input)
+            } else {
+                (&blur_view, &output_view, output)
+            };
+            let pass_params = FilmEffectsParams {
+                radius,
+                pass_index,
+                tile_offset_x: output.x,
+                tile_offset_y: output.y,
+                offset_x: if pass_index == 0 { 0 } else { output.x - input.x },
+                offset_y: if pass_index == 0 { 0 } else { output.y - input.y },
+                ..params
+            };
+            let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Film Effects Params Buffer"),
+                contents: bytemuck::bytes_of(&pass_params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Film Effects Bind Group"),
+                layout: bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&input_view) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(blur_in) },
+                    wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(target) },
+                    wgpu::BindGroupEntry { binding: 3, resource: params_buffer.as_entire_binding() },
+                ],
+            });
+
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+            compute_pass.set_pipeline(compute_pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups((dispatch.width + 7) / 8, (dispatch.height + 7) / 8, 1);
+        }
+        if let Some(scopes) = scopes {
+            scopes.record(context, &mut encoder, &output_view, output, core);
+        }
+        queue.submit(Some(encoder.finish()));
+
+        let data = read_texture_data(device, queue, &output_texture, output.extent(1), 8)?;
+        Ok(half_float_to_unorm8(&data))
+    })
+}
+
+// Denoises the image pixels within a region of the image, see denoise.wgsl.
+fn run_denoise_pass(
+    context: &GpuContext,
+    pixels: &SourcePixels,
+    width: u32,
+    height: u32,
+    params: DenoiseParams,
+    region: TileRegion,
+) -> Result<SourcePixels, String> {
+    let device = &context.device;
+    let queue = &context.queue;
+
+    let cached = get_or_create_pipeline(context, PipelineKind::Denoise);
+    let CachedPipeline { bind_group_layout, pipeline: compute_pipeline } = cached.as_ref();
+
+    let level_texture_descriptor = |size: wgpu::Extent3d, label: &'static str, usage: wgpu::TextureUsages| wgpu::TextureDescriptor {
+        label: Some(label), size, mip_level_count: 1, sample_count: 1,
+        dimension: wgpu::TextureDimension::D2, format: wgpu::TextureFormat::Rgba16Float,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | usage, view_formats: &[],
+    };
+    let unused_sum_texture = device.create_texture(&level_texture_descriptor(
+        wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        "Unused Denoise Sum Texture",
+        wgpu::TextureUsages::empty(),
+    ));
+    let unused_sum_view = unused_sum_texture.create_view(&Default::default());
+
+    // Returns the denoised tile as half floats.
+    let denoise_tile = |output: TileRegion, input: TileRegion| -> Result<Vec<u8>, String> {
+        let input_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Denoise Input Tile Texture"), size: input.extent(1), mip_level_count: 1, sample_count: 1,
+            dimension: wgpu::TextureDimension::D2, format: pixels.format(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST, view_formats: &[],
+        });
+        write_texture_region(queue, &input_texture, 0, pixels.bytes(), width, pixels.bytes_per_pixel(), input);
+        let input_view = input_texture.create_view(&Default::default());
+
+        // Approximations and detail sums ping-pong between two pairs of textures.
+        let level_views: Vec<(wgpu::TextureView, wgpu::TextureView)> = (0..2)
+            .map(|_| {
+                let create = |label| {
+                    device
+                        .create_texture(&level_texture_descriptor(input.extent(1), label, wgpu::TextureUsages::TEXTURE_BINDING))
+                        .create_view(&Default::default())
+                };
+                (create("Denoise Approximation Texture"), create("Denoise Sum Texture"))
+            })
+            .collect();
+
+        let output_texture = device.create_texture(&level_texture_descriptor(
+            output.extent(1),
+            "Denoise Output Tile Texture",
+            wgpu::TextureUsages::COPY_SRC,
+        ));
+        let output_view = output_texture.create_view(&Default::default());
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Denoise Encoder") });
+        for level in 0..DENOISE_LEVELS {
+            let last_level = level + 1 == DENOISE_LEVELS;
+            let (approx_in, sum_in) = if level == 0 {
+                (&input_view, &input_view)
+            } else {
+                let (approx, sum) = &level_views[(level as usize + 1) % 2];
+                (approx, sum)
+            };
+            let (approx_out, sum_out) = if last_level {
+                (&output_view, &unused_sum_view)
+            } else {
+                let (approx, sum) = &level_views[level as usize % 2];
+                (approx, sum)
+            };
+
+            let level_params = DenoiseParams {
+                level,
+                last_level: last_level as u32,
+                offset_x: if last_level { output.x - input.x } else { 0 },
+                offset_y: if last_level { output.y - input.y } else { 0 },
+                ..params
+            };
+            let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Denoise Params Buffer"),
+                contents: bytemuck::bytes_of(&level_params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Denoise Bind Group"),
+                layout: bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(approx_in) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(sum_in) },
+                    wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(approx_out) },
+                    wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(sum_out) },
+                    wgpu::BindGroupEntry { binding: 4, resource: params_buffer.as_entire_binding() },
+                ],
+            });
+
+            let dispatch = if last_level { output } else { input };
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+            compute_pass.set_pipeline(compute_pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups((dispatch.width + 7) / 8, (dispatch.height + 7) / 8, 1);
+        }
+        queue.submit(Some(encoder.finish()));
+
+        read_texture_data(device, queue, &output_texture, output.extent(1), 8)
+    };
+
+    match pixels {
+        SourcePixels::Unorm8(_) => process_in_tiles(context, region, width, height, DENOISE_HALO, |output, input, _core| {
+            denoise_tile(output, input).map(|data| half_float_to_unorm8(&data))
+        })
+        .map(SourcePixels::Unorm8),
+        SourcePixels::Float(_) => process_in_tiles(context, region, width, height, DENOISE_HALO, |output, input, _core| {
+            denoise_tile(output, input).map(|data| half_float_to_f32(&data))
+        })
+        .map(SourcePixels::Float),
+    }
+}
+
+pub fn process_and_get_dynamic_image(
+    context: &GpuContext,
+    base_image: &DynamicImage,
     all_adjustments: AllAdjustments,
     mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
+    lut: Option<&Lut>,
 ) -> Result<DynamicImage, String> {
-    let processed_pixels = run_gpu_processing(context, base_image, all_adjustments, mask_bitmaps)?;
+    let processed_pixels = run_gpu_processing(context, base_image, all_adjustments, mask_bitmaps, lut)?;
     let (width, height) = base_image.dimensions();
     let img_buf = ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(width, height, processed_pixels)
         .ok_or("Failed to create image buffer from GPU data")?;
     Ok(DynamicImage::ImageRgba8(img_buf))
-}
\ No newline at end of file
+}
+
+// Renders the image for export to a color space wider than sRGB. Returns sRGB encoded floats that keep
+// colors outside the sRGB gamut as negative or above-white values instead of clipping them. Lens blur
+// and film effects only render in 8 bits, so images using them fall back to the clipped rendering.
+pub fn process_wide_gamut_image(
+    context: &GpuContext,
+    base_image: &DynamicImage,
+    all_adjustments: AllAdjustments,
+    mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
+    lut: Option<&Lut>,
+) -> Result<DynamicImage, String> {
+    if all_adjustments.film_effects.is_enabled() || all_adjustments.lens_blur.amount > 0.0 {
+        return process_and_get_dynamic_image(context, base_image, all_adjustments, mask_bitmaps, lut);
+    }
+    let (width, height) = base_image.dimensions();
+    let region = TileRegion::full(width, height);
+    let processed_pixels = render_adjustments(context, base_image, all_adjustments, mask_bitmaps, lut, region, None, HdrPreviewMode::Off)?;
+    let img_buf = ImageBuffer::<Rgba<f32>, Vec<f32>>::from_raw(width, height, processed_pixels)
+        .ok_or("Failed to create image buffer from GPU data")?;
+    Ok(DynamicImage::ImageRgba32F(img_buf))
+}
+
+pub fn process_region_and_get_dynamic_image(
+    context: &GpuContext,
+    base_image: &DynamicImage,
+    all_adjustments: AllAdjustments,
+    mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
+    lut: Option<&Lut>,
+    region: TileRegion,
+) -> Result<DynamicImage, String> {
+    let (width, height) = base_image.dimensions();
+    let region = region.expand(0, 0, TileRegion::full(width, height));
+    let processed_pixels = run_gpu_processing_region(context, base_image, all_adjustments, mask_bitmaps, lut, region)?;
+    let img_buf = ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(region.width, region.height, processed_pixels)
+        .ok_or("Failed to create image buffer from GPU data")?;
+    Ok(DynamicImage::ImageRgba8(img_buf))
+}
+
+pub fn process_with_scopes(
+    context: &GpuContext,
+    base_image: &DynamicImage,
+    all_adjustments: AllAdjustments,
+    mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
+    lut: Option<&Lut>,
+) -> Result<(DynamicImage, ScopeCounts), String> {
+    let (width, height) = base_image.dimensions();
+    let region = TileRegion::full(width, height);
+    let scopes = ScopesAccumulator::new(context, region);
+    let processed_pixels = render_region(context, base_image, all_adjustments, mask_bitmaps, lut, region, Some(&scopes))?;
+    let counts = scopes.read(context)?;
+    let img_buf = ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(width, height, processed_pixels)
+        .ok_or("Failed to create image buffer from GPU data")?;
+    Ok((DynamicImage::ImageRgba8(img_buf), counts))
+}
+
+// Renders the image with highlights above SDR white kept and encoded for an HDR display. Returns
+// 16-bit RGBA code values in Rec.2020 with the mode's transfer function. Lens blur and film effects
+// aren't applied.
+pub fn process_hdr_preview(
+    context: &GpuContext,
+    base_image: &DynamicImage,
+    all_adjustments: AllAdjustments,
+    mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
+    lut: Option<&Lut>,
+    hdr_mode: HdrPreviewMode,
+) -> Result<ImageBuffer<Rgba<u16>, Vec<u16>>, String> {
+    let (width, height) = base_image.dimensions();
+    let region = TileRegion::full(width, height);
+    let processed_pixels = render_adjustments(context, base_image, all_adjustments, mask_bitmaps, lut, region, None, hdr_mode)?;
+    ImageBuffer::<Rgba<u16>, Vec<u16>>::from_raw(width, height, processed_pixels)
+        .ok_or_else(|| "Failed to create image buffer from GPU data".to_string())
+}
+
+fn render_compare_region(context: &GpuContext, side: &CompareSide, region: TileRegion) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, String> {
+    let pixels = render_region(context, side.image, side.adjustments.clone(), side.mask_bitmaps, side.lut, region, None)?;
+    ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(region.width, region.height, pixels)
+        .ok_or_else(|| "Failed to create image buffer from GPU data".to_string())
+}
+
+// Renders two adjustment states into one frame. A split frame renders each state only on its side of
+// the divider, so no pixel goes through the pipeline twice. Side by side renders both images in full
+// next to each other.
+pub fn process_comparison(
+    context: &GpuContext,
+    left: CompareSide,
+    right: CompareSide,
+    layout: CompareLayout,
+    split_position: f32,
+) -> Result<DynamicImage, String> {
+    let (left_width, left_height) = left.image.dimensions();
+    let (right_width, right_height) = right.image.dimensions();
+
+    // Sides with different crops can't share a divider.
+    let layout = if (left_width, left_height) != (right_width, right_height) { CompareLayout::SideBySide } else { layout };
+
+    let canvas = match layout {
+        CompareLayout::Split => {
+            let (width, height) = (right_width, right_height);
+            let split_x = (split_position.clamp(0.0, 1.0) * width as f32).round() as u32;
+            let mut canvas = ImageBuffer::<Rgba<u8>, Vec<u8>>::new(width, height);
+            if split_x > 0 {
+                let pixels = render_compare_region(context, &left, TileRegion { x: 0, y: 0, width: split_x, height })?;
+                imageops::replace(&mut canvas, &pixels, 0, 0);
+            }
+            if split_x < width {
+                let region = TileRegion { x: split_x, y: 0, width: width - split_x, height };
+                let pixels = render_compare_region(context, &right, region)?;
+                imageops::replace(&mut canvas, &pixels, split_x as i64, 0);
+            }
+            let divider_start = split_x.saturating_sub(COMPARE_DIVIDER_WIDTH / 2);
+            for x in divider_start..(divider_start + COMPARE_DIVIDER_WIDTH).min(width) {
+                for y in 0..height {
+                    canvas.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+                }
+            }
+            canvas
+        }
+        CompareLayout::SideBySide => {
+            let height = left_height.max(right_height);
+            let mut canvas = ImageBuffer::<Rgba<u8>, Vec<u8>>::new(left_width + COMPARE_GAP + right_width, height);
+            let pixels = render_compare_region(context, &left, TileRegion::full(left_width, left_height))?;
+            imageops::replace(&mut canvas, &pixels, 0, ((height - left_height) / 2) as i64);
+            let pixels = render_compare_region(context, &right, TileRegion::full(right_width, right_height))?;
+            imageops::replace(
+                &mut canvas,
+                &pixels,
+                (left_width + COMPARE_GAP) as i64,
+                ((height - right_height) / 2) as i64,
+            );
+            canvas
+        }
+    };
+    Ok(DynamicImage::ImageRgba8(canvas))
+}
+
+// Warps one panorama frame onto a region of the output canvas. Returns RGBA colors (alpha marks
+// coverage and low detail) and the distance of every covered pixel to the source image border.
+pub fn run_panorama_warp(
+    context: &GpuContext,
+    source_rgba: &[f32],
+    source_width: u32,
+    source_height: u32,
+    params: PanoramaWarpParams,
+    region: TileRegion,
+) -> Result<(Vec<f32>, Vec<f32>), String> {
+    let device = &context.device;
+    let queue = &context.queue;
+    let max_dim = context.limits.max_texture_dimension_2d;
+    if source_width > max_dim || source_height > max_dim {
+        return Err(format!("Image size {}x{} exceeds GPU texture limits ({}).", source_width, source_height, max_dim));
+    }
+    let source_bytes: &[u8] = bytemuck::cast_slice(source_rgba);
+    if source_bytes.len() as u64 > context.limits.max_buffer_size {
+        return Err(format!("Image size {}x{} exceeds the GPU upload limit.", source_width, source_height));
+    }
+
+    let cached = get_or_create_pipeline(context, PipelineKind::PanoramaWarp);
+    let CachedPipeline { bind_group_layout, pipeline: compute_pipeline } = cached.as_ref();
+
+    let source_texture = device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: Some("Panorama Warp Source Texture"),
+            size: wgpu::Extent3d { width: source_width, height: source_height, depth_or_array_layers: 1 },
+            mip_level_count: 1, sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        },
+        TextureDataOrder::MipMajor, source_bytes,
+    );
+    let source_texture_view = source_texture.create_view(&Default::default());
+
+    let output_texture_descriptor = |size: wgpu::Extent3d, label: &'static str, format: wgpu::TextureFormat| wgpu::TextureDescriptor {
+        label: Some(label), size, mip_level_count: 1, sample_count: 1,
+        dimension: wgpu::TextureDimension::D2, format,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC, view_formats: &[],
+    };
+
+    let tile_size = TILE_SIZE.min(max_dim);
+    let region_width = region.width as usize;
+    let mut colors = vec![0.0f32; region_width * region.height as usize * 4];
+    let mut distances = vec![-1.0f32; region_width * region.height as usize];
+
+    for y in (region.y..region.y + region.height).step_by(tile_size as usize) {
+        for x in (region.x..region.x + region.width).step_by(tile_size as usize) {
+            let tile = TileRegion {
+                x, y,
+                width: tile_size.min(region.x + region.width - x),
+                height: tile_size.min(region.y + region.height - y),
+            };
+            let color_texture = device.create_texture(&output_texture_descriptor(
+                tile.extent(1), "Panorama Warp Color Texture", wgpu::TextureFormat::Rgba32Float,
+            ));
+            let distance_texture = device.create_texture(&output_texture_descriptor(
+                tile.extent(1), "Panorama Warp Distance Texture", wgpu::TextureFormat::R32Float,
+            ));
+            let color_texture_view = color_texture.create_view(&Default::default());
+            let distance_texture_view = distance_texture.create_view(&Default::default());
+
+            let mut tile_params = params;
+            tile_params.tile_offset_x = tile.x;
+            tile_params.tile_offset_y = tile.y;
+            let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Panorama Warp Params Buffer"),
+                contents: bytemuck::bytes_of(&tile_params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Panorama Warp Bind Group"),
+                layout: bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&source_texture_view) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&color_texture_view) },
+                    wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::TextureView(&distance_texture_view) },
+                    wgpu::BindGroupEntry { binding: 3, resource: params_buffer.as_entire_binding() },
+                ],
+            });
+
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Panorama Warp Encoder") });
+            {
+                let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+                compute_pass.set_pipeline(compute_pipeline);
+                compute_pass.set_bind_group(0, &bind_group, &[]);
+                compute_pass.dispatch_workgroups((tile.width + 7) / 8, (tile.height + 7) / 8, 1);
+            }
+            queue.submit(Some(encoder.finish()));
+
+            let tile_colors = read_texture_data(device, queue, &color_texture, tile.extent(1), 16)?;
+            let tile_distances = read_texture_data(device, queue, &distance_texture, tile.extent(1), 4)?;
+            let local = tile.relative_to(region);
+            let tile_width = tile.width as usize;
+            for row in 0..tile.height as usize {
+                let start = (local.y as usize + row) * region_width + local.x as usize;
+                for (target, bytes) in colors[start * 4..(start + tile_width) * 4]
+                    .iter_mut()
+                    .zip(tile_colors[row * tile_width * 16..(row + 1) * tile_width * 16].chunks_exact(4))
+                {
+                    *target = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                }
+                for (target, bytes) in distances[start..start + tile_width]
+                    .iter_mut()
+                    .zip(tile_distances[row * tile_width * 4..(row + 1) * tile_width * 4].chunks_exact(4))
+                {
+                    *target = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                }
+            }
+        }
+    }
+
+    Ok((colors, distances))
+}
+
+// Blends a warped frame into a region of the panorama along its seam. `panorama_rgba` covers the same
+// region as `warped_rgba`, with alpha 1 where the canvas already has content, and is updated in place.
+// The region is processed in row bands that fit the storage buffer binding limit.
+pub fn run_panorama_seam_blend(
+    context: &GpuContext,
+    panorama_rgba: &mut [f32],
+    warped_rgba: &[f32],
+    seam: &[i32],
+    params: PanoramaBlendParams,
+) -> Result<(), String> {
+    let device = &context.device;
+    let queue = &context.queue;
+    let row_bytes = params.width as u64 * 16;
+    let max_binding = (context.limits.max_storage_buffer_binding_size as u64).min(context.limits.max_buffer_size);
+    let band_rows = (max_binding / row_bytes.max(1)).min(params.height as u64) as u32;
+    if band_rows == 0 {
+        return Err(format!("Panorama width {} exceeds the GPU buffer limits.", params.width));
+    }
+
+    let cached = get_or_create_pipeline(context, PipelineKind::PanoramaBlend);
+    let CachedPipeline { bind_group_layout, pipeline: compute_pipeline } = cached.as_ref();
+
+    // Storage bindings can't be empty, so a missing seam still uploads one entry.
+    let seam_data: &[i32] = if seam.is_empty() { &[0] } else { seam };
+    let seam_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Panorama Blend Seam Buffer"),
+        contents: bytemuck::cast_slice(seam_data),
+        usage: wgpu::BufferUsages::STORAGE,
+    });
+
+    let row_len = params.width as usize * 4;
+    for band_y in (0..params.height).step_by(band_rows as usize) {
+        let rows = band_rows.min(params.height - band_y);
+        let range = band_y as usize * row_len..(band_y + rows) as usize * row_len;
+        let size = (range.len() * std::mem::size_of::<f32>()) as u64;
+
+        let panorama_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Panorama Blend Canvas Buffer"),
+            contents: bytemuck::cast_slice(&panorama_rgba[range.clone()]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        });
+        let warped_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Panorama Blend Warped Buffer"),
+            contents: bytemuck::cast_slice(&warped_rgba[range.clone()]),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let band_params = PanoramaBlendParams { region_y: params.region_y + band_y, height: rows, ..params };
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Panorama Blend Params Buffer"),
+            contents: bytemuck::bytes_of(&band_params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Panorama Blend Readback Buffer"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Panorama Blend Bind Group"),
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: panorama_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: warped_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: seam_buffer.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: params_buffer.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Panorama Blend Encoder") });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None, timestamp_writes: None });
+            compute_pass.set_pipeline(compute_pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups((params.width + 7) / 8, (rows + 7) / 8, 1);
+        }
+        encoder.copy_buffer_to_buffer(&panorama_buffer, 0, &readback_buffer, 0, size);
+        queue.submit(Some(encoder.finish()));
+
+        let buffer_slice = readback_buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| { tx.send(result).unwrap(); });
+        device.poll(wgpu::Maintain::Wait);
+        rx.recv().unwrap().map_err(|e| e.to_string())?;
+        {
+            let data = buffer_slice.get_mapped_range();
+            panorama_rgba[range].copy_from_slice(bytemuck::cast_slice::<u8, f32>(&data));
+        }
+        readback_buffer.unmap();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,345 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use std::time::Instant;
+
+use exif::{In, Reader as ExifReader, Tag, Value as ExifValue};
+use image::{GrayImage, Luma, Rgb32FImage, RgbImage};
+use nalgebra::{Matrix3, Point2, Vector3};
+use rawler::{decoders::RawDecodeParams, rawsource::RawSource};
+use rayon::prelude::*;
+use tauri::{AppHandle, Emitter};
+
+use crate::formats::is_raw_file;
+use crate::image_loader::load_image_with_orientation;
+use crate::panorama_stitching::KeyPoint;
+use crate::panorama_utils::processing;
+use crate::raw_processing::{apply_tonemap_and_gamma, develop_raw_image_linear};
+
+const CLIP_HIGH: f32 = 0.97;
+const CLIP_LOW: f32 = 0.002;
+
+struct BracketFrame {
+    filename: String,
+    linear: Rgb32FImage,
+    exposure: Option<f32>,
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    let c = c.clamp(0.0, 1.0);
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn read_exposure(file_bytes: &[u8], path: &str) -> Option<f32> {
+    let (exposure_time, fnumber, iso) = if is_raw_file(path) {
+        let source = RawSource::new_from_slice(file_bytes);
+        let metadata = rawler::get_decoder(&source)
+            .and_then(|d| d.raw_metadata(&source, &RawDecodeParams::default()))
+            .ok()?;
+        (
+            metadata.exif.exposure_time.map(|r| r.as_f32()),
+            metadata.exif.fnumber.map(|r| r.as_f32()),
+            metadata
+                .exif
+                .iso_speed_ratings
+                .map(|v| v as f32)
+                .or(metadata.exif.iso_speed.map(|v| v as f32)),
+        )
+    } else {
+        let exif = ExifReader::new().read_from_container(&mut Cursor::new(file_bytes)).ok()?;
+        let rational = |tag: Tag| match &exif.get_field(tag, In::PRIMARY)?.value {
+            ExifValue::Rational(v) => v.first().map(|r| r.to_f64() as f32),
+            _ => None,
+        };
+        let iso = exif
+            .get_field(Tag::PhotographicSensitivity, In::PRIMARY)
+            .and_then(|f| f.value.get_uint(0))
+            .map(|v| v as f32);
+        (rational(Tag::ExposureTime), rational(Tag::FNumber), iso)
+    };
+
+    let exposure_time = exposure_time.filter(|t| *t > 0.0)?;
+    let fnumber = fnumber.filter(|f| *f > 0.0).unwrap_or(1.0);
+    let iso = iso.filter(|i| *i > 0.0).unwrap_or(100.0);
+    Some(exposure_time * iso / (fnumber * fnumber))
+}
+
+fn load_frame(path: &str) -> Result<BracketFrame, String> {
+    let file_bytes = fs::read(path).map_err(|e| format!("Failed to read image {}: {}", path, e))?;
+    let exposure = read_exposure(&file_bytes, path);
+
+    let linear = if is_raw_file(path) {
+        develop_raw_image_linear(&file_bytes)
+            .map_err(|e| format!("Failed to develop image {}: {}", path, e))?
+            .to_rgb32f()
+    } else {
+        let mut image = load_image_with_orientation(&file_bytes)
+            .map_err(|e| format!("Failed to load image {}: {}", path, e))?
+            .to_rgb32f();
+        image.pixels_mut().for_each(|p| {
+            p.0 = p.0.map(srgb_to_linear);
+        });
+        image
+    };
+
+    Ok(BracketFrame { filename: path.to_string(), linear, exposure })
+}
+
+fn mean_well_exposed(a: &Rgb32FImage, b: &Rgb32FImage) -> Option<f32> {
+    let (sum_a, sum_b) = a
+        .pixels()
+        .zip(b.pixels())
+        .step_by(7)
+        .filter(|(pa, pb)| {
+            let max_a = pa.0.iter().cloned().fold(0.0, f32::max);
+            let max_b = pb.0.iter().cloned().fold(0.0, f32::max);
+            max_a > 0.02 && max_a < 0.9 && max_b > 0.02 && max_b < 0.9
+        })
+        .fold((0.0f64, 0.0f64), |(sa, sb), (pa, pb)| {
+            (sa + pa.0.iter().sum::<f32>() as f64, sb + pb.0.iter().sum::<f32>() as f64)
+        });
+    if sum_a > 0.0 && sum_b > 0.0 {
+        Some((sum_b / sum_a) as f32)
+    } else {
+        None
+    }
+}
+
+fn to_normalized_gray(image: &Rgb32FImage, gain: f32) -> GrayImage {
+    let (w, h) = image.dimensions();
+    GrayImage::from_fn(w, h, |x, y| {
+        let p = image.get_pixel(x, y);
+        let luma = (0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2]) * gain;
+        Luma([(linear_to_srgb(luma) * 255.0).round() as u8])
+    })
+}
+
+pub fn find_alignment(reference_gray: &GrayImage, frame_gray: &GrayImage, brief_pairs: &[(Point2<i32>, Point2<i32>)]) -> Option<Matrix3<f64>> {
+    let (w, h) = reference_gray.dimensions();
+    let (new_w, new_h, scale) = processing::calculate_downscale_dimensions(w, h);
+    let small_ref = image::imageops::resize(reference_gray, new_w, new_h, image::imageops::FilterType::Triangle);
+    let small_frame = image::imageops::resize(frame_gray, new_w, new_h, image::imageops::FilterType::Triangle);
+
+    let features_ref = processing::find_features(&small_ref, brief_pairs);
+    let features_frame = processing::find_features(&small_frame, brief_pairs);
+    let matches = processing::match_features(&features_ref, &features_frame);
+    if matches.len() < processing::MIN_INLIERS_FOR_CONNECTION {
+        return None;
+    }
+
+    let keypoints_ref: Vec<KeyPoint> = features_ref.iter().map(|f| f.keypoint).collect();
+    let keypoints_frame: Vec<KeyPoint> = features_frame.iter().map(|f| f.keypoint).collect();
+    let (_, inliers) = processing::find_homography_ransac(&matches, &keypoints_ref, &keypoints_frame)?;
+    if inliers.len() < processing::MIN_INLIERS_FOR_CONNECTION {
+        return None;
+    }
+
+    let inlier_points: Vec<(Point2<f64>, Point2<f64>)> = inliers
+        .iter()
+        .map(|m| {
+            let p1 = keypoints_ref[m.index1];
+            let p2 = keypoints_frame[m.index2];
+            (Point2::new(p1.x as f64, p1.y as f64), Point2::new(p2.x as f64, p2.y as f64))
+        })
+        .collect();
+    let h_small = processing::compute_homography(&inlier_points)?;
+
+    let to_small = Matrix3::new(1.0 / scale, 0.0, 0.0, 0.0, 1.0 / scale, 0.0, 0.0, 0.0, 1.0);
+    let to_full = Matrix3::new(scale, 0.0, 0.0, 0.0, scale, 0.0, 0.0, 0.0, 1.0);
+    Some(to_full * h_small * to_small)
+}
+
+fn sample_bilinear(image: &Rgb32FImage, x: f64, y: f64) -> Option<[f32; 3]> {
+    let (w, h) = image.dimensions();
+    if x < 0.0 || y < 0.0 || x > (w - 1) as f64 || y > (h - 1) as f64 {
+        return None;
+    }
+    let x0 = x.floor() as u32;
+    let y0 = y.floor() as u32;
+    let x1 = (x0 + 1).min(w - 1);
+    let y1 = (y0 + 1).min(h - 1);
+    let fx = (x - x0 as f64) as f32;
+    let fy = (y - y0 as f64) as f32;
+
+    let p00 = image.get_pixel(x0, y0).0;
+    let p10 = image.get_pixel(x1, y0).0;
+    let p01 = image.get_pixel(x0, y1).0;
+    let p11 = image.get_pixel(x1, y1).0;
+    let mut out = [0.0; 3];
+    for c in 0..3 {
+        let top = p00[c] + (p10[c] - p00[c]) * fx;
+        let bottom = p01[c] + (p11[c] - p01[c]) * fx;
+        out[c] = top + (bottom - top) * fy;
+    }
+    Some(out)
+}
+
+pub fn warp_to_reference(frame: &Rgb32FImage, homography: &Matrix3<f64>, width: u32, height: u32) -> Vec<Option<[f32; 3]>> {
+    (0..(width * height) as usize)
+        .into_par_iter()
+        .map(|i| {
+            let x = (i as u32 % width) as f64;
+            let y = (i as u32 / width) as f64;
+            let p = homography * Vector3::new(x, y, 1.0);
+            if p.z.abs() < 1e-9 {
+                return None;
+            }
+            sample_bilinear(frame, p.x / p.z, p.y / p.z)
+        })
+        .collect()
+}
+
+fn merge_weight(pixel: &[f32; 3]) -> f32 {
+    let max_c = pixel.iter().cloned().fold(0.0, f32::max);
+    if max_c >= CLIP_HIGH || max_c <= CLIP_LOW {
+        return 0.0;
+    }
+    let v = linear_to_srgb(max_c);
+    1.0 - (2.0 * v - 1.0).abs().powi(4)
+}
+
+pub fn merge_hdr_images(paths: Vec<String>, app_handle: AppHandle) -> Result<Rgb32FImage, String> {
+    if paths.len() < 2 {
+        return Err("At least two bracketed exposures are required for an HDR merge.".to_string());
+    }
+
+    let start_time = Instant::now();
+    let _ = app_handle.emit("hdr-progress", "Loading bracketed exposures...");
+    println!("Starting HDR merge for {} images...", paths.len());
+
+    let frames: Vec<BracketFrame> = paths
+        .par_iter()
+        .map(|p| {
+            let _ = app_handle.emit("hdr-progress", format!("Loading '{}'", Path::new(p).file_name().unwrap_or_default().to_string_lossy()));
+            load_frame(p)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let (width, height) = frames[0].linear.dimensions();
+    if frames.iter().any(|f| f.linear.dimensions() != (width, height)) {
+        return Err("All bracketed exposures must have the same dimensions.".to_string());
+    }
+    println!("Loaded exposures in {:.2?}", start_time.elapsed());
+
+    let reference_index = frames.len() / 2;
+    let reference = &frames[reference_index];
+
+    let _ = app_handle.emit("hdr-progress", "Estimating exposure differences...");
+    let relative_exposures: Vec<f32> = frames
+        .iter()
+        .map(|frame| match (frame.exposure, reference.exposure) {
+            (Some(e), Some(r)) if r > 0.0 => e / r,
+            _ => mean_well_exposed(&reference.linear, &frame.linear).unwrap_or(1.0),
+        })
+        .collect();
+    println!("Relative exposures: {:?}", relative_exposures);
+
+    let _ = app_handle.emit("hdr-progress", "Aligning exposures...");
+    let brief_pairs = processing::generate_brief_pairs();
+    let reference_gray = to_normalized_gray(&reference.linear, 1.0);
+
+    let aligned: Vec<Vec<Option<[f32; 3]>>> = frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            let homography = if i == reference_index {
+                Matrix3::identity()
+            } else {
+                let frame_gray = to_normalized_gray(&frame.linear, 1.0 / relative_exposures[i].max(1e-6));
+                find_alignment(&reference_gray, &frame_gray, &brief_pairs).unwrap_or_else(|| {
+                    let warning = format!("Could not align '{}', merging without alignment.", Path::new(&frame.filename).file_name().unwrap_or_default().to_string_lossy());
+                    println!("{}", warning);
+                    let _ = app_handle.emit("hdr-warning", warning);
+                    Matrix3::identity()
+                })
+            };
+            warp_to_reference(&frame.linear, &homography, width, height)
+        })
+        .collect();
+
+    let darkest = relative_exposures
+        .iter()
+        .enumerate()
+        .min_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let brightest = relative_exposures
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+
+    let _ = app_handle.emit("hdr-progress", "Merging to 32-bit radiance map...");
+    let merged: Vec<f32> = (0..(width * height) as usize)
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            let mut accum = [0.0f32; 3];
+            let mut total_weight = 0.0f32;
+            for (frame_index, samples) in aligned.iter().enumerate() {
+                if let Some(pixel) = &samples[i] {
+                    let w = merge_weight(pixel);
+                    if w > 0.0 {
+                        let exposure = relative_exposures[frame_index].max(1e-6);
+                        for c in 0..3 {
+                            accum[c] += w * pixel[c] / exposure;
+                        }
+                        total_weight += w;
+                    }
+                }
+            }
+
+            if total_weight > 0.0 {
+                accum.map(|c| c / total_weight)
+            } else {
+                let reference_pixel = aligned[reference_index][i].unwrap_or([0.0; 3]);
+                let fallback = if reference_pixel.iter().cloned().fold(0.0, f32::max) >= CLIP_HIGH { darkest } else { brightest };
+                let pixel = aligned[fallback][i].unwrap_or(reference_pixel);
+                let exposure = relative_exposures[fallback].max(1e-6);
+                pixel.map(|c| c / exposure)
+            }
+        })
+        .collect();
+
+    println!("HDR merge completed in {:.2?}", start_time.elapsed());
+    let _ = app_handle.emit("hdr-progress", "Finalizing HDR image...");
+
+    Rgb32FImage::from_raw(width, height, merged).ok_or_else(|| "Failed to assemble merged HDR image.".to_string())
+}
+
+// The editor reads float sources as sRGB encoded, like every other image, and tonemaps values above
+// 1.0 on the GPU. The merge is saved with the same encoding, without clamping, so its headroom survives.
+pub fn encode_for_editing(mut image: Rgb32FImage) -> Rgb32FImage {
+    image.par_chunks_mut(3).for_each(|p| {
+        for c in p.iter_mut() {
+            let linear = c.max(0.0);
+            *c = if linear <= 0.0031308 { linear * 12.92 } else { 1.055 * linear.powf(1.0 / 2.4) - 0.055 };
+        }
+    });
+    image
+}
+
+pub fn tonemap_for_display(image: &Rgb32FImage) -> RgbImage {
+    let (w, h) = image.dimensions();
+    let mut out = RgbImage::new(w, h);
+    out.par_chunks_mut(3)
+        .zip(image.par_chunks(3))
+        .for_each(|(dst, src)| {
+            for c in 0..3 {
+                dst[c] = (apply_tonemap_and_gamma(src[c]) * 255.0).round().clamp(0.0, 255.0) as u8;
+            }
+        });
+    out
+}
@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+
+use base64::{engine::general_purpose, Engine as _};
+use futures_util::{SinkExt, StreamExt};
+use image::codecs::jpeg::JpegEncoder;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::{Message, Role};
+use tokio_tungstenite::WebSocketStream;
+
+use crate::file_management::{
+    get_cached_or_generate_thumbnail_image, get_sidecar_path, list_images_in_dir, load_export_presets, AppSettings,
+};
+use crate::gpu_processing::get_or_init_gpu_context;
+use crate::iptc_metadata::read_metadata;
+use crate::{xmp_sidecar, AppState, ExportSettings};
+
+pub const DEFAULT_HTTP_API_PORT: u16 = 8741;
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+// The listener and every connection it accepted, WebSockets included, stop once the server is stopped
+// or dropped, since they all wait on the shutdown channel.
+pub struct HttpApiServer {
+    port: u16,
+    token: String,
+    shutdown: watch::Sender<bool>,
+}
+
+impl HttpApiServer {
+    fn stop(self) {
+        let _ = self.shutdown.send(true);
+        println!("HTTP API on port {} stopped.", self.port);
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+enum ApiResponse {
+    Json(Value),
+    Jpeg(Vec<u8>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RatingParams {
+    paths: Vec<String>,
+    rating: u8,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportParams {
+    paths: Vec<String>,
+    preset_id: String,
+    output_folder: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WsRequest {
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        let n = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("Connection closed".to_string());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buf.len() > MAX_HEAD_SIZE {
+            return Err("Request header too large".to_string());
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default();
+    let (path, query_string) = target.split_once('?').unwrap_or((target, ""));
+    let query = query_string
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (percent_decode(k), percent_decode(v)))
+        .collect();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+
+    let content_length: usize = headers.get("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+    if content_length > MAX_BODY_SIZE {
+        return Err("Request body too large".to_string());
+    }
+    let mut body = buf[head_end + 4..].to_vec();
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    Ok(Request { method, path: path.to_string(), query, headers, body })
+}
+
+async fn write_response(stream: &mut TcpStream, status: u16, content_type: &str, body: &[u8]) {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        content_type,
+        body.len()
+    );
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(body).await;
+}
+
+async fn write_json(stream: &mut TcpStream, status: u16, value: Value) {
+    write_response(stream, status, "application/json", value.to_string().as_bytes()).await;
+}
+
+// Compares every byte, so the time taken doesn't reveal how much of the token was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn is_authorized(request: &Request, token: &str) -> bool {
+    let bearer = request.headers.get("authorization").and_then(|v| v.strip_prefix("Bearer "));
+    bearer
+        .or(request.query.get("token").map(String::as_str))
+        .map_or(false, |given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+}
+
+fn set_rating(params: RatingParams, app_handle: &AppHandle) -> Result<Value, String> {
+    let rating = params.rating.min(5);
+    for path in &params.paths {
+        let mut metadata = read_metadata(path);
+        metadata.rating = rating;
+        if !metadata.adjustments.is_object() {
+            metadata.adjustments = json!({});
+        }
+        metadata.adjustments["rating"] = json!(rating);
+        let json_string = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
+        fs::write(get_sidecar_path(path), json_string).map_err(|e| e.to_string())?;
+        let _ = app_handle.emit("thumbnail-generated", json!({ "path": path, "rating": rating }));
+    }
+    xmp_sidecar::sync_if_enabled(app_handle, &params.paths);
+    Ok(json!({ "updated": params.paths.len() }))
+}
+
+fn thumbnail(path: &str, app_handle: &AppHandle) -> Result<Vec<u8>, String> {
+    let state = app_handle.state::<AppState>();
+    let gpu_context = get_or_init_gpu_context(&state).ok();
+    let image = get_cached_or_generate_thumbnail_image(path, app_handle, gpu_context.as_ref()).map_err(|e| e.to_string())?;
+    let mut buf = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut buf, 85)
+        .encode_image(&image.to_rgb8())
+        .map_err(|e| e.to_string())?;
+    Ok(buf.into_inner())
+}
+
+// Starts a batch export with a saved export preset. Progress is reported through the regular export
+// events, so the app shows it like an export started from the UI.
+async fn export(params: ExportParams, app_handle: &AppHandle) -> Result<Value, String> {
+    let preset = load_export_presets(app_handle.clone())?
+        .into_iter()
+        .find(|p| p.id == params.preset_id)
+        .ok_or_else(|| format!("Export preset not found: {}", params.preset_id))?;
+    let output_folder = params
+        .output_folder
+        .or(preset.output_folder)
+        .ok_or("The export preset has no output folder, pass outputFolder.")?;
+    let export_settings: ExportSettings =
+        serde_json::from_value(preset.export_settings).map_err(|e| format!("Invalid export preset: {}", e))?;
+    let output_format = if preset.file_format == "jpeg" { "jpg".to_string() } else { preset.file_format };
+
+    let total = params.paths.len();
+    crate::batch_export_images(
+        output_folder,
+        params.paths,
+        export_settings,
+        output_format,
+        None,
+        app_handle.state::<AppState>(),
+        app_handle.clone(),
+    )
+    .await?;
+    Ok(json!({ "started": total }))
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, String> {
+    serde_json::from_value(params).map_err(|e| format!("Invalid parameters: {}", e))
+}
+
+async fn dispatch(method: &str, params: Value, app_handle: &AppHandle) -> Result<ApiResponse, String> {
+    let path_param = || params.get("path").and_then(Value::as_str).map(String::from).ok_or("Missing 'path'.".to_string());
+    match method {
+        "listFolder" => {
+            let path = path_param()?;
+            let handle = app_handle.clone();
+            let images = tokio::task::spawn_blocking(move || list_images_in_dir(path, handle))
+                .await
+                .map_err(|e| e.to_string())??;
+            Ok(ApiResponse::Json(json!(images)))
+        }
+        "thumbnail" => {
+            let path = path_param()?;
+            let handle = app_handle.clone();
+            let data = tokio::task::spawn_blocking(move || thumbnail(&path, &handle))
+                .await
+                .map_err(|e| e.to_string())??;
+            Ok(ApiResponse::Jpeg(data))
+        }
+        "setRating" => set_rating(parse_params(params)?, app_handle).map(ApiResponse::Json),
+        "exportPresets" => Ok(ApiResponse::Json(json!(load_export_presets(app_handle.clone())?))),
+        "export" => export(parse_params(params)?, app_handle).await.map(ApiResponse::Json),
+        _ => Err(format!("Unknown method '{}'.", method)),
+    }
+}
+
+fn route(request: &Request) -> Option<(&'static str, Value)> {
+    let query = json!(request.query);
+    let body = || serde_json::from_slice(&request.body).unwrap_or(Value::Null);
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/api/folder") => Some(("listFolder", query)),
+        ("GET", "/api/thumbnail") => Some(("thumbnail", query)),
+        ("POST", "/api/rating") => Some(("setRating", body())),
+        ("GET", "/api/export-presets") => Some(("exportPresets", Value::Null)),
+        ("POST", "/api/export") => Some(("export", body())),
+        _ => None,
+    }
+}
+
+// The socket speaks JSON-RPC style messages: {"id", "method", "params"} answered by {"id", "result"}
+// or {"id", "error"}, with the same methods as the REST routes. Thumbnails are returned base64 encoded.
+async fn handle_websocket(mut stream: TcpStream, request: Request, app_handle: AppHandle) {
+    let Some(key) = request.headers.get("sec-websocket-key") else {
+        write_json(&mut stream, 400, json!({ "error": "Missing Sec-WebSocket-Key" })).await;
+        return;
+    };
+    let head = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.as_bytes())
+    );
+    if stream.write_all(head.as_bytes()).await.is_err() {
+        return;
+    }
+
+    let mut socket = WebSocketStream::from_raw_socket(stream, Role::Server, None).await;
+    while let Some(Ok(message)) = socket.next().await {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let reply = match serde_json::from_str::<WsRequest>(&text) {
+            Ok(ws_request) => match dispatch(&ws_request.method, ws_request.params, &app_handle).await {
+                Ok(ApiResponse::Json(result)) => json!({ "id": ws_request.id, "result": result }),
+                Ok(ApiResponse::Jpeg(data)) => json!({
+                    "id": ws_request.id,
+                    "result": { "data": format!("data:image/jpeg;base64,{}", general_purpose::STANDARD.encode(data)) },
+                }),
+                Err(e) => json!({ "id": ws_request.id, "error": e }),
+            },
+            Err(e) => json!({ "id": null, "error": format!("Invalid message: {}", e) }),
+        };
+        if socket.send(Message::Text(reply.to_string())).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, token: String, app_handle: AppHandle) {
+    let request = match read_request(&mut stream).await {
+        Ok(request) => request,
+        Err(e) => {
+            write_json(&mut stream, 400, json!({ "error": e })).await;
+            return;
+        }
+    };
+    if !is_authorized(&request, &token) {
+        write_json(&mut stream, 401, json!({ "error": "Missing or invalid API token." })).await;
+        return;
+    }
+
+    let is_upgrade = request.headers.get("upgrade").map_or(false, |v| v.eq_ignore_ascii_case("websocket"));
+    if request.path == "/ws" && is_upgrade {
+        handle_websocket(stream, request, app_handle).await;
+        return;
+    }
+
+    let Some((method, params)) = route(&request) else {
+        write_json(&mut stream, 404, json!({ "error": "Not found" })).await;
+        return;
+    };
+    match dispatch(method, params, &app_handle).await {
+        Ok(ApiResponse::Json(value)) => write_json(&mut stream, 200, value).await,
+        Ok(ApiResponse::Jpeg(data)) => write_response(&mut stream, 200, "image/jpeg", &data).await,
+        Err(e) => write_json(&mut stream, 500, json!({ "error": e })).await,
+    }
+}
+
+fn start_server(port: u16, token: String, app_handle: &AppHandle) -> Result<HttpApiServer, String> {
+    // Only reachable from this machine; every request also has to carry the token.
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Failed to start the HTTP API on port {}: {}", port, e))?;
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+    let server_token = token.clone();
+    let app_handle = app_handle.clone();
+    let (shutdown, mut server_shutdown) = watch::channel(false);
+    let connection_shutdown = shutdown.subscribe();
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Failed to start the HTTP API: {}", e);
+                return;
+            }
+        };
+        println!("HTTP API listening on 127.0.0.1:{}", port);
+        loop {
+            let stream = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(_) => break,
+                },
+                _ = server_shutdown.changed() => break,
+            };
+            let mut shutdown = connection_shutdown.clone();
+            let connection = handle_connection(stream, server_token.clone(), app_handle.clone());
+            tokio::spawn(async move {
+                tokio::select! {
+                    _ = connection => {}
+                    _ = shutdown.changed() => {}
+                }
+            });
+        }
+    });
+    Ok(HttpApiServer { port, token, shutdown })
+}
+
+// Starts, restarts or stops the server to match the settings.
+pub fn apply_http_api(app_handle: &AppHandle, settings: &AppSettings) {
+    let enabled = settings.enable_http_api.unwrap_or(false);
+    let port = settings.http_api_port.unwrap_or(DEFAULT_HTTP_API_PORT);
+    let token = settings.http_api_token.clone().unwrap_or_default();
+
+    let state = app_handle.state::<AppState>();
+    let mut current = state.http_api.lock().unwrap();
+    if let Some(server) = current.as_ref() {
+        if enabled && server.port == port && server.token == token {
+            return;
+        }
+    }
+    if let Some(server) = current.take() {
+        server.stop();
+    }
+    if !enabled {
+        return;
+    }
+    if token.is_empty() {
+        eprintln!("The HTTP API needs a token and was not started.");
+        return;
+    }
+    match start_server(port, token, app_handle) {
+        Ok(server) => *current = Some(server),
+        Err(e) => eprintln!("{}", e),
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::{Result, Context};
 use base64::{engine::general_purpose, Engine as _};
-use image::{imageops, DynamicImage, ImageReader, RgbaImage, Rgba};
+use image::{imageops, DynamicImage, ImageDecoder, ImageReader, RgbaImage, Rgba};
 use rawler::Orientation;
 use std::io::Cursor;
 use rayon::prelude::*;
@@ -8,10 +8,12 @@ use serde_json::Value;
 use std::fs;
 
 use exif::{Reader as ExifReader, Tag};
-use crate::image_processing::apply_orientation;
+use crate::color_profiles::{convert_from_embedded_profile, identify_icc_profile, OutputColorSpace};
+use crate::image_processing::{apply_orientation, oriented_dimensions};
 
 use crate::formats::is_raw_file;
-use crate::raw_processing::develop_raw_image;
+use crate::raw_processing::{develop_raw_image, developed_raw_dimensions};
+use crate::spot_removal::apply_spot_edits;
 
 pub fn load_and_composite(
     path: &str,
@@ -35,6 +37,26 @@ pub fn load_base_image_from_bytes(
     }
 }
 
+// Dimensions the base image will have once loaded, read from the headers instead of decoding it.
+pub fn base_image_dimensions_from_bytes(bytes: &[u8], path_for_ext_check: &str) -> Result<(u32, u32)> {
+    if is_raw_file(path_for_ext_check) {
+        return developed_raw_dimensions(bytes);
+    }
+    let cursor = Cursor::new(bytes);
+    let (width, height) = ImageReader::new(cursor.clone())
+        .with_guessed_format()
+        .context("Failed to guess image format")?
+        .into_dimensions()
+        .context("Failed to read image dimensions")?;
+    let orientation = ExifReader::new()
+        .read_from_container(&mut cursor.clone())
+        .ok()
+        .and_then(|exif| exif.get_field(Tag::Orientation, exif::In::PRIMARY).and_then(|f| f.value.get_uint(0)))
+        .map(|o| Orientation::from_u16(o as u16))
+        .unwrap_or(Orientation::Normal);
+    Ok(oriented_dimensions(width, height, orientation))
+}
+
 pub fn load_image_with_orientation(bytes: &[u8]) -> Result<DynamicImage> {
     let cursor = Cursor::new(bytes);
     let mut reader = ImageReader::new(cursor.clone())
@@ -42,7 +64,12 @@ pub fn load_image_with_orientation(bytes: &[u8]) -> Result<DynamicImage> {
         .context("Failed to guess image format")?;
 
     reader.no_limits();
-    let image = reader.decode().context("Failed to decode image")?;
+    let mut decoder = reader.into_decoder().context("Failed to decode image")?;
+    let icc_profile = decoder.icc_profile().ok().flatten();
+    let mut image = DynamicImage::from_decoder(decoder).context("Failed to decode image")?;
+    if let Some(icc_profile) = icc_profile {
+        image = convert_from_embedded_profile(image, &icc_profile);
+    }
 
     let exif_reader = ExifReader::new();
     if let Ok(exif) = exif_reader.read_from_container(&mut cursor.clone()) {
@@ -55,10 +82,23 @@ pub fn load_image_with_orientation(bytes: &[u8]) -> Result<DynamicImage> {
     Ok(image)
 }
 
+// Standard color space of the profile embedded in a non-raw source, if any. Reads only the headers.
+pub fn source_color_space(path: &str) -> Option<OutputColorSpace> {
+    if is_raw_file(path) {
+        return None;
+    }
+    let mut decoder = ImageReader::open(path).ok()?.with_guessed_format().ok()?.into_decoder().ok()?;
+    let icc_profile = decoder.icc_profile().ok().flatten()?;
+    identify_icc_profile(&icc_profile)
+}
+
 pub fn composite_patches_on_image(
     base_image: &DynamicImage,
     current_adjustments: &Value,
 ) -> Result<DynamicImage> {
+    let spot_edited_image = apply_spot_edits(base_image, current_adjustments).map_err(anyhow::Error::msg)?;
+    let base_image = spot_edited_image.as_ref().unwrap_or(base_image);
+
     let patches_val = match current_adjustments.get("aiPatches") {
         Some(val) => val,
         None => return Ok(base_image.clone()),
@@ -94,6 +134,8 @@ pub fn composite_patches_on_image(
             
             let color_b64 = patch_data.get("color").and_then(|v| v.as_str())?;
             let mask_b64 = patch_data.get("mask").and_then(|v| v.as_str())?;
+            let opacity = patch_obj.get("opacity").and_then(|v| v.as_f64()).unwrap_or(100.0);
+            let opacity_multiplier = (opacity as f32 / 100.0).clamp(0.0, 1.0);
 
             let result: Result<RgbaImage> = (|| {
                 let color_bytes = general_purpose::STANDARD.decode(color_b64)?;
@@ -113,7 +155,7 @@ pub fn composite_patches_on_image(
                             color_pixel[0],
                             color_pixel[1],
                             color_pixel[2],
-                            mask_pixel[0],
+                            (mask_pixel[0] as f32 * opacity_multiplier) as u8,
                         ]));
                     }
                 }
@@ -0,0 +1,172 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::sync::{Arc, Condvar, Mutex, Once};
+use std::time::SystemTime;
+
+use image::DynamicImage;
+
+use crate::formats::is_video_file;
+use crate::image_loader::load_base_image_from_bytes;
+use crate::lens_correction::{read_lens_info, LensInfo};
+use crate::memory_budget::{enforce_memory_budget, image_bytes};
+use crate::{read_exif_data, AppState};
+
+const DEFAULT_CACHE_CAPACITY: usize = 3;
+
+// The image is shared with the editor's loaded image, so opening a prefetched file doesn't copy it.
+pub struct DecodedImage {
+    pub image: Arc<DynamicImage>,
+    pub lens_info: LensInfo,
+    pub exif: HashMap<String, String>,
+    modified: Option<SystemTime>,
+}
+
+struct CacheEntries {
+    capacity: usize,
+    entries: VecDeque<(String, Arc<DecodedImage>)>,
+}
+
+#[derive(Default)]
+struct PrefetchRequest {
+    pending: VecDeque<String>,
+    paths: HashSet<String>,
+}
+
+// Prefetches are decoded one at a time by a single worker. A new request replaces the paths still
+// waiting from the previous one.
+pub struct PrefetchCache {
+    entries: Mutex<CacheEntries>,
+    request: Mutex<PrefetchRequest>,
+    pending_changed: Condvar,
+    worker: Once,
+}
+
+impl Default for PrefetchCache {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::new(CacheEntries { capacity: DEFAULT_CACHE_CAPACITY, entries: VecDeque::new() }),
+            request: Mutex::new(PrefetchRequest::default()),
+            pending_changed: Condvar::new(),
+            worker: Once::new(),
+        }
+    }
+}
+
+fn file_modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl PrefetchCache {
+    pub fn get(&self, path: &str) -> Option<Arc<DecodedImage>> {
+        let mut cache = self.entries.lock().unwrap();
+        let index = cache.entries.iter().position(|(p, _)| p == path)?;
+        let (key, image) = cache.entries.remove(index)?;
+        if image.modified != file_modified(path) {
+            return None;
+        }
+        cache.entries.push_front((key, image.clone()));
+        Some(image)
+    }
+
+    pub fn insert(&self, path: String, image: Arc<DecodedImage>) {
+        let mut cache = self.entries.lock().unwrap();
+        cache.entries.retain(|(p, _)| p != &path);
+        cache.entries.push_front((path, image));
+        let capacity = cache.capacity;
+        cache.entries.truncate(capacity);
+    }
+
+    // Bytes held only by the cache; an image that is also open in the editor is counted there.
+    pub fn total_bytes(&self, loaded: Option<&Arc<DynamicImage>>) -> u64 {
+        let cache = self.entries.lock().unwrap();
+        cache
+            .entries
+            .iter()
+            .filter(|(_, image)| !loaded.map_or(false, |loaded| Arc::ptr_eq(loaded, &image.image)))
+            .map(|(_, image)| image_bytes(&image.image))
+            .sum()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().entries.clear();
+    }
+
+    pub fn evict_oldest(&self) -> Option<u64> {
+        let mut cache = self.entries.lock().unwrap();
+        cache.entries.pop_back().map(|(_, image)| {
+            if Arc::strong_count(&image.image) > 1 {
+                0
+            } else {
+                image_bytes(&image.image)
+            }
+        })
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.entries.lock().unwrap().entries.iter().any(|(p, _)| p == path)
+    }
+
+    fn set_capacity(&self, capacity: usize) {
+        let mut cache = self.entries.lock().unwrap();
+        cache.capacity = capacity.max(1);
+        let capacity = cache.capacity;
+        cache.entries.truncate(capacity);
+    }
+}
+
+pub fn decode_for_editing(path: &str) -> Result<DecodedImage, String> {
+    if is_video_file(path) {
+        return Err("Videos can't be opened in the editor.".to_string());
+    }
+    let modified = file_modified(path);
+    let file_bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let image = load_base_image_from_bytes(&file_bytes, path, false).map_err(|e| e.to_string())?;
+    Ok(DecodedImage {
+        image: Arc::new(image),
+        lens_info: read_lens_info(&file_bytes, path),
+        exif: read_exif_data(&file_bytes),
+        modified,
+    })
+}
+
+fn run_prefetch_worker(cache: Arc<PrefetchCache>, app_handle: tauri::AppHandle) {
+    loop {
+        let path = {
+            let mut request = cache.request.lock().unwrap();
+            loop {
+                match request.pending.pop_front() {
+                    Some(path) => break path,
+                    None => request = cache.pending_changed.wait(request).unwrap(),
+                }
+            }
+        };
+        if cache.contains(&path) {
+            continue;
+        }
+        match decode_for_editing(&path) {
+            Ok(decoded) => {
+                // The request may have moved on while this image was decoding.
+                if cache.request.lock().unwrap().paths.contains(&path) {
+                    cache.insert(path, Arc::new(decoded));
+                    enforce_memory_budget(&app_handle);
+                }
+            }
+            Err(e) => eprintln!("Failed to prefetch {}: {}", path, e),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn prefetch_images(paths: Vec<String>, state: tauri::State<AppState>, app_handle: tauri::AppHandle) {
+    let cache = state.prefetch_cache.clone();
+    cache.set_capacity(paths.len() + 1);
+    cache.worker.call_once(|| {
+        let cache = cache.clone();
+        std::thread::spawn(move || run_prefetch_worker(cache, app_handle));
+    });
+
+    let mut request = cache.request.lock().unwrap();
+    request.paths = paths.iter().cloned().collect();
+    request.pending = paths.into_iter().collect();
+    cache.pending_changed.notify_one();
+}
@@ -1,16 +1,79 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use bytemuck::{Pod, Zeroable};
-use image::{DynamicImage, GenericImageView, Rgba};
+use image::{DynamicImage, GenericImageView, ImageBuffer, ImageFormat, Rgba, Rgba32FImage};
+use std::io::Cursor;
 use imageproc::geometric_transformations::{rotate_about_center, Interpolation};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::f32::consts::PI;
 use rawler::decoders::Orientation;
 use serde_json::json;
+use rayon::prelude::*;
 
 pub use crate::gpu_processing::{get_or_init_gpu_context, process_and_get_dynamic_image};
+use crate::gpu_processing::{CachedPipeline, PipelineKind, ScopeCounts, SCOPE_BINS};
 use crate::{AppState, mask_generation::MaskDefinition, load_settings};
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GpsLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IptcMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_terms: Option<String>,
+}
+
+impl IptcMetadata {
+    pub fn is_empty(&self) -> bool {
+        self == &IptcMetadata::default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFlag {
+    Pick,
+    Reject,
+}
+
+impl ImageFlag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageFlag::Pick => "pick",
+            ImageFlag::Reject => "reject",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pick" => Some(ImageFlag::Pick),
+            "reject" => Some(ImageFlag::Reject),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EditVersion {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub adjustments: Value,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ImageMetadata {
     pub version: u32,
@@ -18,6 +81,18 @@ pub struct ImageMetadata {
     pub adjustments: Value,
     #[serde(default)]
     pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gps: Option<GpsLocation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flag: Option<ImageFlag>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<EditVersion>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iptc: Option<IptcMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture_time_shift: Option<i64>,
 }
 
 impl Default for ImageMetadata {
@@ -27,6 +102,12 @@ impl Default for ImageMetadata {
             rating: 0,
             adjustments: Value::Null,
             tags: None,
+            gps: None,
+            flag: None,
+            versions: Vec::new(),
+            active_version: None,
+            iptc: None,
+            capture_time_shift: None,
         }
     }
 }
@@ -52,6 +133,13 @@ pub fn apply_orientation(image: DynamicImage, orientation: Orientation) -> Dynam
     }
 }
 
+pub fn oriented_dimensions(width: u32, height: u32, orientation: Orientation) -> (u32, u32) {
+    match orientation {
+        Orientation::Transpose | Orientation::Rotate90 | Orientation::Transverse | Orientation::Rotate270 => (height, width),
+        _ => (width, height),
+    }
+}
+
 pub fn apply_coarse_rotation(image: DynamicImage, orientation_steps: u8) -> DynamicImage {
     match orientation_steps {
         1 => image.rotate90(),
@@ -102,6 +190,22 @@ pub fn apply_crop(mut image: DynamicImage, crop_value: &Value) -> DynamicImage {
     image
 }
 
+// Export post-processing works on float pixels and hands back the depth and alpha of the image it
+// was given, so a wide-gamut render is only quantized by the encoder.
+pub fn with_depth_of(source: &DynamicImage, rgba: Rgba32FImage) -> DynamicImage {
+    let color = source.color();
+    let bytes_per_channel = color.bytes_per_pixel() / color.channel_count();
+    let rgba = DynamicImage::ImageRgba32F(rgba);
+    match (bytes_per_channel, color.has_alpha()) {
+        (4, true) => rgba,
+        (4, false) => DynamicImage::ImageRgb32F(rgba.to_rgb32f()),
+        (2, true) => DynamicImage::ImageRgba16(rgba.to_rgba16()),
+        (2, false) => DynamicImage::ImageRgb16(rgba.to_rgb16()),
+        (_, true) => DynamicImage::ImageRgba8(rgba.to_rgba8()),
+        (_, false) => DynamicImage::ImageRgb8(rgba.to_rgb8()),
+    }
+}
+
 pub fn apply_flip(image: DynamicImage, horizontal: bool, vertical: bool) -> DynamicImage {
     let mut img = image;
     if horizontal {
@@ -173,6 +277,7 @@ pub struct GlobalAdjustments {
     pub clarity: f32,
     pub dehaze: f32,
     pub structure: f32,
+    pub texture: f32,
     pub vignette_amount: f32,
     pub vignette_midpoint: f32,
     pub vignette_roundness: f32,
@@ -189,7 +294,25 @@ pub struct GlobalAdjustments {
     pub negative_green_balance: f32,
     pub negative_blue_balance: f32,
     _pad_neg1: f32,
-    _pad_neg2: f32,
+
+    pub chromatic_aberration: f32,
+    pub lut_intensity: f32,
+    pub lut_size: u32,
+    pub working_space: u32,
+
+    pub defringe_purple_amount: f32,
+    pub defringe_purple_hue_min: f32,
+    pub defringe_purple_hue_max: f32,
+    pub defringe_green_amount: f32,
+    pub defringe_green_hue_min: f32,
+    pub defringe_green_hue_max: f32,
+    _pad_df1: f32,
+    _pad_df2: f32,
+
+    pub glow_amount: f32,
+    pub glow_radius: f32,
+    pub glow_threshold: f32,
+    pub custom_effect_amount: f32,
 
     pub color_grading_shadows: ColorGradeSettings,
     pub color_grading_midtones: ColorGradeSettings,
@@ -230,12 +353,26 @@ pub struct MaskAdjustments {
     pub clarity: f32,
     pub dehaze: f32,
     pub structure: f32,
+    pub texture: f32,
     
-    _pad1: f32,
     _pad2: f32,
     _pad3: f32,
     _pad4: f32,
 
+    pub defringe_purple_amount: f32,
+    pub defringe_purple_hue_min: f32,
+    pub defringe_purple_hue_max: f32,
+    pub defringe_green_amount: f32,
+    pub defringe_green_hue_min: f32,
+    pub defringe_green_hue_max: f32,
+    _pad_df1: f32,
+    _pad_df2: f32,
+
+    pub glow_amount: f32,
+    pub glow_radius: f32,
+    pub glow_threshold: f32,
+    _pad_glow1: f32,
+
     pub color_grading_shadows: ColorGradeSettings,
     pub color_grading_midtones: ColorGradeSettings,
     pub color_grading_highlights: ColorGradeSettings,
@@ -257,13 +394,206 @@ pub struct MaskAdjustments {
 
 #[derive(Debug, Clone, Copy, Pod, Zeroable, Default)]
 #[repr(C)]
-pub struct AllAdjustments {
-    pub global: GlobalAdjustments,
-    pub mask_adjustments: [MaskAdjustments; 16],
-    pub mask_count: u32,
+pub struct LensBlurParams {
+    pub amount: f32,
+    pub source: u32,
+    pub depth_mask_index: i32,
+    pub blade_count: u32,
+    pub center_x: f32,
+    pub center_y: f32,
+    pub focus_radius: f32,
+    pub feather: f32,
+    pub focal_depth: f32,
+    pub focus_range: f32,
+    pub blade_rotation: f32,
+    pub highlight_boost: f32,
+    pub tile_offset_x: u32,
+    pub tile_offset_y: u32,
+    pub input_offset_x: u32,
+    pub input_offset_y: u32,
+    pub full_width: u32,
+    pub full_height: u32,
+    _pad1: u32,
+    _pad2: u32,
+}
+
+#[derive(Debug, Clone, Copy, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct DenoiseParams {
+    pub luma_strength: f32,
+    pub chroma_strength: f32,
+    pub detail: f32,
+    pub level: u32,
+    pub last_level: u32,
+    pub offset_x: u32,
+    pub offset_y: u32,
+    _pad1: u32,
+}
+
+impl DenoiseParams {
+    pub fn is_enabled(&self) -> bool {
+        self.luma_strength > 0.0 || self.chroma_strength > 0.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, Pod, Zeroable, Default)]
+#[repr(C)]
+pub struct FilmEffectsParams {
+    pub halation_strength: f32,
+    pub halation_threshold: f32,
+    pub bloom_strength: f32,
+    pub radius: f32,
+    pub halation_tint_r: f32,
+    pub halation_tint_g: f32,
+    pub halation_tint_b: f32,
+    pub grain_size: f32,
+    pub grain_red: f32,
+    pub grain_green: f32,
+    pub grain_blue: f32,
+    pub pass_index: u32,
     pub tile_offset_x: u32,
     pub tile_offset_y: u32,
-    pub mask_atlas_cols: u32,
+    pub offset_x: u32,
+    pub offset_y: u32,
+}
+
+impl FilmEffectsParams {
+    pub fn is_enabled(&self) -> bool {
+        self.halation_strength > 0.0
+            || self.bloom_strength > 0.0
+            || self.grain_red > 0.0
+            || self.grain_green > 0.0
+            || self.grain_blue > 0.0
+    }
+}
+
+// Where the lens axis lies in the processed image, in pixels, and how much red and blue are
+// magnified against green about it.
+#[derive(Debug, Clone, Copy)]
+pub struct OpticalCenter {
+    pub x: f32,
+    pub y: f32,
+    pub lateral_ca: [f32; 2],
+}
+
+impl OpticalCenter {
+    // Flips and the straightening rotation turn the image about its center, so the axis stays at the
+    // center of the uncropped image and only the crop moves it.
+    pub fn of_crop(uncropped_width: u32, uncropped_height: u32, crop_offset: (f32, f32)) -> Self {
+        OpticalCenter {
+            x: uncropped_width as f32 / 2.0 - crop_offset.0,
+            y: uncropped_height as f32 / 2.0 - crop_offset.1,
+            lateral_ca: [1.0, 1.0],
+        }
+    }
+
+    pub fn with_lateral_ca(mut self, lateral_ca: [f32; 2]) -> Self {
+        self.lateral_ca = lateral_ca;
+        self
+    }
+}
+
+const LATERAL_CA_ANALYSIS_DIM: u32 = 1024;
+const LATERAL_CA_MAX_SCALE: f32 = 0.004;
+const LATERAL_CA_STEPS: i32 = 20;
+const LATERAL_CA_MIN_EDGE: f32 = 0.05;
+const LATERAL_CA_MIN_SAMPLES: usize = 100;
+const LATERAL_CA_MAX_SAMPLES: usize = 20000;
+
+fn sample_rgb32f_bilinear(image: &image::Rgb32FImage, x: f32, y: f32, channel: usize) -> f32 {
+    let (w, h) = image.dimensions();
+    let x = x.clamp(0.0, (w - 1) as f32);
+    let y = y.clamp(0.0, (h - 1) as f32);
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let top = image.get_pixel(x0, y0)[channel] * (1.0 - fx) + image.get_pixel(x1, y0)[channel] * fx;
+    let bottom = image.get_pixel(x0, y1)[channel] * (1.0 - fx) + image.get_pixel(x1, y1)[channel] * fx;
+    top * (1.0 - fy) + bottom * fy
+}
+
+// Lateral CA is a slight magnification of red and blue against green about the lens axis. The two
+// scales are found once per image on a small copy by lining up each channel's radial gradients with
+// green's at strong edges. Rotations and crops don't change them, so any transformed view can reuse them.
+pub fn estimate_lateral_ca(image: &DynamicImage) -> [f32; 2] {
+    let small = image.thumbnail(LATERAL_CA_ANALYSIS_DIM, LATERAL_CA_ANALYSIS_DIM).to_rgb32f();
+    let (width, height) = small.dimensions();
+    if width < 16 || height < 16 {
+        return [1.0, 1.0];
+    }
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let max_radius = cx.hypot(cy);
+
+    let radial_gradient = |x: f32, y: f32, dir: (f32, f32), scale: f32, channel: usize| -> f32 {
+        let (px, py) = (cx + (x - cx) * scale, cy + (y - cy) * scale);
+        sample_rgb32f_bilinear(&small, px + dir.0, py + dir.1, channel)
+            - sample_rgb32f_bilinear(&small, px - dir.0, py - dir.1, channel)
+    };
+
+    // The center barely shows any CA, so only edges in the outer part of the frame are measured.
+    let edges: Vec<(f32, f32, (f32, f32), f32)> = (2..height - 2)
+        .into_par_iter()
+        .flat_map_iter(|y| {
+            let radial_gradient = &radial_gradient;
+            (2..width - 2).filter_map(move |x| {
+                let (x, y) = (x as f32, y as f32);
+                let radius = (x - cx).hypot(y - cy);
+                if radius < max_radius * 0.3 {
+                    return None;
+                }
+                let dir = ((x - cx) / radius, (y - cy) / radius);
+                let green = radial_gradient(x, y, dir, 1.0, 1);
+                (green.abs() > LATERAL_CA_MIN_EDGE).then_some((x, y, dir, green))
+            })
+        })
+        .collect();
+    if edges.len() < LATERAL_CA_MIN_SAMPLES {
+        return [1.0, 1.0];
+    }
+    let stride = (edges.len() / LATERAL_CA_MAX_SAMPLES).max(1);
+    let edges: Vec<_> = edges.into_iter().step_by(stride).collect();
+
+    let best_scale = |channel: usize| -> f32 {
+        (-LATERAL_CA_STEPS..=LATERAL_CA_STEPS)
+            .map(|i| 1.0 + i as f32 / LATERAL_CA_STEPS as f32 * LATERAL_CA_MAX_SCALE)
+            .map(|scale| {
+                let error: f32 = edges
+                    .par_iter()
+                    .map(|&(x, y, dir, green)| (radial_gradient(x, y, dir, scale, channel) - green).abs())
+                    .sum();
+                (scale, error)
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map_or(1.0, |(scale, _)| scale)
+    };
+    [best_scale(0), best_scale(2)]
+}
+
+// Skips the estimate when the correction is off, so untouched images don't pay for it.
+pub fn lateral_ca_for(image: &DynamicImage, js_adjustments: &Value) -> [f32; 2] {
+    if js_adjustments["chromaticAberration"].as_f64().unwrap_or(0.0) > 0.0 {
+        estimate_lateral_ca(image)
+    } else {
+        [1.0, 1.0]
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AllAdjustments {
+    pub global: GlobalAdjustments,
+    pub mask_adjustments: Vec<MaskAdjustments>,
+    pub lens_blur: LensBlurParams,
+    pub denoise: DenoiseParams,
+    pub film_effects: FilmEffectsParams,
+    // Unset for images rendered without a crop, where the axis is the image center.
+    pub optical_center: Option<OpticalCenter>,
+}
+
+impl AllAdjustments {
+    pub fn with_optical_center(mut self, optical_center: OpticalCenter) -> Self {
+        self.optical_center = Some(optical_center);
+        self
+    }
 }
 
 struct AdjustmentScales {
@@ -284,6 +614,13 @@ struct AdjustmentScales {
     clarity: f32,
     dehaze: f32,
     structure: f32,
+    texture: f32,
+    chromatic_aberration: f32,
+    defringe_amount: f32,
+    glow_amount: f32,
+    glow_radius: f32,
+    glow_threshold: f32,
+    custom_effect_amount: f32,
 
     vignette_amount: f32,
     vignette_midpoint: f32,
@@ -321,6 +658,13 @@ const SCALES: AdjustmentScales = AdjustmentScales {
     clarity: 100.0,
     dehaze: 750.0,
     structure: 100.0,
+    texture: 100.0,
+    chromatic_aberration: 100.0,
+    defringe_amount: 100.0,
+    glow_amount: 100.0,
+    glow_radius: 2.0,
+    glow_threshold: 100.0,
+    custom_effect_amount: 100.0,
 
     vignette_amount: 100.0,
     vignette_midpoint: 100.0,
@@ -340,6 +684,23 @@ const SCALES: AdjustmentScales = AdjustmentScales {
     color_grading_balance: 200.0,
 };
 
+const WB_TEMPERATURE_RED_BLUE: f32 = 0.2;
+const WB_TEMPERATURE_GREEN: f32 = 0.05;
+const WB_TINT: f32 = 0.25;
+
+pub fn neutralize_white_balance(rgb: [f32; 3]) -> (f32, f32) {
+    let [r, g, b] = rgb.map(|c| c.max(1e-6));
+    let temp = ((b - r) / (WB_TEMPERATURE_RED_BLUE * (r + b))).clamp(-1.0 / WB_TEMPERATURE_RED_BLUE + 0.01, 1.0 / WB_TEMPERATURE_RED_BLUE - 0.01);
+    let red_blue = r * (1.0 + WB_TEMPERATURE_RED_BLUE * temp);
+    let green = g * (1.0 + WB_TEMPERATURE_GREEN * temp);
+    let tint = (red_blue - green) / (WB_TINT * (red_blue + green));
+
+    (
+        (temp * SCALES.temperature).clamp(-100.0, 100.0),
+        (tint * SCALES.tint).clamp(-100.0, 100.0),
+    )
+}
+
 fn parse_hsl_adjustments(js_hsl: &serde_json::Value) -> [HslColor; 8] {
     let mut hsl_array = [HslColor::default(); 8];
     if let Some(hsl_map) = js_hsl.as_object() {
@@ -383,6 +744,132 @@ fn convert_points_to_aligned(frontend_points: Vec<serde_json::Value>) -> [Point;
     aligned_points
 }
 
+const PARAMETRIC_CURVE_POINTS: usize = 16;
+const PARAMETRIC_REGION_STRENGTH: f32 = 0.25;
+
+// Falls off from 1 at the center to 0 at `reach` either side of it.
+fn parametric_bump(x: f32, center: f32, reach: f32) -> f32 {
+    let d = (x - center).abs() / reach.max(0.001);
+    if d >= 1.0 {
+        0.0
+    } else {
+        (d * std::f32::consts::FRAC_PI_2).cos().powi(2)
+    }
+}
+
+// The monotone cubic interpolation of apply_curve in shader.wgsl, on the 0-255 scale of the points.
+fn evaluate_point_curve(points: &[(f32, f32)], x: f32) -> f32 {
+    let count = points.len();
+    if count < 2 {
+        return x;
+    }
+    if x <= points[0].0 {
+        return points[0].1;
+    }
+    if x >= points[count - 1].0 {
+        return points[count - 1].1;
+    }
+    let i = points.windows(2).position(|w| x <= w[1].0).unwrap_or(count - 2);
+    let (p0, p1, p2, p3) = (points[i.saturating_sub(1)], points[i], points[i + 1], points[(i + 2).min(count - 1)]);
+    let slope = |a: (f32, f32), b: (f32, f32)| (b.1 - a.1) / (b.0 - a.0).max(0.001);
+    let (delta_before, delta_current, delta_after) = (slope(p0, p1), slope(p1, p2), slope(p2, p3));
+    let mut m1 = if i == 0 {
+        delta_current
+    } else if delta_before * delta_current <= 0.0 {
+        0.0
+    } else {
+        (delta_before + delta_current) / 2.0
+    };
+    let mut m2 = if i + 1 == count - 1 {
+        delta_current
+    } else if delta_current * delta_after <= 0.0 {
+        0.0
+    } else {
+        (delta_current + delta_after) / 2.0
+    };
+    if delta_current != 0.0 {
+        let (alpha, beta) = (m1 / delta_current, m2 / delta_current);
+        if alpha * alpha + beta * beta > 9.0 {
+            let tau = 3.0 / (alpha * alpha + beta * beta).sqrt();
+            m1 *= tau;
+            m2 *= tau;
+        }
+    }
+    let dx = p2.0 - p1.0;
+    if dx <= 0.0 {
+        return p1.1;
+    }
+    let t = (x - p1.0) / dx;
+    let (t2, t3) = (t * t, t * t * t);
+    let y = (2.0 * t3 - 3.0 * t2 + 1.0) * p1.1
+        + (t3 - 2.0 * t2 + t) * m1 * dx
+        + (-2.0 * t3 + 3.0 * t2) * p2.1
+        + (t3 - t2) * m2 * dx;
+    y.clamp(0.0, 255.0)
+}
+
+// The parametric curve is applied first and the luma point curve on top of it, sampled back into the
+// 16 points the shader takes.
+fn generate_parametric_curve_points(parametric: &serde_json::Value, luma_points: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    let get = |key: &str, default: f64| parametric[key].as_f64().unwrap_or(default) as f32;
+    let amounts = [
+        get("shadows", 0.0) / 100.0,
+        get("darks", 0.0) / 100.0,
+        get("lights", 0.0) / 100.0,
+        get("highlights", 0.0) / 100.0,
+    ];
+    if amounts.iter().all(|a| a.abs() < 1e-4) {
+        return luma_points.to_vec();
+    }
+    let point_curve: Vec<(f32, f32)> = luma_points
+        .iter()
+        .take(PARAMETRIC_CURVE_POINTS)
+        .filter_map(|p| Some((p["x"].as_f64()? as f32, p["y"].as_f64()? as f32)))
+        .collect();
+
+    let split1 = (get("splitShadows", 25.0) / 100.0).clamp(0.05, 0.9);
+    let split2 = (get("splitMidtones", 50.0) / 100.0).clamp(split1 + 0.025, 0.95);
+    let split3 = (get("splitHighlights", 75.0) / 100.0).clamp(split2 + 0.025, 0.975);
+    let bounds = [0.0, split1, split2, split3, 1.0];
+
+    let mut last_y = 0.0f32;
+    (0..PARAMETRIC_CURVE_POINTS)
+        .map(|i| {
+            let x = i as f32 / (PARAMETRIC_CURVE_POINTS - 1) as f32;
+            let envelope = (8.0 * x * (1.0 - x)).min(1.0);
+            let offset: f32 = (0..4)
+                .map(|region| {
+                    let center = (bounds[region] + bounds[region + 1]) * 0.5;
+                    let reach = bounds[region + 1] - bounds[region];
+                    amounts[region] * PARAMETRIC_REGION_STRENGTH * parametric_bump(x, center, reach)
+                })
+                .sum();
+            let parametric_y = (x + offset * envelope).clamp(0.0, 1.0).max(last_y);
+            last_y = parametric_y;
+            serde_json::json!({ "x": x * 255.0, "y": evaluate_point_curve(&point_curve, parametric_y * 255.0) })
+        })
+        .collect()
+}
+
+fn get_luma_curve_points(curves_obj: &serde_json::Value) -> Vec<serde_json::Value> {
+    let luma_points = curves_obj["luma"].as_array().cloned().unwrap_or_default();
+    if curves_obj["mode"].as_str() == Some("parametric") {
+        generate_parametric_curve_points(&curves_obj["parametric"], &luma_points)
+    } else {
+        luma_points
+    }
+}
+
+// Matches the working space values in shader.wgsl. Edits made before the setting existed render in
+// linear sRGB, which is also the default.
+fn working_space_from_json(js_adjustments: &serde_json::Value) -> u32 {
+    match js_adjustments["workingSpace"].as_str() {
+        Some("rec2020") => 1,
+        Some("proPhoto") => 2,
+        _ => 0,
+    }
+}
+
 fn get_global_adjustments_from_json(js_adjustments: &serde_json::Value) -> GlobalAdjustments {
     if js_adjustments.is_null() {
         return GlobalAdjustments::default();
@@ -405,7 +892,7 @@ fn get_global_adjustments_from_json(js_adjustments: &serde_json::Value) -> Globa
     };
 
     let curves_obj = js_adjustments.get("curves").cloned().unwrap_or_default();
-    let luma_points: Vec<serde_json::Value> = if is_visible("curves") { curves_obj["luma"].as_array().cloned().unwrap_or_default() } else { Vec::new() };
+    let luma_points: Vec<serde_json::Value> = if is_visible("curves") { get_luma_curve_points(&curves_obj) } else { Vec::new() };
     let red_points: Vec<serde_json::Value> = if is_visible("curves") { curves_obj["red"].as_array().cloned().unwrap_or_default() } else { Vec::new() };
     let green_points: Vec<serde_json::Value> = if is_visible("curves") { curves_obj["green"].as_array().cloned().unwrap_or_default() } else { Vec::new() };
     let blue_points: Vec<serde_json::Value> = if is_visible("curves") { curves_obj["blue"].as_array().cloned().unwrap_or_default() } else { Vec::new() };
@@ -413,15 +900,7 @@ fn get_global_adjustments_from_json(js_adjustments: &serde_json::Value) -> Globa
     let cg_obj = js_adjustments.get("colorGrading").cloned().unwrap_or_default();
 
     let neg_conv_enabled = js_adjustments["enableNegativeConversion"].as_bool().unwrap_or(false);
-    let film_base_hex = js_adjustments["filmBaseColor"].as_str().unwrap_or("#ff8800");
-    let film_base_rgb = if film_base_hex.starts_with('#') && film_base_hex.len() == 7 {
-        let r = u8::from_str_radix(&film_base_hex[1..3], 16).unwrap_or(255) as f32 / 255.0;
-        let g = u8::from_str_radix(&film_base_hex[3..5], 16).unwrap_or(136) as f32 / 255.0;
-        let b = u8::from_str_radix(&film_base_hex[5..7], 16).unwrap_or(0) as f32 / 255.0;
-        [r, g, b]
-    } else {
-        [1.0, 0.53, 0.0] // Default orange
-    };
+    let film_base_rgb = hex_to_rgb(js_adjustments["filmBaseColor"].as_str().unwrap_or("#ff8800"), [1.0, 0.53, 0.0]);
 
     GlobalAdjustments {
         exposure: get_val("basic", "exposure", SCALES.exposure, None),
@@ -443,6 +922,7 @@ fn get_global_adjustments_from_json(js_adjustments: &serde_json::Value) -> Globa
         clarity: get_val("effects", "clarity", SCALES.clarity, None),
         dehaze: get_val("effects", "dehaze", SCALES.dehaze, None),
         structure: get_val("effects", "structure", SCALES.structure, None),
+        texture: get_val("effects", "texture", SCALES.texture, None),
         vignette_amount: get_val("effects", "vignetteAmount", SCALES.vignette_amount, None),
         vignette_midpoint: get_val("effects", "vignetteMidpoint", SCALES.vignette_midpoint, Some(50.0)),
         vignette_roundness: get_val("effects", "vignetteRoundness", SCALES.vignette_roundness, Some(0.0)),
@@ -459,7 +939,25 @@ fn get_global_adjustments_from_json(js_adjustments: &serde_json::Value) -> Globa
         negative_green_balance: js_adjustments["negativeGreenBalance"].as_f64().unwrap_or(0.0) as f32 / 100.0,
         negative_blue_balance: js_adjustments["negativeBlueBalance"].as_f64().unwrap_or(0.0) as f32 / 100.0,
         _pad_neg1: 0.0,
-        _pad_neg2: 0.0,
+
+        chromatic_aberration: get_val("details", "chromaticAberration", SCALES.chromatic_aberration, None),
+        lut_intensity: js_adjustments["lut"]["intensity"].as_f64().unwrap_or(100.0) as f32 / 100.0,
+        lut_size: 0,
+        working_space: working_space_from_json(js_adjustments),
+
+        defringe_purple_amount: get_val("details", "defringePurpleAmount", SCALES.defringe_amount, None),
+        defringe_purple_hue_min: get_val("details", "defringePurpleHueMin", 1.0, Some(260.0)),
+        defringe_purple_hue_max: get_val("details", "defringePurpleHueMax", 1.0, Some(320.0)),
+        defringe_green_amount: get_val("details", "defringeGreenAmount", SCALES.defringe_amount, None),
+        defringe_green_hue_min: get_val("details", "defringeGreenHueMin", 1.0, Some(80.0)),
+        defringe_green_hue_max: get_val("details", "defringeGreenHueMax", 1.0, Some(160.0)),
+        _pad_df1: 0.0,
+        _pad_df2: 0.0,
+
+        glow_amount: get_val("effects", "glowAmount", SCALES.glow_amount, None),
+        glow_radius: get_val("effects", "glowRadius", SCALES.glow_radius, Some(50.0)),
+        glow_threshold: get_val("effects", "glowThreshold", SCALES.glow_threshold, Some(50.0)),
+        custom_effect_amount: get_val("effects", "customEffectAmount", SCALES.custom_effect_amount, None),
 
         color_grading_shadows: if is_visible("color") { parse_color_grade_settings(&cg_obj["shadows"]) } else { ColorGradeSettings::default() },
         color_grading_midtones: if is_visible("color") { parse_color_grade_settings(&cg_obj["midtones"]) } else { ColorGradeSettings::default() },
@@ -503,7 +1001,7 @@ fn get_mask_adjustments_from_json(adj: &serde_json::Value) -> MaskAdjustments {
     };
 
     let curves_obj = adj.get("curves").cloned().unwrap_or_default();
-    let luma_points: Vec<serde_json::Value> = if is_visible("curves") { curves_obj["luma"].as_array().cloned().unwrap_or_default() } else { Vec::new() };
+    let luma_points: Vec<serde_json::Value> = if is_visible("curves") { get_luma_curve_points(&curves_obj) } else { Vec::new() };
     let red_points: Vec<serde_json::Value> = if is_visible("curves") { curves_obj["red"].as_array().cloned().unwrap_or_default() } else { Vec::new() };
     let green_points: Vec<serde_json::Value> = if is_visible("curves") { curves_obj["green"].as_array().cloned().unwrap_or_default() } else { Vec::new() };
     let blue_points: Vec<serde_json::Value> = if is_visible("curves") { curves_obj["blue"].as_array().cloned().unwrap_or_default() } else { Vec::new() };
@@ -529,8 +1027,23 @@ fn get_mask_adjustments_from_json(adj: &serde_json::Value) -> MaskAdjustments {
         clarity: get_val("effects", "clarity", SCALES.clarity),
         dehaze: get_val("effects", "dehaze", SCALES.dehaze),
         structure: get_val("effects", "structure", SCALES.structure),
+        texture: get_val("effects", "texture", SCALES.texture),
         
-        _pad1: 0.0, _pad2: 0.0, _pad3: 0.0, _pad4: 0.0,
+        _pad2: 0.0, _pad3: 0.0, _pad4: 0.0,
+
+        defringe_purple_amount: get_val("details", "defringePurpleAmount", SCALES.defringe_amount),
+        defringe_purple_hue_min: get_val("details", "defringePurpleHueMin", 1.0),
+        defringe_purple_hue_max: get_val("details", "defringePurpleHueMax", 1.0),
+        defringe_green_amount: get_val("details", "defringeGreenAmount", SCALES.defringe_amount),
+        defringe_green_hue_min: get_val("details", "defringeGreenHueMin", 1.0),
+        defringe_green_hue_max: get_val("details", "defringeGreenHueMax", 1.0),
+        _pad_df1: 0.0,
+        _pad_df2: 0.0,
+
+        glow_amount: get_val("effects", "glowAmount", SCALES.glow_amount),
+        glow_radius: get_val("effects", "glowRadius", SCALES.glow_radius),
+        glow_threshold: get_val("effects", "glowThreshold", SCALES.glow_threshold),
+        _pad_glow1: 0.0,
 
         color_grading_shadows: if is_visible("color") { parse_color_grade_settings(&cg_obj["shadows"]) } else { ColorGradeSettings::default() },
         color_grading_midtones: if is_visible("color") { parse_color_grade_settings(&cg_obj["midtones"]) } else { ColorGradeSettings::default() },
@@ -552,27 +1065,110 @@ fn get_mask_adjustments_from_json(adj: &serde_json::Value) -> MaskAdjustments {
     }
 }
 
+fn hex_to_rgb(hex: &str, default: [f32; 3]) -> [f32; 3] {
+    if !hex.starts_with('#') || hex.len() != 7 {
+        return default;
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[1 + 2 * i..3 + 2 * i], 16).map_or(default[i], |v| v as f32 / 255.0)
+    };
+    [channel(0), channel(1), channel(2)]
+}
+
+fn get_film_effects_params_from_json(js_adjustments: &serde_json::Value) -> FilmEffectsParams {
+    let effects_visible = js_adjustments
+        .get("sectionVisibility")
+        .and_then(|v| v.get("effects"))
+        .and_then(|s| s.as_bool())
+        .unwrap_or(true);
+    if !effects_visible {
+        return FilmEffectsParams::default();
+    }
+
+    let get = |key: &str, default: f64| js_adjustments[key].as_f64().unwrap_or(default) as f32 / 100.0;
+    let tint = hex_to_rgb(js_adjustments["halationColor"].as_str().unwrap_or("#ff3c14"), [1.0, 0.24, 0.08]);
+    FilmEffectsParams {
+        halation_strength: get("halationAmount", 0.0),
+        halation_threshold: get("halationThreshold", 80.0),
+        bloom_strength: get("bloomAmount", 0.0),
+        halation_tint_r: tint[0],
+        halation_tint_g: tint[1],
+        halation_tint_b: tint[2],
+        grain_size: 1.0 + get("colorGrainSize", 25.0) * 3.0,
+        grain_red: get("colorGrainRed", 0.0),
+        grain_green: get("colorGrainGreen", 0.0),
+        grain_blue: get("colorGrainBlue", 0.0),
+        ..FilmEffectsParams::default()
+    }
+}
+
+fn get_lens_blur_params_from_json(js_adjustments: &serde_json::Value, visible_mask_ids: &[&str]) -> LensBlurParams {
+    let lens_blur = &js_adjustments["lensBlur"];
+    let amount = lens_blur["amount"].as_f64().unwrap_or(0.0) as f32 / 100.0;
+    if amount <= 0.0 {
+        return LensBlurParams::default();
+    }
+
+    let depth_mask_index = lens_blur["depthMaskId"]
+        .as_str()
+        .and_then(|id| visible_mask_ids.iter().position(|mask_id| *mask_id == id))
+        .map_or(-1, |i| i as i32);
+    let use_depth_map = lens_blur["source"].as_str() == Some("depthMap") && depth_mask_index >= 0;
+    let get = |key: &str, default: f64| lens_blur[key].as_f64().unwrap_or(default) as f32;
+
+    LensBlurParams {
+        amount,
+        source: if use_depth_map { 1 } else { 0 },
+        depth_mask_index,
+        blade_count: lens_blur["bladeCount"].as_u64().unwrap_or(0) as u32,
+        center_x: get("centerX", 0.5),
+        center_y: get("centerY", 0.5),
+        focus_radius: get("focusRadius", 20.0) / 100.0,
+        feather: get("feather", 30.0) / 100.0,
+        focal_depth: get("focalDepth", 100.0) / 100.0,
+        focus_range: get("focusRange", 10.0) / 100.0,
+        blade_rotation: get("bladeRotation", 0.0).to_radians(),
+        highlight_boost: get("highlightBoost", 50.0) / 100.0,
+        ..LensBlurParams::default()
+    }
+}
+
+// The global noise reduction runs as a separate multi-scale pass before the adjustments shader, so the
+// shader's local filter is only used by masks.
+fn take_denoise_params(js_adjustments: &serde_json::Value, global: &mut GlobalAdjustments) -> DenoiseParams {
+    let params = DenoiseParams {
+        luma_strength: global.luma_noise_reduction,
+        chroma_strength: global.color_noise_reduction,
+        detail: js_adjustments["noiseReductionDetail"].as_f64().unwrap_or(50.0) as f32 / 100.0,
+        ..DenoiseParams::default()
+    };
+    global.luma_noise_reduction = 0.0;
+    global.color_noise_reduction = 0.0;
+    params
+}
+
 pub fn get_all_adjustments_from_json(js_adjustments: &serde_json::Value) -> AllAdjustments {
-    let global = get_global_adjustments_from_json(js_adjustments);
-    let mut mask_adjustments = [MaskAdjustments::default(); 16];
-    let mut mask_count = 0;
+    let mut global = get_global_adjustments_from_json(js_adjustments);
+    let denoise = take_denoise_params(js_adjustments, &mut global);
 
     let mask_definitions: Vec<MaskDefinition> = js_adjustments.get("masks")
         .and_then(|m| serde_json::from_value(m.clone()).ok())
         .unwrap_or_else(Vec::new);
 
-    for (i, mask_def) in mask_definitions.iter().filter(|m| m.visible).enumerate().take(16) {
-        mask_adjustments[i] = get_mask_adjustments_from_json(&mask_def.adjustments);
-        mask_count += 1;
+    let mut mask_adjustments = Vec::new();
+    let mut visible_mask_ids = Vec::new();
+    for mask_def in mask_definitions.iter().filter(|m| m.visible) {
+        mask_adjustments.push(get_mask_adjustments_from_json(&mask_def.adjustments));
+        visible_mask_ids.push(mask_def.id.as_str());
     }
 
     AllAdjustments {
         global,
         mask_adjustments,
-        mask_count,
-        tile_offset_x: 0,
-        tile_offset_y: 0,
-        mask_atlas_cols: 1,
+        lens_blur: get_lens_blur_params_from_json(js_adjustments, &visible_mask_ids),
+        denoise,
+        film_effects: get_film_effects_params_from_json(js_adjustments),
+        optical_center: None,
     }
 }
 
@@ -581,6 +1177,8 @@ pub struct GpuContext {
     pub device: Arc<wgpu::Device>,
     pub queue: Arc<wgpu::Queue>,
     pub limits: wgpu::Limits,
+    pub pipelines: Arc<Mutex<HashMap<PipelineKind, Arc<CachedPipeline>>>>,
+    pub custom_effect: Arc<Mutex<Option<String>>>,
 }
 
 #[derive(Serialize, Clone)]
@@ -627,10 +1225,77 @@ pub fn calculate_histogram_from_image(image: &DynamicImage) -> Result<HistogramD
         luma_counts[luma_val.min(255)] += 1;
     }
 
-    let mut red: Vec<f32> = red_counts.into_iter().map(|c| c as f32).collect();
-    let mut green: Vec<f32> = green_counts.into_iter().map(|c| c as f32).collect();
-    let mut blue: Vec<f32> = blue_counts.into_iter().map(|c| c as f32).collect();
-    let mut luma: Vec<f32> = luma_counts.into_iter().map(|c| c as f32).collect();
+    Ok(histogram_from_counts(&red_counts, &green_counts, &blue_counts, &luma_counts))
+}
+
+const CLIPPING_HIGHLIGHT_LEVEL: u8 = 254;
+const CLIPPING_SHADOW_LEVEL: u8 = 1;
+
+// Pixels with a blown channel are marked red, pixels crushed to black in every channel blue. The rest
+// stays transparent so the overlay can be laid over the preview.
+pub fn generate_clipping_overlay(image: &DynamicImage) -> Result<Vec<u8>, String> {
+    let rgb = image.to_rgb8();
+    let overlay = ImageBuffer::from_fn(rgb.width(), rgb.height(), |x, y| {
+        let pixel = rgb.get_pixel(x, y);
+        if pixel.0.iter().any(|&c| c >= CLIPPING_HIGHLIGHT_LEVEL) {
+            Rgba([255u8, 0, 0, 255])
+        } else if pixel.0.iter().all(|&c| c <= CLIPPING_SHADOW_LEVEL) {
+            Rgba([0, 96, 255, 255])
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    });
+
+    let mut buf = Cursor::new(Vec::new());
+    DynamicImage::ImageRgba8(overlay).write_to(&mut buf, ImageFormat::Png).map_err(|e| e.to_string())?;
+    Ok(buf.into_inner())
+}
+
+const FOCUS_PEAKING_COLOR: [f32; 3] = [1.0, 0.1, 0.1];
+
+// Marks pixels with a strong luma gradient. Out of focus areas lack that high frequency contrast, so
+// the overlay traces what is critically sharp. Higher sensitivity (0-1) lowers the threshold.
+pub fn generate_focus_peaking_overlay(image: &DynamicImage, color: &str, sensitivity: f32) -> Result<Vec<u8>, String> {
+    let luma = image.to_luma32f();
+    let (width, height) = luma.dimensions();
+    if width < 3 || height < 3 {
+        return Err("The preview is too small for focus peaking.".to_string());
+    }
+
+    let [r, g, b] = hex_to_rgb(color, FOCUS_PEAKING_COLOR).map(|c| (c * 255.0).round() as u8);
+    let threshold = 1.2 - sensitivity.clamp(0.0, 1.0);
+    let at = |x: u32, y: u32| luma.get_pixel(x, y)[0];
+
+    let overlay = ImageBuffer::from_fn(width, height, |x, y| {
+        if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+            return Rgba([0, 0, 0, 0]);
+        }
+        let gx = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+            - at(x - 1, y - 1) - 2.0 * at(x - 1, y) - at(x - 1, y + 1);
+        let gy = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+            - at(x - 1, y - 1) - 2.0 * at(x, y - 1) - at(x + 1, y - 1);
+        let magnitude = (gx * gx + gy * gy).sqrt();
+        let alpha = ((magnitude - threshold) / threshold).clamp(0.0, 1.0);
+        Rgba([r, g, b, (alpha * 255.0) as u8])
+    });
+
+    let mut buf = Cursor::new(Vec::new());
+    DynamicImage::ImageRgba8(overlay).write_to(&mut buf, ImageFormat::Png).map_err(|e| e.to_string())?;
+    Ok(buf.into_inner())
+}
+
+pub fn histogram_from_scope_counts(counts: &ScopeCounts) -> HistogramData {
+    let mut channels = counts.histogram.chunks_exact(SCOPE_BINS);
+    let mut next = || channels.next().unwrap_or(&[]);
+    let (red, green, blue, luma) = (next(), next(), next(), next());
+    histogram_from_counts(red, green, blue, luma)
+}
+
+fn histogram_from_counts(red_counts: &[u32], green_counts: &[u32], blue_counts: &[u32], luma_counts: &[u32]) -> HistogramData {
+    let mut red: Vec<f32> = red_counts.iter().map(|&c| c as f32).collect();
+    let mut green: Vec<f32> = green_counts.iter().map(|&c| c as f32).collect();
+    let mut blue: Vec<f32> = blue_counts.iter().map(|&c| c as f32).collect();
+    let mut luma: Vec<f32> = luma_counts.iter().map(|&c| c as f32).collect();
 
     let smoothing_sigma = 2.5;
     apply_gaussian_smoothing(&mut red, smoothing_sigma);
@@ -643,7 +1308,7 @@ pub fn calculate_histogram_from_image(image: &DynamicImage) -> Result<HistogramD
     normalize_histogram_range(&mut blue, 0.99);
     normalize_histogram_range(&mut luma, 0.99);
 
-    Ok(HistogramData { red, green, blue, luma })
+    HistogramData { red, green, blue, luma }
 }
 
 fn apply_gaussian_smoothing(histogram: &mut Vec<f32>, sigma: f32) {
@@ -714,6 +1379,57 @@ pub struct WaveformData {
     luma: Vec<u32>,
     width: u32,
     height: u32,
+    parade: ParadeData,
+    vectorscope: VectorscopeData,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ParadeData {
+    red: Vec<u32>,
+    green: Vec<u32>,
+    blue: Vec<u32>,
+    channel_width: u32,
+    height: u32,
+}
+
+#[derive(Serialize, Clone)]
+pub struct VectorscopeData {
+    counts: Vec<u32>,
+    size: u32,
+}
+
+const PARADE_CHANNEL_WIDTH: u32 = 85;
+const VECTORSCOPE_SIZE: u32 = 256;
+
+// Squeezes the channel waveforms to a third of their width so the three fit next to each other.
+fn parade_from_waveform(red: &[u32], green: &[u32], blue: &[u32], width: u32, height: u32) -> ParadeData {
+    let squeeze = |plane: &[u32]| {
+        let mut channel = vec![0u32; (PARADE_CHANNEL_WIDTH * height) as usize];
+        for (i, &count) in plane.iter().enumerate() {
+            let (row, col) = (i as u32 / width, i as u32 % width);
+            channel[(row * PARADE_CHANNEL_WIDTH + col * PARADE_CHANNEL_WIDTH / width) as usize] += count;
+        }
+        channel
+    };
+    ParadeData {
+        red: squeeze(red),
+        green: squeeze(green),
+        blue: squeeze(blue),
+        channel_width: PARADE_CHANNEL_WIDTH,
+        height,
+    }
+}
+
+// Must match vectorscope_index in scopes.wgsl.
+fn vectorscope_index(r: f32, g: f32, b: f32) -> usize {
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let cb = (b - y) / 1.8556;
+    let cr = (r - y) / 1.5748;
+    let scale = (VECTORSCOPE_SIZE - 1) as f32;
+    let u = ((cb + 0.5).clamp(0.0, 1.0) * scale).round() as u32;
+    let v = ((cr + 0.5).clamp(0.0, 1.0) * scale).round() as u32;
+    ((VECTORSCOPE_SIZE - 1 - v) * VECTORSCOPE_SIZE + u) as usize
 }
 
 #[tauri::command]
@@ -753,6 +1469,7 @@ pub fn calculate_waveform_from_image(image: &DynamicImage) -> Result<WaveformDat
     let mut green = vec![0; (WAVEFORM_WIDTH * WAVEFORM_HEIGHT) as usize];
     let mut blue = vec![0; (WAVEFORM_WIDTH * WAVEFORM_HEIGHT) as usize];
     let mut luma = vec![0; (WAVEFORM_WIDTH * WAVEFORM_HEIGHT) as usize];
+    let mut vectorscope = vec![0; (VECTORSCOPE_SIZE * VECTORSCOPE_SIZE) as usize];
 
     for (x, _, pixel) in rgb_image.enumerate_pixels() {
         let r = pixel[0] as usize;
@@ -770,9 +1487,13 @@ pub fn calculate_waveform_from_image(image: &DynamicImage) -> Result<WaveformDat
         let luma_val = (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32).round() as usize;
         let luma_idx = (255 - luma_val.min(255)) * WAVEFORM_WIDTH as usize + x as usize;
         luma[luma_idx] += 1;
+
+        vectorscope[vectorscope_index(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)] += 1;
     }
 
     Ok(WaveformData {
+        parade: parade_from_waveform(&red, &green, &blue, WAVEFORM_WIDTH, WAVEFORM_HEIGHT),
+        vectorscope: VectorscopeData { counts: vectorscope, size: VECTORSCOPE_SIZE },
         red,
         green,
         blue,
@@ -782,6 +1503,26 @@ pub fn calculate_waveform_from_image(image: &DynamicImage) -> Result<WaveformDat
     })
 }
 
+pub fn waveform_from_scope_counts(counts: &ScopeCounts) -> WaveformData {
+    let plane = SCOPE_BINS * SCOPE_BINS;
+    let mut channels = counts.waveform.chunks_exact(plane).map(|c| c.to_vec());
+    let mut next = || channels.next().unwrap_or_else(|| vec![0; plane]);
+    let (red, green, blue, luma) = (next(), next(), next(), next());
+    let size = SCOPE_BINS as u32;
+    let mut vectorscope = counts.vectorscope.clone();
+    vectorscope.resize((VECTORSCOPE_SIZE * VECTORSCOPE_SIZE) as usize, 0);
+    WaveformData {
+        parade: parade_from_waveform(&red, &green, &blue, size, size),
+        vectorscope: VectorscopeData { counts: vectorscope, size: VECTORSCOPE_SIZE },
+        red,
+        green,
+        blue,
+        luma,
+        width: size,
+        height: size,
+    }
+}
+
 pub fn perform_auto_analysis(image: &DynamicImage) -> AutoAdjustmentResults {
     let analysis_preview = image.thumbnail(1024, 1024);
     let rgb_image = analysis_preview.to_rgb8();
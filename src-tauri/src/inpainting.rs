@@ -1,5 +1,5 @@
 use image::{
-    RgbImage, Rgb, Rgba, RgbaImage, GrayImage, DynamicImage,
+    RgbImage, Rgb, Rgba, RgbaImage, Rgba32FImage, GrayImage, DynamicImage,
     GenericImageView,
 };
 use std::collections::{BinaryHeap, HashMap};
@@ -28,9 +28,12 @@ impl PartialOrd for HeapItem { fn partial_cmp(&self, other: &Self) -> Option<Ord
 impl PartialEq for HeapItem { fn eq(&self, other: &Self) -> bool { self.priority == other.priority } }
 impl Eq for HeapItem {}
 
-fn inpaint_criminisi(source_image: &RgbImage, mask: &GrayImage, patch_radius: u32) -> RgbImage {
+// Patches are matched on the 8-bit guide, whose pixels are the values times `scale`, and the values
+// are copied along, so float values keep their precision and their range above 1.0.
+fn inpaint_criminisi(source_image: &RgbImage, values: &[[f32; 3]], scale: f32, mask: &GrayImage, patch_radius: u32) -> Vec<[f32; 3]> {
     let (width, height) = source_image.dimensions();
     let mut output = source_image.clone();
+    let mut current = values.to_vec();
     let mut pixel_states = vec![PIXEL_KNOWN; (width * height) as usize];
     let mut confidence = vec![0.0f32; (width * height) as usize];
     let mut narrow_band = BinaryHeap::new();
@@ -47,8 +50,7 @@ fn inpaint_criminisi(source_image: &RgbImage, mask: &GrayImage, patch_radius: u3
                 pixel_states[idx] = PIXEL_HOLE;
             } else {
                 confidence[idx] = 1.0;
-                let p = source_image.get_pixel(x, y);
-                float_output[idx] = [p[0] as f32, p[1] as f32, p[2] as f32];
+                float_output[idx] = values[idx];
                 total_weights[idx] = 1.0;
             }
         }
@@ -110,20 +112,17 @@ fn inpaint_criminisi(source_image: &RgbImage, mask: &GrayImage, patch_radius: u3
                             let source_y = (best_match_y as i32 + dy).clamp(0, (height - 1) as i32) as u32;
                             
                             let weight = gaussian_kernel[((dy + r) as usize * patch_diameter) + (dx + r) as usize];
-                            let source_pixel = output.get_pixel(source_x, source_y);
+                            let source_value = current[(source_y * width + source_x) as usize];
 
                             for i in 0..3 {
-                                float_output[idx][i] += source_pixel[i] as f32 * weight;
+                                float_output[idx][i] += source_value[i] * weight;
                             }
                             total_weights[idx] += weight;
 
                             if total_weights[idx] > 0.0 {
-                                let final_color = Rgb([
-                                    (float_output[idx][0] / total_weights[idx]).clamp(0.0, 255.0) as u8,
-                                    (float_output[idx][1] / total_weights[idx]).clamp(0.0, 255.0) as u8,
-                                    (float_output[idx][2] / total_weights[idx]).clamp(0.0, 255.0) as u8,
-                                ]);
-                                output.put_pixel(target_x, target_y, final_color);
+                                let value = float_output[idx].map(|v| v / total_weights[idx]);
+                                current[idx] = value;
+                                output.put_pixel(target_x, target_y, Rgb(value.map(|v| (v * scale).clamp(0.0, 255.0) as u8)));
                             }
                             
                             if pixel_states[idx] != PIXEL_KNOWN {
@@ -148,7 +147,7 @@ fn inpaint_criminisi(source_image: &RgbImage, mask: &GrayImage, patch_radius: u3
             }
         }
     }
-    output
+    current
 }
 
 fn get_gaussian_kernel(radius: u32, sigma: f32) -> Vec<f32> {
@@ -388,15 +387,32 @@ fn find_best_match_local(image: &RgbImage, pixel_states: &[u8], width: u32, heig
 pub fn perform_fast_inpaint(source_image: &DynamicImage, mask: &GrayImage, patch_radius: u32) -> Result<RgbaImage, String> {
     if patch_radius == 0 { return Err("Patch radius must be greater than 0.".to_string()); }
     let source_rgb = source_image.to_rgb8();
-    let inpainted_rgb = inpaint_criminisi(&source_rgb, mask, patch_radius);
-    let (width, height) = inpainted_rgb.dimensions();
+    let values: Vec<[f32; 3]> = source_rgb.pixels().map(|p| p.0.map(f32::from)).collect();
+    let inpainted = inpaint_criminisi(&source_rgb, &values, 1.0, mask, patch_radius);
+    let (width, height) = source_rgb.dimensions();
     let mut final_image = RgbaImage::new(width, height);
     for y in 0..height {
         for x in 0..width {
             let original_pixel = source_image.get_pixel(x, y);
-            let inpainted_pixel = inpainted_rgb.get_pixel(x, y);
+            let inpainted_pixel = inpainted[(y * width + x) as usize].map(|v| v.clamp(0.0, 255.0) as u8);
             final_image.put_pixel(x, y, Rgba([inpainted_pixel[0], inpainted_pixel[1], inpainted_pixel[2], original_pixel[3]]));
         }
     }
     Ok(final_image)
+}
+
+// Inpaints a float image without going through 8 bits, so healed areas keep their precision and any
+// values above 1.0. Only the patch search looks at an 8-bit copy.
+pub fn perform_fast_inpaint_f32(source_image: &Rgba32FImage, mask: &GrayImage, patch_radius: u32) -> Result<Rgba32FImage, String> {
+    if patch_radius == 0 { return Err("Patch radius must be greater than 0.".to_string()); }
+    let (width, height) = source_image.dimensions();
+    let values: Vec<[f32; 3]> = source_image.pixels().map(|p| [p[0], p[1], p[2]]).collect();
+    let guide = RgbImage::from_fn(width, height, |x, y| {
+        Rgb(values[(y * width + x) as usize].map(|v| (v * 255.0).round().clamp(0.0, 255.0) as u8))
+    });
+    let inpainted = inpaint_criminisi(&guide, &values, 255.0, mask, patch_radius);
+    Ok(Rgba32FImage::from_fn(width, height, |x, y| {
+        let [r, g, b] = inpainted[(y * width + x) as usize];
+        Rgba([r, g, b, source_image.get_pixel(x, y)[3]])
+    }))
 }
\ No newline at end of file
@@ -0,0 +1,213 @@
+use std::fs;
+
+use rayon::prelude::*;
+
+use crate::file_management::get_sidecar_path;
+use crate::image_processing::{ImageMetadata, IptcMetadata};
+use crate::tagging::{COLOR_TAG_PREFIX, PERSON_TAG_PREFIX};
+use crate::xmp_sidecar::{escape, xmp_label, xmp_rating};
+
+const JPEG_XMP_NAMESPACE: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+const PHOTOSHOP_SIGNATURE: &[u8] = b"Photoshop 3.0\0";
+const PHOTOSHOP_IPTC_RESOURCE: u16 = 0x0404;
+// ESC % G, declaring the record 2 strings as UTF-8.
+const IIM_UTF8_MARKER: &[u8] = &[0x1B, 0x25, 0x47];
+
+pub fn read_metadata(path: &str) -> ImageMetadata {
+    fs::read_to_string(get_sidecar_path(path))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// Tags as keywords for exported files. Color labels stay internal, people are written by name.
+pub fn export_keywords(metadata: &ImageMetadata) -> Vec<String> {
+    metadata
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .filter(|tag| !tag.starts_with(COLOR_TAG_PREFIX))
+        .map(|tag| tag.strip_prefix(PERSON_TAG_PREFIX).unwrap_or(tag).to_string())
+        .collect()
+}
+
+#[tauri::command]
+pub fn load_iptc_metadata(path: String) -> Result<IptcMetadata, String> {
+    Ok(read_metadata(&path).iptc.unwrap_or_default())
+}
+
+// Fields left out of `fields` are kept, so a batch edit can set e.g. only the copyright notice.
+// An empty string clears the field.
+#[tauri::command]
+pub fn set_iptc_for_paths(paths: Vec<String>, fields: IptcMetadata) -> Result<(), String> {
+    paths.par_iter().try_for_each(|path| {
+        let mut metadata = read_metadata(path);
+        let mut iptc = metadata.iptc.take().unwrap_or_default();
+
+        let updates = [
+            (&mut iptc.title, &fields.title),
+            (&mut iptc.caption, &fields.caption),
+            (&mut iptc.creator, &fields.creator),
+            (&mut iptc.copyright, &fields.copyright),
+            (&mut iptc.usage_terms, &fields.usage_terms),
+        ];
+        for (current, update) in updates {
+            if let Some(value) = update {
+                let value = value.trim();
+                *current = if value.is_empty() { None } else { Some(value.to_string()) };
+            }
+        }
+        metadata.iptc = if iptc.is_empty() { None } else { Some(iptc) };
+
+        let json_string = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
+        fs::write(get_sidecar_path(path), json_string).map_err(|e| e.to_string())
+    })
+}
+
+fn alt_property(name: &str, value: &str) -> String {
+    format!(
+        "   <{name}>\n    <rdf:Alt>\n     <rdf:li xml:lang=\"x-default\">{}</rdf:li>\n    </rdf:Alt>\n   </{name}>\n",
+        escape(value)
+    )
+}
+
+fn append_iim_dataset(buf: &mut Vec<u8>, record: u8, dataset: u8, value: &[u8]) {
+    let len = value.len().min(u16::MAX as usize);
+    buf.extend_from_slice(&[0x1C, record, dataset]);
+    buf.extend_from_slice(&(len as u16).to_be_bytes());
+    buf.extend_from_slice(&value[..len]);
+}
+
+// IPTC-IIM application record with the descriptive fields and keywords, or None when there is
+// nothing to write. Usage terms have no IIM dataset and only go into XMP.
+pub fn iim_record(iptc: &IptcMetadata, keywords: &[String]) -> Option<Vec<u8>> {
+    let mut datasets = Vec::new();
+    let fields = [(5, &iptc.title), (120, &iptc.caption), (80, &iptc.creator), (116, &iptc.copyright)];
+    for (dataset, value) in fields {
+        if let Some(value) = value {
+            append_iim_dataset(&mut datasets, 2, dataset, value.as_bytes());
+        }
+    }
+    for keyword in keywords {
+        append_iim_dataset(&mut datasets, 2, 25, keyword.as_bytes());
+    }
+    if datasets.is_empty() {
+        return None;
+    }
+
+    let mut record = Vec::new();
+    append_iim_dataset(&mut record, 1, 90, IIM_UTF8_MARKER);
+    append_iim_dataset(&mut record, 2, 0, &[0, 4]);
+    record.extend_from_slice(&datasets);
+    Some(record)
+}
+
+// XMP counterpart of the IPTC fields and keywords, which also carries the fields (title, usage
+// terms) that not every reader picks up from IIM, plus the rating and color label.
+pub fn xmp_packet(metadata: &ImageMetadata) -> Option<String> {
+    let iptc = metadata.iptc.clone().unwrap_or_default();
+    let keywords = export_keywords(metadata);
+    let rating = xmp_rating(metadata);
+    let label = xmp_label(metadata);
+    if iptc.is_empty() && keywords.is_empty() && rating == 0 && label.is_none() {
+        return None;
+    }
+
+    let mut properties = String::new();
+    if rating != 0 {
+        properties.push_str(&format!("   <xmp:Rating>{}</xmp:Rating>\n", rating));
+    }
+    if let Some(label) = &label {
+        properties.push_str(&format!("   <xmp:Label>{}</xmp:Label>\n", escape(label)));
+    }
+    if let Some(title) = &iptc.title {
+        properties.push_str(&alt_property("dc:title", title));
+    }
+    if let Some(caption) = &iptc.caption {
+        properties.push_str(&alt_property("dc:description", caption));
+    }
+    if let Some(creator) = &iptc.creator {
+        properties.push_str(&format!(
+            "   <dc:creator>\n    <rdf:Seq>\n     <rdf:li>{}</rdf:li>\n    </rdf:Seq>\n   </dc:creator>\n",
+            escape(creator)
+        ));
+    }
+    if let Some(copyright) = &iptc.copyright {
+        properties.push_str(&alt_property("dc:rights", copyright));
+    }
+    if let Some(usage_terms) = &iptc.usage_terms {
+        properties.push_str(&alt_property("xmpRights:UsageTerms", usage_terms));
+    }
+    if !keywords.is_empty() {
+        properties.push_str("   <dc:subject>\n    <rdf:Bag>\n");
+        for keyword in keywords {
+            properties.push_str(&format!("     <rdf:li>{}</rdf:li>\n", escape(keyword)));
+        }
+        properties.push_str("    </rdf:Bag>\n   </dc:subject>\n");
+    }
+
+    Some(format!(
+        concat!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n",
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n",
+            " <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n",
+            "  <rdf:Description rdf:about=\"\"\n",
+            "    xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n",
+            "    xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n",
+            "    xmlns:xmpRights=\"http://ns.adobe.com/xap/1.0/rights/\">\n",
+            "{}",
+            "  </rdf:Description>\n",
+            " </rdf:RDF>\n",
+            "</x:xmpmeta>\n",
+            "<?xpacket end=\"w\"?>",
+        ),
+        properties
+    ))
+}
+
+// New application segments go after the leading JFIF/EXIF/XMP segments, where readers expect them.
+fn insert_jpeg_segment(bytes: &mut Vec<u8>, marker: u8, payload: &[u8]) -> Result<(), String> {
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return Err("Not a JPEG stream.".to_string());
+    }
+    let segment_len = 2 + payload.len();
+    if segment_len > u16::MAX as usize {
+        return Err("Metadata is too large for a JPEG segment.".to_string());
+    }
+
+    let mut insert_at = 2;
+    while let Some(&[0xFF, existing, len_hi, len_lo]) = bytes.get(insert_at..insert_at + 4) {
+        if !(0xE0..=0xE1).contains(&existing) {
+            break;
+        }
+        insert_at += 2 + u16::from_be_bytes([len_hi, len_lo]) as usize;
+    }
+
+    let mut segment = vec![0xFF, marker];
+    segment.extend_from_slice(&(segment_len as u16).to_be_bytes());
+    segment.extend_from_slice(payload);
+    let insert_at = insert_at.min(bytes.len());
+    bytes.splice(insert_at..insert_at, segment);
+    Ok(())
+}
+
+pub fn embed_jpeg_xmp(bytes: &mut Vec<u8>, xmp: &str) -> Result<(), String> {
+    let mut payload = JPEG_XMP_NAMESPACE.to_vec();
+    payload.extend_from_slice(xmp.as_bytes());
+    insert_jpeg_segment(bytes, 0xE1, &payload)
+}
+
+// JPEG carries IIM inside a Photoshop image resource block in APP13.
+pub fn embed_jpeg_iptc(bytes: &mut Vec<u8>, record: &[u8]) -> Result<(), String> {
+    let mut payload = PHOTOSHOP_SIGNATURE.to_vec();
+    payload.extend_from_slice(b"8BIM");
+    payload.extend_from_slice(&PHOTOSHOP_IPTC_RESOURCE.to_be_bytes());
+    payload.extend_from_slice(&[0, 0]);
+    payload.extend_from_slice(&(record.len() as u32).to_be_bytes());
+    payload.extend_from_slice(record);
+    if record.len() % 2 != 0 {
+        payload.push(0);
+    }
+    insert_jpeg_segment(bytes, 0xED, &payload)
+}
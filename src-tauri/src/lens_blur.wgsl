@@ -0,0 +1,117 @@
+struct LensBlurParams {
+    amount: f32,
+    source: u32,
+    depth_mask_index: i32,
+    blade_count: u32,
+    center_x: f32,
+    center_y: f32,
+    focus_radius: f32,
+    feather: f32,
+    focal_depth: f32,
+    focus_range: f32,
+    blade_rotation: f32,
+    highlight_boost: f32,
+    tile_offset_x: u32,
+    tile_offset_y: u32,
+    input_offset_x: u32,
+    input_offset_y: u32,
+    full_width: u32,
+    full_height: u32,
+    _pad1: u32,
+    _pad2: u32,
+}
+
+@group(0) @binding(0) var input_texture: texture_2d<f32>;
+@group(0) @binding(1) var output_texture: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(2) var<uniform> params: LensBlurParams;
+@group(0) @binding(3) var depth_texture: texture_2d<f32>;
+
+const PI: f32 = 3.14159265;
+const GOLDEN_ANGLE: f32 = 2.39996323;
+const SAMPLE_COUNT: i32 = 96;
+const MAX_RADIUS_FRACTION: f32 = 0.03;
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let cutoff = vec3<f32>(0.04045);
+    let a = vec3<f32>(0.055);
+    let higher = pow((c + a) / (1.0 + a), vec3<f32>(2.4));
+    let lower = c / 12.92;
+    return select(higher, lower, c <= cutoff);
+}
+
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let c_clamped = clamp(c, vec3<f32>(0.0), vec3<f32>(1.0));
+    let cutoff = vec3<f32>(0.0031308);
+    let a = vec3<f32>(0.055);
+    let higher = (1.0 + a) * pow(c_clamped, vec3<f32>(1.0 / 2.4)) - a;
+    let lower = c_clamped * 12.92;
+    return select(higher, lower, c_clamped <= cutoff);
+}
+
+fn get_luma(c: vec3<f32>) -> f32 {
+    return dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+fn get_blur_amount(coord: vec2<u32>, local_coord: vec2<u32>, dims: vec2<f32>) -> f32 {
+    if (params.source == 1u) {
+        let depth = textureLoad(depth_texture, local_coord, 0).r;
+        let distance_from_focus = abs(depth - params.focal_depth);
+        return smoothstep(params.focus_range, params.focus_range + params.feather, distance_from_focus);
+    }
+    let short_side = min(dims.x, dims.y);
+    let center = vec2<f32>(params.center_x, params.center_y) * dims;
+    let d = length(vec2<f32>(coord) - center) / short_side;
+    return smoothstep(params.focus_radius, params.focus_radius + params.feather, d);
+}
+
+fn aperture_extent(angle: f32) -> f32 {
+    if (params.blade_count < 3u) {
+        return 1.0;
+    }
+    let segment = 2.0 * PI / f32(params.blade_count);
+    let local_angle = (angle - params.blade_rotation) - segment * floor((angle - params.blade_rotation) / segment);
+    return cos(PI / f32(params.blade_count)) / cos(local_angle - segment * 0.5);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let out_dims = vec2<u32>(textureDimensions(output_texture));
+    if (id.x >= out_dims.x || id.y >= out_dims.y) { return; }
+
+    let absolute_coord = id.xy + vec2<u32>(params.tile_offset_x, params.tile_offset_y);
+    let local_coord = absolute_coord - vec2<u32>(params.input_offset_x, params.input_offset_y);
+    let dims = vec2<f32>(f32(params.full_width), f32(params.full_height));
+    let max_coords = vec2<i32>(textureDimensions(input_texture) - 1u);
+    let center_color = textureLoad(input_texture, local_coord, 0);
+
+    let radius = get_blur_amount(absolute_coord, local_coord, dims) * params.amount * MAX_RADIUS_FRACTION * max(dims.x, dims.y);
+    if (radius < 0.5) {
+        textureStore(output_texture, id.xy, center_color);
+        return;
+    }
+
+    var accumulated = vec3<f32>(0.0);
+    var total_weight = 0.0;
+    for (var i = 0; i < SAMPLE_COUNT; i += 1) {
+        let t = (f32(i) + 0.5) / f32(SAMPLE_COUNT);
+        let angle = f32(i) * GOLDEN_ANGLE;
+        let r = sqrt(t);
+        if (r > aperture_extent(angle)) {
+            continue;
+        }
+        let offset = vec2<f32>(cos(angle), sin(angle)) * r * radius;
+        let sample_coords = clamp(vec2<i32>(local_coord) + vec2<i32>(round(offset)), vec2<i32>(0), max_coords);
+        let sample_linear = srgb_to_linear(textureLoad(input_texture, sample_coords, 0).rgb);
+        let luma = get_luma(sample_linear);
+        let weight = 1.0 + params.highlight_boost * 8.0 * luma * luma * luma * luma;
+        accumulated += sample_linear * weight;
+        total_weight += weight;
+    }
+
+    if (total_weight <= 0.0) {
+        textureStore(output_texture, id.xy, center_color);
+        return;
+    }
+    let blurred = linear_to_srgb(accumulated / total_weight);
+    textureStore(output_texture, id.xy, vec4<f32>(blurred, center_color.a));
+}
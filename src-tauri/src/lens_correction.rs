@@ -0,0 +1,389 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use anyhow::Result;
+use exif::{In, Reader as ExifReader, Tag, Value as ExifValue};
+use image::{DynamicImage, Rgb32FImage};
+use once_cell::sync::Lazy;
+use rawler::{decoders::RawDecodeParams, rawsource::RawSource};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::formats::is_raw_file;
+
+const LENS_PROFILES_DIR: &str = "lens_profiles";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DistortionCalibration {
+    pub focal: f32,
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TcaCalibration {
+    pub focal: f32,
+    pub vr: f32,
+    pub vb: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VignettingCalibration {
+    pub focal: f32,
+    pub aperture: f32,
+    pub k1: f32,
+    pub k2: f32,
+    pub k3: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LensProfile {
+    pub maker: String,
+    pub model: String,
+    #[serde(default)]
+    pub distortion: Vec<DistortionCalibration>,
+    #[serde(default)]
+    pub tca: Vec<TcaCalibration>,
+    #[serde(default)]
+    pub vignetting: Vec<VignettingCalibration>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LensInfo {
+    pub lens_model: Option<String>,
+    pub focal_length: Option<f32>,
+    pub aperture: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LensCorrectionParams {
+    distortion: Option<[f32; 3]>,
+    tca: Option<[f32; 2]>,
+    vignetting: Option<[f32; 3]>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LensCorrectionInfo {
+    pub lens: LensInfo,
+    pub detected_profile: Option<String>,
+    pub available_profiles: Vec<String>,
+}
+
+static LENS_DATABASE: Lazy<RwLock<Vec<LensProfile>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+fn get_lens_profiles_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(LENS_PROFILES_DIR);
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    Ok(dir)
+}
+
+pub fn init_lens_database(app_handle: &AppHandle) {
+    let Ok(dir) = get_lens_profiles_dir(app_handle) else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+
+    let mut profiles = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<Vec<LensProfile>>(&content).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(mut p) => profiles.append(&mut p),
+            Err(e) => eprintln!("Failed to load lens profiles from {}: {}", path.display(), e),
+        }
+    }
+
+    println!("Loaded {} lens correction profiles.", profiles.len());
+    if let Ok(mut db) = LENS_DATABASE.write() {
+        *db = profiles;
+    }
+}
+
+fn normalize_lens_name(name: &str) -> String {
+    name.to_lowercase().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Exact model or maker + model matches win; otherwise the profile's model has to equal the
+// trailing whole words of the EXIF name, so "24-70mm" never matches "124-70mm".
+fn find_lens_profile(lens_model: &str) -> Option<LensProfile> {
+    let target = normalize_lens_name(lens_model);
+    if target.is_empty() {
+        return None;
+    }
+    let target_tokens: Vec<&str> = target.split(' ').collect();
+    let db = LENS_DATABASE.read().ok()?;
+    db.iter()
+        .find(|p| {
+            let model = normalize_lens_name(&p.model);
+            !model.is_empty()
+                && (model == target || normalize_lens_name(&format!("{} {}", p.maker, p.model)) == target)
+        })
+        .or_else(|| {
+            db.iter().find(|p| {
+                let model = normalize_lens_name(&p.model);
+                let model_tokens: Vec<&str> = model.split_whitespace().collect();
+                !model_tokens.is_empty() && target_tokens.ends_with(&model_tokens)
+            })
+        })
+        .cloned()
+}
+
+fn ascii_field(exif: &exif::Exif, tag: Tag) -> Option<String> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        ExifValue::Ascii(values) => values
+            .first()
+            .map(|v| String::from_utf8_lossy(v).trim().to_string())
+            .filter(|s| !s.is_empty()),
+        _ => None,
+    }
+}
+
+fn rational_field(exif: &exif::Exif, tag: Tag) -> Option<f32> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        ExifValue::Rational(values) => values.first().map(|r| r.to_f64() as f32),
+        _ => None,
+    }
+}
+
+pub fn read_lens_info(file_bytes: &[u8], path: &str) -> LensInfo {
+    if is_raw_file(path) {
+        let source = RawSource::new_from_slice(file_bytes);
+        if let Ok(metadata) = rawler::get_decoder(&source).and_then(|d| d.raw_metadata(&source, &RawDecodeParams::default())) {
+            return LensInfo {
+                lens_model: metadata
+                    .lens
+                    .as_ref()
+                    .map(|l| l.lens_name.clone())
+                    .or(metadata.exif.lens_model.clone()),
+                focal_length: metadata.exif.focal_length.map(|r| r.as_f32()),
+                aperture: metadata.exif.fnumber.map(|r| r.as_f32()),
+            };
+        }
+    }
+
+    match ExifReader::new().read_from_container(&mut Cursor::new(file_bytes)) {
+        Ok(exif) => LensInfo {
+            lens_model: ascii_field(&exif, Tag::LensModel),
+            focal_length: rational_field(&exif, Tag::FocalLength),
+            aperture: rational_field(&exif, Tag::FNumber),
+        },
+        Err(_) => LensInfo::default(),
+    }
+}
+
+pub fn read_lens_info_from_path(path: &str) -> LensInfo {
+    fs::read(path)
+        .map(|bytes| read_lens_info(&bytes, path))
+        .unwrap_or_default()
+}
+
+fn interpolate_by_focal<T, const N: usize>(entries: &[T], focal: f32, get: impl Fn(&T) -> (f32, [f32; N])) -> Option<[f32; N]> {
+    let mut sorted: Vec<(f32, [f32; N])> = entries.iter().map(get).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    if focal <= sorted[0].0 {
+        return Some(sorted[0].1);
+    }
+    if focal >= sorted[sorted.len() - 1].0 {
+        return Some(sorted[sorted.len() - 1].1);
+    }
+
+    let upper = sorted.iter().position(|(f, _)| *f >= focal)?;
+    let (f0, v0) = sorted[upper - 1];
+    let (f1, v1) = sorted[upper];
+    let t = if f1 > f0 { (focal - f0) / (f1 - f0) } else { 0.0 };
+    let mut result = [0.0; N];
+    for i in 0..N {
+        result[i] = v0[i] + (v1[i] - v0[i]) * t;
+    }
+    Some(result)
+}
+
+fn resolve_correction_params(profile: &LensProfile, lens: &LensInfo, settings: &Value) -> LensCorrectionParams {
+    let focal = lens.focal_length.unwrap_or(0.0);
+    let use_distortion = settings["distortion"].as_bool().unwrap_or(true);
+    let use_tca = settings["chromaticAberration"].as_bool().unwrap_or(true);
+    let use_vignetting = settings["vignetting"].as_bool().unwrap_or(true);
+
+    let distortion = if use_distortion {
+        interpolate_by_focal(&profile.distortion, focal, |d| (d.focal, [d.a, d.b, d.c]))
+    } else {
+        None
+    };
+
+    let tca = if use_tca {
+        interpolate_by_focal(&profile.tca, focal, |t| (t.focal, [t.vr, t.vb]))
+    } else {
+        None
+    };
+
+    let vignetting = if use_vignetting && !profile.vignetting.is_empty() {
+        let aperture = lens.aperture.unwrap_or(0.0);
+        let nearest_aperture = profile
+            .vignetting
+            .iter()
+            .map(|v| v.aperture)
+            .min_by(|a, b| (a - aperture).abs().partial_cmp(&(b - aperture).abs()).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap_or(aperture);
+        let at_aperture: Vec<&VignettingCalibration> = profile
+            .vignetting
+            .iter()
+            .filter(|v| (v.aperture - nearest_aperture).abs() < f32::EPSILON)
+            .collect();
+        interpolate_by_focal(&at_aperture, focal, |v| (v.focal, [v.k1, v.k2, v.k3]))
+    } else {
+        None
+    };
+
+    LensCorrectionParams { distortion, tca, vignetting }
+}
+
+fn sample_channel(image: &Rgb32FImage, x: f32, y: f32, channel: usize) -> f32 {
+    let (w, h) = image.dimensions();
+    let x = x.clamp(0.0, (w - 1) as f32);
+    let y = y.clamp(0.0, (h - 1) as f32);
+    let x0 = x.floor() as u32;
+    let y0 = y.floor() as u32;
+    let x1 = (x0 + 1).min(w - 1);
+    let y1 = (y0 + 1).min(h - 1);
+    let fx = x - x0 as f32;
+    let fy = y - y0 as f32;
+
+    let p00 = image.get_pixel(x0, y0)[channel];
+    let p10 = image.get_pixel(x1, y0)[channel];
+    let p01 = image.get_pixel(x0, y1)[channel];
+    let p11 = image.get_pixel(x1, y1)[channel];
+
+    let top = p00 + (p10 - p00) * fx;
+    let bottom = p01 + (p11 - p01) * fx;
+    top + (bottom - top) * fy
+}
+
+fn apply_correction_params(image: &DynamicImage, params: &LensCorrectionParams) -> DynamicImage {
+    let source = image.to_rgb32f();
+    let (width, height) = source.dimensions();
+    if width < 2 || height < 2 {
+        return image.clone();
+    }
+
+    let cx = (width as f32 - 1.0) / 2.0;
+    let cy = (height as f32 - 1.0) / 2.0;
+    let norm = (width.min(height) as f32) / 2.0;
+    let half_diagonal = ((width as f32).hypot(height as f32) / 2.0) / norm;
+
+    let mut output = Rgb32FImage::new(width, height);
+    output
+        .par_chunks_mut((width * 3) as usize)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for x in 0..width as usize {
+                let dx = (x as f32 - cx) / norm;
+                let dy = (y as f32 - cy) / norm;
+                let r = (dx * dx + dy * dy).sqrt();
+
+                let distortion_scale = match params.distortion {
+                    Some([a, b, c]) => a * r * r * r + b * r * r + c * r + (1.0 - a - b - c),
+                    None => 1.0,
+                };
+                let (red_scale, blue_scale) = match params.tca {
+                    Some([vr, vb]) => (vr, vb),
+                    None => (1.0, 1.0),
+                };
+
+                let channel_scales = [distortion_scale * red_scale, distortion_scale, distortion_scale * blue_scale];
+                let mut pixel = [0.0f32; 3];
+                for (c, scale) in channel_scales.iter().enumerate() {
+                    let sx = cx + dx * scale * norm;
+                    let sy = cy + dy * scale * norm;
+                    pixel[c] = sample_channel(&source, sx, sy, c);
+                }
+
+                if let Some([k1, k2, k3]) = params.vignetting {
+                    let rv = r / half_diagonal;
+                    let r2 = rv * rv;
+                    let falloff = 1.0 + k1 * r2 + k2 * r2 * r2 + k3 * r2 * r2 * r2;
+                    if falloff > 1e-3 {
+                        for value in pixel.iter_mut() {
+                            let linear = value.max(0.0).powf(2.2) / falloff;
+                            *value = linear.powf(1.0 / 2.2);
+                        }
+                    }
+                }
+
+                row[x * 3..x * 3 + 3].copy_from_slice(&pixel);
+            }
+        });
+
+    DynamicImage::ImageRgb32F(output)
+}
+
+pub fn apply_lens_correction(image: &DynamicImage, adjustments: &Value, lens: Option<&LensInfo>) -> DynamicImage {
+    let settings = &adjustments["lensCorrection"];
+    if !settings["enabled"].as_bool().unwrap_or(false) {
+        return image.clone();
+    }
+
+    let lens_model = settings["profile"]
+        .as_str()
+        .map(|s| s.to_string())
+        .or_else(|| lens.and_then(|l| l.lens_model.clone()));
+    let Some(profile) = lens_model.as_deref().and_then(find_lens_profile) else {
+        return image.clone();
+    };
+
+    let mut lens_info = lens.cloned().unwrap_or_default();
+    if let Some(focal) = settings["focalLength"].as_f64() {
+        lens_info.focal_length = Some(focal as f32);
+    }
+
+    let params = resolve_correction_params(&profile, &lens_info, settings);
+    if params.distortion.is_none() && params.tca.is_none() && params.vignetting.is_none() {
+        return image.clone();
+    }
+    apply_correction_params(image, &params)
+}
+
+#[tauri::command]
+pub fn get_lens_correction_info(path: String) -> Result<LensCorrectionInfo, String> {
+    let lens = read_lens_info_from_path(&path);
+    let detected_profile = lens
+        .lens_model
+        .as_deref()
+        .and_then(find_lens_profile)
+        .map(|p| p.model);
+    let available_profiles = LENS_DATABASE
+        .read()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|p| p.model.clone())
+        .collect();
+
+    Ok(LensCorrectionInfo { lens, detected_profile, available_profiles })
+}
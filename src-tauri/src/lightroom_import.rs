@@ -0,0 +1,270 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+use crate::catalog::{sync_folder, with_catalog};
+use crate::file_management::{add_paths_to_album, create_album, get_sidecar_path, load_albums};
+use crate::image_processing::{ImageFlag, ImageMetadata};
+use crate::iptc_metadata::read_metadata;
+use crate::tagging::COLOR_TAG_PREFIX;
+
+const COLOR_LABELS: [&str; 5] = ["red", "yellow", "green", "blue", "purple"];
+const REGULAR_COLLECTION: &str = "com.adobe.ag.library.collection";
+
+// Process 2012+ develop settings whose sliders share RapidRAW's ranges.
+const DEVELOP_MAPPING: [(&str, &str); 10] = [
+    ("Exposure2012", "exposure"),
+    ("Contrast2012", "contrast"),
+    ("Highlights2012", "highlights"),
+    ("Shadows2012", "shadows"),
+    ("Whites2012", "whites"),
+    ("Blacks2012", "blacks"),
+    ("Clarity2012", "clarity"),
+    ("Dehaze", "dehaze"),
+    ("Vibrance", "vibrance"),
+    ("Saturation", "saturation"),
+];
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LightroomImportSummary {
+    pub images: usize,
+    pub missing: usize,
+    pub albums: usize,
+}
+
+#[derive(Default)]
+struct LightroomImage {
+    path: String,
+    rating: u8,
+    flag: Option<ImageFlag>,
+    color_label: Option<String>,
+    keywords: Vec<String>,
+    collections: Vec<String>,
+    develop: Option<String>,
+}
+
+// Lightroom stores forward-slash paths on every platform; library paths on Windows use backslashes.
+fn native_path(path: String) -> String {
+    if cfg!(windows) {
+        path.replace('/', "\\")
+    } else {
+        path
+    }
+}
+
+fn read_images(conn: &Connection, include_develop: bool) -> rusqlite::Result<HashMap<i64, LightroomImage>> {
+    let mut images = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT i.id_local, rf.absolutePath || fo.pathFromRoot || f.baseName || '.' || f.extension,
+                i.rating, i.pick, i.colorLabels
+         FROM Adobe_images i
+         JOIN AgLibraryFile f ON f.id_local = i.rootFile
+         JOIN AgLibraryFolder fo ON fo.id_local = f.folder
+         JOIN AgLibraryRootFolder rf ON rf.id_local = fo.rootFolder
+         WHERE i.masterImage IS NULL",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let pick: Option<f64> = row.get(3)?;
+        let color_label: Option<String> = row.get(4)?;
+        Ok((
+            row.get::<_, i64>(0)?,
+            LightroomImage {
+                path: native_path(row.get(1)?),
+                rating: row.get::<_, Option<f64>>(2)?.unwrap_or(0.0).clamp(0.0, 5.0) as u8,
+                flag: match pick {
+                    Some(p) if p > 0.0 => Some(ImageFlag::Pick),
+                    Some(p) if p < 0.0 => Some(ImageFlag::Reject),
+                    _ => None,
+                },
+                color_label: color_label
+                    .map(|l| l.to_lowercase())
+                    .filter(|l| COLOR_LABELS.contains(&l.as_str())),
+                ..Default::default()
+            },
+        ))
+    })?;
+    for row in rows {
+        let (id, image) = row?;
+        images.insert(id, image);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT ki.image, k.name FROM AgLibraryKeywordImage ki
+         JOIN AgLibraryKeyword k ON k.id_local = ki.tag
+         WHERE k.name IS NOT NULL",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (id, keyword) = row?;
+        if let Some(image) = images.get_mut(&id) {
+            image.keywords.push(keyword);
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT ci.image, c.name FROM AgLibraryCollectionImage ci
+         JOIN AgLibraryCollection c ON c.id_local = ci.collection
+         WHERE c.creationId = ?1 AND c.name IS NOT NULL",
+    )?;
+    let rows = stmt.query_map([REGULAR_COLLECTION], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (id, collection) = row?;
+        if let Some(image) = images.get_mut(&id) {
+            image.collections.push(collection);
+        }
+    }
+
+    if include_develop {
+        let mut stmt = conn.prepare("SELECT image, text FROM Adobe_imageDevelopSettings WHERE text IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (id, text) = row?;
+            if let Some(image) = images.get_mut(&id) {
+                image.develop = Some(text);
+            }
+        }
+    }
+
+    Ok(images)
+}
+
+// Develop settings are stored as a Lua table ("s = { Exposure2012 = 0.35, ... }").
+fn lua_number(text: &str, key: &str) -> Option<f64> {
+    let needle = format!("{} = ", key);
+    let mut search_from = 0;
+    while let Some(found) = text[search_from..].find(&needle) {
+        let start = search_from + found;
+        search_from = start + needle.len();
+        let at_key_start = text[..start].chars().last().map_or(true, |c| c.is_whitespace() || c == '{' || c == ',');
+        if !at_key_start {
+            continue;
+        }
+        let value = &text[search_from..];
+        let end = value.find(|c: char| c == ',' || c == '\n' || c == '}').unwrap_or(value.len());
+        return value[..end].trim().parse().ok();
+    }
+    None
+}
+
+fn develop_adjustments(text: &str) -> serde_json::Map<String, Value> {
+    DEVELOP_MAPPING
+        .iter()
+        .filter_map(|(lr_key, key)| lua_number(text, lr_key).filter(|v| *v != 0.0).map(|v| (key.to_string(), serde_json::json!(v))))
+        .collect()
+}
+
+fn has_edits(metadata: &ImageMetadata) -> bool {
+    metadata.adjustments.as_object().map_or(false, |a| a.keys().any(|k| k != "rating"))
+}
+
+// Like the XMP import, only fills in what RapidRAW doesn't know yet.
+fn merge_into(metadata: &mut ImageMetadata, image: &LightroomImage) {
+    if !metadata.adjustments.is_object() {
+        metadata.adjustments = serde_json::json!({});
+    }
+    if metadata.rating == 0 && image.rating > 0 {
+        metadata.rating = image.rating;
+        metadata.adjustments["rating"] = serde_json::json!(image.rating);
+    }
+    if metadata.flag.is_none() {
+        metadata.flag = image.flag;
+    }
+    if let Some(develop) = image.develop.as_deref().filter(|_| !has_edits(metadata)) {
+        if let Some(adjustments) = metadata.adjustments.as_object_mut() {
+            adjustments.extend(develop_adjustments(develop));
+        }
+    }
+
+    let mut tags = metadata.tags.take().unwrap_or_default();
+    if let Some(label) = &image.color_label {
+        if !tags.iter().any(|t| t.starts_with(COLOR_TAG_PREFIX)) {
+            tags.push(format!("{}{}", COLOR_TAG_PREFIX, label));
+        }
+    }
+    for keyword in &image.keywords {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(keyword)) {
+            tags.push(keyword.clone());
+        }
+    }
+    metadata.tags = if tags.is_empty() { None } else { Some(tags) };
+}
+
+// Migrates ratings, flags, color labels, keywords and collections from a Lightroom Classic catalog.
+// Regular collections become albums, merged into an existing album of the same name; develop settings
+// are optional and limited to the basic sliders.
+#[tauri::command]
+pub async fn import_lightroom_catalog(
+    catalog_path: String,
+    include_develop: bool,
+    app_handle: AppHandle,
+) -> Result<LightroomImportSummary, String> {
+    tokio::task::spawn_blocking(move || import_catalog(&catalog_path, include_develop, &app_handle))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn import_catalog(catalog_path: &str, include_develop: bool, app_handle: &AppHandle) -> Result<LightroomImportSummary, String> {
+    let conn = Connection::open_with_flags(&catalog_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open Lightroom catalog: {}", e))?;
+    let images = read_images(&conn, include_develop).map_err(|e| format!("Failed to read Lightroom catalog: {}", e))?;
+
+    let mut summary = LightroomImportSummary::default();
+    let mut folders = HashSet::new();
+    let mut collections: HashMap<String, Vec<String>> = HashMap::new();
+    let total = images.len();
+    for (i, image) in images.values().enumerate() {
+        if i % 50 == 0 {
+            let _ = app_handle.emit("lightroom-import-progress", serde_json::json!({ "current": i, "total": total }));
+        }
+        if !Path::new(&image.path).exists() {
+            summary.missing += 1;
+            continue;
+        }
+        let mut metadata = read_metadata(&image.path);
+        merge_into(&mut metadata, image);
+        let json_string = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
+        if let Err(e) = fs::write(get_sidecar_path(&image.path), json_string) {
+            eprintln!("Failed to write sidecar for '{}': {}", image.path, e);
+            continue;
+        }
+        summary.images += 1;
+        for collection in &image.collections {
+            collections.entry(collection.clone()).or_default().push(image.path.clone());
+        }
+        if let Some(folder) = Path::new(&image.path).parent() {
+            folders.insert(folder.to_string_lossy().into_owned());
+        }
+    }
+
+    let albums = load_albums(app_handle.clone())?;
+    for (name, paths) in collections {
+        match albums.iter().find(|a| a.name == name) {
+            Some(album) => {
+                add_paths_to_album(album.id.clone(), paths, app_handle.clone())?;
+            }
+            None => {
+                create_album(name, Some(paths), app_handle.clone())?;
+                summary.albums += 1;
+            }
+        }
+    }
+
+    with_catalog(app_handle, |conn| {
+        for folder in &folders {
+            sync_folder(conn, folder)?;
+        }
+        Ok(())
+    })?;
+
+    println!(
+        "Imported {} images from Lightroom catalog ({} missing, {} albums).",
+        summary.images, summary.missing, summary.albums
+    );
+    Ok(summary)
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+const LUTS_DIR: &str = "luts";
+const MAX_LUT_SIZE: u32 = 256;
+
+pub struct Lut {
+    pub name: String,
+    pub size: u32,
+    pub data: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LutInfo {
+    pub id: String,
+    pub name: String,
+    pub size: u32,
+}
+
+static LUTS: Lazy<RwLock<HashMap<String, Arc<Lut>>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+fn get_luts_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(LUTS_DIR);
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    Ok(dir)
+}
+
+fn lut_id_from_path(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+pub fn parse_cube(content: &str, fallback_name: &str) -> Result<Lut, String> {
+    let mut name = fallback_name.to_string();
+    let mut size = 0u32;
+    let mut data = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let keyword = parts.next().unwrap_or("");
+        match keyword {
+            "TITLE" => {
+                let title = line["TITLE".len()..].trim().trim_matches('"');
+                if !title.is_empty() {
+                    name = title.to_string();
+                }
+            }
+            "LUT_3D_SIZE" => {
+                size = parts
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| "Invalid LUT_3D_SIZE value".to_string())?;
+                if size < 2 || size > MAX_LUT_SIZE {
+                    return Err(format!("Unsupported LUT size: {}", size));
+                }
+            }
+            "LUT_1D_SIZE" => return Err("1D LUTs are not supported, please use a 3D .cube file.".to_string()),
+            "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                let expected = if keyword == "DOMAIN_MIN" { 0.0 } else { 1.0 };
+                let values: Vec<f32> = parts.filter_map(|v| v.parse().ok()).collect();
+                if values.len() != 3 || values.iter().any(|v| (v - expected).abs() > 1e-6) {
+                    return Err("LUTs with a custom input domain are not supported.".to_string());
+                }
+            }
+            _ => {
+                let values: Vec<f32> = line
+                    .split_whitespace()
+                    .map(|v| v.parse::<f32>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("Unexpected line in .cube file: {}", line))?;
+                if values.len() != 3 {
+                    return Err(format!("Unexpected line in .cube file: {}", line));
+                }
+                data.extend_from_slice(&values);
+            }
+        }
+    }
+
+    if size == 0 {
+        return Err("Missing LUT_3D_SIZE in .cube file.".to_string());
+    }
+    let expected = (size * size * size * 3) as usize;
+    if data.len() != expected {
+        return Err(format!(
+            "LUT data has {} entries, expected {}.",
+            data.len() / 3,
+            expected / 3
+        ));
+    }
+
+    Ok(Lut { name, size, data })
+}
+
+fn load_lut_file(path: &Path) -> Result<Lut, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_cube(&content, &lut_id_from_path(path))
+}
+
+pub fn init_luts(app_handle: &AppHandle) {
+    let Ok(dir) = get_luts_dir(app_handle) else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+    let Ok(mut luts) = LUTS.write() else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let is_cube = path
+            .extension()
+            .map_or(false, |ext| ext.to_string_lossy().eq_ignore_ascii_case("cube"));
+        if !is_cube {
+            continue;
+        }
+        match load_lut_file(&path) {
+            Ok(lut) => {
+                luts.insert(lut_id_from_path(&path), Arc::new(lut));
+            }
+            Err(e) => eprintln!("Failed to load LUT {}: {}", path.display(), e),
+        }
+    }
+}
+
+pub fn get_lut_for_adjustments(adjustments: &Value) -> Option<Arc<Lut>> {
+    let lut = adjustments.get("lut")?;
+    let id = lut.get("id")?.as_str()?;
+    let intensity = lut.get("intensity").and_then(|v| v.as_f64()).unwrap_or(100.0);
+    if intensity <= 0.0 {
+        return None;
+    }
+    LUTS.read().ok()?.get(id).cloned()
+}
+
+#[tauri::command]
+pub fn import_lut(source_path: String, app_handle: AppHandle) -> Result<LutInfo, String> {
+    let source = Path::new(&source_path);
+    let lut = load_lut_file(source)?;
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| "Invalid LUT path".to_string())?
+        .to_string_lossy()
+        .to_string();
+    let dir = get_luts_dir(&app_handle)?;
+    let destination = dir.join(&file_name);
+    fs::copy(source, &destination).map_err(|e| e.to_string())?;
+
+    let id = lut_id_from_path(&destination);
+    let info = LutInfo {
+        id: id.clone(),
+        name: lut.name.clone(),
+        size: lut.size,
+    };
+    LUTS.write().map_err(|e| e.to_string())?.insert(id, Arc::new(lut));
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn get_luts() -> Result<Vec<LutInfo>, String> {
+    let luts = LUTS.read().map_err(|e| e.to_string())?;
+    let mut infos: Vec<LutInfo> = luts
+        .iter()
+        .map(|(id, lut)| LutInfo {
+            id: id.clone(),
+            name: lut.name.clone(),
+            size: lut.size,
+        })
+        .collect();
+    infos.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(infos)
+}
@@ -14,17 +14,61 @@ mod tagging_utils;
 mod panorama_stitching;
 mod panorama_utils;
 mod inpainting;
+mod lens_correction;
+mod hdr_merging;
+mod focus_stacking;
+mod color_profiles;
+mod watermark;
+mod output_sharpening;
+mod tiff_metadata;
+mod avif_metadata;
+mod lut_processing;
+mod spot_removal;
+mod catalog;
+mod smart_collections;
+mod geotagging;
+mod stacking;
+mod edit_versions;
+mod edit_history;
+mod image_prefetch;
+mod memory_budget;
+mod folder_watcher;
+mod preview_protocol;
+mod culling;
+mod auto_straighten;
+mod auto_crop;
+mod xmp_sidecar;
+mod iptc_metadata;
+mod exif_details;
+mod capture_time;
+mod watch_import;
+mod camera_import;
+mod lightroom_import;
+mod export_hooks;
+mod http_api;
+mod upload_targets;
+mod develop_import;
+mod publish_services;
+mod custom_effects;
+mod video;
 
 use std::io::Cursor;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::fs;
-use std::collections::{HashMap, hash_map::DefaultHasher};
+use std::collections::{HashMap, HashSet, hash_map::DefaultHasher};
 use std::hash::{Hash, Hasher};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use image::{DynamicImage, GenericImageView, ImageBuffer, Luma, Rgb, Rgba, RgbaImage, ImageFormat, GrayImage, RgbImage};
+use image::{DynamicImage, GenericImageView, ImageBuffer, Luma, Rgb, Rgba, RgbaImage, ImageFormat, GrayImage, RgbImage, Rgb32FImage};
 use image::codecs::jpeg::JpegEncoder;
+use image::codecs::avif::AvifEncoder;
+use image::codecs::png::PngEncoder;
+use image::ImageEncoder;
+use tiff::encoder::{colortype, TiffEncoder};
+use tiff::tags::Tag as TiffTag;
+use jpegxl_rs::encode::{EncoderResult, EncoderSpeed};
 use imageproc::morphology::dilate;
 use imageproc::distance_transform::Norm as DilationNorm;
 use tauri::{Manager, Emitter, ipc::Response};
@@ -32,6 +76,7 @@ use base64::{Engine as _, engine::general_purpose};
 use serde_json::Value;
 use tokio::sync::Mutex as TokioMutex;
 use tokio::task::JoinHandle;
+use rayon::prelude::*;
 use window_vibrancy::{apply_acrylic, apply_vibrancy, NSVisualEffectMaterial};
 use serde::{Serialize, Deserialize};
 use little_exif::metadata::Metadata;
@@ -41,25 +86,49 @@ use little_exif::rational::uR64;
 use chrono::{DateTime, Utc};
 
 use crate::image_processing::{
-    get_all_adjustments_from_json, get_or_init_gpu_context, GpuContext,
+    get_all_adjustments_from_json, get_or_init_gpu_context, lateral_ca_for, AllAdjustments, GpuContext, OpticalCenter,
     ImageMetadata, process_and_get_dynamic_image, Crop, apply_crop, apply_rotation, apply_flip, apply_coarse_rotation,
 };
-use crate::file_management::{get_sidecar_path, load_settings, AppSettings};
-use crate::mask_generation::{MaskDefinition, generate_mask_bitmap, AiPatchDefinition};
+use crate::gpu_processing::{
+    process_comparison, process_hdr_preview, process_region_and_get_dynamic_image, process_wide_gamut_image,
+    process_with_scopes, CompareLayout, CompareSide, TileRegion,
+};
+use crate::file_management::{get_sidecar_path, load_settings, sidecar_lock, unique_output_path, AppSettings};
+use crate::mask_generation::{MaskDefinition, generate_mask_bitmap, AiPatchDefinition, GenerationParameters, mask_definition_from_ai_patch};
 use crate::ai_processing::{
     AiState, get_or_init_ai_models, generate_image_embeddings, run_sam_decoder,
-    AiSubjectMaskParameters, run_u2netp_model, AiForegroundMaskParameters, run_sky_seg_model, AiSkyMaskParameters
+    AiSubjectMaskParameters, run_u2netp_model, remove_background, AiForegroundMaskParameters, run_sky_seg_model, AiSkyMaskParameters,
+    run_face_detector, get_or_init_face_detector, generate_face_mask, AiFaceMaskParameters, AiModels, ImageEmbeddings, FaceDetection,
+    get_or_init_upscale_model, run_upscale_model, get_or_init_denoise_model, run_denoise_model, blend_denoised,
+    DenoisedImage, SamPoint, AiMaskCandidate, get_or_init_inpaint_model, run_lama_inpaint,
+};
+use crate::formats::{is_raw_file, is_video_file};
+use crate::image_loader::{composite_patches_on_image, load_and_composite, load_base_image_from_bytes};
+use crate::color_profiles::{
+    build_icc_profile, convert_to_output_color_space, encode_hdr_png, encode_preview_jpeg, soft_proof_image, DisplayProfile,
+    HdrPreviewMode, OutputColorSpace, SoftProofSettings,
 };
-use crate::formats::{is_raw_file};
-use crate::image_loader::{load_base_image_from_bytes, composite_patches_on_image, load_and_composite};
+use crate::output_sharpening::{apply_output_sharpening, OutputSharpeningSettings};
+use crate::lut_processing::{get_lut_for_adjustments, Lut};
+use crate::watermark::{apply_watermark, WatermarkSettings};
+use crate::export_hooks::ExportedFile;
+use crate::lens_correction::{apply_lens_correction, read_lens_info_from_path, LensInfo};
+use crate::image_prefetch::{decode_for_editing, DecodedImage, PrefetchCache};
+use crate::memory_budget::{enforce_memory_budget, export_thread_count};
+use crate::folder_watcher::FolderWatcher;
+use crate::watch_import::WatchImporters;
+use crate::http_api::HttpApiServer;
+use crate::preview_protocol::{handle_preview_request, publish_preview_frame, PreviewFrames, PREVIEW_SCHEME};
+use ort::Session;
 use tagging_utils::{candidates, hierarchy};
 
 #[derive(Clone)]
 pub struct LoadedImage {
     path: String,
-    image: DynamicImage,
+    image: Arc<DynamicImage>,
     full_width: u32,
     full_height: u32,
+    lens_info: LensInfo,
 }
 
 #[derive(Clone)]
@@ -68,20 +137,50 @@ pub struct CachedPreview {
     transform_hash: u64,
     scale: f32,
     unscaled_crop_offset: (f32, f32),
+    optical_center: OpticalCenter,
+}
+
+// The transformed full-resolution image and its rasterized masks, reused while a zoomed-in view is panned.
+pub struct CachedFullResolution {
+    path: String,
+    transform_hash: u64,
+    masks_hash: u64,
+    image: DynamicImage,
+    mask_bitmaps: Vec<ImageBuffer<Luma<u8>, Vec<u8>>>,
+    optical_center: OpticalCenter,
 }
 
 pub struct AppState {
     original_image: Mutex<Option<LoadedImage>>,
     cached_preview: Mutex<Option<CachedPreview>>,
+    cached_full_resolution: Mutex<Option<Arc<CachedFullResolution>>>,
     gpu_context: Mutex<Option<GpuContext>>,
     ai_state: Mutex<Option<AiState>>,
     ai_init_lock: TokioMutex<()>,
+    face_detector: Mutex<Option<Arc<Session>>>,
+    upscale_model: Mutex<Option<Arc<Session>>>,
+    denoise_model: Mutex<Option<Arc<Session>>>,
+    denoised_image: Mutex<Option<DenoisedImage>>,
+    inpaint_model: Mutex<Option<Arc<Session>>>,
+    eye_state_model: Mutex<Option<Arc<Session>>>,
+    face_embedding_model: Mutex<Option<Arc<Session>>>,
     export_task_handle: Mutex<Option<JoinHandle<()>>>,
-    panorama_result: Arc<Mutex<Option<RgbImage>>>,
+    panorama_result: Arc<Mutex<Option<panorama_stitching::StitchedPanorama>>>,
+    hdr_result: Arc<Mutex<Option<Rgb32FImage>>>,
+    focus_stack_result: Arc<Mutex<Option<Rgb32FImage>>>,
     indexing_task_handle: Mutex<Option<JoinHandle<()>>>,
+    catalog: Mutex<Option<rusqlite::Connection>>,
+    prefetch_cache: Arc<PrefetchCache>,
+    pending_image_path: Mutex<Option<String>>,
+    folder_watcher: Mutex<Option<FolderWatcher>>,
+    watch_importers: Mutex<Option<WatchImporters>>,
+    http_api: Mutex<Option<HttpApiServer>>,
+    preview_frames: PreviewFrames,
+    display_profile: Mutex<Option<Arc<DisplayProfile>>>,
+    custom_effect: Arc<Mutex<Option<String>>>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, Clone)]
 struct LoadImageResult {
     #[serde(with = "serde_bytes")]
     original_image_bytes: Vec<u8>,
@@ -90,6 +189,14 @@ struct LoadImageResult {
     metadata: ImageMetadata,
     exif: HashMap<String, String>,
     is_raw: bool,
+    is_embedded_preview: bool,
+}
+
+#[derive(serde::Serialize, Clone)]
+struct PreviewUpgradedPayload {
+    path: String,
+    image: Option<LoadImageResult>,
+    error: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -116,19 +223,109 @@ struct ExportSettings {
     keep_metadata: bool,
     strip_gps: bool,
     filename_template: Option<String>,
+    quality: Option<u8>,
+    speed: Option<u8>,
+    output_color_space: Option<OutputColorSpace>,
+    watermark: Option<WatermarkSettings>,
+    output_sharpening: Option<OutputSharpeningSettings>,
+    ai_upscale: Option<u32>,
+    remove_background: Option<bool>,
+    post_export_hook: Option<String>,
+    upload_target_id: Option<String>,
+    upscale_backend: Option<UpscaleBackend>,
+    comfyui_upscale_model: Option<String>,
+    #[serde(skip)]
+    publish_service_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+enum UpscaleBackend {
+    Local,
+    ComfyUi,
+}
+
+enum ExportUpscaler {
+    Local(Arc<Session>),
+    ComfyUi { profile: comfyui_connector::ComfyUiProfile, model: Option<String> },
+}
+
+impl ExportUpscaler {
+    fn upscale(&self, image: &DynamicImage, scale: u32) -> Result<DynamicImage, String> {
+        match self {
+            ExportUpscaler::Local(session) => run_upscale_model(image, session, scale).map_err(|e| e.to_string()),
+            ExportUpscaler::ComfyUi { profile, model } => {
+                comfyui_connector::upscale_image_blocking(profile, image, scale, model.as_deref())
+                    .map_err(|e| format!("ComfyUI upscale failed: {}", e))
+            }
+        }
+    }
+}
+
+async fn init_export_upscaler(
+    export_settings: &ExportSettings,
+    state: &tauri::State<'_, AppState>,
+    app_handle: &tauri::AppHandle,
+) -> Result<Option<ExportUpscaler>, String> {
+    if export_settings.ai_upscale.is_none() {
+        return Ok(None);
+    }
+    if export_settings.upscale_backend == Some(UpscaleBackend::ComfyUi) {
+        let profile = comfyui_connector::select_profile(&load_settings(app_handle.clone()).unwrap_or_default()).await?;
+        return Ok(Some(ExportUpscaler::ComfyUi { profile, model: export_settings.comfyui_upscale_model.clone() }));
+    }
+    let session = get_or_init_upscale_model(app_handle, &state.upscale_model, &state.ai_init_lock)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Some(ExportUpscaler::Local(session)))
+}
+
+// Uploads run before the post-export script, so a script that moves or renames files can't break them.
+async fn finish_export(files: &[ExportedFile], format: &str, export_settings: &ExportSettings, app_handle: &tauri::AppHandle) {
+    if let Some(service_id) = export_settings.publish_service_id.as_deref() {
+        publish_services::post_published_files(service_id, files, app_handle).await;
+        return;
+    }
+    if let Some(target_id) = export_settings.upload_target_id.as_deref() {
+        upload_targets::upload_exported_files(target_id, files, app_handle).await;
+    }
+    let Some(hook) = export_settings.post_export_hook.as_deref().filter(|h| !h.trim().is_empty()) else {
+        return;
+    };
+    let settings_value = serde_json::to_value(export_settings).unwrap_or(Value::Null);
+    export_hooks::run_post_export_hook(
+        hook.to_string(),
+        files.to_vec(),
+        format.to_string(),
+        settings_value,
+        app_handle.clone(),
+    );
+}
+
+fn check_transparent_export(export_settings: &ExportSettings, output_format: &str) -> Result<(), String> {
+    if export_settings.remove_background.unwrap_or(false)
+        && !matches!(output_format.to_lowercase().as_str(), "png" | "tiff" | "webp" | "avif")
+    {
+        return Err("Background removal requires a PNG, TIFF, WebP or AVIF export.".to_string());
+    }
+    Ok(())
 }
 
 fn apply_all_transformations(
     image: &DynamicImage,
     adjustments: &serde_json::Value,
     scale: f32,
-) -> (DynamicImage, (f32, f32)) {
+    lens_info: Option<&LensInfo>,
+) -> (DynamicImage, (f32, f32), OpticalCenter) {
+    let lens_corrected_image = apply_lens_correction(image, adjustments, lens_info);
+    let lateral_ca = lateral_ca_for(&lens_corrected_image, adjustments);
+
     let orientation_steps = adjustments["orientationSteps"].as_u64().unwrap_or(0) as u8;
     let rotation_degrees = adjustments["rotation"].as_f64().unwrap_or(0.0) as f32;
     let flip_horizontal = adjustments["flipHorizontal"].as_bool().unwrap_or(false);
     let flip_vertical = adjustments["flipVertical"].as_bool().unwrap_or(false);
 
-    let coarse_rotated_image = apply_coarse_rotation(image.clone(), orientation_steps);
+    let coarse_rotated_image = apply_coarse_rotation(lens_corrected_image, orientation_steps);
     let flipped_image = apply_flip(coarse_rotated_image, flip_horizontal, flip_vertical);
     let rotated_image = apply_rotation(&flipped_image, rotation_degrees);
 
@@ -145,11 +342,18 @@ fn apply_all_transformations(
         serde_json::Value::Null
     };
 
+    let (uncropped_width, uncropped_height) = rotated_image.dimensions();
     let cropped_image = apply_crop(rotated_image, &scaled_crop_json);
     
     let unscaled_crop_offset = crop_data.map_or((0.0, 0.0), |c| (c.x as f32, c.y as f32));
-
-    (cropped_image, unscaled_crop_offset)
+    let optical_center = OpticalCenter::of_crop(
+        uncropped_width,
+        uncropped_height,
+        (unscaled_crop_offset.0 * scale, unscaled_crop_offset.1 * scale),
+    )
+    .with_lateral_ca(lateral_ca);
+
+    (cropped_image, unscaled_crop_offset, optical_center)
 }
 
 fn calculate_transform_hash(adjustments: &serde_json::Value) -> u64 {
@@ -158,6 +362,9 @@ fn calculate_transform_hash(adjustments: &serde_json::Value) -> u64 {
     let orientation_steps = adjustments["orientationSteps"].as_u64().unwrap_or(0);
     orientation_steps.hash(&mut hasher);
 
+    let ai_denoise = adjustments["aiDenoise"].as_f64().unwrap_or(0.0);
+    (ai_denoise.to_bits()).hash(&mut hasher);
+
     let rotation = adjustments["rotation"].as_f64().unwrap_or(0.0);
     (rotation.to_bits()).hash(&mut hasher);
 
@@ -172,7 +379,22 @@ fn calculate_transform_hash(adjustments: &serde_json::Value) -> u64 {
             crop_val.to_string().hash(&mut hasher);
         }
     }
+
+    if let Some(lens_val) = adjustments.get("lensCorrection") {
+        if !lens_val.is_null() {
+            lens_val.to_string().hash(&mut hasher);
+        }
+    }
+
+    // The lateral CA estimate is only made while the correction is on.
+    (adjustments["chromaticAberration"].as_f64().unwrap_or(0.0) > 0.0).hash(&mut hasher);
     
+    if let Some(spot_val) = adjustments.get("spotEdits") {
+        if !spot_val.is_null() {
+            spot_val.to_string().hash(&mut hasher);
+        }
+    }
+
     if let Some(patches_val) = adjustments.get("aiPatches") {
         if let Some(patches_arr) = patches_val.as_array() {
             patches_arr.len().hash(&mut hasher);
@@ -202,6 +424,9 @@ fn calculate_transform_hash(adjustments: &serde_json::Value) -> u64 {
 
                 let invert = patch.get("invert").and_then(|v| v.as_bool()).unwrap_or(false);
                 invert.hash(&mut hasher);
+
+                let opacity = patch.get("opacity").and_then(|v| v.as_f64()).unwrap_or(100.0);
+                opacity.to_bits().hash(&mut hasher);
             }
         }
     }
@@ -209,12 +434,42 @@ fn calculate_transform_hash(adjustments: &serde_json::Value) -> u64 {
     hasher.finish()
 }
 
+fn hash_image_path(path: &str) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(path.as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+fn ai_denoise_strength(adjustments: &serde_json::Value) -> f32 {
+    adjustments["aiDenoise"].as_f64().unwrap_or(0.0) as f32 / 100.0
+}
+
+fn cached_ai_denoise(
+    image: &DynamicImage,
+    adjustments: &serde_json::Value,
+    path: &str,
+    state: &AppState,
+) -> Option<DynamicImage> {
+    let strength = ai_denoise_strength(adjustments);
+    if strength <= 0.0 {
+        return None;
+    }
+    let denoised_lock = state.denoised_image.lock().unwrap();
+    let denoised = denoised_lock.as_ref()?;
+    if denoised.path_hash != hash_image_path(path) || denoised.image.dimensions() != image.dimensions() {
+        return None;
+    }
+    Some(blend_denoised(image, &denoised.image, strength))
+}
+
 fn generate_transformed_preview(
     loaded_image: &LoadedImage,
     adjustments: &serde_json::Value,
     app_handle: &tauri::AppHandle,
-) -> Result<(DynamicImage, f32, (f32, f32)), String> {
-    let patched_original_image = composite_patches_on_image(&loaded_image.image, adjustments)
+) -> Result<(DynamicImage, f32, (f32, f32), OpticalCenter), String> {
+    let denoised_image = cached_ai_denoise(&loaded_image.image, adjustments, &loaded_image.path, &app_handle.state::<AppState>());
+    let source_image = denoised_image.as_ref().unwrap_or(loaded_image.image.as_ref());
+    let patched_original_image = composite_patches_on_image(source_image, adjustments)
         .map_err(|e| format!("Failed to composite AI patches: {}", e))?;
     
     let (full_w, full_h) = (loaded_image.full_width, loaded_image.full_height);
@@ -231,10 +486,10 @@ fn generate_transformed_preview(
             (patched_original_image.clone(), 1.0)
         };
 
-    let (final_preview_base, unscaled_crop_offset) = 
-        apply_all_transformations(&processing_base, adjustments, scale_for_gpu);
+    let (final_preview_base, unscaled_crop_offset, optical_center) = 
+        apply_all_transformations(&processing_base, adjustments, scale_for_gpu, Some(&loaded_image.lens_info));
     
-    Ok((final_preview_base, scale_for_gpu, unscaled_crop_offset))
+    Ok((final_preview_base, scale_for_gpu, unscaled_crop_offset, optical_center))
 }
 
 fn encode_to_base64_png(image: &GrayImage) -> Result<String, String> {
@@ -258,54 +513,147 @@ fn read_exif_data(file_bytes: &[u8]) -> HashMap<String, String> {
     exif_data
 }
 
-#[tauri::command]
-async fn load_image(path: String, state: tauri::State<'_, AppState>, app_handle: tauri::AppHandle) -> Result<LoadImageResult, String> {
-    let sidecar_path = get_sidecar_path(&path);
-    let metadata: ImageMetadata = if sidecar_path.exists() {
-        let file_content = fs::read_to_string(sidecar_path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&file_content).unwrap_or_default()
-    } else {
-        ImageMetadata::default()
-    };
+// Loads the monitor profile selected in the settings; a profile that fails to load leaves previews
+// unmanaged.
+pub fn apply_display_profile(app_handle: &tauri::AppHandle, settings: &AppSettings) {
+    let path = settings.display_profile_path.as_deref().filter(|p| !p.is_empty());
+    let state = app_handle.state::<AppState>();
+    let mut current = state.display_profile.lock().unwrap();
+    if current.as_ref().map(|p| p.path.as_str()) == path {
+        return;
+    }
+    *current = path.and_then(|path| match DisplayProfile::load(path) {
+        Ok(profile) => Some(Arc::new(profile)),
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    });
+}
 
-    let file_bytes = fs::read(&path).map_err(|e| e.to_string())?;
-    let pristine_img = load_base_image_from_bytes(&file_bytes, &path, false)
-        .map_err(|e| e.to_string())?;
+fn encode_preview(image: &DynamicImage, quality: u8, app_handle: &tauri::AppHandle) -> Result<Vec<u8>, String> {
+    let display_profile = app_handle.state::<AppState>().display_profile.lock().unwrap().clone();
+    encode_preview_jpeg(image, quality, display_profile.as_deref())
+}
 
-    let (orig_width, orig_height) = pristine_img.dimensions();
-    let is_raw = is_raw_file(&path);
+fn encode_display_preview(image: &DynamicImage, app_handle: &tauri::AppHandle) -> Result<Vec<u8>, String> {
+    let settings = load_settings(app_handle.clone()).unwrap_or_default();
+    let display_preview_dim = settings.editor_preview_resolution.unwrap_or(1920);
+    let display_preview = image.thumbnail(display_preview_dim, display_preview_dim);
+    encode_preview(&display_preview, 80, app_handle)
+}
 
-    let exif_data = read_exif_data(&file_bytes);
+#[tauri::command]
+fn list_display_profiles() -> Vec<color_profiles::DisplayProfileInfo> {
+    color_profiles::find_display_profiles()
+}
 
-    let settings = load_settings(app_handle).unwrap_or_default();
-    let display_preview_dim = settings.editor_preview_resolution.unwrap_or(1920);
-    let display_preview = pristine_img.thumbnail(display_preview_dim, display_preview_dim);
-    
-    let mut buf = Cursor::new(Vec::new());
-    display_preview.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut buf, 80)).map_err(|e| e.to_string())?;
-    let original_image_bytes = buf.into_inner();
+fn activate_decoded_image(
+    path: &str,
+    decoded: &DecodedImage,
+    metadata: ImageMetadata,
+    state: &AppState,
+    app_handle: &tauri::AppHandle,
+) -> Result<LoadImageResult, String> {
+    let original_image_bytes = encode_display_preview(&decoded.image, app_handle)?;
+    let (orig_width, orig_height) = decoded.image.dimensions();
 
     *state.cached_preview.lock().unwrap() = None;
+    *state.cached_full_resolution.lock().unwrap() = None;
     *state.original_image.lock().unwrap() = Some(LoadedImage {
-        path: path.clone(),
-        image: pristine_img,
+        path: path.to_string(),
+        image: decoded.image.clone(),
         full_width: orig_width,
         full_height: orig_height,
+        lens_info: decoded.lens_info.clone(),
     });
-    
+
     Ok(LoadImageResult {
         original_image_bytes,
         width: orig_width,
         height: orig_height,
         metadata,
-        exif: exif_data,
-        is_raw,
+        exif: decoded.exif.clone(),
+        is_raw: is_raw_file(path),
+        is_embedded_preview: false,
     })
 }
 
+fn upgrade_embedded_preview(path: String, metadata: ImageMetadata, app_handle: tauri::AppHandle) {
+    thread::spawn(move || {
+        let state = app_handle.state::<AppState>();
+        let result = decode_for_editing(&path).and_then(|decoded| {
+            let decoded = Arc::new(decoded);
+            state.prefetch_cache.insert(path.clone(), decoded.clone());
+            let pending_path = state.pending_image_path.lock().unwrap();
+            if pending_path.as_deref() != Some(path.as_str()) {
+                return Ok(None);
+            }
+            activate_decoded_image(&path, &decoded, metadata, &state, &app_handle).map(Some)
+        });
+        enforce_memory_budget(&app_handle);
+
+        let payload = match result {
+            Ok(None) => return,
+            Ok(Some(image)) => PreviewUpgradedPayload { path, image: Some(image), error: None },
+            Err(e) => PreviewUpgradedPayload { path, image: None, error: Some(e) },
+        };
+        let _ = app_handle.emit("preview-upgraded", payload);
+    });
+}
+
+#[tauri::command]
+async fn load_image(path: String, state: tauri::State<'_, AppState>, app_handle: tauri::AppHandle) -> Result<LoadImageResult, String> {
+    if is_video_file(&path) {
+        return Err("Videos can't be opened in the editor.".to_string());
+    }
+    let sidecar_path = get_sidecar_path(&path);
+    let metadata: ImageMetadata = if sidecar_path.exists() {
+        let file_content = fs::read_to_string(sidecar_path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&file_content).unwrap_or_default()
+    } else {
+        ImageMetadata::default()
+    };
+
+    *state.pending_image_path.lock().unwrap() = Some(path.clone());
+
+    if let Some(decoded) = state.prefetch_cache.get(&path) {
+        let result = activate_decoded_image(&path, &decoded, metadata, &state, &app_handle);
+        enforce_memory_budget(&app_handle);
+        return result;
+    }
+
+    if is_raw_file(&path) {
+        let file_bytes = fs::read(&path).map_err(|e| e.to_string())?;
+        if let Ok(Some(preview)) = raw_processing::extract_embedded_preview(&file_bytes) {
+            let original_image_bytes = encode_display_preview(&preview, &app_handle)?;
+            *state.cached_preview.lock().unwrap() = None;
+            *state.original_image.lock().unwrap() = None;
+            upgrade_embedded_preview(path.clone(), metadata.clone(), app_handle.clone());
+
+            return Ok(LoadImageResult {
+                original_image_bytes,
+                width: preview.width(),
+                height: preview.height(),
+                metadata,
+                exif: read_exif_data(&file_bytes),
+                is_raw: true,
+                is_embedded_preview: true,
+            });
+        }
+    }
+
+    let decoded = Arc::new(decode_for_editing(&path)?);
+    state.prefetch_cache.insert(path.clone(), decoded.clone());
+    let result = activate_decoded_image(&path, &decoded, metadata, &state, &app_handle);
+    enforce_memory_budget(&app_handle);
+    result
+}
+
 #[tauri::command]
 fn apply_adjustments(
     js_adjustments: serde_json::Value,
+    show_clipping: Option<bool>,
     state: tauri::State<AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
@@ -317,29 +665,31 @@ fn apply_adjustments(
 
     let mut cached_preview_lock = state.cached_preview.lock().unwrap();
     
-    let (final_preview_base, scale_for_gpu, unscaled_crop_offset) = 
+    let (final_preview_base, scale_for_gpu, unscaled_crop_offset, optical_center) = 
         if let Some(cached) = &*cached_preview_lock {
             if cached.transform_hash == new_transform_hash {
-                (cached.image.clone(), cached.scale, cached.unscaled_crop_offset)
+                (cached.image.clone(), cached.scale, cached.unscaled_crop_offset, cached.optical_center)
             } else {
-                let (base, scale, offset) = generate_transformed_preview(&loaded_image, &adjustments_clone, &app_handle)?;
+                let (base, scale, offset, center) = generate_transformed_preview(&loaded_image, &adjustments_clone, &app_handle)?;
                 *cached_preview_lock = Some(CachedPreview {
                     image: base.clone(),
                     transform_hash: new_transform_hash,
                     scale,
                     unscaled_crop_offset: offset,
+                    optical_center: center,
                 });
-                (base, scale, offset)
+                (base, scale, offset, center)
             }
         } else {
-            let (base, scale, offset) = generate_transformed_preview(&loaded_image, &adjustments_clone, &app_handle)?;
+            let (base, scale, offset, center) = generate_transformed_preview(&loaded_image, &adjustments_clone, &app_handle)?;
             *cached_preview_lock = Some(CachedPreview {
                 image: base.clone(),
                 transform_hash: new_transform_hash,
                 scale,
                 unscaled_crop_offset: offset,
+                optical_center: center,
             });
-            (base, scale, offset)
+            (base, scale, offset, center)
         };
     
     drop(cached_preview_lock);
@@ -354,23 +704,163 @@ fn apply_adjustments(
         let scaled_crop_offset = (unscaled_crop_offset.0 * scale_for_gpu, unscaled_crop_offset.1 * scale_for_gpu);
 
         let mask_bitmaps: Vec<ImageBuffer<Luma<u8>, Vec<u8>>> = mask_definitions.iter()
-            .filter_map(|def| generate_mask_bitmap(def, preview_width, preview_height, scale_for_gpu, scaled_crop_offset))
+            .filter_map(|def| generate_mask_bitmap(def, preview_width, preview_height, scale_for_gpu, scaled_crop_offset, Some(&final_preview_base)))
             .collect();
 
-        let final_adjustments = get_all_adjustments_from_json(&adjustments_clone);
+        let final_adjustments = get_all_adjustments_from_json(&adjustments_clone).with_optical_center(optical_center);
+        let lut = get_lut_for_adjustments(&adjustments_clone);
+        let hdr_mode = load_settings(app_handle.clone()).ok().and_then(|s| s.hdr_preview).unwrap_or_default();
 
-        if let Ok(final_processed_image) = process_and_get_dynamic_image(&context, &final_preview_base, final_adjustments, &mask_bitmaps) {
-            if let Ok(histogram_data) = image_processing::calculate_histogram_from_image(&final_processed_image) {
-                let _ = app_handle.emit("histogram-update", histogram_data);
+        let processed = process_with_scopes(&context, &final_preview_base, final_adjustments.clone(), &mask_bitmaps, lut.as_deref());
+        if let Err(e) = &processed {
+            let _ = app_handle.emit("preview-error", e.clone());
+        }
+        if let Ok((final_processed_image, scope_counts)) = processed {
+            let _ = app_handle.emit("histogram-update", image_processing::histogram_from_scope_counts(&scope_counts));
+            let _ = app_handle.emit("waveform-update", image_processing::waveform_from_scope_counts(&scope_counts));
+
+            // The scopes always come from the SDR rendering; an HDR frame replaces only what's shown.
+            // Lens blur and film effects have no HDR pass, so those images keep the SDR preview.
+            let frame = if hdr_mode != HdrPreviewMode::Off
+                && final_adjustments.lens_blur.amount <= 0.0
+                && !final_adjustments.film_effects.is_enabled()
+            {
+                process_hdr_preview(&context, &final_preview_base, final_adjustments, &mask_bitmaps, lut.as_deref(), hdr_mode)
+                    .and_then(|hdr_image| encode_hdr_png(&hdr_image, hdr_mode))
+            } else {
+                encode_preview(&final_processed_image, 80, &app_handle)
+            };
+            match frame {
+                Ok(frame) => publish_preview_frame(&app_handle, "preview-update-final", "final", frame),
+                Err(e) => eprintln!("Failed to encode preview: {}", e),
             }
 
-            if let Ok(waveform_data) = image_processing::calculate_waveform_from_image(&final_processed_image) {
-                let _ = app_handle.emit("waveform-update", waveform_data);
+            if show_clipping.unwrap_or(false) {
+                match image_processing::generate_clipping_overlay(&final_processed_image) {
+                    Ok(overlay) => publish_preview_frame(&app_handle, "clipping-overlay-update", "clipping", overlay),
+                    Err(e) => eprintln!("Failed to generate clipping overlay: {}", e),
+                }
             }
+        }
+    });
+
+    Ok(())
+}
+
+// Decodes go into the prefetch cache, so re-rendering while adjusting doesn't decode the compared
+// image again.
+fn load_compare_image(path: &str, state: &AppState) -> Result<LoadedImage, String> {
+    let decoded = match state.prefetch_cache.get(path) {
+        Some(decoded) => decoded,
+        None => {
+            let decoded = Arc::new(decode_for_editing(path)?);
+            state.prefetch_cache.insert(path.to_string(), decoded.clone());
+            decoded
+        }
+    };
+    let (full_width, full_height) = decoded.image.dimensions();
+    Ok(LoadedImage {
+        path: path.to_string(),
+        image: decoded.image.clone(),
+        full_width,
+        full_height,
+        lens_info: decoded.lens_info.clone(),
+    })
+}
+
+fn prepare_compare_side(
+    loaded_image: &LoadedImage,
+    adjustments: &serde_json::Value,
+    app_handle: &tauri::AppHandle,
+) -> Result<(DynamicImage, Vec<ImageBuffer<Luma<u8>, Vec<u8>>>, OpticalCenter), String> {
+    let (base, scale, unscaled_crop_offset, optical_center) = generate_transformed_preview(loaded_image, adjustments, app_handle)?;
+    let (width, height) = base.dimensions();
+    let scaled_crop_offset = (unscaled_crop_offset.0 * scale, unscaled_crop_offset.1 * scale);
+    let mask_definitions: Vec<MaskDefinition> = adjustments.get("masks")
+        .and_then(|m| serde_json::from_value(m.clone()).ok())
+        .unwrap_or_else(Vec::new);
+    let mask_bitmaps = mask_definitions.iter()
+        .filter_map(|def| generate_mask_bitmap(def, width, height, scale, scaled_crop_offset, Some(&base)))
+        .collect();
+    Ok((base, mask_bitmaps, optical_center))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_comparison(
+    context: &GpuContext,
+    loaded_image: &LoadedImage,
+    js_adjustments: &serde_json::Value,
+    compare_path: Option<String>,
+    compare_adjustments: Option<serde_json::Value>,
+    layout: CompareLayout,
+    split_position: f32,
+    app_handle: &tauri::AppHandle,
+) -> Result<DynamicImage, String> {
+    let compare_image = match compare_path.filter(|path| *path != loaded_image.path) {
+        Some(path) => load_compare_image(&path, &app_handle.state::<AppState>())?,
+        None => loaded_image.clone(),
+    };
+    let compare_adjustments = match compare_adjustments {
+        Some(adjustments) => adjustments,
+        None => file_management::load_metadata(compare_image.path.clone())?.adjustments,
+    };
+
+    let (compare_base, compare_masks, compare_center) = prepare_compare_side(&compare_image, &compare_adjustments, app_handle)?;
+    let (current_base, current_masks, current_center) = prepare_compare_side(loaded_image, js_adjustments, app_handle)?;
+    let compare_lut = get_lut_for_adjustments(&compare_adjustments);
+    let current_lut = get_lut_for_adjustments(js_adjustments);
+
+    process_comparison(
+        context,
+        CompareSide {
+            image: &compare_base,
+            adjustments: get_all_adjustments_from_json(&compare_adjustments).with_optical_center(compare_center),
+            mask_bitmaps: &compare_masks,
+            lut: compare_lut.as_deref(),
+        },
+        CompareSide {
+            image: &current_base,
+            adjustments: get_all_adjustments_from_json(js_adjustments).with_optical_center(current_center),
+            mask_bitmaps: &current_masks,
+            lut: current_lut.as_deref(),
+        },
+        layout,
+        split_position,
+    )
+}
+
+// Renders the current adjustments next to another state of the same image, or next to another image
+// with its saved edits when only a path is given. The compared state is on the left.
+#[tauri::command]
+fn generate_comparison(
+    js_adjustments: serde_json::Value,
+    compare_adjustments: Option<serde_json::Value>,
+    compare_path: Option<String>,
+    layout: CompareLayout,
+    split_position: Option<f32>,
+    state: tauri::State<AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let context = get_or_init_gpu_context(&state)?;
+    let loaded_image = state.original_image.lock().unwrap().clone().ok_or("No original image loaded")?;
 
-            let mut buf = Cursor::new(Vec::new());
-            if final_processed_image.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut buf, 80)).is_ok() {
-                let _ = app_handle.emit("preview-update-final", buf.get_ref());
+    thread::spawn(move || {
+        let result = render_comparison(
+            &context,
+            &loaded_image,
+            &js_adjustments,
+            compare_path,
+            compare_adjustments,
+            layout,
+            split_position.unwrap_or(0.5),
+            &app_handle,
+        )
+        .and_then(|image| encode_preview(&image, 80, &app_handle));
+        match result {
+            Ok(frame) => publish_preview_frame(&app_handle, "comparison-update", "comparison", frame),
+            Err(e) => {
+                eprintln!("Failed to render comparison: {}", e);
+                let _ = app_handle.emit("comparison-error", e);
             }
         }
     });
@@ -389,11 +879,13 @@ fn generate_uncropped_preview(
     let loaded_image = state.original_image.lock().unwrap().clone().ok_or("No original image loaded")?;
 
     thread::spawn(move || {
-        let patched_image = match composite_patches_on_image(&loaded_image.image, &adjustments_clone) {
+        let denoised_image = cached_ai_denoise(&loaded_image.image, &adjustments_clone, &loaded_image.path, &app_handle.state::<AppState>());
+        let source_image = denoised_image.as_ref().unwrap_or(loaded_image.image.as_ref());
+        let patched_image = match composite_patches_on_image(source_image, &adjustments_clone) {
             Ok(img) => img,
             Err(e) => {
                 eprintln!("Failed to composite patches for uncropped preview: {}", e);
-                loaded_image.image
+                source_image
             },
         };
         
@@ -421,15 +913,14 @@ fn generate_uncropped_preview(
             .unwrap_or_else(Vec::new);
 
         let mask_bitmaps: Vec<ImageBuffer<Luma<u8>, Vec<u8>>> = mask_definitions.iter()
-            .filter_map(|def| generate_mask_bitmap(def, preview_width, preview_height, scale_for_gpu, (0.0, 0.0)))
+            .filter_map(|def| generate_mask_bitmap(def, preview_width, preview_height, scale_for_gpu, (0.0, 0.0), Some(&processing_base)))
             .collect();
 
         let uncropped_adjustments = get_all_adjustments_from_json(&adjustments_clone);
 
-        if let Ok(processed_image) = process_and_get_dynamic_image(&context, &processing_base, uncropped_adjustments, &mask_bitmaps) {
-            let mut buf = Cursor::new(Vec::new());
-            if processed_image.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut buf, 80)).is_ok() {
-                let _ = app_handle.emit("preview-update-uncropped", buf.get_ref());
+        if let Ok(processed_image) = process_and_get_dynamic_image(&context, &processing_base, uncropped_adjustments, &mask_bitmaps, get_lut_for_adjustments(&adjustments_clone).as_deref()) {
+            if let Ok(jpeg) = encode_preview(&processed_image, 80, &app_handle) {
+                publish_preview_frame(&app_handle, "preview-update-uncropped", "uncropped", jpeg);
             }
         }
     });
@@ -445,173 +936,432 @@ fn generate_original_transformed_preview(
 ) -> Result<Response, String> {
     let loaded_image = state.original_image.lock().unwrap().clone().ok_or("No original image loaded")?;
     
-    let settings = load_settings(app_handle).unwrap_or_default();
+    let settings = load_settings(app_handle.clone()).unwrap_or_default();
     let preview_dim = settings.editor_preview_resolution.unwrap_or(1920);
     let preview_base = loaded_image.image.thumbnail(preview_dim, preview_dim);
     let scale = if loaded_image.full_width > 0 { preview_base.width() as f32 / loaded_image.full_width as f32 } else { 1.0 };
 
-    let (transformed_image, _unscaled_crop_offset) = 
-        apply_all_transformations(&preview_base, &js_adjustments, scale);
+    let (transformed_image, _unscaled_crop_offset, _) = 
+        apply_all_transformations(&preview_base, &js_adjustments, scale, Some(&loaded_image.lens_info));
 
-    let mut buf = Cursor::new(Vec::new());
-    transformed_image.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut buf, 80)).map_err(|e| e.to_string())?;
-    
-    Ok(Response::new(buf.into_inner()))
+    Ok(Response::new(encode_preview(&transformed_image, 80, &app_handle)?))
 }
 
-fn get_full_image_for_processing(state: &tauri::State<AppState>) -> Result<DynamicImage, String> {
+fn get_full_image_for_processing(state: &tauri::State<AppState>) -> Result<Arc<DynamicImage>, String> {
     let original_image_lock = state.original_image.lock().unwrap();
     let loaded_image = original_image_lock.as_ref().ok_or("No original image loaded")?;
     Ok(loaded_image.image.clone())
 }
 
-#[tauri::command]
-fn generate_fullscreen_preview(
-    js_adjustments: serde_json::Value,
-    state: tauri::State<AppState>,
-) -> Result<Response, String> {
-    let context = get_or_init_gpu_context(&state)?;
-    let original_image = get_full_image_for_processing(&state)?;
-    let base_image = composite_patches_on_image(&original_image, &js_adjustments)
-        .map_err(|e| format!("Failed to composite AI patches for fullscreen: {}", e))?;
-    
-    let (transformed_image, unscaled_crop_offset) = 
-        apply_all_transformations(&base_image, &js_adjustments, 1.0);
-    let (img_w, img_h) = transformed_image.dimensions();
-    
-    let mask_definitions: Vec<MaskDefinition> = js_adjustments.get("masks")
-        .and_then(|m| serde_json::from_value(m.clone()).ok())
-        .unwrap_or_else(Vec::new);
+fn get_denoised_full_image(state: &tauri::State<AppState>, adjustments: &serde_json::Value) -> Result<Arc<DynamicImage>, String> {
+    let loaded_image = state.original_image.lock().unwrap().clone().ok_or("No original image loaded")?;
+    Ok(cached_ai_denoise(&loaded_image.image, adjustments, &loaded_image.path, state).map(Arc::new).unwrap_or(loaded_image.image))
+}
 
-    let mask_bitmaps: Vec<ImageBuffer<Luma<u8>, Vec<u8>>> = mask_definitions.iter()
-        .filter_map(|def| generate_mask_bitmap(def, img_w, img_h, 1.0, unscaled_crop_offset))
-        .collect();
+async fn ensure_ai_denoise(state: &tauri::State<'_, AppState>, app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let loaded_image = state.original_image.lock().unwrap().clone().ok_or("No original image loaded")?;
+    let path_hash = hash_image_path(&loaded_image.path);
+    if state.denoised_image.lock().unwrap().as_ref().map_or(false, |d| d.path_hash == path_hash) {
+        return Ok(());
+    }
 
-    let all_adjustments = get_all_adjustments_from_json(&js_adjustments);
-    let final_image = process_and_get_dynamic_image(&context, &transformed_image, all_adjustments, &mask_bitmaps)?;
-    
-    let mut buf = Cursor::new(Vec::new());
-    final_image.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut buf, 92)).map_err(|e| e.to_string())?;
-    
-    Ok(Response::new(buf.into_inner()))
+    let denoiser = get_or_init_denoise_model(app_handle, &state.denoise_model, &state.ai_init_lock)
+        .await
+        .map_err(|e| e.to_string())?;
+    let denoised = tokio::task::spawn_blocking(move || run_denoise_model(&loaded_image.image, &denoiser))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    *state.denoised_image.lock().unwrap() = Some(DenoisedImage { path_hash, image: Arc::new(denoised) });
+    *state.cached_preview.lock().unwrap() = None;
+    *state.cached_full_resolution.lock().unwrap() = None;
+    Ok(())
 }
 
 #[tauri::command]
-async fn export_image(
-    original_path: String,
-    output_path: String,
-    js_adjustments: Value,
-    export_settings: ExportSettings,
+async fn generate_ai_denoise(state: tauri::State<'_, AppState>, app_handle: tauri::AppHandle) -> Result<(), String> {
+    ensure_ai_denoise(&state, &app_handle).await
+}
+
+#[tauri::command]
+async fn auto_straighten(
+    js_adjustments: serde_json::Value,
     state: tauri::State<'_, AppState>,
-    app_handle: tauri::AppHandle,
-) -> Result<(), String> {
-    if state.export_task_handle.lock().unwrap().is_some() {
-        return Err("An export is already in progress.".to_string());
-    }
+) -> Result<auto_straighten::AutoStraightenResult, String> {
+    let loaded_image = state.original_image.lock().unwrap().clone().ok_or("No original image loaded")?;
 
-    let context = get_or_init_gpu_context(&state)?;
-    let original_image_data = get_full_image_for_processing(&state)?;
-    let context = Arc::new(context);
+    tokio::task::spawn_blocking(move || {
+        let orientation_steps = js_adjustments["orientationSteps"].as_u64().unwrap_or(0) as u8;
+        let flip_horizontal = js_adjustments["flipHorizontal"].as_bool().unwrap_or(false);
+        let flip_vertical = js_adjustments["flipVertical"].as_bool().unwrap_or(false);
 
-    let task = tokio::spawn(async move {
-        let processing_result: Result<(), String> = (|| {
-            let base_image = composite_patches_on_image(&original_image_data, &js_adjustments)
-                .map_err(|e| format!("Failed to composite AI patches for export: {}", e))?;
+        let corrected = apply_lens_correction(&loaded_image.image, &js_adjustments, Some(&loaded_image.lens_info));
+        let oriented = apply_flip(apply_coarse_rotation(corrected, orientation_steps), flip_horizontal, flip_vertical);
 
-            let (transformed_image, unscaled_crop_offset) = 
-                apply_all_transformations(&base_image, &js_adjustments, 1.0);
-            let (img_w, img_h) = transformed_image.dimensions();
+        let (rotation, confidence) = auto_straighten::detect_straighten_angle(&oriented)
+            .ok_or("Could not find a horizon or dominant lines to straighten")?;
 
-            let mask_definitions: Vec<MaskDefinition> = js_adjustments.get("masks")
-                .and_then(|m| serde_json::from_value(m.clone()).ok())
-                .unwrap_or_else(Vec::new);
+        let (width, height) = oriented.dimensions();
+        let aspect_ratio = js_adjustments["aspectRatio"]
+            .as_f64()
+            .filter(|r| *r > 0.0)
+            .unwrap_or(width as f64 / height as f64);
+        let crop = auto_straighten::inscribed_crop(width, height, rotation, aspect_ratio);
 
-            let mask_bitmaps: Vec<ImageBuffer<Luma<u8>, Vec<u8>>> = mask_definitions.iter()
-                .filter_map(|def| generate_mask_bitmap(def, img_w, img_h, 1.0, unscaled_crop_offset))
-                .collect();
+        Ok(auto_straighten::AutoStraightenResult { rotation, confidence, crop })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-            let all_adjustments = get_all_adjustments_from_json(&js_adjustments);
-            let mut final_image = process_and_get_dynamic_image(&context, &transformed_image, all_adjustments, &mask_bitmaps)?;
+#[tauri::command]
+async fn suggest_crops(
+    js_adjustments: serde_json::Value,
+    aspect_ratios: Option<Vec<f64>>,
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<auto_crop::CropSuggestion>, String> {
+    let models = get_or_init_ai_models(&app_handle, &state.ai_state, &state.ai_init_lock)
+        .await
+        .map_err(|e| e.to_string())?;
+    let loaded_image = state.original_image.lock().unwrap().clone().ok_or("No original image loaded")?;
 
-            if let Some(resize_opts) = export_settings.resize {
-                let (current_w, current_h) = final_image.dimensions();
-                let should_resize = if resize_opts.dont_enlarge {
-                    match resize_opts.mode {
-                        ResizeMode::LongEdge => current_w.max(current_h) > resize_opts.value,
-                        ResizeMode::Width => current_w > resize_opts.value,
-                        ResizeMode::Height => current_h > resize_opts.value,
-                    }
-                } else { true };
-
-                if should_resize {
-                    final_image = match resize_opts.mode {
-                        ResizeMode::LongEdge => {
-                            let (w, h) = if current_w > current_h {
-                                (resize_opts.value, (resize_opts.value as f32 * (current_h as f32 / current_w as f32)).round() as u32)
-                            } else {
-                                ((resize_opts.value as f32 * (current_w as f32 / current_h as f32)).round() as u32, resize_opts.value)
-                            };
-                            final_image.thumbnail(w, h)
-                        },
-                        ResizeMode::Width => final_image.thumbnail(resize_opts.value, u32::MAX),
-                        ResizeMode::Height => final_image.thumbnail(u32::MAX, resize_opts.value),
-                    };
-                }
-            }
+    tokio::task::spawn_blocking(move || {
+        let ratios: Vec<(String, f64)> = match aspect_ratios {
+            Some(ratios) => ratios
+                .into_iter()
+                .filter(|r| *r > 0.0)
+                .map(|r| (format!("{:.2}", r), r))
+                .collect(),
+            None => auto_crop::DEFAULT_CROP_RATIOS.iter().map(|(label, r)| (label.to_string(), *r)).collect(),
+        };
+        let full_width = loaded_image.image.width();
+        crop_suggestions_for_image(&loaded_image.image, full_width, &js_adjustments, &loaded_image.lens_info, &models.u2netp, &ratios)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-            let output_path_obj = std::path::Path::new(&output_path);
-            let extension = output_path_obj.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-            
-            let mut image_bytes = Vec::new();
-            let mut cursor = Cursor::new(&mut image_bytes);
-
-            match extension.as_str() {
-                "jpg" | "jpeg" => {
-                    let rgb_image = final_image.to_rgb8();
-                    let encoder = JpegEncoder::new_with_quality(&mut cursor, export_settings.jpeg_quality);
-                    rgb_image.write_with_encoder(encoder).map_err(|e| e.to_string())?;
-                }
-                "png" => {
-                    final_image.write_to(&mut cursor, image::ImageFormat::Png).map_err(|e| e.to_string())?;
-                }
-                "tiff" => {
-                    final_image.write_to(&mut cursor, image::ImageFormat::Tiff).map_err(|e| e.to_string())?;
-                }
-                _ => return Err(format!("Unsupported file extension: {}", extension)),
+// `image` may be a smaller rendition of the base image; `full_width` is the base image's width, which
+// the returned crops are scaled to.
+fn crop_suggestions_for_image(
+    image: &DynamicImage,
+    full_width: u32,
+    js_adjustments: &Value,
+    lens_info: &LensInfo,
+    u2netp: &Session,
+    ratios: &[(String, f64)],
+) -> Result<Vec<auto_crop::CropSuggestion>, String> {
+    let mut uncropped_adjustments = js_adjustments.clone();
+    uncropped_adjustments["crop"] = serde_json::Value::Null;
+    let analysis_image = image.thumbnail(auto_crop::ANALYSIS_DIM, auto_crop::ANALYSIS_DIM);
+    let scale = analysis_image.width() as f32 / full_width as f32;
+    let (transformed, _, _) =
+        apply_all_transformations(&analysis_image, &uncropped_adjustments, scale, Some(lens_info));
+
+    let mask = run_u2netp_model(&transformed, u2netp).map_err(|e| e.to_string())?;
+    let rotation = js_adjustments["rotation"].as_f64().unwrap_or(0.0) as f32;
+    let to_full = 1.0 / scale as f64;
+
+    Ok(auto_crop::suggest_crops(&mask, rotation, ratios)
+        .into_iter()
+        .map(|mut suggestion| {
+            suggestion.crop = Crop {
+                x: suggestion.crop.x * to_full,
+                y: suggestion.crop.y * to_full,
+                width: suggestion.crop.width * to_full,
+                height: suggestion.crop.height * to_full,
             };
+            suggestion
+        })
+        .collect())
+}
 
-            write_image_with_metadata(
-                &mut image_bytes,
-                &original_path,
-                &extension,
-                export_settings.keep_metadata,
-                export_settings.strip_gps,
-            )?;
-
-            fs::write(&output_path, image_bytes).map_err(|e| e.to_string())?;
-
-            Ok(())
-        })();
+fn auto_crop_path(path: &str, aspect_ratio: f64, u2netp: &Session) -> Result<(), String> {
+    let lock = sidecar_lock(path);
+    let _guard = lock.lock().unwrap();
+    let sidecar_path = get_sidecar_path(path);
+    let mut metadata: ImageMetadata = fs::read_to_string(&sidecar_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    if !metadata.adjustments.is_object() {
+        metadata.adjustments = serde_json::json!({});
+    }
 
-        if let Err(e) = processing_result {
-            let _ = app_handle.emit("export-error", e);
-        } else {
-            let _ = app_handle.emit("export-complete", ());
-        }
+    // The fast development is plenty for the subject mask; crops are scaled to the full development.
+    let file_bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let (full_width, _) = image_loader::base_image_dimensions_from_bytes(&file_bytes, path).map_err(|e| e.to_string())?;
+    let image = load_base_image_from_bytes(&file_bytes, path, true).map_err(|e| e.to_string())?;
+    let lens_info = read_lens_info_from_path(path);
+    let ratios = vec![(String::new(), aspect_ratio)];
+    let Some(suggestion) =
+        crop_suggestions_for_image(&image, full_width, &metadata.adjustments, &lens_info, u2netp, &ratios)?.into_iter().next()
+    else {
+        return Err("No crop found for this image.".to_string());
+    };
 
-        *app_handle.state::<AppState>().export_task_handle.lock().unwrap() = None;
+    metadata.adjustments["aspectRatio"] = serde_json::json!(suggestion.aspect_ratio);
+    metadata.adjustments["crop"] = serde_json::json!({
+        "unit": "px",
+        "x": suggestion.crop.x,
+        "y": suggestion.crop.y,
+        "width": suggestion.crop.width,
+        "height": suggestion.crop.height,
     });
-
-    *state.export_task_handle.lock().unwrap() = Some(task);
-    Ok(())
+    let json_string = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
+    fs::write(&sidecar_path, json_string).map_err(|e| e.to_string())
 }
 
+// Batch version of the smart crop: applies the best-scoring crop of one aspect ratio to every image.
 #[tauri::command]
-async fn batch_export_images(
-    output_folder: String,
+async fn auto_crop_paths(
     paths: Vec<String>,
-    export_settings: ExportSettings,
-    output_format: String,
+    aspect_ratio: f64,
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    if aspect_ratio <= 0.0 {
+        return Err("Invalid aspect ratio.".to_string());
+    }
+    let paths: Vec<String> = paths.into_iter().filter(|path| !is_video_file(path)).collect();
+    let models = get_or_init_ai_models(&app_handle, &state.ai_state, &state.ai_init_lock)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let handle = app_handle.clone();
+    let cropped = tokio::task::spawn_blocking(move || {
+        let total = paths.len();
+        let mut cropped = Vec::with_capacity(total);
+        for (i, path) in paths.into_iter().enumerate() {
+            let _ = handle.emit("auto-crop-progress", serde_json::json!({ "current": i, "total": total }));
+            match auto_crop_path(&path, aspect_ratio, &models.u2netp) {
+                Ok(()) => cropped.push(path),
+                Err(e) => eprintln!("Failed to auto crop {}: {}", path, e),
+            }
+        }
+        let _ = handle.emit("auto-crop-progress", serde_json::json!({ "current": total, "total": total }));
+        cropped
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    thread::spawn(move || {
+        let _ = file_management::generate_thumbnails_progressive(cropped, app_handle);
+    });
+    Ok(())
+}
+
+fn get_lens_info_for_processing(state: &tauri::State<AppState>) -> LensInfo {
+    state.original_image.lock().unwrap()
+        .as_ref()
+        .map(|loaded_image| loaded_image.lens_info.clone())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+fn generate_fullscreen_preview(
+    js_adjustments: serde_json::Value,
+    state: tauri::State<AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Response, String> {
+    let final_image = render_full_resolution(&js_adjustments, &state, None)?;
+    Ok(Response::new(encode_preview(&final_image, 92, &app_handle)?))
+}
+
+#[tauri::command]
+fn generate_softproof_preview(
+    js_adjustments: serde_json::Value,
+    soft_proof_settings: SoftProofSettings,
+    state: tauri::State<AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Response, String> {
+    let final_image = render_full_resolution(&js_adjustments, &state, None)?;
+    let proofed_image = soft_proof_image(&final_image, &soft_proof_settings)?;
+    Ok(Response::new(encode_preview(&DynamicImage::ImageRgb8(proofed_image), 92, &app_handle)?))
+}
+
+#[tauri::command]
+fn render_region(
+    js_adjustments: serde_json::Value,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    state: tauri::State<AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Response, String> {
+    let region = TileRegion { x, y, width, height };
+    let final_image = render_full_resolution(&js_adjustments, &state, Some(region))?;
+    Ok(Response::new(encode_preview(&final_image, 92, &app_handle)?))
+}
+
+// Panning only moves the requested region, so the CPU work (denoise, patches, geometry and mask
+// rasterization) is done once and reused until one of its inputs changes.
+fn cached_full_resolution(
+    js_adjustments: &serde_json::Value,
+    state: &tauri::State<AppState>,
+) -> Result<Arc<CachedFullResolution>, String> {
+    let path = state.original_image.lock().unwrap().as_ref().map(|loaded| loaded.path.clone()).ok_or("No original image loaded")?;
+    let transform_hash = calculate_transform_hash(js_adjustments);
+    let mut hasher = DefaultHasher::new();
+    js_adjustments.get("masks").map(|m| m.to_string()).unwrap_or_default().hash(&mut hasher);
+    let masks_hash = hasher.finish();
+
+    if let Some(cached) = state.cached_full_resolution.lock().unwrap().as_ref() {
+        if cached.path == path && cached.transform_hash == transform_hash && cached.masks_hash == masks_hash {
+            return Ok(cached.clone());
+        }
+    }
+
+    let original_image = get_denoised_full_image(state, js_adjustments)?;
+    let lens_info = get_lens_info_for_processing(state);
+    let base_image = composite_patches_on_image(&original_image, js_adjustments)
+        .map_err(|e| format!("Failed to composite AI patches for fullscreen: {}", e))?;
+    
+    let (transformed_image, unscaled_crop_offset, optical_center) = 
+        apply_all_transformations(&base_image, js_adjustments, 1.0, Some(&lens_info));
+    let (img_w, img_h) = transformed_image.dimensions();
+    
+    let mask_definitions: Vec<MaskDefinition> = js_adjustments.get("masks")
+        .and_then(|m| serde_json::from_value(m.clone()).ok())
+        .unwrap_or_else(Vec::new);
+
+    let mask_bitmaps: Vec<ImageBuffer<Luma<u8>, Vec<u8>>> = mask_definitions.iter()
+        .filter_map(|def| generate_mask_bitmap(def, img_w, img_h, 1.0, unscaled_crop_offset, Some(&transformed_image)))
+        .collect();
+
+    let cached = Arc::new(CachedFullResolution {
+        path,
+        transform_hash,
+        masks_hash,
+        image: transformed_image,
+        mask_bitmaps,
+        optical_center,
+    });
+    *state.cached_full_resolution.lock().unwrap() = Some(cached.clone());
+    Ok(cached)
+}
+
+fn render_full_resolution(
+    js_adjustments: &serde_json::Value,
+    state: &tauri::State<AppState>,
+    region: Option<TileRegion>,
+) -> Result<DynamicImage, String> {
+    let context = get_or_init_gpu_context(state)?;
+    let cached = cached_full_resolution(js_adjustments, state)?;
+
+    let all_adjustments = get_all_adjustments_from_json(js_adjustments).with_optical_center(cached.optical_center);
+    let lut = get_lut_for_adjustments(js_adjustments);
+    match region {
+        Some(region) => process_region_and_get_dynamic_image(&context, &cached.image, all_adjustments, &cached.mask_bitmaps, lut.as_deref(), region),
+        None => process_and_get_dynamic_image(&context, &cached.image, all_adjustments, &cached.mask_bitmaps, lut.as_deref()),
+    }
+}
+
+// Shared by single and batch export: renders the composited base image with its adjustments and
+// writes the encoded result, with metadata, to `output_path`.
+#[allow(clippy::too_many_arguments)]
+fn export_rendered_image(
+    context: &GpuContext,
+    gpu_lock: &Mutex<()>,
+    base_image: &DynamicImage,
+    js_adjustments: &Value,
+    lens_info: &LensInfo,
+    source_path: &str,
+    output_path: &Path,
+    output_format: &str,
+    export_settings: &ExportSettings,
+    (sequence, total): (usize, usize),
+    upscaler: Option<&ExportUpscaler>,
+    background_remover: Option<&Session>,
+) -> Result<(), String> {
+    let (transformed_image, unscaled_crop_offset, optical_center) =
+        apply_all_transformations(base_image, js_adjustments, 1.0, Some(lens_info));
+    let (img_w, img_h) = transformed_image.dimensions();
+
+    let mask_definitions: Vec<MaskDefinition> = js_adjustments.get("masks")
+        .and_then(|m| serde_json::from_value(m.clone()).ok())
+        .unwrap_or_else(Vec::new);
+
+    let mask_bitmaps: Vec<ImageBuffer<Luma<u8>, Vec<u8>>> = mask_definitions.iter()
+        .filter_map(|def| generate_mask_bitmap(def, img_w, img_h, 1.0, unscaled_crop_offset, Some(&transformed_image)))
+        .collect();
+
+    let all_adjustments = get_all_adjustments_from_json(js_adjustments).with_optical_center(optical_center);
+    let lut = get_lut_for_adjustments(js_adjustments);
+    let color_space = export_color_space(output_format, export_settings, source_path);
+    let mut final_image = {
+        let _gpu_guard = gpu_lock.lock().unwrap();
+        process_for_export(context, &transformed_image, all_adjustments, &mask_bitmaps, lut.as_deref(), color_space)?
+    };
+
+    if let (Some(scale), Some(upscaler)) = (export_settings.ai_upscale, upscaler) {
+        final_image = upscaler.upscale(&final_image, scale)?;
+    }
+
+    if let Some(resize_opts) = &export_settings.resize {
+        let (current_w, current_h) = final_image.dimensions();
+        let should_resize = if resize_opts.dont_enlarge {
+            match resize_opts.mode {
+                ResizeMode::LongEdge => current_w.max(current_h) > resize_opts.value,
+                ResizeMode::Width => current_w > resize_opts.value,
+                ResizeMode::Height => current_h > resize_opts.value,
+            }
+        } else { true };
+
+        if should_resize {
+            final_image = match resize_opts.mode {
+                ResizeMode::LongEdge => {
+                    let (w, h) = if current_w > current_h {
+                        (resize_opts.value, (resize_opts.value as f32 * (current_h as f32 / current_w as f32)).round() as u32)
+                    } else {
+                        ((resize_opts.value as f32 * (current_w as f32 / current_h as f32)).round() as u32, resize_opts.value)
+                    };
+                    final_image.thumbnail(w, h)
+                },
+                ResizeMode::Width => final_image.thumbnail(resize_opts.value, u32::MAX),
+                ResizeMode::Height => final_image.thumbnail(u32::MAX, resize_opts.value),
+            };
+        }
+    }
+
+    if let Some(sharpening_settings) = &export_settings.output_sharpening {
+        final_image = apply_output_sharpening(&final_image, sharpening_settings);
+    }
+
+    if let Some(background_remover) = background_remover {
+        final_image = remove_background(&final_image, background_remover).map_err(|e| e.to_string())?;
+    }
+
+    if let Some(watermark_settings) = &export_settings.watermark {
+        let original_path = Path::new(source_path);
+        let file_date = get_file_date(original_path);
+        apply_watermark(&mut final_image, watermark_settings, original_path, sequence, total, &file_date)?;
+    }
+
+    let mut image_bytes = encode_image_to_bytes(final_image, output_format, export_settings, source_path)?;
+
+    write_image_with_metadata(
+        &mut image_bytes,
+        source_path,
+        output_format,
+        export_settings.keep_metadata,
+        export_settings.strip_gps,
+    )?;
+
+    fs::write(output_path, image_bytes).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn export_image(
+    original_path: String,
+    output_path: String,
+    js_adjustments: Value,
+    export_settings: ExportSettings,
     state: tauri::State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
@@ -619,148 +1369,264 @@ async fn batch_export_images(
         return Err("An export is already in progress.".to_string());
     }
 
+    let output_extension = Path::new(&output_path).extension().and_then(|s| s.to_str()).unwrap_or("").to_string();
+    check_transparent_export(&export_settings, &output_extension)?;
+
     let context = get_or_init_gpu_context(&state)?;
+    if ai_denoise_strength(&js_adjustments) > 0.0 {
+        ensure_ai_denoise(&state, &app_handle).await?;
+    }
+    let original_image_data = get_denoised_full_image(&state, &js_adjustments)?;
+    let lens_info = get_lens_info_for_processing(&state);
+    let source_fingerprint = catalog::file_fingerprint(&original_path);
     let context = Arc::new(context);
+    let upscaler = init_export_upscaler(&export_settings, &state, &app_handle).await?;
+    let background_models = if export_settings.remove_background.unwrap_or(false) {
+        Some(
+            get_or_init_ai_models(&app_handle, &state.ai_state, &state.ai_init_lock)
+                .await
+                .map_err(|e| e.to_string())?,
+        )
+    } else {
+        None
+    };
 
     let task = tokio::spawn(async move {
-        let output_folder_path = std::path::Path::new(&output_folder);
-        let total_paths = paths.len();
+        let processing_result: Result<(), String> = (|| {
+            let base_image = composite_patches_on_image(&original_image_data, &js_adjustments)
+                .map_err(|e| format!("Failed to composite AI patches for export: {}", e))?;
+            let extension = output_extension.to_lowercase();
+            export_rendered_image(
+                &context,
+                &Mutex::new(()),
+                &base_image,
+                &js_adjustments,
+                &lens_info,
+                &original_path,
+                Path::new(&output_path),
+                &extension,
+                &export_settings,
+                (1, 1),
+                upscaler.as_ref(),
+                background_models.as_ref().map(|models| &models.u2netp),
+            )
+        })();
 
-        for (i, image_path_str) in paths.iter().enumerate() {
-            if app_handle.state::<AppState>().export_task_handle.lock().unwrap().is_none() {
-                println!("Export cancelled during batch processing.");
-                let _ = app_handle.emit("export-cancelled", ());
-                return;
-            }
+        if let Err(e) = processing_result {
+            let _ = app_handle.emit("export-error", e);
+        } else {
+            let exported = ExportedFile {
+                source_path: original_path.clone(),
+                output_path: PathBuf::from(&output_path),
+                source_fingerprint,
+            };
+            finish_export(&[exported], &output_extension, &export_settings, &app_handle).await;
+            let _ = app_handle.emit("export-complete", ());
+        }
+
+        *app_handle.state::<AppState>().export_task_handle.lock().unwrap() = None;
+    });
 
-            let _ = app_handle.emit("batch-export-progress", serde_json::json!({ "current": i, "total": total_paths, "path": image_path_str }));
+    *state.export_task_handle.lock().unwrap() = Some(task);
+    Ok(())
+}
 
-            let processing_result: Result<(), String> = (|| {
-                let sidecar_path = get_sidecar_path(image_path_str);
-                let metadata: ImageMetadata = if sidecar_path.exists() {
-                    let file_content = fs::read_to_string(sidecar_path).map_err(|e| e.to_string())?;
-                    serde_json::from_str(&file_content).unwrap_or_default()
-                } else {
-                    ImageMetadata::default()
-                };
-                let js_adjustments = metadata.adjustments;
+fn load_export_adjustments(
+    image_path_str: &str,
+    version_ids: Option<&HashMap<String, String>>,
+) -> Result<Value, String> {
+    let sidecar_path = get_sidecar_path(image_path_str);
+    let metadata: ImageMetadata = if sidecar_path.exists() {
+        let file_content = fs::read_to_string(sidecar_path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&file_content).unwrap_or_default()
+    } else {
+        ImageMetadata::default()
+    };
+    match version_ids.and_then(|ids| ids.get(image_path_str)) {
+        Some(version_id) => edit_versions::adjustments_for_version(&metadata, version_id)
+            .ok_or_else(|| format!("Edit version '{}' not found.", version_id)),
+        None => Ok(metadata.adjustments),
+    }
+}
 
-                let base_image = load_and_composite(image_path_str, &js_adjustments, false)
-                    .map_err(|e| e.to_string())?;
-                
-                let (transformed_image, unscaled_crop_offset) = 
-                    apply_all_transformations(&base_image, &js_adjustments, 1.0);
-                let (img_w, img_h) = transformed_image.dimensions();
-
-                let mask_definitions: Vec<MaskDefinition> = js_adjustments.get("masks")
-                    .and_then(|m| serde_json::from_value(m.clone()).ok())
-                    .unwrap_or_else(Vec::new);
-
-                let mask_bitmaps: Vec<ImageBuffer<Luma<u8>, Vec<u8>>> = mask_definitions.iter()
-                    .filter_map(|def| generate_mask_bitmap(def, img_w, img_h, 1.0, unscaled_crop_offset))
-                    .collect();
-
-                let all_adjustments = get_all_adjustments_from_json(&js_adjustments);
-                let mut final_image = process_and_get_dynamic_image(&context, &transformed_image, all_adjustments, &mask_bitmaps)?;
-
-                if let Some(resize_opts) = &export_settings.resize {
-                    let (current_w, current_h) = final_image.dimensions();
-                    let should_resize = if resize_opts.dont_enlarge {
-                        match resize_opts.mode {
-                            ResizeMode::LongEdge => current_w.max(current_h) > resize_opts.value,
-                            ResizeMode::Width => current_w > resize_opts.value,
-                            ResizeMode::Height => current_h > resize_opts.value,
-                        }
-                    } else { true };
-
-                    if should_resize {
-                        final_image = match resize_opts.mode {
-                            ResizeMode::LongEdge => {
-                                let (w, h) = if current_w > current_h {
-                                    (resize_opts.value, (resize_opts.value as f32 * (current_h as f32 / current_w as f32)).round() as u32)
-                                } else {
-                                    ((resize_opts.value as f32 * (current_w as f32 / current_h as f32)).round() as u32, resize_opts.value)
-                                };
-                                final_image.thumbnail(w, h)
-                            },
-                            ResizeMode::Width => final_image.thumbnail(resize_opts.value, u32::MAX),
-                            ResizeMode::Height => final_image.thumbnail(u32::MAX, resize_opts.value),
-                        };
-                    }
-                }
+#[allow(clippy::too_many_arguments)]
+fn export_single_image(
+    context: &GpuContext,
+    gpu_lock: &Mutex<()>,
+    image_path_str: &str,
+    index: usize,
+    total_paths: usize,
+    output_folder_path: &Path,
+    output_format: &str,
+    export_settings: &ExportSettings,
+    version_ids: Option<&HashMap<String, String>>,
+    upscaler: Option<&ExportUpscaler>,
+    denoiser: Option<&Session>,
+    background_remover: Option<&Session>,
+) -> Result<PathBuf, String> {
+    let js_adjustments = load_export_adjustments(image_path_str, version_ids)?;
+
+    // Denoise before compositing patches, the same order the editor's export uses.
+    let file_bytes = fs::read(image_path_str).map_err(|e| e.to_string())?;
+    let mut base_image = load_base_image_from_bytes(&file_bytes, image_path_str, false)
+        .map_err(|e| e.to_string())?;
+    let denoise_strength = ai_denoise_strength(&js_adjustments);
+    if let (true, Some(denoiser)) = (denoise_strength > 0.0, denoiser) {
+        let denoised = run_denoise_model(&base_image, denoiser).map_err(|e| e.to_string())?;
+        base_image = blend_denoised(&base_image, &denoised, denoise_strength);
+    }
+    let base_image = composite_patches_on_image(&base_image, &js_adjustments)
+        .map_err(|e| format!("Failed to composite AI patches for export: {}", e))?;
+    let lens_info = read_lens_info_from_path(image_path_str);
+
+    let original_path = Path::new(image_path_str);
+    let file_date = get_file_date(original_path);
+    let filename_template = export_settings.filename_template.as_deref().unwrap_or("{original_filename}_edited");
+    let new_stem = crate::file_management::generate_filename_from_template(filename_template, original_path, index + 1, total_paths, &file_date);
+    let output_path = output_folder_path.join(format!("{}.{}", new_stem, output_format));
+
+    export_rendered_image(
+        context,
+        gpu_lock,
+        &base_image,
+        &js_adjustments,
+        &lens_info,
+        image_path_str,
+        &output_path,
+        output_format,
+        export_settings,
+        (index + 1, total_paths),
+        upscaler,
+        background_remover,
+    )?;
+
+    Ok(output_path)
+}
 
-                let original_path = std::path::Path::new(image_path_str);
-                
-                let file_date: DateTime<Utc> = Metadata::new_from_path(original_path)
-                    .ok()
-                    .and_then(|metadata| {
-                        metadata
-                            .get_tag(&ExifTag::DateTimeOriginal("".to_string()))
-                            .next()
-                            .and_then(|tag| {
-                                if let &ExifTag::DateTimeOriginal(ref dt_str) = tag {
-                                    chrono::NaiveDateTime::parse_from_str(dt_str, "%Y:%m:%d %H:%M:%S")
-                                        .ok()
-                                        .map(|dt| DateTime::from_naive_utc_and_offset(dt, Utc))
-                                } else {
-                                    None
-                                }
-                            })
-                    })
-                    .unwrap_or_else(|| {
-                        fs::metadata(original_path)
-                            .ok()
-                            .and_then(|m| m.created().ok())
-                            .map(DateTime::<Utc>::from)
-                            .unwrap_or_else(Utc::now)
-                    });
+#[tauri::command]
+async fn batch_export_images(
+    output_folder: String,
+    paths: Vec<String>,
+    export_settings: ExportSettings,
+    output_format: String,
+    version_ids: Option<HashMap<String, String>>,
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    if state.export_task_handle.lock().unwrap().is_some() {
+        return Err("An export is already in progress.".to_string());
+    }
+    check_transparent_export(&export_settings, &output_format)?;
 
-                let filename_template = export_settings.filename_template.as_deref().unwrap_or("{original_filename}_edited");
-                let new_stem = crate::file_management::generate_filename_from_template(filename_template, original_path, i + 1, total_paths, &file_date);
-                let new_filename = format!("{}.{}", new_stem, output_format);
-                let output_path = output_folder_path.join(new_filename);
+    // Videos share the library with images but can't be rendered, so they are left out.
+    let paths: Vec<String> = paths.into_iter().filter(|path| !is_video_file(path)).collect();
+    if paths.is_empty() {
+        return Err("The selection contains no images to export.".to_string());
+    }
 
-                let mut image_bytes = Vec::new();
-                let mut cursor = Cursor::new(&mut image_bytes);
+    let context = get_or_init_gpu_context(&state)?;
+    let context = Arc::new(context);
+    let upscaler = init_export_upscaler(&export_settings, &state, &app_handle).await?;
+    let needs_denoise = paths.iter().any(|path| {
+        load_export_adjustments(path, version_ids.as_ref()).map_or(false, |adj| ai_denoise_strength(&adj) > 0.0)
+    });
+    let denoiser = if needs_denoise {
+        Some(
+            get_or_init_denoise_model(&app_handle, &state.denoise_model, &state.ai_init_lock)
+                .await
+                .map_err(|e| e.to_string())?,
+        )
+    } else {
+        None
+    };
+    let background_models = if export_settings.remove_background.unwrap_or(false) {
+        Some(
+            get_or_init_ai_models(&app_handle, &state.ai_state, &state.ai_init_lock)
+                .await
+                .map_err(|e| e.to_string())?,
+        )
+    } else {
+        None
+    };
 
-                match output_format.as_str() {
-                    "jpg" | "jpeg" => {
-                        let rgb_image = final_image.to_rgb8();
-                        let encoder = JpegEncoder::new_with_quality(&mut cursor, export_settings.jpeg_quality);
-                        rgb_image.write_with_encoder(encoder).map_err(|e| e.to_string())?;
-                    }
-                    "png" => {
-                        final_image.write_to(&mut cursor, image::ImageFormat::Png).map_err(|e| e.to_string())?;
-                    }
-                    "tiff" => {
-                        final_image.write_to(&mut cursor, image::ImageFormat::Tiff).map_err(|e| e.to_string())?;
-                    }
-                    _ => return Err(format!("Unsupported file format: {}", output_format)),
-                };
+    let task = tokio::spawn(async move {
+        let worker_count = export_thread_count(&app_handle);
+        let worker_handle = app_handle.clone();
+        let export_result = tokio::task::spawn_blocking(move || {
+            let app_handle = worker_handle;
+            let output_folder_path = std::path::Path::new(&output_folder);
+            let total_paths = paths.len();
+            let completed = AtomicUsize::new(0);
+            let exported = Mutex::new(Vec::new());
+            let gpu_lock = Mutex::new(());
+            let is_cancelled = || app_handle.state::<AppState>().export_task_handle.lock().unwrap().is_none();
+
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(worker_count)
+                .build()
+                .map_err(|e| format!("Failed to create export thread pool: {}", e))?;
+            println!("Exporting {} images with {} workers.", total_paths, worker_count);
 
-                write_image_with_metadata(
-                    &mut image_bytes,
-                    image_path_str,
-                    &output_format,
-                    export_settings.keep_metadata,
-                    export_settings.strip_gps,
-                )?;
+            pool.install(|| {
+                paths.par_iter().enumerate().try_for_each(|(i, image_path_str)| {
+                    if is_cancelled() {
+                        return Ok(());
+                    }
 
-                fs::write(&output_path, image_bytes).map_err(|e| e.to_string())?;
+                    let _ = app_handle.emit("batch-export-progress", serde_json::json!({ "current": completed.load(Ordering::SeqCst), "total": total_paths, "path": image_path_str }));
+
+                    let source_fingerprint = catalog::file_fingerprint(image_path_str);
+                    let output_path = export_single_image(
+                        &context,
+                        &gpu_lock,
+                        image_path_str,
+                        i,
+                        total_paths,
+                        output_folder_path,
+                        &output_format,
+                        &export_settings,
+                        version_ids.as_ref(),
+                        upscaler.as_ref(),
+                        denoiser.as_deref(),
+                        background_models.as_ref().map(|models| &models.u2netp),
+                    )
+                    .map_err(|e| format!("Failed to export {}: {}", image_path_str, e))?;
+                    exported.lock().unwrap().push((
+                        i,
+                        ExportedFile { source_path: image_path_str.clone(), output_path, source_fingerprint },
+                    ));
+
+                    completed.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                })
+            })?;
+
+            if is_cancelled() {
+                println!("Export cancelled during batch processing.");
+                let _ = app_handle.emit("export-cancelled", ());
+                return Ok(None);
+            }
+            let _ = app_handle.emit("batch-export-progress", serde_json::json!({ "current": total_paths, "total": total_paths, "path": "" }));
 
-                Ok(())
-            })();
+            let mut exported = exported.into_inner().unwrap();
+            exported.sort_by_key(|(i, _)| *i);
+            let exported: Vec<ExportedFile> = exported.into_iter().map(|(_, file)| file).collect();
+            Ok(Some((exported, output_format, export_settings)))
+        })
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
 
-            if let Err(e) = processing_result {
-                eprintln!("Failed to export {}: {}", image_path_str, e);
+        match export_result {
+            Ok(Some((exported, output_format, export_settings))) => {
+                finish_export(&exported, &output_format, &export_settings, &app_handle).await;
+                let _ = app_handle.emit("export-complete", ());
+            }
+            Ok(None) => return,
+            Err(e) => {
+                eprintln!("{}", e);
                 let _ = app_handle.emit("export-error", e);
-                *app_handle.state::<AppState>().export_task_handle.lock().unwrap() = None;
-                return;
             }
         }
-
-        let _ = app_handle.emit("batch-export-progress", serde_json::json!({ "current": total_paths, "total": total_paths, "path": "" }));
-        let _ = app_handle.emit("export-complete", ());
         *app_handle.state::<AppState>().export_task_handle.lock().unwrap() = None;
     });
 
@@ -768,6 +1634,27 @@ async fn batch_export_images(
     Ok(())
 }
 
+#[tauri::command]
+async fn upscale_image(
+    js_adjustments: Value,
+    scale: u32,
+    output_path: String,
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let upscaler = get_or_init_upscale_model(&app_handle, &state.upscale_model, &state.ai_init_lock)
+        .await
+        .map_err(|e| e.to_string())?;
+    let final_image = render_full_resolution(&js_adjustments, &state, None)?;
+    let upscaled = run_upscale_model(&final_image, &upscaler, scale).map_err(|e| e.to_string())?;
+    upscaled.save(&output_path).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn get_file_date(original_path: &Path) -> DateTime<Utc> {
+    capture_time::file_date(original_path)
+}
+
 #[tauri::command]
 fn cancel_export(state: tauri::State<AppState>) -> Result<(), String> {
     if let Some(handle) = state.export_task_handle.lock().unwrap().take() {
@@ -779,6 +1666,155 @@ fn cancel_export(state: tauri::State<AppState>) -> Result<(), String> {
     Ok(())
 }
 
+fn jxl_distance_from_quality(quality: u8) -> f32 {
+    let q = quality.clamp(1, 100) as f32;
+    if q >= 30.0 {
+        0.1 + (100.0 - q) * 0.09
+    } else {
+        6.4 + 2.5f32.powf((30.0 - q) / 5.0) / 6.25
+    }
+}
+
+fn jxl_speed_from_setting(speed: u8) -> EncoderSpeed {
+    match speed.clamp(1, 10) {
+        1 => EncoderSpeed::Tortoise,
+        2 => EncoderSpeed::Kitten,
+        3 => EncoderSpeed::Squirrel,
+        4 => EncoderSpeed::Wombat,
+        5 => EncoderSpeed::Hare,
+        6 => EncoderSpeed::Cheetah,
+        7 => EncoderSpeed::Falcon,
+        8 => EncoderSpeed::Thunder,
+        _ => EncoderSpeed::Lightning,
+    }
+}
+
+fn write_tiff_with_icc<W: std::io::Write + std::io::Seek>(
+    image: &DynamicImage,
+    icc_profile: &[u8],
+    writer: &mut W,
+) -> Result<(), tiff::TiffError> {
+    let (width, height) = image.dimensions();
+    let mut encoder = TiffEncoder::new(writer)?;
+
+    match image {
+        DynamicImage::ImageRgb8(buffer) => {
+            let mut tiff_image = encoder.new_image::<colortype::RGB8>(width, height)?;
+            tiff_image.encoder().write_tag(TiffTag::Unknown(34675), icc_profile)?;
+            tiff_image.write_data(buffer.as_raw())
+        }
+        DynamicImage::ImageRgb16(buffer) => {
+            let mut tiff_image = encoder.new_image::<colortype::RGB16>(width, height)?;
+            tiff_image.encoder().write_tag(TiffTag::Unknown(34675), icc_profile)?;
+            tiff_image.write_data(buffer.as_raw())
+        }
+        DynamicImage::ImageRgba16(buffer) => {
+            let mut tiff_image = encoder.new_image::<colortype::RGBA16>(width, height)?;
+            tiff_image.encoder().write_tag(TiffTag::Unknown(34675), icc_profile)?;
+            tiff_image.write_data(buffer.as_raw())
+        }
+        _ => {
+            let buffer = image.to_rgba8();
+            let mut tiff_image = encoder.new_image::<colortype::RGBA8>(width, height)?;
+            tiff_image.encoder().write_tag(TiffTag::Unknown(34675), icc_profile)?;
+            tiff_image.write_data(buffer.as_raw())
+        }
+    }
+}
+
+fn export_color_space(output_format: &str, export_settings: &ExportSettings, source_path: &str) -> OutputColorSpace {
+    let embeds_profile = matches!(output_format.to_lowercase().as_str(), "jpg" | "jpeg" | "png" | "tiff");
+    // Without an explicit choice, exports keep the standard profile the source was tagged with.
+    export_settings.output_color_space.unwrap_or_else(|| {
+        image_loader::source_color_space(source_path)
+            .filter(|_| embeds_profile)
+            .unwrap_or_default()
+    })
+}
+
+// Wider color spaces are rendered in float, so they get the colors sRGB would clip.
+fn process_for_export(
+    context: &GpuContext,
+    image: &DynamicImage,
+    all_adjustments: AllAdjustments,
+    mask_bitmaps: &[ImageBuffer<Luma<u8>, Vec<u8>>],
+    lut: Option<&Lut>,
+    color_space: OutputColorSpace,
+) -> Result<DynamicImage, String> {
+    if color_space == OutputColorSpace::Srgb {
+        process_and_get_dynamic_image(context, image, all_adjustments, mask_bitmaps, lut)
+    } else {
+        process_wide_gamut_image(context, image, all_adjustments, mask_bitmaps, lut)
+    }
+}
+
+fn encode_image_to_bytes(
+    image: DynamicImage,
+    output_format: &str,
+    export_settings: &ExportSettings,
+    source_path: &str,
+) -> Result<Vec<u8>, String> {
+    let quality = export_settings.quality.unwrap_or(export_settings.jpeg_quality).clamp(1, 100);
+    let output_format = output_format.to_lowercase();
+    let embeds_profile = matches!(output_format.as_str(), "jpg" | "jpeg" | "png" | "tiff");
+    let color_space = export_color_space(&output_format, export_settings, source_path);
+
+    if color_space != OutputColorSpace::Srgb && !embeds_profile {
+        return Err(format!("The {} output color space can only be embedded in JPEG, PNG or TIFF exports.", color_space.name()));
+    }
+
+    let converted_image = convert_to_output_color_space(image, color_space);
+    let image = &converted_image;
+    let icc_profile = build_icc_profile(color_space);
+
+    let mut image_bytes = Vec::new();
+    let mut cursor = Cursor::new(&mut image_bytes);
+
+    match output_format.as_str() {
+        "jpg" | "jpeg" => {
+            let rgb_image = image.to_rgb8();
+            let mut encoder = JpegEncoder::new_with_quality(&mut cursor, export_settings.jpeg_quality);
+            encoder.set_icc_profile(icc_profile).map_err(|e| e.to_string())?;
+            rgb_image.write_with_encoder(encoder).map_err(|e| e.to_string())?;
+        }
+        "png" => {
+            let mut encoder = PngEncoder::new(&mut cursor);
+            encoder.set_icc_profile(icc_profile).map_err(|e| e.to_string())?;
+            image.write_with_encoder(encoder).map_err(|e| e.to_string())?;
+        }
+        "tiff" => {
+            write_tiff_with_icc(image, &icc_profile, &mut cursor).map_err(|e| e.to_string())?;
+        }
+        "avif" => {
+            let rgba_image = image.to_rgba8();
+            let speed = export_settings.speed.unwrap_or(4).clamp(1, 10);
+            let encoder = AvifEncoder::new_with_speed_quality(&mut cursor, speed, quality);
+            rgba_image.write_with_encoder(encoder).map_err(|e| e.to_string())?;
+        }
+        "webp" => {
+            let rgba_image = image.to_rgba8();
+            let (width, height) = rgba_image.dimensions();
+            let encoded = webp::Encoder::from_rgba(rgba_image.as_raw(), width, height).encode(quality as f32);
+            return Ok(encoded.to_vec());
+        }
+        "jxl" => {
+            let rgb_image = image.to_rgb8();
+            let (width, height) = rgb_image.dimensions();
+            let mut encoder = jpegxl_rs::encoder_builder()
+                .quality(jxl_distance_from_quality(quality))
+                .speed(jxl_speed_from_setting(export_settings.speed.unwrap_or(3)))
+                .build()
+                .map_err(|e| e.to_string())?;
+            let result: EncoderResult<u8> = encoder.encode(rgb_image.as_raw(), width, height)
+                .map_err(|e| e.to_string())?;
+            return Ok(result.data);
+        }
+        _ => return Err(format!("Unsupported file format: {}", output_format)),
+    };
+
+    Ok(image_bytes)
+}
+
 fn write_image_with_metadata(
     image_bytes: &mut Vec<u8>,
     original_path_str: &str,
@@ -786,7 +1822,7 @@ fn write_image_with_metadata(
     keep_metadata: bool,
     strip_gps: bool,
 ) -> Result<(), String> {
-    if !keep_metadata || output_format.to_lowercase() == "tiff" { // FIXME: temporary solution until I find a way to write metadata to TIFF
+    if !keep_metadata {
         return Ok(());
     }
 
@@ -794,6 +1830,9 @@ fn write_image_with_metadata(
         "jpg" | "jpeg" => FileExtension::JPEG,
         "png" => FileExtension::PNG { as_zTXt_chunk: true },
         "tiff" => FileExtension::TIFF,
+        "webp" => FileExtension::WEBP,
+        "jxl" => FileExtension::JXL,
+        "avif" => FileExtension::HEIF,
         _ => return Ok(()),
     };
 
@@ -803,6 +1842,23 @@ fn write_image_with_metadata(
         return Ok(());
     }
 
+    if matches!(file_type, FileExtension::TIFF) {
+        if let Err(e) = tiff_metadata::write_tiff_metadata(image_bytes, original_path_str, strip_gps) {
+            eprintln!("Failed to write TIFF metadata for {}: {}", original_path_str, e);
+        }
+        return Ok(());
+    }
+
+    if matches!(file_type, FileExtension::HEIF) {
+        if let Err(e) = avif_metadata::write_avif_metadata(image_bytes, original_path_str, strip_gps) {
+            eprintln!("Failed to write AVIF metadata for {}: {}", original_path_str, e);
+        }
+        return Ok(());
+    }
+
+    let sidecar = iptc_metadata::read_metadata(original_path_str);
+    let iptc = sidecar.iptc.clone().unwrap_or_default();
+    let is_jpeg = matches!(file_type, FileExtension::JPEG);
     if let Ok(mut metadata) = Metadata::new_from_path(original_path) {
         if strip_gps {
             let dummy_rational = uR64 { nominator: 0, denominator: 1 };
@@ -845,6 +1901,38 @@ fn write_image_with_metadata(
 
         metadata.set_tag(ExifTag::Orientation(vec![1u16]));
 
+        if let Some(gps) = geotagging::read_sidecar_gps(original_path_str).filter(|_| !strip_gps) {
+            let rationals = |value: f64| {
+                geotagging::to_dms_rationals(value)
+                    .iter()
+                    .map(|&(nominator, denominator)| uR64 { nominator, denominator })
+                    .collect::<Vec<_>>()
+            };
+            metadata.set_tag(ExifTag::GPSVersionID(vec![2, 3, 0, 0]));
+            metadata.set_tag(ExifTag::GPSLatitudeRef(if gps.latitude < 0.0 { "S" } else { "N" }.to_string()));
+            metadata.set_tag(ExifTag::GPSLatitude(rationals(gps.latitude)));
+            metadata.set_tag(ExifTag::GPSLongitudeRef(if gps.longitude < 0.0 { "W" } else { "E" }.to_string()));
+            metadata.set_tag(ExifTag::GPSLongitude(rationals(gps.longitude)));
+        }
+
+        if capture_time::capture_time_shift(original_path_str) != 0 {
+            if let Some(shifted) = capture_time::shifted_capture_time(original_path) {
+                let value = shifted.format(capture_time::EXIF_DATE_FORMAT).to_string();
+                metadata.set_tag(ExifTag::DateTimeOriginal(value.clone()));
+                metadata.set_tag(ExifTag::CreateDate(value));
+            }
+        }
+
+        if let Some(caption) = &iptc.caption {
+            metadata.set_tag(ExifTag::ImageDescription(caption.clone()));
+        }
+        if let Some(creator) = &iptc.creator {
+            metadata.set_tag(ExifTag::Artist(creator.clone()));
+        }
+        if let Some(copyright) = &iptc.copyright {
+            metadata.set_tag(ExifTag::Copyright(copyright.clone()));
+        }
+
         if metadata.write_to_vec(image_bytes, file_type).is_err() {
             eprintln!("Failed to write metadata to image vector for {}", original_path_str);
         }
@@ -852,9 +1940,39 @@ fn write_image_with_metadata(
         eprintln!("Failed to read metadata from original file: {}", original_path_str);
     }
 
+    if is_jpeg {
+        let keywords = iptc_metadata::export_keywords(&sidecar);
+        if let Some(xmp) = iptc_metadata::xmp_packet(&sidecar) {
+            if let Err(e) = iptc_metadata::embed_jpeg_xmp(image_bytes, &xmp) {
+                eprintln!("Failed to embed XMP metadata for {}: {}", original_path_str, e);
+            }
+        }
+        if let Some(record) = iptc_metadata::iim_record(&iptc, &keywords) {
+            if let Err(e) = iptc_metadata::embed_jpeg_iptc(image_bytes, &record) {
+                eprintln!("Failed to embed IPTC metadata for {}: {}", original_path_str, e);
+            }
+        }
+    }
+
     Ok(())
 }
 
+#[tauri::command]
+fn generate_focus_peaking(
+    color: Option<String>,
+    sensitivity: Option<f32>,
+    state: tauri::State<AppState>,
+) -> Result<String, String> {
+    let preview = state.cached_preview.lock().unwrap().as_ref().map(|cached| cached.image.clone())
+        .ok_or("No preview available")?;
+    let png = image_processing::generate_focus_peaking_overlay(
+        &preview,
+        color.as_deref().unwrap_or_default(),
+        sensitivity.unwrap_or(0.5),
+    )?;
+    Ok(format!("data:image/png;base64,{}", general_purpose::STANDARD.encode(png)))
+}
+
 #[tauri::command]
 fn generate_mask_overlay(
     mask_def: MaskDefinition,
@@ -862,11 +1980,13 @@ fn generate_mask_overlay(
     height: u32,
     scale: f32,
     crop_offset: (f32, f32),
+    state: tauri::State<AppState>,
 ) -> Result<String, String> {
 
     let scaled_crop_offset = (crop_offset.0 * scale, crop_offset.1 * scale);
+    let preview = state.cached_preview.lock().unwrap().as_ref().map(|cached| cached.image.clone());
 
-    if let Some(gray_mask) = generate_mask_bitmap(&mask_def, width, height, scale, scaled_crop_offset) {
+    if let Some(gray_mask) = generate_mask_bitmap(&mask_def, width, height, scale, scaled_crop_offset, preview.as_ref()) {
         let mut rgba_mask = RgbaImage::new(width, height);
         for (x, y, pixel) in gray_mask.enumerate_pixels() {
             let intensity = pixel[0];
@@ -938,11 +2058,114 @@ async fn generate_ai_sky_mask(
     })
 }
 
+#[tauri::command]
+async fn generate_ai_face_masks(
+    rotation: f32,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    orientation_steps: u8,
+    refine_skin: Option<bool>,
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<AiFaceMaskParameters>, String> {
+    let face_detector = get_or_init_face_detector(&app_handle, &state.face_detector, &state.ai_init_lock)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let full_image = get_full_image_for_processing(&state)?;
+    let faces = run_face_detector(&full_image, &face_detector).map_err(|e| e.to_string())?;
+    if faces.is_empty() {
+        return Err("No faces were detected in this image.".to_string());
+    }
+
+    faces
+        .iter()
+        .map(|face| -> Result<AiFaceMaskParameters, String> {
+            let mask = generate_face_mask(&full_image, face, refine_skin.unwrap_or(false));
+            Ok(AiFaceMaskParameters {
+                mask_data_base64: Some(encode_to_base64_png(&mask)?),
+                face_box: Some([face.x1, face.y1, face.x2, face.y2]),
+                rotation: Some(rotation),
+                flip_horizontal: Some(flip_horizontal),
+                flip_vertical: Some(flip_vertical),
+                orientation_steps: Some(orientation_steps),
+            })
+        })
+        .collect()
+}
+
+fn unrotate_subject_point(
+    point: (f64, f64),
+    rotation: f32,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    orientation_steps: u8,
+    (img_w, img_h): (u32, u32),
+) -> (f64, f64) {
+    let (coarse_rotated_w, coarse_rotated_h) = if orientation_steps % 2 == 1 {
+        (img_h as f64, img_w as f64)
+    } else {
+        (img_w as f64, img_h as f64)
+    };
+
+    let center = (coarse_rotated_w / 2.0, coarse_rotated_h / 2.0);
+
+    let angle_rad = (rotation as f64).to_radians();
+    let cos_a = angle_rad.cos();
+    let sin_a = angle_rad.sin();
+
+    let px = point.0 - center.0;
+    let py = point.1 - center.1;
+    let mut unrotated = (px * cos_a + py * sin_a + center.0, -px * sin_a + py * cos_a + center.1);
+
+    if flip_horizontal {
+        unrotated.0 = coarse_rotated_w - unrotated.0;
+    }
+    if flip_vertical {
+        unrotated.1 = coarse_rotated_h - unrotated.1;
+    }
+
+    match orientation_steps {
+        1 => (unrotated.1, img_h as f64 - unrotated.0),
+        2 => (img_w as f64 - unrotated.0, img_h as f64 - unrotated.1),
+        3 => (img_w as f64 - unrotated.1, unrotated.0),
+        _ => unrotated,
+    }
+}
+
+fn unrotate_subject_box(
+    start_point: (f64, f64),
+    end_point: (f64, f64),
+    rotation: f32,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    orientation_steps: u8,
+    image_size: (u32, u32),
+) -> ((f64, f64), (f64, f64)) {
+    let corners = [
+        start_point,
+        (start_point.0, end_point.1),
+        end_point,
+        (end_point.0, start_point.1),
+    ]
+    .map(|p| unrotate_subject_point(p, rotation, flip_horizontal, flip_vertical, orientation_steps, image_size));
+
+    let min_x = corners.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    let min_y = corners.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let max_x = corners.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+    let max_y = corners.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+
+    ((min_x, min_y), (max_x, max_y))
+}
+
 #[tauri::command]
 async fn generate_ai_subject_mask(
     path: String,
     start_point: (f64, f64),
     end_point: (f64, f64),
+    points: Option<Vec<SamPoint>>,
+    sub_mask_id: Option<String>,
+    refine_from: Option<usize>,
     rotation: f32,
     flip_horizontal: bool,
     flip_vertical: bool,
@@ -970,6 +2193,7 @@ async fn generate_ai_subject_mask(
                 let mut new_embeddings = generate_image_embeddings(&full_image, &models.sam_encoder).map_err(|e| e.to_string())?;
                 new_embeddings.path_hash = path_hash;
                 ai_state.embeddings = Some(new_embeddings.clone());
+                ai_state.sam_low_res_masks.clear();
                 new_embeddings
             }
         } else {
@@ -977,96 +2201,382 @@ async fn generate_ai_subject_mask(
             let mut new_embeddings = generate_image_embeddings(&full_image, &models.sam_encoder).map_err(|e| e.to_string())?;
             new_embeddings.path_hash = path_hash;
             ai_state.embeddings = Some(new_embeddings.clone());
+            ai_state.sam_low_res_masks.clear();
             new_embeddings
         }
     };
 
-    let (img_w, img_h) = embeddings.original_size;
+    let points = points.unwrap_or_default();
+    let has_box = start_point != end_point;
+    if !has_box && points.is_empty() {
+        return Err("An AI subject mask needs a box or at least one point.".to_string());
+    }
 
-    let (coarse_rotated_w, coarse_rotated_h) = if orientation_steps % 2 == 1 {
-        (img_h as f64, img_w as f64)
+    let prompt_box = has_box.then(|| {
+        unrotate_subject_box(
+            start_point,
+            end_point,
+            rotation,
+            flip_horizontal,
+            flip_vertical,
+            orientation_steps,
+            embeddings.original_size,
+        )
+    });
+    let unrotated_points: Vec<SamPoint> = points
+        .iter()
+        .map(|point| {
+            let (x, y) = unrotate_subject_point(
+                (point.x, point.y),
+                rotation,
+                flip_horizontal,
+                flip_vertical,
+                orientation_steps,
+                embeddings.original_size,
+            );
+            SamPoint { x, y, positive: point.positive }
+        })
+        .collect();
+
+    let logits_key = (embeddings.path_hash.clone(), sub_mask_id.unwrap_or_default());
+    let previous_mask = refine_from.and_then(|index| {
+        state.ai_state.lock().unwrap().as_ref()
+            .and_then(|s| s.sam_low_res_masks.get(&logits_key))
+            .and_then(|masks| masks.get(index).cloned())
+    });
+
+    let predictions = run_sam_decoder(&models.sam_decoder, &embeddings, prompt_box, &unrotated_points, previous_mask.as_ref())
+        .map_err(|e| e.to_string())?;
+    if let Some(ai_state) = state.ai_state.lock().unwrap().as_mut() {
+        ai_state.sam_low_res_masks.insert(logits_key, predictions.iter().map(|p| p.low_res_mask.clone()).collect());
+    }
+
+    let candidates = predictions
+        .iter()
+        .map(|p| Ok(AiMaskCandidate { mask_data_base64: encode_to_base64_png(&p.mask)?, score: p.score }))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(AiSubjectMaskParameters {
+        start_x: start_point.0,
+        start_y: start_point.1,
+        end_x: end_point.0,
+        end_y: end_point.1,
+        mask_data_base64: candidates.first().map(|c| c.mask_data_base64.clone()),
+        rotation: Some(rotation),
+        flip_horizontal: Some(flip_horizontal),
+        flip_vertical: Some(flip_vertical),
+        orientation_steps: Some(orientation_steps),
+        points: Some(points),
+        candidates,
+    })
+}
+
+struct TargetTransform {
+    rotation: f32,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    orientation_steps: u8,
+}
+
+impl TargetTransform {
+    fn from_adjustments(adjustments: &Value) -> Self {
+        Self {
+            rotation: adjustments["rotation"].as_f64().unwrap_or(0.0) as f32,
+            flip_horizontal: adjustments["flipHorizontal"].as_bool().unwrap_or(false),
+            flip_vertical: adjustments["flipVertical"].as_bool().unwrap_or(false),
+            orientation_steps: adjustments["orientationSteps"].as_u64().unwrap_or(0) as u8,
+        }
+    }
+
+    fn write_into(&self, params: &mut serde_json::Map<String, Value>) {
+        params.insert("rotation".to_string(), serde_json::json!(self.rotation));
+        params.insert("flipHorizontal".to_string(), serde_json::json!(self.flip_horizontal));
+        params.insert("flipVertical".to_string(), serde_json::json!(self.flip_vertical));
+        params.insert("orientationSteps".to_string(), serde_json::json!(self.orientation_steps));
+    }
+}
+
+fn is_ai_sub_mask_type(mask_type: &str) -> bool {
+    matches!(mask_type, "ai-subject" | "ai-foreground" | "ai-sky" | "ai-face")
+}
+
+fn for_each_ai_sub_mask(container: &mut Value, f: &mut dyn FnMut(&str, &mut serde_json::Map<String, Value>)) {
+    let Some(sub_masks) = container.get_mut("subMasks").and_then(|m| m.as_array_mut()) else {
+        return;
+    };
+    for sub_mask in sub_masks {
+        let mask_type = sub_mask["type"].as_str().unwrap_or_default().to_string();
+        if mask_type == "group" {
+            for_each_ai_sub_mask(sub_mask, f);
+        } else if is_ai_sub_mask_type(&mask_type) {
+            if let Some(params) = sub_mask.get_mut("parameters").and_then(|p| p.as_object_mut()) {
+                f(&mask_type, params);
+            }
+        }
+    }
+}
+
+fn display_size((width, height): (u32, u32), orientation_steps: u8) -> (f64, f64) {
+    if orientation_steps % 2 == 1 {
+        (height as f64, width as f64)
     } else {
-        (img_w as f64, img_h as f64)
+        (width as f64, height as f64)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn recompute_ai_sub_mask(
+    mask_type: &str,
+    params: &mut serde_json::Map<String, Value>,
+    image: &DynamicImage,
+    models: &AiModels,
+    face_detector: Option<&Session>,
+    embeddings: &mut Option<ImageEmbeddings>,
+    source_size: (u32, u32),
+    target: &TargetTransform,
+) -> Result<(), String> {
+    let target_size = image.dimensions();
+    let mask = match mask_type {
+        "ai-foreground" => Some(run_u2netp_model(image, &models.u2netp).map_err(|e| e.to_string())?),
+        "ai-sky" => Some(run_sky_seg_model(image, &models.sky_seg).map_err(|e| e.to_string())?),
+        "ai-face" => {
+            let face_detector = face_detector.ok_or("The face detection model is not loaded.")?;
+            let faces = run_face_detector(image, face_detector).map_err(|e| e.to_string())?;
+            let reference = params
+                .get("faceBox")
+                .and_then(|b| serde_json::from_value::<[f32; 4]>(b.clone()).ok())
+                .map(|[x1, y1, x2, y2]| FaceDetection { x1, y1, x2, y2, score: 1.0 });
+            let best_face = match reference {
+                Some(reference) => faces
+                    .iter()
+                    .max_by(|a, b| a.iou(&reference).partial_cmp(&b.iou(&reference)).unwrap_or(std::cmp::Ordering::Equal)),
+                None => faces.first(),
+            };
+            match best_face {
+                Some(face) => {
+                    params.insert("faceBox".to_string(), serde_json::json!([face.x1, face.y1, face.x2, face.y2]));
+                    Some(generate_face_mask(image, face, false))
+                }
+                None => None,
+            }
+        }
+        "ai-subject" => {
+            let source_orientation = params.get("orientationSteps").and_then(|v| v.as_u64()).unwrap_or(0) as u8;
+            let (source_w, source_h) = display_size(source_size, source_orientation);
+            let (target_w, target_h) = display_size(target_size, target.orientation_steps);
+            let scale_x = target_w / source_w.max(1.0);
+            let scale_y = target_h / source_h.max(1.0);
+
+            let read = |key: &str| params.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+            let start_point = (read("startX") * scale_x, read("startY") * scale_y);
+            let end_point = (read("endX") * scale_x, read("endY") * scale_y);
+            let points: Vec<SamPoint> = params
+                .get("points")
+                .and_then(|p| serde_json::from_value::<Vec<SamPoint>>(p.clone()).ok())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|p| SamPoint { x: p.x * scale_x, y: p.y * scale_y, ..p })
+                .collect();
+
+            if embeddings.is_none() {
+                *embeddings = Some(generate_image_embeddings(image, &models.sam_encoder).map_err(|e| e.to_string())?);
+            }
+            let embeddings = embeddings.as_ref().unwrap();
+
+            let prompt_box = (start_point != end_point).then(|| {
+                unrotate_subject_box(
+                    start_point,
+                    end_point,
+                    target.rotation,
+                    target.flip_horizontal,
+                    target.flip_vertical,
+                    target.orientation_steps,
+                    embeddings.original_size,
+                )
+            });
+            let unrotated_points: Vec<SamPoint> = points
+                .iter()
+                .map(|point| {
+                    let (x, y) = unrotate_subject_point(
+                        (point.x, point.y),
+                        target.rotation,
+                        target.flip_horizontal,
+                        target.flip_vertical,
+                        target.orientation_steps,
+                        embeddings.original_size,
+                    );
+                    SamPoint { x, y, positive: point.positive }
+                })
+                .collect();
+            let mask_bitmap = run_sam_decoder(&models.sam_decoder, embeddings, prompt_box, &unrotated_points, None)
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .next()
+                .map(|prediction| prediction.mask);
+
+            params.insert("startX".to_string(), serde_json::json!(start_point.0));
+            params.insert("startY".to_string(), serde_json::json!(start_point.1));
+            params.insert("endX".to_string(), serde_json::json!(end_point.0));
+            params.insert("endY".to_string(), serde_json::json!(end_point.1));
+            params.insert("points".to_string(), serde_json::json!(points));
+            mask_bitmap
+        }
+        _ => return Ok(()),
     };
 
-    let center = (coarse_rotated_w / 2.0, coarse_rotated_h / 2.0);
+    let mask_data = match mask {
+        Some(mask) => Value::String(encode_to_base64_png(&mask)?),
+        None => Value::Null,
+    };
+    params.insert("maskDataBase64".to_string(), mask_data);
+    target.write_into(params);
+    Ok(())
+}
+
+fn read_sidecar_metadata(path: &str) -> ImageMetadata {
+    let sidecar_path = get_sidecar_path(path);
+    if sidecar_path.exists() {
+        fs::read_to_string(&sidecar_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    } else {
+        ImageMetadata::default()
+    }
+}
+
+fn write_sidecar_metadata(path: &str, metadata: &ImageMetadata) -> Result<(), String> {
+    let json_string = serde_json::to_string_pretty(metadata).map_err(|e| e.to_string())?;
+    fs::write(get_sidecar_path(path), json_string).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn copy_masks_to_paths(
+    masks: Value,
+    source_width: u32,
+    source_height: u32,
+    paths: Vec<String>,
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    if !masks.is_array() {
+        return Err("Masks must be an array of mask definitions.".to_string());
+    }
+    let paths: Vec<String> = paths.into_iter().filter(|path| !is_video_file(path)).collect();
+
+    let source_size = (source_width, source_height);
+
+    let mut has_ai_sub_masks = false;
+    let mut has_face_masks = false;
+    let mut pasted_masks = masks.clone();
+    if let Some(mask_defs) = pasted_masks.as_array_mut() {
+        for mask_def in mask_defs {
+            for_each_ai_sub_mask(mask_def, &mut |mask_type, params| {
+                has_ai_sub_masks = true;
+                has_face_masks |= mask_type == "ai-face";
+                params.insert("maskDataBase64".to_string(), Value::Null);
+            });
+        }
+    }
 
-    let p1 = start_point;
-    let p2 = (start_point.0, end_point.1);
-    let p3 = end_point;
-    let p4 = (end_point.0, start_point.1);
+    // Pasted masks are added to the image's own masks. A mask pasted again replaces its earlier copy.
+    let pasted_defs = pasted_masks.as_array().cloned().unwrap_or_default();
+    let pasted_ids: HashSet<String> = pasted_defs
+        .iter()
+        .filter_map(|m| m.get("id").and_then(Value::as_str).map(str::to_string))
+        .collect();
+    for path in &paths {
+        let lock = sidecar_lock(path);
+        let _guard = lock.lock().unwrap();
+        let mut metadata = read_sidecar_metadata(path);
+        if !metadata.adjustments.is_object() {
+            metadata.adjustments = serde_json::json!({});
+        }
+        let mut mask_defs = metadata.adjustments.get("masks").and_then(Value::as_array).cloned().unwrap_or_default();
+        mask_defs.retain(|m| !m.get("id").and_then(Value::as_str).map_or(false, |id| pasted_ids.contains(id)));
+        mask_defs.extend(pasted_defs.iter().cloned());
+        metadata.adjustments["masks"] = Value::Array(mask_defs);
+        write_sidecar_metadata(path, &metadata)?;
+    }
 
-    let angle_rad = (rotation as f64).to_radians();
-    let cos_a = angle_rad.cos();
-    let sin_a = angle_rad.sin();
+    if !has_ai_sub_masks {
+        thread::spawn(move || {
+            let _ = file_management::generate_thumbnails_progressive(paths, app_handle);
+        });
+        return Ok(());
+    }
 
-    let unrotate = |p: (f64, f64)| {
-        let px = p.0 - center.0;
-        let py = p.1 - center.1;
-        let new_px = px * cos_a + py * sin_a + center.0;
-        let new_py = -px * sin_a + py * cos_a + center.1;
-        (new_px, new_py)
+    let models = get_or_init_ai_models(&app_handle, &state.ai_state, &state.ai_init_lock)
+        .await
+        .map_err(|e| e.to_string())?;
+    let face_detector = if has_face_masks {
+        Some(
+            get_or_init_face_detector(&app_handle, &state.face_detector, &state.ai_init_lock)
+                .await
+                .map_err(|e| e.to_string())?,
+        )
+    } else {
+        None
     };
 
-    let up1 = unrotate(p1);
-    let up2 = unrotate(p2);
-    let up3 = unrotate(p3);
-    let up4 = unrotate(p4);
+    tokio::task::spawn_blocking(move || {
+        let total = paths.len();
+        for (i, path) in paths.iter().enumerate() {
+            let _ = app_handle.emit("mask-recompute-progress", serde_json::json!({ "current": i, "total": total, "path": path }));
+
+            let result: Result<(), String> = (|| {
+                let snapshot = read_sidecar_metadata(path);
+                let image = load_and_composite(path, &snapshot.adjustments, false).map_err(|e| e.to_string())?;
+                let target = TargetTransform::from_adjustments(&snapshot.adjustments);
+                let mut embeddings: Option<ImageEmbeddings> = None;
+                let mut first_error: Option<String> = None;
+
+                // The sidecar isn't locked while the models run; the recomputed components are merged
+                // into whatever the sidecar holds afterwards, matched by mask id.
+                let mut recomputed: HashMap<String, Value> = HashMap::new();
+                let snapshot_masks = snapshot.adjustments.get("masks").and_then(Value::as_array).cloned().unwrap_or_default();
+                for mut mask_def in snapshot_masks {
+                    let Some(id) = mask_def.get("id").and_then(Value::as_str).map(str::to_string) else { continue; };
+                    if !pasted_ids.contains(&id) {
+                        continue;
+                    }
+                    for_each_ai_sub_mask(&mut mask_def, &mut |mask_type, params| {
+                        if let Err(e) = recompute_ai_sub_mask(mask_type, params, &image, &models, face_detector.as_deref(), &mut embeddings, source_size, &target) {
+                            first_error.get_or_insert(e);
+                        }
+                    });
+                    recomputed.insert(id, mask_def["subMasks"].take());
+                }
 
-    let unflip = |p: (f64, f64)| {
-        let mut new_px = p.0;
-        let mut new_py = p.1;
-        if flip_horizontal {
-            new_px = coarse_rotated_w - p.0;
-        }
-        if flip_vertical {
-            new_py = coarse_rotated_h - p.1;
-        }
-        (new_px, new_py)
-    };
+                let lock = sidecar_lock(path);
+                let _guard = lock.lock().unwrap();
+                let mut metadata = read_sidecar_metadata(path);
+                if let Some(mask_defs) = metadata.adjustments.get_mut("masks").and_then(|m| m.as_array_mut()) {
+                    for mask_def in mask_defs.iter_mut() {
+                        let id = mask_def.get("id").and_then(Value::as_str).unwrap_or_default().to_string();
+                        if let Some(sub_masks) = recomputed.remove(&id) {
+                            mask_def["subMasks"] = sub_masks;
+                        }
+                    }
+                }
+                write_sidecar_metadata(path, &metadata)?;
+                match first_error {
+                    Some(e) => Err(e),
+                    None => Ok(()),
+                }
+            })();
 
-    let ufp1 = unflip(up1);
-    let ufp2 = unflip(up2);
-    let ufp3 = unflip(up3);
-    let ufp4 = unflip(up4);
-
-    let un_coarse_rotate = |p: (f64, f64)| -> (f64, f64) {
-        match orientation_steps {
-            0 => p,
-            1 => (p.1, img_h as f64 - p.0),
-            2 => (img_w as f64 - p.0, img_h as f64 - p.1),
-            3 => (img_w as f64 - p.1, p.0),
-            _ => p,
+            if let Err(e) = result {
+                eprintln!("Failed to recompute AI masks for {}: {}", path, e);
+                let _ = app_handle.emit("mask-recompute-error", serde_json::json!({ "path": path, "error": e }));
+            }
         }
-    };
-
-    let ucrp1 = un_coarse_rotate(ufp1);
-    let ucrp2 = un_coarse_rotate(ufp2);
-    let ucrp3 = un_coarse_rotate(ufp3);
-    let ucrp4 = un_coarse_rotate(ufp4);
 
-    let min_x = ucrp1.0.min(ucrp2.0).min(ucrp3.0).min(ucrp4.0);
-    let min_y = ucrp1.1.min(ucrp2.1).min(ucrp3.1).min(ucrp4.1);
-    let max_x = ucrp1.0.max(ucrp2.0).max(ucrp3.0).max(ucrp4.0);
-    let max_y = ucrp1.1.max(ucrp2.1).max(ucrp3.1).max(ucrp4.1);
-
-    let unrotated_start_point = (min_x, min_y);
-    let unrotated_end_point = (max_x, max_y);
-
-    let mask_bitmap = run_sam_decoder(&models.sam_decoder, &embeddings, unrotated_start_point, unrotated_end_point).map_err(|e| e.to_string())?;
-    let base64_data = encode_to_base64_png(&mask_bitmap)?;
+        let _ = app_handle.emit("mask-recompute-finished", serde_json::json!({ "paths": paths }));
+        let _ = file_management::generate_thumbnails_progressive(paths, app_handle);
+    });
 
-    Ok(AiSubjectMaskParameters {
-        start_x: start_point.0,
-        start_y: start_point.1,
-        end_x: end_point.0,
-        end_y: end_point.1,
-        mask_data_base64: Some(base64_data),
-        rotation: Some(rotation),
-        flip_horizontal: Some(flip_horizontal),
-        flip_vertical: Some(flip_vertical),
-        orientation_steps: Some(orientation_steps),
-    })
+    Ok(())
 }
 
 #[tauri::command]
@@ -1078,13 +2588,13 @@ fn generate_preset_preview(
 
     let loaded_image = state.original_image.lock().unwrap().clone()
         .ok_or("No original image loaded for preset preview")?;
-    let original_image = loaded_image.image;
+    let original_image = &loaded_image.image;
     
     const PRESET_PREVIEW_DIM: u32 = 200;
     let preview_base = original_image.thumbnail(PRESET_PREVIEW_DIM, PRESET_PREVIEW_DIM);
 
-    let (transformed_image, unscaled_crop_offset) = 
-        apply_all_transformations(&preview_base, &js_adjustments, 1.0);
+    let (transformed_image, unscaled_crop_offset, optical_center) = 
+        apply_all_transformations(&preview_base, &js_adjustments, 1.0, Some(&loaded_image.lens_info));
     let (img_w, img_h) = transformed_image.dimensions();
 
     let mask_definitions: Vec<MaskDefinition> = js_adjustments.get("masks")
@@ -1092,12 +2602,12 @@ fn generate_preset_preview(
         .unwrap_or_else(Vec::new);
 
     let mask_bitmaps: Vec<ImageBuffer<Luma<u8>, Vec<u8>>> = mask_definitions.iter()
-        .filter_map(|def| generate_mask_bitmap(def, img_w, img_h, 1.0, unscaled_crop_offset))
+        .filter_map(|def| generate_mask_bitmap(def, img_w, img_h, 1.0, unscaled_crop_offset, Some(&transformed_image)))
         .collect();
 
-    let all_adjustments = get_all_adjustments_from_json(&js_adjustments);
+    let all_adjustments = get_all_adjustments_from_json(&js_adjustments).with_optical_center(optical_center);
     
-    let processed_image = process_and_get_dynamic_image(&context, &transformed_image, all_adjustments, &mask_bitmaps)?;
+    let processed_image = process_and_get_dynamic_image(&context, &transformed_image, all_adjustments, &mask_bitmaps, get_lut_for_adjustments(&js_adjustments).as_deref())?;
     
     let mut buf = Cursor::new(Vec::new());
     processed_image.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut buf, 50)).map_err(|e| e.to_string())?;
@@ -1113,14 +2623,18 @@ fn update_window_effect(theme: String, window: tauri::Window) {
 #[tauri::command]
 async fn check_comfyui_status(app_handle: tauri::AppHandle) {
     let settings = load_settings(app_handle.clone()).unwrap_or_default();
-    let is_connected = if let Some(address) = settings.comfyui_address {
-        comfyui_connector::ping_server(&address).await.is_ok()
-    } else {
-        false
-    };
+    let profiles = comfyui_connector::configured_profiles(&settings);
+    let health = comfyui_connector::check_profiles(&profiles).await;
+    let is_connected = health.iter().any(|h| h.online);
     let _ = app_handle.emit("comfyui-status-update", serde_json::json!({ "connected": is_connected }));
 }
 
+#[tauri::command]
+async fn check_comfyui_profiles(app_handle: tauri::AppHandle) -> Vec<comfyui_connector::ProfileHealth> {
+    let settings = load_settings(app_handle).unwrap_or_default();
+    comfyui_connector::check_profiles(&comfyui_connector::configured_profiles(&settings)).await
+}
+
 #[tauri::command]
 async fn test_comfyui_connection(address: String) -> Result<(), String> {
     comfyui_connector::ping_server(&address)
@@ -1128,6 +2642,15 @@ async fn test_comfyui_connection(address: String) -> Result<(), String> {
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn get_comfyui_models(app_handle: tauri::AppHandle) -> Result<comfyui_connector::AvailableModels, String> {
+    let settings = load_settings(app_handle).unwrap_or_default();
+    let profile = comfyui_connector::select_profile(&settings).await?;
+    comfyui_connector::list_models(&profile.address)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 fn calculate_dynamic_patch_radius(width: u32, height: u32) -> u32 {
     const MIN_RADIUS: u32 = 2;
     const MAX_RADIUS: u32 = 32;
@@ -1138,21 +2661,32 @@ fn calculate_dynamic_patch_radius(width: u32, height: u32) -> u32 {
     scaled_radius.clamp(MIN_RADIUS, MAX_RADIUS)
 }
 
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+enum InpaintMode {
+    Fast,
+    Local,
+    ComfyUi,
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn invoke_generative_replace_with_mask_def(
     _path: String,
     patch_definition: AiPatchDefinition,
     current_adjustments: Value,
-    use_fast_inpaint: bool,
+    inpaint_mode: InpaintMode,
+    checkpoint: Option<String>,
+    loras: Option<Vec<comfyui_connector::LoraSelection>>,
     app_handle: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
-    let settings = load_settings(app_handle.clone()).unwrap_or_default();
-    let address = settings.comfyui_address;
-
-    if !use_fast_inpaint && address.is_none() {
-        return Err("ComfyUI address is not configured in settings.".to_string());
-    }
+    let profile = if inpaint_mode == InpaintMode::ComfyUi {
+        let settings = load_settings(app_handle.clone()).unwrap_or_default();
+        Some(comfyui_connector::select_profile(&settings).await?)
+    } else {
+        None
+    };
 
     let mut source_image_adjustments = current_adjustments.clone();
     if let Some(patches) = source_image_adjustments.get_mut("aiPatches").and_then(|v| v.as_array_mut()) {
@@ -1174,43 +2708,73 @@ async fn invoke_generative_replace_with_mask_def(
         sub_masks: patch_definition.sub_masks,
     };
 
-    let mask_bitmap = generate_mask_bitmap(&mask_def_for_generation, img_w, img_h, 1.0, (0.0, 0.0))
+    let mask_bitmap = generate_mask_bitmap(&mask_def_for_generation, img_w, img_h, 1.0, (0.0, 0.0), Some(&source_image))
         .ok_or("Failed to generate mask bitmap for AI replace")?;
 
-    let patch_rgba = if use_fast_inpaint {
-        let patch_radius = calculate_dynamic_patch_radius(img_w, img_h);
-        inpainting::perform_fast_inpaint(&source_image, &mask_bitmap, patch_radius)?
-    } else {
-        let comfy_address = address.unwrap();
-
-        let dilation_amount_u32 = ((img_w.min(img_h) as f32 * 0.01).round() as u32).max(1);
-        let dilation_amount_u8 = std::cmp::min(dilation_amount_u32, 255) as u8;
-        let enlarged_mask_bitmap = dilate(&mask_bitmap, DilationNorm::LInf, dilation_amount_u8);
+    let dilation_amount_u32 = ((img_w.min(img_h) as f32 * 0.01).round() as u32).max(1);
+    let dilation_amount_u8 = std::cmp::min(dilation_amount_u32, 255) as u8;
 
-        let mut rgba_mask = RgbaImage::new(img_w, img_h);
-        for (x, y, luma_pixel) in enlarged_mask_bitmap.enumerate_pixels() {
-            let intensity = luma_pixel[0];
-            rgba_mask.put_pixel(x, y, Rgba([255, 255, 255, intensity]));
+    let mut used_seed = None;
+    let patch_rgba = match inpaint_mode {
+        InpaintMode::Fast => {
+            let patch_radius = calculate_dynamic_patch_radius(img_w, img_h);
+            inpainting::perform_fast_inpaint(&source_image, &mask_bitmap, patch_radius)?
         }
-        let mask_image = DynamicImage::ImageRgba8(rgba_mask);
+        InpaintMode::Local => {
+            let inpaint_session = get_or_init_inpaint_model(&app_handle, &state.inpaint_model, &state.ai_init_lock)
+                .await
+                .map_err(|e| e.to_string())?;
+            let enlarged_mask_bitmap = dilate(&mask_bitmap, DilationNorm::LInf, dilation_amount_u8);
+            tokio::task::spawn_blocking(move || run_lama_inpaint(&source_image, &enlarged_mask_bitmap, &inpaint_session))
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string())?
+        }
+        InpaintMode::ComfyUi => {
+            let profile = profile.ok_or("No ComfyUI server selected.")?;
 
-        let workflow_inputs = comfyui_connector::WorkflowInputs {
-            source_image_node_id: "11".to_string(),
-            mask_image_node_id: Some("148".to_string()),
-            text_prompt_node_id: Some("6".to_string()),
-            final_output_node_id: "252".to_string(),
-        };
+            let enlarged_mask_bitmap = dilate(&mask_bitmap, DilationNorm::LInf, dilation_amount_u8);
+
+            let mut rgba_mask = RgbaImage::new(img_w, img_h);
+            for (x, y, luma_pixel) in enlarged_mask_bitmap.enumerate_pixels() {
+                let intensity = luma_pixel[0];
+                rgba_mask.put_pixel(x, y, Rgba([255, 255, 255, intensity]));
+            }
+            let mask_image = DynamicImage::ImageRgba8(rgba_mask);
+
+            let workflow_inputs = comfyui_connector::WorkflowInputs {
+                source_image_node_id: "11".to_string(),
+                mask_image_node_id: Some("148".to_string()),
+                text_prompt_node_id: Some("6".to_string()),
+                negative_prompt_node_id: Some("7".to_string()),
+                checkpoint_node_id: Some("4".to_string()),
+                sampler_node_id: Some("3".to_string()),
+                upscale_model_node_id: None,
+                final_output_node_id: "252".to_string(),
+            };
+            // Without a seed every generation gets a new one, which is returned so it can be kept with
+            // the patch.
+            let seed = patch_definition.generation.seed.unwrap_or_else(|| rand::random::<u32>() as u64);
+            used_seed = Some(seed);
+            let generation_options = comfyui_connector::GenerationOptions {
+                checkpoint,
+                loras: loras.unwrap_or_default(),
+                parameters: GenerationParameters { seed: Some(seed), ..patch_definition.generation },
+                upscale_model: None,
+            };
 
-        let result_png_bytes = comfyui_connector::execute_workflow(
-            &comfy_address,
-            "generative_replace",
-            workflow_inputs,
-            source_image,
-            Some(mask_image),
-            Some(patch_definition.prompt)
-        ).await.map_err(|e| e.to_string())?;
+            let result_png_bytes = comfyui_connector::execute_workflow(
+                &profile,
+                "generative_replace",
+                workflow_inputs,
+                source_image,
+                Some(mask_image),
+                Some(patch_definition.prompt),
+                &generation_options,
+            ).await.map_err(|e| report_comfyui_error(&app_handle, e))?;
         
-        image::load_from_memory(&result_png_bytes).map_err(|e| e.to_string())?.to_rgba8()
+            image::load_from_memory(&result_png_bytes).map_err(|e| e.to_string())?.to_rgba8()
+        }
     };
 
     let (width, height) = patch_rgba.dimensions();
@@ -1226,6 +2790,19 @@ async fn invoke_generative_replace_with_mask_def(
         }
     }
 
+    encode_patch_result(&color_image, &mask_image, used_seed)
+}
+
+// Reports ComfyUI failures with their kind, so the frontend can tell an unreachable server from a
+// failed workflow, and returns the message for the command's error.
+fn report_comfyui_error(app_handle: &tauri::AppHandle, error: anyhow::Error) -> String {
+    let details = comfyui_connector::error_details(&error);
+    eprintln!("ComfyUI error ({:?}): {}", details.kind, details.message);
+    let _ = app_handle.emit("comfyui-error", &details);
+    details.message
+}
+
+fn encode_patch_result(color_image: &RgbImage, mask_image: &GrayImage, seed: Option<u64>) -> Result<String, String> {
     let quality = 75;
 
     let mut color_buf = Cursor::new(Vec::new());
@@ -1240,54 +2817,189 @@ async fn invoke_generative_replace_with_mask_def(
 
     let result_json = serde_json::json!({
         "color": color_base64,
-        "mask": mask_base64
+        "mask": mask_base64,
+        "seed": seed
     }).to_string();
 
     Ok(result_json)
 }
 
+// Runs a style transfer or relight over the whole image. The result is stored like any other AI patch,
+// with a mask covering the full frame, and blended in with the patch's opacity.
+#[tauri::command]
+async fn invoke_image_transform(
+    patch_definition: AiPatchDefinition,
+    current_adjustments: Value,
+    checkpoint: Option<String>,
+    loras: Option<Vec<comfyui_connector::LoraSelection>>,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let transform = patch_definition.transform.ok_or("This AI edit is not a whole-image transformation.")?;
+    let profile = comfyui_connector::select_profile(&load_settings(app_handle.clone()).unwrap_or_default()).await?;
+
+    let mut source_image_adjustments = current_adjustments.clone();
+    if let Some(patches) = source_image_adjustments.get_mut("aiPatches").and_then(|v| v.as_array_mut()) {
+        patches.retain(|p| p.get("id").and_then(|id| id.as_str()) != Some(&patch_definition.id));
+    }
+
+    let base_image = get_full_image_for_processing(&state)?;
+    let source_image = composite_patches_on_image(&base_image, &source_image_adjustments)
+        .map_err(|e| format!("Failed to prepare source image: {}", e))?;
+    let (img_w, img_h) = source_image.dimensions();
+
+    let seed = patch_definition.generation.seed.unwrap_or_else(|| rand::random::<u32>() as u64);
+    let generation_options = comfyui_connector::GenerationOptions {
+        checkpoint,
+        loras: loras.unwrap_or_default(),
+        parameters: GenerationParameters { seed: Some(seed), ..patch_definition.generation },
+        upscale_model: None,
+    };
+
+    let result = comfyui_connector::transform_image(
+        &profile,
+        source_image,
+        transform,
+        &patch_definition.prompt,
+        &generation_options,
+    ).await.map_err(|e| report_comfyui_error(&app_handle, e))?;
+
+    let mask_image = GrayImage::from_pixel(img_w, img_h, Luma([255]));
+    encode_patch_result(&result.to_rgb8(), &mask_image, Some(seed))
+}
+
+#[tauri::command]
+fn create_mask_from_ai_patch(
+    patch_definition: AiPatchDefinition,
+    rotation: f32,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    orientation_steps: u8,
+) -> Result<MaskDefinition, String> {
+    mask_definition_from_ai_patch(&patch_definition, rotation, flip_horizontal, flip_vertical, orientation_steps)
+}
+
+fn get_loaded_raw_bytes(state: &tauri::State<AppState>) -> Result<Vec<u8>, String> {
+    let path = state.original_image.lock().unwrap()
+        .as_ref()
+        .ok_or("No image loaded")?
+        .path.clone();
+    if !is_raw_file(&path) {
+        return Err("White balance sampling requires a RAW image.".to_string());
+    }
+    fs::read(&path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_white_balance_from_point(x: f32, y: f32, state: tauri::State<AppState>) -> Result<serde_json::Value, String> {
+    let file_bytes = get_loaded_raw_bytes(&state)?;
+    let (temperature, tint) = raw_processing::white_balance_from_point(&file_bytes, x, y)
+        .map_err(|e| e.to_string())?;
+
+    Ok(serde_json::json!({
+        "temperature": temperature,
+        "tint": tint
+    }))
+}
+
+#[tauri::command]
+fn get_white_balance_presets(state: tauri::State<AppState>) -> Result<Vec<raw_processing::WhiteBalancePreset>, String> {
+    let file_bytes = get_loaded_raw_bytes(&state)?;
+    raw_processing::white_balance_presets(&file_bytes).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn get_supported_file_types() -> Result<serde_json::Value, String> {
     let raw_extensions: Vec<&str> = crate::formats::RAW_EXTENSIONS.iter().map(|(ext, _)| *ext).collect();
     let non_raw_extensions: Vec<&str> = crate::formats::NON_RAW_EXTENSIONS.to_vec();
+    let video_extensions: Vec<&str> = crate::formats::VIDEO_EXTENSIONS.to_vec();
     
     Ok(serde_json::json!({
         "raw": raw_extensions,
-        "nonRaw": non_raw_extensions
+        "nonRaw": non_raw_extensions,
+        "video": video_extensions
     }))
 }
 
 #[tauri::command]
 async fn stitch_panorama(
     paths: Vec<String>,
+    blend_mode: Option<panorama_utils::stitching::BlendMode>,
+    projection: Option<panorama_utils::projection::ProjectionMode>,
+    exposure_compensation: Option<bool>,
+    boundary: Option<panorama_utils::boundary::PanoramaBoundary>,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let options = panorama_stitching::StitchOptions {
+        blend_mode: blend_mode.unwrap_or_default(),
+        projection: projection.unwrap_or_default(),
+        exposure_compensation: exposure_compensation.unwrap_or(true),
+        boundary: boundary.unwrap_or_default(),
+    };
+    run_panorama_stitch(paths, options, Vec::new(), state.panorama_result.clone(), app_handle).await
+}
+
+#[tauri::command]
+async fn stitch_with_control_points(
+    paths: Vec<String>,
+    control_points: Vec<panorama_stitching::ControlPoint>,
+    blend_mode: Option<panorama_utils::stitching::BlendMode>,
+    projection: Option<panorama_utils::projection::ProjectionMode>,
+    exposure_compensation: Option<bool>,
+    boundary: Option<panorama_utils::boundary::PanoramaBoundary>,
     app_handle: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if control_points.is_empty() {
+        return Err("Please add control points between the images to align.".to_string());
+    }
+    let options = panorama_stitching::StitchOptions {
+        blend_mode: blend_mode.unwrap_or_default(),
+        projection: projection.unwrap_or_default(),
+        exposure_compensation: exposure_compensation.unwrap_or(true),
+        boundary: boundary.unwrap_or_default(),
+    };
+    run_panorama_stitch(paths, options, control_points, state.panorama_result.clone(), app_handle).await
+}
+
+async fn run_panorama_stitch(
+    paths: Vec<String>,
+    options: panorama_stitching::StitchOptions,
+    control_points: Vec<panorama_stitching::ControlPoint>,
+    panorama_result_handle: Arc<Mutex<Option<panorama_stitching::StitchedPanorama>>>,
+    app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
     if paths.len() < 2 {
         return Err("Please select at least two images to stitch.".to_string());
     }
 
-    let panorama_result_handle = state.panorama_result.clone();
-
     let task = tokio::task::spawn_blocking(move || {
-        let panorama_result = panorama_stitching::stitch_images(paths, app_handle.clone());
+        let gpu_context = get_or_init_gpu_context(&app_handle.state::<AppState>()).ok();
+        let panorama_result = panorama_stitching::stitch_images(
+            paths,
+            options,
+            &control_points,
+            gpu_context.as_ref(),
+            app_handle.clone(),
+        );
 
         match panorama_result {
-            Ok(panorama_image) => {
+            Ok(panorama) => {
                 let _ = app_handle.emit("panorama-progress", "Creating preview...");
 
-                let (w, h) = panorama_image.dimensions();
+                let (w, h) = panorama.image.dimensions();
                 let (new_w, new_h) = if w > h {
                     (800, (800.0 * h as f32 / w as f32).round() as u32)
                 } else {
                     ((800.0 * w as f32 / h as f32).round() as u32, 800)
                 };
-                let preview_image = image::imageops::resize(
-                    &panorama_image,
+                let preview_image = DynamicImage::ImageRgb32F(image::imageops::resize(
+                    &panorama.image,
                     new_w,
                     new_h,
                     image::imageops::FilterType::Triangle,
-                );
+                )).to_rgb8();
                 
                 let mut buf = Cursor::new(Vec::new());
                 
@@ -1298,7 +3010,7 @@ async fn stitch_panorama(
                 let base64_str = general_purpose::STANDARD.encode(buf.get_ref());
                 let final_base64 = format!("data:image/png;base64,{}", base64_str);
 
-                *panorama_result_handle.lock().unwrap() = Some(panorama_image);
+                *panorama_result_handle.lock().unwrap() = Some(panorama);
 
                 let _ = app_handle.emit("panorama-complete", serde_json::json!({
                     "base64": final_base64,
@@ -1322,20 +3034,190 @@ async fn stitch_panorama(
 #[tauri::command]
 async fn save_panorama(
     first_path_str: String,
+    output_format: Option<panorama_stitching::PanoramaOutputFormat>,
+    output_dir: Option<String>,
     state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
-    let panorama_image = state.panorama_result.lock().unwrap().take()
+    let panorama = state.panorama_result.lock().unwrap().take()
         .ok_or_else(|| "No panorama image found in memory to save. It might have already been saved.".to_string())?;
 
     let first_path = Path::new(&first_path_str);
-    let parent_dir = first_path.parent().ok_or_else(|| "Could not determine parent directory of the first image.".to_string())?;
+    let parent_dir = match output_dir {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => first_path.parent()
+            .ok_or_else(|| "Could not determine parent directory of the first image.".to_string())?
+            .to_path_buf(),
+    };
     let stem = first_path.file_stem().and_then(|s| s.to_str()).unwrap_or("panorama");
+    let output_format = output_format.unwrap_or_default();
+
+    let output_path = unique_output_path(&parent_dir, &format!("{}_Pano", stem), output_format.extension());
+
+    panorama_stitching::save_panorama_image(&panorama, &output_path, output_format)?;
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn merge_hdr(
+    paths: Vec<String>,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if paths.len() < 2 {
+        return Err("Please select at least two bracketed exposures to merge.".to_string());
+    }
+
+    let hdr_result_handle = state.hdr_result.clone();
+
+    let task = tokio::task::spawn_blocking(move || {
+        let hdr_result = hdr_merging::merge_hdr_images(paths, app_handle.clone());
+
+        match hdr_result {
+            Ok(hdr_image) => {
+                let _ = app_handle.emit("hdr-progress", "Creating preview...");
+
+                let (w, h) = hdr_image.dimensions();
+                let (new_w, new_h) = if w > h {
+                    (800, (800.0 * h as f32 / w as f32).round() as u32)
+                } else {
+                    ((800.0 * w as f32 / h as f32).round() as u32, 800)
+                };
+                let small_image = image::imageops::resize(
+                    &hdr_image,
+                    new_w,
+                    new_h,
+                    image::imageops::FilterType::Triangle,
+                );
+                let preview_image = hdr_merging::tonemap_for_display(&small_image);
+
+                let mut buf = Cursor::new(Vec::new());
+
+                if let Err(e) = preview_image.write_to(&mut buf, ImageFormat::Png) {
+                    return Err(format!("Failed to encode HDR preview: {}", e));
+                }
+
+                let base64_str = general_purpose::STANDARD.encode(buf.get_ref());
+                let final_base64 = format!("data:image/png;base64,{}", base64_str);
+
+                *hdr_result_handle.lock().unwrap() = Some(hdr_image);
+
+                let _ = app_handle.emit("hdr-complete", serde_json::json!({
+                    "base64": final_base64,
+                }));
+                Ok(())
+            }
+            Err(e) => {
+                let _ = app_handle.emit("hdr-error", e.clone());
+                Err(e)
+            }
+        }
+    });
+
+    match task.await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(e),
+        Err(join_err) => Err(format!("HDR merge task failed: {}", join_err)),
+    }
+}
+
+#[tauri::command]
+async fn save_hdr(
+    first_path_str: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let hdr_image = state.hdr_result.lock().unwrap().take()
+        .ok_or_else(|| "No HDR image found in memory to save. It might have already been saved.".to_string())?;
+
+    let first_path = Path::new(&first_path_str);
+    let parent_dir = first_path.parent().ok_or_else(|| "Could not determine parent directory of the first image.".to_string())?;
+    let stem = first_path.file_stem().and_then(|s| s.to_str()).unwrap_or("hdr");
+    let output_path = unique_output_path(parent_dir, &format!("{}_HDR", stem), "tif");
+
+    DynamicImage::ImageRgb32F(hdr_merging::encode_for_editing(hdr_image)).save_with_format(&output_path, ImageFormat::Tiff)
+        .map_err(|e| format!("Failed to save HDR image: {}", e))?;
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn stack_focus(
+    paths: Vec<String>,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    if paths.len() < 2 {
+        return Err("Please select at least two focus-bracketed images to stack.".to_string());
+    }
+
+    let focus_stack_result_handle = state.focus_stack_result.clone();
+
+    let task = tokio::task::spawn_blocking(move || {
+        let stack_result = focus_stacking::stack_focus_images(paths, app_handle.clone());
+
+        match stack_result {
+            Ok(stacked_image) => {
+                let _ = app_handle.emit("focus-stack-progress", "Creating preview...");
+
+                let (w, h) = stacked_image.dimensions();
+                let (new_w, new_h) = if w > h {
+                    (800, (800.0 * h as f32 / w as f32).round() as u32)
+                } else {
+                    ((800.0 * w as f32 / h as f32).round() as u32, 800)
+                };
+                let preview_image = DynamicImage::ImageRgb32F(image::imageops::resize(
+                    &stacked_image,
+                    new_w,
+                    new_h,
+                    image::imageops::FilterType::Triangle,
+                ))
+                .to_rgb8();
 
-    let output_filename = format!("{}_Pano.png", stem);
-    let output_path = parent_dir.join(output_filename);
+                let mut buf = Cursor::new(Vec::new());
+
+                if let Err(e) = preview_image.write_to(&mut buf, ImageFormat::Png) {
+                    return Err(format!("Failed to encode focus stack preview: {}", e));
+                }
+
+                let base64_str = general_purpose::STANDARD.encode(buf.get_ref());
+                let final_base64 = format!("data:image/png;base64,{}", base64_str);
+
+                *focus_stack_result_handle.lock().unwrap() = Some(stacked_image);
+
+                let _ = app_handle.emit("focus-stack-complete", serde_json::json!({
+                    "base64": final_base64,
+                }));
+                Ok(())
+            }
+            Err(e) => {
+                let _ = app_handle.emit("focus-stack-error", e.clone());
+                Err(e)
+            }
+        }
+    });
+
+    match task.await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(e),
+        Err(join_err) => Err(format!("Focus stacking task failed: {}", join_err)),
+    }
+}
+
+#[tauri::command]
+async fn save_focus_stack(
+    first_path_str: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let stacked_image = state.focus_stack_result.lock().unwrap().take()
+        .ok_or_else(|| "No focus-stacked image found in memory to save. It might have already been saved.".to_string())?;
+
+    let first_path = Path::new(&first_path_str);
+    let parent_dir = first_path.parent().ok_or_else(|| "Could not determine parent directory of the first image.".to_string())?;
+    let stem = first_path.file_stem().and_then(|s| s.to_str()).unwrap_or("focus");
+    let output_path = unique_output_path(parent_dir, &format!("{}_Focus", stem), "tif");
 
-    panorama_image.save(&output_path)
-        .map_err(|e| format!("Failed to save panorama image: {}", e))?;
+    DynamicImage::ImageRgb32F(stacked_image).into_rgb16().save_with_format(&output_path, ImageFormat::Tiff)
+        .map_err(|e| format!("Failed to save focus-stacked image: {}", e))?;
 
     Ok(output_path.to_string_lossy().to_string())
 }
@@ -1386,6 +3268,7 @@ fn main() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_process::init())
+        .register_uri_scheme_protocol(PREVIEW_SCHEME, handle_preview_request)
         .setup(|app| {
             let app_handle = app.handle().clone();
 
@@ -1404,6 +3287,15 @@ fn main() {
             println!("Set ORT_DYLIB_PATH to: {}", ort_library_path.display());
 
             let settings: AppSettings = load_settings(app_handle.clone()).unwrap_or_default();
+            raw_processing::init_camera_profiles(&app_handle, &settings);
+            raw_processing::init_pixel_maps(&app_handle);
+            raw_processing::apply_defect_pixel_settings(&settings);
+            lens_correction::init_lens_database(&app_handle);
+            lut_processing::init_luts(&app_handle);
+            watch_import::apply_watch_folders(&app_handle, settings.watch_folders.clone().unwrap_or_default());
+            http_api::apply_http_api(&app_handle, &settings);
+            apply_display_profile(&app_handle, &settings);
+            custom_effects::apply_custom_effect(&app_handle, &settings);
             let window_cfg = app.config().app.windows.get(0).unwrap().clone();
             let transparent = settings.transparent.unwrap_or(window_cfg.transparent);
             let decorations = settings.decorations.unwrap_or(window_cfg.decorations);
@@ -1425,34 +3317,76 @@ fn main() {
         .manage(AppState {
             original_image: Mutex::new(None),
             cached_preview: Mutex::new(None),
+            cached_full_resolution: Mutex::new(None),
             gpu_context: Mutex::new(None),
             ai_state: Mutex::new(None),
             ai_init_lock: TokioMutex::new(()),
+            face_detector: Mutex::new(None),
+            upscale_model: Mutex::new(None),
+            denoise_model: Mutex::new(None),
+            denoised_image: Mutex::new(None),
+            inpaint_model: Mutex::new(None),
+            eye_state_model: Mutex::new(None),
+            face_embedding_model: Mutex::new(None),
             export_task_handle: Mutex::new(None),
             panorama_result: Arc::new(Mutex::new(None)),
+            hdr_result: Arc::new(Mutex::new(None)),
+            focus_stack_result: Arc::new(Mutex::new(None)),
             indexing_task_handle: Mutex::new(None),
+            catalog: Mutex::new(None),
+            prefetch_cache: Arc::new(PrefetchCache::default()),
+            pending_image_path: Mutex::new(None),
+            folder_watcher: Mutex::new(None),
+            watch_importers: Mutex::new(None),
+            http_api: Mutex::new(None),
+            preview_frames: PreviewFrames::default(),
+            display_profile: Mutex::new(None),
+            custom_effect: Arc::new(Mutex::new(None)),
         })
         .invoke_handler(tauri::generate_handler![
             load_image,
             apply_adjustments,
             export_image,
             batch_export_images,
+            upscale_image,
             cancel_export,
             generate_fullscreen_preview,
+            render_region,
+            generate_softproof_preview,
             generate_original_transformed_preview,
             generate_preset_preview,
             generate_uncropped_preview,
+            generate_comparison,
             generate_mask_overlay,
+            generate_focus_peaking,
             generate_ai_subject_mask,
+            generate_ai_denoise,
+            auto_straighten,
+            suggest_crops,
+            auto_crop_paths,
             generate_ai_foreground_mask,
             generate_ai_sky_mask,
+            generate_ai_face_masks,
+            copy_masks_to_paths,
             update_window_effect,
             check_comfyui_status,
+            check_comfyui_profiles,
+            list_display_profiles,
             test_comfyui_connection,
+            get_comfyui_models,
             invoke_generative_replace_with_mask_def,
+            invoke_image_transform,
+            create_mask_from_ai_patch,
             get_supported_file_types,
+            set_white_balance_from_point,
+            get_white_balance_presets,
             stitch_panorama,
+            stitch_with_control_points,
             save_panorama,
+            merge_hdr,
+            save_hdr,
+            stack_focus,
+            save_focus_stack,
             image_processing::generate_histogram,
             image_processing::generate_waveform,
             image_processing::calculate_auto_adjustments,
@@ -1475,6 +3409,16 @@ fn main() {
             file_management::load_metadata,
             file_management::load_presets,
             file_management::save_presets,
+            file_management::load_export_presets,
+            file_management::save_export_preset,
+            file_management::delete_export_preset,
+            file_management::load_albums,
+            file_management::create_album,
+            file_management::rename_album,
+            file_management::delete_album,
+            file_management::add_paths_to_album,
+            file_management::remove_paths_from_album,
+            file_management::reorder_album,
             file_management::load_settings,
             file_management::save_settings,
             file_management::reset_adjustments_for_paths,
@@ -1484,9 +3428,65 @@ fn main() {
             file_management::clear_all_sidecars,
             file_management::clear_thumbnail_cache,
             file_management::set_color_label_for_paths,
+            file_management::set_flag_for_paths,
             file_management::import_files,
             tagging::start_background_indexing,
-            tagging::clear_all_tags
+            tagging::clear_all_tags,
+            xmp_sidecar::import_xmp_sidecars,
+            xmp_sidecar::write_xmp_sidecars,
+            iptc_metadata::load_iptc_metadata,
+            iptc_metadata::set_iptc_for_paths,
+            exif_details::get_exif_details,
+            capture_time::shift_capture_time_for_paths,
+            camera_import::list_camera_devices,
+            camera_import::list_camera_files,
+            camera_import::import_from_camera,
+            lightroom_import::import_lightroom_catalog,
+            develop_import::import_develop_settings,
+            publish_services::get_publish_status,
+            publish_services::publish_to_service,
+            custom_effects::validate_custom_effect,
+            video::get_video_metadata,
+            tagging::search_by_text,
+            tagging::index_faces,
+            tagging::get_face_clusters,
+            tagging::name_face_cluster,
+            tagging::get_person_images,
+            raw_processing::get_camera_profiles,
+            raw_processing::set_camera_profile,
+            raw_processing::import_camera_profile,
+            raw_processing::scan_hot_pixels_from_dark_frame,
+            lens_correction::get_lens_correction_info,
+            lut_processing::import_lut,
+            lut_processing::get_luts,
+            catalog::query_catalog,
+            catalog::sync_catalog,
+            catalog::filter_images,
+            catalog::get_filter_options,
+            geotagging::get_geotagged_images,
+            geotagging::set_gps_for_paths,
+            geotagging::geotag_from_gpx,
+            geotagging::reverse_geocode,
+            stacking::set_stack_collapsed,
+            stacking::set_stack_top,
+            edit_versions::list_edit_versions,
+            edit_versions::create_edit_version,
+            edit_versions::duplicate_edit_version,
+            edit_versions::switch_edit_version,
+            edit_versions::rename_edit_version,
+            edit_versions::delete_edit_version,
+            edit_history::undo_edit,
+            edit_history::redo_edit,
+            edit_history::get_history,
+            smart_collections::list_smart_collections,
+            smart_collections::save_smart_collection,
+            smart_collections::delete_smart_collection,
+            smart_collections::evaluate_smart_collection,
+            image_prefetch::prefetch_images,
+            memory_budget::get_memory_usage,
+            folder_watcher::watch_folder,
+            folder_watcher::unwatch_folder,
+            culling::analyze_for_culling
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,18 +1,21 @@
-use image::{GrayImage, Luma};
+use image::{imageops, DynamicImage, GrayImage, Luma};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::f32::consts::PI;
 use base64::{Engine as _, engine::general_purpose};
 use imageproc::morphology::{dilate, erode};
 use imageproc::distance_transform::Norm as DilationNorm;
+use uuid::Uuid;
 // --- UPDATED IMPORT ---
-use crate::ai_processing::{AiSubjectMaskParameters, AiForegroundMaskParameters, AiSkyMaskParameters};
+use crate::ai_processing::{AiSubjectMaskParameters, AiForegroundMaskParameters, AiSkyMaskParameters, AiFaceMaskParameters};
+use crate::comfyui_connector::ImageTransform;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum SubMaskMode {
     Additive,
     Subtractive,
+    Intersect,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -24,6 +27,8 @@ pub struct SubMask {
     pub visible: bool,
     pub mode: SubMaskMode,
     pub parameters: Value,
+    #[serde(default)]
+    pub sub_masks: Vec<SubMask>,
 }
 
 fn default_opacity() -> f32 {
@@ -63,6 +68,24 @@ pub struct AiPatchDefinition {
     #[serde(default = "default_opacity")]
     pub opacity: f32,
     pub sub_masks: Vec<SubMask>,
+    #[serde(default)]
+    pub generation: GenerationParameters,
+    #[serde(default)]
+    pub transform: Option<ImageTransform>,
+}
+
+// Sampler settings for ComfyUI generations. Unset values keep what the workflow specifies; the seed
+// is filled in after a generation so the result can be reproduced.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationParameters {
+    #[serde(default)]
+    pub negative_prompt: String,
+    pub seed: Option<u64>,
+    pub steps: Option<u32>,
+    pub cfg: Option<f32>,
+    pub denoise: Option<f32>,
+    pub sampler: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -113,6 +136,31 @@ impl Default for LinearMaskParameters {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct LuminanceMaskParameters {
+    #[serde(default)]
+    min_luminance: f32,
+    #[serde(default = "default_max_luminance")]
+    max_luminance: f32,
+    #[serde(default)]
+    feather: f32,
+}
+
+fn default_max_luminance() -> f32 {
+    1.0
+}
+
+impl Default for LuminanceMaskParameters {
+    fn default() -> Self {
+        Self {
+            min_luminance: 0.0,
+            max_luminance: default_max_luminance(),
+            feather: 0.0,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct Point {
     x: f64,
@@ -504,6 +552,31 @@ fn generate_ai_sky_bitmap(
     Some(mask)
 }
 
+fn generate_ai_face_bitmap(
+    params_value: &Value,
+    width: u32,
+    height: u32,
+    scale: f32,
+    crop_offset: (f32, f32),
+) -> Option<GrayImage> {
+    let params: AiFaceMaskParameters = serde_json::from_value(params_value.clone()).ok()?;
+    let grow_feather: GrowFeatherParameters = serde_json::from_value(params_value.clone()).unwrap_or_default();
+    let data_url = params.mask_data_base64?;
+
+    let mut mask = generate_ai_bitmap_from_base64(
+        &data_url,
+        params.rotation.unwrap_or(0.0),
+        params.flip_horizontal.unwrap_or(false),
+        params.flip_vertical.unwrap_or(false),
+        params.orientation_steps.unwrap_or(0),
+        width, height, scale, crop_offset
+    )?;
+
+    apply_grow_and_feather(&mut mask, grow_feather.grow, grow_feather.feather);
+
+    Some(mask)
+}
+
 fn generate_ai_foreground_bitmap(
     params_value: &Value,
     width: u32,
@@ -554,12 +627,42 @@ fn generate_ai_subject_bitmap(
     Some(mask)
 }
 
+// Selects a luminance range of the image being masked. The feather widens both ends of the range
+// into a smooth falloff.
+fn generate_luminance_bitmap(params_value: &Value, image: &DynamicImage, width: u32, height: u32) -> GrayImage {
+    let params: LuminanceMaskParameters = serde_json::from_value(params_value.clone()).unwrap_or_default();
+    let mut luma = image.to_luma8();
+    if luma.dimensions() != (width, height) {
+        luma = imageops::resize(&luma, width, height, imageops::FilterType::Triangle);
+    }
+
+    let min = params.min_luminance.clamp(0.0, 1.0);
+    let max = params.max_luminance.clamp(min, 1.0);
+    let feather = params.feather.clamp(0.0, 1.0) * 0.5;
+    let ramp = |edge0: f32, edge1: f32, x: f32| {
+        if edge1 <= edge0 {
+            if x >= edge1 { 1.0 } else { 0.0 }
+        } else {
+            let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        }
+    };
+
+    for pixel in luma.pixels_mut() {
+        let l = pixel[0] as f32 / 255.0;
+        let weight = ramp(min - feather, min, l) * (1.0 - ramp(max, max + feather, l));
+        pixel[0] = (weight * 255.0).round() as u8;
+    }
+    luma
+}
+
 fn generate_sub_mask_bitmap(
     sub_mask: &SubMask,
     width: u32,
     height: u32,
     scale: f32,
     crop_offset: (f32, f32),
+    image: Option<&DynamicImage>,
 ) -> Option<GrayImage> {
     if !sub_mask.visible {
         return None;
@@ -572,41 +675,72 @@ fn generate_sub_mask_bitmap(
         "ai-subject" => generate_ai_subject_bitmap(&sub_mask.parameters, width, height, scale, crop_offset),
         "ai-foreground" => generate_ai_foreground_bitmap(&sub_mask.parameters, width, height, scale, crop_offset),
         "ai-sky" => generate_ai_sky_bitmap(&sub_mask.parameters, width, height, scale, crop_offset),
+        "ai-face" => generate_ai_face_bitmap(&sub_mask.parameters, width, height, scale, crop_offset),
+        "ai-patch" => generate_ai_foreground_bitmap(&sub_mask.parameters, width, height, scale, crop_offset),
         "quick-eraser" => generate_ai_subject_bitmap(&sub_mask.parameters, width, height, scale, crop_offset),
+        "luminance" => image.map(|image| generate_luminance_bitmap(&sub_mask.parameters, image, width, height)),
+        "group" => composite_sub_masks(&sub_mask.sub_masks, width, height, scale, crop_offset, image),
         _ => None,
     }
 }
 
-pub fn generate_mask_bitmap(
-    mask_def: &MaskDefinition,
+fn composite_sub_masks(
+    sub_masks: &[SubMask],
     width: u32,
     height: u32,
     scale: f32,
     crop_offset: (f32, f32),
+    image: Option<&DynamicImage>,
 ) -> Option<GrayImage> {
-    if !mask_def.visible || mask_def.sub_masks.is_empty() {
+    if sub_masks.is_empty() {
         return None;
     }
 
     let mut additive_canvas = GrayImage::new(width, height);
     let mut subtractive_canvas = GrayImage::new(width, height);
+    let mut intersect_canvas: Option<GrayImage> = None;
 
-    for sub_mask in &mask_def.sub_masks {
-        if let Some(sub_bitmap) = generate_sub_mask_bitmap(sub_mask, width, height, scale, crop_offset) {
-            match sub_mask.mode {
-                SubMaskMode::Additive => {
+    for sub_mask in sub_masks {
+        let sub_bitmap = generate_sub_mask_bitmap(sub_mask, width, height, scale, crop_offset, image);
+        match sub_mask.mode {
+            SubMaskMode::Additive => {
+                if let Some(sub_bitmap) = sub_bitmap {
                     for (x, y, pixel) in additive_canvas.enumerate_pixels_mut() {
                         let sub_pixel = sub_bitmap.get_pixel(x, y);
                         pixel[0] = pixel[0].max(sub_pixel[0]);
                     }
                 }
-                SubMaskMode::Subtractive => {
+            }
+            SubMaskMode::Subtractive => {
+                if let Some(sub_bitmap) = sub_bitmap {
                     for (x, y, pixel) in subtractive_canvas.enumerate_pixels_mut() {
                         let sub_pixel = sub_bitmap.get_pixel(x, y);
                         pixel[0] = pixel[0].max(sub_pixel[0]);
                     }
                 }
             }
+            SubMaskMode::Intersect => {
+                if !sub_mask.visible {
+                    continue;
+                }
+                let sub_bitmap = sub_bitmap.unwrap_or_else(|| GrayImage::new(width, height));
+                match intersect_canvas.as_mut() {
+                    Some(canvas) => {
+                        for (x, y, pixel) in canvas.enumerate_pixels_mut() {
+                            let sub_pixel = sub_bitmap.get_pixel(x, y);
+                            pixel[0] = pixel[0].min(sub_pixel[0]);
+                        }
+                    }
+                    None => intersect_canvas = Some(sub_bitmap),
+                }
+            }
+        }
+    }
+
+    if let Some(intersect_canvas) = &intersect_canvas {
+        for (x, y, pixel) in additive_canvas.enumerate_pixels_mut() {
+            let intersect_pixel = intersect_canvas.get_pixel(x, y);
+            pixel[0] = pixel[0].min(intersect_pixel[0]);
         }
     }
 
@@ -615,18 +749,75 @@ pub fn generate_mask_bitmap(
         final_pixel[0] = final_pixel[0].saturating_sub(subtractive_pixel[0]);
     }
 
+    Some(additive_canvas)
+}
+
+pub fn generate_mask_bitmap(
+    mask_def: &MaskDefinition,
+    width: u32,
+    height: u32,
+    scale: f32,
+    crop_offset: (f32, f32),
+    image: Option<&DynamicImage>,
+) -> Option<GrayImage> {
+    if !mask_def.visible {
+        return None;
+    }
+
+    let mut final_canvas = composite_sub_masks(&mask_def.sub_masks, width, height, scale, crop_offset, image)?;
+
     if mask_def.invert {
-        for pixel in additive_canvas.pixels_mut() {
+        for pixel in final_canvas.pixels_mut() {
             pixel[0] = 255 - pixel[0];
         }
     }
 
     let opacity_multiplier = (mask_def.opacity / 100.0).clamp(0.0, 1.0);
     if opacity_multiplier < 1.0 {
-        for pixel in additive_canvas.pixels_mut() {
+        for pixel in final_canvas.pixels_mut() {
             pixel[0] = (pixel[0] as f32 * opacity_multiplier) as u8;
         }
     }
 
-    Some(additive_canvas)
-}
\ No newline at end of file
+    Some(final_canvas)
+}
+
+pub fn mask_definition_from_ai_patch(
+    patch: &AiPatchDefinition,
+    rotation: f32,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    orientation_steps: u8,
+) -> Result<MaskDefinition, String> {
+    let patch_data = patch
+        .patch_data
+        .as_ref()
+        .ok_or("This AI patch has not been generated yet.")?;
+
+    let patch_sub_mask = SubMask {
+        id: Uuid::new_v4().to_string(),
+        mask_type: "ai-patch".to_string(),
+        visible: true,
+        mode: SubMaskMode::Additive,
+        parameters: serde_json::json!({
+            "maskDataBase64": patch_data.mask,
+            "rotation": rotation,
+            "flipHorizontal": flip_horizontal,
+            "flipVertical": flip_vertical,
+            "orientationSteps": orientation_steps,
+            "grow": 0.0,
+            "feather": 0.0,
+        }),
+        sub_masks: Vec::new(),
+    };
+
+    Ok(MaskDefinition {
+        id: Uuid::new_v4().to_string(),
+        name: format!("{} Mask", patch.name),
+        visible: true,
+        invert: patch.invert,
+        opacity: 100.0,
+        adjustments: serde_json::json!({}),
+        sub_masks: vec![patch_sub_mask],
+    })
+}
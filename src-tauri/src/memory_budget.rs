@@ -0,0 +1,129 @@
+use image::DynamicImage;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::file_management::load_settings;
+use crate::AppState;
+
+const DEFAULT_MEMORY_BUDGET_MB: u64 = 4096;
+const MIN_MEMORY_BUDGET_MB: u64 = 512;
+const THUMBNAIL_WORKING_SET_MB: u64 = 384;
+const EXPORT_WORKING_SET_MB: u64 = 1536;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsage {
+    pub budget_bytes: u64,
+    pub original_image_bytes: u64,
+    pub cached_preview_bytes: u64,
+    pub prefetch_bytes: u64,
+    pub ai_embeddings_bytes: u64,
+    pub total_bytes: u64,
+}
+
+pub fn image_bytes(image: &DynamicImage) -> u64 {
+    image.as_bytes().len() as u64
+}
+
+fn budget_mb(app_handle: &AppHandle) -> u64 {
+    load_settings(app_handle.clone())
+        .ok()
+        .and_then(|settings| settings.memory_budget_mb)
+        .map_or(DEFAULT_MEMORY_BUDGET_MB, |mb| mb as u64)
+        .max(MIN_MEMORY_BUDGET_MB)
+}
+
+fn measure(state: &AppState, budget_bytes: u64) -> MemoryUsage {
+    let loaded_image = state.original_image.lock().unwrap().as_ref().map(|loaded| loaded.image.clone());
+    let original_image_bytes = loaded_image.as_ref().map_or(0, |image| image_bytes(image));
+    let cached_preview_bytes = state
+        .cached_preview
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map_or(0, |preview| image_bytes(&preview.image))
+        + state
+            .cached_full_resolution
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(0, |cached| image_bytes(&cached.image));
+    let prefetch_bytes = state.prefetch_cache.total_bytes(loaded_image.as_ref());
+    let ai_embeddings_bytes = state
+        .ai_state
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|ai| ai.embeddings.as_ref())
+        .map_or(0, |e| (e.embeddings.len() * std::mem::size_of::<f32>()) as u64);
+
+    MemoryUsage {
+        budget_bytes,
+        original_image_bytes,
+        cached_preview_bytes,
+        prefetch_bytes,
+        ai_embeddings_bytes,
+        total_bytes: original_image_bytes + cached_preview_bytes + prefetch_bytes + ai_embeddings_bytes,
+    }
+}
+
+pub fn enforce_memory_budget(app_handle: &AppHandle) -> MemoryUsage {
+    let state = app_handle.state::<AppState>();
+    let budget_bytes = budget_mb(app_handle) * BYTES_PER_MB;
+    let mut usage = measure(&state, budget_bytes);
+
+    while usage.total_bytes > budget_bytes {
+        match state.prefetch_cache.evict_oldest() {
+            Some(freed) => {
+                usage.prefetch_bytes -= freed;
+                usage.total_bytes -= freed;
+            }
+            None => break,
+        }
+    }
+
+    if usage.total_bytes > budget_bytes && usage.ai_embeddings_bytes > 0 {
+        if let Some(ai_state) = state.ai_state.lock().unwrap().as_mut() {
+            ai_state.embeddings = None;
+        }
+        usage.total_bytes -= usage.ai_embeddings_bytes;
+        usage.ai_embeddings_bytes = 0;
+    }
+
+    if usage.total_bytes > budget_bytes && usage.cached_preview_bytes > 0 {
+        *state.cached_preview.lock().unwrap() = None;
+        *state.cached_full_resolution.lock().unwrap() = None;
+        usage.total_bytes -= usage.cached_preview_bytes;
+        usage.cached_preview_bytes = 0;
+    }
+
+    if usage.total_bytes > budget_bytes {
+        eprintln!(
+            "Memory usage ({} MB) exceeds the budget ({} MB) after evicting caches.",
+            usage.total_bytes / BYTES_PER_MB,
+            budget_bytes / BYTES_PER_MB
+        );
+    }
+    usage
+}
+
+pub fn thumbnail_thread_count(app_handle: &AppHandle) -> usize {
+    let by_budget = (budget_mb(app_handle) / THUMBNAIL_WORKING_SET_MB).max(1) as usize;
+    by_budget.min(rayon::current_num_threads())
+}
+
+pub fn export_thread_count(app_handle: &AppHandle) -> usize {
+    let by_budget = (budget_mb(app_handle) / EXPORT_WORKING_SET_MB).max(1) as usize;
+    let limit = by_budget.min(rayon::current_num_threads());
+    let configured = load_settings(app_handle.clone())
+        .ok()
+        .and_then(|settings| settings.export_worker_count)
+        .filter(|&count| count > 0);
+    configured.map_or(limit, |count| (count as usize).min(limit))
+}
+
+#[tauri::command]
+pub fn get_memory_usage(app_handle: AppHandle) -> MemoryUsage {
+    enforce_memory_budget(&app_handle)
+}
@@ -0,0 +1,84 @@
+use image::{imageops, DynamicImage, GenericImageView, ImageBuffer, Luma};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::image_processing::with_depth_of;
+
+const DEFAULT_PRINT_RESOLUTION: f32 = 300.0;
+// Screen exports larger than this are viewed scaled down, which shrinks the halo along with them.
+const SCREEN_REFERENCE_LONG_EDGE: f32 = 2048.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SharpeningMedia {
+    Screen,
+    Matte,
+    Glossy,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SharpeningAmount {
+    Low,
+    Standard,
+    High,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputSharpeningSettings {
+    pub media: SharpeningMedia,
+    pub amount: SharpeningAmount,
+    pub print_resolution: Option<u32>,
+}
+
+impl OutputSharpeningSettings {
+    fn radius(&self, long_edge: u32) -> f32 {
+        let print_scale = self.print_resolution.map(|ppi| ppi as f32).unwrap_or(DEFAULT_PRINT_RESOLUTION) / DEFAULT_PRINT_RESOLUTION;
+        match self.media {
+            SharpeningMedia::Screen => 0.5 * (long_edge as f32 / SCREEN_REFERENCE_LONG_EDGE).clamp(1.0, 3.0),
+            SharpeningMedia::Matte => 1.0 * print_scale,
+            SharpeningMedia::Glossy => 0.75 * print_scale,
+        }
+    }
+
+    fn strength(&self) -> f32 {
+        match (self.media, self.amount) {
+            (SharpeningMedia::Screen, SharpeningAmount::Low) => 0.35,
+            (SharpeningMedia::Screen, SharpeningAmount::Standard) => 0.6,
+            (SharpeningMedia::Screen, SharpeningAmount::High) => 0.9,
+            (SharpeningMedia::Matte, SharpeningAmount::Low) => 0.6,
+            (SharpeningMedia::Matte, SharpeningAmount::Standard) => 0.9,
+            (SharpeningMedia::Matte, SharpeningAmount::High) => 1.3,
+            (SharpeningMedia::Glossy, SharpeningAmount::Low) => 0.45,
+            (SharpeningMedia::Glossy, SharpeningAmount::Standard) => 0.7,
+            (SharpeningMedia::Glossy, SharpeningAmount::High) => 1.0,
+        }
+    }
+}
+
+pub fn apply_output_sharpening(image: &DynamicImage, settings: &OutputSharpeningSettings) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    if width < 3 || height < 3 {
+        return image.clone();
+    }
+    let mut rgba = image.to_rgba32f();
+
+    let luminance: ImageBuffer<Luma<f32>, Vec<f32>> = ImageBuffer::from_fn(width, height, |x, y| {
+        let p = rgba.get_pixel(x, y);
+        Luma([0.2126 * p[0] + 0.7152 * p[1] + 0.0722 * p[2]])
+    });
+    let blurred = imageops::blur(&luminance, settings.radius(width.max(height)));
+    let strength = settings.strength();
+
+    rgba.par_chunks_mut(4)
+        .zip(luminance.par_iter().zip(blurred.par_iter()))
+        .for_each(|(pixel, (l, b))| {
+            let detail = (l - b) * strength;
+            for value in &mut pixel[..3] {
+                *value += detail;
+            }
+        });
+
+    with_depth_of(image, rgba)
+}
@@ -0,0 +1,60 @@
+struct PanoramaBlendParams {
+    region_x: u32,
+    region_y: u32,
+    width: u32,
+    height: u32,
+    orientation: u32,
+    use_seam: u32,
+    new_is_dominant: u32,
+    feather_width: f32,
+}
+
+// `panorama` holds the canvas region (alpha 1 where the canvas is already covered) and is blended in
+// place. `warped` is the new frame on the same region, with the warp's coverage/low-detail alpha.
+@group(0) @binding(0) var<storage, read_write> panorama: array<vec4<f32>>;
+@group(0) @binding(1) var<storage, read> warped: array<vec4<f32>>;
+@group(0) @binding(2) var<storage, read> seam: array<i32>;
+@group(0) @binding(3) var<uniform> params: PanoramaBlendParams;
+
+const ORIENTATION_VERTICAL: u32 = 0u;
+const COVERED_ALPHA: f32 = 0.5;
+const LOW_DETAIL_FEATHER_SCALE: f32 = 5.0;
+const PI: f32 = 3.14159265;
+
+// Mirrors the CPU path in panorama_utils/stitching.rs.
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.width || id.y >= params.height) { return; }
+    let index = id.y * params.width + id.x;
+    let add = warped[index];
+    if (add.a <= 0.0) { return; }
+
+    let current = panorama[index];
+    if (current.a <= 0.0 || params.use_seam == 0u) {
+        panorama[index] = vec4<f32>(add.rgb, 1.0);
+        return;
+    }
+
+    let x = i32(params.region_x + id.x);
+    let y = i32(params.region_y + id.y);
+    var position = y;
+    var seam_position = seam[params.region_x + id.x];
+    if (params.orientation == ORIENTATION_VERTICAL) {
+        position = x;
+        seam_position = seam[params.region_y + id.y];
+    }
+    let dist_to_seam = f32(position - seam_position);
+    let feather_width = select(params.feather_width, params.feather_width * LOW_DETAIL_FEATHER_SCALE, add.a > COVERED_ALPHA);
+
+    if (abs(dist_to_seam) < feather_width / 2.0) {
+        let signed_dist = select(-dist_to_seam, dist_to_seam, params.new_is_dominant == 1u);
+        let alpha = clamp((signed_dist + feather_width / 2.0) / feather_width, 0.0, 1.0);
+        let weight_add = (1.0 - cos(alpha * PI)) / 2.0;
+        panorama[index] = vec4<f32>(mix(current.rgb, add.rgb, weight_add), 1.0);
+    } else {
+        let new_owns_pixel = select(position < seam_position, position > seam_position, params.new_is_dominant == 1u);
+        if (new_owns_pixel) {
+            panorama[index] = vec4<f32>(add.rgb, 1.0);
+        }
+    }
+}
@@ -1,16 +1,28 @@
-use image::{GrayImage, RgbImage};
-use nalgebra::Matrix3;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, GrayImage, ImageFormat, Rgb32FImage, RgbImage};
+use nalgebra::{Matrix3, Point2};
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::time::Instant;
 use tauri::{AppHandle, Emitter};
 use std::fs;
+use std::io::{BufWriter, Cursor};
 use std::path::Path;
+use tiff::encoder::{colortype, Rational, SRational, TiffEncoder};
+use tiff::tags::Tag as TiffTag;
 
-use crate::panorama_utils::{processing, stitching};
+use crate::image_processing::GpuContext;
+use crate::iptc_metadata::embed_jpeg_xmp;
+use crate::panorama_utils::projection::{self, PanoramaProjection, ProjectionMode};
+use crate::panorama_utils::boundary::{self, PanoramaBoundary};
+use crate::panorama_utils::{exposure, processing, stitching};
 
 pub const BRIEF_DESCRIPTOR_SIZE: usize = 256;
 pub type Descriptor = [u8; BRIEF_DESCRIPTOR_SIZE / 8];
+const MAX_CONTROL_POINT_KEYPOINTS: usize = 300;
+// User control points outrank any automatic match when building the stitching order.
+const CONTROL_POINT_INLIERS: usize = 10_000;
 
 #[derive(Debug, Clone, Copy)]
 pub struct KeyPoint {
@@ -32,7 +44,7 @@ pub struct Match {
 pub struct ImageInfo {
     pub id: usize,
     pub filename: String,
-    pub color_full: RgbImage,
+    pub color_full: Rgb32FImage,
     pub low_detail_mask: GrayImage,
     pub scale_factor: f64,
     pub features: Vec<Feature>,
@@ -44,10 +56,161 @@ pub struct MatchInfo {
     pub inliers: usize,
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StitchOptions {
+    pub blend_mode: stitching::BlendMode,
+    pub projection: ProjectionMode,
+    pub exposure_compensation: bool,
+    pub boundary: PanoramaBoundary,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PanoramaOutputFormat {
+    #[default]
+    Tiff,
+    Dng,
+    Jpeg,
+    Png,
+}
+
+impl PanoramaOutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PanoramaOutputFormat::Tiff => "tif",
+            PanoramaOutputFormat::Dng => "dng",
+            PanoramaOutputFormat::Jpeg => "jpg",
+            PanoramaOutputFormat::Png => "png",
+        }
+    }
+}
+
+pub struct StitchedPanorama {
+    pub image: Rgb32FImage,
+    pub projection: ProjectionMode,
+}
+
+// A user-picked correspondence between two of the input images, in full-resolution pixels.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlPoint {
+    pub image_a: usize,
+    pub x_a: f64,
+    pub y_a: f64,
+    pub image_b: usize,
+    pub x_b: f64,
+    pub y_b: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlPointImage {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    pub keypoints: Vec<[f64; 2]>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchedPair {
+    pub image_a: usize,
+    pub image_b: usize,
+    pub inliers: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlPointRequest {
+    pub message: String,
+    pub images: Vec<ControlPointImage>,
+    pub matched_pairs: Vec<MatchedPair>,
+}
+
+fn emit_control_point_request(
+    app_handle: &AppHandle,
+    image_data: &[ImageInfo],
+    pairwise_matches: &HashMap<(usize, usize), MatchInfo>,
+    message: &str,
+) {
+    let images = image_data
+        .iter()
+        .map(|img_info| {
+            let step = (img_info.features.len() / MAX_CONTROL_POINT_KEYPOINTS).max(1);
+            ControlPointImage {
+                path: img_info.filename.clone(),
+                width: img_info.color_full.width(),
+                height: img_info.color_full.height(),
+                keypoints: img_info
+                    .features
+                    .iter()
+                    .step_by(step)
+                    .take(MAX_CONTROL_POINT_KEYPOINTS)
+                    .map(|f| [f.keypoint.x as f64 * img_info.scale_factor, f.keypoint.y as f64 * img_info.scale_factor])
+                    .collect(),
+            }
+        })
+        .collect();
+    let matched_pairs = pairwise_matches
+        .iter()
+        .map(|(&(image_a, image_b), m)| MatchedPair { image_a, image_b, inliers: m.inliers })
+        .collect();
+
+    let _ = app_handle.emit(
+        "panorama-control-points-needed",
+        ControlPointRequest { message: message.to_string(), images, matched_pairs },
+    );
+}
+
+fn control_point_matches(
+    image_data: &[ImageInfo],
+    control_points: &[ControlPoint],
+) -> Result<HashMap<(usize, usize), MatchInfo>, String> {
+    let mut grouped: HashMap<(usize, usize), Vec<(Point2<f64>, Point2<f64>)>> = HashMap::new();
+    for cp in control_points {
+        if cp.image_a >= image_data.len() || cp.image_b >= image_data.len() || cp.image_a == cp.image_b {
+            return Err("Control point references an invalid image pair.".to_string());
+        }
+        // Keep the (lower, higher) key order used by the automatic matches.
+        let ((i, xi, yi), (j, xj, yj)) = if cp.image_a < cp.image_b {
+            ((cp.image_a, cp.x_a, cp.y_a), (cp.image_b, cp.x_b, cp.y_b))
+        } else {
+            ((cp.image_b, cp.x_b, cp.y_b), (cp.image_a, cp.x_a, cp.y_a))
+        };
+        let (si, sj) = (image_data[i].scale_factor, image_data[j].scale_factor);
+        grouped
+            .entry((i, j))
+            .or_default()
+            .push((Point2::new(xi / si, yi / si), Point2::new(xj / sj, yj / sj)));
+    }
+
+    let mut matches = HashMap::new();
+    for ((i, j), points) in grouped {
+        let name_i = Path::new(&image_data[i].filename).file_name().unwrap_or_default().to_string_lossy().to_string();
+        let name_j = Path::new(&image_data[j].filename).file_name().unwrap_or_default().to_string_lossy().to_string();
+        if points.len() < 4 {
+            return Err(format!("At least 4 control points are needed between '{}' and '{}'.", name_i, name_j));
+        }
+        let h_small = processing::compute_homography(&points)
+            .ok_or_else(|| format!("Control points between '{}' and '{}' are degenerate.", name_i, name_j))?;
+
+        let s1 = image_data[i].scale_factor;
+        let s2 = image_data[j].scale_factor;
+        let scale_mat_i_inv = Matrix3::new(1.0 / s1, 0.0, 0.0, 0.0, 1.0 / s1, 0.0, 0.0, 0.0, 1.0);
+        let scale_mat_j = Matrix3::new(s2, 0.0, 0.0, 0.0, s2, 0.0, 0.0, 0.0, 1.0);
+        println!("  - Using {} control points for '{}' <-> '{}'", points.len(), name_i, name_j);
+        matches.insert((i, j), MatchInfo { homography: scale_mat_j * h_small * scale_mat_i_inv, inliers: CONTROL_POINT_INLIERS });
+    }
+    Ok(matches)
+}
+
 pub fn stitch_images(
     image_paths: Vec<String>,
+    options: StitchOptions,
+    control_points: &[ControlPoint],
+    gpu_context: Option<&GpuContext>,
     app_handle: AppHandle,
-) -> Result<RgbImage, String> {
+) -> Result<StitchedPanorama, String> {
     if image_paths.len() < 2 {
         return Err("At least two images are required for a panorama.".to_string());
     }
@@ -71,8 +234,8 @@ pub fn stitch_images(
             let dynamic_image = crate::image_loader::load_base_image_from_bytes(&file_bytes, filename, false)
                 .map_err(|e| format!("Failed to load image {}: {}", filename, e))?;
             
-            let color_full = dynamic_image.to_rgb8();
-            let gray_full = image::imageops::colorops::grayscale(&color_full);
+            let color_full = dynamic_image.to_rgb32f();
+            let gray_full = dynamic_image.to_luma8();
 
             let (w, h) = gray_full.dimensions();
             let (new_w, new_h, scale_factor) =
@@ -164,25 +327,41 @@ pub fn stitch_images(
     for result in match_results.into_iter().flatten() {
         pairwise_matches.insert(result.0, result.1);
     }
+    pairwise_matches.extend(control_point_matches(&image_data, control_points)?);
     println!("Pairwise matching completed in {:.2?}\n", start_time.elapsed());
 
     if pairwise_matches.is_empty() {
-        return Err("No suitable matches found between any pair of images. Cannot create a panorama.".to_string());
+        let message = "No suitable matches found between any pair of images. Cannot create a panorama.";
+        emit_control_point_request(&app_handle, &image_data, &pairwise_matches, message);
+        return Err(message.to_string());
     }
 
     let start_time = Instant::now();
     let _ = app_handle.emit("panorama-progress", "Determining stitching order...");
     println!("Determining stitching order...");
-    let (ordered_indices, global_homographies) = build_stitching_order(&image_data, &pairwise_matches);
+    let (ordered_indices, mut global_homographies) = build_stitching_order(&image_data, &pairwise_matches);
     
     if ordered_indices.len() < 2 {
-        return Err("Could not find a connected sequence of at least two images.".to_string());
+        let message = "Could not find a connected sequence of at least two images.";
+        emit_control_point_request(&app_handle, &image_data, &pairwise_matches, message);
+        return Err(message.to_string());
     }
 
     let ordered_filenames: Vec<_> = ordered_indices.iter().map(|&i| Path::new(&image_data[i].filename).file_name().unwrap_or_default().to_string_lossy().to_string()).collect();
     println!("Stitching order determined: {:?}", ordered_filenames);
     let _ = app_handle.emit("panorama-progress", format!("Stitching order: {}", ordered_filenames.join(" -> ")));
     
+    if options.exposure_compensation {
+        let _ = app_handle.emit("panorama-progress", "Compensating exposure between images...");
+        println!("Estimating exposure gains from overlaps...");
+        let ordered_images: Vec<&ImageInfo> = ordered_indices.iter().map(|&i| &image_data[i]).collect();
+        let gains = exposure::estimate_gains(&ordered_images, &global_homographies);
+        for (&i, gain) in ordered_indices.iter().zip(gains) {
+            println!("  - '{}': gain R {:.3}, G {:.3}, B {:.3}", image_data[i].filename, gain[0], gain[1], gain[2]);
+            exposure::apply_gain(&mut image_data[i].color_full, gain);
+        }
+    }
+
     let stitched_images_info: Vec<&ImageInfo> = ordered_indices.iter().map(|&i| &image_data[i]).collect();
     let unstitched_count = image_data.len() - stitched_images_info.len();
     if unstitched_count > 0 {
@@ -190,18 +369,48 @@ pub fn stitch_images(
         println!("{}", warning_msg);
         let _ = app_handle.emit("panorama-warning", warning_msg);
     }
+
+    let reference_id = if options.projection == ProjectionMode::Rectilinear {
+        stitched_images_info[0].id
+    } else {
+        projection::recenter_homographies(&stitched_images_info, &mut global_homographies)
+    };
+    let projection = PanoramaProjection::new(options.projection, reference_id, &stitched_images_info, &global_homographies);
+    println!("Using {:?} projection", options.projection);
     println!("Global homography calculation completed in {:.2?}\n", start_time.elapsed());
 
     let start_time = Instant::now();
     let _ = app_handle.emit("panorama-progress", "Warping and blending images...");
     println!("Warping and blending full-resolution images with progressive optimal seams...");
+    if gpu_context.is_none() {
+        println!("  - No GPU context available, warping on the CPU");
+    }
 
-    let panorama = stitching::progressive_seam_stitcher(&stitched_images_info, &global_homographies, app_handle.clone());
+    let (panorama, coverage) = match options.blend_mode {
+        stitching::BlendMode::Feather => stitching::progressive_seam_stitcher(&stitched_images_info, &global_homographies, &projection, gpu_context, app_handle.clone()),
+        stitching::BlendMode::MultiBand => stitching::multi_band_stitcher(&stitched_images_info, &global_homographies, &projection, gpu_context, app_handle.clone()),
+    };
     
     println!("Stitching completed in {:.2?}\n", start_time.elapsed());
 
+    let panorama = match options.boundary {
+        PanoramaBoundary::Keep => panorama,
+        PanoramaBoundary::Crop if options.projection == ProjectionMode::Equirectangular => {
+            println!("Skipping auto crop: a 360° panorama has to keep its full 2:1 frame.");
+            panorama
+        }
+        PanoramaBoundary::Crop => {
+            let _ = app_handle.emit("panorama-progress", "Cropping to the largest full rectangle...");
+            boundary::crop_to_coverage(&panorama, &coverage)
+        }
+        PanoramaBoundary::Fill => {
+            let _ = app_handle.emit("panorama-progress", "Filling empty edges...");
+            boundary::fill_uncovered(&panorama, &coverage)?
+        }
+    };
+
     let _ = app_handle.emit("panorama-progress", "Finalizing panorama...");
-    Ok(panorama)
+    Ok(StitchedPanorama { image: panorama, projection: options.projection })
 }
 
 struct DSU {
@@ -307,4 +516,111 @@ fn build_stitching_order(
     }
 
     (ordered_indices, global_homographies)
-}
\ No newline at end of file
+}
+
+// XYZ (D65) to linear sRGB, which is the "camera" space of a DNG written from sRGB data.
+const SRGB_COLOR_MATRIX: [i32; 9] = [32406, -15372, -4986, -9689, 18758, 415, 557, -2040, 10570];
+const DNG_CALIBRATION_ILLUMINANT_D65: u16 = 21;
+const DNG_PHOTOMETRIC_LINEAR_RAW: u16 = 34892;
+
+// Google Photo Sphere metadata, which viewers and web platforms use to detect 360° images.
+fn gpano_xmp(width: u32, height: u32) -> String {
+    format!(
+        concat!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n",
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n",
+            " <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n",
+            "  <rdf:Description rdf:about=\"\" xmlns:GPano=\"http://ns.google.com/photos/1.0/panorama/\">\n",
+            "   <GPano:ProjectionType>equirectangular</GPano:ProjectionType>\n",
+            "   <GPano:UsePanoramaViewer>True</GPano:UsePanoramaViewer>\n",
+            "   <GPano:StitchingSoftware>RapidRAW</GPano:StitchingSoftware>\n",
+            "   <GPano:CroppedAreaImageWidthPixels>{w}</GPano:CroppedAreaImageWidthPixels>\n",
+            "   <GPano:CroppedAreaImageHeightPixels>{h}</GPano:CroppedAreaImageHeightPixels>\n",
+            "   <GPano:FullPanoWidthPixels>{w}</GPano:FullPanoWidthPixels>\n",
+            "   <GPano:FullPanoHeightPixels>{h}</GPano:FullPanoHeightPixels>\n",
+            "   <GPano:CroppedAreaLeftPixels>0</GPano:CroppedAreaLeftPixels>\n",
+            "   <GPano:CroppedAreaTopPixels>0</GPano:CroppedAreaTopPixels>\n",
+            "  </rdf:Description>\n",
+            " </rdf:RDF>\n",
+            "</x:xmpmeta>\n",
+            "<?xpacket end=\"w\"?>",
+        ),
+        w = width,
+        h = height,
+    )
+}
+
+fn write_tiff16(image: &Rgb32FImage, path: &Path, xmp: Option<&str>) -> Result<(), String> {
+    let data: Vec<u16> = image.as_raw().par_iter().map(|v| (v.clamp(0.0, 1.0) * 65535.0).round() as u16).collect();
+    let file = fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut encoder = TiffEncoder::new(BufWriter::new(file)).map_err(|e| e.to_string())?;
+    let (width, height) = image.dimensions();
+    let mut tiff_image = encoder.new_image::<colortype::RGB16>(width, height).map_err(|e| e.to_string())?;
+    if let Some(xmp) = xmp {
+        tiff_image.encoder().write_tag(TiffTag::Unknown(700), xmp.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    tiff_image.write_data(&data).map_err(|e| e.to_string())
+}
+
+fn write_jpeg(image: &RgbImage, path: &Path, xmp: Option<&str>) -> Result<(), String> {
+    let mut buf = Cursor::new(Vec::new());
+    image.write_with_encoder(JpegEncoder::new_with_quality(&mut buf, 95))
+        .map_err(|e| format!("Failed to encode panorama image: {}", e))?;
+    let mut bytes = buf.into_inner();
+
+    if let Some(xmp) = xmp {
+        embed_jpeg_xmp(&mut bytes, xmp)?;
+    }
+
+    fs::write(path, bytes).map_err(|e| format!("Failed to save panorama image: {}", e))
+}
+
+// Writes the panorama as a linear DNG so it opens with the same raw pipeline as the source frames.
+fn write_linear_dng(image: &Rgb32FImage, path: &Path, xmp: Option<&str>) -> Result<(), String> {
+    let data: Vec<u16> = image.as_raw().par_iter().map(|v| {
+        let srgb = v.clamp(0.0, 1.0) as f64;
+        let linear = if srgb <= 0.04045 { srgb / 12.92 } else { ((srgb + 0.055) / 1.055).powf(2.4) };
+        (linear * 65535.0).round() as u16
+    }).collect();
+
+    let file = fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut encoder = TiffEncoder::new(BufWriter::new(file)).map_err(|e| e.to_string())?;
+    let (width, height) = image.dimensions();
+    let mut dng = encoder.new_image::<colortype::RGB16>(width, height).map_err(|e| e.to_string())?;
+
+    let color_matrix: Vec<SRational> = SRGB_COLOR_MATRIX.iter().map(|&n| SRational { n, d: 10000 }).collect();
+    let neutral = [Rational { n: 1, d: 1 }, Rational { n: 1, d: 1 }, Rational { n: 1, d: 1 }];
+    let directory = dng.encoder();
+    directory.write_tag(TiffTag::PhotometricInterpretation, DNG_PHOTOMETRIC_LINEAR_RAW).map_err(|e| e.to_string())?;
+    directory.write_tag(TiffTag::Unknown(50706), &[1u8, 4, 0, 0][..]).map_err(|e| e.to_string())?;
+    directory.write_tag(TiffTag::Unknown(50707), &[1u8, 1, 0, 0][..]).map_err(|e| e.to_string())?;
+    directory.write_tag(TiffTag::Unknown(50708), "RapidRAW Panorama").map_err(|e| e.to_string())?;
+    directory.write_tag(TiffTag::Unknown(50721), &color_matrix[..]).map_err(|e| e.to_string())?;
+    directory.write_tag(TiffTag::Unknown(50728), &neutral[..]).map_err(|e| e.to_string())?;
+    directory.write_tag(TiffTag::Unknown(50778), DNG_CALIBRATION_ILLUMINANT_D65).map_err(|e| e.to_string())?;
+    if let Some(xmp) = xmp {
+        directory.write_tag(TiffTag::Unknown(700), xmp.as_bytes()).map_err(|e| e.to_string())?;
+    }
+
+    dng.write_data(&data).map_err(|e| e.to_string())
+}
+
+pub fn save_panorama_image(panorama: &StitchedPanorama, path: &Path, format: PanoramaOutputFormat) -> Result<(), String> {
+    let image = &panorama.image;
+    let xmp = (panorama.projection == ProjectionMode::Equirectangular)
+        .then(|| gpano_xmp(image.width(), image.height()));
+    match format {
+        PanoramaOutputFormat::Tiff => write_tiff16(image, path, xmp.as_deref()),
+        PanoramaOutputFormat::Dng => write_linear_dng(image, path, xmp.as_deref()),
+        PanoramaOutputFormat::Jpeg => write_jpeg(&DynamicImage::ImageRgb32F(image.clone()).to_rgb8(), path, xmp.as_deref()),
+        PanoramaOutputFormat::Png => {
+            if xmp.is_some() {
+                println!("PNG output has no XMP support, the 360° metadata is not written.");
+            }
+            DynamicImage::ImageRgb32F(image.clone())
+                .to_rgb8()
+                .save_with_format(path, ImageFormat::Png)
+                .map_err(|e| format!("Failed to save panorama image: {}", e))
+        }
+    }
+}
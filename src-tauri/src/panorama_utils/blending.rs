@@ -0,0 +1,121 @@
+use rayon::prelude::*;
+
+const KERNEL: [f32; 5] = [1.0 / 16.0, 4.0 / 16.0, 6.0 / 16.0, 4.0 / 16.0, 1.0 / 16.0];
+
+pub trait BlendPixel: Copy + Default + Send + Sync {
+    fn scaled_add(self, other: Self, weight: f32) -> Self;
+}
+
+impl BlendPixel for f32 {
+    fn scaled_add(self, other: Self, weight: f32) -> Self {
+        self + other * weight
+    }
+}
+
+impl BlendPixel for [f32; 3] {
+    fn scaled_add(self, other: Self, weight: f32) -> Self {
+        [self[0] + other[0] * weight, self[1] + other[1] * weight, self[2] + other[2] * weight]
+    }
+}
+
+pub struct PyramidLevel<P> {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<P>,
+}
+
+impl<P: BlendPixel> PyramidLevel<P> {
+    pub fn new(width: usize, height: usize) -> Self {
+        PyramidLevel { width, height, data: vec![P::default(); width * height] }
+    }
+}
+
+fn blur<P: BlendPixel>(width: usize, height: usize, data: &[P]) -> Vec<P> {
+    let mut horizontal = vec![P::default(); data.len()];
+    horizontal.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+        let src = &data[y * width..(y + 1) * width];
+        for x in 0..width {
+            let mut acc = P::default();
+            for (k, weight) in KERNEL.iter().enumerate() {
+                let sx = (x as isize + k as isize - 2).clamp(0, width as isize - 1) as usize;
+                acc = acc.scaled_add(src[sx], *weight);
+            }
+            row[x] = acc;
+        }
+    });
+
+    let mut out = vec![P::default(); data.len()];
+    out.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+        for x in 0..width {
+            let mut acc = P::default();
+            for (k, weight) in KERNEL.iter().enumerate() {
+                let sy = (y as isize + k as isize - 2).clamp(0, height as isize - 1) as usize;
+                acc = acc.scaled_add(horizontal[sy * width + x], *weight);
+            }
+            row[x] = acc;
+        }
+    });
+    out
+}
+
+fn downsample<P: BlendPixel>(level: &PyramidLevel<P>) -> PyramidLevel<P> {
+    let blurred = blur(level.width, level.height, &level.data);
+    let width = (level.width / 2).max(1);
+    let height = (level.height / 2).max(1);
+    let mut data = vec![P::default(); width * height];
+    data.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+        let sy = (y * 2).min(level.height - 1);
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = blurred[sy * level.width + (x * 2).min(level.width - 1)];
+        }
+    });
+    PyramidLevel { width, height, data }
+}
+
+// The same expansion is used when building and collapsing, so reconstruction is exact.
+pub fn upsample<P: BlendPixel>(level: &PyramidLevel<P>, width: usize, height: usize) -> Vec<P> {
+    let mut expanded = vec![P::default(); width * height];
+    expanded.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+        let sy = (y / 2).min(level.height - 1);
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = level.data[sy * level.width + (x / 2).min(level.width - 1)];
+        }
+    });
+    blur(width, height, &expanded)
+}
+
+pub fn gaussian_pyramid<P: BlendPixel>(base: PyramidLevel<P>, bands: usize) -> Vec<PyramidLevel<P>> {
+    let mut pyramid = vec![base];
+    for _ in 0..bands {
+        let next = downsample(pyramid.last().unwrap());
+        pyramid.push(next);
+    }
+    pyramid
+}
+
+pub fn laplacian_pyramid<P: BlendPixel>(base: PyramidLevel<P>, bands: usize) -> Vec<PyramidLevel<P>> {
+    let mut pyramid = gaussian_pyramid(base, bands);
+    for i in 0..bands {
+        let (fine, coarse) = pyramid.split_at_mut(i + 1);
+        let fine = &mut fine[i];
+        let expanded = upsample(&coarse[0], fine.width, fine.height);
+        fine.data
+            .par_iter_mut()
+            .zip(expanded.par_iter())
+            .for_each(|(pixel, low)| *pixel = pixel.scaled_add(*low, -1.0));
+    }
+    pyramid
+}
+
+pub fn collapse<P: BlendPixel>(mut pyramid: Vec<PyramidLevel<P>>) -> PyramidLevel<P> {
+    let mut current = pyramid.pop().expect("pyramid must have at least one level");
+    while let Some(mut fine) = pyramid.pop() {
+        let expanded = upsample(&current, fine.width, fine.height);
+        fine.data
+            .par_iter_mut()
+            .zip(expanded.par_iter())
+            .for_each(|(pixel, low)| *pixel = pixel.scaled_add(*low, 1.0));
+        current = fine;
+    }
+    current
+}
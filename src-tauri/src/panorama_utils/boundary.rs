@@ -0,0 +1,75 @@
+use image::{DynamicImage, GrayImage, Luma, Rgb32FImage};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PanoramaBoundary {
+    #[default]
+    Keep,
+    Crop,
+    Fill,
+}
+
+// Largest axis-aligned rectangle that only contains covered pixels, using the histogram-stack
+// method row by row. Returns (x, y, width, height).
+pub fn largest_inscribed_rect(mask: &GrayImage) -> Option<(u32, u32, u32, u32)> {
+    let (width, height) = mask.dimensions();
+    let mut heights = vec![0u32; width as usize];
+    let mut best: Option<(u32, u32, u32, u32)> = None;
+    let mut best_area = 0u64;
+    let mut stack: Vec<usize> = Vec::with_capacity(width as usize + 1);
+
+    for y in 0..height {
+        for x in 0..width {
+            let column = &mut heights[x as usize];
+            *column = if mask.get_pixel(x, y)[0] > 0 { *column + 1 } else { 0 };
+        }
+
+        stack.clear();
+        for x in 0..=width as usize {
+            let current = if x < width as usize { heights[x] } else { 0 };
+            while let Some(&top) = stack.last() {
+                if heights[top] < current {
+                    break;
+                }
+                stack.pop();
+                let rect_height = heights[top];
+                let left = stack.last().map_or(0, |&i| i + 1);
+                let rect_width = (x - left) as u32;
+                let area = rect_width as u64 * rect_height as u64;
+                if area > best_area {
+                    best_area = area;
+                    best = Some((left as u32, y + 1 - rect_height, rect_width, rect_height));
+                }
+            }
+            stack.push(x);
+        }
+    }
+    best
+}
+
+pub fn crop_to_coverage(panorama: &Rgb32FImage, coverage: &GrayImage) -> Rgb32FImage {
+    match largest_inscribed_rect(coverage) {
+        Some((x, y, width, height)) => {
+            println!("  - Cropping panorama to {}x{} at ({}, {})", width, height, x, y);
+            image::imageops::crop_imm(panorama, x, y, width, height).to_image()
+        }
+        None => panorama.clone(),
+    }
+}
+
+pub fn fill_uncovered(panorama: &Rgb32FImage, coverage: &GrayImage) -> Result<Rgb32FImage, String> {
+    let (width, height) = panorama.dimensions();
+    let fill_mask = GrayImage::from_fn(width, height, |x, y| {
+        if coverage.get_pixel(x, y)[0] > 0 { Luma([0]) } else { Luma([255]) }
+    });
+    if fill_mask.pixels().all(|p| p[0] == 0) {
+        return Ok(panorama.clone());
+    }
+
+    let patch_radius = crate::calculate_dynamic_patch_radius(width, height);
+    println!("  - Filling uncovered panorama edges (patch radius {})", patch_radius);
+    let source = DynamicImage::ImageRgb32F(panorama.clone()).to_rgba32f();
+    let filled = crate::inpainting::perform_fast_inpaint_f32(&source, &fill_mask, patch_radius)?;
+    Ok(DynamicImage::ImageRgba32F(filled).to_rgb32f())
+}
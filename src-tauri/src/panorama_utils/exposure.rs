@@ -0,0 +1,140 @@
+use crate::panorama_stitching::ImageInfo;
+use image::Rgb32FImage;
+use nalgebra::{DMatrix, DVector, Matrix3, Point3};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+const SAMPLES_PER_SIDE: u32 = 128;
+// Error weights from Brown & Lowe: intensity noise (on a 0-255 scale) against a prior keeping gains near 1.
+const INTENSITY_SIGMA: f64 = 10.0;
+const GAIN_SIGMA: f64 = 0.1;
+const MIN_GAIN: f64 = 0.5;
+const MAX_GAIN: f64 = 2.0;
+
+fn srgb_to_linear(value: f64) -> f64 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f64) -> f64 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// Overlap statistics per ordered image pair: sample count and the mean linear color of each side.
+struct OverlapStats {
+    count: f64,
+    sum_self: [f64; 3],
+    sum_other: [f64; 3],
+}
+
+fn overlap_stats(
+    images: &[&ImageInfo],
+    global_homographies: &HashMap<usize, Matrix3<f64>>,
+) -> Vec<Vec<OverlapStats>> {
+    let inverses: Vec<Option<Matrix3<f64>>> =
+        images.iter().map(|img_info| global_homographies[&img_info.id].try_inverse()).collect();
+
+    images
+        .par_iter()
+        .enumerate()
+        .map(|(i, img_info)| {
+            let mut stats: Vec<OverlapStats> = (0..images.len())
+                .map(|_| OverlapStats { count: 0.0, sum_self: [0.0; 3], sum_other: [0.0; 3] })
+                .collect();
+            let h_i = global_homographies[&img_info.id];
+            let (w, h) = img_info.color_full.dimensions();
+            let step = (w.max(h) / SAMPLES_PER_SIDE).max(1);
+
+            for y in (0..h).step_by(step as usize) {
+                for x in (0..w).step_by(step as usize) {
+                    let plane_p = h_i * Point3::new(x as f64, y as f64, 1.0);
+                    let own = img_info.color_full.get_pixel(x, y);
+                    for (j, other) in images.iter().enumerate() {
+                        if j == i {
+                            continue;
+                        }
+                        let Some(h_j_inv) = inverses[j] else { continue; };
+                        let source_p = h_j_inv * plane_p;
+                        if source_p.z <= 0.0 {
+                            continue;
+                        }
+                        let (sx, sy) = (source_p.x / source_p.z, source_p.y / source_p.z);
+                        if sx < 0.0 || sy < 0.0 || sx >= other.color_full.width() as f64 || sy >= other.color_full.height() as f64 {
+                            continue;
+                        }
+                        let theirs = other.color_full.get_pixel(sx as u32, sy as u32);
+                        let entry = &mut stats[j];
+                        entry.count += 1.0;
+                        for c in 0..3 {
+                            entry.sum_self[c] += srgb_to_linear(own[c] as f64) * 255.0;
+                            entry.sum_other[c] += srgb_to_linear(theirs[c] as f64) * 255.0;
+                        }
+                    }
+                }
+            }
+            stats
+        })
+        .collect()
+}
+
+// Solves the Brown & Lowe gain compensation least-squares system independently per channel, so
+// white balance drift between frames is corrected along with exposure.
+pub fn estimate_gains(images: &[&ImageInfo], global_homographies: &HashMap<usize, Matrix3<f64>>) -> Vec<[f64; 3]> {
+    let n = images.len();
+    let stats = overlap_stats(images, global_homographies);
+    let alpha = 1.0 / (INTENSITY_SIGMA * INTENSITY_SIGMA);
+    let beta = 1.0 / (GAIN_SIGMA * GAIN_SIGMA);
+    let mut gains = vec![[1.0; 3]; n];
+
+    for c in 0..3 {
+        let mut a = DMatrix::<f64>::zeros(n, n);
+        let mut b = DVector::<f64>::zeros(n);
+        for i in 0..n {
+            for j in 0..n {
+                if i == j {
+                    continue;
+                }
+                // Pairs are sampled from both sides; average them so the system stays symmetric.
+                let count = (stats[i][j].count + stats[j][i].count) / 2.0;
+                if count <= 0.0 {
+                    continue;
+                }
+                let mean_i = (stats[i][j].sum_self[c] + stats[j][i].sum_other[c]) / (stats[i][j].count + stats[j][i].count);
+                let mean_j = (stats[i][j].sum_other[c] + stats[j][i].sum_self[c]) / (stats[i][j].count + stats[j][i].count);
+                a[(i, i)] += count * (alpha * mean_i * mean_i + beta);
+                a[(i, j)] -= count * alpha * mean_i * mean_j;
+                b[i] += count * beta;
+            }
+            if a[(i, i)] == 0.0 {
+                a[(i, i)] = 1.0;
+                b[i] = 1.0;
+            }
+        }
+
+        match a.lu().solve(&b) {
+            Some(solution) => {
+                for (gain, value) in gains.iter_mut().zip(solution.iter()) {
+                    gain[c] = value.clamp(MIN_GAIN, MAX_GAIN);
+                }
+            }
+            None => println!("  - Gain compensation system was singular for channel {}", c),
+        }
+    }
+    gains
+}
+
+pub fn apply_gain(image: &mut Rgb32FImage, gain: [f64; 3]) {
+    image.par_chunks_mut(3).for_each(|pixel| {
+        for c in 0..3 {
+            let linear = srgb_to_linear(pixel[c] as f64) * gain[c];
+            pixel[c] = linear_to_srgb(linear.clamp(0.0, 1.0)) as f32;
+        }
+    });
+}
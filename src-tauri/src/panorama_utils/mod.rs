@@ -1,2 +1,7 @@
+pub mod blending;
+pub mod boundary;
+pub mod exposure;
 pub mod processing;
-pub mod stitching;
\ No newline at end of file
+pub mod projection;
+pub mod stitching;
+pub mod warping;
\ No newline at end of file
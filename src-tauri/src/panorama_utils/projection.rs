@@ -0,0 +1,212 @@
+use crate::panorama_stitching::ImageInfo;
+use nalgebra::{Matrix3, Point3};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ProjectionMode {
+    #[default]
+    Rectilinear,
+    Cylindrical,
+    Spherical,
+    Equirectangular,
+}
+
+// Maps between the reference image plane (where the global homographies land) and the output
+// surface. Surface coordinates are scaled by the focal length so the reference image keeps its size.
+// Plane points are homogeneous: equirectangular output covers directions behind the reference camera,
+// which only survive as rays with a negative z.
+#[derive(Debug, Clone, Copy)]
+pub struct PanoramaProjection {
+    pub mode: ProjectionMode,
+    pub focal: f64,
+    pub center_x: f64,
+    pub center_y: f64,
+}
+
+impl PanoramaProjection {
+    pub fn new(
+        mode: ProjectionMode,
+        reference_id: usize,
+        images: &[&ImageInfo],
+        global_homographies: &HashMap<usize, Matrix3<f64>>,
+    ) -> Self {
+        let reference = images.iter().find(|img_info| img_info.id == reference_id).copied().unwrap_or(images[0]);
+        let (w, h) = reference.color_full.dimensions();
+        let center = global_homographies[&reference.id] * Point3::new(w as f64 / 2.0, h as f64 / 2.0, 1.0);
+        let focal = if mode == ProjectionMode::Rectilinear {
+            1.0
+        } else {
+            estimate_focal_length(reference, images, global_homographies)
+        };
+        PanoramaProjection { mode, focal, center_x: center.x / center.z, center_y: center.y / center.z }
+    }
+
+    pub fn to_plane(&self, u: f64, v: f64) -> Option<Point3<f64>> {
+        let f = self.focal;
+        match self.mode {
+            ProjectionMode::Rectilinear => Some(Point3::new(u, v, 1.0)),
+            ProjectionMode::Cylindrical => {
+                let theta = (u - self.center_x) / f;
+                if theta.abs() >= FRAC_PI_2 {
+                    return None;
+                }
+                Some(Point3::new(self.center_x + f * theta.tan(), self.center_y + (v - self.center_y) / theta.cos(), 1.0))
+            }
+            ProjectionMode::Spherical => {
+                let theta = (u - self.center_x) / f;
+                let phi = (v - self.center_y) / f;
+                if theta.abs() >= FRAC_PI_2 || phi.abs() >= FRAC_PI_2 {
+                    return None;
+                }
+                Some(Point3::new(self.center_x + f * theta.tan(), self.center_y + f * phi.tan() / theta.cos(), 1.0))
+            }
+            ProjectionMode::Equirectangular => {
+                let longitude = (u - self.center_x) / f;
+                let latitude = (v - self.center_y) / f;
+                if longitude.abs() > PI || latitude.abs() > FRAC_PI_2 {
+                    return None;
+                }
+                let ray_x = longitude.sin() * latitude.cos();
+                let ray_y = latitude.sin();
+                let ray_z = longitude.cos() * latitude.cos();
+                Some(Point3::new(self.center_x * ray_z + f * ray_x, self.center_y * ray_z + f * ray_y, ray_z))
+            }
+        }
+    }
+
+    pub fn from_plane(&self, p: &Point3<f64>) -> Option<(f64, f64)> {
+        let f = self.focal;
+        if self.mode == ProjectionMode::Equirectangular {
+            let ray_x = (p.x - self.center_x * p.z) / f;
+            let ray_y = (p.y - self.center_y * p.z) / f;
+            let longitude = ray_x.atan2(p.z);
+            let latitude = ray_y.atan2((ray_x * ray_x + p.z * p.z).sqrt());
+            return Some((self.center_x + f * longitude, self.center_y + f * latitude));
+        }
+        if p.z <= 0.0 {
+            return None;
+        }
+        let (x, y) = (p.x / p.z, p.y / p.z);
+        let (dx, dy) = (x - self.center_x, y - self.center_y);
+        Some(match self.mode {
+            ProjectionMode::Rectilinear | ProjectionMode::Equirectangular => (x, y),
+            ProjectionMode::Cylindrical => {
+                let r = (dx * dx + f * f).sqrt();
+                (self.center_x + f * dx.atan2(f), self.center_y + f * dy / r)
+            }
+            ProjectionMode::Spherical => {
+                let r = (dx * dx + f * f).sqrt();
+                (self.center_x + f * dx.atan2(f), self.center_y + f * dy.atan2(r))
+            }
+        })
+    }
+
+    // Equirectangular output always spans the full sphere at 2:1, so viewers can wrap it.
+    // Returns (offset_x, offset_y, width, height) in the same form as the fitted canvas bounds.
+    pub fn full_sphere_canvas(&self) -> Option<(f64, f64, u32, u32)> {
+        if self.mode != ProjectionMode::Equirectangular {
+            return None;
+        }
+        let height = (PI * self.focal).ceil() as u32;
+        Some((PI * self.focal - self.center_x, FRAC_PI_2 * self.focal - self.center_y, height * 2, height))
+    }
+}
+
+// Focal length candidates from each homography to the reference (Szeliski & Shum), in centered
+// pixel coordinates. Falls back to a normal-lens guess when the homographies are degenerate.
+fn focals_from_homography(h: &Matrix3<f64>) -> Option<f64> {
+    let h = h / h[(2, 2)];
+    let (h0, h1, h2) = (h[(0, 0)], h[(0, 1)], h[(0, 2)]);
+    let (h3, h4, h5) = (h[(1, 0)], h[(1, 1)], h[(1, 2)]);
+    let (h6, h7) = (h[(2, 0)], h[(2, 1)]);
+
+    let pick = |d1: f64, d2: f64, v1: f64, v2: f64| -> Option<f64> {
+        let (v1, v2) = if v1 < v2 { (v2, v1) } else { (v1, v2) };
+        if v1 > 0.0 && v2 > 0.0 {
+            Some(if d1.abs() > d2.abs() { v1 } else { v2 }.sqrt())
+        } else if v1 > 0.0 {
+            Some(v1.sqrt())
+        } else {
+            None
+        }
+    };
+
+    let d1 = h6 * h7;
+    let d2 = (h7 - h6) * (h7 + h6);
+    let f1 = pick(d1, d2, -(h0 * h1 + h3 * h4) / d1, (h0 * h0 + h3 * h3 - h1 * h1 - h4 * h4) / d2);
+
+    let d1 = h0 * h3 + h1 * h4;
+    let d2 = h0 * h0 + h1 * h1 - h3 * h3 - h4 * h4;
+    let f0 = pick(d1, d2, -h2 * h5 / d1, (h5 * h5 - h2 * h2) / d2);
+
+    match (f0, f1) {
+        (Some(f0), Some(f1)) if f0.is_finite() && f1.is_finite() => Some((f0 * f1).sqrt()),
+        _ => None,
+    }
+}
+
+fn estimate_focal_length(
+    reference: &ImageInfo,
+    images: &[&ImageInfo],
+    global_homographies: &HashMap<usize, Matrix3<f64>>,
+) -> f64 {
+    let (ref_w, ref_h) = reference.color_full.dimensions();
+    let to_reference_center = Matrix3::new(1.0, 0.0, -(ref_w as f64) / 2.0, 0.0, 1.0, -(ref_h as f64) / 2.0, 0.0, 0.0, 1.0);
+    let h_ref_inv = global_homographies[&reference.id].try_inverse().unwrap_or_else(Matrix3::identity);
+
+    let mut focals: Vec<f64> = images
+        .iter()
+        .filter(|img_info| img_info.id != reference.id)
+        .filter_map(|img_info| {
+            let (w, h) = img_info.color_full.dimensions();
+            let from_center = Matrix3::new(1.0, 0.0, w as f64 / 2.0, 0.0, 1.0, h as f64 / 2.0, 0.0, 0.0, 1.0);
+            let h_centered = to_reference_center * h_ref_inv * global_homographies[&img_info.id] * from_center;
+            focals_from_homography(&h_centered)
+        })
+        .collect();
+
+    if focals.is_empty() {
+        let fallback = ref_w.max(ref_h) as f64;
+        println!("  - Could not estimate focal length, assuming {:.0}px", fallback);
+        return fallback;
+    }
+    focals.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let focal = focals[focals.len() / 2];
+    println!("  - Estimated focal length: {:.0}px", focal);
+    focal
+}
+
+// Curved projections are only well behaved around their center, so re-express every homography
+// relative to the frame nearest the middle of the panorama instead of the first frame in the order.
+pub fn recenter_homographies(images: &[&ImageInfo], global_homographies: &mut HashMap<usize, Matrix3<f64>>) -> usize {
+    let centers: Vec<(usize, f64, f64)> = images
+        .iter()
+        .map(|img_info| {
+            let (w, h) = img_info.color_full.dimensions();
+            let p = global_homographies[&img_info.id] * Point3::new(w as f64 / 2.0, h as f64 / 2.0, 1.0);
+            (img_info.id, p.x / p.z, p.y / p.z)
+        })
+        .collect();
+    let mean_x = centers.iter().map(|c| c.1).sum::<f64>() / centers.len() as f64;
+    let mean_y = centers.iter().map(|c| c.2).sum::<f64>() / centers.len() as f64;
+
+    let reference_id = centers
+        .iter()
+        .min_by(|a, b| {
+            let da = (a.1 - mean_x).powi(2) + (a.2 - mean_y).powi(2);
+            let db = (b.1 - mean_x).powi(2) + (b.2 - mean_y).powi(2);
+            da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|c| c.0)
+        .unwrap_or(images[0].id);
+
+    if let Some(h_ref_inv) = global_homographies[&reference_id].try_inverse() {
+        for h in global_homographies.values_mut() {
+            *h = h_ref_inv * *h;
+        }
+    }
+    reference_id
+}
@@ -1,18 +1,41 @@
+use crate::gpu_processing::{run_panorama_seam_blend, PanoramaBlendParams};
+use crate::image_processing::GpuContext;
 use crate::panorama_stitching::ImageInfo;
-use image::{GrayImage, Rgb, RgbImage};
+use crate::panorama_utils::blending::{collapse, gaussian_pyramid, laplacian_pyramid, PyramidLevel};
+use crate::panorama_utils::projection::PanoramaProjection;
+use crate::panorama_utils::warping::{footprint, projected_bounds, warp_image, WarpedRegion};
+use image::{GrayImage, Rgb, Rgb32FImage};
 use nalgebra::{Matrix3, Point3};
 use rayon::prelude::*;
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::Path;
 use tauri::{AppHandle, Emitter};
 
 const FEATHER_WIDTH: f64 = 100.0;
+const MAX_BLEND_BANDS: usize = 6;
+const MIN_BAND_SIZE: f64 = 16.0;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BlendMode {
+    #[default]
+    Feather,
+    MultiBand,
+}
 
 enum SeamOrientation {
     Vertical,
     Horizontal,
 }
 
+struct Seam {
+    orientation: SeamOrientation,
+    coords: Vec<i32>,
+    new_is_dominant: bool,
+    used: bool,
+}
+
 struct SeamInfo {
     orientation: SeamOrientation,
     coords: Vec<i32>,
@@ -23,64 +46,43 @@ struct SeamInfo {
 pub fn progressive_seam_stitcher(
     images: &[&ImageInfo],
     global_homographies: &HashMap<usize, Matrix3<f64>>,
+    projection: &PanoramaProjection,
+    gpu_context: Option<&GpuContext>,
     app_handle: AppHandle,
-) -> RgbImage {
+) -> (Rgb32FImage, GrayImage) {
     if images.is_empty() {
-        return RgbImage::new(0, 0);
-    }
-
-    let mut min_x = f64::INFINITY; let mut max_x = f64::NEG_INFINITY;
-    let mut min_y = f64::INFINITY; let mut max_y = f64::NEG_INFINITY;
-
-    for &img_info in images {
-        let h = global_homographies[&img_info.id];
-        let (w, h_img) = img_info.color_full.dimensions();
-        let corners = [
-            Point3::new(0.0, 0.0, 1.0), Point3::new(w as f64, 0.0, 1.0),
-            Point3::new(w as f64, h_img as f64, 1.0), Point3::new(0.0, h_img as f64, 1.0),
-        ];
-        for p in corners.iter() {
-            let tp = h * p;
-            let tx = tp.x / tp.z; let ty = tp.y / tp.z;
-            min_x = min_x.min(tx); max_x = max_x.max(tx);
-            min_y = min_y.min(ty); max_y = max_y.max(ty);
-        }
+        return (Rgb32FImage::new(0, 0), GrayImage::new(0, 0));
     }
 
-    let offset_x = -min_x;
-    let offset_y = -min_y;
-    let out_width = (max_x - min_x).ceil() as u32;
-    let out_height = (max_y - min_y).ceil() as u32;
+    let (offset_x, offset_y, out_width, out_height) = canvas_bounds(images, global_homographies, projection);
     println!("  - Output canvas size: {}x{}", out_width, out_height);
+    let offset = (offset_x, offset_y);
+    let canvas = (out_width as usize, out_height as usize);
 
-    let mut panorama = RgbImage::new(out_width, out_height);
+    let mut panorama = Rgb32FImage::new(out_width, out_height);
     let mut panorama_mask = GrayImage::new(out_width, out_height);
 
     let base_img_info = images[0];
     let h_base = &global_homographies[&base_img_info.id];
-    let h_base_inv = h_base.try_inverse().unwrap();
     println!("  - Placing base image: '{}'", base_img_info.filename);
 
     let num_pixels_per_row = out_width as usize * 3;
-    panorama.par_chunks_mut(num_pixels_per_row)
-        .zip(panorama_mask.par_chunks_mut(out_width as usize))
-        .enumerate()
-        .for_each(|(y, (row_slice, mask_row))| {
-            for x in 0..out_width {
-                let target_p = Point3::new(x as f64 - offset_x, y as f64 - offset_y, 1.0);
-                let source_p = h_base_inv * target_p;
-                let sx = source_p.x / source_p.z;
-                let sy = source_p.y / source_p.z;
-
-                if sx >= 0.0 && sx < base_img_info.color_full.width() as f64 &&
-                   sy >= 0.0 && sy < base_img_info.color_full.height() as f64 {
-                    let color = get_interpolated_pixel(&base_img_info.color_full, sx, sy);
-                    let start = x as usize * 3;
-                    row_slice[start..start + 3].copy_from_slice(&color.0);
-                    mask_row[x as usize] = 255;
+    if let Some(bounds) = footprint(base_img_info, h_base, projection, offset, 1.0, canvas) {
+        let warped = warp_image(gpu_context, base_img_info, h_base, projection, offset, bounds);
+        panorama.par_chunks_mut(num_pixels_per_row)
+            .zip(panorama_mask.par_chunks_mut(out_width as usize))
+            .enumerate()
+            .skip(warped.y)
+            .take(warped.height)
+            .for_each(|(y, (row_slice, mask_row))| {
+                for x in warped.x..warped.x + warped.width {
+                    if warped.is_covered(x, y) {
+                        row_slice[x * 3..x * 3 + 3].copy_from_slice(&warped.color(x, y).0);
+                        mask_row[x] = 255;
+                    }
                 }
-            }
-        });
+            });
+    }
 
     for (i, &img_to_add_info) in images.iter().skip(1).enumerate() {
         let progress_msg = format!("Stitching image {} of {}: {}", i + 2, images.len(), Path::new(&img_to_add_info.filename).file_name().unwrap_or_default().to_string_lossy());
@@ -88,11 +90,14 @@ pub fn progressive_seam_stitcher(
         println!("  - Progressively stitching '{}'", img_to_add_info.filename);
         
         let h_add = &global_homographies[&img_to_add_info.id];
-        let h_add_inv = h_add.try_inverse().unwrap();
-        let img_to_add = &img_to_add_info.color_full;
+        let Some(bounds) = footprint(img_to_add_info, h_add, projection, offset, 1.0, canvas) else {
+            println!("    - Warning: Image does not project onto the canvas. Skipping.");
+            continue;
+        };
+        let warped = warp_image(gpu_context, img_to_add_info, h_add, projection, offset, bounds);
 
         let seam_info = find_adaptive_seam(
-            &panorama, &panorama_mask, img_to_add, h_add,
+            &panorama, &panorama_mask, &warped, &img_to_add_info.color_full, h_add, projection,
             offset_x, offset_y, out_width, out_height,
         );
         
@@ -120,168 +125,321 @@ pub fn progressive_seam_stitcher(
             println!("    - New image is on the {} side of the seam.", side);
         }
 
-        match orientation {
-            SeamOrientation::Vertical => {
-                panorama.par_chunks_mut(num_pixels_per_row)
-                    .zip(panorama_mask.par_chunks_mut(out_width as usize))
-                    .enumerate()
-                    .for_each(|(y, (row_slice, mask_row))| {
-                        for x in 0..out_width {
-                            let target_p = Point3::new(x as f64 - offset_x, y as f64 - offset_y, 1.0);
-                            
-                            let source_p_add = h_add_inv * target_p;
-                            let sx = source_p_add.x / source_p_add.z;
-                            let sy = source_p_add.y / source_p_add.z;
-                            let is_on_add = sx >= 0.0 && sx < img_to_add.width() as f64 && sy >= 0.0 && sy < img_to_add.height() as f64;
-
-                            let is_on_pano = mask_row[x as usize] > 0;
-
-                            if !is_on_add && !is_on_pano { continue; }
-
-                            if is_on_add && is_on_pano && use_seam {
-                                let seam_x_val = seam_coords[y];
-                                let dist_to_seam = x as f64 - seam_x_val as f64;
-
-                                let low_detail_mask_add = &img_to_add_info.low_detail_mask;
-                                let sx_u = (sx.round() as u32).min(low_detail_mask_add.width() - 1);
-                                let sy_u = (sy.round() as u32).min(low_detail_mask_add.height() - 1);
-                                let is_low_detail = low_detail_mask_add.get_pixel(sx_u, sy_u)[0] > 0;
-                                let dynamic_feather_width = if is_low_detail { FEATHER_WIDTH * 5.0 } else { FEATHER_WIDTH };
-
-                                if dist_to_seam.abs() < dynamic_feather_width / 2.0 {
-                                    let color_on_pano = Rgb(row_slice[x as usize * 3..x as usize * 3 + 3].try_into().unwrap());
-                                    let color_to_add = get_interpolated_pixel(img_to_add, sx, sy);
-                                    
-                                    let alpha = if new_image_is_dominant_side {
-                                        (dist_to_seam + dynamic_feather_width / 2.0) / dynamic_feather_width
-                                    } else {
-                                        (-dist_to_seam + dynamic_feather_width / 2.0) / dynamic_feather_width
-                                    };
-                                    let weight_add = (1.0 - (alpha.clamp(0.0, 1.0) * std::f64::consts::PI).cos()) / 2.0;
-                                    let weight_pano = 1.0 - weight_add;
-
-                                    let final_color = Rgb([
-                                        (color_on_pano[0] as f64 * weight_pano + color_to_add[0] as f64 * weight_add).round() as u8,
-                                        (color_on_pano[1] as f64 * weight_pano + color_to_add[1] as f64 * weight_add).round() as u8,
-                                        (color_on_pano[2] as f64 * weight_pano + color_to_add[2] as f64 * weight_add).round() as u8,
-                                    ]);
-                                    let start = x as usize * 3;
-                                    row_slice[start..start + 3].copy_from_slice(&final_color.0);
-                                } else {
-                                    let new_image_owns_pixel = if new_image_is_dominant_side { x as i32 > seam_x_val } else { (x as i32) < seam_x_val };
-                                    if new_image_owns_pixel {
-                                        let color_to_add = get_interpolated_pixel(img_to_add, sx, sy);
-                                        let start = x as usize * 3;
-                                        row_slice[start..start + 3].copy_from_slice(&color_to_add.0);
-                                    }
-                                }
-                            } else if is_on_add {
-                                let color_to_add = get_interpolated_pixel(img_to_add, sx, sy);
-                                let start = x as usize * 3;
-                                row_slice[start..start + 3].copy_from_slice(&color_to_add.0);
-                                mask_row[x as usize] = 255;
-                            }
-                        }
-                    });
-            },
-            SeamOrientation::Horizontal => {
-                panorama.par_chunks_mut(num_pixels_per_row)
-                    .zip(panorama_mask.par_chunks_mut(out_width as usize))
-                    .enumerate()
-                    .for_each(|(y, (row_slice, mask_row))| {
-                        for x in 0..out_width {
-                            let target_p = Point3::new(x as f64 - offset_x, y as f64 - offset_y, 1.0);
-                            
-                            let source_p_add = h_add_inv * target_p;
-                            let sx = source_p_add.x / source_p_add.z;
-                            let sy = source_p_add.y / source_p_add.z;
-                            let is_on_add = sx >= 0.0 && sx < img_to_add.width() as f64 && sy >= 0.0 && sy < img_to_add.height() as f64;
-
-                            let is_on_pano = mask_row[x as usize] > 0;
-
-                            if !is_on_add && !is_on_pano { continue; }
-
-                            if is_on_add && is_on_pano && use_seam {
-                                let seam_y_val = seam_coords[x as usize];
-                                let dist_to_seam = y as f64 - seam_y_val as f64;
-
-                                let low_detail_mask_add = &img_to_add_info.low_detail_mask;
-                                let sx_u = (sx.round() as u32).min(low_detail_mask_add.width() - 1);
-                                let sy_u = (sy.round() as u32).min(low_detail_mask_add.height() - 1);
-                                let is_low_detail = low_detail_mask_add.get_pixel(sx_u, sy_u)[0] > 0;
-                                let dynamic_feather_width = if is_low_detail { FEATHER_WIDTH * 5.0 } else { FEATHER_WIDTH };
-
-                                if dist_to_seam.abs() < dynamic_feather_width / 2.0 {
-                                    let color_on_pano = Rgb(row_slice[x as usize * 3..x as usize * 3 + 3].try_into().unwrap());
-                                    let color_to_add = get_interpolated_pixel(img_to_add, sx, sy);
-                                    
-                                    let alpha = if new_image_is_dominant_side {
-                                        (dist_to_seam + dynamic_feather_width / 2.0) / dynamic_feather_width
-                                    } else {
-                                        (-dist_to_seam + dynamic_feather_width / 2.0) / dynamic_feather_width
-                                    };
-                                    let weight_add = (1.0 - (alpha.clamp(0.0, 1.0) * std::f64::consts::PI).cos()) / 2.0;
-                                    let weight_pano = 1.0 - weight_add;
-
-                                    let final_color = Rgb([
-                                        (color_on_pano[0] as f64 * weight_pano + color_to_add[0] as f64 * weight_add).round() as u8,
-                                        (color_on_pano[1] as f64 * weight_pano + color_to_add[1] as f64 * weight_add).round() as u8,
-                                        (color_on_pano[2] as f64 * weight_pano + color_to_add[2] as f64 * weight_add).round() as u8,
-                                    ]);
-                                    let start = x as usize * 3;
-                                    row_slice[start..start + 3].copy_from_slice(&final_color.0);
-                                } else {
-                                    let new_image_owns_pixel = if new_image_is_dominant_side { y as i32 > seam_y_val } else { (y as i32) < seam_y_val };
-                                    if new_image_owns_pixel {
-                                        let color_to_add = get_interpolated_pixel(img_to_add, sx, sy);
-                                        let start = x as usize * 3;
-                                        row_slice[start..start + 3].copy_from_slice(&color_to_add.0);
-                                    }
-                                }
-                            } else if is_on_add {
-                                let color_to_add = get_interpolated_pixel(img_to_add, sx, sy);
-                                let start = x as usize * 3;
-                                row_slice[start..start + 3].copy_from_slice(&color_to_add.0);
-                                mask_row[x as usize] = 255;
-                            }
+        let seam = Seam { orientation, coords: seam_coords, new_is_dominant: new_image_is_dominant_side, used: use_seam };
+        let gpu_result = gpu_context.map(|context| blend_on_gpu(context, &mut panorama, &mut panorama_mask, &warped, &seam));
+        match gpu_result {
+            Some(Ok(())) => {}
+            Some(Err(e)) => {
+                println!("    - GPU blend failed, falling back to CPU: {}", e);
+                blend_on_cpu(&mut panorama, &mut panorama_mask, &warped, &seam);
+            }
+            None => blend_on_cpu(&mut panorama, &mut panorama_mask, &warped, &seam),
+        }
+    }
+
+    (panorama, panorama_mask)
+}
+
+fn blend_on_cpu(panorama: &mut Rgb32FImage, panorama_mask: &mut GrayImage, warped: &WarpedRegion, seam: &Seam) {
+    let out_width = panorama.width() as usize;
+    panorama.par_chunks_mut(out_width * 3)
+        .zip(panorama_mask.par_chunks_mut(out_width))
+        .enumerate()
+        .skip(warped.y)
+        .take(warped.height)
+        .for_each(|(y, (row_slice, mask_row))| {
+            for x in warped.x..warped.x + warped.width {
+                if !warped.is_covered(x, y) { continue; }
+                let start = x * 3;
+
+                if mask_row[x] > 0 && seam.used {
+                    // Distance is measured across the seam: along x for vertical seams, along y for horizontal ones.
+                    let (position, seam_position) = match seam.orientation {
+                        SeamOrientation::Vertical => (x as i32, seam.coords[y]),
+                        SeamOrientation::Horizontal => (y as i32, seam.coords[x]),
+                    };
+                    let dist_to_seam = position as f64 - seam_position as f64;
+                    let dynamic_feather_width = if warped.is_low_detail(x, y) { FEATHER_WIDTH * 5.0 } else { FEATHER_WIDTH };
+
+                    if dist_to_seam.abs() < dynamic_feather_width / 2.0 {
+                        let color_on_pano = Rgb(row_slice[start..start + 3].try_into().unwrap());
+                        let color_to_add = warped.color(x, y);
+                        
+                        let alpha = if seam.new_is_dominant {
+                            (dist_to_seam + dynamic_feather_width / 2.0) / dynamic_feather_width
+                        } else {
+                            (-dist_to_seam + dynamic_feather_width / 2.0) / dynamic_feather_width
+                        };
+                        let weight_add = (1.0 - (alpha.clamp(0.0, 1.0) * std::f64::consts::PI).cos()) / 2.0;
+                        let weight_pano = 1.0 - weight_add;
+
+                        let final_color = Rgb([
+                            (color_on_pano[0] as f64 * weight_pano + color_to_add[0] as f64 * weight_add) as f32,
+                            (color_on_pano[1] as f64 * weight_pano + color_to_add[1] as f64 * weight_add) as f32,
+                            (color_on_pano[2] as f64 * weight_pano + color_to_add[2] as f64 * weight_add) as f32,
+                        ]);
+                        row_slice[start..start + 3].copy_from_slice(&final_color.0);
+                    } else {
+                        let new_image_owns_pixel = if seam.new_is_dominant { position > seam_position } else { position < seam_position };
+                        if new_image_owns_pixel {
+                            row_slice[start..start + 3].copy_from_slice(&warped.color(x, y).0);
                         }
-                    });
+                    }
+                } else {
+                    row_slice[start..start + 3].copy_from_slice(&warped.color(x, y).0);
+                    mask_row[x] = 255;
+                }
             }
+        });
+}
+
+// Runs the same seam blend in a compute shader over the frame's region of the canvas.
+fn blend_on_gpu(
+    context: &GpuContext,
+    panorama: &mut Rgb32FImage,
+    panorama_mask: &mut GrayImage,
+    warped: &WarpedRegion,
+    seam: &Seam,
+) -> Result<(), String> {
+    let out_width = panorama.width() as usize;
+    let mut region = vec![0.0f32; warped.width * warped.height * 4];
+    region.par_chunks_mut(warped.width * 4).enumerate().for_each(|(ly, row)| {
+        let y = warped.y + ly;
+        for lx in 0..warped.width {
+            let x = warped.x + lx;
+            let p = panorama.get_pixel(x as u32, y as u32);
+            let covered = panorama_mask.get_pixel(x as u32, y as u32)[0] > 0;
+            row[lx * 4..lx * 4 + 4].copy_from_slice(&[p[0], p[1], p[2], if covered { 1.0 } else { 0.0 }]);
+        }
+    });
+
+    let params = PanoramaBlendParams {
+        region_x: warped.x as u32,
+        region_y: warped.y as u32,
+        width: warped.width as u32,
+        height: warped.height as u32,
+        orientation: match seam.orientation { SeamOrientation::Vertical => 0, SeamOrientation::Horizontal => 1 },
+        use_seam: seam.used as u32,
+        new_is_dominant: seam.new_is_dominant as u32,
+        feather_width: FEATHER_WIDTH as f32,
+    };
+    run_panorama_seam_blend(context, &mut region, warped.rgba(), &seam.coords, params)?;
+
+    panorama.par_chunks_mut(out_width * 3)
+        .zip(panorama_mask.par_chunks_mut(out_width))
+        .skip(warped.y)
+        .take(warped.height)
+        .enumerate()
+        .for_each(|(ly, (row_slice, mask_row))| {
+            for lx in 0..warped.width {
+                let pixel = &region[(ly * warped.width + lx) * 4..(ly * warped.width + lx) * 4 + 4];
+                if pixel[3] <= 0.0 {
+                    continue;
+                }
+                let x = warped.x + lx;
+                row_slice[x * 3..x * 3 + 3].copy_from_slice(&pixel[..3]);
+                mask_row[x] = 255;
+            }
+        });
+    Ok(())
+}
+
+fn canvas_bounds(
+    images: &[&ImageInfo],
+    global_homographies: &HashMap<usize, Matrix3<f64>>,
+    projection: &PanoramaProjection,
+) -> (f64, f64, u32, u32) {
+    if let Some(canvas) = projection.full_sphere_canvas() {
+        return canvas;
+    }
+    let mut min_x = f64::INFINITY; let mut max_x = f64::NEG_INFINITY;
+    let mut min_y = f64::INFINITY; let mut max_y = f64::NEG_INFINITY;
+
+    for &img_info in images {
+        let (x0, y0, x1, y1) = projected_bounds(img_info, &global_homographies[&img_info.id], projection);
+        min_x = min_x.min(x0); max_x = max_x.max(x1);
+        min_y = min_y.min(y0); max_y = max_y.max(y1);
+    }
+
+    (-min_x, -min_y, (max_x - min_x).ceil() as u32, (max_y - min_y).ceil() as u32)
+}
+
+// Blends every image over a Laplacian pyramid: low frequencies (sky gradients, exposure drift) are mixed
+// over wide transitions while fine detail still switches sharply at the seams.
+pub fn multi_band_stitcher(
+    images: &[&ImageInfo],
+    global_homographies: &HashMap<usize, Matrix3<f64>>,
+    projection: &PanoramaProjection,
+    gpu_context: Option<&GpuContext>,
+    app_handle: AppHandle,
+) -> (Rgb32FImage, GrayImage) {
+    if images.is_empty() {
+        return (Rgb32FImage::new(0, 0), GrayImage::new(0, 0));
+    }
+
+    let (offset_x, offset_y, out_width, out_height) = canvas_bounds(images, global_homographies, projection);
+    println!("  - Output canvas size: {}x{}", out_width, out_height);
+    let (out_w, out_h) = (out_width as usize, out_height as usize);
+    let offset = (offset_x, offset_y);
+
+    // Each canvas pixel is owned by the image whose border is farthest away, which keeps the
+    // seams in the middle of the overlaps.
+    let _ = app_handle.emit("panorama-progress", "Computing blend seams...");
+    let mut labels = vec![-1i32; out_w * out_h];
+    let mut best_distances = vec![0.0f32; out_w * out_h];
+    for (i, &img_info) in images.iter().enumerate() {
+        let h = &global_homographies[&img_info.id];
+        let Some(bounds) = footprint(img_info, h, projection, offset, 1.0, (out_w, out_h)) else { continue; };
+        let warped = warp_image(gpu_context, img_info, h, projection, offset, bounds);
+        labels.par_chunks_mut(out_w)
+            .zip(best_distances.par_chunks_mut(out_w))
+            .enumerate()
+            .skip(warped.y)
+            .take(warped.height)
+            .for_each(|(y, (label_row, distance_row))| {
+                for x in warped.x..warped.x + warped.width {
+                    let Some(distance) = warped.border_distance(x, y) else { continue; };
+                    if label_row[x] < 0 || distance > distance_row[x] {
+                        distance_row[x] = distance;
+                        label_row[x] = i as i32;
+                    }
+                }
+            });
+    }
+    drop(best_distances);
+
+    let bands = (((out_width.min(out_height) as f64) / MIN_BAND_SIZE).log2().floor() as usize).clamp(1, MAX_BLEND_BANDS);
+    let align = 1usize << bands;
+    let padded_w = out_w.div_ceil(align) * align;
+    let padded_h = out_h.div_ceil(align) * align;
+    println!("  - Multi-band blending with {} bands", bands);
+
+    let mut blended: Vec<PyramidLevel<[f32; 3]>> =
+        (0..=bands).map(|level| PyramidLevel::new(padded_w >> level, padded_h >> level)).collect();
+    let mut weights: Vec<PyramidLevel<f32>> =
+        (0..=bands).map(|level| PyramidLevel::new(padded_w >> level, padded_h >> level)).collect();
+
+    for (i, &img_info) in images.iter().enumerate() {
+        let progress_msg = format!("Blending image {} of {}: {}", i + 1, images.len(), Path::new(&img_info.filename).file_name().unwrap_or_default().to_string_lossy());
+        let _ = app_handle.emit("panorama-progress", &progress_msg);
+        println!("  - Multi-band blending '{}'", img_info.filename);
+
+        // Work on the image's own footprint, padded so the coarse levels see some context and
+        // aligned so every level maps onto the canvas pyramid without rounding.
+        let h = &global_homographies[&img_info.id];
+        let (min_x, min_y, max_x, max_y) = projected_bounds(img_info, h, projection);
+        let margin = (align * 2) as f64;
+        let x0 = (((min_x + offset_x - margin).max(0.0) as usize) / align) * align;
+        let y0 = (((min_y + offset_y - margin).max(0.0) as usize) / align) * align;
+        let x1 = (((max_x + offset_x + margin).max(0.0).ceil() as usize).div_ceil(align) * align).min(padded_w);
+        let y1 = (((max_y + offset_y + margin).max(0.0).ceil() as usize).div_ceil(align) * align).min(padded_h);
+        if x1 <= x0 || y1 <= y0 {
+            continue;
+        }
+        let (region_w, region_h) = (x1 - x0, y1 - y0);
+        let warped = warp_image(gpu_context, img_info, h, projection, offset, (x0, y0, x1, y1));
+
+        let mut color = PyramidLevel::<[f32; 3]>::new(region_w, region_h);
+        let mut mask = PyramidLevel::<f32>::new(region_w, region_h);
+        color.data.par_chunks_mut(region_w)
+            .zip(mask.data.par_chunks_mut(region_w))
+            .enumerate()
+            .for_each(|(ly, (color_row, mask_row))| {
+                let y = y0 + ly;
+                for lx in 0..region_w {
+                    let x = x0 + lx;
+                    // Warped colors clamp to the image border, so the pyramid never sees black edges.
+                    let pixel = warped.color(x, y);
+                    color_row[lx] = pixel.0;
+                    if x < out_w && y < out_h && labels[y * out_w + x] == i as i32 {
+                        mask_row[lx] = 1.0;
+                    }
+                }
+            });
+        drop(warped);
+        let color_pyramid = laplacian_pyramid(color, bands);
+        let mask_pyramid = gaussian_pyramid(mask, bands);
+
+        for (level, (color_level, mask_level)) in color_pyramid.iter().zip(mask_pyramid.iter()).enumerate() {
+            let (lx0, ly0) = (x0 >> level, y0 >> level);
+            let canvas_w = blended[level].width;
+            blended[level].data.par_chunks_mut(canvas_w)
+                .zip(weights[level].data.par_chunks_mut(canvas_w))
+                .skip(ly0)
+                .take(color_level.height)
+                .enumerate()
+                .for_each(|(ly, (blend_row, weight_row))| {
+                    for lx in 0..color_level.width {
+                        let weight = mask_level.data[ly * mask_level.width + lx];
+                        if weight <= 0.0 {
+                            continue;
+                        }
+                        let c = color_level.data[ly * color_level.width + lx];
+                        let target = &mut blend_row[lx0 + lx];
+                        target[0] += c[0] * weight;
+                        target[1] += c[1] * weight;
+                        target[2] += c[2] * weight;
+                        weight_row[lx0 + lx] += weight;
+                    }
+                });
         }
     }
 
-    panorama
+    let _ = app_handle.emit("panorama-progress", "Collapsing blend pyramid...");
+    for (blend_level, weight_level) in blended.iter_mut().zip(weights.iter()) {
+        blend_level.data.par_iter_mut().zip(weight_level.data.par_iter()).for_each(|(pixel, weight)| {
+            if *weight > 1e-6 {
+                *pixel = [pixel[0] / weight, pixel[1] / weight, pixel[2] / weight];
+            } else {
+                *pixel = [0.0; 3];
+            }
+        });
+    }
+    let result = collapse(blended);
+
+    let mut panorama = Rgb32FImage::new(out_width, out_height);
+    let mut panorama_mask = GrayImage::new(out_width, out_height);
+    panorama.par_chunks_mut(out_w * 3)
+        .zip(panorama_mask.par_chunks_mut(out_w))
+        .enumerate()
+        .for_each(|(y, (row_slice, mask_row))| {
+            for x in 0..out_w {
+                if labels[y * out_w + x] < 0 {
+                    continue;
+                }
+                mask_row[x] = 255;
+                let c = result.data[y * result.width + x];
+                row_slice[x * 3..x * 3 + 3].copy_from_slice(&c);
+            }
+        });
+
+    (panorama, panorama_mask)
 }
 
 fn find_adaptive_seam(
-    pano: &RgbImage,
+    pano: &Rgb32FImage,
     pano_mask: &GrayImage,
-    img_to_add: &RgbImage,
+    warped: &WarpedRegion,
+    img_to_add: &Rgb32FImage,
     h_add: &Matrix3<f64>,
+    projection: &PanoramaProjection,
     offset_x: f64,
     offset_y: f64,
     out_width: u32,
     out_height: u32,
 ) -> Option<SeamInfo> {
-    let h_add_inv = h_add.try_inverse().unwrap();
     let (w_add, h_add_img) = img_to_add.dimensions();
 
-    let mut min_ox = u32::MAX; let mut max_ox = 0;
-    let mut min_oy = u32::MAX; let mut max_oy = 0;
+    let mut min_ox = usize::MAX; let mut max_ox = 0;
+    let mut min_oy = usize::MAX; let mut max_oy = 0;
     let mut has_overlap = false;
 
-    for y in 0..out_height {
-        for x in 0..out_width {
-            if pano_mask.get_pixel(x, y)[0] > 0 {
-                let target_p = Point3::new(x as f64 - offset_x, y as f64 - offset_y, 1.0);
-                let source_p = h_add_inv * target_p;
-                let sx = source_p.x / source_p.z;
-                let sy = source_p.y / source_p.z;
-                if sx >= 0.0 && sx < w_add as f64 && sy >= 0.0 && sy < h_add_img as f64 {
-                    has_overlap = true;
-                    min_ox = min_ox.min(x); max_ox = max_ox.max(x);
-                    min_oy = min_oy.min(y); max_oy = max_oy.max(y);
-                }
+    for y in warped.y..warped.y + warped.height {
+        for x in warped.x..warped.x + warped.width {
+            if pano_mask.get_pixel(x as u32, y as u32)[0] > 0 && warped.is_covered(x, y) {
+                has_overlap = true;
+                min_ox = min_ox.min(x); max_ox = max_ox.max(x);
+                min_oy = min_oy.min(y); max_oy = max_oy.max(y);
             }
         }
     }
@@ -291,9 +449,9 @@ fn find_adaptive_seam(
     }
 
     let center_p_source = Point3::new(w_add as f64 / 2.0, h_add_img as f64 / 2.0, 1.0);
-    let center_p_target = h_add * center_p_source;
-    let center_add_x = (center_p_target.x / center_p_target.z) + offset_x;
-    let center_add_y = (center_p_target.y / center_p_target.z) + offset_y;
+    let (center_add_x, center_add_y) = projection.from_plane(&(h_add * center_p_source))?;
+    let center_add_x = center_add_x + offset_x;
+    let center_add_y = center_add_y + offset_y;
 
     let center_overlap_x = (min_ox + max_ox) as f64 / 2.0;
     let center_overlap_y = (min_oy + max_oy) as f64 / 2.0;
@@ -303,36 +461,36 @@ fn find_adaptive_seam(
 
     if dx.abs() > dy.abs() {
         println!("    - Overlap is vertical. Finding vertical seam...");
-        let seam = find_pairwise_seam_dp_vertical(pano, pano_mask, img_to_add, h_add, offset_x, offset_y, out_width, out_height);
+        let seam = find_pairwise_seam_dp_vertical(pano, pano_mask, warped, out_width, out_height);
         Some(SeamInfo { orientation: SeamOrientation::Vertical, coords: seam, dx, dy })
     } else {
         println!("    - Overlap is horizontal. Finding horizontal seam...");
-        let seam = find_pairwise_seam_dp_horizontal(pano, pano_mask, img_to_add, h_add, offset_x, offset_y, out_width, out_height);
+        let seam = find_pairwise_seam_dp_horizontal(pano, pano_mask, warped, out_width, out_height);
         Some(SeamInfo { orientation: SeamOrientation::Horizontal, coords: seam, dx, dy })
     }
 }
 
+// Seam energy is the color difference between the panorama so far and the warped new image.
+fn seam_energy(pano: &Rgb32FImage, pano_mask: &GrayImage, warped: &WarpedRegion, x: usize, y: usize) -> Option<f64> {
+    if pano_mask.get_pixel(x as u32, y as u32)[0] == 0 || !warped.is_covered(x, y) {
+        return None;
+    }
+    let p_pano = pano.get_pixel(x as u32, y as u32);
+    let p_add = warped.color(x, y);
+    Some(((p_pano[0] as f64 - p_add[0] as f64).powi(2) + (p_pano[1] as f64 - p_add[1] as f64).powi(2) + (p_pano[2] as f64 - p_add[2] as f64).powi(2)).sqrt())
+}
+
 fn find_pairwise_seam_dp_vertical(
-    pano: &RgbImage, pano_mask: &GrayImage, img_to_add: &RgbImage, h_add: &Matrix3<f64>,
-    offset_x: f64, offset_y: f64, out_width: u32, out_height: u32,
+    pano: &Rgb32FImage, pano_mask: &GrayImage, warped: &WarpedRegion, out_width: u32, out_height: u32,
 ) -> Vec<i32> {
-    let h_add_inv = h_add.try_inverse().unwrap();
-    let (w_add, h_add_img) = img_to_add.dimensions();
     let mut cost_matrix = vec![vec![f64::INFINITY; out_width as usize]; out_height as usize];
     let mut path_matrix = vec![vec![0i32; out_width as usize]; out_height as usize];
     let mut first_overlap_row = usize::MAX; let mut last_overlap_row = 0;
 
-    for y_out in 0..out_height as usize {
+    for y_out in warped.y..warped.y + warped.height {
         let mut row_has_overlap = false;
-        for x_out in 0..out_width as usize {
-            if pano_mask.get_pixel(x_out as u32, y_out as u32)[0] == 0 { continue; }
-            let target_p = Point3::new(x_out as f64 - offset_x, y_out as f64 - offset_y, 1.0);
-            let source_p = h_add_inv * target_p;
-            let sx = source_p.x / source_p.z; let sy = source_p.y / source_p.z;
-            if sx >= 0.0 && sx < w_add as f64 - 1.0 && sy >= 0.0 && sy < h_add_img as f64 - 1.0 {
-                let p_pano = pano.get_pixel(x_out as u32, y_out as u32);
-                let p_add = get_interpolated_pixel(img_to_add, sx, sy);
-                let energy = ((p_pano[0] as f64 - p_add[0] as f64).powi(2) + (p_pano[1] as f64 - p_add[1] as f64).powi(2) + (p_pano[2] as f64 - p_add[2] as f64).powi(2)).sqrt();
+        for x_out in warped.x..warped.x + warped.width {
+            if let Some(energy) = seam_energy(pano, pano_mask, warped, x_out, y_out) {
                 cost_matrix[y_out][x_out] = energy;
                 row_has_overlap = true;
             }
@@ -380,25 +538,15 @@ fn find_pairwise_seam_dp_vertical(
 }
 
 fn find_pairwise_seam_dp_horizontal(
-    pano: &RgbImage, pano_mask: &GrayImage, img_to_add: &RgbImage, h_add: &Matrix3<f64>,
-    offset_x: f64, offset_y: f64, out_width: u32, out_height: u32,
+    pano: &Rgb32FImage, pano_mask: &GrayImage, warped: &WarpedRegion, out_width: u32, out_height: u32,
 ) -> Vec<i32> {
-    let h_add_inv = h_add.try_inverse().unwrap();
-    let (w_add, h_add_img) = img_to_add.dimensions();
     let mut cost_matrix = vec![vec![f64::INFINITY; out_width as usize]; out_height as usize];
     let mut path_matrix = vec![vec![0i32; out_width as usize]; out_height as usize];
     let mut first_overlap_col = usize::MAX; let mut last_overlap_col = 0;
 
-    for y_out in 0..out_height as usize {
-        for x_out in 0..out_width as usize {
-            if pano_mask.get_pixel(x_out as u32, y_out as u32)[0] == 0 { continue; }
-            let target_p = Point3::new(x_out as f64 - offset_x, y_out as f64 - offset_y, 1.0);
-            let source_p = h_add_inv * target_p;
-            let sx = source_p.x / source_p.z; let sy = source_p.y / source_p.z;
-            if sx >= 0.0 && sx < w_add as f64 - 1.0 && sy >= 0.0 && sy < h_add_img as f64 - 1.0 {
-                let p_pano = pano.get_pixel(x_out as u32, y_out as u32);
-                let p_add = get_interpolated_pixel(img_to_add, sx, sy);
-                let energy = ((p_pano[0] as f64 - p_add[0] as f64).powi(2) + (p_pano[1] as f64 - p_add[1] as f64).powi(2) + (p_pano[2] as f64 - p_add[2] as f64).powi(2)).sqrt();
+    for y_out in warped.y..warped.y + warped.height {
+        for x_out in warped.x..warped.x + warped.width {
+            if let Some(energy) = seam_energy(pano, pano_mask, warped, x_out, y_out) {
                 cost_matrix[y_out][x_out] = energy;
                 first_overlap_col = first_overlap_col.min(x_out);
                 last_overlap_col = last_overlap_col.max(x_out);
@@ -441,29 +589,3 @@ fn find_pairwise_seam_dp_horizontal(
     for x in (last_overlap_col + 1)..out_width as usize { seam[x] = seam[last_overlap_col]; }
     seam
 }
-
-fn get_interpolated_pixel(img: &RgbImage, x: f64, y: f64) -> Rgb<u8> {
-    let (width, height) = img.dimensions();
-    let x_floor = x.floor() as u32;
-    let y_floor = y.floor() as u32;
-    if x_floor + 1 >= width || y_floor + 1 >= height || x < 0.0 || y < 0.0 {
-        return *img.get_pixel(x.max(0.0).min(width as f64 - 1.0) as u32, y.max(0.0).min(height as f64 - 1.0) as u32);
-    }
-    let dx = x - x_floor as f64;
-    let dy = y - y_floor as f64;
-    let p00 = img.get_pixel(x_floor, y_floor);
-    let p10 = img.get_pixel(x_floor + 1, y_floor);
-    let p01 = img.get_pixel(x_floor, y_floor + 1);
-    let p11 = img.get_pixel(x_floor + 1, y_floor + 1);
-    let mut final_pixel = [0.0; 3];
-    for i in 0..3 {
-        let c00 = p00[i] as f64;
-        let c10 = p10[i] as f64;
-        let c01 = p01[i] as f64;
-        let c11 = p11[i] as f64;
-        let top = c00 * (1.0 - dx) + c10 * dx;
-        let bottom = c01 * (1.0 - dx) + c11 * dx;
-        final_pixel[i] = top * (1.0 - dy) + bottom * dy;
-    }
-    Rgb([final_pixel[0].round() as u8, final_pixel[1].round() as u8, final_pixel[2].round() as u8])
-}
\ No newline at end of file
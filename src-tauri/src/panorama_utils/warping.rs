@@ -0,0 +1,228 @@
+use crate::gpu_processing::{run_panorama_warp, PanoramaWarpParams, TileRegion};
+use crate::image_processing::GpuContext;
+use crate::panorama_stitching::ImageInfo;
+use crate::panorama_utils::projection::{PanoramaProjection, ProjectionMode};
+use image::{Rgb, Rgb32FImage};
+use nalgebra::{Matrix3, Point3};
+use rayon::prelude::*;
+
+// Alpha values written by the warp: 0 outside the frame, otherwise whether the low-detail mask is set.
+const COVERED_ALPHA: f32 = 0.5;
+const LOW_DETAIL_ALPHA: f32 = 1.0;
+const EDGE_SAMPLES: usize = 32;
+
+// One frame resampled onto a rectangle of the output canvas. Colors are filled wherever the inverse
+// projection is defined (clamped to the frame border), coverage only where the frame really is.
+pub struct WarpedRegion {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<f32>,
+    distances: Vec<f32>,
+}
+
+impl WarpedRegion {
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.x || y < self.y || x >= self.x + self.width || y >= self.y + self.height {
+            return None;
+        }
+        Some((y - self.y) * self.width + (x - self.x))
+    }
+
+    pub fn color(&self, x: usize, y: usize) -> Rgb<f32> {
+        match self.index(x, y) {
+            Some(i) => Rgb([self.pixels[i * 4], self.pixels[i * 4 + 1], self.pixels[i * 4 + 2]]),
+            None => Rgb([0.0, 0.0, 0.0]),
+        }
+    }
+
+    // Colors and coverage of the whole region as RGBA rows, the layout the GPU blend uploads.
+    pub fn rgba(&self) -> &[f32] {
+        &self.pixels
+    }
+
+    pub fn is_covered(&self, x: usize, y: usize) -> bool {
+        self.index(x, y).is_some_and(|i| self.pixels[i * 4 + 3] > 0.0)
+    }
+
+    pub fn is_low_detail(&self, x: usize, y: usize) -> bool {
+        self.index(x, y).is_some_and(|i| self.pixels[i * 4 + 3] > COVERED_ALPHA)
+    }
+
+    // Distance to the nearest frame border, used as the seam cost when choosing pixel ownership.
+    pub fn border_distance(&self, x: usize, y: usize) -> Option<f32> {
+        self.index(x, y).map(|i| self.distances[i]).filter(|d| *d >= 0.0)
+    }
+}
+
+fn projection_code(mode: ProjectionMode) -> u32 {
+    match mode {
+        ProjectionMode::Rectilinear => 0,
+        ProjectionMode::Cylindrical => 1,
+        ProjectionMode::Spherical => 2,
+        ProjectionMode::Equirectangular => 3,
+    }
+}
+
+fn source_rgba(img_info: &ImageInfo) -> Vec<f32> {
+    img_info
+        .color_full
+        .pixels()
+        .zip(img_info.low_detail_mask.pixels())
+        .flat_map(|(color, mask)| [color[0], color[1], color[2], if mask[0] > 0 { 1.0 } else { 0.0 }])
+        .collect()
+}
+
+fn warp_on_gpu(
+    context: &GpuContext,
+    img_info: &ImageInfo,
+    inverse: &Matrix3<f64>,
+    projection: &PanoramaProjection,
+    offset: (f64, f64),
+    region: TileRegion,
+) -> Result<(Vec<f32>, Vec<f32>), String> {
+    let row = |r: usize| [inverse[(r, 0)] as f32, inverse[(r, 1)] as f32, inverse[(r, 2)] as f32, 0.0];
+    let params = PanoramaWarpParams {
+        inverse_rows: [row(0), row(1), row(2)],
+        projection_mode: projection_code(projection.mode),
+        focal: projection.focal as f32,
+        center_x: projection.center_x as f32,
+        center_y: projection.center_y as f32,
+        offset_x: offset.0 as f32,
+        offset_y: offset.1 as f32,
+        tile_offset_x: 0,
+        tile_offset_y: 0,
+    };
+    let (width, height) = img_info.color_full.dimensions();
+    run_panorama_warp(context, &source_rgba(img_info), width, height, params, region)
+}
+
+fn warp_on_cpu(
+    img_info: &ImageInfo,
+    inverse: &Matrix3<f64>,
+    projection: &PanoramaProjection,
+    offset: (f64, f64),
+    region: TileRegion,
+) -> (Vec<f32>, Vec<f32>) {
+    let (region_w, region_h) = (region.width as usize, region.height as usize);
+    let mut pixels = vec![0.0f32; region_w * region_h * 4];
+    let mut distances = vec![-1.0f32; region_w * region_h];
+    let (w, h) = (img_info.color_full.width() as f64, img_info.color_full.height() as f64);
+    let mask = &img_info.low_detail_mask;
+
+    pixels.par_chunks_mut(region_w * 4)
+        .zip(distances.par_chunks_mut(region_w))
+        .enumerate()
+        .for_each(|(ly, (pixel_row, distance_row))| {
+            let y = (region.y as usize + ly) as f64;
+            for lx in 0..region_w {
+                let x = (region.x as usize + lx) as f64;
+                let Some(target_p) = projection.to_plane(x - offset.0, y - offset.1) else { continue; };
+                let source_p = inverse * target_p;
+                if source_p.z <= 0.0 {
+                    continue;
+                }
+                let (sx, sy) = (source_p.x / source_p.z, source_p.y / source_p.z);
+                let color = get_interpolated_pixel(&img_info.color_full, sx, sy);
+                pixel_row[lx * 4..lx * 4 + 3].copy_from_slice(&color.0);
+                if sx >= 0.0 && sx < w && sy >= 0.0 && sy < h {
+                    let mx = (sx.round() as u32).min(mask.width() - 1);
+                    let my = (sy.round() as u32).min(mask.height() - 1);
+                    pixel_row[lx * 4 + 3] = if mask.get_pixel(mx, my)[0] > 0 { LOW_DETAIL_ALPHA } else { COVERED_ALPHA };
+                    distance_row[lx] = sx.min(w - sx).min(sy).min(h - sy) as f32;
+                }
+            }
+        });
+
+    (pixels, distances)
+}
+
+// Resamples a frame onto the canvas rectangle (x0, y0, x1, y1). Uses the GPU when a context is
+// available and falls back to the rayon path if it is missing or the frame exceeds its limits.
+pub fn warp_image(
+    gpu_context: Option<&GpuContext>,
+    img_info: &ImageInfo,
+    h: &Matrix3<f64>,
+    projection: &PanoramaProjection,
+    offset: (f64, f64),
+    (x0, y0, x1, y1): (usize, usize, usize, usize),
+) -> WarpedRegion {
+    let inverse = h.try_inverse().unwrap_or_else(Matrix3::identity);
+    let (x, y, width, height) = (x0, y0, x1 - x0, y1 - y0);
+    let region = TileRegion { x: x as u32, y: y as u32, width: width as u32, height: height as u32 };
+
+    let gpu_result = gpu_context.map(|context| warp_on_gpu(context, img_info, &inverse, projection, offset, region));
+    let (pixels, distances) = match gpu_result {
+        Some(Ok(result)) => result,
+        Some(Err(e)) => {
+            println!("    - GPU warp failed, falling back to CPU: {}", e);
+            warp_on_cpu(img_info, &inverse, projection, offset, region)
+        }
+        None => warp_on_cpu(img_info, &inverse, projection, offset, region),
+    };
+
+    WarpedRegion { x, y, width, height, pixels, distances }
+}
+
+// Image edges are sampled rather than just the corners, since curved projections bend them.
+pub fn projected_bounds(img_info: &ImageInfo, h: &Matrix3<f64>, projection: &PanoramaProjection) -> (f64, f64, f64, f64) {
+    let mut min_x = f64::INFINITY; let mut max_x = f64::NEG_INFINITY;
+    let mut min_y = f64::INFINITY; let mut max_y = f64::NEG_INFINITY;
+    let (w, h_img) = (img_info.color_full.width() as f64, img_info.color_full.height() as f64);
+    for i in 0..=EDGE_SAMPLES {
+        let t = i as f64 / EDGE_SAMPLES as f64;
+        let edge_points = [(t * w, 0.0), (t * w, h_img), (0.0, t * h_img), (w, t * h_img)];
+        for (px, py) in edge_points {
+            let Some((tx, ty)) = projection.from_plane(&(h * Point3::new(px, py, 1.0))) else { continue; };
+            min_x = min_x.min(tx); max_x = max_x.max(tx);
+            min_y = min_y.min(ty); max_y = max_y.max(ty);
+        }
+    }
+    (min_x, min_y, max_x, max_y)
+}
+
+// Canvas rectangle covered by a frame, grown by `margin` pixels and clamped to the canvas.
+pub fn footprint(
+    img_info: &ImageInfo,
+    h: &Matrix3<f64>,
+    projection: &PanoramaProjection,
+    offset: (f64, f64),
+    margin: f64,
+    canvas: (usize, usize),
+) -> Option<(usize, usize, usize, usize)> {
+    let (min_x, min_y, max_x, max_y) = projected_bounds(img_info, h, projection);
+    if !min_x.is_finite() || !min_y.is_finite() {
+        return None;
+    }
+    let x0 = (min_x + offset.0 - margin).floor().max(0.0) as usize;
+    let y0 = (min_y + offset.1 - margin).floor().max(0.0) as usize;
+    let x1 = ((max_x + offset.0 + margin).ceil().max(0.0) as usize).min(canvas.0);
+    let y1 = ((max_y + offset.1 + margin).ceil().max(0.0) as usize).min(canvas.1);
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+    Some((x0, y0, x1, y1))
+}
+
+pub fn get_interpolated_pixel(img: &Rgb32FImage, x: f64, y: f64) -> Rgb<f32> {
+    let (width, height) = img.dimensions();
+    let x_floor = x.floor() as u32;
+    let y_floor = y.floor() as u32;
+    if x_floor + 1 >= width || y_floor + 1 >= height || x < 0.0 || y < 0.0 {
+        return *img.get_pixel(x.max(0.0).min(width as f64 - 1.0) as u32, y.max(0.0).min(height as f64 - 1.0) as u32);
+    }
+    let dx = x - x_floor as f64;
+    let dy = y - y_floor as f64;
+    let p00 = img.get_pixel(x_floor, y_floor);
+    let p10 = img.get_pixel(x_floor + 1, y_floor);
+    let p01 = img.get_pixel(x_floor, y_floor + 1);
+    let p11 = img.get_pixel(x_floor + 1, y_floor + 1);
+    let mut final_pixel = [0.0; 3];
+    for i in 0..3 {
+        let top = p00[i] as f64 * (1.0 - dx) + p10[i] as f64 * dx;
+        let bottom = p01[i] as f64 * (1.0 - dx) + p11[i] as f64 * dx;
+        final_pixel[i] = top * (1.0 - dy) + bottom * dy;
+    }
+    Rgb([final_pixel[0] as f32, final_pixel[1] as f32, final_pixel[2] as f32])
+}
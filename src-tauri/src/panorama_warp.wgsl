@@ -0,0 +1,99 @@
+struct PanoramaWarpParams {
+    inverse_row0: vec4<f32>,
+    inverse_row1: vec4<f32>,
+    inverse_row2: vec4<f32>,
+    projection_mode: u32,
+    focal: f32,
+    center_x: f32,
+    center_y: f32,
+    offset_x: f32,
+    offset_y: f32,
+    tile_offset_x: u32,
+    tile_offset_y: u32,
+}
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var color_texture: texture_storage_2d<rgba32float, write>;
+@group(0) @binding(2) var distance_texture: texture_storage_2d<r32float, write>;
+@group(0) @binding(3) var<uniform> params: PanoramaWarpParams;
+
+const HALF_PI: f32 = 1.57079633;
+const PROJECTION_CYLINDRICAL: u32 = 1u;
+const PROJECTION_SPHERICAL: u32 = 2u;
+const PROJECTION_EQUIRECTANGULAR: u32 = 3u;
+const PI: f32 = 3.14159265;
+const COVERED_ALPHA: f32 = 0.5;
+const LOW_DETAIL_ALPHA: f32 = 1.0;
+
+// Mirrors PanoramaProjection::to_plane. The result is homogeneous; all zeros marks points with no
+// plane position.
+fn to_plane(u: f32, v: f32) -> vec3<f32> {
+    let f = params.focal;
+    if (params.projection_mode == PROJECTION_CYLINDRICAL) {
+        let theta = (u - params.center_x) / f;
+        if (abs(theta) >= HALF_PI) { return vec3<f32>(0.0); }
+        return vec3<f32>(params.center_x + f * tan(theta), params.center_y + (v - params.center_y) / cos(theta), 1.0);
+    }
+    if (params.projection_mode == PROJECTION_SPHERICAL) {
+        let theta = (u - params.center_x) / f;
+        let phi = (v - params.center_y) / f;
+        if (abs(theta) >= HALF_PI || abs(phi) >= HALF_PI) { return vec3<f32>(0.0); }
+        return vec3<f32>(params.center_x + f * tan(theta), params.center_y + f * tan(phi) / cos(theta), 1.0);
+    }
+    if (params.projection_mode == PROJECTION_EQUIRECTANGULAR) {
+        let longitude = (u - params.center_x) / f;
+        let latitude = (v - params.center_y) / f;
+        if (abs(longitude) > PI || abs(latitude) > HALF_PI) { return vec3<f32>(0.0); }
+        let ray = vec3<f32>(sin(longitude) * cos(latitude), sin(latitude), cos(longitude) * cos(latitude));
+        return vec3<f32>(params.center_x * ray.z + f * ray.x, params.center_y * ray.z + f * ray.y, ray.z);
+    }
+    return vec3<f32>(u, v, 1.0);
+}
+
+// Bilinear sample that clamps to the image border, so coarse blend levels never see black edges.
+fn sample_bilinear(p: vec2<f32>, size: vec2<f32>) -> vec3<f32> {
+    let clamped = clamp(p, vec2<f32>(0.0), size - vec2<f32>(1.0));
+    let base = vec2<i32>(floor(clamped));
+    let next = min(base + vec2<i32>(1), vec2<i32>(size) - vec2<i32>(1));
+    let t = clamped - floor(clamped);
+    let c00 = textureLoad(source_texture, base, 0).rgb;
+    let c10 = textureLoad(source_texture, vec2<i32>(next.x, base.y), 0).rgb;
+    let c01 = textureLoad(source_texture, vec2<i32>(base.x, next.y), 0).rgb;
+    let c11 = textureLoad(source_texture, next, 0).rgb;
+    return mix(mix(c00, c10, t.x), mix(c01, c11, t.x), t.y);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let out_dims = textureDimensions(color_texture);
+    if (id.x >= out_dims.x || id.y >= out_dims.y) { return; }
+
+    let canvas_x = f32(params.tile_offset_x + id.x);
+    let canvas_y = f32(params.tile_offset_y + id.y);
+    var color = vec4<f32>(0.0);
+    var distance = -1.0;
+
+    let plane = to_plane(canvas_x - params.offset_x, canvas_y - params.offset_y);
+    if (any(plane != vec3<f32>(0.0))) {
+        let source = vec3<f32>(
+            dot(params.inverse_row0.xyz, plane),
+            dot(params.inverse_row1.xyz, plane),
+            dot(params.inverse_row2.xyz, plane),
+        );
+        if (source.z > 0.0) {
+            let size = vec2<f32>(textureDimensions(source_texture));
+            let p = source.xy / source.z;
+            color = vec4<f32>(sample_bilinear(p, size), 0.0);
+            if (p.x >= 0.0 && p.y >= 0.0 && p.x < size.x && p.y < size.y) {
+                // The source alpha carries the low-detail mask; seams get a wider feather there.
+                let nearest = min(vec2<i32>(round(p)), vec2<i32>(size) - vec2<i32>(1));
+                let low_detail = textureLoad(source_texture, nearest, 0).a > 0.5;
+                color.a = select(COVERED_ALPHA, LOW_DETAIL_ALPHA, low_detail);
+                distance = min(min(p.x, size.x - p.x), min(p.y, size.y - p.y));
+            }
+        }
+    }
+
+    textureStore(color_texture, vec2<i32>(id.xy), color);
+    textureStore(distance_texture, vec2<i32>(id.xy), vec4<f32>(distance, 0.0, 0.0, 0.0));
+}
@@ -0,0 +1,91 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Emitter, Manager, Runtime, UriSchemeContext};
+
+use crate::AppState;
+
+pub const PREVIEW_SCHEME: &str = "preview";
+const FRAMES_PER_KIND: usize = 4;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewFramePayload {
+    pub frame: String,
+}
+
+#[derive(Default)]
+pub struct PreviewFrames {
+    frames: Mutex<HashMap<String, VecDeque<(u64, Arc<Vec<u8>>)>>>,
+    next_id: AtomicU64,
+}
+
+impl PreviewFrames {
+    fn insert(&self, kind: &str, data: Vec<u8>) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let mut frames = self.frames.lock().unwrap();
+        let queue = frames.entry(kind.to_string()).or_default();
+        queue.push_back((id, Arc::new(data)));
+        while queue.len() > FRAMES_PER_KIND {
+            queue.pop_front();
+        }
+        id
+    }
+
+    fn get(&self, kind: &str, id: u64) -> Option<Arc<Vec<u8>>> {
+        let frames = self.frames.lock().unwrap();
+        frames
+            .get(kind)?
+            .iter()
+            .find(|(frame_id, _)| *frame_id == id)
+            .map(|(_, data)| data.clone())
+    }
+}
+
+pub fn publish_preview_frame(app_handle: &AppHandle, event: &str, kind: &str, data: Vec<u8>) {
+    let state = app_handle.state::<AppState>();
+    let id = state.preview_frames.insert(kind, data);
+    let _ = app_handle.emit(event, PreviewFramePayload { frame: format!("{}-{}", kind, id) });
+}
+
+// Frames are JPEGs, except HDR previews and clipping overlays which are PNGs.
+fn content_type(data: &[u8]) -> &'static str {
+    if data.starts_with(b"\x89PNG") { "image/png" } else { "image/jpeg" }
+}
+
+fn error_response(status: StatusCode) -> Response<Cow<'static, [u8]>> {
+    Response::builder()
+        .status(status)
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Cow::Borrowed(&[][..]))
+        .unwrap()
+}
+
+pub fn handle_preview_request<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+) -> Response<Cow<'static, [u8]>> {
+    let frame = request.uri().path().trim_start_matches('/');
+    let Some((kind, id)) = frame.rsplit_once('-') else {
+        return error_response(StatusCode::BAD_REQUEST);
+    };
+    let Ok(id) = id.parse::<u64>() else {
+        return error_response(StatusCode::BAD_REQUEST);
+    };
+
+    let state = ctx.app_handle().state::<AppState>();
+    match state.preview_frames.get(kind, id) {
+        Some(data) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, content_type(&data))
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .header(header::CACHE_CONTROL, "max-age=31536000, immutable")
+            .body(Cow::Owned(data.as_ref().clone()))
+            .unwrap(),
+        None => error_response(StatusCode::NOT_FOUND),
+    }
+}
@@ -0,0 +1,242 @@
+use std::fs;
+use std::time::Duration;
+
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::catalog::{file_fingerprint, load_published, mark_published, with_catalog};
+use crate::export_hooks::ExportedFile;
+use crate::file_management::{load_albums, load_export_presets, load_settings};
+use crate::iptc_metadata::read_metadata;
+use crate::tagging::COLOR_TAG_PREFIX;
+use crate::{AppState, ExportSettings};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const POST_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishService {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub url: String,
+    pub album_id: Option<String>,
+    pub preset_id: Option<String>,
+    pub auth_token: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishStatus {
+    pub total: usize,
+    pub pending: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PublishErrorPayload {
+    path: String,
+    service: String,
+    error: String,
+}
+
+fn find_service(service_id: &str, app_handle: &AppHandle) -> Result<PublishService, String> {
+    load_settings(app_handle.clone())?
+        .publish_services
+        .unwrap_or_default()
+        .into_iter()
+        .find(|s| s.id == service_id)
+        .ok_or_else(|| format!("Publish service not found: {}", service_id))
+}
+
+fn collection_paths(service: &PublishService, app_handle: &AppHandle) -> Result<Vec<String>, String> {
+    let album_id = service.album_id.as_deref().ok_or("The publish service isn't linked to an album.")?;
+    load_albums(app_handle.clone())?
+        .into_iter()
+        .find(|a| a.id == album_id)
+        .map(|a| a.paths)
+        .ok_or_else(|| format!("Album not found: {}", album_id))
+}
+
+// An image needs publishing when it was never published or the image or its edits changed since.
+fn pending_paths(service: &PublishService, app_handle: &AppHandle) -> Result<(usize, Vec<String>), String> {
+    let paths = collection_paths(service, app_handle)?;
+    let published = with_catalog(app_handle, |conn| load_published(conn, &service.id))?;
+    let total = paths.len();
+    let pending = paths
+        .into_iter()
+        .filter(|path| {
+            published
+                .get(path)
+                .map_or(true, |p| (p.modified, p.sidecar_modified) != file_fingerprint(path))
+        })
+        .collect();
+    Ok((total, pending))
+}
+
+#[tauri::command]
+pub fn get_publish_status(service_id: String, app_handle: AppHandle) -> Result<PublishStatus, String> {
+    let service = find_service(&service_id, &app_handle)?;
+    let (total, pending) = pending_paths(&service, &app_handle)?;
+    Ok(PublishStatus { total, pending: pending.len() })
+}
+
+// Exports the new and changed images of the service's album with its export preset. The files are
+// posted to the endpoint once the export finishes, see post_published_files.
+#[tauri::command]
+pub async fn publish_to_service(service_id: String, app_handle: AppHandle) -> Result<usize, String> {
+    let service = find_service(&service_id, &app_handle)?;
+    if service.url.trim().is_empty() {
+        return Err("The publish service has no URL.".to_string());
+    }
+    let (_, pending) = pending_paths(&service, &app_handle)?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let preset_id = service.preset_id.as_deref().ok_or("The publish service has no export preset.")?;
+    let preset = load_export_presets(app_handle.clone())?
+        .into_iter()
+        .find(|p| p.id == preset_id)
+        .ok_or_else(|| format!("Export preset not found: {}", preset_id))?;
+    let mut export_settings: ExportSettings =
+        serde_json::from_value(preset.export_settings).map_err(|e| format!("Invalid export preset: {}", e))?;
+    export_settings.post_export_hook = None;
+    export_settings.upload_target_id = None;
+    export_settings.publish_service_id = Some(service.id.clone());
+    // Every image of the album lands in the same folder, so same-named sources from different folders
+    // get the sequence number in front to keep them apart.
+    let filename_template = export_settings.filename_template.as_deref().unwrap_or("{original_filename}_edited");
+    export_settings.filename_template = Some(format!("{{sequence}}_{}", filename_template));
+    let output_format = if preset.file_format == "jpeg" { "jpg".to_string() } else { preset.file_format };
+
+    let output_folder = std::env::temp_dir().join("rapidraw-publish").join(&service.id);
+    fs::create_dir_all(&output_folder).map_err(|e| e.to_string())?;
+
+    let count = pending.len();
+    crate::batch_export_images(
+        output_folder.to_string_lossy().into_owned(),
+        pending,
+        export_settings,
+        output_format,
+        None,
+        app_handle.state::<AppState>(),
+        app_handle.clone(),
+    )
+    .await?;
+    Ok(count)
+}
+
+fn mime_type(file_name: &str) -> &'static str {
+    match file_name.rsplit('.').next().unwrap_or_default().to_lowercase().as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "tif" | "tiff" => "image/tiff",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "jxl" => "image/jxl",
+        _ => "application/octet-stream",
+    }
+}
+
+fn image_metadata(service: &PublishService, file: &ExportedFile, remote_id: Option<&str>) -> Value {
+    let metadata = read_metadata(&file.source_path);
+    let tags = metadata.tags.unwrap_or_default();
+    json!({
+        "serviceId": service.id,
+        "sourcePath": file.source_path,
+        "fileName": file.output_path.file_name().map(|n| n.to_string_lossy().into_owned()),
+        "rating": metadata.rating,
+        "flag": metadata.flag,
+        "colorLabel": tags.iter().find_map(|t| t.strip_prefix(COLOR_TAG_PREFIX)),
+        "tags": tags.iter().filter(|t| !t.starts_with(COLOR_TAG_PREFIX)).collect::<Vec<_>>(),
+        "remoteId": remote_id,
+    })
+}
+
+// Posts the file as multipart/form-data with a "file" and a JSON "metadata" part. An "id" in the
+// response is remembered and sent along as remoteId next time, so the endpoint can replace the image.
+async fn post_file(
+    client: &reqwest::Client,
+    service: &PublishService,
+    file: &ExportedFile,
+    remote_id: Option<&str>,
+) -> Result<Option<String>, String> {
+    let bytes = tokio::fs::read(&file.output_path).await.map_err(|e| e.to_string())?;
+    let file_name = file.output_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let part = Part::bytes(bytes).file_name(file_name.clone()).mime_str(mime_type(&file_name)).map_err(|e| e.to_string())?;
+    let form = Form::new()
+        .part("file", part)
+        .text("metadata", image_metadata(service, file, remote_id).to_string());
+
+    let mut request = client.post(&service.url).multipart(form);
+    if let Some(token) = service.auth_token.as_deref().filter(|t| !t.is_empty()) {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Endpoint responded with {}", response.status()));
+    }
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    Ok(match body.get("id") {
+        Some(Value::String(id)) => Some(id.clone()),
+        Some(Value::Number(id)) => Some(id.to_string()),
+        _ => remote_id.map(String::from),
+    })
+}
+
+// Called when a publish export finishes. Successfully posted images are marked as published; the
+// exported files are temporary and removed either way.
+pub async fn post_published_files(service_id: &str, files: &[ExportedFile], app_handle: &AppHandle) {
+    let service = match find_service(service_id, app_handle) {
+        Ok(service) => service,
+        Err(e) => {
+            eprintln!("{}", e);
+            let _ = app_handle.emit("publish-error", PublishErrorPayload { path: String::new(), service: service_id.to_string(), error: e });
+            return;
+        }
+    };
+    let published = with_catalog(app_handle, |conn| load_published(conn, &service.id)).unwrap_or_default();
+
+    let client = match reqwest::Client::builder().connect_timeout(CONNECT_TIMEOUT).timeout(POST_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            let _ = app_handle.emit(
+                "publish-error",
+                PublishErrorPayload { path: String::new(), service: service.name.clone(), error: e.to_string() },
+            );
+            files.iter().for_each(|file| {
+                let _ = fs::remove_file(&file.output_path);
+            });
+            return;
+        }
+    };
+    let mut published_count = 0;
+    for file in files {
+        let remote_id = published.get(&file.source_path).and_then(|p| p.remote_id.as_deref());
+        let result = post_file(&client, &service, file, remote_id).await.and_then(|remote_id| {
+            with_catalog(app_handle, |conn| {
+                mark_published(conn, &service.id, &file.source_path, file.source_fingerprint, remote_id.as_deref())
+            })
+        });
+        let _ = fs::remove_file(&file.output_path);
+        match result {
+            Ok(()) => published_count += 1,
+            Err(error) => {
+                eprintln!("Failed to publish {} to '{}': {}", file.source_path, service.name, error);
+                let _ = app_handle.emit(
+                    "publish-error",
+                    PublishErrorPayload { path: file.source_path.clone(), service: service.name.clone(), error },
+                );
+            }
+        }
+    }
+    println!("Published {} of {} images to '{}'.", published_count, files.len(), service.name);
+    let _ = app_handle.emit(
+        "publish-complete",
+        json!({ "serviceId": service.id, "published": published_count, "failed": files.len() - published_count }),
+    );
+}
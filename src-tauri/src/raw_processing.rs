@@ -1,19 +1,187 @@
-use anyhow::Result;
-use image::DynamicImage;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use anyhow::{anyhow, Result};
+use image::{DynamicImage, Rgb32FImage};
+use nalgebra::Matrix3;
+use once_cell::sync::Lazy;
 use rawler::{
     decoders::{Orientation, RawDecodeParams},
     imgop::develop::{DemosaicAlgorithm, Intermediate, ProcessingStep, RawDevelop},
-    rawimage::RawImage,
+    imgop::xyz::Illuminant,
+    rawimage::{RawImage, RawImageData, RawPhotometricInterpretation},
     rawsource::RawSource,
 };
-use crate::image_processing::apply_orientation;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::file_management::{load_settings, save_settings, AppSettings};
+use crate::image_processing::{apply_orientation, neutralize_white_balance, oriented_dimensions};
+
+const CAMERA_PROFILES_DIR: &str = "camera_profiles";
+const PIXEL_MAPS_DIR: &str = "pixel_maps";
+
+const DEFECT_NEIGHBOR_RATIO: f32 = 4.0;
+const DEFECT_MIN_SIGNAL: f32 = 0.05;
+const DARK_FRAME_SIGMA: f32 = 8.0;
+const MAX_DARK_FRAME_BYTES: u64 = 512 * 1024 * 1024;
+// A dark frame sits close to the black level and only a tiny share of its pixels stand out.
+const MAX_DARK_FRAME_LEVEL: f64 = 0.05;
+const MAX_DARK_FRAME_DEFECT_RATIO: f64 = 0.001;
+
+const SRGB_TO_XYZ_D65: [[f32; 3]; 3] = [
+    [0.4124564, 0.3575761, 0.1804375],
+    [0.2126729, 0.7151522, 0.0721750],
+    [0.0193339, 0.1191920, 0.9503041],
+];
+
+const WHITE_BALANCE_SAMPLE_RADIUS: usize = 8;
+
+const WHITE_BALANCE_PRESETS: [(&str, f32); 4] = [
+    ("Daylight", 5500.0),
+    ("Shade", 7500.0),
+    ("Tungsten", 2850.0),
+    ("Flash", 5900.0),
+];
+
+const BRADFORD_D50_TO_D65: [[f32; 3]; 3] = [
+    [0.9555766, -0.0230393, 0.0631636],
+    [-0.0282895, 1.0099416, 0.0210077],
+    [0.0122982, -0.0204830, 1.3299098],
+];
+
+const PROPHOTO_TO_XYZ_D50: [[f32; 3]; 3] = [
+    [0.7976749, 0.1351917, 0.0313534],
+    [0.2880402, 0.7118741, 0.0000857],
+    [0.0000000, 0.0000000, 0.8252100],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CameraProfileKind {
+    Dcp,
+    Icc,
+}
+
+#[derive(Debug, Clone)]
+pub struct HueSatMap {
+    hue_divisions: usize,
+    sat_divisions: usize,
+    val_divisions: usize,
+    data: Vec<[f32; 3]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CameraProfile {
+    pub name: String,
+    pub kind: CameraProfileKind,
+    pub camera_model: Option<String>,
+    pub xyz_to_cam: Option<[f32; 9]>,
+    pub hue_sat_map: Option<HueSatMap>,
+    pub look_table: Option<HueSatMap>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraProfileInfo {
+    pub file_name: String,
+    pub name: String,
+    pub kind: CameraProfileKind,
+    pub camera_model: Option<String>,
+    pub matches_camera: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CameraProfileSelection {
+    pub camera: String,
+    pub selected: Option<String>,
+    pub profiles: Vec<CameraProfileInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PixelMap {
+    pub camera: String,
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u32; 2]>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WhiteBalancePreset {
+    pub name: String,
+    pub temperature: f32,
+    pub tint: f32,
+}
+
+static PIXEL_MAPS: Lazy<RwLock<HashMap<String, Arc<PixelMap>>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+// The neighbour heuristic can't tell a hot pixel from a star or a specular point, so it only runs
+// when enabled in the settings. Mapped pixels from dark frames are always repaired.
+static AUTO_DEFECT_DETECTION: AtomicBool = AtomicBool::new(false);
+
+static ACTIVE_CAMERA_PROFILES: Lazy<RwLock<HashMap<String, Arc<CameraProfile>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+// The developed image is linear sRGB, while DCP hue/sat maps and look tables are defined on linear
+// ProPhoto RGB, the space the profile's forward matrix leads to.
+static SRGB_TO_PROPHOTO: Lazy<Matrix3<f32>> = Lazy::new(|| {
+    let xyz_d65_to_d50 = Matrix3::from_fn(|i, j| BRADFORD_D50_TO_D65[i][j]).try_inverse().unwrap_or_else(Matrix3::identity);
+    let xyz_to_prophoto = Matrix3::from_fn(|i, j| PROPHOTO_TO_XYZ_D50[i][j]).try_inverse().unwrap_or_else(Matrix3::identity);
+    xyz_to_prophoto * xyz_d65_to_d50 * Matrix3::from_fn(|i, j| SRGB_TO_XYZ_D65[i][j])
+});
+static PROPHOTO_TO_SRGB: Lazy<Matrix3<f32>> =
+    Lazy::new(|| SRGB_TO_PROPHOTO.try_inverse().unwrap_or_else(Matrix3::identity));
 
 pub fn develop_raw_image(file_bytes: &[u8], fast_demosaic: bool) -> Result<DynamicImage> {
-    let (developed_image, orientation) = develop_internal(file_bytes, fast_demosaic)?;
+    let (developed_image, orientation) = develop_internal(file_bytes, fast_demosaic, false)?;
+    Ok(apply_orientation(developed_image, orientation))
+}
+
+// Size of the developed, oriented image, read without demosaicing.
+pub fn developed_raw_dimensions(file_bytes: &[u8]) -> Result<(u32, u32)> {
+    let source = RawSource::new_from_slice(file_bytes);
+    let decoder = rawler::get_decoder(&source)?;
+    let raw_image: RawImage = decoder.raw_image(&source, &RawDecodeParams::default(), true)?;
+    let metadata = decoder.raw_metadata(&source, &RawDecodeParams::default())?;
+    let (width, height) = match raw_image.crop_area.or(raw_image.active_area) {
+        Some(rect) => (rect.d.w as u32, rect.d.h as u32),
+        None => (raw_image.width as u32, raw_image.height as u32),
+    };
+    let orientation = metadata.exif.orientation.map(Orientation::from_u16).unwrap_or(Orientation::Normal);
+    Ok(oriented_dimensions(width, height, orientation))
+}
+
+pub fn develop_raw_image_linear(file_bytes: &[u8]) -> Result<DynamicImage> {
+    let (developed_image, orientation) = develop_internal(file_bytes, false, true)?;
     Ok(apply_orientation(developed_image, orientation))
 }
 
-fn apply_tonemap_and_gamma(linear_val: f32) -> f32 {
+pub fn extract_embedded_preview(file_bytes: &[u8]) -> Result<Option<DynamicImage>> {
+    let source = RawSource::new_from_slice(file_bytes);
+    let decoder = rawler::get_decoder(&source)?;
+    let params = RawDecodeParams::default();
+    let preview = match decoder.preview_image(&source, &params)? {
+        Some(preview) => Some(preview),
+        None => decoder.full_image(&source, &params)?,
+    };
+
+    let orientation = decoder
+        .raw_metadata(&source, &params)
+        .ok()
+        .and_then(|metadata| metadata.exif.orientation)
+        .map(Orientation::from_u16)
+        .unwrap_or(Orientation::Normal);
+    Ok(preview.map(|image| apply_orientation(image, orientation)))
+}
+
+pub fn apply_tonemap_and_gamma(linear_val: f32) -> f32 {
     let x = linear_val.max(0.0);
     let a = 2.51;
     let b = 0.03;
@@ -29,12 +197,20 @@ fn apply_tonemap_and_gamma(linear_val: f32) -> f32 {
     }
 }
 
-fn develop_internal(file_bytes: &[u8], fast_demosaic: bool) -> Result<(DynamicImage, Orientation)> {
+fn develop_internal(file_bytes: &[u8], fast_demosaic: bool, linear_output: bool) -> Result<(DynamicImage, Orientation)> {
     let source = RawSource::new_from_slice(file_bytes);
     let decoder = rawler::get_decoder(&source)?;
     let mut raw_image: RawImage = decoder.raw_image(&source, &RawDecodeParams::default(), false)?;
 
     let metadata = decoder.raw_metadata(&source, &RawDecodeParams::default())?;
+    let camera = camera_key(&metadata.make, &metadata.model);
+    correct_defective_pixels(&mut raw_image, &camera);
+
+    let camera_profile = get_active_camera_profile(&camera);
+    if let Some(matrix) = camera_profile.as_ref().and_then(|p| p.xyz_to_cam) {
+        raw_image.color_matrix.insert(Illuminant::D65, matrix.to_vec());
+    }
+
     let orientation = metadata
         .exif
         .orientation
@@ -60,6 +236,29 @@ fn develop_internal(file_bytes: &[u8], fast_demosaic: bool) -> Result<(DynamicIm
     let denominator = (original_white_level - original_black_level).max(1.0);
     let rescale_factor = (headroom_white_level - original_black_level) / denominator;
 
+    if linear_output {
+        let (width, height, data): (usize, usize, Vec<f32>) = match &developed_intermediate {
+            Intermediate::Monochrome(pixels) => (
+                pixels.width,
+                pixels.height,
+                pixels.data.iter().flat_map(|p| [*p * rescale_factor; 3]).collect(),
+            ),
+            Intermediate::ThreeColor(pixels) => (
+                pixels.width,
+                pixels.height,
+                pixels.data.iter().flat_map(|p| [p[0] * rescale_factor, p[1] * rescale_factor, p[2] * rescale_factor]).collect(),
+            ),
+            Intermediate::FourColor(pixels) => (
+                pixels.width,
+                pixels.height,
+                pixels.data.iter().flat_map(|p| [p[0] * rescale_factor, p[1] * rescale_factor, p[2] * rescale_factor]).collect(),
+            ),
+        };
+        let linear_image = Rgb32FImage::from_raw(width as u32, height as u32, data)
+            .ok_or_else(|| anyhow!("Failed to build linear image buffer"))?;
+        return Ok((DynamicImage::ImageRgb32F(linear_image), orientation));
+    }
+
     const HIGHLIGHT_COMPRESSION_POINT: f32 = 2.2; // FIXME: This is not a good solution yet
 
     match &mut developed_intermediate {
@@ -71,9 +270,15 @@ fn develop_internal(file_bytes: &[u8], fast_demosaic: bool) -> Result<(DynamicIm
         }
         Intermediate::ThreeColor(pixels) => {
             pixels.data.iter_mut().for_each(|p| {
-                let r = (p[0] * rescale_factor).max(0.0);
-                let g = (p[1] * rescale_factor).max(0.0);
-                let b = (p[2] * rescale_factor).max(0.0);
+                let mut rgb = [
+                    (p[0] * rescale_factor).max(0.0),
+                    (p[1] * rescale_factor).max(0.0),
+                    (p[2] * rescale_factor).max(0.0),
+                ];
+                if let Some(profile) = camera_profile.as_ref().filter(|p| p.hue_sat_map.is_some() || p.look_table.is_some()) {
+                    rgb = profile.apply_tables(rgb);
+                }
+                let [r, g, b] = rgb;
 
                 let max_c = r.max(g).max(b);
 
@@ -117,4 +322,907 @@ fn develop_internal(file_bytes: &[u8], fast_demosaic: bool) -> Result<(DynamicIm
         .ok_or_else(|| anyhow::anyhow!("Failed to convert developed image to DynamicImage"))?;
 
     Ok((dynamic_image, orientation))
-}
\ No newline at end of file
+}
+
+pub fn camera_key(make: &str, model: &str) -> String {
+    let make = make.trim();
+    let model = model.trim();
+    if model.to_lowercase().starts_with(&make.to_lowercase()) {
+        model.to_string()
+    } else {
+        format!("{} {}", make, model)
+    }
+}
+
+fn get_active_camera_profile(camera: &str) -> Option<Arc<CameraProfile>> {
+    ACTIVE_CAMERA_PROFILES.read().ok()?.get(camera).cloned()
+}
+
+fn set_active_camera_profile(camera: &str, profile: Option<CameraProfile>) {
+    if let Ok(mut profiles) = ACTIVE_CAMERA_PROFILES.write() {
+        match profile {
+            Some(p) => profiles.insert(camera.to_string(), Arc::new(p)),
+            None => profiles.remove(camera),
+        };
+    }
+}
+
+fn rgb_to_hsv(rgb: [f32; 3]) -> (f32, f32, f32) {
+    let [r, g, b] = rgb;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    if max <= 0.0 || delta <= 0.0 {
+        return (0.0, 0.0, max);
+    }
+    let mut h = if r == max {
+        (g - b) / delta
+    } else if g == max {
+        2.0 + (b - r) / delta
+    } else {
+        4.0 + (r - g) / delta
+    };
+    if h < 0.0 {
+        h += 6.0;
+    }
+    (h, delta / max, max)
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [f32; 3] {
+    if s <= 0.0 {
+        return [v, v, v];
+    }
+    let h = h.rem_euclid(6.0);
+    let i = h.floor();
+    let f = h - i;
+    let p = v * (1.0 - s);
+    let q = v * (1.0 - s * f);
+    let t = v * (1.0 - s * (1.0 - f));
+    match i as u32 {
+        0 => [v, t, p],
+        1 => [q, v, p],
+        2 => [p, v, t],
+        3 => [p, q, v],
+        4 => [t, p, v],
+        _ => [v, p, q],
+    }
+}
+
+fn grid_position(value: f32, divisions: usize) -> (usize, usize, f32) {
+    if divisions < 2 {
+        return (0, 0, 0.0);
+    }
+    let scaled = value.clamp(0.0, 1.0) * (divisions - 1) as f32;
+    let i0 = (scaled.floor() as usize).min(divisions - 2);
+    (i0, i0 + 1, scaled - i0 as f32)
+}
+
+impl CameraProfile {
+    fn apply_tables(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let prophoto = *SRGB_TO_PROPHOTO * nalgebra::Vector3::from(rgb);
+        let mut mapped = [prophoto[0].max(0.0), prophoto[1].max(0.0), prophoto[2].max(0.0)];
+        if let Some(map) = &self.hue_sat_map {
+            mapped = map.apply(mapped);
+        }
+        if let Some(table) = &self.look_table {
+            mapped = table.apply(mapped);
+        }
+        let srgb = *PROPHOTO_TO_SRGB * nalgebra::Vector3::from(mapped);
+        [srgb[0].max(0.0), srgb[1].max(0.0), srgb[2].max(0.0)]
+    }
+}
+
+impl HueSatMap {
+    fn entry(&self, h: usize, s: usize, v: usize) -> [f32; 3] {
+        self.data[(v * self.hue_divisions + h) * self.sat_divisions + s]
+    }
+
+    fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let (h, s, v) = rgb_to_hsv(rgb);
+        if v <= 0.0 {
+            return rgb;
+        }
+
+        let hue_scaled = h * self.hue_divisions as f32 / 6.0;
+        let h0 = hue_scaled.floor() as usize % self.hue_divisions;
+        let h1 = (h0 + 1) % self.hue_divisions;
+        let hf = hue_scaled - hue_scaled.floor();
+        let (s0, s1, sf) = grid_position(s, self.sat_divisions);
+        let (v0, v1, vf) = grid_position(v, self.val_divisions);
+
+        let mut acc = [0.0f32; 3];
+        for (vi, vw) in [(v0, 1.0 - vf), (v1, vf)] {
+            for (hi, hw) in [(h0, 1.0 - hf), (h1, hf)] {
+                for (si, sw) in [(s0, 1.0 - sf), (s1, sf)] {
+                    let w = vw * hw * sw;
+                    if w == 0.0 {
+                        continue;
+                    }
+                    let e = self.entry(hi, si, vi);
+                    acc[0] += e[0] * w;
+                    acc[1] += e[1] * w;
+                    acc[2] += e[2] * w;
+                }
+            }
+        }
+
+        let new_h = h + acc[0] * 6.0 / 360.0;
+        let new_s = (s * acc[1]).clamp(0.0, 1.0);
+        let new_v = v * acc[2];
+        hsv_to_rgb(new_h, new_s, new_v)
+    }
+}
+
+struct TiffEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    value_offset: usize,
+}
+
+struct TiffReader<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> TiffReader<'a> {
+    fn u16_at(&self, offset: usize) -> Result<u16> {
+        let b = self.data.get(offset..offset + 2).ok_or_else(|| anyhow!("Unexpected end of profile"))?;
+        Ok(if self.little_endian { u16::from_le_bytes([b[0], b[1]]) } else { u16::from_be_bytes([b[0], b[1]]) })
+    }
+
+    fn u32_at(&self, offset: usize) -> Result<u32> {
+        let b = self.data.get(offset..offset + 4).ok_or_else(|| anyhow!("Unexpected end of profile"))?;
+        let bytes = [b[0], b[1], b[2], b[3]];
+        Ok(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    fn u64_at(&self, offset: usize) -> Result<u64> {
+        let (first, second) = (self.u32_at(offset)? as u64, self.u32_at(offset + 4)? as u64);
+        Ok(if self.little_endian { first | (second << 32) } else { (first << 32) | second })
+    }
+
+    fn entries(&self) -> Result<Vec<TiffEntry>> {
+        let ifd_offset = self.u32_at(4)? as usize;
+        let count = self.u16_at(ifd_offset)? as usize;
+        let mut entries = Vec::with_capacity(count);
+        for i in 0..count {
+            let base = ifd_offset + 2 + i * 12;
+            let field_type = self.u16_at(base + 2)?;
+            let count = self.u32_at(base + 4)?;
+            let size = match field_type {
+                3 | 8 => 2,
+                4 | 9 | 11 => 4,
+                5 | 10 | 12 => 8,
+                _ => 1,
+            } * count as usize;
+            let value_offset = if size <= 4 { base + 8 } else { self.u32_at(base + 8)? as usize };
+            entries.push(TiffEntry { tag: self.u16_at(base)?, field_type, count, value_offset });
+        }
+        Ok(entries)
+    }
+
+    fn read_f32s(&self, entry: &TiffEntry) -> Result<Vec<f32>> {
+        let mut values = Vec::with_capacity(entry.count as usize);
+        for i in 0..entry.count as usize {
+            let value = match entry.field_type {
+                3 => self.u16_at(entry.value_offset + i * 2)? as f32,
+                4 => self.u32_at(entry.value_offset + i * 4)? as f32,
+                9 => self.u32_at(entry.value_offset + i * 4)? as i32 as f32,
+                5 => {
+                    let num = self.u32_at(entry.value_offset + i * 8)? as f32;
+                    let den = self.u32_at(entry.value_offset + i * 8 + 4)? as f32;
+                    if den == 0.0 { 0.0 } else { num / den }
+                }
+                10 => {
+                    let num = self.u32_at(entry.value_offset + i * 8)? as i32 as f32;
+                    let den = self.u32_at(entry.value_offset + i * 8 + 4)? as i32 as f32;
+                    if den == 0.0 { 0.0 } else { num / den }
+                }
+                11 => f32::from_bits(self.u32_at(entry.value_offset + i * 4)?),
+                12 => f64::from_bits(self.u64_at(entry.value_offset + i * 8)?) as f32,
+                other => return Err(anyhow!("Unsupported TIFF field type {} in profile", other)),
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    fn read_string(&self, entry: &TiffEntry) -> Option<String> {
+        let bytes = self.data.get(entry.value_offset..entry.value_offset + entry.count as usize)?;
+        let s = String::from_utf8_lossy(bytes).trim_end_matches('\0').trim().to_string();
+        if s.is_empty() { None } else { Some(s) }
+    }
+}
+
+fn read_hue_sat_map(reader: &TiffReader, dims: Option<&TiffEntry>, data: Option<&TiffEntry>) -> Result<Option<HueSatMap>> {
+    let (Some(dims), Some(data)) = (dims, data) else {
+        return Ok(None);
+    };
+    let dims = reader.read_f32s(dims)?;
+    if dims.len() < 2 {
+        return Ok(None);
+    }
+    let hue_divisions = dims[0] as usize;
+    let sat_divisions = dims[1] as usize;
+    let val_divisions = dims.get(2).map(|v| *v as usize).unwrap_or(1).max(1);
+    let values = reader.read_f32s(data)?;
+    if hue_divisions == 0 || sat_divisions == 0 || values.len() != hue_divisions * sat_divisions * val_divisions * 3 {
+        return Err(anyhow!("Malformed hue/saturation map in camera profile"));
+    }
+    Ok(Some(HueSatMap {
+        hue_divisions,
+        sat_divisions,
+        val_divisions,
+        data: values.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+    }))
+}
+
+fn parse_dcp_profile(data: &[u8], fallback_name: &str) -> Result<CameraProfile> {
+    let little_endian = match data.get(0..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return Err(anyhow!("Not a valid DCP profile")),
+    };
+    let reader = TiffReader { data, little_endian };
+    let magic = reader.u16_at(2)?;
+    if magic != 0x4352 && magic != 42 {
+        return Err(anyhow!("Not a valid DCP profile"));
+    }
+
+    let entries = reader.entries()?;
+    let find = |tag: u16| entries.iter().find(|e| e.tag == tag);
+
+    let illuminant1 = find(50778).and_then(|e| reader.read_f32s(e).ok()).and_then(|v| v.first().copied());
+    let illuminant2 = find(50779).and_then(|e| reader.read_f32s(e).ok()).and_then(|v| v.first().copied());
+    let use_second = illuminant2 == Some(Illuminant::D65 as u16 as f32)
+        || (illuminant1 != Some(Illuminant::D65 as u16 as f32) && find(50722).is_some());
+
+    let matrix_entry = if use_second { find(50722).or(find(50721)) } else { find(50721) };
+    let xyz_to_cam = match matrix_entry {
+        Some(e) => {
+            let values = reader.read_f32s(e)?;
+            if values.len() != 9 {
+                return Err(anyhow!("Only three-channel DCP color matrices are supported"));
+            }
+            let mut m = [0.0f32; 9];
+            m.copy_from_slice(&values);
+            Some(m)
+        }
+        None => None,
+    };
+
+    let hue_sat_data = if use_second { find(50939).or(find(50938)) } else { find(50938) };
+    let hue_sat_map = read_hue_sat_map(&reader, find(50937), hue_sat_data)?;
+    let look_table = read_hue_sat_map(&reader, find(50981), find(50982))?;
+
+    Ok(CameraProfile {
+        name: find(50936).and_then(|e| reader.read_string(e)).unwrap_or_else(|| fallback_name.to_string()),
+        kind: CameraProfileKind::Dcp,
+        camera_model: find(50708).and_then(|e| reader.read_string(e)),
+        xyz_to_cam,
+        hue_sat_map,
+        look_table,
+    })
+}
+
+fn parse_icc_profile(data: &[u8], fallback_name: &str) -> Result<CameraProfile> {
+    if data.len() < 132 || &data[36..40] != b"acsp" {
+        return Err(anyhow!("Not a valid ICC profile"));
+    }
+    let reader = TiffReader { data, little_endian: false };
+    let tag_count = reader.u32_at(128)? as usize;
+
+    let mut columns: HashMap<&[u8], [f32; 3]> = HashMap::new();
+    for i in 0..tag_count {
+        let base = 132 + i * 12;
+        let signature = data.get(base..base + 4).ok_or_else(|| anyhow!("Truncated ICC tag table"))?;
+        if signature != b"rXYZ" && signature != b"gXYZ" && signature != b"bXYZ" {
+            continue;
+        }
+        let offset = reader.u32_at(base + 4)? as usize;
+        if data.get(offset..offset + 4) != Some(&b"XYZ "[..]) {
+            return Err(anyhow!("Unexpected ICC tag type for colorant"));
+        }
+        let mut xyz = [0.0f32; 3];
+        for (c, value) in xyz.iter_mut().enumerate() {
+            *value = reader.u32_at(offset + 8 + c * 4)? as i32 as f32 / 65536.0;
+        }
+        columns.insert(signature, xyz);
+    }
+
+    let (Some(r), Some(g), Some(b)) = (columns.get(&b"rXYZ"[..]), columns.get(&b"gXYZ"[..]), columns.get(&b"bXYZ"[..])) else {
+        return Err(anyhow!("Only matrix-based ICC input profiles are supported"));
+    };
+
+    let cam_to_xyz_d50 = Matrix3::new(r[0], g[0], b[0], r[1], g[1], b[1], r[2], g[2], b[2]);
+    let bradford = Matrix3::from_fn(|i, j| BRADFORD_D50_TO_D65[i][j]);
+    let xyz_to_cam = (bradford * cam_to_xyz_d50)
+        .try_inverse()
+        .ok_or_else(|| anyhow!("ICC colorant matrix is not invertible"))?;
+
+    let mut m = [0.0f32; 9];
+    for i in 0..3 {
+        for j in 0..3 {
+            m[i * 3 + j] = xyz_to_cam[(i, j)];
+        }
+    }
+
+    Ok(CameraProfile {
+        name: fallback_name.to_string(),
+        kind: CameraProfileKind::Icc,
+        camera_model: None,
+        xyz_to_cam: Some(m),
+        hue_sat_map: None,
+        look_table: None,
+    })
+}
+
+pub fn load_camera_profile(path: &Path) -> Result<CameraProfile> {
+    let data = fs::read(path)?;
+    let fallback_name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let extension = path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    match extension.as_str() {
+        "dcp" => parse_dcp_profile(&data, &fallback_name),
+        "icc" | "icm" => parse_icc_profile(&data, &fallback_name),
+        _ => Err(anyhow!("Unsupported camera profile format: {}", extension)),
+    }
+}
+
+fn get_camera_profiles_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(CAMERA_PROFILES_DIR);
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    Ok(dir)
+}
+
+fn profile_matches_camera(profile: &CameraProfile, file_name: &str, camera: &str) -> bool {
+    let camera = camera.to_lowercase();
+    match &profile.camera_model {
+        Some(model) => model.to_lowercase() == camera,
+        None => file_name.to_lowercase().contains(&camera),
+    }
+}
+
+pub fn init_camera_profiles(app_handle: &AppHandle, settings: &AppSettings) {
+    let Some(selected) = &settings.camera_profiles else {
+        return;
+    };
+    let Ok(dir) = get_camera_profiles_dir(app_handle) else {
+        return;
+    };
+    for (camera, file_name) in selected {
+        match load_camera_profile(&dir.join(file_name)) {
+            Ok(profile) => set_active_camera_profile(camera, Some(profile)),
+            Err(e) => eprintln!("Failed to load camera profile '{}' for {}: {}", file_name, camera, e),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_camera_profiles(path: String, app_handle: AppHandle) -> Result<CameraProfileSelection, String> {
+    let file_bytes = fs::read(&path).map_err(|e| e.to_string())?;
+    let source = RawSource::new_from_slice(&file_bytes);
+    let decoder = rawler::get_decoder(&source).map_err(|e| e.to_string())?;
+    let metadata = decoder
+        .raw_metadata(&source, &RawDecodeParams::default())
+        .map_err(|e| e.to_string())?;
+    let camera = camera_key(&metadata.make, &metadata.model);
+
+    let dir = get_camera_profiles_dir(&app_handle)?;
+    let mut profiles = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| e.to_string())?.filter_map(Result::ok) {
+        let file_path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        match load_camera_profile(&file_path) {
+            Ok(profile) => profiles.push(CameraProfileInfo {
+                matches_camera: profile_matches_camera(&profile, &file_name, &camera),
+                file_name,
+                name: profile.name,
+                kind: profile.kind,
+                camera_model: profile.camera_model,
+            }),
+            Err(e) => eprintln!("Skipping camera profile {}: {}", file_path.display(), e),
+        }
+    }
+    profiles.sort_by(|a, b| b.matches_camera.cmp(&a.matches_camera).then_with(|| a.name.cmp(&b.name)));
+
+    let settings = load_settings(app_handle).unwrap_or_default();
+    let selected = settings.camera_profiles.and_then(|m| m.get(&camera).cloned());
+
+    Ok(CameraProfileSelection { camera, selected, profiles })
+}
+
+#[tauri::command]
+pub fn set_camera_profile(
+    camera: String,
+    file_name: Option<String>,
+    state: tauri::State<crate::AppState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let profile = match &file_name {
+        Some(name) => {
+            if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+                return Err(format!("Invalid camera profile name: {}", name));
+            }
+            let dir = get_camera_profiles_dir(&app_handle)?;
+            Some(load_camera_profile(&dir.join(name)).map_err(|e| e.to_string())?)
+        }
+        None => None,
+    };
+
+    let mut settings = load_settings(app_handle.clone()).unwrap_or_default();
+    let mut selected = settings.camera_profiles.take().unwrap_or_default();
+    match &file_name {
+        Some(name) => selected.insert(camera.clone(), name.clone()),
+        None => selected.remove(&camera),
+    };
+    settings.camera_profiles = Some(selected);
+    save_settings(settings, app_handle)?;
+
+    set_active_camera_profile(&camera, profile);
+
+    // Decodes made with the previous profile are stale, so the open image has to be loaded again.
+    state.prefetch_cache.clear();
+    *state.cached_preview.lock().unwrap() = None;
+    *state.cached_full_resolution.lock().unwrap() = None;
+    *state.original_image.lock().unwrap() = None;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn import_camera_profile(source_path: String, app_handle: AppHandle) -> Result<CameraProfileInfo, String> {
+    let source = Path::new(&source_path);
+    let profile = load_camera_profile(source).map_err(|e| e.to_string())?;
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| "Invalid profile path".to_string())?
+        .to_string_lossy()
+        .to_string();
+    let dir = get_camera_profiles_dir(&app_handle)?;
+    fs::copy(source, dir.join(&file_name)).map_err(|e| e.to_string())?;
+
+    Ok(CameraProfileInfo {
+        file_name,
+        name: profile.name,
+        kind: profile.kind,
+        camera_model: profile.camera_model,
+        matches_camera: false,
+    })
+}
+
+fn is_bayer_image(raw_image: &RawImage) -> bool {
+    raw_image.cpp == 1
+        && matches!(&raw_image.photometric, RawPhotometricInterpretation::Cfa(config) if config.cfa.width == 2 && config.cfa.height == 2)
+}
+
+fn same_color_neighbors(data: &[u16], width: usize, height: usize, x: usize, y: usize) -> ([u16; 8], usize) {
+    let mut neighbors = [0u16; 8];
+    let mut count = 0;
+    for (dx, dy) in [(-2, -2), (0, -2), (2, -2), (-2, 0), (2, 0), (-2, 2), (0, 2), (2, 2)] {
+        let nx = x as i64 + dx;
+        let ny = y as i64 + dy;
+        if nx >= 0 && ny >= 0 && (nx as usize) < width && (ny as usize) < height {
+            neighbors[count] = data[ny as usize * width + nx as usize];
+            count += 1;
+        }
+    }
+    (neighbors, count)
+}
+
+fn detect_defective_pixels(data: &[u16], width: usize, height: usize, black: f32, white: f32) -> Vec<[u32; 2]> {
+    let min_signal = (white - black) * DEFECT_MIN_SIGNAL;
+    (2..height.saturating_sub(2))
+        .into_par_iter()
+        .flat_map_iter(|y| {
+            (2..width.saturating_sub(2)).filter_map(move |x| {
+                let value = data[y * width + x] as f32 - black;
+                let (neighbors, count) = same_color_neighbors(data, width, height, x, y);
+                let neighbors = &neighbors[..count];
+                let max_neighbor = neighbors.iter().map(|&n| n as f32 - black).fold(f32::MIN, f32::max).max(0.0);
+                let min_neighbor = neighbors.iter().map(|&n| n as f32 - black).fold(f32::MAX, f32::min).max(0.0);
+
+                let is_hot = value > min_signal && value > max_neighbor * DEFECT_NEIGHBOR_RATIO;
+                let is_dead = min_neighbor > min_signal && value * DEFECT_NEIGHBOR_RATIO < min_neighbor;
+                if is_hot || is_dead {
+                    Some([x as u32, y as u32])
+                } else {
+                    None
+                }
+            })
+        })
+        .collect()
+}
+
+fn detect_hot_pixels_in_dark_frame(data: &[u16], width: usize, height: usize) -> Vec<[u32; 2]> {
+    let count = data.len().max(1) as f64;
+    let mean = data.iter().map(|&v| v as f64).sum::<f64>() / count;
+    let variance = data.iter().map(|&v| (v as f64 - mean).powi(2)).sum::<f64>() / count;
+    let threshold = (mean + DARK_FRAME_SIGMA as f64 * variance.sqrt().max(1.0)) as f32;
+
+    (0..height)
+        .into_par_iter()
+        .flat_map_iter(|y| {
+            (0..width).filter_map(move |x| {
+                if data[y * width + x] as f32 > threshold {
+                    Some([x as u32, y as u32])
+                } else {
+                    None
+                }
+            })
+        })
+        .collect()
+}
+
+fn repair_pixels(data: &mut [u16], width: usize, height: usize, pixels: &[[u32; 2]]) {
+    let replacements: Vec<(usize, u16)> = pixels
+        .iter()
+        .filter(|[x, y]| (*x as usize) < width && (*y as usize) < height)
+        .filter_map(|[x, y]| {
+            let (mut neighbors, count) = same_color_neighbors(data, width, height, *x as usize, *y as usize);
+            if count == 0 {
+                return None;
+            }
+            let neighbors = &mut neighbors[..count];
+            neighbors.sort_unstable();
+            Some((*y as usize * width + *x as usize, neighbors[neighbors.len() / 2]))
+        })
+        .collect();
+
+    for (index, value) in replacements {
+        data[index] = value;
+    }
+}
+
+fn correct_defective_pixels(raw_image: &mut RawImage, camera: &str) {
+    if !is_bayer_image(raw_image) {
+        return;
+    }
+    let (width, height) = (raw_image.width, raw_image.height);
+    let black = raw_image.blacklevel.levels.first().map(|r| r.as_f32()).unwrap_or(0.0);
+    let white = raw_image.whitelevel.0.first().cloned().unwrap_or(u16::MAX as u32) as f32;
+    let pixel_map = PIXEL_MAPS.read().ok().and_then(|maps| maps.get(camera).cloned());
+
+    let RawImageData::Integer(data) = &mut raw_image.data else {
+        return;
+    };
+
+    let mut defects = if AUTO_DEFECT_DETECTION.load(Ordering::Relaxed) {
+        detect_defective_pixels(data, width, height, black, white)
+    } else {
+        Vec::new()
+    };
+    if let Some(map) = pixel_map.filter(|m| m.width == width && m.height == height) {
+        defects.extend_from_slice(&map.pixels);
+    }
+    if !defects.is_empty() {
+        repair_pixels(data, width, height, &defects);
+    }
+}
+
+pub fn apply_defect_pixel_settings(settings: &AppSettings) {
+    AUTO_DEFECT_DETECTION.store(settings.auto_hot_pixel_removal.unwrap_or(false), Ordering::Relaxed);
+}
+
+fn get_pixel_maps_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(PIXEL_MAPS_DIR);
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    Ok(dir)
+}
+
+fn pixel_map_file_name(camera: &str) -> String {
+    let sanitized: String = camera
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}.json", sanitized)
+}
+
+pub fn init_pixel_maps(app_handle: &AppHandle) {
+    let Ok(dir) = get_pixel_maps_dir(app_handle) else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+    let Ok(mut maps) = PIXEL_MAPS.write() else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        match fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|c| serde_json::from_str::<PixelMap>(&c).map_err(|e| e.to_string())) {
+            Ok(map) => {
+                maps.insert(map.camera.clone(), Arc::new(map));
+            }
+            Err(e) => eprintln!("Failed to load pixel map {}: {}", path.display(), e),
+        }
+    }
+}
+
+fn read_raw_sensor_info(path: &str) -> Result<(String, usize, usize), String> {
+    let file_bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let source = RawSource::new_from_slice(&file_bytes);
+    let decoder = rawler::get_decoder(&source).map_err(|e| e.to_string())?;
+    let raw_image = decoder
+        .raw_image(&source, &RawDecodeParams::default(), true)
+        .map_err(|e| e.to_string())?;
+    let metadata = decoder
+        .raw_metadata(&source, &RawDecodeParams::default())
+        .map_err(|e| e.to_string())?;
+    Ok((camera_key(&metadata.make, &metadata.model), raw_image.width, raw_image.height))
+}
+
+#[tauri::command]
+pub async fn scan_hot_pixels_from_dark_frame(path: String, reference_path: String, app_handle: AppHandle) -> Result<usize, String> {
+    let file_size = fs::metadata(&path).map_err(|e| e.to_string())?.len();
+    if file_size > MAX_DARK_FRAME_BYTES {
+        return Err("The dark frame file is too large.".to_string());
+    }
+    let (reference_camera, reference_width, reference_height) = read_raw_sensor_info(&reference_path)?;
+
+    let file_bytes = fs::read(&path).map_err(|e| e.to_string())?;
+    let source = RawSource::new_from_slice(&file_bytes);
+    let decoder = rawler::get_decoder(&source).map_err(|e| e.to_string())?;
+    let raw_image = decoder
+        .raw_image(&source, &RawDecodeParams::default(), false)
+        .map_err(|e| e.to_string())?;
+    let metadata = decoder
+        .raw_metadata(&source, &RawDecodeParams::default())
+        .map_err(|e| e.to_string())?;
+
+    if raw_image.cpp != 1 {
+        return Err("Dark frame scanning is only supported for single-channel sensor data.".to_string());
+    }
+    let RawImageData::Integer(data) = &raw_image.data else {
+        return Err("Dark frame scanning is not supported for floating point RAW data.".to_string());
+    };
+
+    let camera = camera_key(&metadata.make, &metadata.model);
+    if camera != reference_camera {
+        return Err(format!("The dark frame was taken with a {}, not a {}.", camera, reference_camera));
+    }
+    if raw_image.width != reference_width || raw_image.height != reference_height {
+        return Err(format!(
+            "The dark frame is {}x{}, but images from this camera are {}x{}.",
+            raw_image.width, raw_image.height, reference_width, reference_height
+        ));
+    }
+
+    let black = raw_image.blacklevel.levels.first().map(|r| r.as_f32()).unwrap_or(0.0) as f64;
+    let white = raw_image.whitelevel.0.first().cloned().unwrap_or(u16::MAX as u32) as f64;
+    let mean = data.iter().map(|&v| v as f64).sum::<f64>() / data.len().max(1) as f64;
+    if mean - black > (white - black) * MAX_DARK_FRAME_LEVEL {
+        return Err("The selected file is too bright to be a dark frame.".to_string());
+    }
+
+    let pixels = detect_hot_pixels_in_dark_frame(data, raw_image.width, raw_image.height);
+    let pixel_count = pixels.len();
+    if pixel_count as f64 > data.len() as f64 * MAX_DARK_FRAME_DEFECT_RATIO {
+        return Err(format!("Found {} hot pixels, which does not look like a dark frame.", pixel_count));
+    }
+    let map = PixelMap {
+        camera: camera.clone(),
+        width: raw_image.width,
+        height: raw_image.height,
+        pixels,
+    };
+
+    let map_path = get_pixel_maps_dir(&app_handle)?.join(pixel_map_file_name(&camera));
+    let json_string = serde_json::to_string(&map).map_err(|e| e.to_string())?;
+    fs::write(map_path, json_string).map_err(|e| e.to_string())?;
+
+    println!("Mapped {} hot pixels for {}", pixel_count, camera);
+    if let Ok(mut maps) = PIXEL_MAPS.write() {
+        maps.insert(camera, Arc::new(map));
+    }
+    Ok(pixel_count)
+}
+
+struct WhiteBalanceContext {
+    raw_image: RawImage,
+    orientation: Orientation,
+    xyz_to_cam: Matrix3<f32>,
+    cam_to_rgb: Matrix3<f32>,
+    as_shot_wb: [f32; 3],
+}
+
+fn load_white_balance_context(file_bytes: &[u8]) -> Result<WhiteBalanceContext> {
+    let source = RawSource::new_from_slice(file_bytes);
+    let decoder = rawler::get_decoder(&source)?;
+    let raw_image: RawImage = decoder.raw_image(&source, &RawDecodeParams::default(), false)?;
+    let metadata = decoder.raw_metadata(&source, &RawDecodeParams::default())?;
+    let orientation = metadata
+        .exif
+        .orientation
+        .map(Orientation::from_u16)
+        .unwrap_or(Orientation::Normal);
+
+    let profile_matrix = get_active_camera_profile(&camera_key(&metadata.make, &metadata.model)).and_then(|p| p.xyz_to_cam);
+    let matrix: Vec<f32> = match profile_matrix {
+        Some(m) => m.to_vec(),
+        None => raw_image
+            .color_matrix
+            .get(&Illuminant::D65)
+            .cloned()
+            .ok_or_else(|| anyhow!("Color matrix for D65 illuminant not found"))?,
+    };
+    if matrix.len() < 9 {
+        return Err(anyhow!("Unsupported color matrix for white balance"));
+    }
+
+    let xyz_to_cam = Matrix3::from_row_slice(&matrix[0..9]);
+    let mut rgb_to_cam = xyz_to_cam * Matrix3::from_fn(|i, j| SRGB_TO_XYZ_D65[i][j]);
+    for i in 0..3 {
+        let sum: f32 = (0..3).map(|j| rgb_to_cam[(i, j)]).sum();
+        if sum.abs() > 1e-6 {
+            for j in 0..3 {
+                rgb_to_cam[(i, j)] /= sum;
+            }
+        }
+    }
+    let cam_to_rgb = rgb_to_cam
+        .try_inverse()
+        .ok_or_else(|| anyhow!("Camera color matrix is not invertible"))?;
+
+    let wb = raw_image.wb_coeffs;
+    let as_shot_wb = if wb[0].is_nan() || wb[1] <= 0.0 {
+        let neutral = raw_image.neutralwb();
+        [neutral[0], neutral[1], neutral[2]]
+    } else {
+        [wb[0] / wb[1], 1.0, wb[2] / wb[1]]
+    };
+
+    Ok(WhiteBalanceContext { raw_image, orientation, xyz_to_cam, cam_to_rgb, as_shot_wb })
+}
+
+fn unorient_point(x: f32, y: f32, orientation: Orientation) -> (f32, f32) {
+    match orientation {
+        Orientation::Normal | Orientation::Unknown => (x, y),
+        Orientation::HorizontalFlip => (1.0 - x, y),
+        Orientation::Rotate180 => (1.0 - x, 1.0 - y),
+        Orientation::VerticalFlip => (x, 1.0 - y),
+        Orientation::Transpose => (1.0 - y, 1.0 - x),
+        Orientation::Rotate90 => (y, 1.0 - x),
+        Orientation::Transverse => (y, x),
+        Orientation::Rotate270 => (1.0 - y, x),
+    }
+}
+
+fn camera_neutral_to_adjustments(ctx: &WhiteBalanceContext, camera_rgb: [f32; 3]) -> (f32, f32) {
+    let balanced = nalgebra::Vector3::new(
+        camera_rgb[0] * ctx.as_shot_wb[0],
+        camera_rgb[1] * ctx.as_shot_wb[1],
+        camera_rgb[2] * ctx.as_shot_wb[2],
+    );
+    let rgb = ctx.cam_to_rgb * balanced;
+    neutralize_white_balance([rgb[0], rgb[1], rgb[2]])
+}
+
+pub fn white_balance_from_point(file_bytes: &[u8], x: f32, y: f32) -> Result<(f32, f32)> {
+    let ctx = load_white_balance_context(file_bytes)?;
+    let raw = &ctx.raw_image;
+    let (ux, uy) = unorient_point(x.clamp(0.0, 1.0), y.clamp(0.0, 1.0), ctx.orientation);
+
+    let (origin_x, origin_y, area_w, area_h) = match raw.crop_area.or(raw.active_area) {
+        Some(rect) => (rect.p.x, rect.p.y, rect.d.w, rect.d.h),
+        None => (0, 0, raw.width, raw.height),
+    };
+    let center_x = origin_x + ((ux * area_w as f32) as usize).min(area_w.saturating_sub(1));
+    let center_y = origin_y + ((uy * area_h as f32) as usize).min(area_h.saturating_sub(1));
+
+    let black = raw.blacklevel.levels.first().map(|r| r.as_f32()).unwrap_or(0.0);
+    let white = raw.whitelevel.0.first().cloned().unwrap_or(u16::MAX as u32) as f32;
+    let range = (white - black).max(1.0);
+    // Only the window is read, so the buffer isn't converted to float as a whole.
+    let pixel = |index: usize| match &raw.data {
+        RawImageData::Integer(data) => data[index] as f32,
+        RawImageData::Float(data) => data[index],
+    };
+
+    let mut sums = [0.0f64; 3];
+    let mut counts = [0usize; 3];
+    let x_range = center_x.saturating_sub(WHITE_BALANCE_SAMPLE_RADIUS)..(center_x + WHITE_BALANCE_SAMPLE_RADIUS + 1).min(raw.width);
+    let y_range = center_y.saturating_sub(WHITE_BALANCE_SAMPLE_RADIUS)..(center_y + WHITE_BALANCE_SAMPLE_RADIUS + 1).min(raw.height);
+
+    for py in y_range {
+        for px in x_range.clone() {
+            match (&raw.photometric, raw.cpp) {
+                (RawPhotometricInterpretation::Cfa(config), 1) => {
+                    let channel = config.cfa.color_at(py, px);
+                    if channel < 3 {
+                        let value = (pixel(py * raw.width + px) - black) / range;
+                        if value < 0.98 {
+                            sums[channel] += value as f64;
+                            counts[channel] += 1;
+                        }
+                    }
+                }
+                (_, cpp) if cpp >= 3 => {
+                    let base = (py * raw.width + px) * cpp;
+                    for c in 0..3 {
+                        sums[c] += ((pixel(base + c) - black) / range) as f64;
+                        counts[c] += 1;
+                    }
+                }
+                _ => return Err(anyhow!("White balance sampling is not supported for monochrome images")),
+            }
+        }
+    }
+
+    if counts.iter().any(|&c| c == 0) {
+        return Err(anyhow!("Not enough unclipped data at the selected point"));
+    }
+    let camera_rgb = [
+        (sums[0] / counts[0] as f64) as f32,
+        (sums[1] / counts[1] as f64) as f32,
+        (sums[2] / counts[2] as f64) as f32,
+    ];
+    if camera_rgb.iter().any(|&c| c <= 0.0) {
+        return Err(anyhow!("Selected point is too dark to sample white balance"));
+    }
+
+    Ok(camera_neutral_to_adjustments(&ctx, camera_rgb))
+}
+
+fn cct_to_xy(cct: f32) -> (f64, f64) {
+    let t = cct.clamp(1667.0, 25000.0) as f64;
+    if t <= 4000.0 {
+        let x = -0.2661239e9 / t.powi(3) - 0.2343589e6 / t.powi(2) + 0.8776956e3 / t + 0.179910;
+        let y = if t <= 2222.0 {
+            -1.1063814 * x.powi(3) - 1.34811020 * x.powi(2) + 2.18555832 * x - 0.20219683
+        } else {
+            -0.9549476 * x.powi(3) - 1.37418593 * x.powi(2) + 2.09137015 * x - 0.16748867
+        };
+        (x, y)
+    } else {
+        let x = if t <= 7000.0 {
+            -4.6070e9 / t.powi(3) + 2.9678e6 / t.powi(2) + 0.09911e3 / t + 0.244063
+        } else {
+            -2.0064e9 / t.powi(3) + 1.9018e6 / t.powi(2) + 0.24748e3 / t + 0.237040
+        };
+        (x, -3.0 * x * x + 2.87 * x - 0.275)
+    }
+}
+
+fn kelvin_to_adjustments(ctx: &WhiteBalanceContext, cct: f32) -> (f32, f32) {
+    let (x, y) = cct_to_xy(cct);
+    let white_xyz = nalgebra::Vector3::new((x / y) as f32, 1.0, ((1.0 - x - y) / y) as f32);
+    let camera_white = ctx.xyz_to_cam * white_xyz;
+    camera_neutral_to_adjustments(ctx, [camera_white[0], camera_white[1], camera_white[2]])
+}
+
+pub fn white_balance_presets(file_bytes: &[u8]) -> Result<Vec<WhiteBalancePreset>> {
+    let ctx = load_white_balance_context(file_bytes)?;
+    let mut presets = vec![WhiteBalancePreset { name: "As Shot".to_string(), temperature: 0.0, tint: 0.0 }];
+
+    for (name, cct) in WHITE_BALANCE_PRESETS {
+        let (temperature, tint) = kelvin_to_adjustments(&ctx, cct);
+        presets.push(WhiteBalancePreset { name: name.to_string(), temperature, tint });
+    }
+
+    Ok(presets)
+}
+
+// For white balance set in other raw developers, either as a color temperature or as per-channel
+// multipliers applied to the camera data.
+pub fn white_balance_from_kelvin(file_bytes: &[u8], kelvin: f32) -> Result<(f32, f32)> {
+    let ctx = load_white_balance_context(file_bytes)?;
+    Ok(kelvin_to_adjustments(&ctx, kelvin))
+}
+
+pub fn white_balance_from_multipliers(file_bytes: &[u8], multipliers: [f32; 3]) -> Result<(f32, f32)> {
+    let ctx = load_white_balance_context(file_bytes)?;
+    let neutral = multipliers.map(|m| 1.0 / m.max(1e-6));
+    Ok(camera_neutral_to_adjustments(&ctx, neutral))
+}
@@ -0,0 +1,62 @@
+struct ScopesParams {
+    core_x: u32,
+    core_y: u32,
+    core_width: u32,
+    core_height: u32,
+    column_offset: u32,
+    scope_width: u32,
+    _pad0: u32,
+    _pad1: u32,
+}
+
+@group(0) @binding(0) var input_texture: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> params: ScopesParams;
+@group(0) @binding(2) var<storage, read_write> counts: array<atomic<u32>>;
+
+const HISTOGRAM_BINS: u32 = 256u;
+const WAVEFORM_WIDTH: u32 = 256u;
+const WAVEFORM_HEIGHT: u32 = 256u;
+const VECTORSCOPE_SIZE: u32 = 256u;
+
+fn waveform_index(channel: u32, value: u32, column: u32) -> u32 {
+    let plane = WAVEFORM_WIDTH * WAVEFORM_HEIGHT;
+    return 4u * HISTOGRAM_BINS + channel * plane + (WAVEFORM_HEIGHT - 1u - value) * WAVEFORM_WIDTH + column;
+}
+
+// Cb is plotted to the right and Cr upwards, so the rec. 709 primaries land where a vectorscope's
+// graticule expects them.
+fn vectorscope_index(rgb: vec3<f32>) -> u32 {
+    let y = dot(rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let cb = (rgb.b - y) / 1.8556;
+    let cr = (rgb.r - y) / 1.5748;
+    let scale = f32(VECTORSCOPE_SIZE - 1u);
+    let u = u32(round(clamp(cb + 0.5, 0.0, 1.0) * scale));
+    let v = u32(round(clamp(cr + 0.5, 0.0, 1.0) * scale));
+    let offset = 4u * HISTOGRAM_BINS + 4u * WAVEFORM_WIDTH * WAVEFORM_HEIGHT;
+    return offset + (VECTORSCOPE_SIZE - 1u - v) * VECTORSCOPE_SIZE + u;
+}
+
+@compute @workgroup_size(16, 16, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.core_width || id.y >= params.core_height) {
+        return;
+    }
+
+    let pixel = textureLoad(input_texture, vec2<u32>(id.x + params.core_x, id.y + params.core_y), 0);
+    let rgb = vec3<u32>(round(clamp(pixel.rgb, vec3<f32>(0.0), vec3<f32>(1.0)) * 255.0));
+    let luma_value = 0.2126 * f32(rgb.r) + 0.7152 * f32(rgb.g) + 0.0722 * f32(rgb.b);
+    let luma = min(u32(round(luma_value)), 255u);
+
+    atomicAdd(&counts[rgb.r], 1u);
+    atomicAdd(&counts[HISTOGRAM_BINS + rgb.g], 1u);
+    atomicAdd(&counts[2u * HISTOGRAM_BINS + rgb.b], 1u);
+    atomicAdd(&counts[3u * HISTOGRAM_BINS + luma], 1u);
+
+    let column = min((params.column_offset + id.x) * WAVEFORM_WIDTH / params.scope_width, WAVEFORM_WIDTH - 1u);
+    atomicAdd(&counts[waveform_index(0u, rgb.r, column)], 1u);
+    atomicAdd(&counts[waveform_index(1u, rgb.g, column)], 1u);
+    atomicAdd(&counts[waveform_index(2u, rgb.b, column)], 1u);
+    atomicAdd(&counts[waveform_index(3u, luma, column)], 1u);
+
+    atomicAdd(&counts[vectorscope_index(vec3<f32>(rgb) / 255.0)], 1u);
+}
@@ -37,6 +37,7 @@ struct GlobalAdjustments {
     clarity: f32,
     dehaze: f32,
     structure: f32,
+    texture: f32,
     vignette_amount: f32,
     vignette_midpoint: f32,
     vignette_roundness: f32,
@@ -53,7 +54,25 @@ struct GlobalAdjustments {
     negative_green_balance: f32,
     negative_blue_balance: f32,
     _pad_neg1: f32,
-    _pad_neg2: f32,
+
+    chromatic_aberration: f32,
+    lut_intensity: f32,
+    lut_size: u32,
+    working_space: u32,
+
+    defringe_purple_amount: f32,
+    defringe_purple_hue_min: f32,
+    defringe_purple_hue_max: f32,
+    defringe_green_amount: f32,
+    defringe_green_hue_min: f32,
+    defringe_green_hue_max: f32,
+    _pad_df1: f32,
+    _pad_df2: f32,
+
+    glow_amount: f32,
+    glow_radius: f32,
+    glow_threshold: f32,
+    custom_effect_amount: f32,
 
     color_grading_shadows: ColorGradeSettings,
     color_grading_midtones: ColorGradeSettings,
@@ -92,12 +111,26 @@ struct MaskAdjustments {
     clarity: f32,
     dehaze: f32,
     structure: f32,
+    texture: f32,
     
-    _pad1: f32,
     _pad2: f32,
     _pad3: f32,
     _pad4: f32,
 
+    defringe_purple_amount: f32,
+    defringe_purple_hue_min: f32,
+    defringe_purple_hue_max: f32,
+    defringe_green_amount: f32,
+    defringe_green_hue_min: f32,
+    defringe_green_hue_max: f32,
+    _pad_df1: f32,
+    _pad_df2: f32,
+
+    glow_amount: f32,
+    glow_radius: f32,
+    glow_threshold: f32,
+    _pad_glow1: f32,
+
     color_grading_shadows: ColorGradeSettings,
     color_grading_midtones: ColorGradeSettings,
     color_grading_highlights: ColorGradeSettings,
@@ -119,11 +152,18 @@ struct MaskAdjustments {
 
 struct AllAdjustments {
     global: GlobalAdjustments,
-    mask_adjustments: array<MaskAdjustments, 16>,
     mask_count: u32,
     tile_offset_x: u32,
     tile_offset_y: u32,
-    mask_atlas_cols: u32,
+    input_offset_x: u32,
+    input_offset_y: u32,
+    full_width: u32,
+    full_height: u32,
+    hdr_output: u32,
+    optical_center_x: f32,
+    optical_center_y: f32,
+    lateral_ca_r: f32,
+    lateral_ca_b: f32,
 }
 
 struct HslRange {
@@ -146,35 +186,97 @@ const HSL_RANGES: array<HslRange, 8> = array<HslRange, 8>(
 @group(0) @binding(1) var output_texture: texture_storage_2d<rgba8unorm, write>;
 @group(0) @binding(2) var<uniform> adjustments: AllAdjustments;
 
-@group(0) @binding(3) var mask0: texture_2d<f32>;
-@group(0) @binding(4) var mask1: texture_2d<f32>;
-@group(0) @binding(5) var mask2: texture_2d<f32>;
-@group(0) @binding(6) var mask3: texture_2d<f32>;
-@group(0) @binding(7) var mask4: texture_2d<f32>;
-@group(0) @binding(8) var mask5: texture_2d<f32>;
-@group(0) @binding(9) var mask6: texture_2d<f32>;
-@group(0) @binding(10) var mask7: texture_2d<f32>;
-@group(0) @binding(11) var mask8: texture_2d<f32>;
-@group(0) @binding(12) var mask9: texture_2d<f32>;
-@group(0) @binding(13) var mask10: texture_2d<f32>;
-@group(0) @binding(14) var mask11: texture_2d<f32>;
-@group(0) @binding(15) var mask12: texture_2d<f32>;
-@group(0) @binding(16) var mask13: texture_2d<f32>;
-@group(0) @binding(17) var mask14: texture_2d<f32>;
-@group(0) @binding(18) var mask15: texture_2d<f32>;
+@group(0) @binding(3) var<storage, read> mask_adjustments: array<MaskAdjustments>;
+@group(0) @binding(4) var mask_textures: texture_2d_array<f32>;
+
+@group(0) @binding(5) var lut_texture: texture_3d<f32>;
 
 const LUMA_COEFF = vec3<f32>(0.2126, 0.7152, 0.0722);
 
+// Working spaces: 0 = linear sRGB, 1 = linear Rec.2020, 2 = linear ProPhoto (D50, Bradford adapted).
+// Input pixels are sRGB and converted into the working space when loaded; from_working converts
+// back to linear sRGB before the display encoding, so curves, LUTs and grain stay in sRGB.
+const REC2020_LUMA_COEFF = vec3<f32>(0.2627, 0.6780, 0.0593);
+const PROPHOTO_LUMA_COEFF = vec3<f32>(0.2880, 0.7119, 0.0001);
+const SRGB_TO_REC2020 = mat3x3<f32>(
+    vec3<f32>(0.6275, 0.0691, 0.0164),
+    vec3<f32>(0.3293, 0.9195, 0.0880),
+    vec3<f32>(0.0433, 0.0114, 0.8954)
+);
+const REC2020_TO_SRGB = mat3x3<f32>(
+    vec3<f32>(1.6602, -0.1246, -0.0182),
+    vec3<f32>(-0.5875, 1.1329, -0.1006),
+    vec3<f32>(-0.0728, -0.0083, 1.1190)
+);
+const SRGB_TO_PROPHOTO = mat3x3<f32>(
+    vec3<f32>(0.5293, 0.0984, 0.0169),
+    vec3<f32>(0.3301, 0.8735, 0.1177),
+    vec3<f32>(0.1406, 0.0282, 0.8654)
+);
+const PROPHOTO_TO_SRGB = mat3x3<f32>(
+    vec3<f32>(2.0341, -0.2288, -0.0086),
+    vec3<f32>(-0.7273, 1.2317, -0.1533),
+    vec3<f32>(-0.3067, -0.0029, 1.1619)
+);
+
+fn working_luma_coeff() -> vec3<f32> {
+    switch (adjustments.global.working_space) {
+        case 1u: { return REC2020_LUMA_COEFF; }
+        case 2u: { return PROPHOTO_LUMA_COEFF; }
+        default: { return LUMA_COEFF; }
+    }
+}
+
+// Luma of a color in the working space.
 fn get_luma(c: vec3<f32>) -> f32 {
+    return dot(c, working_luma_coeff());
+}
+
+// Luma of an sRGB color, for the steps after the working space.
+fn get_display_luma(c: vec3<f32>) -> f32 {
     return dot(c, LUMA_COEFF);
 }
 
+fn to_working(linear_srgb: vec3<f32>) -> vec3<f32> {
+    switch (adjustments.global.working_space) {
+        case 1u: { return SRGB_TO_REC2020 * linear_srgb; }
+        case 2u: { return SRGB_TO_PROPHOTO * linear_srgb; }
+        default: { return linear_srgb; }
+    }
+}
+
+// Colors outside the sRGB gamut are desaturated towards their luma until they fit, which keeps the
+// hue instead of clipping channels independently.
+fn compress_to_srgb_gamut(c: vec3<f32>) -> vec3<f32> {
+    let lowest = min(c.r, min(c.g, c.b));
+    if (lowest >= 0.0) {
+        return c;
+    }
+    let luma = max(get_display_luma(c), 0.0);
+    let t = luma / max(luma - lowest, 1e-6);
+    return vec3<f32>(luma) + (c - vec3<f32>(luma)) * t;
+}
+
+fn from_working(working: vec3<f32>) -> vec3<f32> {
+    switch (adjustments.global.working_space) {
+        case 1u: { return compress_to_srgb_gamut(REC2020_TO_SRGB * working); }
+        case 2u: { return compress_to_srgb_gamut(PROPHOTO_TO_SRGB * working); }
+        default: { return working; }
+    }
+}
+
+fn load_working(coords: vec2<i32>) -> vec3<f32> {
+    return to_working(srgb_to_linear(textureLoad(input_texture, coords, 0).rgb));
+}
+
+// Mirrored around zero, so float sources converted from wider profiles keep their out-of-sRGB colors.
 fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let v = abs(c);
     let cutoff = vec3<f32>(0.04045);
     let a = vec3<f32>(0.055);
-    let higher = pow((c + a) / (1.0 + a), vec3<f32>(2.4));
-    let lower = c / 12.92;
-    return select(higher, lower, c <= cutoff);
+    let higher = pow((v + a) / (1.0 + a), vec3<f32>(2.4));
+    let lower = v / 12.92;
+    return sign(c) * select(higher, lower, v <= cutoff);
 }
 
 fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
@@ -520,7 +622,7 @@ fn apply_local_contrast(
             let offset = vec2<i32>(x, y);
             let sample_coords = clamp(coords_i + offset, vec2<i32>(0), max_coords);
             
-            let sample_linear = srgb_to_linear(textureLoad(input_texture, sample_coords, 0).rgb);
+            let sample_linear = load_working(sample_coords);
             let sample_luma = get_luma(sample_linear);
 
             let spatial_dist_sq = f32(x * x + y * y);
@@ -579,7 +681,7 @@ fn apply_noise_reduction(color: vec3<f32>, coords_i: vec2<i32>, luma_amount: f32
         for (var x = -1; x <= 1; x = x + 1) {
             let offset = vec2<i32>(x, y);
             let sample_coords = clamp(coords_i + offset, vec2<i32>(0), max_coords);
-            let sample_color_linear = srgb_to_linear(textureLoad(input_texture, sample_coords, 0).rgb);
+            let sample_color_linear = load_working(sample_coords);
             var luma_weight = 1.0;
             if (luma_amount > 0.0) { let luma_diff = abs(get_luma(sample_color_linear) - center_luma); luma_weight = 1.0 - smoothstep(0.0, 0.1, luma_diff / luma_amount); }
             var color_weight = 1.0;
@@ -593,6 +695,90 @@ fn apply_noise_reduction(color: vec3<f32>, coords_i: vec2<i32>, luma_amount: f32
     return color;
 }
 
+fn full_image_dims() -> vec2<f32> {
+    return vec2<f32>(f32(adjustments.full_width), f32(adjustments.full_height));
+}
+
+fn sample_input_bilinear(pos: vec2<f32>) -> vec3<f32> {
+    let max_coords = vec2<f32>(textureDimensions(input_texture) - 1u);
+    let input_offset = vec2<f32>(f32(adjustments.input_offset_x), f32(adjustments.input_offset_y));
+    let p = clamp(pos - input_offset, vec2<f32>(0.0), max_coords);
+    let p0 = vec2<i32>(floor(p));
+    let p1 = min(p0 + vec2<i32>(1), vec2<i32>(max_coords));
+    let f = p - floor(p);
+    let c00 = textureLoad(input_texture, p0, 0).rgb;
+    let c10 = textureLoad(input_texture, vec2<i32>(p1.x, p0.y), 0).rgb;
+    let c01 = textureLoad(input_texture, vec2<i32>(p0.x, p1.y), 0).rgb;
+    let c11 = textureLoad(input_texture, p1, 0).rgb;
+    return to_working(srgb_to_linear(mix(mix(c00, c10, f.x), mix(c01, c11, f.x), f.y)));
+}
+
+fn apply_chromatic_aberration_correction(color: vec3<f32>, coords_i: vec2<i32>, amount: f32) -> vec3<f32> {
+    if (amount <= 0.0) { return color; }
+    // Red and blue are rescaled about the lens axis by the per-image estimate, which a crop moves
+    // away from the image center.
+    let center = vec2<f32>(adjustments.optical_center_x, adjustments.optical_center_y);
+    let offset = vec2<f32>(coords_i) - center;
+    let strength = clamp(amount, 0.0, 1.0);
+    let r_scale = 1.0 + (adjustments.lateral_ca_r - 1.0) * strength;
+    let b_scale = 1.0 + (adjustments.lateral_ca_b - 1.0) * strength;
+    return vec3<f32>(
+        sample_input_bilinear(center + offset * r_scale).r,
+        color.g,
+        sample_input_bilinear(center + offset * b_scale).b
+    );
+}
+
+// HDR preview output: 0 = SDR, 1 = PQ, 2 = HLG. SDR white sits at the BT.2408 reference level.
+const HDR_REFERENCE_WHITE_NITS: f32 = 203.0;
+const HDR_PEAK_NITS: f32 = 1000.0;
+const HLG_REFERENCE_WHITE: f32 = 0.2647;
+const REC709_TO_REC2020 = mat3x3<f32>(
+    vec3<f32>(0.6274, 0.0691, 0.0164),
+    vec3<f32>(0.3293, 0.9195, 0.0880),
+    vec3<f32>(0.0433, 0.0114, 0.8956)
+);
+
+fn pq_oetf(nits: vec3<f32>) -> vec3<f32> {
+    let m1 = 0.1593017578125;
+    let m2 = 78.84375;
+    let c1 = 0.8359375;
+    let c2 = 18.8515625;
+    let c3 = 18.6875;
+    let y = pow(clamp(nits / 10000.0, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(m1));
+    return pow((c1 + c2 * y) / (1.0 + c3 * y), vec3<f32>(m2));
+}
+
+fn hlg_oetf(e: vec3<f32>) -> vec3<f32> {
+    let a = 0.17883277;
+    let b = 0.28466892;
+    let c = 0.55991073;
+    let clamped = clamp(e, vec3<f32>(0.0), vec3<f32>(1.0));
+    let lower = sqrt(3.0 * clamped);
+    let upper = a * log(max(12.0 * clamped - b, vec3<f32>(1e-6))) + c;
+    return select(upper, lower, clamped <= vec3<f32>(1.0 / 12.0));
+}
+
+// The SDR rendering plus whatever the linear adjustments pushed above white, relative to SDR white.
+fn encode_hdr(display_srgb: vec3<f32>, headroom: vec3<f32>, mode: u32) -> vec3<f32> {
+    let linear = srgb_to_linear(clamp(display_srgb, vec3<f32>(0.0), vec3<f32>(1.0))) + headroom;
+    let relative = min(max(REC709_TO_REC2020 * linear, vec3<f32>(0.0)), vec3<f32>(HDR_PEAK_NITS / HDR_REFERENCE_WHITE_NITS));
+    if (mode == 2u) {
+        return hlg_oetf(relative * HLG_REFERENCE_WHITE);
+    }
+    return pq_oetf(relative * HDR_REFERENCE_WHITE_NITS);
+}
+
+// The SDR rendering plus the linear values outside 0..1 the display clip removed, sRGB encoded with
+// the transfer extended to negative and above-white values. Used for wide gamut exports.
+fn encode_extended_srgb(display_srgb: vec3<f32>, out_of_range: vec3<f32>) -> vec3<f32> {
+    let linear = srgb_to_linear(clamp(display_srgb, vec3<f32>(0.0), vec3<f32>(1.0))) + out_of_range;
+    let magnitude = abs(linear);
+    let higher = 1.055 * pow(magnitude, vec3<f32>(1.0 / 2.4)) - 0.055;
+    let lower = magnitude * 12.92;
+    return sign(linear) * select(higher, lower, magnitude <= vec3<f32>(0.0031308));
+}
+
 fn aces_fitted(c: vec3<f32>) -> vec3<f32> {
     return c;
 }
@@ -614,9 +800,9 @@ fn apply_all_curves(color: vec3<f32>, luma_curve: array<Point, 16>, luma_curve_c
 
     if (rgb_curves_are_active) {
         let color_graded = vec3<f32>(apply_curve(color.r, red_curve, red_curve_count), apply_curve(color.g, green_curve, green_curve_count), apply_curve(color.b, blue_curve, blue_curve_count));
-        let luma_initial = get_luma(color);
+        let luma_initial = get_display_luma(color);
         let luma_target = apply_curve(luma_initial, luma_curve, luma_curve_count);
-        let luma_graded = get_luma(color_graded);
+        let luma_graded = get_display_luma(color_graded);
         var final_color: vec3<f32>;
         if (luma_graded > 0.001) { final_color = color_graded * (luma_target / luma_graded); } else { final_color = vec3<f32>(luma_target); }
         let max_comp = max(final_color.r, max(final_color.g, final_color.b));
@@ -627,21 +813,148 @@ fn apply_all_curves(color: vec3<f32>, luma_curve: array<Point, 16>, luma_curve_c
     }
 }
 
+fn apply_texture(processed_color_linear: vec3<f32>, coords_i: vec2<i32>, amount: f32) -> vec3<f32> {
+    if (amount == 0.0) {
+        return processed_color_linear;
+    }
+    let center_luma = get_luma(processed_color_linear);
+    let max_coords = vec2<i32>(textureDimensions(input_texture) - 1u);
+    let fine_sigma = 1.0;
+    let coarse_sigma = 3.0;
+    let fine_radius = 2;
+    // The coarse blur reads every other pixel, which its sigma of three pixels tolerates. Together
+    // with the 5x5 fine window that's 74 taps instead of a full 13x13 window for both.
+    let coarse_steps = 3;
+    let coarse_stride = 2;
+    var fine_luma = 0.0;
+    var fine_weight = 0.0;
+    for (var y = -fine_radius; y <= fine_radius; y += 1) {
+        for (var x = -fine_radius; x <= fine_radius; x += 1) {
+            let sample_coords = clamp(coords_i + vec2<i32>(x, y), vec2<i32>(0), max_coords);
+            let sample_luma = get_luma(load_working(sample_coords));
+            let fw = exp(-f32(x * x + y * y) / (2.0 * fine_sigma * fine_sigma));
+            fine_luma += sample_luma * fw;
+            fine_weight += fw;
+        }
+    }
+    var coarse_luma = 0.0;
+    var coarse_weight = 0.0;
+    for (var y = -coarse_steps; y <= coarse_steps; y += 1) {
+        for (var x = -coarse_steps; x <= coarse_steps; x += 1) {
+            let offset = vec2<i32>(x, y) * coarse_stride;
+            let sample_coords = clamp(coords_i + offset, vec2<i32>(0), max_coords);
+            let sample_luma = get_luma(load_working(sample_coords));
+            let cw = exp(-f32(offset.x * offset.x + offset.y * offset.y) / (2.0 * coarse_sigma * coarse_sigma));
+            coarse_luma += sample_luma * cw;
+            coarse_weight += cw;
+        }
+    }
+    let band = fine_luma / fine_weight - coarse_luma / coarse_weight;
+    // Compress large band values so strong edges don't pick up halos.
+    let detail = band / (1.0 + abs(band) * 10.0);
+    let new_luma = max(center_luma + detail * amount * 2.0, 0.0);
+    let safe_center_luma = max(center_luma, 0.0001);
+    return processed_color_linear * (new_luma / safe_center_luma);
+}
+
+fn get_hue_range_influence(hue: f32, hue_min: f32, hue_max: f32) -> f32 {
+    let feather = 10.0;
+    var h = hue;
+    var h_max = hue_max;
+    if (h_max < hue_min) {
+        h_max += 360.0;
+        if (h < hue_min) { h += 360.0; }
+    }
+    let rising = smoothstep(hue_min - feather, hue_min, h);
+    let falling = 1.0 - smoothstep(h_max, h_max + feather, h);
+    return rising * falling;
+}
+
+fn apply_defringe(
+    color: vec3<f32>,
+    coords_i: vec2<i32>,
+    purple_amount: f32,
+    purple_hue_min: f32,
+    purple_hue_max: f32,
+    green_amount: f32,
+    green_hue_min: f32,
+    green_hue_max: f32
+) -> vec3<f32> {
+    if (purple_amount <= 0.0 && green_amount <= 0.0) {
+        return color;
+    }
+    let hsv = rgb_to_hsv(color);
+    if (hsv.y < 0.05) {
+        return color;
+    }
+
+    let max_coords = vec2<i32>(textureDimensions(input_texture) - 1u);
+    var min_luma = 1.0;
+    var max_luma = 0.0;
+    for (var y = -2; y <= 2; y += 1) {
+        for (var x = -2; x <= 2; x += 1) {
+            let sample_coords = clamp(coords_i + vec2<i32>(x, y), vec2<i32>(0), max_coords);
+            let sample_luma = get_luma(load_working(sample_coords));
+            min_luma = min(min_luma, sample_luma);
+            max_luma = max(max_luma, sample_luma);
+        }
+    }
+    let edge_strength = smoothstep(0.05, 0.3, max_luma - min_luma);
+    if (edge_strength <= 0.0) {
+        return color;
+    }
+
+    let purple = purple_amount * get_hue_range_influence(hsv.x, purple_hue_min, purple_hue_max);
+    let green = green_amount * get_hue_range_influence(hsv.x, green_hue_min, green_hue_max);
+    let strength = clamp(max(purple, green) * edge_strength * smoothstep(0.05, 0.25, hsv.y), 0.0, 1.0);
+    return mix(color, vec3<f32>(get_luma(color)), strength);
+}
+
+fn apply_glow(color: vec3<f32>, coords_i: vec2<i32>, amount: f32, radius: f32, threshold: f32) -> vec3<f32> {
+    if (amount <= 0.0 || radius <= 0.0) {
+        return color;
+    }
+    let max_coords = vec2<i32>(textureDimensions(input_texture) - 1u);
+    let steps = 6;
+    let step_size = max(radius / f32(steps), 1.0);
+    let sigma = radius * 0.5;
+    var glow = vec3<f32>(0.0);
+    var total_weight = 0.0;
+    for (var y = -steps; y <= steps; y += 1) {
+        for (var x = -steps; x <= steps; x += 1) {
+            let offset = vec2<f32>(f32(x), f32(y)) * step_size;
+            let sample_coords = clamp(coords_i + vec2<i32>(round(offset)), vec2<i32>(0), max_coords);
+            let sample_linear = load_working(sample_coords);
+            let bright = smoothstep(threshold, threshold + 0.2, get_luma(sample_linear));
+            let weight = exp(-dot(offset, offset) / (2.0 * sigma * sigma));
+            glow += sample_linear * bright * weight;
+            total_weight += weight;
+        }
+    }
+    glow = clamp(glow / max(total_weight, 0.0001) * amount, vec3<f32>(0.0), vec3<f32>(1.0));
+    let base = clamp(color, vec3<f32>(0.0), vec3<f32>(1.0));
+    let screened = 1.0 - (1.0 - base) * (1.0 - glow);
+    return color + (screened - base);
+}
+
 fn apply_all_adjustments(initial_rgb: vec3<f32>, adj: GlobalAdjustments, coords_i: vec2<i32>) -> vec3<f32> {
     var processed_rgb = apply_noise_reduction(initial_rgb, coords_i, adj.luma_noise_reduction, adj.color_noise_reduction);
 
+    processed_rgb = apply_defringe(processed_rgb, coords_i, adj.defringe_purple_amount, adj.defringe_purple_hue_min, adj.defringe_purple_hue_max, adj.defringe_green_amount, adj.defringe_green_hue_min, adj.defringe_green_hue_max);
     processed_rgb = apply_white_balance(processed_rgb, adj.temperature, adj.tint);
     processed_rgb = processed_rgb * pow(2.0, adj.exposure);
     processed_rgb = apply_tonal_adjustments(processed_rgb, adj.contrast, adj.highlights, adj.shadows, adj.whites, adj.blacks);
 
     processed_rgb = apply_dehaze(processed_rgb, adj.dehaze);
     processed_rgb = apply_local_contrast(processed_rgb, coords_i, 2, adj.sharpness);
+    processed_rgb = apply_texture(processed_rgb, coords_i, adj.texture);
     processed_rgb = apply_local_contrast(processed_rgb, coords_i, 8, adj.clarity);
     processed_rgb = apply_local_contrast(processed_rgb, coords_i, 20, adj.structure);
 
     processed_rgb = apply_hsl_panel(processed_rgb, adj.hsl, coords_i);
     processed_rgb = apply_color_grading(processed_rgb, adj.color_grading_shadows, adj.color_grading_midtones, adj.color_grading_highlights, adj.color_grading_blending, adj.color_grading_balance);
     processed_rgb = apply_creative_color(processed_rgb, adj.saturation, adj.vibrance);
+    processed_rgb = apply_glow(processed_rgb, coords_i, adj.glow_amount, adj.glow_radius, adj.glow_threshold);
 
     return processed_rgb;
 }
@@ -649,42 +962,63 @@ fn apply_all_adjustments(initial_rgb: vec3<f32>, adj: GlobalAdjustments, coords_
 fn apply_all_mask_adjustments(initial_rgb: vec3<f32>, adj: MaskAdjustments, coords_i: vec2<i32>) -> vec3<f32> {
     var processed_rgb = apply_noise_reduction(initial_rgb, coords_i, adj.luma_noise_reduction, adj.color_noise_reduction);
 
+    processed_rgb = apply_defringe(processed_rgb, coords_i, adj.defringe_purple_amount, adj.defringe_purple_hue_min, adj.defringe_purple_hue_max, adj.defringe_green_amount, adj.defringe_green_hue_min, adj.defringe_green_hue_max);
     processed_rgb = apply_white_balance(processed_rgb, adj.temperature, adj.tint);
     processed_rgb = processed_rgb * pow(2.0, adj.exposure);
     processed_rgb = apply_tonal_adjustments(processed_rgb, adj.contrast, adj.highlights, adj.shadows, adj.whites, adj.blacks);
 
     processed_rgb = apply_dehaze(processed_rgb, adj.dehaze);
     processed_rgb = apply_local_contrast(processed_rgb, coords_i, 2, adj.sharpness);
+    processed_rgb = apply_texture(processed_rgb, coords_i, adj.texture);
     processed_rgb = apply_local_contrast(processed_rgb, coords_i, 8, adj.clarity);
     processed_rgb = apply_local_contrast(processed_rgb, coords_i, 20, adj.structure);
 
     processed_rgb = apply_hsl_panel(processed_rgb, adj.hsl, coords_i);
     processed_rgb = apply_color_grading(processed_rgb, adj.color_grading_shadows, adj.color_grading_midtones, adj.color_grading_highlights, adj.color_grading_blending, adj.color_grading_balance);
     processed_rgb = apply_creative_color(processed_rgb, adj.saturation, adj.vibrance);
+    processed_rgb = apply_glow(processed_rgb, coords_i, adj.glow_amount, adj.glow_radius, adj.glow_threshold);
     
     return processed_rgb;
 }
 
 fn get_mask_influence(mask_index: u32, coords: vec2<u32>) -> f32 {
-    switch (mask_index) {
-        case 0u: { return textureLoad(mask0, coords, 0).r; }
-        case 1u: { return textureLoad(mask1, coords, 0).r; }
-        case 2u: { return textureLoad(mask2, coords, 0).r; }
-        case 3u: { return textureLoad(mask3, coords, 0).r; }
-        case 4u: { return textureLoad(mask4, coords, 0).r; }
-        case 5u: { return textureLoad(mask5, coords, 0).r; }
-        case 6u: { return textureLoad(mask6, coords, 0).r; }
-        case 7u: { return textureLoad(mask7, coords, 0).r; }
-        case 8u: { return textureLoad(mask8, coords, 0).r; }
-        case 9u: { return textureLoad(mask9, coords, 0).r; }
-        case 10u: { return textureLoad(mask10, coords, 0).r; }
-        case 11u: { return textureLoad(mask11, coords, 0).r; }
-        case 12u: { return textureLoad(mask12, coords, 0).r; }
-        case 13u: { return textureLoad(mask13, coords, 0).r; }
-        case 14u: { return textureLoad(mask14, coords, 0).r; }
-        case 15u: { return textureLoad(mask15, coords, 0).r; }
-        default: { return 0.0; }
-    }
+    return textureLoad(mask_textures, coords, mask_index, 0).r;
+}
+
+// Experimental effects slot, replaced by the user's snippet when one is set (see custom_effects.rs).
+// The color is display-referred sRGB and uv spans the full image from 0 to 1.
+// CUSTOM_EFFECT_BEGIN
+fn custom_effect(color: vec3<f32>, uv: vec2<f32>) -> vec3<f32> {
+    return color;
+}
+// CUSTOM_EFFECT_END
+
+fn apply_lut(color: vec3<f32>, size: u32, intensity: f32) -> vec3<f32> {
+    let max_index = i32(size) - 1;
+    let pos = clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)) * f32(max_index);
+    let base = floor(pos);
+    let f = pos - base;
+    let p0 = vec3<i32>(base);
+    let p1 = min(p0 + vec3<i32>(1), vec3<i32>(max_index));
+
+    let c000 = textureLoad(lut_texture, vec3<i32>(p0.x, p0.y, p0.z), 0).rgb;
+    let c100 = textureLoad(lut_texture, vec3<i32>(p1.x, p0.y, p0.z), 0).rgb;
+    let c010 = textureLoad(lut_texture, vec3<i32>(p0.x, p1.y, p0.z), 0).rgb;
+    let c110 = textureLoad(lut_texture, vec3<i32>(p1.x, p1.y, p0.z), 0).rgb;
+    let c001 = textureLoad(lut_texture, vec3<i32>(p0.x, p0.y, p1.z), 0).rgb;
+    let c101 = textureLoad(lut_texture, vec3<i32>(p1.x, p0.y, p1.z), 0).rgb;
+    let c011 = textureLoad(lut_texture, vec3<i32>(p0.x, p1.y, p1.z), 0).rgb;
+    let c111 = textureLoad(lut_texture, vec3<i32>(p1.x, p1.y, p1.z), 0).rgb;
+
+    let c00 = mix(c000, c100, f.x);
+    let c10 = mix(c010, c110, f.x);
+    let c01 = mix(c001, c101, f.x);
+    let c11 = mix(c011, c111, f.x);
+    let c0 = mix(c00, c10, f.y);
+    let c1 = mix(c01, c11, f.y);
+    let lut_color = mix(c0, c1, f.z);
+
+    return mix(color, lut_color, clamp(intensity, 0.0, 1.0));
 }
 
 @compute @workgroup_size(8, 8, 1)
@@ -694,22 +1028,27 @@ fn main(@builtin(global_invocation_id) id: vec3<u32>) {
 
     let absolute_coord = id.xy + vec2<u32>(adjustments.tile_offset_x, adjustments.tile_offset_y);
     let absolute_coord_i = vec2<i32>(absolute_coord);
+    let local_coord = absolute_coord - vec2<u32>(adjustments.input_offset_x, adjustments.input_offset_y);
+    let local_coord_i = vec2<i32>(local_coord);
 
-    let original_color = textureLoad(input_texture, absolute_coord, 0);
-    var initial_linear_rgb = srgb_to_linear(original_color.rgb);
+    let original_color = textureLoad(input_texture, local_coord, 0);
+    var initial_linear_rgb = to_working(srgb_to_linear(original_color.rgb));
+    initial_linear_rgb = apply_chromatic_aberration_correction(initial_linear_rgb, absolute_coord_i, adjustments.global.chromatic_aberration);
 
     if (adjustments.global.enable_negative_conversion == 1u) {
         initial_linear_rgb = vec3<f32>(1.0) - initial_linear_rgb;
-        let film_base_color = vec3<f32>(adjustments.global.film_base_r, adjustments.global.film_base_g, adjustments.global.film_base_b);
+        let film_base_color = to_working(vec3<f32>(adjustments.global.film_base_r, adjustments.global.film_base_g, adjustments.global.film_base_b));
         initial_linear_rgb -= film_base_color;
         let balance_mult = vec3<f32>(1.0 + adjustments.global.negative_red_balance, 1.0 + adjustments.global.negative_green_balance, 1.0 + adjustments.global.negative_blue_balance);
         initial_linear_rgb *= balance_mult;
         initial_linear_rgb = max(initial_linear_rgb, vec3<f32>(0.0));
     }
 
-    var processed_rgb_linear = apply_all_adjustments(initial_linear_rgb, adjustments.global, absolute_coord_i);
+    var processed_rgb_linear = apply_all_adjustments(initial_linear_rgb, adjustments.global, local_coord_i);
+    let display_linear = from_working(processed_rgb_linear);
+    var out_of_range = display_linear - clamp(display_linear, vec3<f32>(0.0), vec3<f32>(1.0));
 
-    let base_srgb = linear_to_srgb(aces_fitted(processed_rgb_linear));
+    let base_srgb = linear_to_srgb(aces_fitted(display_linear));
     
     var final_rgb = apply_all_curves(base_srgb,
         adjustments.global.luma_curve, adjustments.global.luma_curve_count,
@@ -719,27 +1058,37 @@ fn main(@builtin(global_invocation_id) id: vec3<u32>) {
     );
 
     for (var i = 0u; i < adjustments.mask_count; i = i + 1u) {
-        let influence = get_mask_influence(i, absolute_coord);
+        let influence = get_mask_influence(i, local_coord);
         if (influence > 0.001) {
-            let mask_adjusted_linear = apply_all_mask_adjustments(processed_rgb_linear, adjustments.mask_adjustments[i], absolute_coord_i);
+            let mask_adjusted_linear = from_working(apply_all_mask_adjustments(processed_rgb_linear, mask_adjustments[i], local_coord_i));
             let mask_base_srgb = linear_to_srgb(aces_fitted(mask_adjusted_linear));
             let mask_final_srgb = apply_all_curves(mask_base_srgb,
-                adjustments.mask_adjustments[i].luma_curve, adjustments.mask_adjustments[i].luma_curve_count,
-                adjustments.mask_adjustments[i].red_curve, adjustments.mask_adjustments[i].red_curve_count,
-                adjustments.mask_adjustments[i].green_curve, adjustments.mask_adjustments[i].green_curve_count,
-                adjustments.mask_adjustments[i].blue_curve, adjustments.mask_adjustments[i].blue_curve_count
+                mask_adjustments[i].luma_curve, mask_adjustments[i].luma_curve_count,
+                mask_adjustments[i].red_curve, mask_adjustments[i].red_curve_count,
+                mask_adjustments[i].green_curve, mask_adjustments[i].green_curve_count,
+                mask_adjustments[i].blue_curve, mask_adjustments[i].blue_curve_count
             );
             final_rgb = mix(final_rgb, mask_final_srgb, influence);
+            out_of_range = mix(out_of_range, mask_adjusted_linear - clamp(mask_adjusted_linear, vec3<f32>(0.0), vec3<f32>(1.0)), influence);
         }
     }
 
+    if (adjustments.global.lut_size > 1u && adjustments.global.lut_intensity > 0.0) {
+        final_rgb = apply_lut(final_rgb, adjustments.global.lut_size, adjustments.global.lut_intensity);
+    }
+
+    if (adjustments.global.custom_effect_amount > 0.0) {
+        let uv = vec2<f32>(absolute_coord) / full_image_dims();
+        final_rgb = mix(final_rgb, custom_effect(final_rgb, uv), adjustments.global.custom_effect_amount);
+    }
+
     if (adjustments.global.grain_amount > 0.0) {
         let g = adjustments.global;
         let coord = vec2<f32>(absolute_coord_i);
         let amount = g.grain_amount * 0.5;
         let scale = 1.0 / max(g.grain_size, 0.1);
         let roughness = g.grain_roughness;
-        let luma = max(0.0, get_luma(final_rgb));
+        let luma = max(0.0, get_display_luma(final_rgb));
         let luma_mask = smoothstep(0.0, 0.15, luma) * (1.0 - smoothstep(0.6, 1.0, luma));
         let base_coord = coord * scale;
         let rough_coord = coord * scale * 0.6;
@@ -751,7 +1100,7 @@ fn main(@builtin(global_invocation_id) id: vec3<u32>) {
 
     let g = adjustments.global;
     if (g.vignette_amount != 0.0) {
-        let full_dims_f = vec2<f32>(textureDimensions(input_texture));
+        let full_dims_f = full_image_dims();
         let coord_f = vec2<f32>(absolute_coord);
         let v_amount = g.vignette_amount;
         let v_mid = g.vignette_midpoint;
@@ -765,5 +1114,16 @@ fn main(@builtin(global_invocation_id) id: vec3<u32>) {
         if (v_amount < 0.0) { final_rgb *= (1.0 + v_amount * vignette_mask); } else { final_rgb = mix(final_rgb, vec3<f32>(1.0), v_amount * vignette_mask); }
     }
 
+    if (adjustments.hdr_output == 3u) {
+        textureStore(output_texture, id.xy, vec4<f32>(encode_extended_srgb(final_rgb, out_of_range), original_color.a));
+        return;
+    }
+
+    if (adjustments.hdr_output != 0u) {
+        let headroom = max(out_of_range, vec3<f32>(0.0));
+        textureStore(output_texture, id.xy, vec4<f32>(encode_hdr(final_rgb, headroom, adjustments.hdr_output), original_color.a));
+        return;
+    }
+
     textureStore(output_texture, id.xy, vec4<f32>(clamp(final_rgb, vec3<f32>(0.0), vec3<f32>(1.0)), original_color.a));
 }
\ No newline at end of file
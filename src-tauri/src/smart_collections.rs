@@ -0,0 +1,299 @@
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, ToSql};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::catalog::{escape_like, with_catalog};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SmartCollection {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub root_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Eq,
+    NotEq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Contains,
+}
+
+#[derive(Debug, Clone)]
+enum RuleValue {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    field: String,
+    operator: Operator,
+    value: RuleValue,
+}
+
+const OPERATORS: [(&str, Operator); 8] = [
+    (">=", Operator::Gte),
+    ("<=", Operator::Lte),
+    ("!=", Operator::NotEq),
+    ("==", Operator::Eq),
+    (">", Operator::Gt),
+    ("<", Operator::Lt),
+    ("=", Operator::Eq),
+    (":", Operator::Contains),
+];
+
+fn ensure_table(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS smart_collections (
+             id TEXT PRIMARY KEY,
+             name TEXT NOT NULL,
+             query TEXT NOT NULL,
+             root_path TEXT
+         );",
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn split_keyword<'a>(input: &'a str, keyword: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut rest = input;
+    let needle = format!(" {} ", keyword);
+    loop {
+        match rest.to_ascii_uppercase().find(&needle) {
+            Some(idx) => {
+                parts.push(&rest[..idx]);
+                rest = &rest[idx + needle.len()..];
+            }
+            None => {
+                parts.push(rest);
+                return parts;
+            }
+        }
+    }
+}
+
+fn parse_rule(input: &str) -> Result<Rule, String> {
+    let input = input.trim();
+    let (idx, op_str, operator) = OPERATORS
+        .iter()
+        .filter_map(|(op_str, op)| input.find(op_str).map(|idx| (idx, *op_str, *op)))
+        .min_by_key(|(idx, op_str, _)| (*idx, usize::MAX - op_str.len()))
+        .ok_or_else(|| format!("Invalid rule '{}': expected a comparison like 'rating >= 4'", input))?;
+
+    let field = input[..idx].trim().to_lowercase();
+    let raw_value = input[idx + op_str.len()..].trim().trim_matches('"').trim_matches('\'');
+    if field.is_empty() || raw_value.is_empty() {
+        return Err(format!("Invalid rule '{}'", input));
+    }
+
+    let value = match raw_value.parse::<f64>() {
+        Ok(n) => RuleValue::Number(n),
+        Err(_) => RuleValue::Text(raw_value.to_string()),
+    };
+    Ok(Rule { field, operator, value })
+}
+
+fn parse_query(query: &str) -> Result<Vec<Vec<Rule>>, String> {
+    let groups = split_keyword(query.trim(), "OR")
+        .into_iter()
+        .map(|group| {
+            split_keyword(group, "AND")
+                .into_iter()
+                .filter(|rule| !rule.trim().is_empty())
+                .map(parse_rule)
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if groups.iter().all(|g| g.is_empty()) {
+        return Err("Smart collection query is empty.".to_string());
+    }
+    Ok(groups)
+}
+
+fn column_for_field(field: &str) -> Option<&'static str> {
+    match field {
+        "rating" | "stars" => Some("rating"),
+        "iso" => Some("iso"),
+        "aperture" | "f" | "fnumber" => Some("aperture"),
+        "focal" | "focallength" | "focal_length" => Some("focal_length"),
+        "camera" | "model" => Some("camera_model"),
+        "make" => Some("camera_make"),
+        "lens" => Some("lens_model"),
+        "date" | "captured" => Some("capture_date"),
+        "edited" => Some("is_edited"),
+        "color" | "label" => Some("color_label"),
+        "flag" => Some("flag"),
+        "name" | "filename" => Some("file_name"),
+        "folder" => Some("folder"),
+        _ => None,
+    }
+}
+
+fn rule_to_sql(rule: &Rule, values: &mut Vec<Box<dyn ToSql>>) -> Result<String, String> {
+    if rule.field == "tag" || rule.field == "tags" {
+        let RuleValue::Text(tag) = &rule.value else {
+            return Err("Tag rules need a text value, e.g. 'tag:portrait'".to_string());
+        };
+        let exists = "EXISTS (SELECT 1 FROM image_tags t WHERE t.path = images.path AND t.tag = ? COLLATE NOCASE)";
+        values.push(Box::new(tag.clone()));
+        return match rule.operator {
+            Operator::Eq | Operator::Contains => Ok(exists.to_string()),
+            Operator::NotEq => Ok(format!("NOT {}", exists)),
+            _ => Err("Tag rules only support ':', '=' and '!='".to_string()),
+        };
+    }
+
+    let column = column_for_field(&rule.field).ok_or_else(|| format!("Unknown field '{}'", rule.field))?;
+    let sql_op = match rule.operator {
+        Operator::Eq => "=",
+        Operator::NotEq => "!=",
+        Operator::Gt => ">",
+        Operator::Gte => ">=",
+        Operator::Lt => "<",
+        Operator::Lte => "<=",
+        Operator::Contains => "LIKE",
+    };
+
+    match (&rule.value, rule.operator) {
+        (RuleValue::Text(text), Operator::Contains) => {
+            values.push(Box::new(format!("%{}%", escape_like(text))));
+            Ok(format!("{} LIKE ? ESCAPE '\\'", column))
+        }
+        (RuleValue::Number(n), Operator::Contains) => {
+            values.push(Box::new(*n));
+            Ok(format!("{} = ?", column))
+        }
+        (RuleValue::Text(text), _) if column == "is_edited" => {
+            let edited = matches!(text.to_lowercase().as_str(), "true" | "yes");
+            values.push(Box::new(edited));
+            Ok(format!("{} {} ?", column, sql_op))
+        }
+        (RuleValue::Text(text), _) => {
+            values.push(Box::new(text.clone()));
+            Ok(format!("{} {} ? COLLATE NOCASE", column, sql_op))
+        }
+        (RuleValue::Number(n), _) => {
+            values.push(Box::new(*n));
+            Ok(format!("{} {} ?", column, sql_op))
+        }
+    }
+}
+
+fn evaluate_query(conn: &Connection, query: &str, root_path: Option<&str>) -> Result<Vec<String>, String> {
+    let groups = parse_query(query)?;
+    let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+    let mut group_sql = Vec::new();
+    for group in groups.iter().filter(|g| !g.is_empty()) {
+        let rules = group
+            .iter()
+            .map(|rule| rule_to_sql(rule, &mut values))
+            .collect::<Result<Vec<_>, _>>()?;
+        group_sql.push(format!("({})", rules.join(" AND ")));
+    }
+
+    let mut sql = format!("SELECT path FROM images WHERE ({})", group_sql.join(" OR "));
+    if let Some(root) = root_path {
+        let prefix = format!("{}{}", root.trim_end_matches(std::path::MAIN_SEPARATOR), std::path::MAIN_SEPARATOR);
+        sql.push_str(" AND (folder = ? OR folder LIKE ? ESCAPE '\\')");
+        values.push(Box::new(root.to_string()));
+        values.push(Box::new(format!("{}%", escape_like(&prefix))));
+    }
+    sql.push_str(" ORDER BY capture_date, path");
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_smart_collections(app_handle: AppHandle) -> Result<Vec<SmartCollection>, String> {
+    with_catalog(&app_handle, |conn| {
+        ensure_table(conn)?;
+        let mut stmt = conn
+            .prepare("SELECT id, name, query, root_path FROM smart_collections ORDER BY name COLLATE NOCASE")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(SmartCollection {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    query: row.get(2)?,
+                    root_path: row.get(3)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn save_smart_collection(collection: SmartCollection, app_handle: AppHandle) -> Result<SmartCollection, String> {
+    parse_query(&collection.query)?;
+    let mut collection = collection;
+    if collection.id.is_empty() {
+        collection.id = Uuid::new_v4().to_string();
+    }
+
+    with_catalog(&app_handle, |conn| {
+        ensure_table(conn)?;
+        conn.execute(
+            "INSERT INTO smart_collections (id, name, query, root_path) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(id) DO UPDATE SET name = excluded.name, query = excluded.query, root_path = excluded.root_path",
+            params![collection.id, collection.name, collection.query, collection.root_path],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(collection)
+    })
+}
+
+#[tauri::command]
+pub fn delete_smart_collection(id: String, app_handle: AppHandle) -> Result<(), String> {
+    with_catalog(&app_handle, |conn| {
+        ensure_table(conn)?;
+        conn.execute("DELETE FROM smart_collections WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn evaluate_smart_collection(
+    id: Option<String>,
+    query: Option<String>,
+    root_path: Option<String>,
+    app_handle: AppHandle,
+) -> Result<Vec<String>, String> {
+    with_catalog(&app_handle, |conn| {
+        ensure_table(conn)?;
+        let (query, root_path) = match (id, query) {
+            (_, Some(query)) => (query, root_path),
+            (Some(id), None) => {
+                let stored: Option<(String, Option<String>)> = conn
+                    .query_row(
+                        "SELECT query, root_path FROM smart_collections WHERE id = ?1",
+                        params![id],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .optional()
+                    .map_err(|e| e.to_string())?;
+                let (stored_query, stored_root) = stored.ok_or("Smart collection not found.")?;
+                (stored_query, root_path.or(stored_root))
+            }
+            (None, None) => return Err("Either a collection id or a query is required.".to_string()),
+        };
+        evaluate_query(conn, &query, root_path.as_deref())
+    })
+}
@@ -0,0 +1,219 @@
+use image::{imageops, DynamicImage, GrayImage, Luma, Rgba32FImage};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::inpainting::perform_fast_inpaint_f32;
+
+const HEAL_CONTEXT_FACTOR: f32 = 3.0;
+const HEAL_PATCH_RADIUS: u32 = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SpotEditKind {
+    Heal,
+    Clone,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct SpotPoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SpotEdit {
+    #[serde(rename = "type")]
+    pub kind: SpotEditKind,
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+    pub points: Vec<SpotPoint>,
+    pub brush_size: f32,
+    #[serde(default = "default_feather")]
+    pub feather: f32,
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    pub source_offset: Option<SpotPoint>,
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+fn default_feather() -> f32 {
+    0.5
+}
+
+fn default_opacity() -> f32 {
+    100.0
+}
+
+struct Region {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+fn stroke_region(edit: &SpotEdit, padding: f32, image_width: u32, image_height: u32) -> Option<Region> {
+    let reach = edit.brush_size / 2.0 + padding;
+    let min_x = edit.points.iter().map(|p| p.x).fold(f32::MAX, f32::min) - reach;
+    let min_y = edit.points.iter().map(|p| p.y).fold(f32::MAX, f32::min) - reach;
+    let max_x = edit.points.iter().map(|p| p.x).fold(f32::MIN, f32::max) + reach;
+    let max_y = edit.points.iter().map(|p| p.y).fold(f32::MIN, f32::max) + reach;
+
+    let x0 = min_x.floor().max(0.0) as u32;
+    let y0 = min_y.floor().max(0.0) as u32;
+    let x1 = (max_x.ceil().max(0.0) as u32).min(image_width);
+    let y1 = (max_y.ceil().max(0.0) as u32).min(image_height);
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+    Some(Region { x: x0, y: y0, width: x1 - x0, height: y1 - y0 })
+}
+
+fn distance_to_segment(px: f32, py: f32, a: SpotPoint, b: SpotPoint) -> f32 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq > 0.0 { (((px - a.x) * dx + (py - a.y) * dy) / len_sq).clamp(0.0, 1.0) } else { 0.0 };
+    let (cx, cy) = (a.x + t * dx, a.y + t * dy);
+    ((px - cx).powi(2) + (py - cy).powi(2)).sqrt()
+}
+
+fn stroke_alpha(edit: &SpotEdit, region: &Region) -> Vec<f32> {
+    let radius = (edit.brush_size / 2.0).max(0.5);
+    let inner_radius = radius * (1.0 - edit.feather.clamp(0.0, 1.0));
+    let segments: Vec<(SpotPoint, SpotPoint)> = if edit.points.len() > 1 {
+        edit.points.windows(2).map(|w| (w[0], w[1])).collect()
+    } else {
+        vec![(edit.points[0], edit.points[0])]
+    };
+
+    let mut alpha = vec![0.0f32; (region.width * region.height) as usize];
+    alpha
+        .par_chunks_mut(region.width as usize)
+        .enumerate()
+        .for_each(|(row, line)| {
+            let py = (region.y + row as u32) as f32 + 0.5;
+            for (col, value) in line.iter_mut().enumerate() {
+                let px = (region.x + col as u32) as f32 + 0.5;
+                let dist = segments
+                    .iter()
+                    .map(|&(a, b)| distance_to_segment(px, py, a, b))
+                    .fold(f32::MAX, f32::min);
+                *value = if dist <= inner_radius {
+                    1.0
+                } else if dist < radius {
+                    1.0 - (dist - inner_radius) / (radius - inner_radius).max(0.01)
+                } else {
+                    0.0
+                };
+            }
+        });
+    alpha
+}
+
+fn blend_into(image: &mut Rgba32FImage, region: &Region, alpha: &[f32], opacity: f32, source: impl Fn(u32, u32) -> [f32; 3] + Sync) {
+    let row_len = image.width() as usize * 4;
+    image
+        .par_chunks_mut(row_len)
+        .enumerate()
+        .skip(region.y as usize)
+        .take(region.height as usize)
+        .for_each(|(y, line)| {
+            let row = y as u32 - region.y;
+            for col in 0..region.width {
+                let a = alpha[(row * region.width + col) as usize] * opacity;
+                if a <= 0.0 {
+                    continue;
+                }
+                let x = region.x + col;
+                let color = source(x, y as u32);
+                let pixel = &mut line[x as usize * 4..x as usize * 4 + 3];
+                for c in 0..3 {
+                    pixel[c] = pixel[c] * (1.0 - a) + color[c] * a;
+                }
+            }
+        });
+}
+
+fn apply_clone(image: &mut Rgba32FImage, edit: &SpotEdit, region: &Region, alpha: &[f32], opacity: f32) {
+    let Some(offset) = edit.source_offset else {
+        return;
+    };
+    let (max_x, max_y) = (image.width() as f32 - 1.0, image.height() as f32 - 1.0);
+    let source_x = |x: f32| (x + offset.x).round().clamp(0.0, max_x) as u32;
+    let source_y = |y: f32| (y + offset.y).round().clamp(0.0, max_y) as u32;
+
+    // Only the source area under the stroke is copied, not the whole image.
+    let (sx0, sy0) = (source_x(region.x as f32), source_y(region.y as f32));
+    let sx1 = source_x((region.x + region.width - 1) as f32);
+    let sy1 = source_y((region.y + region.height - 1) as f32);
+    let source = imageops::crop_imm(image, sx0, sy0, sx1 - sx0 + 1, sy1 - sy0 + 1).to_image();
+
+    blend_into(image, region, alpha, opacity, |x, y| {
+        let p = source.get_pixel(source_x(x as f32) - sx0, source_y(y as f32) - sy0);
+        [p[0], p[1], p[2]]
+    });
+}
+
+fn apply_heal(image: &mut Rgba32FImage, edit: &SpotEdit, opacity: f32) -> Result<(), String> {
+    let (width, height) = image.dimensions();
+    let padding = edit.brush_size * HEAL_CONTEXT_FACTOR;
+    let Some(context) = stroke_region(edit, padding, width, height) else {
+        return Ok(());
+    };
+
+    let alpha = stroke_alpha(edit, &context);
+    let mut hole = GrayImage::new(context.width, context.height);
+    for (i, a) in alpha.iter().enumerate() {
+        if *a > 0.0 {
+            hole.put_pixel(i as u32 % context.width, i as u32 / context.width, Luma([255]));
+        }
+    }
+
+    let crop = imageops::crop_imm(image, context.x, context.y, context.width, context.height).to_image();
+    let inpainted = perform_fast_inpaint_f32(&crop, &hole, HEAL_PATCH_RADIUS)?;
+
+    blend_into(image, &context, &alpha, opacity, |x, y| {
+        let p = inpainted.get_pixel(x - context.x, y - context.y);
+        [p[0], p[1], p[2]]
+    });
+    Ok(())
+}
+
+pub fn apply_spot_edits(base_image: &DynamicImage, adjustments: &Value) -> Result<Option<DynamicImage>, String> {
+    let edits: Vec<SpotEdit> = match adjustments.get("spotEdits") {
+        Some(val) if !val.is_null() => serde_json::from_value(val.clone()).map_err(|e| e.to_string())?,
+        _ => return Ok(None),
+    };
+    let visible_edits: Vec<&SpotEdit> = edits
+        .iter()
+        .filter(|e| e.visible && !e.points.is_empty() && e.brush_size > 0.0)
+        .collect();
+    if visible_edits.is_empty() {
+        return Ok(None);
+    }
+
+    let mut image = base_image.to_rgba32f();
+    let (width, height) = image.dimensions();
+
+    for edit in visible_edits {
+        let opacity = (edit.opacity / 100.0).clamp(0.0, 1.0);
+        if opacity <= 0.0 {
+            continue;
+        }
+        match edit.kind {
+            SpotEditKind::Heal => apply_heal(&mut image, edit, opacity)?,
+            SpotEditKind::Clone => {
+                if let Some(region) = stroke_region(edit, 0.0, width, height) {
+                    let alpha = stroke_alpha(edit, &region);
+                    apply_clone(&mut image, edit, &region, &alpha, opacity);
+                }
+            }
+        }
+    }
+
+    Ok(Some(DynamicImage::ImageRgba32F(image)))
+}
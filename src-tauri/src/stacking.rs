@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::catalog::with_catalog;
+use crate::formats::is_raw_file;
+
+const BURST_INTERVAL_SECS: i64 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StackInfo {
+    pub id: String,
+    pub count: usize,
+    pub top_path: String,
+    pub collapsed: bool,
+}
+
+struct StackMember {
+    path: String,
+    capture_date: Option<NaiveDateTime>,
+}
+
+fn ensure_table(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS stacks (
+             id TEXT PRIMARY KEY,
+             top_path TEXT,
+             collapsed INTEGER NOT NULL DEFAULT 1
+         );",
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn file_stem_key(path: &str) -> String {
+    let path = Path::new(path);
+    let parent = path.parent().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default();
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_lowercase()).unwrap_or_default();
+    format!("{}/{}", parent, stem)
+}
+
+fn group_members(members: Vec<StackMember>) -> Vec<Vec<StackMember>> {
+    let mut pairs: BTreeMap<String, Vec<StackMember>> = BTreeMap::new();
+    for member in members {
+        pairs.entry(file_stem_key(&member.path)).or_default().push(member);
+    }
+
+    let mut pairs: Vec<Vec<StackMember>> = pairs.into_values().collect();
+    let pair_date = |group: &Vec<StackMember>| group.iter().filter_map(|m| m.capture_date).min();
+    pairs.sort_by(|a, b| pair_date(a).cmp(&pair_date(b)).then_with(|| a[0].path.cmp(&b[0].path)));
+
+    let mut groups: Vec<Vec<StackMember>> = Vec::new();
+    let mut last_date: Option<NaiveDateTime> = None;
+    for pair in pairs {
+        let date = pair_date(&pair);
+        let is_burst = match (last_date, date) {
+            (Some(last), Some(current)) => (current - last).num_seconds() <= BURST_INTERVAL_SECS,
+            _ => false,
+        };
+        last_date = date;
+        match groups.last_mut() {
+            Some(group) if is_burst => group.extend(pair),
+            _ => groups.push(pair),
+        }
+    }
+    groups.into_iter().filter(|g| g.len() > 1).collect()
+}
+
+fn default_top(group: &[StackMember]) -> String {
+    group
+        .iter()
+        .find(|m| is_raw_file(&m.path))
+        .unwrap_or(&group[0])
+        .path
+        .clone()
+}
+
+pub fn stacks_for_folder(conn: &Connection, folder: &str) -> Result<HashMap<String, StackInfo>, String> {
+    ensure_table(conn)?;
+
+    let members = {
+        let mut stmt = conn
+            .prepare_cached("SELECT path, capture_date FROM images WHERE folder = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![folder], |row| {
+                let capture_date: Option<String> = row.get(1)?;
+                Ok(StackMember {
+                    path: row.get(0)?,
+                    capture_date: capture_date
+                        .and_then(|d| NaiveDateTime::parse_from_str(&d, "%Y-%m-%d %H:%M:%S").ok()),
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())?
+    };
+
+    let mut settings_stmt = conn
+        .prepare_cached("SELECT top_path, collapsed FROM stacks WHERE id = ?1")
+        .map_err(|e| e.to_string())?;
+
+    let mut stacks = HashMap::new();
+    for group in group_members(members) {
+        let id = group.iter().map(|m| m.path.as_str()).min().unwrap_or_default().to_string();
+        let stored: Option<(Option<String>, bool)> = settings_stmt
+            .query_row(params![id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .ok();
+        let (stored_top, collapsed) = stored.unwrap_or((None, true));
+        let top_path = stored_top
+            .filter(|top| group.iter().any(|m| &m.path == top))
+            .unwrap_or_else(|| default_top(&group));
+
+        let info = StackInfo { id, count: group.len(), top_path, collapsed };
+        for member in group {
+            stacks.insert(member.path, info.clone());
+        }
+    }
+    Ok(stacks)
+}
+
+#[tauri::command]
+pub fn set_stack_collapsed(stack_id: String, collapsed: bool, app_handle: AppHandle) -> Result<(), String> {
+    with_catalog(&app_handle, |conn| {
+        ensure_table(conn)?;
+        conn.execute(
+            "INSERT INTO stacks (id, collapsed) VALUES (?1, ?2)
+             ON CONFLICT(id) DO UPDATE SET collapsed = excluded.collapsed",
+            params![stack_id, collapsed],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn set_stack_top(stack_id: String, path: String, app_handle: AppHandle) -> Result<(), String> {
+    with_catalog(&app_handle, |conn| {
+        ensure_table(conn)?;
+        conn.execute(
+            "INSERT INTO stacks (id, top_path) VALUES (?1, ?2)
+             ON CONFLICT(id) DO UPDATE SET top_path = excluded.top_path",
+            params![stack_id, path],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    })
+}
@@ -1,11 +1,15 @@
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use anyhow::Result;
+use serde::Serialize;
 use futures::stream::{self, StreamExt};
 use image::{DynamicImage, imageops::FilterType};
 use ndarray::{Array, Axis};
 use ort::{Session, Value};
+use rayon::prelude::*;
+use rusqlite::{params, Connection, OptionalExtension};
 use serde_json;
 use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::task::JoinHandle;
@@ -13,14 +17,50 @@ use tokenizers::Tokenizer;
 use walkdir::WalkDir;
 use std::sync::{Arc, Mutex};
 
-use crate::formats::is_supported_image_file;
+use crate::ai_processing::{
+    compute_face_embedding, get_or_init_face_detector, get_or_init_face_embedding_model, run_face_detector, FaceDetection,
+};
+use crate::catalog::{
+    blob_to_embedding, embedding_to_blob, has_current_embedding, load_embeddings, store_embedding, sync_folder,
+    with_catalog,
+};
+use crate::formats::{is_supported_image_file, is_video_file};
 use crate::image_processing::ImageMetadata;
 use crate::file_management::{self, get_sidecar_path};
+use crate::xmp_sidecar;
 use crate::AppState;
 use crate::candidates::TAG_CANDIDATES;
 use crate::hierarchy::TAG_HIERARCHY;
 
 pub const COLOR_TAG_PREFIX: &str = "color:";
+pub const PERSON_TAG_PREFIX: &str = "person:";
+const FACE_CLUSTER_SIMILARITY: f32 = 0.45;
+const CLIP_TEXT_EMBEDS_OUTPUT: usize = 2;
+const CLIP_IMAGE_EMBEDS_OUTPUT: usize = 3;
+const TEXT_SEARCH_MIN_SIMILARITY: f32 = 0.2;
+const TEXT_SEARCH_DEFAULT_LIMIT: usize = 200;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TextSearchMatch {
+    pub path: String,
+    pub score: f32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FaceCluster {
+    pub id: i64,
+    pub name: Option<String>,
+    pub face_count: usize,
+    pub sample_path: String,
+    pub sample_face: [f32; 4],
+}
+
+struct ScannedFaces {
+    path: String,
+    faces: Vec<(FaceDetection, Vec<f32>)>,
+}
 
 fn preprocess_clip_image(image: &DynamicImage) -> Array<f32, ndarray::Dim<[usize; 4]>> {
     let input_size = 224;
@@ -39,6 +79,63 @@ fn preprocess_clip_image(image: &DynamicImage) -> Array<f32, ndarray::Dim<[usize
     array
 }
 
+fn encode_clip_texts(
+    texts: Vec<&str>,
+    tokenizer: &Tokenizer,
+) -> Result<(Array<i64, ndarray::Dim<[usize; 2]>>, Array<i64, ndarray::Dim<[usize; 2]>>)> {
+    let count = texts.len();
+    let encodings = tokenizer.encode_batch(texts, true)
+        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+
+    let max_len = encodings.iter().map(|e| e.get_ids().len()).max().unwrap_or(0);
+
+    let mut ids_data = Vec::new();
+    let mut mask_data = Vec::new();
+    for encoding in encodings {
+        let mut ids = encoding.get_ids().iter().map(|&i| i as i64).collect::<Vec<_>>();
+        let mut mask = encoding.get_attention_mask().iter().map(|&m| m as i64).collect::<Vec<_>>();
+        ids.resize(max_len, 0);
+        mask.resize(max_len, 0);
+        ids_data.extend_from_slice(&ids);
+        mask_data.extend_from_slice(&mask);
+    }
+
+    let ids_array = Array::from_shape_vec((count, max_len), ids_data)?;
+    let mask_array = Array::from_shape_vec((count, max_len), mask_data)?;
+    Ok((ids_array, mask_array))
+}
+
+fn normalized_embedding(output: &Value, row: usize) -> Result<Vec<f32>> {
+    let embeds = output.try_extract::<f32>()?.view().to_owned();
+    let embeds = embeds.into_dimensionality::<ndarray::Dim<[usize; 2]>>()?;
+    let embedding: Vec<f32> = embeds.row(row).to_vec();
+    let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm <= f32::EPSILON {
+        return Err(anyhow::anyhow!("CLIP returned an empty embedding"));
+    }
+    Ok(embedding.into_iter().map(|v| v / norm).collect())
+}
+
+pub fn encode_text_with_clip(query: &str, clip_session: &Session, tokenizer: &Tokenizer) -> Result<Vec<f32>> {
+    let (ids_array, mask_array) = encode_clip_texts(vec![query], tokenizer)?;
+    let image_input: Array<f32, ndarray::Dim<[usize; 4]>> = Array::zeros((1, 3, 224, 224));
+
+    let image_input_dyn = image_input.into_dyn();
+    let ids_array_dyn = ids_array.into_dyn();
+    let mask_array_dyn = mask_array.into_dyn();
+
+    let image_layout = image_input_dyn.as_standard_layout();
+    let ids_layout = ids_array_dyn.as_standard_layout();
+    let mask_layout = mask_array_dyn.as_standard_layout();
+
+    let image_val = Value::from_array(clip_session.allocator(), &image_layout)?;
+    let ids_val = Value::from_array(clip_session.allocator(), &ids_layout)?;
+    let mask_val = Value::from_array(clip_session.allocator(), &mask_layout)?;
+
+    let outputs = clip_session.run(vec![ids_val, image_val, mask_val])?;
+    normalized_embedding(&outputs[CLIP_TEXT_EMBEDS_OUTPUT], 0)
+}
+
 fn softmax(array: &Array<f32, ndarray::Dim<[usize; 2]>>) -> Array<f32, ndarray::Dim<[usize; 2]>> {
     let mut new_array = array.clone();
     for mut row in new_array.axis_iter_mut(Axis(0)) {
@@ -133,28 +230,9 @@ pub fn generate_tags_with_clip(
     image: &DynamicImage,
     clip_session: &Session,
     tokenizer: &Tokenizer,
-) -> Result<Vec<String>> {
+) -> Result<(Vec<String>, Vec<f32>)> {
     let image_input = preprocess_clip_image(image);
-
-    let text_inputs = TAG_CANDIDATES.to_vec();
-    let encodings = tokenizer.encode_batch(text_inputs.clone(), true)
-        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
-    
-    let max_len = encodings.iter().map(|e| e.get_ids().len()).max().unwrap_or(0);
-
-    let mut ids_data = Vec::new();
-    let mut mask_data = Vec::new();
-    for encoding in encodings {
-        let mut ids = encoding.get_ids().iter().map(|&i| i as i64).collect::<Vec<_>>();
-        let mut mask = encoding.get_attention_mask().iter().map(|&m| m as i64).collect::<Vec<_>>();
-        ids.resize(max_len, 0);
-        mask.resize(max_len, 0);
-        ids_data.extend_from_slice(&ids);
-        mask_data.extend_from_slice(&mask);
-    }
-
-    let ids_array = Array::from_shape_vec((text_inputs.len(), max_len), ids_data)?;
-    let mask_array = Array::from_shape_vec((text_inputs.len(), max_len), mask_data)?;
+    let (ids_array, mask_array) = encode_clip_texts(TAG_CANDIDATES.to_vec(), tokenizer)?;
 
     let image_input_dyn = image_input.into_dyn();
     let ids_array_dyn = ids_array.into_dyn();
@@ -170,6 +248,7 @@ pub fn generate_tags_with_clip(
 
     let inputs = vec![ids_val, image_val, mask_val];
     let outputs = clip_session.run(inputs)?;
+    let image_embedding = normalized_embedding(&outputs[CLIP_IMAGE_EMBEDS_OUTPUT], 0)?;
 
     let logits_dyn = outputs[0].try_extract::<f32>()?.view().to_owned();
     let logits = logits_dyn.into_dimensionality::<ndarray::Dim<[usize; 2]>>()?;
@@ -209,7 +288,7 @@ pub fn generate_tags_with_clip(
 
     let final_tags = final_tags_set.into_iter().collect();
 
-    Ok(final_tags)
+    Ok((final_tags, image_embedding))
 }
 
 #[tauri::command]
@@ -233,6 +312,16 @@ pub async fn start_background_indexing(folder_path: String, app_handle: AppHandl
     )
     .await
     .map_err(|e| e.to_string())?;
+    let face_models = match (
+        get_or_init_face_detector(&app_handle, &state.face_detector, &state.ai_init_lock).await,
+        get_or_init_face_embedding_model(&app_handle, &state.face_embedding_model, &state.ai_init_lock).await,
+    ) {
+        (Ok(detector), Ok(embedder)) => Some((detector, embedder)),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Face recognition unavailable, skipping people indexing: {}", e);
+            None
+        }
+    };
 
     let app_handle_clone = app_handle.clone();
 
@@ -284,7 +373,10 @@ pub async fn start_background_indexing(folder_path: String, app_handle: AppHandl
                         ImageMetadata::default()
                     };
 
-                    if metadata.tags.is_none() {
+                    let needs_embedding = !with_catalog(&app_handle_inner, |conn| has_current_embedding(conn, &path_str))
+                        .unwrap_or(false);
+
+                    if metadata.tags.is_none() || needs_embedding {
                         match file_management::get_cached_or_generate_thumbnail_image(
                             &path_str,
                             &app_handle_inner,
@@ -292,15 +384,21 @@ pub async fn start_background_indexing(folder_path: String, app_handle: AppHandl
                         ) {
                             Ok(image) => {
                                 if let (Some(clip_model), Some(clip_tokenizer)) = (&models_inner.clip_model, &models_inner.clip_tokenizer) {
-                                    if let Ok(tags) = generate_tags_with_clip(
+                                    if let Ok((tags, embedding)) = generate_tags_with_clip(
                                         &image,
                                         clip_model,
                                         clip_tokenizer,
                                     ) {
-                                        println!("Found tags for {}: {:?}", path_str, tags);
-                                        metadata.tags = Some(tags);
-                                        if let Ok(json_string) = serde_json::to_string_pretty(&metadata) {
-                                            let _ = fs::write(sidecar_path, json_string);
+                                        if let Err(e) = with_catalog(&app_handle_inner, |conn| store_embedding(conn, &path_str, &embedding)) {
+                                            eprintln!("Failed to store embedding for {}: {}", path_str, e);
+                                        }
+                                        if metadata.tags.is_none() {
+                                            println!("Found tags for {}: {:?}", path_str, tags);
+                                            metadata.tags = Some(tags);
+                                            if let Ok(json_string) = serde_json::to_string_pretty(&metadata) {
+                                                let _ = fs::write(sidecar_path, json_string);
+                                            }
+                                            xmp_sidecar::sync_if_enabled(&app_handle_inner, std::slice::from_ref(&path_str));
                                         }
                                     }
                                 }
@@ -321,6 +419,20 @@ pub async fn start_background_indexing(folder_path: String, app_handle: AppHandl
             })
             .await;
 
+        if let Some((face_detector, face_embedder)) = face_models {
+            let worker_handle = app_handle_clone.clone();
+            let folder = folder_path.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                index_faces_in_folder(&folder, &worker_handle, &face_detector, &face_embedder)
+            })
+            .await;
+            match result {
+                Ok(Ok(face_count)) => println!("Indexed {} new faces in {}", face_count, folder_path),
+                Ok(Err(e)) => eprintln!("Failed to index faces in {}: {}", folder_path, e),
+                Err(e) => eprintln!("Face indexing task failed for {}: {}", folder_path, e),
+            }
+        }
+
         println!("Background indexing finished for: {}", folder_path);
         let _ = app_handle_clone.emit("indexing-finished", ());
 
@@ -333,12 +445,422 @@ pub async fn start_background_indexing(folder_path: String, app_handle: AppHandl
 }
 
 #[tauri::command]
-pub fn clear_all_tags(root_path: String) -> Result<usize, String> {
+pub async fn search_by_text(
+    query: String,
+    folder: Option<String>,
+    limit: Option<usize>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<TextSearchMatch>, String> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let models = crate::ai_processing::get_or_init_ai_models(
+        &app_handle,
+        &state.ai_state,
+        &state.ai_init_lock,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    tokio::task::spawn_blocking(move || {
+        let (Some(clip_model), Some(clip_tokenizer)) = (&models.clip_model, &models.clip_tokenizer) else {
+            return Err("AI tagging must be enabled to search by text.".to_string());
+        };
+        let query_embedding = encode_text_with_clip(&query, clip_model, clip_tokenizer).map_err(|e| e.to_string())?;
+        let embeddings = with_catalog(&app_handle, |conn| load_embeddings(conn, folder.as_deref()))?;
+
+        let mut matches: Vec<TextSearchMatch> = embeddings
+            .into_iter()
+            .filter(|(_, embedding)| embedding.len() == query_embedding.len())
+            .map(|(path, embedding)| {
+                let score = embedding.iter().zip(&query_embedding).map(|(a, b)| a * b).sum();
+                TextSearchMatch { path, score }
+            })
+            .filter(|m| m.score >= TEXT_SEARCH_MIN_SIMILARITY)
+            .collect();
+
+        matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        matches.truncate(limit.unwrap_or(TEXT_SEARCH_DEFAULT_LIMIT));
+        Ok(matches)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn paths_needing_face_scan(conn: &Connection, folder: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare_cached(
+            "SELECT i.path FROM images i LEFT JOIN face_scans s ON s.path = i.path
+             WHERE i.folder = ?1 AND (s.modified IS NULL OR s.modified != i.modified)
+             ORDER BY i.path",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![folder], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+}
+
+fn store_face_scan(conn: &mut Connection, scan: &ScannedFaces) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM faces WHERE path = ?1", params![scan.path])
+        .map_err(|e| e.to_string())?;
+    for (face, embedding) in &scan.faces {
+        tx.execute(
+            "INSERT INTO faces (path, x1, y1, x2, y2, embedding) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![scan.path, face.x1, face.y1, face.x2, face.y2, embedding_to_blob(embedding)],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.execute(
+        "INSERT INTO face_scans (path, modified) SELECT path, modified FROM images WHERE path = ?1
+         ON CONFLICT(path) DO UPDATE SET modified = excluded.modified",
+        params![scan.path],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+fn cosine_to_centroid(centroid: &[f32], embedding: &[f32]) -> f32 {
+    let norm = centroid.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm <= f32::EPSILON || centroid.len() != embedding.len() {
+        return 0.0;
+    }
+    centroid.iter().zip(embedding).map(|(a, b)| a * b).sum::<f32>() / norm
+}
+
+// Named clusters keep their faces; every other face is regrouped greedily against the running centroids.
+fn cluster_faces(conn: &mut Connection) -> Result<HashSet<String>, String> {
+    let faces: Vec<(i64, String, Vec<f32>, Option<i64>, bool)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT f.id, f.path, f.embedding, c.id, c.name IS NOT NULL
+                 FROM faces f LEFT JOIN face_clusters c ON c.id = f.cluster_id ORDER BY f.id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                let bytes: Vec<u8> = row.get(2)?;
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    blob_to_embedding(&bytes),
+                    row.get(3)?,
+                    row.get::<_, Option<bool>>(4)?.unwrap_or(false),
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())?
+    };
+
+    let mut centroids: Vec<(i64, bool, Vec<f32>)> = Vec::new();
+    for (_, _, embedding, cluster_id, is_named) in &faces {
+        if let (Some(cluster_id), true) = (cluster_id, is_named) {
+            match centroids.iter_mut().find(|(id, _, _)| id == cluster_id) {
+                Some((_, _, sum)) => sum.iter_mut().zip(embedding).for_each(|(s, v)| *s += v),
+                None => centroids.push((*cluster_id, true, embedding.clone())),
+            }
+        }
+    }
+
+    let mut named_paths = HashSet::new();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM face_clusters WHERE name IS NULL", [])
+        .map_err(|e| e.to_string())?;
+    {
+        let mut assign = tx
+            .prepare_cached("UPDATE faces SET cluster_id = ?2 WHERE id = ?1")
+            .map_err(|e| e.to_string())?;
+        for (face_id, path, embedding, _, is_named) in &faces {
+            if *is_named {
+                continue;
+            }
+            let best = centroids
+                .iter_mut()
+                .map(|entry| (cosine_to_centroid(&entry.2, embedding), entry))
+                .filter(|(similarity, _)| *similarity >= FACE_CLUSTER_SIMILARITY)
+                .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(_, entry)| entry);
+
+            let cluster_id = match best {
+                Some((cluster_id, named, sum)) => {
+                    sum.iter_mut().zip(embedding).for_each(|(s, v)| *s += v);
+                    if *named {
+                        named_paths.insert(path.clone());
+                    }
+                    *cluster_id
+                }
+                None => {
+                    tx.execute("INSERT INTO face_clusters (name) VALUES (NULL)", [])
+                        .map_err(|e| e.to_string())?;
+                    let cluster_id = tx.last_insert_rowid();
+                    centroids.push((cluster_id, false, embedding.clone()));
+                    cluster_id
+                }
+            };
+            assign.execute(params![face_id, cluster_id]).map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(named_paths)
+}
+
+fn write_person_tags(app_handle: &AppHandle, paths: Vec<String>) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let names_by_path = with_catalog(app_handle, |conn| {
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT DISTINCT c.name FROM faces f JOIN face_clusters c ON c.id = f.cluster_id
+                 WHERE f.path = ?1 AND c.name IS NOT NULL ORDER BY c.name",
+            )
+            .map_err(|e| e.to_string())?;
+        paths
+            .iter()
+            .map(|path| {
+                let names = stmt
+                    .query_map(params![path], |row| row.get::<_, String>(0))
+                    .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+                    .map_err(|e| e.to_string())?;
+                Ok((path.clone(), names))
+            })
+            .collect::<Result<Vec<_>, String>>()
+    })?;
+
+    let mut folders = HashSet::new();
+    let mut updated_paths = Vec::new();
+    for (path, names) in names_by_path {
+        let sidecar_path = get_sidecar_path(&path);
+        let mut metadata: ImageMetadata = if sidecar_path.exists() {
+            fs::read_to_string(&sidecar_path)
+                .ok()
+                .and_then(|c| serde_json::from_str(&c).ok())
+                .unwrap_or_default()
+        } else {
+            ImageMetadata::default()
+        };
+
+        let mut tags = metadata.tags.clone().unwrap_or_default();
+        tags.retain(|tag| !tag.starts_with(PERSON_TAG_PREFIX));
+        tags.extend(names.iter().map(|name| format!("{}{}", PERSON_TAG_PREFIX, name)));
+        let tags = if tags.is_empty() { None } else { Some(tags) };
+        if tags == metadata.tags {
+            continue;
+        }
+
+        metadata.tags = tags;
+        if let Ok(json_string) = serde_json::to_string_pretty(&metadata) {
+            let _ = fs::write(sidecar_path, json_string);
+        }
+        if let Some(parent) = Path::new(&path).parent() {
+            folders.insert(parent.to_string_lossy().into_owned());
+        }
+        updated_paths.push(path);
+    }
+
+    xmp_sidecar::sync_if_enabled(app_handle, &updated_paths);
+    for folder in folders {
+        with_catalog(app_handle, |conn| sync_folder(conn, &folder))?;
+    }
+    Ok(())
+}
+
+fn index_faces_in_folder(
+    folder: &str,
+    app_handle: &AppHandle,
+    face_detector: &Session,
+    face_embedder: &Session,
+) -> Result<usize, String> {
+    let paths = with_catalog(app_handle, |conn| {
+        sync_folder(conn, folder)?;
+        paths_needing_face_scan(conn, folder)
+    })?;
+    let paths: Vec<String> = paths.into_iter().filter(|path| !is_video_file(path)).collect();
+    if paths.is_empty() {
+        return Ok(0);
+    }
+
+    let gpu_context = crate::gpu_processing::get_or_init_gpu_context(&app_handle.state::<AppState>()).ok();
+    let total = paths.len();
+    let completed = AtomicUsize::new(0);
+
+    let scans: Vec<ScannedFaces> = paths
+        .par_iter()
+        .filter_map(|path| {
+            let result = file_management::get_cached_or_generate_thumbnail_image(path, app_handle, gpu_context.as_ref())
+                .map_err(|e| e.to_string())
+                .and_then(|image| {
+                    let detections = run_face_detector(&image, face_detector).map_err(|e| e.to_string())?;
+                    let faces = detections
+                        .into_iter()
+                        .map(|face| compute_face_embedding(&image, &face, face_embedder).map(|embedding| (face, embedding)))
+                        .collect::<Result<Vec<_>>>()
+                        .map_err(|e| e.to_string())?;
+                    Ok(ScannedFaces { path: path.clone(), faces })
+                });
+
+            let current = completed.fetch_add(1, Ordering::SeqCst) + 1;
+            let _ = app_handle.emit("face-indexing-progress", serde_json::json!({ "current": current, "total": total }));
+            match result {
+                Ok(scan) => Some(scan),
+                Err(e) => {
+                    eprintln!("Failed to scan faces in {}: {}", path, e);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    let face_count = scans.iter().map(|scan| scan.faces.len()).sum();
+    let named_paths = with_catalog(app_handle, |conn| {
+        for scan in &scans {
+            store_face_scan(conn, scan)?;
+        }
+        cluster_faces(conn)
+    })?;
+    write_person_tags(app_handle, named_paths.into_iter().collect())?;
+
+    Ok(face_count)
+}
+
+#[tauri::command]
+pub async fn index_faces(folder_path: String, app_handle: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    let face_detector = get_or_init_face_detector(&app_handle, &state.face_detector, &state.ai_init_lock)
+        .await
+        .map_err(|e| e.to_string())?;
+    let face_embedder = get_or_init_face_embedding_model(&app_handle, &state.face_embedding_model, &state.ai_init_lock)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tokio::spawn(async move {
+        let worker_handle = app_handle.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            index_faces_in_folder(&folder_path, &worker_handle, &face_detector, &face_embedder)
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r);
+
+        match result {
+            Ok(face_count) => {
+                let _ = app_handle.emit("face-indexing-finished", serde_json::json!({ "faceCount": face_count }));
+            }
+            Err(e) => {
+                let _ = app_handle.emit("face-indexing-error", e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_face_clusters(app_handle: AppHandle) -> Result<Vec<FaceCluster>, String> {
+    with_catalog(&app_handle, |conn| {
+        let mut stmt = conn
+            .prepare(
+                "SELECT c.id, c.name, COUNT(f.id), s.path, s.x1, s.y1, s.x2, s.y2
+                 FROM face_clusters c
+                 JOIN faces f ON f.cluster_id = c.id
+                 JOIN faces s ON s.id = (SELECT MIN(id) FROM faces WHERE cluster_id = c.id)
+                 GROUP BY c.id
+                 ORDER BY c.name IS NULL, COUNT(f.id) DESC, c.name COLLATE NOCASE",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(FaceCluster {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    face_count: row.get::<_, i64>(2)? as usize,
+                    sample_path: row.get(3)?,
+                    sample_face: [row.get(4)?, row.get(5)?, row.get(6)?, row.get(7)?],
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn name_face_cluster(cluster_id: i64, name: Option<String>, app_handle: AppHandle) -> Result<(), String> {
+    let name = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+
+    let paths = with_catalog(&app_handle, |conn| {
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        let paths = {
+            let mut stmt = tx
+                .prepare("SELECT DISTINCT path FROM faces WHERE cluster_id = ?1")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![cluster_id], |row| row.get::<_, String>(0))
+                .map_err(|e| e.to_string())?;
+            rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())?
+        };
+
+        let existing: Option<i64> = match &name {
+            Some(name) => tx
+                .query_row(
+                    "SELECT id FROM face_clusters WHERE name = ?1 AND id != ?2",
+                    params![name, cluster_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?,
+            None => None,
+        };
+
+        match existing {
+            Some(target_id) => {
+                tx.execute("UPDATE faces SET cluster_id = ?2 WHERE cluster_id = ?1", params![cluster_id, target_id])
+                    .map_err(|e| e.to_string())?;
+                tx.execute("DELETE FROM face_clusters WHERE id = ?1", params![cluster_id])
+                    .map_err(|e| e.to_string())?;
+            }
+            None => {
+                tx.execute("UPDATE face_clusters SET name = ?2 WHERE id = ?1", params![cluster_id, name])
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(paths)
+    })?;
+
+    write_person_tags(&app_handle, paths)
+}
+
+#[tauri::command]
+pub fn get_person_images(name: String, folder: Option<String>, app_handle: AppHandle) -> Result<Vec<String>, String> {
+    with_catalog(&app_handle, |conn| {
+        let mut stmt = conn
+            .prepare(
+                "SELECT DISTINCT f.path FROM faces f
+                 JOIN face_clusters c ON c.id = f.cluster_id
+                 JOIN images i ON i.path = f.path
+                 WHERE c.name = ?1 AND (?2 IS NULL OR i.folder = ?2)
+                 ORDER BY f.path",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![name, folder], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<Vec<_>>>().map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+pub fn clear_all_tags(root_path: String, app_handle: AppHandle) -> Result<usize, String> {
     if !Path::new(&root_path).exists() {
         return Err(format!("Root path does not exist: {}", root_path));
     }
 
-    let mut updated_count = 0;
+    let mut updated_paths = Vec::new();
     let walker = WalkDir::new(root_path).into_iter();
 
     for entry in walker.filter_map(|e| e.ok()) {
@@ -348,7 +870,7 @@ pub fn clear_all_tags(root_path: String) -> Result<usize, String> {
                 if let Ok(mut metadata) = serde_json::from_str::<ImageMetadata>(&content) {
                     if let Some(tags) = &mut metadata.tags {
                         let original_len = tags.len();
-                        tags.retain(|tag| tag.starts_with(COLOR_TAG_PREFIX)); // don't remove color tags, just AI tags
+                        tags.retain(|tag| tag.starts_with(COLOR_TAG_PREFIX) || tag.starts_with(PERSON_TAG_PREFIX)); // don't remove color or person tags, just AI tags
                         
                         if tags.len() < original_len {
                             if tags.is_empty() {
@@ -356,7 +878,9 @@ pub fn clear_all_tags(root_path: String) -> Result<usize, String> {
                             }
                             if let Ok(json_string) = serde_json::to_string_pretty(&metadata) {
                                 if fs::write(path, json_string).is_ok() {
-                                    updated_count += 1;
+                                    if let Some(image_path) = path.to_str().and_then(|p| p.strip_suffix(".rrdata")) {
+                                        updated_paths.push(image_path.to_string());
+                                    }
                                 }
                             }
                         }
@@ -365,5 +889,6 @@ pub fn clear_all_tags(root_path: String) -> Result<usize, String> {
             }
         }
     }
-    Ok(updated_count)
+    xmp_sidecar::sync_if_enabled(&app_handle, &updated_paths);
+    Ok(updated_paths.len())
 }
\ No newline at end of file
@@ -0,0 +1,379 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use exif::{Context, Field, In, Reader as ExifReader, Tag, Value as ExifValue};
+
+use crate::capture_time::{capture_time_shift, shifted_capture_time, EXIF_DATE_FORMAT};
+use crate::geotagging::{read_sidecar_gps, to_dms_rationals};
+use crate::image_processing::IptcMetadata;
+use crate::iptc_metadata::{export_keywords, iim_record, read_metadata, xmp_packet};
+
+const TAG_ORIENTATION: u16 = 274;
+const TAG_XMP: u16 = 700;
+const TAG_IPTC_NAA: u16 = 33723;
+const TAG_EXIF_IFD: u16 = 34665;
+const TAG_GPS_IFD: u16 = 34853;
+const TAG_MAKER_NOTE: u16 = 37500;
+const TAG_INTEROP_IFD: u16 = 40965;
+const TAG_PIXEL_X_DIMENSION: u16 = 40962;
+const TAG_PIXEL_Y_DIMENSION: u16 = 40963;
+const TAG_IMAGE_WIDTH: u16 = 256;
+const TAG_IMAGE_LENGTH: u16 = 257;
+
+const TYPE_BYTE: u16 = 1;
+const TYPE_ASCII: u16 = 2;
+const TYPE_SHORT: u16 = 3;
+const TYPE_LONG: u16 = 4;
+const TYPE_RATIONAL: u16 = 5;
+const TYPE_UNDEFINED: u16 = 7;
+
+const COPIED_PRIMARY_TAGS: &[Tag] = &[
+    Tag::ImageDescription,
+    Tag::Make,
+    Tag::Model,
+    Tag::DateTime,
+    Tag::Artist,
+    Tag::Copyright,
+];
+
+struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    data: Vec<u8>,
+}
+
+struct TiffWriter<'a> {
+    bytes: &'a mut Vec<u8>,
+    little_endian: bool,
+}
+
+impl<'a> TiffWriter<'a> {
+    fn u16_bytes(&self, v: u16) -> [u8; 2] {
+        if self.little_endian { v.to_le_bytes() } else { v.to_be_bytes() }
+    }
+
+    fn u32_bytes(&self, v: u32) -> [u8; 4] {
+        if self.little_endian { v.to_le_bytes() } else { v.to_be_bytes() }
+    }
+
+    fn read_u16(&self, pos: usize) -> Option<u16> {
+        let b: [u8; 2] = self.bytes.get(pos..pos + 2)?.try_into().ok()?;
+        Some(if self.little_endian { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+    }
+
+    fn read_u32(&self, pos: usize) -> Option<u32> {
+        let b: [u8; 4] = self.bytes.get(pos..pos + 4)?.try_into().ok()?;
+        Some(if self.little_endian { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    fn align(&mut self) {
+        if self.bytes.len() % 2 != 0 {
+            self.bytes.push(0);
+        }
+    }
+
+    fn write_ifd(&mut self, mut entries: Vec<IfdEntry>, next_ifd: u32) -> u32 {
+        entries.sort_by_key(|e| e.tag);
+
+        let mut inline_values = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            if entry.data.len() <= 4 {
+                let mut value = [0u8; 4];
+                value[..entry.data.len()].copy_from_slice(&entry.data);
+                inline_values.push(value);
+            } else {
+                self.align();
+                let offset = self.bytes.len() as u32;
+                self.bytes.extend_from_slice(&entry.data);
+                inline_values.push(self.u32_bytes(offset));
+            }
+        }
+
+        self.align();
+        let ifd_offset = self.bytes.len() as u32;
+        let count = self.u16_bytes(entries.len() as u16);
+        self.bytes.extend_from_slice(&count);
+        for (entry, value) in entries.iter().zip(inline_values) {
+            let tag = self.u16_bytes(entry.tag);
+            let field_type = self.u16_bytes(entry.field_type);
+            let entry_count = self.u32_bytes(entry.count);
+            self.bytes.extend_from_slice(&tag);
+            self.bytes.extend_from_slice(&field_type);
+            self.bytes.extend_from_slice(&entry_count);
+            self.bytes.extend_from_slice(&value);
+        }
+        let next = self.u32_bytes(next_ifd);
+        self.bytes.extend_from_slice(&next);
+        ifd_offset
+    }
+
+    fn long_entry(&self, tag: u16, value: u32) -> IfdEntry {
+        IfdEntry { tag, field_type: TYPE_LONG, count: 1, data: self.u32_bytes(value).to_vec() }
+    }
+
+    fn short_entry(&self, tag: u16, value: u16) -> IfdEntry {
+        IfdEntry { tag, field_type: TYPE_SHORT, count: 1, data: self.u16_bytes(value).to_vec() }
+    }
+
+    fn rational_entry(&self, tag: u16, values: &[(u32, u32)]) -> IfdEntry {
+        let data = values.iter().flat_map(|&(num, denom)| [self.u32_bytes(num), self.u32_bytes(denom)]).flatten().collect();
+        IfdEntry { tag, field_type: TYPE_RATIONAL, count: values.len() as u32, data }
+    }
+
+    fn ascii_entry(&self, tag: u16, value: &str) -> IfdEntry {
+        let mut data = value.as_bytes().to_vec();
+        data.push(0);
+        IfdEntry { tag, field_type: TYPE_ASCII, count: data.len() as u32, data }
+    }
+
+    fn entry_from_field(&self, field: &Field) -> Option<IfdEntry> {
+        let mut data = Vec::new();
+        let (field_type, count) = match &field.value {
+            ExifValue::Byte(v) => {
+                data.extend_from_slice(v);
+                (1, v.len())
+            }
+            ExifValue::Ascii(strings) => {
+                for s in strings {
+                    data.extend_from_slice(s);
+                    data.push(0);
+                }
+                (2, data.len())
+            }
+            ExifValue::Short(v) => {
+                v.iter().for_each(|x| data.extend_from_slice(&self.u16_bytes(*x)));
+                (3, v.len())
+            }
+            ExifValue::Long(v) => {
+                v.iter().for_each(|x| data.extend_from_slice(&self.u32_bytes(*x)));
+                (4, v.len())
+            }
+            ExifValue::Rational(v) => {
+                v.iter().for_each(|r| {
+                    data.extend_from_slice(&self.u32_bytes(r.num));
+                    data.extend_from_slice(&self.u32_bytes(r.denom));
+                });
+                (5, v.len())
+            }
+            ExifValue::SByte(v) => {
+                data.extend(v.iter().map(|x| *x as u8));
+                (6, v.len())
+            }
+            ExifValue::Undefined(v, _) => {
+                data.extend_from_slice(v);
+                (7, v.len())
+            }
+            ExifValue::SShort(v) => {
+                v.iter().for_each(|x| data.extend_from_slice(&self.u16_bytes(*x as u16)));
+                (8, v.len())
+            }
+            ExifValue::SLong(v) => {
+                v.iter().for_each(|x| data.extend_from_slice(&self.u32_bytes(*x as u32)));
+                (9, v.len())
+            }
+            ExifValue::SRational(v) => {
+                v.iter().for_each(|r| {
+                    data.extend_from_slice(&self.u32_bytes(r.num as u32));
+                    data.extend_from_slice(&self.u32_bytes(r.denom as u32));
+                });
+                (10, v.len())
+            }
+            ExifValue::Float(v) => {
+                v.iter().for_each(|x| data.extend_from_slice(&self.u32_bytes(x.to_bits())));
+                (11, v.len())
+            }
+            ExifValue::Double(v) => {
+                v.iter().for_each(|x| {
+                    let bits = x.to_bits();
+                    data.extend_from_slice(&if self.little_endian { bits.to_le_bytes() } else { bits.to_be_bytes() });
+                });
+                (12, v.len())
+            }
+            _ => return None,
+        };
+        if count == 0 {
+            return None;
+        }
+        Some(IfdEntry { tag: field.tag.number(), field_type, count: count as u32, data })
+    }
+}
+
+// Descriptive fields entered in RapidRAW take precedence over the ones in the original EXIF.
+fn build_iptc_record(fields: &[Field], keywords: &[String], iptc: &IptcMetadata) -> Option<Vec<u8>> {
+    let ascii = |tag: Tag| {
+        fields
+            .iter()
+            .find(|f| f.tag == tag && f.ifd_num == In::PRIMARY)
+            .map(|f| f.display_value().to_string().trim_matches('"').trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+
+    let effective = IptcMetadata {
+        title: iptc.title.clone(),
+        caption: iptc.caption.clone().or_else(|| ascii(Tag::ImageDescription)),
+        creator: iptc.creator.clone().or_else(|| ascii(Tag::Artist)),
+        copyright: iptc.copyright.clone().or_else(|| ascii(Tag::Copyright)),
+        usage_terms: iptc.usage_terms.clone(),
+    };
+    let mut record = iim_record(&effective, keywords)?;
+    while record.len() % 4 != 0 {
+        record.push(0);
+    }
+    Some(record)
+}
+
+pub fn write_tiff_metadata(image_bytes: &mut Vec<u8>, original_path_str: &str, strip_gps: bool) -> Result<(), String> {
+    let little_endian = match image_bytes.get(0..4) {
+        Some([b'I', b'I', 42, 0]) => true,
+        Some([b'M', b'M', 0, 42]) => false,
+        _ => return Err("Exported TIFF has an invalid header.".to_string()),
+    };
+
+    let file = File::open(Path::new(original_path_str)).map_err(|e| e.to_string())?;
+    let exif = match ExifReader::new().read_from_container(&mut BufReader::new(file)) {
+        Ok(exif) => exif,
+        Err(e) => {
+            eprintln!("Failed to read metadata from original file {}: {}", original_path_str, e);
+            return Ok(());
+        }
+    };
+    let fields: Vec<Field> = exif.fields().cloned().collect();
+    let sidecar = read_metadata(original_path_str);
+    let keywords = export_keywords(&sidecar);
+    let iptc = sidecar.iptc.clone().unwrap_or_default();
+
+    let mut writer = TiffWriter { bytes: image_bytes, little_endian };
+    let ifd0_offset = writer.read_u32(4).ok_or("Exported TIFF is truncated.")? as usize;
+    let entry_count = writer.read_u16(ifd0_offset).ok_or("Exported TIFF is truncated.")? as usize;
+    let next_ifd_pos = ifd0_offset + 2 + entry_count * 12;
+    let next_ifd = writer.read_u32(next_ifd_pos).unwrap_or(0);
+
+    let copied_tags: Vec<u16> = COPIED_PRIMARY_TAGS.iter().map(|t| t.number()).collect();
+    let replaced_tags = [TAG_ORIENTATION, TAG_XMP, TAG_IPTC_NAA, TAG_EXIF_IFD, TAG_GPS_IFD];
+
+    let mut width = 0;
+    let mut height = 0;
+    let mut ifd0_entries = Vec::new();
+    for i in 0..entry_count {
+        let pos = ifd0_offset + 2 + i * 12;
+        let tag = writer.read_u16(pos).ok_or("Exported TIFF is truncated.")?;
+        let field_type = writer.read_u16(pos + 2).ok_or("Exported TIFF is truncated.")?;
+        let count = writer.read_u32(pos + 4).ok_or("Exported TIFF is truncated.")?;
+        let raw_value = writer.bytes.get(pos + 8..pos + 12).ok_or("Exported TIFF is truncated.")?.to_vec();
+
+        if tag == TAG_IMAGE_WIDTH || tag == TAG_IMAGE_LENGTH {
+            let value = if field_type == TYPE_SHORT {
+                writer.read_u16(pos + 8).unwrap_or(0) as u32
+            } else {
+                writer.read_u32(pos + 8).unwrap_or(0)
+            };
+            if tag == TAG_IMAGE_WIDTH { width = value } else { height = value }
+        }
+
+        if copied_tags.contains(&tag) || replaced_tags.contains(&tag) {
+            continue;
+        }
+        // Out-of-line values keep pointing at the original data, which stays in place.
+        ifd0_entries.push(IfdEntry { tag, field_type, count, data: raw_value });
+    }
+
+    let shifted_date = if capture_time_shift(original_path_str) != 0 {
+        shifted_capture_time(Path::new(original_path_str)).map(|dt| dt.format(EXIF_DATE_FORMAT).to_string())
+    } else {
+        None
+    };
+    let date_tags = [Tag::DateTimeOriginal, Tag::DateTimeDigitized];
+
+    let mut exif_entries: Vec<IfdEntry> = fields
+        .iter()
+        .filter(|f| f.ifd_num == In::PRIMARY && f.tag.context() == Context::Exif)
+        .filter(|f| {
+            let number = f.tag.number();
+            number != TAG_MAKER_NOTE && number != TAG_INTEROP_IFD && number != TAG_PIXEL_X_DIMENSION && number != TAG_PIXEL_Y_DIMENSION
+        })
+        .filter(|f| shifted_date.is_none() || !date_tags.contains(&f.tag))
+        .filter_map(|f| writer.entry_from_field(f))
+        .chain([
+            writer.long_entry(TAG_PIXEL_X_DIMENSION, width),
+            writer.long_entry(TAG_PIXEL_Y_DIMENSION, height),
+        ])
+        .collect();
+    if let Some(date) = &shifted_date {
+        exif_entries.extend(date_tags.iter().map(|tag| writer.ascii_entry(tag.number(), date)));
+    }
+    let exif_ifd_offset = writer.write_ifd(exif_entries, 0);
+    ifd0_entries.push(writer.long_entry(TAG_EXIF_IFD, exif_ifd_offset));
+
+    if !strip_gps {
+        // Coordinates set in RapidRAW replace whatever GPS block the camera wrote.
+        let gps_entries: Vec<IfdEntry> = match read_sidecar_gps(original_path_str) {
+            Some(gps) => vec![
+                IfdEntry { tag: Tag::GPSVersionID.number(), field_type: TYPE_BYTE, count: 4, data: vec![2, 3, 0, 0] },
+                writer.ascii_entry(Tag::GPSLatitudeRef.number(), if gps.latitude < 0.0 { "S" } else { "N" }),
+                writer.rational_entry(Tag::GPSLatitude.number(), &to_dms_rationals(gps.latitude)),
+                writer.ascii_entry(Tag::GPSLongitudeRef.number(), if gps.longitude < 0.0 { "W" } else { "E" }),
+                writer.rational_entry(Tag::GPSLongitude.number(), &to_dms_rationals(gps.longitude)),
+            ],
+            None => fields
+                .iter()
+                .filter(|f| f.ifd_num == In::PRIMARY && f.tag.context() == Context::Gps)
+                .filter_map(|f| writer.entry_from_field(f))
+                .collect(),
+        };
+        if !gps_entries.is_empty() {
+            let gps_ifd_offset = writer.write_ifd(gps_entries, 0);
+            ifd0_entries.push(writer.long_entry(TAG_GPS_IFD, gps_ifd_offset));
+        }
+    }
+
+    let overrides = [
+        (Tag::ImageDescription, &iptc.caption),
+        (Tag::Artist, &iptc.creator),
+        (Tag::Copyright, &iptc.copyright),
+    ];
+    ifd0_entries.extend(
+        fields
+            .iter()
+            .filter(|f| f.ifd_num == In::PRIMARY && COPIED_PRIMARY_TAGS.contains(&f.tag))
+            .filter(|f| !overrides.iter().any(|(tag, value)| *tag == f.tag && value.is_some()))
+            .filter_map(|f| writer.entry_from_field(f)),
+    );
+    for (tag, value) in &overrides {
+        if let Some(value) = value {
+            ifd0_entries.push(writer.ascii_entry(tag.number(), value));
+        }
+    }
+    ifd0_entries.push(writer.short_entry(TAG_ORIENTATION, 1));
+
+    if let Some(record) = build_iptc_record(&fields, &keywords, &iptc) {
+        ifd0_entries.push(IfdEntry { tag: TAG_IPTC_NAA, field_type: TYPE_UNDEFINED, count: record.len() as u32, data: record });
+    }
+    if let Some(xmp) = xmp_packet(&sidecar) {
+        let data = xmp.into_bytes();
+        ifd0_entries.push(IfdEntry { tag: TAG_XMP, field_type: TYPE_BYTE, count: data.len() as u32, data });
+    }
+
+    let new_ifd0_offset = writer.write_ifd(ifd0_entries, next_ifd);
+    let header_offset = writer.u32_bytes(new_ifd0_offset);
+    writer.bytes[4..8].copy_from_slice(&header_offset);
+
+    Ok(())
+}
+
+// Standalone Exif block for containers that store it apart from the image data, such as AVIF.
+pub fn exif_payload(original_path_str: &str, width: u32, height: u32, strip_gps: bool) -> Result<Option<Vec<u8>>, String> {
+    let mut bytes = vec![b'I', b'I', 42, 0, 8, 0, 0, 0, 2, 0];
+    for (tag, value) in [(TAG_IMAGE_WIDTH, width), (TAG_IMAGE_LENGTH, height)] {
+        bytes.extend_from_slice(&tag.to_le_bytes());
+        bytes.extend_from_slice(&TYPE_LONG.to_le_bytes());
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    let stub_len = bytes.len();
+
+    write_tiff_metadata(&mut bytes, original_path_str, strip_gps)?;
+    Ok((bytes.len() > stub_len).then_some(bytes))
+}
@@ -0,0 +1,285 @@
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+use tokio::process::Command;
+
+use crate::export_hooks::ExportedFile;
+use crate::file_management::load_settings;
+
+const MAX_ATTEMPTS: u32 = 3;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(600);
+
+// Required fields default to empty so a target that is still being filled in doesn't break loading
+// the settings.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum UploadDestination {
+    // Uses the system's OpenSSH client, so authentication goes through keys or the ssh agent.
+    #[serde(rename_all = "camelCase")]
+    Sftp {
+        #[serde(default)]
+        host: String,
+        port: Option<u16>,
+        #[serde(default)]
+        username: String,
+        #[serde(default)]
+        remote_dir: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    S3 {
+        #[serde(default)]
+        endpoint: String,
+        #[serde(default)]
+        region: String,
+        #[serde(default)]
+        bucket: String,
+        prefix: Option<String>,
+        #[serde(default)]
+        access_key_id: String,
+        #[serde(default)]
+        secret_access_key: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    WebDav {
+        #[serde(default)]
+        url: String,
+        username: Option<String>,
+        password: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadTarget {
+    pub id: String,
+    pub name: String,
+    pub destination: UploadDestination,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct UploadErrorPayload {
+    path: String,
+    target: String,
+    error: String,
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = if key.len() > BLOCK_SIZE { Sha256::digest(key).to_vec() } else { key.to_vec() };
+    block.resize(BLOCK_SIZE, 0);
+    let inner_key: Vec<u8> = block.iter().map(|b| b ^ 0x36).collect();
+    let outer_key: Vec<u8> = block.iter().map(|b| b ^ 0x5c).collect();
+    let inner = Sha256::new().chain_update(&inner_key).chain_update(data).finalize();
+    Sha256::new().chain_update(&outer_key).chain_update(inner).finalize().to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn join_remote(dir: &str, name: &str) -> String {
+    let dir = dir.trim_end_matches('/');
+    if dir.is_empty() { name.to_string() } else { format!("{}/{}", dir, name) }
+}
+
+// sftp batch files are parsed line by line with backslash escapes inside quotes, so a newline
+// would start a new command and a bare quote or backslash would end or alter the argument.
+fn quote_batch_arg(arg: &str) -> Result<String, String> {
+    if arg.chars().any(|c| c.is_control()) {
+        return Err(format!("Path contains control characters: {:?}", arg));
+    }
+    Ok(format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\"")))
+}
+
+async fn upload_sftp(path: &Path, host: &str, port: Option<u16>, username: &str, remote_dir: &str) -> Result<(), String> {
+    let remote_path = join_remote(remote_dir, &path.file_name().unwrap_or_default().to_string_lossy());
+    let batch = format!("put {} {}\n", quote_batch_arg(&path.to_string_lossy())?, quote_batch_arg(&remote_path)?);
+    let batch_path = std::env::temp_dir().join(format!("rapidraw-sftp-{}.txt", uuid::Uuid::new_v4()));
+    std::fs::write(&batch_path, batch).map_err(|e| e.to_string())?;
+
+    let output = tokio::time::timeout(
+        UPLOAD_TIMEOUT,
+        Command::new("sftp")
+            .arg("-b")
+            .arg(&batch_path)
+            .args(["-o", "BatchMode=yes", "-o", &format!("ConnectTimeout={}", CONNECT_TIMEOUT.as_secs())])
+            .args(["-P", &port.unwrap_or(22).to_string()])
+            .arg(format!("{}@{}", username, host))
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await;
+    let _ = std::fs::remove_file(&batch_path);
+    let output = output
+        .map_err(|_| format!("sftp timed out after {} seconds", UPLOAD_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("Failed to run sftp: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(())
+}
+
+// Path-style PUT signed with AWS Signature Version 4, which also works for MinIO and other
+// S3-compatible services.
+async fn upload_s3(
+    client: &reqwest::Client,
+    path: &Path,
+    endpoint: &str,
+    region: &str,
+    bucket: &str,
+    prefix: Option<&str>,
+    access_key_id: &str,
+    secret_access_key: &str,
+) -> Result<(), String> {
+    let body = tokio::fs::read(path).await.map_err(|e| e.to_string())?;
+    let key = join_remote(prefix.unwrap_or(""), &path.file_name().unwrap_or_default().to_string_lossy());
+    let url = reqwest::Url::parse(endpoint).map_err(|e| format!("Invalid S3 endpoint: {}", e))?;
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        _ => return Err("Invalid S3 endpoint: missing host".to_string()),
+    };
+    let canonical_uri = format!(
+        "/{}/{}",
+        uri_encode(bucket),
+        key.split('/').map(uri_encode).collect::<Vec<_>>().join("/")
+    );
+
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = format!("{:x}", Sha256::digest(&body));
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        canonical_uri, host, payload_hash, amz_date, signed_headers, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{:x}",
+        amz_date,
+        scope,
+        Sha256::digest(canonical_request.as_bytes())
+    );
+    let signing_key = [date.as_str(), region, "s3", "aws4_request"]
+        .iter()
+        .fold(format!("AWS4{}", secret_access_key).into_bytes(), |key, part| hmac_sha256(&key, part.as_bytes()));
+    let signature = to_hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        access_key_id, scope, signed_headers, signature
+    );
+
+    let request_url = format!("{}://{}{}", url.scheme(), host, canonical_uri);
+    let response = client
+        .put(request_url)
+        .header("x-amz-content-sha256", payload_hash)
+        .header("x-amz-date", amz_date)
+        .header("authorization", authorization)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("S3 responded with {}", response.status()));
+    }
+    Ok(())
+}
+
+async fn upload_webdav(
+    client: &reqwest::Client,
+    path: &Path,
+    url: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+) -> Result<(), String> {
+    let body = tokio::fs::read(path).await.map_err(|e| e.to_string())?;
+    let file_name = uri_encode(&path.file_name().unwrap_or_default().to_string_lossy());
+    let mut request = client.put(join_remote(url, &file_name)).body(body);
+    if let Some(username) = username.filter(|u| !u.is_empty()) {
+        request = request.basic_auth(username, password);
+    }
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("WebDAV server responded with {}", response.status()));
+    }
+    Ok(())
+}
+
+async fn upload_file(client: &reqwest::Client, path: &Path, destination: &UploadDestination) -> Result<(), String> {
+    match destination {
+        UploadDestination::Sftp { host, port, username, remote_dir } => {
+            upload_sftp(path, host, *port, username, remote_dir).await
+        }
+        UploadDestination::S3 { endpoint, region, bucket, prefix, access_key_id, secret_access_key } => {
+            upload_s3(client, path, endpoint, region, bucket, prefix.as_deref(), access_key_id, secret_access_key).await
+        }
+        UploadDestination::WebDav { url, username, password } => {
+            upload_webdav(client, path, url, username.as_deref(), password.as_deref()).await
+        }
+    }
+}
+
+// Uploads the exported files to the target configured in the settings, retrying each file with a
+// growing delay. Failed uploads are reported per file; the exported files stay on disk either way.
+pub async fn upload_exported_files(target_id: &str, files: &[ExportedFile], app_handle: &AppHandle) {
+    let targets = load_settings(app_handle.clone()).ok().and_then(|s| s.upload_targets).unwrap_or_default();
+    let Some(target) = targets.into_iter().find(|t| t.id == target_id) else {
+        eprintln!("Upload target not found: {}", target_id);
+        let _ = app_handle.emit(
+            "upload-error",
+            UploadErrorPayload { path: String::new(), target: target_id.to_string(), error: "Upload target not found.".to_string() },
+        );
+        return;
+    };
+
+    let client = match reqwest::Client::builder().connect_timeout(CONNECT_TIMEOUT).timeout(UPLOAD_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            let _ = app_handle.emit(
+                "upload-error",
+                UploadErrorPayload { path: String::new(), target: target.name.clone(), error: e.to_string() },
+            );
+            return;
+        }
+    };
+    let total = files.len();
+    for (i, file) in files.iter().enumerate() {
+        let path_str = file.output_path.to_string_lossy().into_owned();
+        let _ = app_handle.emit("upload-progress", serde_json::json!({ "current": i, "total": total, "path": path_str }));
+
+        let mut attempt = 1;
+        let result = loop {
+            match upload_file(&client, &file.output_path, &target.destination).await {
+                Ok(()) => break Ok(()),
+                Err(e) if attempt >= MAX_ATTEMPTS => break Err(e),
+                Err(e) => {
+                    eprintln!("Upload of {} to '{}' failed (attempt {}): {}", path_str, target.name, attempt, e);
+                    tokio::time::sleep(Duration::from_secs(2u64.pow(attempt))).await;
+                    attempt += 1;
+                }
+            }
+        };
+        if let Err(error) = result {
+            eprintln!("Failed to upload {} to '{}': {}", path_str, target.name, error);
+            let _ = app_handle.emit("upload-error", UploadErrorPayload { path: path_str, target: target.name.clone(), error });
+        }
+    }
+    let _ = app_handle.emit("upload-progress", serde_json::json!({ "current": total, "total": total, "path": "" }));
+}
@@ -0,0 +1,103 @@
+use std::io::ErrorKind;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+use std::process::Command;
+
+use image::DynamicImage;
+use serde::Serialize;
+use serde_json::Value;
+
+// Videos are only browsed and organized, never edited. Frames and metadata come from ffmpeg's
+// command line tools, which read every container and codec cameras and phones write.
+const FFMPEG: &str = "ffmpeg";
+const FFPROBE: &str = "ffprobe";
+const POSTER_FRAME_DIM: u32 = 1280;
+// Keeps Windows from flashing a console window for every thumbnail.
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoMetadata {
+    pub duration_seconds: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub creation_time: Option<String>,
+}
+
+fn run_tool(tool: &str, args: &[&str]) -> Result<Vec<u8>, String> {
+    let mut command = Command::new(tool);
+    command.args(args);
+    #[cfg(target_os = "windows")]
+    command.creation_flags(CREATE_NO_WINDOW);
+    let output = command.output().map_err(|e| {
+        if e.kind() == ErrorKind::NotFound {
+            format!("{} was not found. Install ffmpeg to show previews and details of videos.", tool)
+        } else {
+            e.to_string()
+        }
+    })?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(output.stdout)
+}
+
+// The thumbnail filter picks the most representative of the first frames, which skips the black
+// or blurry frames many clips start with. ffmpeg applies the rotation phones record in.
+pub fn extract_poster_frame(path: &str) -> Result<DynamicImage, String> {
+    let filter = format!(
+        "thumbnail,scale={0}:{0}:force_original_aspect_ratio=decrease",
+        POSTER_FRAME_DIM
+    );
+    let png = run_tool(
+        FFMPEG,
+        &["-v", "error", "-i", path, "-vf", &filter, "-frames:v", "1", "-f", "image2pipe", "-vcodec", "png", "-"],
+    )?;
+    if png.is_empty() {
+        return Err(format!("No frame could be read from '{}'.", path));
+    }
+    image::load_from_memory(&png).map_err(|e| e.to_string())
+}
+
+// ffprobe reports frame rates as fractions such as "30000/1001".
+fn parse_frame_rate(rate: &str) -> Option<f64> {
+    let (num, den) = rate.split_once('/')?;
+    let (num, den) = (num.parse::<f64>().ok()?, den.parse::<f64>().ok()?);
+    (den > 0.0 && num > 0.0).then(|| num / den)
+}
+
+pub fn read_video_metadata(path: &str) -> Result<VideoMetadata, String> {
+    let output = run_tool(
+        FFPROBE,
+        &["-v", "error", "-print_format", "json", "-show_format", "-show_streams", path],
+    )?;
+    let probe: Value = serde_json::from_slice(&output).map_err(|e| e.to_string())?;
+    let streams = probe["streams"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let stream_of = |kind: &str| streams.iter().find(|s| s["codec_type"] == kind);
+    let video = stream_of("video");
+    let audio = stream_of("audio");
+    let format = &probe["format"];
+
+    Ok(VideoMetadata {
+        duration_seconds: format["duration"].as_str().and_then(|d| d.parse().ok()),
+        width: video.and_then(|s| s["width"].as_u64()).map(|w| w as u32),
+        height: video.and_then(|s| s["height"].as_u64()).map(|h| h as u32),
+        frame_rate: video
+            .and_then(|s| s["avg_frame_rate"].as_str())
+            .and_then(parse_frame_rate),
+        video_codec: video.and_then(|s| s["codec_name"].as_str()).map(str::to_string),
+        audio_codec: audio.and_then(|s| s["codec_name"].as_str()).map(str::to_string),
+        creation_time: format["tags"]["creation_time"].as_str().map(str::to_string),
+    })
+}
+
+#[tauri::command]
+pub async fn get_video_metadata(path: String) -> Result<VideoMetadata, String> {
+    tokio::task::spawn_blocking(move || read_video_metadata(&path))
+        .await
+        .map_err(|e| e.to_string())?
+}
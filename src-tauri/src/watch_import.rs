@@ -0,0 +1,188 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::file_management::{apply_adjustments_to_paths, import_file, load_presets, ImportSettings, PresetItem};
+use crate::formats::is_supported_image_file;
+use crate::AppState;
+
+// Cameras and card readers write in chunks, so a file is only picked up once its size stops changing
+// between two checks.
+const SETTLE_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchFolder {
+    pub source: String,
+    pub destination: String,
+    pub import_settings: ImportSettings,
+    #[serde(default)]
+    pub preset_id: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchImportPayload {
+    pub source: String,
+    pub destination: String,
+    pub imported: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchImportErrorPayload {
+    pub source: String,
+    pub path: Option<String>,
+    pub error: String,
+}
+
+pub struct WatchImporters {
+    folders: Vec<WatchFolder>,
+    _watchers: Vec<RecommendedWatcher>,
+}
+
+fn emit_error(app_handle: &AppHandle, source: &str, path: Option<&str>, error: String) {
+    eprintln!("Watch folder import failed for '{}': {}", path.unwrap_or(source), error);
+    let _ = app_handle.emit(
+        "watch-import-error",
+        WatchImportErrorPayload { source: source.to_string(), path: path.map(String::from), error },
+    );
+}
+
+fn find_preset(items: &[PresetItem], id: &str) -> Option<Value> {
+    items.iter().find_map(|item| match item {
+        PresetItem::Preset(preset) => (preset.id == id).then(|| preset.adjustments.clone()),
+        PresetItem::Folder(folder) => folder.children.iter().find(|p| p.id == id).map(|p| p.adjustments.clone()),
+    })
+}
+
+fn import_ready(folder: &WatchFolder, paths: &[String], sequence: &mut usize, app_handle: &AppHandle) {
+    let mut imported = Vec::new();
+    for path in paths {
+        *sequence += 1;
+        match import_file(path, &folder.destination, &folder.import_settings, *sequence, paths.len()) {
+            Ok(dest) => imported.push(dest.to_string_lossy().into_owned()),
+            Err(e) => emit_error(app_handle, &folder.source, Some(path), e),
+        }
+    }
+    if imported.is_empty() {
+        return;
+    }
+
+    if let Some(preset_id) = &folder.preset_id {
+        let preset = load_presets(app_handle.clone()).ok().and_then(|items| find_preset(&items, preset_id));
+        match preset {
+            Some(adjustments) => {
+                if let Err(e) = apply_adjustments_to_paths(imported.clone(), adjustments, app_handle.clone()) {
+                    emit_error(app_handle, &folder.source, None, e);
+                }
+            }
+            None => emit_error(app_handle, &folder.source, None, format!("Preset '{}' no longer exists.", preset_id)),
+        }
+    }
+
+    println!("Watch folder '{}' imported {} file(s).", folder.source, imported.len());
+    let _ = app_handle.emit(
+        "watch-import-complete",
+        WatchImportPayload { source: folder.source.clone(), destination: folder.destination.clone(), imported },
+    );
+}
+
+fn handle_events(folder: WatchFolder, rx: Receiver<notify::Result<Event>>, app_handle: AppHandle) {
+    // Last seen size of each new file, None until it has been checked once.
+    let mut pending: HashMap<String, Option<u64>> = HashMap::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut sequence = 0;
+    loop {
+        let received = if pending.is_empty() {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            rx.recv_timeout(SETTLE_INTERVAL)
+        };
+
+        match received {
+            Ok(Ok(event)) => {
+                if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    continue;
+                }
+                for path in event.paths.iter().filter_map(|p| p.to_str()) {
+                    if is_supported_image_file(path) && !seen.contains(path) {
+                        pending.insert(path.to_string(), None);
+                    }
+                }
+            }
+            Ok(Err(e)) => emit_error(&app_handle, &folder.source, None, e.to_string()),
+            Err(RecvTimeoutError::Timeout) => {
+                let mut ready = Vec::new();
+                pending.retain(|path, last_size| {
+                    let Ok(size) = fs::metadata(path).map(|m| m.len()) else { return false; };
+                    if *last_size == Some(size) && size > 0 {
+                        ready.push(path.clone());
+                        return false;
+                    }
+                    *last_size = Some(size);
+                    true
+                });
+                if !ready.is_empty() {
+                    ready.sort();
+                    seen.extend(ready.iter().cloned());
+                    import_ready(&folder, &ready, &mut sequence, &app_handle);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+fn start_watcher(folder: &WatchFolder, app_handle: &AppHandle) -> Result<RecommendedWatcher, String> {
+    if !Path::new(&folder.source).is_dir() {
+        return Err(format!("Watch folder does not exist: {}", folder.source));
+    }
+    if Path::new(&folder.source) == Path::new(&folder.destination) {
+        return Err("The destination must be different from the watched folder.".to_string());
+    }
+
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        let _ = tx.send(res);
+    })
+    .map_err(|e| e.to_string())?;
+    watcher
+        .watch(Path::new(&folder.source), RecursiveMode::NonRecursive)
+        .map_err(|e| e.to_string())?;
+
+    let folder = folder.clone();
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || handle_events(folder, rx, app_handle));
+    Ok(watcher)
+}
+
+// Restarts the watchers when the configured folders changed. Dropping a watcher closes its channel,
+// which ends the matching event thread.
+pub fn apply_watch_folders(app_handle: &AppHandle, folders: Vec<WatchFolder>) {
+    let state = app_handle.state::<AppState>();
+    let mut current = state.watch_importers.lock().unwrap();
+    if current.as_ref().map_or(folders.is_empty(), |w| w.folders == folders) {
+        return;
+    }
+    *current = None;
+
+    let watchers = folders
+        .iter()
+        .filter_map(|folder| match start_watcher(folder, app_handle) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                emit_error(app_handle, &folder.source, None, e);
+                None
+            }
+        })
+        .collect();
+    *current = Some(WatchImporters { folders, _watchers: watchers });
+}
@@ -0,0 +1,190 @@
+use std::fs;
+use std::path::Path;
+
+use ab_glyph::{FontVec, PxScale};
+use chrono::{DateTime, Utc};
+use image::{imageops, DynamicImage, GenericImageView, Rgba, RgbaImage};
+use imageproc::drawing::{draw_text_mut, text_size};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::file_management::generate_filename_from_template;
+use crate::image_processing::with_depth_of;
+
+const SYSTEM_FONT_CANDIDATES: &[&str] = &[
+    "C:\\Windows\\Fonts\\segoeui.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "/System/Library/Fonts/Helvetica.ttc",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/liberation/LiberationSans-Regular.ttf",
+    "/usr/share/fonts/noto/NotoSans-Regular.ttf",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WatermarkKind {
+    Text,
+    Image,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WatermarkPosition {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WatermarkSettings {
+    pub kind: WatermarkKind,
+    pub text: Option<String>,
+    pub image_path: Option<String>,
+    pub font_path: Option<String>,
+    pub color: Option<String>,
+    pub position: WatermarkPosition,
+    pub opacity: f32,
+    pub scale: f32,
+    pub margin: Option<f32>,
+}
+
+fn parse_hex_color(color: Option<&str>) -> [u8; 3] {
+    let hex = color.unwrap_or("#ffffff").trim_start_matches('#');
+    if hex.len() != 6 {
+        return [255, 255, 255];
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(255);
+    [channel(0), channel(2), channel(4)]
+}
+
+fn load_font(font_path: Option<&str>) -> Result<FontVec, String> {
+    let candidates: Vec<&str> = match font_path {
+        Some(path) if !path.is_empty() => vec![path],
+        _ => SYSTEM_FONT_CANDIDATES.to_vec(),
+    };
+
+    for candidate in candidates {
+        if let Ok(bytes) = fs::read(candidate) {
+            if let Ok(font) = FontVec::try_from_vec_and_index(bytes, 0) {
+                return Ok(font);
+            }
+        }
+    }
+
+    Err("Could not load a font for the text watermark. Please choose a font file in the watermark settings.".to_string())
+}
+
+fn render_text_layer(text: &str, settings: &WatermarkSettings, short_edge: u32) -> Result<RgbaImage, String> {
+    let font = load_font(settings.font_path.as_deref())?;
+    let px_height = (short_edge as f32 * settings.scale / 100.0).max(8.0);
+    let scale = PxScale::from(px_height);
+    let (text_w, text_h) = text_size(scale, &font, text);
+    if text_w == 0 || text_h == 0 {
+        return Err("Watermark text is empty.".to_string());
+    }
+
+    let [r, g, b] = parse_hex_color(settings.color.as_deref());
+    let padding = (px_height * 0.2).ceil() as u32;
+    let mut layer = RgbaImage::from_pixel(text_w + padding * 2, text_h + padding * 2, Rgba([r, g, b, 0]));
+    draw_text_mut(&mut layer, Rgba([r, g, b, 255]), padding as i32, padding as i32, scale, &font, text);
+    Ok(layer)
+}
+
+fn load_image_layer(settings: &WatermarkSettings, image_width: u32) -> Result<RgbaImage, String> {
+    let path = settings
+        .image_path
+        .as_deref()
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| "No image selected for the watermark.".to_string())?;
+    let overlay = image::open(path)
+        .map_err(|e| format!("Failed to load watermark image {}: {}", path, e))?;
+
+    let (w, h) = overlay.dimensions();
+    let target_w = ((image_width as f32 * settings.scale / 100.0).round() as u32).max(1);
+    let target_h = ((target_w as f32 * h as f32 / w.max(1) as f32).round() as u32).max(1);
+    Ok(overlay.resize_exact(target_w, target_h, imageops::FilterType::Lanczos3).to_rgba8())
+}
+
+fn layer_origin(position: WatermarkPosition, image_size: (u32, u32), layer_size: (u32, u32), margin: u32) -> (i64, i64) {
+    let (iw, ih) = (image_size.0 as i64, image_size.1 as i64);
+    let (lw, lh) = (layer_size.0 as i64, layer_size.1 as i64);
+    let m = margin as i64;
+
+    let x = match position {
+        WatermarkPosition::TopLeft | WatermarkPosition::CenterLeft | WatermarkPosition::BottomLeft => m,
+        WatermarkPosition::TopCenter | WatermarkPosition::Center | WatermarkPosition::BottomCenter => (iw - lw) / 2,
+        WatermarkPosition::TopRight | WatermarkPosition::CenterRight | WatermarkPosition::BottomRight => iw - lw - m,
+    };
+    let y = match position {
+        WatermarkPosition::TopLeft | WatermarkPosition::TopCenter | WatermarkPosition::TopRight => m,
+        WatermarkPosition::CenterLeft | WatermarkPosition::Center | WatermarkPosition::CenterRight => (ih - lh) / 2,
+        WatermarkPosition::BottomLeft | WatermarkPosition::BottomCenter | WatermarkPosition::BottomRight => ih - lh - m,
+    };
+    (x, y)
+}
+
+pub fn apply_watermark(
+    image: &mut DynamicImage,
+    settings: &WatermarkSettings,
+    original_path: &Path,
+    sequence: usize,
+    total: usize,
+    file_date: &DateTime<Utc>,
+) -> Result<(), String> {
+    let (width, height) = image.dimensions();
+    let short_edge = width.min(height);
+
+    let layer = match settings.kind {
+        WatermarkKind::Text => {
+            let template = settings.text.as_deref().unwrap_or("");
+            let text = generate_filename_from_template(template, original_path, sequence, total, file_date);
+            render_text_layer(&text, settings, short_edge)?
+        }
+        WatermarkKind::Image => load_image_layer(settings, width)?,
+    };
+
+    let margin = (short_edge as f32 * settings.margin.unwrap_or(3.0) / 100.0).round() as u32;
+    let (origin_x, origin_y) = layer_origin(settings.position, (width, height), layer.dimensions(), margin);
+    let opacity = (settings.opacity / 100.0).clamp(0.0, 1.0);
+    let (layer_w, layer_h) = layer.dimensions();
+
+    let mut base = image.to_rgba32f();
+    base.par_chunks_mut(width as usize * 4)
+        .enumerate()
+        .for_each(|(y, row)| {
+            let ly = y as i64 - origin_y;
+            if ly < 0 || ly >= layer_h as i64 {
+                return;
+            }
+            for x in 0..width as i64 {
+                let lx = x - origin_x;
+                if lx < 0 || lx >= layer_w as i64 {
+                    continue;
+                }
+                let src = layer.get_pixel(lx as u32, ly as u32);
+                let alpha = src[3] as f32 / 255.0 * opacity;
+                if alpha <= 0.0 {
+                    continue;
+                }
+                let dst = &mut row[x as usize * 4..x as usize * 4 + 3];
+                for c in 0..3 {
+                    dst[c] = dst[c] * (1.0 - alpha) + src[c] as f32 / 255.0 * alpha;
+                }
+            }
+        });
+
+    *image = with_depth_of(image, base);
+    Ok(())
+}
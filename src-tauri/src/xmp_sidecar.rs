@@ -0,0 +1,339 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use tauri::AppHandle;
+use walkdir::WalkDir;
+
+use crate::file_management::{get_sidecar_path, load_settings};
+use crate::formats::{is_raw_file, is_supported_image_file};
+use crate::image_processing::{ImageFlag, ImageMetadata};
+use crate::iptc_metadata::export_keywords;
+use crate::tagging::COLOR_TAG_PREFIX;
+
+// Lightroom writes label names, darktable writes indices into this same order.
+const COLOR_LABELS: [&str; 5] = ["red", "yellow", "green", "blue", "purple"];
+const REJECTED_RATING: i32 = -1;
+
+#[derive(Debug, Default)]
+struct XmpData {
+    rating: Option<i32>,
+    label: Option<String>,
+    keywords: Vec<String>,
+}
+
+// Lightroom names sidecars after the stem (IMG_0001.xmp), darktable after the full filename
+// (IMG_0001.CR3.xmp). Both are accepted, in either case.
+pub fn find_xmp_sidecar(image_path: &str) -> Option<PathBuf> {
+    let path = Path::new(image_path);
+    let file_name = path.file_name()?.to_string_lossy();
+    ["xmp", "XMP"]
+        .iter()
+        .flat_map(|ext| [path.with_file_name(format!("{}.{}", file_name, ext)), path.with_extension(ext)])
+        .find(|candidate| candidate.is_file())
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+pub fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// A simple property may be serialized as an attribute of rdf:Description or as a child element.
+pub fn read_property(xml: &str, name: &str) -> Option<String> {
+    let attribute = format!("{}=\"", name);
+    if let Some(start) = xml.find(&attribute).map(|i| i + attribute.len()) {
+        let end = xml[start..].find('"')?;
+        return Some(unescape(xml[start..start + end].trim()));
+    }
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&close)?;
+    Some(unescape(xml[start..start + end].trim()))
+}
+
+pub fn element_block<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let start = xml.find(&open)?;
+    let end = xml[start..].find(&close)?;
+    Some(&xml[start..start + end])
+}
+
+fn list_items(block: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut rest = block;
+    while let Some(start) = rest.find("<rdf:li") {
+        let Some(content_start) = rest[start..].find('>').map(|i| start + i + 1) else { break; };
+        let Some(end) = rest[content_start..].find("</rdf:li>") else { break; };
+        let item = unescape(rest[content_start..content_start + end].trim());
+        if !item.is_empty() {
+            items.push(item);
+        }
+        rest = &rest[content_start + end..];
+    }
+    items
+}
+
+fn parse_xmp(xml: &str) -> XmpData {
+    let rating = read_property(xml, "xmp:Rating").and_then(|r| r.parse::<f64>().ok()).map(|r| r.round() as i32);
+    let label = read_property(xml, "xmp:Label")
+        .map(|l| l.to_lowercase())
+        .filter(|l| COLOR_LABELS.contains(&l.as_str()))
+        .or_else(|| {
+            element_block(xml, "darktable:colorlabels")
+                .and_then(|block| list_items(block).into_iter().next())
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| COLOR_LABELS.get(index).map(|l| l.to_string()))
+        });
+    let keywords = element_block(xml, "dc:subject").map(list_items).unwrap_or_default();
+    XmpData { rating, label, keywords }
+}
+
+fn read_metadata(image_path: &str) -> ImageMetadata {
+    fs::read_to_string(get_sidecar_path(image_path))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// Fills in what RapidRAW doesn't know yet; ratings, flags and labels set in RapidRAW are kept.
+fn merge_into(metadata: &mut ImageMetadata, xmp: XmpData) -> bool {
+    let mut changed = false;
+
+    match xmp.rating {
+        Some(REJECTED_RATING) if metadata.flag.is_none() => {
+            metadata.flag = Some(ImageFlag::Reject);
+            changed = true;
+        }
+        Some(rating @ 1..=5) if metadata.rating == 0 => {
+            metadata.rating = rating as u8;
+            if !metadata.adjustments.is_object() {
+                metadata.adjustments = serde_json::json!({});
+            }
+            metadata.adjustments["rating"] = serde_json::json!(rating);
+            changed = true;
+        }
+        _ => {}
+    }
+
+    let mut tags = metadata.tags.take().unwrap_or_default();
+    if let Some(label) = xmp.label {
+        if !tags.iter().any(|t| t.starts_with(COLOR_TAG_PREFIX)) {
+            tags.push(format!("{}{}", COLOR_TAG_PREFIX, label));
+            changed = true;
+        }
+    }
+    for keyword in xmp.keywords {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(&keyword)) {
+            tags.push(keyword);
+            changed = true;
+        }
+    }
+    metadata.tags = if tags.is_empty() { None } else { Some(tags) };
+
+    changed
+}
+
+fn import_for_image(image_path: &str) -> Result<bool, String> {
+    let Some(xmp_path) = find_xmp_sidecar(image_path) else { return Ok(false); };
+    let xml = fs::read_to_string(&xmp_path).map_err(|e| e.to_string())?;
+    let mut metadata = read_metadata(image_path);
+    if !merge_into(&mut metadata, parse_xmp(&xml)) {
+        return Ok(false);
+    }
+    let json_string = serde_json::to_string_pretty(&metadata).map_err(|e| e.to_string())?;
+    fs::write(get_sidecar_path(image_path), json_string).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+fn remove_property(xml: &mut String, name: &str) {
+    let attribute = format!("{}=\"", name);
+    while let Some(name_start) = xml.find(&attribute) {
+        let value_start = name_start + attribute.len();
+        let Some(end) = xml[value_start..].find('"') else { break; };
+        let start = xml[..name_start].trim_end().len();
+        xml.replace_range(start..value_start + end + 1, "");
+    }
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    while let Some(start) = xml.find(&open) {
+        let Some(end) = xml[start..].find(&close) else { break; };
+        xml.replace_range(start..start + end + close.len(), "");
+    }
+}
+
+// xmp:Rating follows the Adobe convention: 0 unrated, 1-5 stars, -1 rejected.
+pub fn xmp_rating(metadata: &ImageMetadata) -> i32 {
+    if metadata.flag == Some(ImageFlag::Reject) {
+        REJECTED_RATING
+    } else {
+        metadata.rating.min(5) as i32
+    }
+}
+
+fn darktable_label_index(metadata: &ImageMetadata) -> Option<usize> {
+    metadata
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .find_map(|t| t.strip_prefix(COLOR_TAG_PREFIX))
+        .and_then(|l| COLOR_LABELS.iter().position(|label| *label == l))
+}
+
+// xmp:Label uses the default label set names shared by Lightroom and Bridge ("Red", "Yellow", ...).
+pub fn xmp_label(metadata: &ImageMetadata) -> Option<String> {
+    metadata
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .find_map(|t| t.strip_prefix(COLOR_TAG_PREFIX))
+        .filter(|l| COLOR_LABELS.contains(l))
+        .map(|l| {
+            let mut chars = l.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+}
+
+fn rapidraw_description(metadata: &ImageMetadata) -> String {
+    let rating = xmp_rating(metadata);
+    let label = xmp_label(metadata);
+    let darktable_label = darktable_label_index(metadata);
+    let keywords = export_keywords(metadata);
+
+    let mut description = format!(
+        "  <rdf:Description rdf:about=\"\"\n    xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n    xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n    xmlns:darktable=\"http://darktable.sf.net/\"\n    xmp:Rating=\"{}\"",
+        rating
+    );
+    if let Some(label) = label {
+        description.push_str(&format!("\n    xmp:Label=\"{}\"", escape(&label)));
+    }
+    if keywords.is_empty() && darktable_label.is_none() {
+        description.push_str("/>\n");
+        return description;
+    }
+    description.push_str(">\n");
+    if let Some(index) = darktable_label {
+        description.push_str(&format!(
+            "   <darktable:colorlabels>\n    <rdf:Seq>\n     <rdf:li>{}</rdf:li>\n    </rdf:Seq>\n   </darktable:colorlabels>\n",
+            index
+        ));
+    }
+    if !keywords.is_empty() {
+        description.push_str("   <dc:subject>\n    <rdf:Bag>\n");
+        for keyword in &keywords {
+            description.push_str(&format!("     <rdf:li>{}</rdf:li>\n", escape(keyword)));
+        }
+        description.push_str("    </rdf:Bag>\n   </dc:subject>\n");
+    }
+    description.push_str("  </rdf:Description>\n");
+    description
+}
+
+// Updates an existing sidecar in place so develop settings from other tools survive, or creates one
+// next to the image using the Lightroom naming for raws and the darktable naming otherwise.
+pub fn write_for_image(image_path: &str, metadata: &ImageMetadata) -> Result<(), String> {
+    let description = rapidraw_description(metadata);
+    let existing = find_xmp_sidecar(image_path);
+    let existing_xml = existing.as_ref().and_then(|p| fs::read_to_string(p).ok());
+
+    let (target, xml) = match (existing, existing_xml) {
+        (Some(path), Some(mut xml)) if xml.contains("</rdf:RDF>") => {
+            remove_property(&mut xml, "xmp:Rating");
+            remove_property(&mut xml, "xmp:Label");
+            remove_property(&mut xml, "dc:subject");
+            remove_property(&mut xml, "darktable:colorlabels");
+            let insert_at = xml.rfind("</rdf:RDF>").unwrap_or(xml.len());
+            xml.insert_str(insert_at, &description);
+            (path, xml)
+        }
+        (existing, _) => {
+            let path = existing.unwrap_or_else(|| {
+                let image = Path::new(image_path);
+                if is_raw_file(image_path) {
+                    image.with_extension("xmp")
+                } else {
+                    image.with_file_name(format!("{}.xmp", image.file_name().unwrap_or_default().to_string_lossy()))
+                }
+            });
+            let xml = format!(
+                "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\" x:xmptk=\"RapidRAW\">\n <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n{} </rdf:RDF>\n</x:xmpmeta>\n",
+                description
+            );
+            (path, xml)
+        }
+    };
+
+    fs::write(target, xml).map_err(|e| e.to_string())
+}
+
+// Called after ratings, labels, flags or tags change; a no-op unless write-back is enabled in settings.
+pub fn sync_if_enabled(app_handle: &AppHandle, paths: &[String]) {
+    let enabled = load_settings(app_handle.clone()).ok().and_then(|s| s.write_xmp_sidecars).unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    paths.par_iter().for_each(|path| {
+        if let Err(e) = write_for_image(path, &read_metadata(path)) {
+            eprintln!("Failed to write XMP sidecar for '{}': {}", path, e);
+        }
+    });
+}
+
+pub fn images_under(root_path: &str) -> Result<Vec<String>, String> {
+    if !Path::new(root_path).exists() {
+        return Err(format!("Root path does not exist: {}", root_path));
+    }
+    Ok(WalkDir::new(root_path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file())
+        .map(|e| e.path().to_string_lossy().into_owned())
+        .filter(|p| is_supported_image_file(p))
+        .collect())
+}
+
+#[tauri::command]
+pub fn import_xmp_sidecars(root_path: String) -> Result<usize, String> {
+    let images = images_under(&root_path)?;
+    Ok(images
+        .par_iter()
+        .filter(|path| match import_for_image(path) {
+            Ok(updated) => updated,
+            Err(e) => {
+                eprintln!("Failed to import XMP sidecar for '{}': {}", path, e);
+                false
+            }
+        })
+        .count())
+}
+
+#[tauri::command]
+pub fn write_xmp_sidecars(root_path: String) -> Result<usize, String> {
+    let images = images_under(&root_path)?;
+    Ok(images
+        .par_iter()
+        .filter(|path| get_sidecar_path(path).exists())
+        .filter(|path| match write_for_image(path, &read_metadata(path)) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Failed to write XMP sidecar for '{}': {}", path, e);
+                false
+            }
+        })
+        .count())
+}
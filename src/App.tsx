@@ -1,30 +1,42 @@
 import { useState, useEffect, useCallback, useRef, useMemo } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
-import { invoke } from '@tauri-apps/api/core';
+import { convertFileSrc, invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { open } from '@tauri-apps/plugin-dialog';
 import { homeDir } from '@tauri-apps/api/path';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import debounce from 'lodash.debounce';
 import clsx from 'clsx';
+import { v4 as uuidv4 } from 'uuid';
 import {
   Aperture,
   Check,
   ClipboardPaste,
   Copy,
   CopyPlus,
+  Ban,
+  Camera,
+  Crop as CropIcon,
   Edit,
   FileEdit,
+  GitBranch,
+  Flag,
+  Focus,
   Folder,
   FolderInput,
   FolderPlus,
   Images,
+  Layers,
   Redo,
   RotateCcw,
+  ScanEye,
+  ScanFace,
   Star,
+  Sun,
   Tag,
   Trash2,
   Undo,
+  Users,
   X,
 } from 'lucide-react';
 import TitleBar from './window/TitleBar';
@@ -42,14 +54,23 @@ import AIPanel from './components/panel/right/AIPanel';
 import ExportPanel from './components/panel/right/ExportPanel';
 import LibraryExportPanel from './components/panel/right/LibraryExportPanel';
 import MasksPanel from './components/panel/right/MasksPanel';
+import SpotRemovalPanel, { SpotSettings } from './components/panel/right/SpotRemovalPanel';
 import BottomBar from './components/panel/BottomBar';
 import { ContextMenuProvider, useContextMenu } from './context/ContextMenuContext';
 import CreateFolderModal from './components/modals/CreateFolderModal';
 import RenameFolderModal from './components/modals/RenameFolderModal';
 import ConfirmModal from './components/modals/ConfirmModal';
 import ImportSettingsModal from './components/modals/ImportSettingsModal';
+import CameraImportModal, { CameraFile } from './components/modals/CameraImportModal';
 import RenameFileModal from './components/modals/RenameFileModal';
-import PanoramaModal from './components/modals/PanoramaModal';
+import PanoramaModal, {
+  DEFAULT_PANORAMA_OPTIONS,
+  PanoramaOptions,
+  PanoramaOutputFormat,
+} from './components/modals/PanoramaModal';
+import MergeModal, { CLOSED_MERGE_MODAL_STATE, MergeModalState } from './components/modals/MergeModal';
+import { ControlPoint, ControlPointRequest } from './components/modals/ControlPointEditor';
+import PeopleModal from './components/modals/PeopleModal';
 import { useHistoryState } from './hooks/useHistoryState';
 import Resizer from './components/ui/Resizer';
 import {
@@ -60,13 +81,16 @@ import {
   Coord,
   COPYABLE_ADJUSTMENT_KEYS,
   INITIAL_ADJUSTMENTS,
+  INITIAL_MASK_ADJUSTMENTS,
+  INITIAL_MASK_CONTAINER,
   MaskContainer,
   normalizeLoadedAdjustments,
+  SpotEditType,
 } from './utils/adjustments';
 import { generatePaletteFromImage } from './utils/palette';
 import { useKeyboardShortcuts } from './hooks/useKeyboardShortcuts';
 import { THEMES, DEFAULT_THEME_ID, ThemeProps } from './utils/themes';
-import { SubMask, ToolType } from './components/panel/right/Masks';
+import { AiMaskCandidates, Mask, SamPoint, SubMask, SubMaskMode, ToolType } from './components/panel/right/Masks';
 import {
   EXPORT_TIMEOUT,
   ExportState,
@@ -77,9 +101,16 @@ import {
 import {
   AppSettings,
   BrushSettings,
+  CompareLayout,
+  EditVersionsInfo,
   FilterCriteria,
+  FlagStatus,
+  hasExifFilter,
+  InpaintMode,
   Invokes,
   ImageFile,
+  ImageFlag,
+  ModelSelection,
   Option,
   OPTION_SEPARATOR,
   Panel,
@@ -116,6 +147,12 @@ interface ConfirmModalState {
   title?: string;
 }
 
+interface CopiedMasks {
+  height: number;
+  masks: Array<MaskContainer>;
+  width: number;
+}
+
 interface Metadata {
   adjustments: Adjustments;
   rating: number;
@@ -130,15 +167,26 @@ interface MultiSelectOptions {
 }
 
 interface PanoramaModalState {
+  controlPointRequest: ControlPointRequest | null;
+  controlPoints: Array<ControlPoint> | null;
   error: string | null;
   finalImageBase64: string | null;
   isOpen: boolean;
+  options: PanoramaOptions;
   progressMessage: string | null;
   stitchingSourcePaths: Array<string>;
 }
 
 const DEBUG = true;
 const REVOCATION_DELAY = 5000;
+const PREVIEW_PROTOCOL = 'preview';
+const SMART_CROP_RATIOS = [
+  { label: '1:1', value: 1 },
+  { label: '4:5', value: 4 / 5 },
+  { label: '9:16', value: 9 / 16 },
+  { label: '16:9', value: 16 / 9 },
+  { label: '3:2', value: 3 / 2 },
+];
 
 const useDelayedRevokeBlobUrl = (url: string | null | undefined) => {
   const previousUrlRef = useRef<string | null | undefined>(null);
@@ -180,12 +228,17 @@ function App() {
     rating: 0,
     rawStatus: RawStatus.All,
   });
+  const [exifFilteredPaths, setExifFilteredPaths] = useState<Set<string> | null>(null);
+  const [semanticSearchPaths, setSemanticSearchPaths] = useState<Set<string> | null>(null);
+  const [editVersions, setEditVersions] = useState<EditVersionsInfo | null>(null);
   const [supportedTypes, setSupportedTypes] = useState<SupportedTypes | null>(null);
   const [selectedImage, setSelectedImage] = useState<SelectedImage | null>(null);
   const [multiSelectedPaths, setMultiSelectedPaths] = useState<Array<string>>([]);
   const [libraryActivePath, setLibraryActivePath] = useState<string | null>(null);
   const [libraryActiveAdjustments, setLibraryActiveAdjustments] = useState<Adjustments>(INITIAL_ADJUSTMENTS);
   const [finalPreviewUrl, setFinalPreviewUrl] = useState<string | null>(null);
+  const [clippingOverlayUrl, setClippingOverlayUrl] = useState<string | null>(null);
+  const [comparisonUrl, setComparisonUrl] = useState<string | null>(null);
   const [uncroppedAdjustedPreviewUrl, setUncroppedAdjustedPreviewUrl] = useState<string | null>(null);
   const {
     state: historyAdjustments,
@@ -198,6 +251,9 @@ function App() {
   } = useHistoryState(INITIAL_ADJUSTMENTS);
   const [adjustments, setLiveAdjustments] = useState<Adjustments>(INITIAL_ADJUSTMENTS);
   const [showOriginal, setShowOriginal] = useState(false);
+  const [showClipping, setShowClipping] = useState(false);
+  const [compareLayout, setCompareLayout] = useState<CompareLayout | null>(null);
+  const [compareSnapshot, setCompareSnapshot] = useState<Adjustments | null>(null);
   const [isTreeLoading, setIsTreeLoading] = useState(false);
   const [isViewLoading, setIsViewLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
@@ -209,6 +265,8 @@ function App() {
     filmstrip: true,
   });
   const [isAdjusting, setIsAdjusting] = useState(false);
+  const [denoisedPath, setDenoisedPath] = useState<string | null>(null);
+  const denoisingPathRef = useRef<string | null>(null);
   const [isFullScreen, setIsFullScreen] = useState(false);
   const [isFullScreenLoading, setIsFullScreenLoading] = useState(false);
   const [fullScreenUrl, setFullScreenUrl] = useState<string | null>(null);
@@ -227,11 +285,7 @@ function App() {
   const [baseRenderSize, setBaseRenderSize] = useState<ImageDimensions>({ width: 0, height: 0 });
   const [originalSize, setOriginalSize] = useState<ImageDimensions>({ width: 0, height: 0 });
   const [isFullResolution, setIsFullResolution] = useState(false);
-  const [fullResolutionUrl, setFullResolutionUrl] = useState<string | null>(null);
-  const [isLoadingFullRes, setIsLoadingFullRes] = useState(false);
   const [transformedOriginalUrl, setTransformedOriginalUrl] = useState<string | null>(null);
-  const fullResRequestRef = useRef<any>(null);
-  const fullResCacheKeyRef = useRef<string | null>(null);
 
   useDelayedRevokeBlobUrl(finalPreviewUrl);
   useDelayedRevokeBlobUrl(uncroppedAdjustedPreviewUrl);
@@ -266,7 +320,9 @@ function App() {
   const [thumbnailSize, setThumbnailSize] = useState(ThumbnailSize.Medium);
   const [thumbnailAspectRatio, setThumbnailAspectRatio] = useState(ThumbnailAspectRatio.Cover);
   const [copiedAdjustments, setCopiedAdjustments] = useState<Adjustments | null>(null);
+  const [copiedMasks, setCopiedMasks] = useState<CopiedMasks | null>(null);
   const [isStraightenActive, setIsStraightenActive] = useState(false);
+  const [isWbPickerActive, setIsWbPickerActive] = useState(false);
   const [copiedFilePaths, setCopiedFilePaths] = useState<Array<string>>([]);
   const [aiModelDownloadStatus, setAiModelDownloadStatus] = useState<string | null>(null);
   const [copiedSectionAdjustments, setCopiedSectionAdjustments] = useState(null);
@@ -281,24 +337,39 @@ function App() {
     feather: 50,
     tool: ToolType.Brush,
   });
+  const [spotSettings, setSpotSettings] = useState<SpotSettings>({
+    feather: 50,
+    opacity: 100,
+    size: 40,
+    type: SpotEditType.Heal,
+  });
   const [isCreateFolderModalOpen, setIsCreateFolderModalOpen] = useState(false);
   const [isRenameFolderModalOpen, setIsRenameFolderModalOpen] = useState(false);
   const [isRenameFileModalOpen, setIsRenameFileModalOpen] = useState(false);
   const [renameTargetPaths, setRenameTargetPaths] = useState<Array<string>>([]);
   const [isImportModalOpen, setIsImportModalOpen] = useState(false);
+  const [isPeopleModalOpen, setIsPeopleModalOpen] = useState(false);
   const [importTargetFolder, setImportTargetFolder] = useState<string | null>(null);
   const [importSourcePaths, setImportSourcePaths] = useState<Array<string>>([]);
+  const [isCameraImportModalOpen, setIsCameraImportModalOpen] = useState(false);
+  const [cameraImport, setCameraImport] = useState<{ files: Array<CameraFile>; port: string } | null>(null);
   const [folderActionTarget, setFolderActionTarget] = useState<string | null>(null);
   const [confirmModalState, setConfirmModalState] = useState<ConfirmModalState>({ isOpen: false });
   const [panoramaModalState, setPanoramaModalState] = useState<PanoramaModalState>({
+    controlPointRequest: null,
+    controlPoints: null,
     error: null,
     finalImageBase64: null,
     isOpen: false,
+    options: DEFAULT_PANORAMA_OPTIONS,
     progressMessage: '',
     stitchingSourcePaths: [],
   });
+  const [focusStackModalState, setFocusStackModalState] = useState<MergeModalState>(CLOSED_MERGE_MODAL_STATE);
+  const [hdrModalState, setHdrModalState] = useState<MergeModalState>(CLOSED_MERGE_MODAL_STATE);
   const [customEscapeHandler, setCustomEscapeHandler] = useState(null);
   const [isGeneratingAiMask, setIsGeneratingAiMask] = useState(false);
+  const [aiMaskCandidates, setAiMaskCandidates] = useState<AiMaskCandidates | null>(null);
   const [isComfyUiConnected, setIsComfyUiConnected] = useState(false);
   const [isGeneratingAi, setIsGeneratingAi] = useState(false);
   const [isMaskControlHovered, setIsMaskControlHovered] = useState(false);
@@ -329,6 +400,14 @@ function App() {
     currentFolderPathRef.current = currentFolderPath;
   }, [currentFolderPath]);
 
+  useEffect(() => {
+    if (currentFolderPath) {
+      invoke(Invokes.WatchFolder, { path: currentFolderPath }).catch((err) => console.error('Failed to watch folder:', err));
+    } else {
+      invoke(Invokes.UnwatchFolder).catch((err) => console.error('Failed to stop watching folder:', err));
+    }
+  }, [currentFolderPath]);
+
   useEffect(() => {
     if (!isCopied) {
       return;
@@ -372,6 +451,28 @@ function App() {
     [setAdjustments],
   );
 
+  const handleWhiteBalancePick = useCallback(
+    async (x: number, y: number) => {
+      setIsWbPickerActive(false);
+      try {
+        const { temperature, tint }: any = await invoke(Invokes.SetWhiteBalanceFromPoint, { x, y });
+        setAdjustments((prev: Partial<Adjustments>) => ({
+          ...prev,
+          temperature: Math.round(temperature),
+          tint: Math.round(tint),
+        }));
+      } catch (err) {
+        console.error('Failed to sample white balance:', err);
+        setError(`Failed to sample white balance: ${err}`);
+      }
+    },
+    [setAdjustments],
+  );
+
+  useEffect(() => {
+    setIsWbPickerActive(false);
+  }, [selectedImage?.path, renderedRightPanel]);
+
   useEffect(() => {
     setLiveAdjustments(historyAdjustments);
   }, [historyAdjustments]);
@@ -397,24 +498,38 @@ function App() {
     adjustments?.orientationSteps,
   ]);
 
-  const visualAdjustmentsKey = useMemo(() => {
-    if (!adjustments) return '';
-    const { rating, sectionVisibility, ...visualAdjustments } = adjustments;
-    return JSON.stringify(visualAdjustments);
-  }, [adjustments]);
+  const stepPersistentHistory = useCallback(
+    (command: Invokes) => {
+      if (!selectedImage?.path) {
+        return;
+      }
+      invoke(command, { path: selectedImage.path })
+        .then((restored: any) => {
+          const normalized = normalizeLoadedAdjustments(restored);
+          setLiveAdjustments(normalized);
+          resetAdjustmentsHistory(normalized);
+        })
+        .catch((err) => console.log('No persistent edit history step available:', err));
+    },
+    [selectedImage?.path, resetAdjustmentsHistory],
+  );
 
   const undo = useCallback(() => {
     if (canUndo) {
       undoAdjustments();
       debouncedSetHistory.cancel();
+    } else {
+      stepPersistentHistory(Invokes.UndoEdit);
     }
-  }, [canUndo, undoAdjustments, debouncedSetHistory]);
+  }, [canUndo, undoAdjustments, debouncedSetHistory, stepPersistentHistory]);
   const redo = useCallback(() => {
     if (canRedo) {
       redoAdjustments();
       debouncedSetHistory.cancel();
+    } else {
+      stepPersistentHistory(Invokes.RedoEdit);
     }
-  }, [canRedo, redoAdjustments, debouncedSetHistory]);
+  }, [canRedo, redoAdjustments, debouncedSetHistory, stepPersistentHistory]);
 
   useEffect(() => {
     setTransformedOriginalUrl(null);
@@ -456,11 +571,18 @@ function App() {
     const unlisten = listen('comfyui-status-update', (event: any) => {
       setIsComfyUiConnected(event.payload.connected);
     });
+    const unlistenError = listen('comfyui-error', (event: any) => {
+      console.error(`ComfyUI error (${event.payload.kind}):`, event.payload.message);
+      if (event.payload.kind === 'unreachable') {
+        setIsComfyUiConnected(false);
+      }
+    });
     invoke(Invokes.CheckComfyuiStatus);
     const interval = setInterval(() => invoke(Invokes.CheckComfyuiStatus), 3000);
     return () => {
       clearInterval(interval);
       unlisten.then((f) => f());
+      unlistenError.then((f) => f());
     };
   }, []);
 
@@ -479,7 +601,7 @@ function App() {
   };
 
   const handleGenerativeReplace = useCallback(
-    async (patchId: string, prompt: string, useFastInpaint: boolean) => {
+    async (patchId: string, prompt: string, inpaintMode: InpaintMode, models?: ModelSelection) => {
       if (!selectedImage?.path || isGeneratingAi) {
         return;
       }
@@ -500,23 +622,34 @@ function App() {
       setIsGeneratingAi(true);
 
       try {
-        const newPatchDataJson: any = await invoke(Invokes.InvokeGenerativeReplaseWithMaskDef, {
-          currentAdjustments: adjustments,
-          patchDefinition: patchDefinition,
-          path: selectedImage.path,
-          useFastInpaint: useFastInpaint,
-        });
-
-        const newPatchData = JSON.parse(newPatchDataJson);
+        const newPatchDataJson: any = patch.transform
+          ? await invoke(Invokes.InvokeImageTransform, {
+              currentAdjustments: adjustments,
+              patchDefinition: patchDefinition,
+              checkpoint: models?.checkpoint || null,
+              loras: models?.loras || [],
+            })
+          : await invoke(Invokes.InvokeGenerativeReplaseWithMaskDef, {
+              currentAdjustments: adjustments,
+              patchDefinition: patchDefinition,
+              path: selectedImage.path,
+              inpaintMode,
+              checkpoint: models?.checkpoint || null,
+              loras: models?.loras || [],
+            });
+
+        const { seed, ...newPatchData } = JSON.parse(newPatchDataJson);
         setAdjustments((prev: Adjustments) => ({
           ...prev,
           aiPatches: prev.aiPatches.map((p: AiPatch) =>
             p.id === patchId
               ? {
                   ...p,
+                  generation: seed != null ? { ...p.generation, seed } : p.generation,
                   patchData: newPatchData,
                   isLoading: false,
-                  name: useFastInpaint ? 'Inpaint' : prompt && prompt.trim() ? prompt.trim() : p.name,
+                  name:
+                    inpaintMode !== InpaintMode.ComfyUi ? 'Inpaint' : prompt && prompt.trim() ? prompt.trim() : p.name,
                 }
               : p,
           ),
@@ -589,10 +722,10 @@ function App() {
           currentAdjustments: updatedAdjustmentsForBackend,
           patchDefinition: { ...patchDefinitionForBackend, prompt: '' },
           path: selectedImage.path,
-          useFastInpaint: true,
+          inpaintMode: InpaintMode.Fast,
         });
 
-        const newPatchData = JSON.parse(newPatchDataJson);
+        const { seed: _seed, ...newPatchData } = JSON.parse(newPatchDataJson);
         if (!newPatchData?.color || !newPatchData?.mask) {
           throw new Error('Inpainting failed to return a valid result.');
         }
@@ -649,6 +782,39 @@ function App() {
     [setAdjustments, activeAiPatchContainerId],
   );
 
+  const handleConvertAiPatchToMask = useCallback(
+    async (patchId: string) => {
+      const patch = adjustments.aiPatches?.find((p: AiPatch) => p.id === patchId);
+      if (!patch) {
+        return;
+      }
+      try {
+        const newMask: MaskContainer = await invoke(Invokes.CreateMaskFromAiPatch, {
+          flipHorizontal: adjustments.flipHorizontal,
+          flipVertical: adjustments.flipVertical,
+          orientationSteps: adjustments.orientationSteps,
+          patchDefinition: patch,
+          rotation: adjustments.rotation,
+        });
+        setAdjustments((prev: Adjustments) => ({
+          ...prev,
+          masks: [...(prev.masks || []), { ...INITIAL_MASK_CONTAINER, ...newMask, adjustments: INITIAL_MASK_ADJUSTMENTS }],
+        }));
+      } catch (err) {
+        console.error('Failed to convert AI patch to mask:', err);
+        setError(`Failed to convert AI patch to mask: ${err}`);
+      }
+    },
+    [
+      adjustments.aiPatches,
+      adjustments.flipHorizontal,
+      adjustments.flipVertical,
+      adjustments.orientationSteps,
+      adjustments.rotation,
+      setAdjustments,
+    ],
+  );
+
   const handleToggleAiPatchVisibility = useCallback(
     (patchId: string) => {
       setAdjustments((prev: Adjustments) => ({
@@ -659,23 +825,34 @@ function App() {
     [setAdjustments],
   );
 
-  const handleGenerateAiMask = async (subMaskId: string, startPoint: Coord, endPoint: Coord) => {
+  const handleGenerateAiMask = async (
+    subMaskId: string,
+    startPoint: Coord,
+    endPoint: Coord,
+    points: Array<SamPoint> = [],
+  ) => {
     if (!selectedImage?.path) {
       console.error('Cannot generate AI mask: No image selected.');
       return;
     }
+    const refineFrom =
+      points.length > 0 && aiMaskCandidates?.subMaskId === subMaskId ? aiMaskCandidates.selected : null;
     setIsGeneratingAiMask(true);
     try {
-      const newParameters = await invoke(Invokes.GenerateAiSubjectMask, {
+      const { candidates, ...newParameters }: any = await invoke(Invokes.GenerateAiSubjectMask, {
         endPoint: [endPoint.x, endPoint.y],
         flipHorizontal: adjustments.flipHorizontal,
         flipVertical: adjustments.flipVertical,
         orientationSteps: adjustments.orientationSteps,
         path: selectedImage.path,
+        points,
+        refineFrom,
         rotation: adjustments.rotation,
         startPoint: [startPoint.x, startPoint.y],
+        subMaskId,
       });
 
+      setAiMaskCandidates(candidates?.length > 1 ? { candidates, selected: 0, subMaskId } : null);
       updateSubMask(subMaskId, { parameters: newParameters });
     } catch (error) {
       console.error('Failed to generate AI subject mask:', error);
@@ -685,6 +862,23 @@ function App() {
     }
   };
 
+  const handleSelectAiMaskCandidate = (index: number) => {
+    if (!aiMaskCandidates) {
+      return;
+    }
+    const { candidates, subMaskId } = aiMaskCandidates;
+    const subMask = adjustments.masks
+      .flatMap((c: MaskContainer) => c.subMasks)
+      .find((sm: SubMask) => sm.id === subMaskId);
+    if (!subMask || !candidates[index]) {
+      return;
+    }
+    setAiMaskCandidates({ ...aiMaskCandidates, selected: index });
+    updateSubMask(subMaskId, {
+      parameters: { ...subMask.parameters, maskDataBase64: candidates[index].maskDataBase64 },
+    });
+  };
+
   const handleGenerateAiForegroundMask = async (subMaskId: string) => {
     if (!selectedImage?.path) {
       console.error('Cannot generate AI mask: No image selected.');
@@ -731,8 +925,113 @@ function App() {
     }
   };
 
+  const handleGenerateAiFaceMasks = async (subMaskId: string) => {
+    if (!selectedImage?.path) {
+      console.error('Cannot generate AI mask: No image selected.');
+      return;
+    }
+    setIsGeneratingAiMask(true);
+    try {
+      const faceParameters: Array<any> = await invoke(Invokes.GenerateAiFaceMasks, {
+        flipHorizontal: adjustments.flipHorizontal,
+        flipVertical: adjustments.flipVertical,
+        orientationSteps: adjustments.orientationSteps,
+        refineSkin: false,
+        rotation: adjustments.rotation,
+      });
+      const [firstFace, ...otherFaces] = faceParameters;
+
+      setAdjustments((prev: Adjustments) => {
+        const masks = prev.masks.map((c: MaskContainer) => ({
+          ...c,
+          subMasks: c.subMasks.map((sm: SubMask) =>
+            sm.id === subMaskId ? { ...sm, parameters: { ...sm.parameters, ...firstFace } } : sm,
+          ),
+        }));
+        const extraContainers = otherFaces.map((parameters: any, index: number) => ({
+          ...INITIAL_MASK_CONTAINER,
+          id: uuidv4(),
+          name: `Face ${index + 2}`,
+          subMasks: [
+            {
+              id: uuidv4(),
+              mode: SubMaskMode.Additive,
+              parameters: { grow: 0, feather: 0, ...parameters },
+              type: Mask.AiFace,
+              visible: true,
+            },
+          ],
+        }));
+        return { ...prev, masks: [...masks, ...extraContainers] };
+      });
+    } catch (error) {
+      console.error('Failed to generate AI face masks:', error);
+      setError(`AI Mask Failed: ${error}`);
+    } finally {
+      setIsGeneratingAiMask(false);
+    }
+  };
+
+  useEffect(() => {
+    if (!currentFolderPath || !hasExifFilter(filterCriteria)) {
+      setExifFilteredPaths(null);
+      return;
+    }
+    let isCancelled = false;
+    invoke(Invokes.FilterImages, { folder: currentFolderPath, criteria: filterCriteria })
+      .then((paths: any) => {
+        if (!isCancelled) {
+          setExifFilteredPaths(new Set(paths));
+        }
+      })
+      .catch((err) => {
+        console.error('Failed to apply EXIF filters:', err);
+        if (!isCancelled) {
+          setExifFilteredPaths(null);
+        }
+      });
+    return () => {
+      isCancelled = true;
+    };
+  }, [currentFolderPath, filterCriteria, imageList]);
+
+  useEffect(() => {
+    const query = searchQuery.trim();
+    if (!currentFolderPath || !appSettings?.enableAiTagging || query.length < 3) {
+      setSemanticSearchPaths(null);
+      return;
+    }
+    let isCancelled = false;
+    const timeoutId = setTimeout(() => {
+      invoke(Invokes.SearchByText, { query, folder: currentFolderPath })
+        .then((matches: any) => {
+          if (!isCancelled) {
+            setSemanticSearchPaths(new Set(matches.map((match: any) => match.path)));
+          }
+        })
+        .catch((err) => {
+          console.error('Semantic search failed:', err);
+          if (!isCancelled) {
+            setSemanticSearchPaths(null);
+          }
+        });
+    }, 400);
+    return () => {
+      isCancelled = true;
+      clearTimeout(timeoutId);
+    };
+  }, [currentFolderPath, searchQuery, appSettings?.enableAiTagging]);
+
   const sortedImageList = useMemo(() => {
     const filteredList = imageList.filter((image) => {
+      if (exifFilteredPaths && !exifFilteredPaths.has(image.path)) {
+        return false;
+      }
+
+      if (image.stack?.collapsed && image.stack.top_path !== image.path) {
+        return false;
+      }
+
       if (filterCriteria.rating > 0) {
         const rating = imageRatings[image.path] || 0;
         if (filterCriteria.rating === 5) {
@@ -754,6 +1053,19 @@ function App() {
         }
       }
 
+      if (filterCriteria.flagStatus && filterCriteria.flagStatus !== FlagStatus.All) {
+        const flag = image.flag || null;
+        if (filterCriteria.flagStatus === FlagStatus.Picked && flag !== ImageFlag.Pick) {
+          return false;
+        }
+        if (filterCriteria.flagStatus === FlagStatus.Rejected && flag !== ImageFlag.Reject) {
+          return false;
+        }
+        if (filterCriteria.flagStatus === FlagStatus.Unflagged && flag !== null) {
+          return false;
+        }
+      }
+
       if (filterCriteria.colors && filterCriteria.colors.length > 0) {
         const imageColor = (image.tags || []).find((tag: string) => tag.startsWith('color:'))?.substring(6);
 
@@ -783,6 +1095,9 @@ function App() {
               if (image.tags && image.tags.some((tag: string) => tag.toLowerCase().includes(query))) {
                 return true;
               }
+              if (semanticSearchPaths?.has(image.path)) {
+                return true;
+              }
             }
 
             return false;
@@ -803,7 +1118,17 @@ function App() {
       return order === SortDirection.Ascending ? comparison : -comparison;
     });
     return list;
-  }, [imageList, sortCriteria, imageRatings, filterCriteria, supportedTypes, searchQuery, appSettings]);
+  }, [
+    imageList,
+    sortCriteria,
+    imageRatings,
+    filterCriteria,
+    exifFilteredPaths,
+    supportedTypes,
+    searchQuery,
+    semanticSearchPaths,
+    appSettings,
+  ]);
 
   const applyAdjustments = useCallback(
     debounce((currentAdjustments) => {
@@ -812,15 +1137,56 @@ function App() {
       }
       setIsAdjusting(true);
       setError(null);
-      invoke(Invokes.ApplyAdjustments, { jsAdjustments: currentAdjustments }).catch((err) => {
+      invoke(Invokes.ApplyAdjustments, { jsAdjustments: currentAdjustments, showClipping }).catch((err) => {
         console.error('Failed to invoke apply_adjustments:', err);
         setError(`Processing failed: ${err}`);
         setIsAdjusting(false);
       });
     }, 50),
-    [selectedImage?.isReady],
+    [selectedImage?.isReady, showClipping],
   );
 
+  useEffect(() => {
+    if (!showClipping) {
+      setClippingOverlayUrl(null);
+    }
+  }, [showClipping]);
+
+  const generateComparison = useCallback(
+    debounce((currentAdjustments, snapshot, layout) => {
+      invoke(Invokes.GenerateComparison, {
+        compareAdjustments: snapshot,
+        jsAdjustments: currentAdjustments,
+        layout,
+      }).catch((err) => console.error('Failed to generate comparison:', err));
+    }, 100),
+    [],
+  );
+
+  const handleCycleCompareLayout = useCallback(() => {
+    if (compareLayout === null) {
+      setCompareSnapshot(adjustments);
+      setCompareLayout(CompareLayout.Split);
+    } else if (compareLayout === CompareLayout.Split) {
+      setCompareLayout(CompareLayout.SideBySide);
+    } else {
+      setCompareLayout(null);
+    }
+  }, [adjustments, compareLayout]);
+
+  useEffect(() => {
+    setCompareLayout(null);
+  }, [selectedImage?.path]);
+
+  useEffect(() => {
+    if (!compareLayout || !compareSnapshot || !selectedImage?.isReady) {
+      setComparisonUrl(null);
+      return;
+    }
+    generateComparison(adjustments, compareSnapshot, compareLayout);
+    return () => generateComparison.cancel();
+  }, [adjustments, compareLayout, compareSnapshot, selectedImage?.isReady, generateComparison]);
+
   const debouncedGenerateUncroppedPreview = useCallback(
     debounce((currentAdjustments) => {
       if (!selectedImage?.isReady) {
@@ -1104,6 +1470,9 @@ function App() {
         if (isNewRoot) {
           setIsTreeLoading(true);
           handleSettingsChange({ ...appSettings, lastRootPath: path } as AppSettings);
+          invoke(Invokes.SyncCatalog, { rootPath: path }).catch((err) => {
+            console.error('Failed to sync library catalog:', err);
+          });
           try {
             const treeData = await invoke(Invokes.GetFolderTree, { path });
             setFolderTree(treeData);
@@ -1122,6 +1491,7 @@ function App() {
         if (selectedImage) {
           setSelectedImage(null);
           setFinalPreviewUrl(null);
+          setClippingOverlayUrl(null);
           setUncroppedAdjustedPreviewUrl(null);
           setHistogram(null);
         }
@@ -1184,6 +1554,7 @@ function App() {
     const lastActivePath = selectedImage?.path ?? null;
     setSelectedImage(null);
     setFinalPreviewUrl(null);
+    setClippingOverlayUrl(null);
     setUncroppedAdjustedPreviewUrl(null);
     setHistogram(null);
     setWaveform(null);
@@ -1286,6 +1657,33 @@ function App() {
     setIsCopied(true);
   }, [selectedImage, adjustments, libraryActiveAdjustments]);
 
+  const handleCopyMasks = useCallback(() => {
+    if (!selectedImage || adjustments.masks.length === 0) {
+      return;
+    }
+    setCopiedMasks({ height: selectedImage.height, masks: adjustments.masks, width: selectedImage.width });
+    setIsCopied(true);
+  }, [selectedImage, adjustments.masks]);
+
+  const handlePasteMasks = useCallback(
+    (paths: Array<string>) => {
+      if (!copiedMasks || paths.length === 0) {
+        return;
+      }
+      invoke(Invokes.CopyMasksToPaths, {
+        masks: copiedMasks.masks,
+        paths,
+        sourceHeight: copiedMasks.height,
+        sourceWidth: copiedMasks.width,
+      }).catch((err) => {
+        console.error('Failed to paste masks:', err);
+        setError(`Failed to paste masks: ${err}`);
+      });
+      setIsPasted(true);
+    },
+    [copiedMasks],
+  );
+
   const handlePasteAdjustments = useCallback(
     (paths?: Array<string>) => {
       if (!copiedAdjustments) {
@@ -1418,6 +1816,122 @@ function App() {
     [multiSelectedPaths, selectedImage, libraryActivePath, imageList],
   );
 
+  const handleSetFlag = useCallback(
+    async (flag: ImageFlag | null, paths?: Array<string>) => {
+      const pathsToUpdate =
+        paths || (multiSelectedPaths.length > 0 ? multiSelectedPaths : selectedImage ? [selectedImage.path] : []);
+      if (pathsToUpdate.length === 0) {
+        return;
+      }
+      const primaryPath = selectedImage?.path || libraryActivePath;
+      const currentFlag = imageList.find((img: ImageFile) => img.path === primaryPath)?.flag || null;
+      const finalFlag = flag !== null && flag === currentFlag ? null : flag;
+      try {
+        await invoke(Invokes.SetFlagForPaths, { paths: pathsToUpdate, flag: finalFlag });
+
+        setImageList((prevList: Array<ImageFile>) =>
+          prevList.map((image: ImageFile) =>
+            pathsToUpdate.includes(image.path) ? { ...image, flag: finalFlag } : image,
+          ),
+        );
+      } catch (err) {
+        console.error('Failed to set flag:', err);
+        setError(`Failed to set flag: ${err}`);
+      }
+    },
+    [multiSelectedPaths, selectedImage, libraryActivePath, imageList],
+  );
+
+  const handleAnalyzeForCulling = useCallback(async (paths: Array<string>) => {
+    if (paths.length === 0) {
+      return;
+    }
+    try {
+      await invoke(Invokes.AnalyzeForCulling, { paths, flagRejects: true });
+    } catch (err) {
+      console.error('Failed to start culling analysis:', err);
+      setError(`Failed to analyze images: ${err}`);
+    }
+  }, []);
+
+  const handleSetStackCollapsed = useCallback(async (stackId: string, collapsed: boolean) => {
+    try {
+      await invoke(Invokes.SetStackCollapsed, { stackId, collapsed });
+      setImageList((prevList: Array<ImageFile>) =>
+        prevList.map((image: ImageFile) =>
+          image.stack?.id === stackId ? { ...image, stack: { ...image.stack, collapsed } } : image,
+        ),
+      );
+    } catch (err) {
+      console.error('Failed to update stack:', err);
+      setError(`Failed to update stack: ${err}`);
+    }
+  }, []);
+
+  const handleSetStackTop = useCallback(async (stackId: string, path: string) => {
+    try {
+      await invoke(Invokes.SetStackTop, { stackId, path });
+      setImageList((prevList: Array<ImageFile>) =>
+        prevList.map((image: ImageFile) =>
+          image.stack?.id === stackId ? { ...image, stack: { ...image.stack, top_path: path } } : image,
+        ),
+      );
+    } catch (err) {
+      console.error('Failed to set stack top:', err);
+      setError(`Failed to set stack top: ${err}`);
+    }
+  }, []);
+
+  useEffect(() => {
+    if (!selectedImage?.path) {
+      setEditVersions(null);
+      return;
+    }
+    invoke(Invokes.ListEditVersions, { path: selectedImage.path })
+      .then((info: any) => setEditVersions(info))
+      .catch((err) => console.error('Failed to load edit versions:', err));
+  }, [selectedImage?.path]);
+
+  const handleEditVersionAction = useCallback(
+    async (command: Invokes, args: Record<string, any> = {}) => {
+      if (!selectedImage?.path) {
+        return;
+      }
+      const path = selectedImage.path;
+      try {
+        debouncedSave.flush();
+        const info: EditVersionsInfo = await invoke(command, { path, ...args });
+        setEditVersions(info);
+        const metadata: any = await invoke(Invokes.LoadMetadata, { path });
+        if (metadata.adjustments && !metadata.adjustments.is_null) {
+          const normalized = normalizeLoadedAdjustments(metadata.adjustments);
+          setLiveAdjustments(normalized);
+          resetAdjustmentsHistory(normalized);
+        }
+      } catch (err) {
+        console.error('Failed to update edit versions:', err);
+        setError(`Failed to update edit versions: ${err}`);
+      }
+    },
+    [selectedImage?.path, debouncedSave, resetAdjustmentsHistory],
+  );
+
+  const handleDeleteRejectedInFolder = useCallback(
+    async (folder: string) => {
+      try {
+        await invoke(Invokes.DeleteFilesFromDisk, { paths: [], rejectedInFolder: folder });
+        if (folder === currentFolderPath) {
+          setMultiSelectedPaths([]);
+          handleLibraryRefresh();
+        }
+      } catch (err) {
+        console.error('Failed to delete rejected images:', err);
+        setError(`Failed to delete rejected images: ${err}`);
+      }
+    },
+    [currentFolderPath, handleLibraryRefresh],
+  );
+
   const closeConfirmModal = () => setConfirmModalState({ ...confirmModalState, isOpen: false });
 
   const handlePasteFiles = useCallback(
@@ -1440,98 +1954,17 @@ function App() {
     [copiedFilePaths, currentFolderPath, handleLibraryRefresh],
   );
 
-  const requestFullResolution = useCallback(
-    debounce((currentAdjustments: any, key: string) => {
-      if (!selectedImage?.path) return;
-
-      if (fullResRequestRef.current) {
-        fullResRequestRef.current.cancelled = true;
-      }
-
-      const request = { cancelled: false };
-      fullResRequestRef.current = request;
-
-      invoke(Invokes.GenerateFullscreenPreview, {
-        jsAdjustments: currentAdjustments,
-      })
-        .then((imageData: Uint8Array) => {
-          if (!request.cancelled) {
-            const blob = new Blob([imageData], { type: 'image/jpeg' });
-            const url = URL.createObjectURL(blob);
-            setFullResolutionUrl(url);
-            fullResCacheKeyRef.current = key;
-            setIsFullResolution(true);
-            setIsLoadingFullRes(false);
-          }
-        })
-        .catch((error: any) => {
-          if (!request.cancelled) {
-            console.error('Failed to generate full resolution preview:', error);
-            setIsFullResolution(false);
-            setFullResolutionUrl(null);
-            fullResCacheKeyRef.current = null;
-            setIsLoadingFullRes(false);
-          }
-        });
-    }, 300),
-    [selectedImage?.path],
-  );
-
-  useEffect(() => {
-    if (isFullResolution && selectedImage?.path) {
-      if (fullResCacheKeyRef.current !== visualAdjustmentsKey) {
-        setIsLoadingFullRes(true);
-        requestFullResolution(adjustments, visualAdjustmentsKey);
-      }
-    }
-  }, [adjustments, isFullResolution, selectedImage?.path, requestFullResolution, visualAdjustmentsKey]);
-
   const handleFullResolutionLogic = useCallback(
-    (targetZoomPercent: number, currentDisplayWidth: number) => {
+    (targetZoomPercent: number) => {
       if (!initialFitScale) {
         return;
       }
       const highResThreshold = Math.max(initialFitScale * 2, 0.5);
       const needsFullRes = targetZoomPercent > highResThreshold;
       const previewIsAlreadyFullRes = previewSize.width >= originalSize.width;
-      if (needsFullRes && !previewIsAlreadyFullRes) {
-        if (isFullResolution) {
-          return;
-        }
-        if (fullResolutionUrl && fullResCacheKeyRef.current === visualAdjustmentsKey) {
-          setIsFullResolution(true);
-          return;
-        }
-        if (!isLoadingFullRes) {
-          setIsLoadingFullRes(true);
-          requestFullResolution(adjustments, visualAdjustmentsKey);
-        }
-      } else {
-        if (fullResRequestRef.current) {
-          fullResRequestRef.current.cancelled = true;
-        }
-        if (requestFullResolution.cancel) {
-          requestFullResolution.cancel();
-        }
-        if (isFullResolution) {
-          setIsFullResolution(false);
-        }
-        if (isLoadingFullRes) {
-          setIsLoadingFullRes(false);
-        }
-      }
+      setIsFullResolution(needsFullRes && !previewIsAlreadyFullRes);
     },
-    [
-      initialFitScale,
-      previewSize.width,
-      originalSize.width,
-      isFullResolution,
-      isLoadingFullRes,
-      requestFullResolution,
-      adjustments,
-      fullResolutionUrl,
-      visualAdjustmentsKey,
-    ],
+    [initialFitScale, previewSize.width, originalSize.width],
   );
 
   const handleZoomChange = useCallback(
@@ -1579,8 +2012,7 @@ function App() {
       }
       isProgrammaticZoom.current = true;
       setZoom(transformZoom);
-      const currentDisplayWidth = baseRenderSize.width * transformZoom;
-      handleFullResolutionLogic(targetZoomPercent, currentDisplayWidth);
+      handleFullResolutionLogic(targetZoomPercent);
     },
     [originalSize, baseRenderSize, handleFullResolutionLogic, adjustments.orientationSteps],
   );
@@ -1600,18 +2032,28 @@ function App() {
         const effectiveOriginalWidth = isSwapped ? originalSize.height : originalSize.width;
 
         const targetZoomPercent = (baseRenderSize.width * transformState.scale) / effectiveOriginalWidth;
-        const currentDisplayWidth = baseRenderSize.width * transformState.scale;
-        handleFullResolutionLogic(targetZoomPercent, currentDisplayWidth);
+        handleFullResolutionLogic(targetZoomPercent);
       }
     },
     [originalSize, baseRenderSize, handleFullResolutionLogic, adjustments.orientationSteps],
   );
 
+  const isVideoPath = useCallback(
+    (path: string) => !!supportedTypes?.video?.includes(path.split('.').pop()?.toLowerCase() || ''),
+    [supportedTypes],
+  );
+
   const handleImageSelect = useCallback(
     (path: string) => {
       if (selectedImage?.path === path) {
         return;
       }
+      // Videos can be browsed and organized, but not edited.
+      if (isVideoPath(path)) {
+        setMultiSelectedPaths([path]);
+        setLibraryActivePath(path);
+        return;
+      }
       applyAdjustments.cancel();
       debouncedSave.cancel();
 
@@ -1634,9 +2076,9 @@ function App() {
       setError(null);
       setHistogram(null);
       setFinalPreviewUrl(null);
+      setClippingOverlayUrl(null);
       setUncroppedAdjustedPreviewUrl(null);
       setFullScreenUrl(null);
-      setFullResolutionUrl(null);
       setTransformedOriginalUrl(null);
       setLiveAdjustments(INITIAL_ADJUSTMENTS);
       resetAdjustmentsHistory(INITIAL_ADJUSTMENTS);
@@ -1652,8 +2094,19 @@ function App() {
 
       setZoom(1);
       setIsLibraryExportPanelVisible(false);
-    },
-    [selectedImage?.path, applyAdjustments, debouncedSave, thumbnails, resetAdjustmentsHistory],
+
+      invoke(Invokes.GenerateThumbnails, { paths: [path], size: 'large' })
+        .then((result: any) => {
+          const largeThumbnail = result?.[path];
+          if (largeThumbnail) {
+            setSelectedImage((prev: SelectedImage | null) =>
+              prev?.path === path && !prev.isReady ? { ...prev, thumbnailUrl: largeThumbnail } : prev,
+            );
+          }
+        })
+        .catch((err) => console.error('Failed to load large thumbnail:', err));
+    },
+    [selectedImage?.path, applyAdjustments, debouncedSave, thumbnails, resetAdjustmentsHistory, isVideoPath],
   );
 
   useKeyboardShortcuts({
@@ -1666,6 +2119,7 @@ function App() {
     customEscapeHandler,
     handleBackToLibrary,
     handleCopyAdjustments,
+    handleCycleCompareLayout,
     handleDeleteSelected,
     handleImageSelect,
     handlePasteAdjustments,
@@ -1673,11 +2127,13 @@ function App() {
     handleRate,
     handleRightPanelSelect,
     handleSetColorLabel,
+    handleSetFlag,
     handleToggleFullScreen,
     handleZoomChange,
     isFullScreen,
     isStraightenActive,
     isViewLoading,
+    isWbPickerActive,
     libraryActivePath,
     multiSelectedPaths,
     redo,
@@ -1687,8 +2143,10 @@ function App() {
     setCopiedFilePaths,
     setIsStraightenActive,
     setIsWaveformVisible,
+    setIsWbPickerActive,
     setLibraryActivePath,
     setMultiSelectedPaths,
+    setShowClipping,
     setShowOriginal,
     sortedImageList,
     undo,
@@ -1703,19 +2161,23 @@ function App() {
     const listeners = [
       listen('preview-update-final', (event: any) => {
         if (isEffectActive) {
-          const imageData = new Uint8Array(event.payload);
-          const blob = new Blob([imageData], { type: 'image/jpeg' });
-          const url = URL.createObjectURL(blob);
-          setFinalPreviewUrl(url);
+          setFinalPreviewUrl(convertFileSrc(event.payload.frame, PREVIEW_PROTOCOL));
           setIsAdjusting(false);
         }
       }),
+      listen('comparison-update', (event: any) => {
+        if (isEffectActive) {
+          setComparisonUrl(convertFileSrc(event.payload.frame, PREVIEW_PROTOCOL));
+        }
+      }),
+      listen('clipping-overlay-update', (event: any) => {
+        if (isEffectActive) {
+          setClippingOverlayUrl(convertFileSrc(event.payload.frame, PREVIEW_PROTOCOL));
+        }
+      }),
       listen('preview-update-uncropped', (event: any) => {
         if (isEffectActive) {
-          const imageData = new Uint8Array(event.payload);
-          const blob = new Blob([imageData], { type: 'image/jpeg' });
-          const url = URL.createObjectURL(blob);
-          setUncroppedAdjustedPreviewUrl(url);
+          setUncroppedAdjustedPreviewUrl(convertFileSrc(event.payload.frame, PREVIEW_PROTOCOL));
         }
       }),
       listen('histogram-update', (event: any) => {
@@ -1739,6 +2201,41 @@ function App() {
           }
         }
       }),
+      listen('folder-changed', (event: any) => {
+        if (isEffectActive && event.payload.folder === currentFolderPathRef.current) {
+          const { removed } = event.payload;
+          if (removed.length > 0) {
+            setThumbnails((prev) => {
+              const next = { ...prev };
+              removed.forEach((path: string) => delete next[path]);
+              return next;
+            });
+          }
+          invoke(Invokes.ListImagesInDir, { path: event.payload.folder })
+            .then((list: any) => {
+              if (Array.isArray(list) && event.payload.folder === currentFolderPathRef.current) {
+                setImageList(list);
+              }
+            })
+            .catch((err) => console.error('Failed to refresh folder after external changes:', err));
+        }
+      }),
+      listen('preview-error', (event: any) => {
+        if (isEffectActive) {
+          setError(`Failed to render the preview: ${event.payload}`);
+        }
+      }),
+      listen('comparison-error', (event: any) => {
+        if (isEffectActive) {
+          setComparisonUrl(null);
+          setError(`Failed to render the comparison: ${event.payload}`);
+        }
+      }),
+      listen('mask-recompute-error', (event: any) => {
+        if (isEffectActive) {
+          setError(`Failed to recompute AI masks for ${event.payload.path}: ${event.payload.error}`);
+        }
+      }),
       listen('ai-model-download-start', (event: any) => {
         if (isEffectActive) {
           setAiModelDownloadStatus(event.payload);
@@ -1779,6 +2276,28 @@ function App() {
           }
         }
       }),
+      listen('culling-finished', (event: any) => {
+        if (isEffectActive) {
+          const rejectedPaths = new Set(
+            event.payload.filter((result: any) => result.likelyReject).map((result: any) => result.path),
+          );
+          setImageList((prevList: Array<ImageFile>) =>
+            prevList.map((image: ImageFile) =>
+              rejectedPaths.has(image.path) ? { ...image, flag: ImageFlag.Reject } : image,
+            ),
+          );
+        }
+      }),
+      listen('face-indexing-error', (event: any) => {
+        if (isEffectActive) {
+          setError(`Face recognition failed: ${event.payload}`);
+        }
+      }),
+      listen('culling-error', (event: any) => {
+        if (isEffectActive) {
+          setError(`Culling analysis failed: ${event.payload}`);
+        }
+      }),
       listen('batch-export-progress', (event: any) => {
         if (isEffectActive) {
           setExportState((prev: ExportState) => ({ ...prev, progress: event.payload }));
@@ -1831,6 +2350,39 @@ function App() {
           }
         }
       }),
+      listen('watch-import-complete', (event: any) => {
+        if (isEffectActive) {
+          handleRefreshFolderTree();
+          if (currentFolderPathRef.current?.startsWith(event.payload.destination)) {
+            handleSelectSubfolder(currentFolderPathRef.current, false);
+          }
+        }
+      }),
+      listen('upload-progress', (event: any) => {
+        if (isEffectActive) {
+          setExportState((prev: ExportState) => ({ ...prev, progress: event.payload }));
+        }
+      }),
+      listen('upload-error', (event: any) => {
+        if (isEffectActive) {
+          setError(`Upload to ${event.payload.target} failed for ${event.payload.path}: ${event.payload.error}`);
+        }
+      }),
+      listen('publish-error', (event: any) => {
+        if (isEffectActive) {
+          setError(`Publishing to ${event.payload.service} failed for ${event.payload.path}: ${event.payload.error}`);
+        }
+      }),
+      listen('export-hook-error', (event: any) => {
+        if (isEffectActive) {
+          setError(`Post-export script failed for ${event.payload.path}: ${event.payload.error}`);
+        }
+      }),
+      listen('watch-import-error', (event: any) => {
+        if (isEffectActive) {
+          setError(`Watch folder import failed for ${event.payload.path || event.payload.source}: ${event.payload.error}`);
+        }
+      }),
       listen('import-error', (event) => {
         if (isEffectActive) {
           setImportState((prev: ImportState) => ({
@@ -1915,6 +2467,12 @@ function App() {
       }
     });
 
+    const unlistenControlPoints = listen('panorama-control-points-needed', (event: any) => {
+      if (isEffectActive) {
+        setPanoramaModalState((prev: PanoramaModalState) => ({ ...prev, controlPointRequest: event.payload }));
+      }
+    });
+
     const unlistenError = listen('panorama-error', (event: any) => {
       if (isEffectActive) {
         setPanoramaModalState((prev: PanoramaModalState) => ({
@@ -1930,11 +2488,43 @@ function App() {
       isEffectActive = false;
       unlistenProgress.then((f: any) => f());
       unlistenComplete.then((f: any) => f());
+      unlistenControlPoints.then((f: any) => f());
       unlistenError.then((f: any) => f());
     };
   }, []);
 
-  const handleSavePanorama = async (): Promise<string> => {
+  const handleStitchPanorama = (
+    paths: Array<string>,
+    options: PanoramaOptions,
+    controlPoints: Array<ControlPoint> | null = null,
+  ) => {
+    setPanoramaModalState({
+      controlPointRequest: null,
+      controlPoints,
+      error: null,
+      finalImageBase64: null,
+      isOpen: true,
+      options,
+      progressMessage: 'Starting panorama process...',
+      stitchingSourcePaths: paths,
+    });
+    const stitchPromise = controlPoints
+      ? invoke(Invokes.StitchWithControlPoints, { paths, controlPoints, ...options })
+      : invoke(Invokes.StitchPanorama, { paths, ...options });
+    stitchPromise.catch((err) => {
+      setPanoramaModalState((prev: PanoramaModalState) => ({
+        ...prev,
+        error: String(err),
+        isOpen: true,
+        progressMessage: 'Failed to start.',
+      }));
+    });
+  };
+
+  const handleSavePanorama = async (
+    outputFormat: PanoramaOutputFormat,
+    outputDir: string | null,
+  ): Promise<string> => {
     if (panoramaModalState.stitchingSourcePaths.length === 0) {
       const err = 'Source paths for panorama not found.';
       setPanoramaModalState((prev: PanoramaModalState) => ({ ...prev, error: err }));
@@ -1944,6 +2534,8 @@ function App() {
     try {
       const savedPath: string = await invoke(Invokes.SavePanorama, {
         firstPathStr: panoramaModalState.stitchingSourcePaths[0],
+        outputDir,
+        outputFormat,
       });
       handleLibraryRefresh();
       return savedPath;
@@ -1954,6 +2546,146 @@ function App() {
     }
   };
 
+  useEffect(() => {
+    let isEffectActive = true;
+
+    const unlistenProgress = listen('focus-stack-progress', (event: any) => {
+      if (isEffectActive) {
+        setFocusStackModalState((prev: MergeModalState) => ({
+          ...prev,
+          error: null,
+          finalImageBase64: null,
+          isOpen: true,
+          progressMessage: event.payload,
+        }));
+      }
+    });
+
+    const unlistenComplete = listen('focus-stack-complete', (event: any) => {
+      if (isEffectActive) {
+        setFocusStackModalState((prev: MergeModalState) => ({
+          ...prev,
+          error: null,
+          finalImageBase64: event.payload.base64,
+          progressMessage: 'Focus Stack Ready',
+        }));
+      }
+    });
+
+    const unlistenError = listen('focus-stack-error', (event: any) => {
+      if (isEffectActive) {
+        setFocusStackModalState((prev: MergeModalState) => ({
+          ...prev,
+          error: String(event.payload),
+          finalImageBase64: null,
+          progressMessage: 'An error occurred.',
+        }));
+      }
+    });
+
+    return () => {
+      isEffectActive = false;
+      unlistenProgress.then((f: any) => f());
+      unlistenComplete.then((f: any) => f());
+      unlistenError.then((f: any) => f());
+    };
+  }, []);
+
+  useEffect(() => {
+    let isEffectActive = true;
+
+    const unlistenProgress = listen('hdr-progress', (event: any) => {
+      if (isEffectActive) {
+        setHdrModalState((prev: MergeModalState) => ({
+          ...prev,
+          error: null,
+          finalImageBase64: null,
+          isOpen: true,
+          progressMessage: event.payload,
+        }));
+      }
+    });
+
+    const unlistenComplete = listen('hdr-complete', (event: any) => {
+      if (isEffectActive) {
+        setHdrModalState((prev: MergeModalState) => ({
+          ...prev,
+          error: null,
+          finalImageBase64: event.payload.base64,
+          progressMessage: 'HDR Ready',
+        }));
+      }
+    });
+
+    const unlistenError = listen('hdr-error', (event: any) => {
+      if (isEffectActive) {
+        setHdrModalState((prev: MergeModalState) => ({
+          ...prev,
+          error: String(event.payload),
+          finalImageBase64: null,
+          progressMessage: 'An error occurred.',
+        }));
+      }
+    });
+
+    return () => {
+      isEffectActive = false;
+      unlistenProgress.then((f: any) => f());
+      unlistenComplete.then((f: any) => f());
+      unlistenError.then((f: any) => f());
+    };
+  }, []);
+
+  const handleMergeHdr = (paths: Array<string>) => {
+    setHdrModalState({
+      ...CLOSED_MERGE_MODAL_STATE,
+      isOpen: true,
+      progressMessage: 'Starting HDR merge...',
+      sourcePaths: paths,
+    });
+    invoke(Invokes.MergeHdr, { paths }).catch((err) => {
+      setHdrModalState((prev: MergeModalState) => ({ ...prev, error: String(err), isOpen: true }));
+    });
+  };
+
+  const handleSaveHdr = async (): Promise<string> => {
+    try {
+      const savedPath: string = await invoke(Invokes.SaveHdr, { firstPathStr: hdrModalState.sourcePaths[0] });
+      handleLibraryRefresh();
+      return savedPath;
+    } catch (err) {
+      console.error('Failed to save HDR image:', err);
+      setHdrModalState((prev: MergeModalState) => ({ ...prev, error: String(err) }));
+      throw err;
+    }
+  };
+
+  const handleStackFocus = (paths: Array<string>) => {
+    setFocusStackModalState({
+      ...CLOSED_MERGE_MODAL_STATE,
+      isOpen: true,
+      progressMessage: 'Starting focus stacking...',
+      sourcePaths: paths,
+    });
+    invoke(Invokes.StackFocus, { paths }).catch((err) => {
+      setFocusStackModalState((prev: MergeModalState) => ({ ...prev, error: String(err), isOpen: true }));
+    });
+  };
+
+  const handleSaveFocusStack = async (): Promise<string> => {
+    try {
+      const savedPath: string = await invoke(Invokes.SaveFocusStack, {
+        firstPathStr: focusStackModalState.sourcePaths[0],
+      });
+      handleLibraryRefresh();
+      return savedPath;
+    } catch (err) {
+      console.error('Failed to save focus stack:', err);
+      setFocusStackModalState((prev: MergeModalState) => ({ ...prev, error: String(err) }));
+      throw err;
+    }
+  };
+
   useEffect(() => {
     if (selectedImage?.isReady) {
       applyAdjustments(adjustments);
@@ -1963,7 +2695,29 @@ function App() {
       applyAdjustments.cancel();
       debouncedSave.cancel();
     };
-  }, [adjustments, selectedImage?.path, selectedImage?.isReady, applyAdjustments, debouncedSave]);
+  }, [adjustments, denoisedPath, selectedImage?.path, selectedImage?.isReady, applyAdjustments, debouncedSave]);
+
+  useEffect(() => {
+    if (!selectedImage?.isReady || !adjustments.aiDenoise) {
+      return;
+    }
+    const path = selectedImage.path;
+    if (denoisedPath === path || denoisingPathRef.current === path) {
+      return;
+    }
+    denoisingPathRef.current = path;
+    invoke(Invokes.GenerateAiDenoise)
+      .then(() => setDenoisedPath(path))
+      .catch((err) => {
+        console.error('Failed to generate AI denoise:', err);
+        setError(`AI denoise failed: ${err}`);
+      })
+      .finally(() => {
+        if (denoisingPathRef.current === path) {
+          denoisingPathRef.current = null;
+        }
+      });
+  }, [adjustments.aiDenoise, denoisedPath, selectedImage?.path, selectedImage?.isReady]);
 
   useEffect(() => {
     if (activeRightPanel === Panel.Crop && selectedImage?.isReady) {
@@ -2122,104 +2876,174 @@ function App() {
   }, [isWaveformVisible, selectedImage?.isReady, waveform]);
 
   useEffect(() => {
-    if (selectedImage && !selectedImage.isReady && selectedImage.path) {
+    if (!selectedImage || selectedImage.isReady || !selectedImage.path) {
+      return;
+    }
     let isEffectActive = true;
-    const loadFullImageData = async () => {
-        try {
-        const loadImageResult: any = await invoke(Invokes.LoadImage, { path: selectedImage.path });
-        if (!isEffectActive) {
-            return;
-        }
-        const histData: any = await invoke(Invokes.GenerateHistogram);
-        if (!isEffectActive) {
-            return;
+    const path = selectedImage.path;
+    let unlistenUpgrade: (() => void) | null = null;
+
+    const applyLoadedImage = async (loadImageResult: any) => {
+      const histData: any = await invoke(Invokes.GenerateHistogram);
+      if (!isEffectActive) {
+        return;
+      }
+
+      const { width, height } = loadImageResult;
+      setOriginalSize({ width, height });
+
+      if (appSettings?.editorPreviewResolution) {
+        const maxSize = appSettings.editorPreviewResolution;
+        const aspectRatio = width / height;
+
+        if (width > height) {
+          const pWidth = Math.min(width, maxSize);
+          const pHeight = Math.round(pWidth / aspectRatio);
+          setPreviewSize({ width: pWidth, height: pHeight });
+        } else {
+          const pHeight = Math.min(height, maxSize);
+          const pWidth = Math.round(pHeight * aspectRatio);
+          setPreviewSize({ width: pWidth, height: pHeight });
         }
+      } else {
+        setPreviewSize({ width: 0, height: 0 });
+      }
 
-        // --- START OF ADDED/MOVED LOGIC ---
+      setIsFullResolution(false);
+
+      const blob = new Blob([loadImageResult.original_image_bytes], { type: 'image/jpeg' });
+      const originalUrl = URL.createObjectURL(blob);
+
+      setSelectedImage((currentSelected: SelectedImage | null) => {
+        if (currentSelected && currentSelected.path === path) {
+          return {
+            ...currentSelected,
+            exif: loadImageResult.exif,
+            height: loadImageResult.height,
+            isRaw: loadImageResult.is_raw,
+            isReady: true,
+            metadata: loadImageResult.metadata,
+            originalUrl: originalUrl,
+            width: loadImageResult.width,
+          };
+        }
+        return currentSelected;
+      });
 
-        const { width, height } = loadImageResult;
+      let initialAdjusts;
+      if (loadImageResult.metadata.adjustments && !loadImageResult.metadata.adjustments.is_null) {
+        initialAdjusts = normalizeLoadedAdjustments(loadImageResult.metadata.adjustments);
+      } else {
+        initialAdjusts = {
+          ...INITIAL_ADJUSTMENTS,
+          aspectRatio: loadImageResult.width / loadImageResult.height,
+        };
+      }
+      setLiveAdjustments(initialAdjusts);
+      resetAdjustmentsHistory(initialAdjusts);
+      setHistogram(histData);
+    };
 
-        // 1. Set originalSize directly from the loaded data
-        setOriginalSize({ width, height });
+    const showEmbeddedPreview = (loadImageResult: any) => {
+      const blob = new Blob([loadImageResult.original_image_bytes], { type: 'image/jpeg' });
+      const originalUrl = URL.createObjectURL(blob);
+      setSelectedImage((currentSelected: SelectedImage | null) => {
+        if (currentSelected && currentSelected.path === path) {
+          return {
+            ...currentSelected,
+            exif: loadImageResult.exif,
+            isRaw: loadImageResult.is_raw,
+            metadata: loadImageResult.metadata,
+            originalUrl: originalUrl,
+          };
+        }
+        return currentSelected;
+      });
+    };
 
-        // 2. Calculate and set previewSize
-        if (appSettings?.editorPreviewResolution) {
-            const maxSize = appSettings.editorPreviewResolution;
-            const aspectRatio = width / height;
+    const handleLoadError = (err: any) => {
+      if (isEffectActive) {
+        console.error('Failed to load image:', err);
+        setError(`Failed to load image: ${err}`);
+        setSelectedImage(null);
+      }
+    };
 
-            if (width > height) {
-            const pWidth = Math.min(width, maxSize);
-            const pHeight = Math.round(pWidth / aspectRatio);
-            setPreviewSize({ width: pWidth, height: pHeight });
-            } else {
-            const pHeight = Math.min(height, maxSize);
-            const pWidth = Math.round(pHeight * aspectRatio);
-            setPreviewSize({ width: pWidth, height: pHeight });
-            }
-        } else {
-            setPreviewSize({ width: 0, height: 0 });
-        }
-
-        // 3. Reset full resolution state
-        setIsFullResolution(false);
-        setFullResolutionUrl(null);
-        fullResCacheKeyRef.current = null;
-
-        // --- END OF ADDED/MOVED LOGIC ---
-
-        const blob = new Blob([loadImageResult.original_image_bytes], { type: 'image/jpeg' });
-        const originalUrl = URL.createObjectURL(blob);
-
-        setSelectedImage((currentSelected: SelectedImage | null) => {
-            if (currentSelected && currentSelected.path === selectedImage.path) {
-            return {
-                ...currentSelected,
-                exif: loadImageResult.exif,
-                height: loadImageResult.height,
-                isRaw: loadImageResult.is_raw,
-                isReady: true,
-                metadata: loadImageResult.metadata,
-                originalUrl: originalUrl,
-                width: loadImageResult.width,
-            };
-            }
-            return currentSelected;
+    const loadFullImageData = async () => {
+      try {
+        let resolveUpgrade: (result: any) => void = () => {};
+        let rejectUpgrade: (error: any) => void = () => {};
+        const upgraded = new Promise<any>((resolve, reject) => {
+          resolveUpgrade = resolve;
+          rejectUpgrade = reject;
         });
-
-        let initialAdjusts;
-        if (loadImageResult.metadata.adjustments && !loadImageResult.metadata.adjustments.is_null) {
-            initialAdjusts = normalizeLoadedAdjustments(loadImageResult.metadata.adjustments);
-        } else {
-            initialAdjusts = {
-            ...INITIAL_ADJUSTMENTS,
-            aspectRatio: loadImageResult.width / loadImageResult.height,
-            };
+        const unlisten = await listen('preview-upgraded', (event: any) => {
+          if (event.payload.path !== path) {
+            return;
+          }
+          if (event.payload.error) {
+            rejectUpgrade(event.payload.error);
+          } else {
+            resolveUpgrade(event.payload.image);
+          }
+        });
+        if (!isEffectActive) {
+          unlisten();
+          return;
         }
-        if (loadImageResult.metadata.adjustments && !loadImageResult.metadata.adjustments.is_null) {
-            initialAdjusts = normalizeLoadedAdjustments(loadImageResult.metadata.adjustments);
+        unlistenUpgrade = unlisten;
+
+        let loadImageResult: any = await invoke(Invokes.LoadImage, { path });
+        if (!isEffectActive) {
+          return;
         }
-        setLiveAdjustments(initialAdjusts);
-        resetAdjustmentsHistory(initialAdjusts);
-        setHistogram(histData);
-        } catch (err) {
-        if (isEffectActive) {
-            console.error('Failed to load image:', err);
-            setError(`Failed to load image: ${err}`);
-            setSelectedImage(null);
+        if (loadImageResult.is_embedded_preview) {
+          showEmbeddedPreview(loadImageResult);
+          setIsViewLoading(false);
+          loadImageResult = await upgraded;
+          if (!isEffectActive) {
+            return;
+          }
         }
-        } finally {
+        await applyLoadedImage(loadImageResult);
+      } catch (err) {
+        handleLoadError(err);
+      } finally {
         if (isEffectActive) {
-            setIsViewLoading(false);
-        }
+          setIsViewLoading(false);
         }
+      }
     };
     loadFullImageData();
     return () => {
-        isEffectActive = false;
+      isEffectActive = false;
+      unlistenUpgrade?.();
     };
-    }
   }, [selectedImage?.path, selectedImage?.isReady, resetAdjustmentsHistory, appSettings?.editorPreviewResolution]);
 
+  useEffect(() => {
+    if (!selectedImage?.isReady) {
+      return;
+    }
+    const prefetchCount = appSettings?.editorPrefetchCount ?? 1;
+    const currentIndex = sortedImageList.findIndex((f: ImageFile) => f.path === selectedImage.path);
+    if (currentIndex === -1) {
+      return;
+    }
+    const paths: Array<string> = [];
+    for (let offset = 1; offset <= prefetchCount; offset++) {
+      const next = sortedImageList[currentIndex + offset];
+      const previous = sortedImageList[currentIndex - offset];
+      if (next) {
+        paths.push(next.path);
+      }
+      if (previous) {
+        paths.push(previous.path);
+      }
+    }
+    invoke(Invokes.PrefetchImages, { paths }).catch((err) => console.error('Failed to prefetch images:', err));
+  }, [selectedImage?.path, selectedImage?.isReady, sortedImageList, appSettings?.editorPrefetchCount]);
+
   const handleClearSelection = () => {
     if (selectedImage) {
       setMultiSelectedPaths([selectedImage.path]);
@@ -2279,7 +3103,17 @@ function App() {
   );
 
   const handleStartImport = async (settings: AppSettings) => {
-    if (importSourcePaths.length > 0 && importTargetFolder) {
+    if (cameraImport && importTargetFolder) {
+      invoke(Invokes.ImportFromCamera, {
+        destinationFolder: importTargetFolder,
+        files: cameraImport.files,
+        port: cameraImport.port,
+        settings: settings,
+      }).catch((err) => {
+        console.error('Failed to start camera import:', err);
+        setImportState({ status: Status.Error, errorMessage: `Failed to start import: ${err}` });
+      });
+    } else if (importSourcePaths.length > 0 && importTargetFolder) {
       invoke(Invokes.ImportFiles, {
         destinationFolder: importTargetFolder,
         settings: settings,
@@ -2323,13 +3157,14 @@ function App() {
       try {
         const nonRaw = supportedTypes?.nonRaw || [];
         const raw = supportedTypes?.raw || [];
-        const allImageExtensions = [...nonRaw, ...raw];
+        const video = supportedTypes?.video || [];
+        const allSupportedExtensions = [...nonRaw, ...raw, ...video];
 
         const selected = await open({
           filters: [
             {
-              name: 'All Supported Images',
-              extensions: allImageExtensions,
+              name: 'All Supported Files',
+              extensions: allSupportedExtensions,
             },
             {
               name: 'RAW Images',
@@ -2339,6 +3174,10 @@ function App() {
               name: 'Standard Images (JPEG, PNG, etc.)',
               extensions: nonRaw,
             },
+            {
+              name: 'Videos',
+              extensions: video,
+            },
             {
               name: 'All Files',
               extensions: ['*'],
@@ -2349,6 +3188,7 @@ function App() {
         });
 
         if (Array.isArray(selected) && selected.length > 0) {
+          setCameraImport(null);
           setImportSourcePaths(selected);
           setImportTargetFolder(targetPath);
           setIsImportModalOpen(true);
@@ -2364,8 +3204,8 @@ function App() {
     event.preventDefault();
     event.stopPropagation();
     const options: Array<Option> = [
-      { label: 'Undo', icon: Undo, onClick: undo, disabled: !canUndo },
-      { label: 'Redo', icon: Redo, onClick: redo, disabled: !canRedo },
+      { label: 'Undo', icon: Undo, onClick: undo, disabled: !canUndo && !selectedImage },
+      { label: 'Redo', icon: Redo, onClick: redo, disabled: !canRedo && !selectedImage },
       { type: OPTION_SEPARATOR },
       { label: 'Copy Adjustments', icon: Copy, onClick: handleCopyAdjustments },
       {
@@ -2374,7 +3214,40 @@ function App() {
         onClick: handlePasteAdjustments,
         disabled: copiedAdjustments === null,
       },
+      { label: 'Copy Masks', icon: Copy, onClick: handleCopyMasks, disabled: adjustments.masks.length === 0 },
       { type: OPTION_SEPARATOR },
+      {
+        label: 'Versions',
+        icon: GitBranch,
+        disabled: !editVersions,
+        submenu: [
+          ...(editVersions?.versions || []).map((version: { id: string; name: string }) => ({
+            label: version.name,
+            icon: version.id === editVersions?.activeVersionId ? Check : undefined,
+            onClick: () => handleEditVersionAction(Invokes.SwitchEditVersion, { versionId: version.id }),
+          })),
+          { type: OPTION_SEPARATOR },
+          {
+            label: 'New Version',
+            onClick: () =>
+              handleEditVersionAction(Invokes.CreateEditVersion, {
+                name: `Version ${(editVersions?.versions.length || 0) + 1}`,
+              }),
+          },
+          {
+            label: 'Duplicate Current Version',
+            onClick: () =>
+              handleEditVersionAction(Invokes.DuplicateEditVersion, { versionId: editVersions?.activeVersionId }),
+          },
+          {
+            label: 'Delete Current Version',
+            isDestructive: true,
+            disabled: (editVersions?.versions.length || 0) <= 1,
+            onClick: () =>
+              handleEditVersionAction(Invokes.DeleteEditVersion, { versionId: editVersions?.activeVersionId }),
+          },
+        ],
+      },
       { label: 'Auto Adjust', icon: Aperture, onClick: handleAutoAdjustments },
       {
         label: 'Set Rating',
@@ -2396,6 +3269,15 @@ function App() {
           })),
         ],
       },
+      {
+        label: 'Set Flag',
+        icon: Flag,
+        submenu: [
+          { label: 'Unflagged', onClick: () => handleSetFlag(null) },
+          { label: 'Pick', icon: Flag, onClick: () => handleSetFlag(ImageFlag.Pick) },
+          { label: 'Reject', icon: Ban, onClick: () => handleSetFlag(ImageFlag.Reject) },
+        ],
+      },
       { type: OPTION_SEPARATOR },
       {
         label: 'Reset Adjustments',
@@ -2436,6 +3318,7 @@ function App() {
     const copyLabel = isSingleSelection ? 'Copy Image' : `Copy ${selectionCount} Images`;
     const autoAdjustLabel = isSingleSelection ? 'Auto Adjust Image' : `Auto Adjust ${selectionCount} Images`;
     const renameLabel = isSingleSelection ? 'Rename Image' : `Rename ${selectionCount} Images`;
+    const stack = imageList.find((image: ImageFile) => image.path === path)?.stack;
 
     const handleApplyAutoAdjustmentsToSelection = () => {
       if (finalSelection.length === 0) {
@@ -2443,31 +3326,42 @@ function App() {
       }
 
       invoke(Invokes.ApplyAutoAdjustmentsToPaths, { paths: finalSelection })
-        .then(async () => {
-          if (selectedImage && finalSelection.includes(selectedImage.path)) {
-            const metadata: Metadata = await invoke(Invokes.LoadMetadata, { path: selectedImage.path });
-
-            if (metadata.adjustments && !metadata.adjustments.is_null) {
-              const normalized = normalizeLoadedAdjustments(metadata.adjustments);
-              setLiveAdjustments(normalized);
-              resetAdjustmentsHistory(normalized);
-            }
-          }
-          if (libraryActivePath && finalSelection.includes(libraryActivePath)) {
-            const metadata: Metadata = await invoke(Invokes.LoadMetadata, { path: libraryActivePath });
-
-            if (metadata.adjustments && !metadata.adjustments.is_null) {
-              const normalized = normalizeLoadedAdjustments(metadata.adjustments);
-              setLibraryActiveAdjustments(normalized);
-            }
-          }
-        })
+        .then(reloadSelectionAdjustments)
         .catch((err) => {
           console.error('Failed to apply auto adjustments to paths:', err);
           setError(`Failed to apply auto adjustments: ${err}`);
         });
     };
 
+    const handleAutoCropSelection = (aspectRatio: number) => {
+      invoke(Invokes.AutoCropPaths, { paths: finalSelection, aspectRatio })
+        .then(reloadSelectionAdjustments)
+        .catch((err) => {
+          console.error('Failed to auto crop paths:', err);
+          setError(`Failed to auto crop: ${err}`);
+        });
+    };
+
+    const reloadSelectionAdjustments = async () => {
+      if (selectedImage && finalSelection.includes(selectedImage.path)) {
+        const metadata: Metadata = await invoke(Invokes.LoadMetadata, { path: selectedImage.path });
+
+        if (metadata.adjustments && !metadata.adjustments.is_null) {
+          const normalized = normalizeLoadedAdjustments(metadata.adjustments);
+          setLiveAdjustments(normalized);
+          resetAdjustmentsHistory(normalized);
+        }
+      }
+      if (libraryActivePath && finalSelection.includes(libraryActivePath)) {
+        const metadata: Metadata = await invoke(Invokes.LoadMetadata, { path: libraryActivePath });
+
+        if (metadata.adjustments && !metadata.adjustments.is_null) {
+          const normalized = normalizeLoadedAdjustments(metadata.adjustments);
+          setLibraryActiveAdjustments(normalized);
+        }
+      }
+    };
+
     const options = [
       ...(!isEditingThisImage
         ? [
@@ -2509,28 +3403,38 @@ function App() {
         label: pasteLabel,
         onClick: handlePasteAdjustments,
       },
+      {
+        disabled: copiedMasks === null,
+        icon: ClipboardPaste,
+        label: isSingleSelection ? 'Paste Masks' : `Paste Masks to ${selectionCount} Images`,
+        onClick: () => handlePasteMasks(finalSelection),
+      },
       { label: autoAdjustLabel, icon: Aperture, onClick: handleApplyAutoAdjustmentsToSelection },
+      {
+        icon: CropIcon,
+        label: isSingleSelection ? 'Smart Crop' : `Smart Crop ${selectionCount} Images`,
+        submenu: SMART_CROP_RATIOS.map(({ label, value }) => ({
+          label,
+          onClick: () => handleAutoCropSelection(value),
+        })),
+      },
       {
         disabled: selectionCount < 2,
         icon: Images,
         label: isSingleSelection ? 'Stitch Image' : `Stitch ${selectionCount} Images`,
-        onClick: () => {
-          setPanoramaModalState({
-            error: null,
-            finalImageBase64: null,
-            isOpen: true,
-            progressMessage: 'Starting panorama process...',
-            stitchingSourcePaths: finalSelection,
-          });
-          invoke(Invokes.StitchPanorama, { paths: finalSelection }).catch((err) => {
-            setPanoramaModalState((prev: PanoramaModalState) => ({
-              ...prev,
-              error: String(err),
-              isOpen: true,
-              progressMessage: 'Failed to start.',
-            }));
-          });
-        },
+        onClick: () => handleStitchPanorama(finalSelection, panoramaModalState.options),
+      },
+      {
+        disabled: selectionCount < 2,
+        icon: Focus,
+        label: isSingleSelection ? 'Focus Stack Image' : `Focus Stack ${selectionCount} Images`,
+        onClick: () => handleStackFocus(finalSelection),
+      },
+      {
+        disabled: selectionCount < 2,
+        icon: Sun,
+        label: isSingleSelection ? 'Merge to HDR' : `Merge ${selectionCount} Images to HDR`,
+        onClick: () => handleMergeHdr(finalSelection),
       },
       { type: OPTION_SEPARATOR },
       {
@@ -2556,6 +3460,21 @@ function App() {
         },
       },
       { icon: FileEdit, label: renameLabel, onClick: () => handleRenameFiles(finalSelection) },
+      ...(stack
+        ? [
+            {
+              icon: Layers,
+              label: stack.collapsed ? 'Expand Stack' : 'Collapse Stack',
+              onClick: () => handleSetStackCollapsed(stack.id, !stack.collapsed),
+            },
+            {
+              disabled: stack.top_path === path,
+              icon: Layers,
+              label: 'Set as Stack Top',
+              onClick: () => handleSetStackTop(stack.id, path),
+            },
+          ]
+        : []),
       { type: OPTION_SEPARATOR },
       {
         icon: Star,
@@ -2577,6 +3496,21 @@ function App() {
           })),
         ],
       },
+      {
+        label: 'Set Flag',
+        icon: Flag,
+        submenu: [
+          { label: 'Unflagged', onClick: () => handleSetFlag(null, finalSelection) },
+          { label: 'Pick', icon: Flag, onClick: () => handleSetFlag(ImageFlag.Pick, finalSelection) },
+          { label: 'Reject', icon: Ban, onClick: () => handleSetFlag(ImageFlag.Reject, finalSelection) },
+          { type: OPTION_SEPARATOR },
+          {
+            label: 'Auto-Reject Blurry & Closed Eyes',
+            icon: ScanEye,
+            onClick: () => handleAnalyzeForCulling(finalSelection),
+          },
+        ],
+      },
       { type: OPTION_SEPARATOR },
       {
         disabled: !isSingleSelection,
@@ -2677,6 +3611,24 @@ function App() {
         },
       },
       { type: OPTION_SEPARATOR },
+      {
+        disabled: !appSettings?.enableAiTagging,
+        icon: ScanFace,
+        label: 'Scan for Faces',
+        onClick: async () => {
+          try {
+            await invoke(Invokes.IndexFaces, { folderPath: targetPath });
+          } catch (err) {
+            setError(`Failed to scan for faces: ${err}`);
+          }
+        },
+      },
+      {
+        icon: Users,
+        label: 'People...',
+        onClick: () => setIsPeopleModalOpen(true),
+      },
+      { type: OPTION_SEPARATOR },
       {
         disabled: copiedFilePaths.length === 0,
         icon: ClipboardPaste,
@@ -2710,6 +3662,14 @@ function App() {
         ],
       },
       { icon: FolderInput, label: 'Import Images', onClick: () => handleImportClick(targetPath) },
+      {
+        icon: Camera,
+        label: 'Import from Device',
+        onClick: () => {
+          setImportTargetFolder(targetPath);
+          setIsCameraImportModalOpen(true);
+        },
+      },
       { type: OPTION_SEPARATOR },
       {
         icon: Folder,
@@ -2717,6 +3677,20 @@ function App() {
         onClick: () =>
           invoke(Invokes.ShowInFinder, { path: targetPath }).catch((err) => setError(`Could not show folder: ${err}`)),
       },
+      {
+        icon: Ban,
+        isDestructive: true,
+        label: 'Delete Rejected Images',
+        submenu: [
+          { label: 'Cancel', icon: X, onClick: () => {} },
+          {
+            label: 'Confirm',
+            icon: Check,
+            isDestructive: true,
+            onClick: () => handleDeleteRejectedInFolder(targetPath),
+          },
+        ],
+      },
       ...(path
         ? [
             {
@@ -2812,6 +3786,8 @@ function App() {
               brushSettings={brushSettings}
               canRedo={canRedo}
               canUndo={canUndo}
+              clippingOverlayUrl={clippingOverlayUrl}
+              comparisonUrl={comparisonUrl}
               finalPreviewUrl={finalPreviewUrl}
               fullScreenUrl={fullScreenUrl}
               isAdjusting={isAdjusting}
@@ -2821,6 +3797,7 @@ function App() {
               isMaskControlHovered={isMaskControlHovered}
               isStraightenActive={isStraightenActive}
               isWaveformVisible={isWaveformVisible}
+              isWbPickerActive={isWbPickerActive}
               onBackToLibrary={handleBackToLibrary}
               onCloseWaveform={() => setIsWaveformVisible(false)}
               onContextMenu={handleEditorContextMenu}
@@ -2833,12 +3810,14 @@ function App() {
               onToggleFullScreen={handleToggleFullScreen}
               onToggleWaveform={handleToggleWaveform}
               onUndo={undo}
+              onWhiteBalancePick={handleWhiteBalancePick}
               onZoomed={handleUserTransform}
               renderedRightPanel={renderedRightPanel}
               selectedImage={selectedImage}
               setAdjustments={setAdjustments}
               setShowOriginal={setShowOriginal}
               showOriginal={showOriginal}
+              spotSettings={spotSettings}
               targetZoom={zoom}
               thumbnails={thumbnails}
               transformWrapperRef={transformWrapperRef}
@@ -2852,8 +3831,6 @@ function App() {
               originalSize={originalSize}
               baseRenderSize={baseRenderSize}
               isFullResolution={isFullResolution}
-              fullResolutionUrl={fullResolutionUrl}
-              isLoadingFullRes={isLoadingFullRes}
             />
             <Resizer
               direction={Orientation.Horizontal}
@@ -2920,14 +3897,18 @@ function App() {
                           copiedSectionAdjustments={copiedSectionAdjustments}
                           handleAutoAdjustments={handleAutoAdjustments}
                           histogram={histogram}
+                          isWbPickerActive={isWbPickerActive}
                           selectedImage={selectedImage}
                           setAdjustments={setAdjustments}
                           setCollapsibleState={setCollapsibleSectionsState}
                           setCopiedSectionAdjustments={setCopiedSectionAdjustments}
+                          setIsWbPickerActive={setIsWbPickerActive}
                           theme={theme}
                         />
                       )}
-                      {renderedRightPanel === Panel.Metadata && <MetadataPanel selectedImage={selectedImage} />}
+                      {renderedRightPanel === Panel.Metadata && (
+                        <MetadataPanel multiSelectedPaths={multiSelectedPaths} selectedImage={selectedImage} />
+                      )}
                       {renderedRightPanel === Panel.Crop && (
                         <CropPanel
                           adjustments={adjustments}
@@ -2945,10 +3926,13 @@ function App() {
                           aiModelDownloadStatus={aiModelDownloadStatus}
                           brushSettings={brushSettings}
                           copiedMask={copiedMask}
+                          aiMaskCandidates={aiMaskCandidates}
                           histogram={histogram}
                           isGeneratingAiMask={isGeneratingAiMask}
+                          onGenerateAiFaceMasks={handleGenerateAiFaceMasks}
                           onGenerateAiForegroundMask={handleGenerateAiForegroundMask}
                           onGenerateAiSkyMask={handleGenerateAiSkyMask}
+                          onSelectAiMaskCandidate={handleSelectAiMaskCandidate}
                           onSelectContainer={setActiveMaskContainerId}
                           onSelectMask={setActiveMaskId}
                           selectedImage={selectedImage}
@@ -2959,6 +3943,15 @@ function App() {
                           setIsMaskControlHovered={setIsMaskControlHovered}
                         />
                       )}
+                      {renderedRightPanel === Panel.SpotRemoval && (
+                        <SpotRemovalPanel
+                          adjustments={adjustments}
+                          selectedImage={selectedImage}
+                          setAdjustments={setAdjustments}
+                          setSpotSettings={setSpotSettings}
+                          spotSettings={spotSettings}
+                        />
+                      )}
                       {renderedRightPanel === Panel.Presets && (
                         <PresetsPanel
                           activePanel={activeRightPanel}
@@ -2971,9 +3964,11 @@ function App() {
                         <ExportPanel
                           adjustments={adjustments}
                           exportState={exportState}
+                          isComfyUiConnected={isComfyUiConnected}
                           multiSelectedPaths={multiSelectedPaths}
                           selectedImage={selectedImage}
                           setExportState={setExportState}
+                          uploadTargets={appSettings?.uploadTargets || []}
                         />
                       )}
                       {renderedRightPanel === Panel.Ai && (
@@ -2986,6 +3981,7 @@ function App() {
                           isComfyUiConnected={isComfyUiConnected}
                           isGeneratingAi={isGeneratingAi}
                           isGeneratingAiMask={isGeneratingAiMask}
+                          onConvertPatchToMask={handleConvertAiPatchToMask}
                           onDeletePatch={handleDeleteAiPatch}
                           onGenerateAiForegroundMask={handleGenerateAiForegroundMask}
                           onGenerativeReplace={handleGenerativeReplace}
@@ -3140,32 +4136,72 @@ function App() {
           >
             <LibraryExportPanel
               exportState={exportState}
+              isComfyUiConnected={isComfyUiConnected}
               isVisible={isLibraryExportPanelVisible}
               multiSelectedPaths={multiSelectedPaths}
               onClose={() => setIsLibraryExportPanelVisible(false)}
               setExportState={setExportState}
+              uploadTargets={appSettings?.uploadTargets || []}
             />
           </div>
         </div>
       </div>
       <PanoramaModal
+        controlPointRequest={panoramaModalState.controlPointRequest}
         error={panoramaModalState.error}
         finalImageBase64={panoramaModalState.finalImageBase64}
         isOpen={panoramaModalState.isOpen}
         onClose={() =>
-          setPanoramaModalState({
+          setPanoramaModalState((prev: PanoramaModalState) => ({
+            controlPointRequest: null,
+            controlPoints: null,
             isOpen: false,
+            options: prev.options,
             progressMessage: '',
             finalImageBase64: null,
             error: null,
             stitchingSourcePaths: [],
-          })
+          }))
         }
         onOpenFile={(path: string) => {
           handleImageSelect(path);
         }}
+        onOptionsChange={(options: PanoramaOptions) =>
+          handleStitchPanorama(panoramaModalState.stitchingSourcePaths, options, panoramaModalState.controlPoints)
+        }
         onSave={handleSavePanorama}
+        onStitchWithControlPoints={(controlPoints: Array<ControlPoint>) =>
+          handleStitchPanorama(panoramaModalState.stitchingSourcePaths, panoramaModalState.options, controlPoints)
+        }
+        options={panoramaModalState.options}
         progressMessage={panoramaModalState.progressMessage}
+        thumbnails={thumbnails}
+      />
+      <MergeModal
+        error={focusStackModalState.error}
+        failedTitle="Focus Stacking Failed"
+        finalImageBase64={focusStackModalState.finalImageBase64}
+        isOpen={focusStackModalState.isOpen}
+        onClose={() => setFocusStackModalState(CLOSED_MERGE_MODAL_STATE)}
+        onOpenFile={(path: string) => {
+          handleImageSelect(path);
+        }}
+        onSave={handleSaveFocusStack}
+        progressMessage={focusStackModalState.progressMessage}
+        progressTitle="Stacking Focus"
+      />
+      <MergeModal
+        error={hdrModalState.error}
+        failedTitle="HDR Merge Failed"
+        finalImageBase64={hdrModalState.finalImageBase64}
+        isOpen={hdrModalState.isOpen}
+        onClose={() => setHdrModalState(CLOSED_MERGE_MODAL_STATE)}
+        onOpenFile={(path: string) => {
+          handleImageSelect(path);
+        }}
+        onSave={handleSaveHdr}
+        progressMessage={hdrModalState.progressMessage}
+        progressTitle="Merging HDR"
       />
       <CreateFolderModal
         isOpen={isCreateFolderModalOpen}
@@ -3185,8 +4221,25 @@ function App() {
         onSave={handleSaveRename}
       />
       <ConfirmModal {...confirmModalState} onClose={closeConfirmModal} />
+      <PeopleModal
+        isOpen={isPeopleModalOpen}
+        onClose={() => setIsPeopleModalOpen(false)}
+        onShowPerson={(name: string) => {
+          setSearchQuery(`person:${name}`);
+          setIsPeopleModalOpen(false);
+        }}
+        thumbnails={thumbnails}
+      />
+      <CameraImportModal
+        isOpen={isCameraImportModalOpen}
+        onClose={() => setIsCameraImportModalOpen(false)}
+        onContinue={(port: string, files: Array<CameraFile>) => {
+          setCameraImport({ files, port });
+          setIsImportModalOpen(true);
+        }}
+      />
       <ImportSettingsModal
-        fileCount={importSourcePaths.length}
+        fileCount={cameraImport ? cameraImport.files.length : importSourcePaths.length}
         isOpen={isImportModalOpen}
         onClose={() => setIsImportModalOpen(false)}
         onSave={handleStartImport}
@@ -1,8 +1,13 @@
-import { useState } from 'react';
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Pipette } from 'lucide-react';
+import clsx from 'clsx';
 import Slider from '../ui/Slider';
 import ColorWheel from '../ui/ColorWheel';
-import { ColorAdjustment, HueSatLum, INITIAL_ADJUSTMENTS } from '../../utils/adjustments';
+import Dropdown from '../ui/Dropdown';
+import { ColorAdjustment, HueSatLum, INITIAL_ADJUSTMENTS, WorkingSpace } from '../../utils/adjustments';
 import { Adjustments, ColorGrading } from '../../utils/adjustments';
+import { Invokes, SelectedImage } from '../ui/AppProperties';
 
 interface ColorProps {
   color: string;
@@ -11,7 +16,17 @@ interface ColorProps {
 
 interface ColorPanelProps {
   adjustments: Adjustments;
+  isForMask?: boolean;
+  isWbPickerActive?: boolean;
+  selectedImage?: SelectedImage;
   setAdjustments(adjustments: Partial<Adjustments>): any;
+  setIsWbPickerActive?(active: any): void;
+}
+
+interface WhiteBalancePreset {
+  name: string;
+  temperature: number;
+  tint: number;
 }
 
 interface ColorSwatchProps {
@@ -32,6 +47,12 @@ const HSL_COLORS: Array<ColorProps> = [
   { name: 'magentas', color: '#f472b6' },
 ];
 
+const WORKING_SPACES = [
+  { label: 'sRGB', value: WorkingSpace.Srgb },
+  { label: 'Rec.2020 (Linear)', value: WorkingSpace.Rec2020 },
+  { label: 'ProPhoto RGB (Linear)', value: WorkingSpace.ProPhoto },
+];
+
 const ColorSwatch = ({ color, name, isActive, onClick }: ColorSwatchProps) => (
   <button
     aria-label={`Select ${name} color`}
@@ -124,8 +145,48 @@ const ColorGradingPanel = ({ adjustments, setAdjustments }: ColorPanelProps) =>
   );
 };
 
-export default function ColorPanel({ adjustments, setAdjustments }: ColorPanelProps) {
+export default function ColorPanel({
+  adjustments,
+  isForMask = false,
+  isWbPickerActive = false,
+  selectedImage,
+  setAdjustments,
+  setIsWbPickerActive,
+}: ColorPanelProps) {
   const [activeColor, setActiveColor] = useState('reds');
+  const [wbPresets, setWbPresets] = useState<Array<WhiteBalancePreset>>([]);
+  const showRawWhiteBalance = !isForMask && !!selectedImage?.isRaw;
+
+  useEffect(() => {
+    if (!showRawWhiteBalance || !selectedImage?.isReady) {
+      setWbPresets([]);
+      return;
+    }
+    invoke(Invokes.GetWhiteBalancePresets)
+      .then((presets: any) => setWbPresets(presets))
+      .catch((err) => {
+        console.error('Failed to load white balance presets:', err);
+        setWbPresets([]);
+      });
+  }, [showRawWhiteBalance, selectedImage?.path, selectedImage?.isReady]);
+
+  const activeWbPreset = wbPresets.find(
+    (preset: WhiteBalancePreset) =>
+      Math.round(preset.temperature) === (adjustments.temperature || 0) &&
+      Math.round(preset.tint) === (adjustments.tint || 0),
+  );
+
+  const handleWbPresetChange = (name: string) => {
+    const preset = wbPresets.find((p: WhiteBalancePreset) => p.name === name);
+    if (!preset) {
+      return;
+    }
+    setAdjustments((prev: Partial<Adjustments>) => ({
+      ...prev,
+      temperature: Math.round(preset.temperature),
+      tint: Math.round(preset.tint),
+    }));
+  };
 
   const handleGlobalChange = (key: ColorAdjustment, value: string) => {
     setAdjustments((prev: Partial<Adjustments>) => ({ ...prev, [key]: parseFloat(value) }));
@@ -148,8 +209,49 @@ export default function ColorPanel({ adjustments, setAdjustments }: ColorPanelPr
 
   return (
     <div>
+      {!isForMask && (
+        <div className="mb-4 p-2 bg-bg-tertiary rounded-md">
+          <p className="text-md font-semibold mb-2 text-primary">Working Space</p>
+          <Dropdown
+            onChange={(value: WorkingSpace) =>
+              setAdjustments((prev: Partial<Adjustments>) => ({ ...prev, workingSpace: value }))
+            }
+            options={WORKING_SPACES}
+            value={adjustments.workingSpace || WorkingSpace.Srgb}
+          />
+          <p className="text-xs text-text-secondary mt-2">
+            A wide-gamut space keeps strong saturation and color edits from clipping before the output conversion.
+          </p>
+        </div>
+      )}
+
       <div className="mb-4 p-2 bg-bg-tertiary rounded-md">
-        <p className="text-md font-semibold mb-2 text-primary">White Balance</p>
+        <div className="flex items-center justify-between mb-2">
+          <p className="text-md font-semibold text-primary">White Balance</p>
+          {showRawWhiteBalance && setIsWbPickerActive && (
+            <button
+              className={clsx(
+                'p-1.5 rounded-md transition-colors',
+                isWbPickerActive
+                  ? 'bg-accent text-button-text'
+                  : 'text-text-secondary hover:bg-card-active hover:text-text-primary',
+              )}
+              onClick={() => setIsWbPickerActive((isActive: boolean) => !isActive)}
+              title="Pick a neutral point in the image"
+            >
+              <Pipette size={16} />
+            </button>
+          )}
+        </div>
+        {showRawWhiteBalance && wbPresets.length > 0 && (
+          <Dropdown
+            className="mb-2"
+            onChange={handleWbPresetChange}
+            options={wbPresets.map((preset: WhiteBalancePreset) => ({ label: preset.name, value: preset.name }))}
+            placeholder="Custom"
+            value={activeWbPreset?.name || ''}
+          />
+        )}
         <Slider
           label="Temperature"
           max={100}
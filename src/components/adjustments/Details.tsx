@@ -1,17 +1,28 @@
 import Slider from '../ui/Slider';
-import { Adjustments, DetailsAdjustment, Effect } from '../../utils/adjustments';
+import Switch from '../ui/Switch';
+import { Adjustments, DetailsAdjustment, Effect, LensCorrection } from '../../utils/adjustments';
 
 interface DetailsPanelProps {
   adjustments: Adjustments;
+  isForMask?: boolean;
   setAdjustments(adjustments: Partial<Adjustments>): any;
 }
 
-export default function DetailsPanel({ adjustments, setAdjustments }: DetailsPanelProps) {
+export default function DetailsPanel({ adjustments, setAdjustments, isForMask = false }: DetailsPanelProps) {
   const handleAdjustmentChange = (key: string, value: string) => {
     const numericValue = parseInt(value, 10);
     setAdjustments((prev: Partial<Adjustments>) => ({ ...prev, [key]: numericValue }));
   };
 
+  const handleLensCorrectionChange = (key: keyof LensCorrection, checked: boolean) => {
+    setAdjustments((prev: Partial<Adjustments>) => ({
+      ...prev,
+      lensCorrection: { ...(prev.lensCorrection as LensCorrection), [key]: checked },
+    }));
+  };
+
+  const lensCorrection: LensCorrection = adjustments.lensCorrection;
+
   return (
     <div>
       <div className="mb-4 p-2 bg-bg-tertiary rounded-md">
@@ -28,6 +39,14 @@ export default function DetailsPanel({ adjustments, setAdjustments }: DetailsPan
 
       <div className="mb-4 p-2 bg-bg-tertiary rounded-md">
         <p className="text-md font-semibold mb-2 text-primary">Presence</p>
+        <Slider
+          label="Texture"
+          max={100}
+          min={-100}
+          onChange={(e: any) => handleAdjustmentChange(Effect.Texture, e.target.value)}
+          step={1}
+          value={adjustments.texture}
+        />
         <Slider
           label="Clarity"
           max={100}
@@ -56,6 +75,14 @@ export default function DetailsPanel({ adjustments, setAdjustments }: DetailsPan
 
       <div className="p-2 bg-bg-tertiary rounded-md">
         <p className="text-md font-semibold mb-2 text-primary">Noise Reduction</p>
+        <Slider
+          label="AI Denoise"
+          max={100}
+          min={0}
+          onChange={(e: any) => handleAdjustmentChange(DetailsAdjustment.AiDenoise, e.target.value)}
+          step={1}
+          value={adjustments.aiDenoise}
+        />
         <Slider
           label="Luminance"
           max={100}
@@ -72,7 +99,111 @@ export default function DetailsPanel({ adjustments, setAdjustments }: DetailsPan
           step={1}
           value={adjustments.colorNoiseReduction}
         />
+        {!isForMask && (
+          <Slider
+            label="Detail"
+            max={100}
+            min={0}
+            onChange={(e: any) => handleAdjustmentChange(DetailsAdjustment.NoiseReductionDetail, e.target.value)}
+            step={1}
+            value={adjustments.noiseReductionDetail}
+          />
+        )}
+      </div>
+
+      <div className="mt-4 p-2 bg-bg-tertiary rounded-md">
+        <p className="text-md font-semibold mb-2 text-primary">Defringe</p>
+        {!isForMask && (
+          <Slider
+            label="Chromatic Aberration"
+            max={100}
+            min={0}
+            onChange={(e: any) => handleAdjustmentChange(DetailsAdjustment.ChromaticAberration, e.target.value)}
+            step={1}
+            value={adjustments.chromaticAberration}
+          />
+        )}
+        <Slider
+          label="Purple Amount"
+          max={100}
+          min={0}
+          onChange={(e: any) => handleAdjustmentChange(DetailsAdjustment.DefringePurpleAmount, e.target.value)}
+          step={1}
+          value={adjustments.defringePurpleAmount}
+        />
+        <Slider
+          label="Purple Hue Min"
+          max={360}
+          min={0}
+          onChange={(e: any) => handleAdjustmentChange(DetailsAdjustment.DefringePurpleHueMin, e.target.value)}
+          step={1}
+          value={adjustments.defringePurpleHueMin}
+        />
+        <Slider
+          label="Purple Hue Max"
+          max={360}
+          min={0}
+          onChange={(e: any) => handleAdjustmentChange(DetailsAdjustment.DefringePurpleHueMax, e.target.value)}
+          step={1}
+          value={adjustments.defringePurpleHueMax}
+        />
+        <Slider
+          label="Green Amount"
+          max={100}
+          min={0}
+          onChange={(e: any) => handleAdjustmentChange(DetailsAdjustment.DefringeGreenAmount, e.target.value)}
+          step={1}
+          value={adjustments.defringeGreenAmount}
+        />
+        <Slider
+          label="Green Hue Min"
+          max={360}
+          min={0}
+          onChange={(e: any) => handleAdjustmentChange(DetailsAdjustment.DefringeGreenHueMin, e.target.value)}
+          step={1}
+          value={adjustments.defringeGreenHueMin}
+        />
+        <Slider
+          label="Green Hue Max"
+          max={360}
+          min={0}
+          onChange={(e: any) => handleAdjustmentChange(DetailsAdjustment.DefringeGreenHueMax, e.target.value)}
+          step={1}
+          value={adjustments.defringeGreenHueMax}
+        />
       </div>
+
+      {!isForMask && (
+        <div className="mt-4 p-2 bg-bg-tertiary rounded-md">
+          <p className="text-md font-semibold mb-2 text-primary">Lens Correction</p>
+          <div className="mb-2">
+            <Switch
+              label="Enable Profile Correction"
+              checked={!!lensCorrection?.enabled}
+              onChange={(checked: boolean) => handleLensCorrectionChange('enabled', checked)}
+            />
+          </div>
+          {lensCorrection?.enabled && (
+            <div className="space-y-2 mt-2 pt-2 border-t border-bg-secondary">
+              <Switch
+                label="Distortion"
+                checked={lensCorrection.distortion}
+                onChange={(checked: boolean) => handleLensCorrectionChange('distortion', checked)}
+              />
+              <Switch
+                label="Chromatic Aberration"
+                checked={lensCorrection.chromaticAberration}
+                onChange={(checked: boolean) => handleLensCorrectionChange('chromaticAberration', checked)}
+              />
+              <Switch
+                label="Vignetting"
+                checked={lensCorrection.vignetting}
+                onChange={(checked: boolean) => handleLensCorrectionChange('vignetting', checked)}
+              />
+            </div>
+          )}
+        </div>
+      )}
     </div>
   );
 }
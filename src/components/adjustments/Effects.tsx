@@ -1,6 +1,12 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { open } from '@tauri-apps/plugin-dialog';
+import { FolderInput } from 'lucide-react';
 import Slider from '../ui/Slider';
 import Switch from '../ui/Switch';
-import { Adjustments, Effect } from '../../utils/adjustments';
+import Dropdown from '../ui/Dropdown';
+import { Adjustments, Effect, LensBlur, LensBlurSource, LutAdjustment, MaskContainer } from '../../utils/adjustments';
+import { Invokes } from '../ui/AppProperties';
 
 interface EffectsPanelProps {
   adjustments: Adjustments;
@@ -8,7 +14,46 @@ interface EffectsPanelProps {
   setAdjustments(adjustments: Partial<Adjustments>): any;
 }
 
+interface LutInfo {
+  id: string;
+  name: string;
+  size: number;
+}
+
 export default function EffectsPanel({ adjustments, setAdjustments, isForMask = false }: EffectsPanelProps) {
+  const [luts, setLuts] = useState<Array<LutInfo>>([]);
+
+  useEffect(() => {
+    if (isForMask) {
+      return;
+    }
+    invoke(Invokes.GetLuts)
+      .then((list: any) => setLuts(list))
+      .catch((err) => console.error('Failed to load LUTs:', err));
+  }, [isForMask]);
+
+  const handleLutChange = (lut: LutAdjustment | null) => {
+    setAdjustments((prev: Partial<Adjustments>) => ({ ...prev, lut }));
+  };
+
+  const handleImportLut = async () => {
+    const sourcePath = await open({ filters: [{ name: '3D LUT', extensions: ['cube'] }], multiple: false });
+    if (typeof sourcePath !== 'string') {
+      return;
+    }
+    try {
+      const imported: LutInfo = await invoke(Invokes.ImportLut, { sourcePath });
+      setLuts((prev: Array<LutInfo>) =>
+        [...prev.filter((l: LutInfo) => l.id !== imported.id), imported].sort((a: LutInfo, b: LutInfo) =>
+          a.name.toLowerCase().localeCompare(b.name.toLowerCase()),
+        ),
+      );
+      handleLutChange({ id: imported.id, intensity: adjustments.lut?.intensity ?? 100 });
+    } catch (err) {
+      console.error('Failed to import LUT:', err);
+    }
+  };
+
   const handleAdjustmentChange = (key: Effect, value: string) => {
     const numericValue = parseInt(value, 10);
     setAdjustments((prev: Partial<Adjustments>) => ({ ...prev, [key]: numericValue }));
@@ -22,8 +67,51 @@ export default function EffectsPanel({ adjustments, setAdjustments, isForMask =
     setAdjustments((prev: Partial<Adjustments>) => ({ ...prev, [key]: value }));
   };
 
+  const handleLensBlurChange = (key: keyof LensBlur, value: any) => {
+    setAdjustments((prev: Partial<Adjustments>) => ({
+      ...prev,
+      lensBlur: { ...(prev.lensBlur as LensBlur), [key]: value },
+    }));
+  };
+
+  const lensBlur: LensBlur = adjustments.lensBlur;
+  const depthMaskOptions = (adjustments.masks || []).map((mask: MaskContainer) => ({
+    label: mask.name,
+    value: mask.id,
+  }));
+
   return (
     <div>
+      <div className="mb-4 p-2 bg-bg-tertiary rounded-md">
+        <p className="text-md font-semibold mb-2 text-primary">Glow</p>
+        <Slider
+          label="Amount"
+          max={100}
+          min={0}
+          onChange={(e: any) => handleAdjustmentChange(Effect.GlowAmount, e.target.value)}
+          step={1}
+          value={adjustments.glowAmount}
+        />
+        <Slider
+          defaultValue={50}
+          label="Radius"
+          max={100}
+          min={1}
+          onChange={(e: any) => handleAdjustmentChange(Effect.GlowRadius, e.target.value)}
+          step={1}
+          value={adjustments.glowRadius}
+        />
+        <Slider
+          defaultValue={50}
+          label="Threshold"
+          max={100}
+          min={0}
+          onChange={(e: any) => handleAdjustmentChange(Effect.GlowThreshold, e.target.value)}
+          step={1}
+          value={adjustments.glowThreshold}
+        />
+      </div>
+
       {!isForMask && (
         <>
           <div className="mb-4 p-2 bg-bg-tertiary rounded-md">
@@ -77,6 +165,160 @@ export default function EffectsPanel({ adjustments, setAdjustments, isForMask =
             )}
           </div>
 
+          <div className="mb-4 p-2 bg-bg-tertiary rounded-md">
+            <div className="flex items-center justify-between mb-2">
+              <p className="text-md font-semibold text-primary">LUT</p>
+              <button
+                className="p-1.5 rounded-md text-text-secondary hover:bg-card-active hover:text-text-primary transition-colors"
+                onClick={handleImportLut}
+                title="Import .cube LUT"
+              >
+                <FolderInput size={16} />
+              </button>
+            </div>
+            <Dropdown
+              onChange={(id: string) =>
+                handleLutChange(id ? { id, intensity: adjustments.lut?.intensity ?? 100 } : null)
+              }
+              options={[
+                { label: 'None', value: '' },
+                ...luts.map((lut: LutInfo) => ({ label: lut.name, value: lut.id })),
+              ]}
+              value={adjustments.lut?.id || ''}
+            />
+            {adjustments.lut && (
+              <div className="mt-2 pt-2 border-t border-bg-secondary">
+                <Slider
+                  defaultValue={100}
+                  label="Intensity"
+                  max={100}
+                  min={0}
+                  onChange={(e: any) =>
+                    handleLutChange({ ...(adjustments.lut as LutAdjustment), intensity: parseInt(e.target.value, 10) })
+                  }
+                  step={1}
+                  value={adjustments.lut.intensity}
+                />
+              </div>
+            )}
+          </div>
+
+          <div className="mb-4 p-2 bg-bg-tertiary rounded-md">
+            <p className="text-md font-semibold mb-2 text-primary">Lens Blur</p>
+            <Slider
+              label="Amount"
+              max={100}
+              min={0}
+              onChange={(e: any) => handleLensBlurChange('amount', parseInt(e.target.value, 10))}
+              step={1}
+              value={lensBlur?.amount ?? 0}
+            />
+            {lensBlur?.amount > 0 && (
+              <div className="space-y-2 mt-2 pt-2 border-t border-bg-secondary">
+                <Dropdown
+                  onChange={(value: LensBlurSource) => handleLensBlurChange('source', value)}
+                  options={[
+                    { label: 'Radial Focus', value: LensBlurSource.Radial },
+                    { label: 'Depth Map (Mask)', value: LensBlurSource.DepthMap },
+                  ]}
+                  value={lensBlur.source}
+                />
+                {lensBlur.source === LensBlurSource.DepthMap ? (
+                  <>
+                    <Dropdown
+                      onChange={(value: string) => handleLensBlurChange('depthMaskId', value)}
+                      options={depthMaskOptions}
+                      placeholder="Select a mask"
+                      value={lensBlur.depthMaskId ?? ''}
+                    />
+                    <Slider
+                      defaultValue={100}
+                      label="Focal Depth"
+                      max={100}
+                      min={0}
+                      onChange={(e: any) => handleLensBlurChange('focalDepth', parseInt(e.target.value, 10))}
+                      step={1}
+                      value={lensBlur.focalDepth}
+                    />
+                    <Slider
+                      defaultValue={10}
+                      label="Focus Range"
+                      max={100}
+                      min={0}
+                      onChange={(e: any) => handleLensBlurChange('focusRange', parseInt(e.target.value, 10))}
+                      step={1}
+                      value={lensBlur.focusRange}
+                    />
+                  </>
+                ) : (
+                  <>
+                    <Slider
+                      defaultValue={50}
+                      label="Center X"
+                      max={100}
+                      min={0}
+                      onChange={(e: any) => handleLensBlurChange('centerX', parseInt(e.target.value, 10) / 100)}
+                      step={1}
+                      value={Math.round(lensBlur.centerX * 100)}
+                    />
+                    <Slider
+                      defaultValue={50}
+                      label="Center Y"
+                      max={100}
+                      min={0}
+                      onChange={(e: any) => handleLensBlurChange('centerY', parseInt(e.target.value, 10) / 100)}
+                      step={1}
+                      value={Math.round(lensBlur.centerY * 100)}
+                    />
+                    <Slider
+                      defaultValue={20}
+                      label="Focus Radius"
+                      max={100}
+                      min={0}
+                      onChange={(e: any) => handleLensBlurChange('focusRadius', parseInt(e.target.value, 10))}
+                      step={1}
+                      value={lensBlur.focusRadius}
+                    />
+                  </>
+                )}
+                <Slider
+                  defaultValue={30}
+                  label="Feather"
+                  max={100}
+                  min={0}
+                  onChange={(e: any) => handleLensBlurChange('feather', parseInt(e.target.value, 10))}
+                  step={1}
+                  value={lensBlur.feather}
+                />
+                <Slider
+                  label="Aperture Blades"
+                  max={9}
+                  min={0}
+                  onChange={(e: any) => handleLensBlurChange('bladeCount', parseInt(e.target.value, 10))}
+                  step={1}
+                  value={lensBlur.bladeCount}
+                />
+                <Slider
+                  label="Blade Rotation"
+                  max={180}
+                  min={0}
+                  onChange={(e: any) => handleLensBlurChange('bladeRotation', parseInt(e.target.value, 10))}
+                  step={1}
+                  value={lensBlur.bladeRotation}
+                />
+                <Slider
+                  defaultValue={50}
+                  label="Bokeh Highlights"
+                  max={100}
+                  min={0}
+                  onChange={(e: any) => handleLensBlurChange('highlightBoost', parseInt(e.target.value, 10))}
+                  step={1}
+                  value={lensBlur.highlightBoost}
+                />
+              </div>
+            )}
+          </div>
+
           <div className="mb-4 p-2 bg-bg-tertiary rounded-md">
             <p className="text-md font-semibold mb-2 text-primary">Vignette</p>
             <Slider
@@ -144,6 +386,92 @@ export default function EffectsPanel({ adjustments, setAdjustments, isForMask =
               value={adjustments.grainRoughness}
             />
           </div>
+
+          <div className="mt-4 p-2 bg-bg-tertiary rounded-md">
+            <p className="text-md font-semibold mb-2 text-primary">Film</p>
+            <Slider
+              label="Halation"
+              max={100}
+              min={0}
+              onChange={(e: any) => handleAdjustmentChange(Effect.HalationAmount, e.target.value)}
+              step={1}
+              value={adjustments.halationAmount ?? 0}
+            />
+            <Slider
+              defaultValue={80}
+              label="Halation Threshold"
+              max={100}
+              min={0}
+              onChange={(e: any) => handleAdjustmentChange(Effect.HalationThreshold, e.target.value)}
+              step={1}
+              value={adjustments.halationThreshold ?? 80}
+            />
+            <div className="flex items-center justify-between mb-2">
+              <label htmlFor="halationColor" className="text-sm font-medium text-text-primary">
+                Halation Color
+              </label>
+              <input
+                className="p-0 h-8 w-12 border-none rounded-md cursor-pointer bg-bg-secondary"
+                id="halationColor"
+                onChange={(e: any) => handleColorChange(Effect.HalationColor, e.target.value)}
+                type="color"
+                value={adjustments.halationColor || '#ff3c14'}
+              />
+            </div>
+            <Slider
+              label="Bloom"
+              max={100}
+              min={0}
+              onChange={(e: any) => handleAdjustmentChange(Effect.BloomAmount, e.target.value)}
+              step={1}
+              value={adjustments.bloomAmount ?? 0}
+            />
+            <Slider
+              label="Red Grain"
+              max={100}
+              min={0}
+              onChange={(e: any) => handleAdjustmentChange(Effect.ColorGrainRed, e.target.value)}
+              step={1}
+              value={adjustments.colorGrainRed ?? 0}
+            />
+            <Slider
+              label="Green Grain"
+              max={100}
+              min={0}
+              onChange={(e: any) => handleAdjustmentChange(Effect.ColorGrainGreen, e.target.value)}
+              step={1}
+              value={adjustments.colorGrainGreen ?? 0}
+            />
+            <Slider
+              label="Blue Grain"
+              max={100}
+              min={0}
+              onChange={(e: any) => handleAdjustmentChange(Effect.ColorGrainBlue, e.target.value)}
+              step={1}
+              value={adjustments.colorGrainBlue ?? 0}
+            />
+            <Slider
+              defaultValue={25}
+              label="Color Grain Size"
+              max={100}
+              min={0}
+              onChange={(e: any) => handleAdjustmentChange(Effect.ColorGrainSize, e.target.value)}
+              step={1}
+              value={adjustments.colorGrainSize ?? 25}
+            />
+          </div>
+
+          <div className="mt-4 p-2 bg-bg-tertiary rounded-md">
+            <p className="text-md font-semibold mb-2 text-primary">Experimental</p>
+            <Slider
+              label="Custom Effect"
+              max={100}
+              min={0}
+              onChange={(e: any) => handleAdjustmentChange(Effect.CustomEffectAmount, e.target.value)}
+              step={1}
+              value={adjustments.customEffectAmount ?? 0}
+            />
+          </div>
         </>
       )}
     </div>
@@ -0,0 +1,199 @@
+import { useState, useEffect, useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Camera, RefreshCw } from 'lucide-react';
+import { Invokes } from '../ui/AppProperties';
+
+export interface CameraFile {
+  folder: string;
+  name: string;
+  number: number;
+  sizeKb: number | null;
+}
+
+interface CameraDevice {
+  model: string;
+  port: string;
+}
+
+interface CameraImportModalProps {
+  isOpen: boolean;
+  onClose(): void;
+  onContinue(port: string, files: Array<CameraFile>): void;
+}
+
+export default function CameraImportModal({ isOpen, onClose, onContinue }: CameraImportModalProps) {
+  const [isMounted, setIsMounted] = useState(false);
+  const [show, setShow] = useState(false);
+
+  const [devices, setDevices] = useState<Array<CameraDevice>>([]);
+  const [selectedPort, setSelectedPort] = useState<string | null>(null);
+  const [files, setFiles] = useState<Array<CameraFile>>([]);
+  const [selectedNumbers, setSelectedNumbers] = useState<Set<number>>(new Set());
+  const [isLoading, setIsLoading] = useState(false);
+  const [message, setMessage] = useState('');
+
+  const loadDevices = useCallback(() => {
+    setIsLoading(true);
+    setMessage('');
+    setSelectedPort(null);
+    setFiles([]);
+    invoke(Invokes.ListCameraDevices)
+      .then((list: any) => {
+        setDevices(list);
+        if (list.length === 0) {
+          setMessage('No cameras or phones detected. Make sure the device is unlocked and in file transfer mode.');
+        }
+      })
+      .catch((err) => setMessage(`Error: ${err}`))
+      .finally(() => setIsLoading(false));
+  }, []);
+
+  useEffect(() => {
+    if (isOpen) {
+      setIsMounted(true);
+      loadDevices();
+      const timer = setTimeout(() => setShow(true), 10);
+      return () => clearTimeout(timer);
+    } else {
+      setShow(false);
+      const timer = setTimeout(() => {
+        setIsMounted(false);
+      }, 300);
+      return () => clearTimeout(timer);
+    }
+  }, [isOpen, loadDevices]);
+
+  const handleSelectDevice = (port: string) => {
+    setSelectedPort(port);
+    setIsLoading(true);
+    setMessage('Reading files from device...');
+    invoke(Invokes.ListCameraFiles, { port })
+      .then((list: any) => {
+        setFiles(list);
+        setSelectedNumbers(new Set(list.map((file: CameraFile) => file.number)));
+        setMessage(list.length === 0 ? 'No supported images found on this device.' : '');
+      })
+      .catch((err) => setMessage(`Error: ${err}`))
+      .finally(() => setIsLoading(false));
+  };
+
+  const toggleFile = (number: number) => {
+    setSelectedNumbers((prev) => {
+      const next = new Set(prev);
+      if (next.has(number)) {
+        next.delete(number);
+      } else {
+        next.add(number);
+      }
+      return next;
+    });
+  };
+
+  const allSelected = files.length > 0 && selectedNumbers.size === files.length;
+
+  const handleContinue = () => {
+    if (!selectedPort || selectedNumbers.size === 0) {
+      return;
+    }
+    onContinue(
+      selectedPort,
+      files.filter((file) => selectedNumbers.has(file.number)),
+    );
+    onClose();
+  };
+
+  if (!isMounted) {
+    return null;
+  }
+
+  return (
+    <div
+      aria-modal="true"
+      className={`fixed inset-0 flex items-center justify-center z-50 bg-black/30 backdrop-blur-sm transition-opacity duration-300 ease-in-out ${
+        show ? 'opacity-100' : 'opacity-0'
+      }`}
+      onClick={onClose}
+      role="dialog"
+    >
+      <div
+        className={`bg-surface rounded-lg shadow-xl p-6 w-full max-w-lg transform transition-all duration-300 ease-out ${
+          show ? 'scale-100 opacity-100 translate-y-0' : 'scale-95 opacity-0 -translate-y-4'
+        }`}
+        onClick={(e: any) => e.stopPropagation()}
+      >
+        <div className="flex items-center justify-between mb-6">
+          <h3 className="text-lg font-semibold text-text-primary">Import from Device</h3>
+          <button
+            className="p-1 text-text-secondary hover:text-text-primary disabled:opacity-50"
+            disabled={isLoading}
+            onClick={loadDevices}
+            title="Rescan devices"
+          >
+            <RefreshCw size={16} />
+          </button>
+        </div>
+
+        <div className="space-y-4 text-sm">
+          <div className="flex flex-col gap-2">
+            {devices.map((device) => (
+              <button
+                className={`flex items-center gap-2 p-2 rounded-md text-left transition-colors ${
+                  selectedPort === device.port ? 'bg-card-active' : 'bg-bg-primary hover:bg-card-active'
+                }`}
+                disabled={isLoading}
+                key={device.port}
+                onClick={() => handleSelectDevice(device.port)}
+              >
+                <Camera size={16} />
+                <span className="text-text-primary">{device.model}</span>
+                <span className="text-xs text-text-secondary ml-auto">{device.port}</span>
+              </button>
+            ))}
+          </div>
+
+          {files.length > 0 && (
+            <div>
+              <label className="flex items-center gap-2 mb-2 text-text-primary">
+                <input
+                  checked={allSelected}
+                  onChange={() =>
+                    setSelectedNumbers(allSelected ? new Set() : new Set(files.map((file) => file.number)))
+                  }
+                  type="checkbox"
+                />
+                {selectedNumbers.size} of {files.length} images selected
+              </label>
+              <div className="max-h-64 overflow-y-auto bg-bg-primary rounded-md p-2 space-y-1">
+                {files.map((file) => (
+                  <label className="flex items-center gap-2 text-xs text-text-secondary" key={file.number}>
+                    <input checked={selectedNumbers.has(file.number)} onChange={() => toggleFile(file.number)} type="checkbox" />
+                    <span className="text-text-primary">{file.name}</span>
+                    <span className="truncate">{file.folder}</span>
+                  </label>
+                ))}
+              </div>
+            </div>
+          )}
+
+          {message && <p className="text-xs text-text-secondary">{message}</p>}
+        </div>
+
+        <div className="flex justify-end gap-3 mt-8">
+          <button
+            className="px-4 py-2 rounded-md text-text-secondary hover:bg-surface transition-colors"
+            onClick={onClose}
+          >
+            Cancel
+          </button>
+          <button
+            className="px-4 py-2 rounded-md bg-accent shadow-shiny text-button-text font-semibold hover:bg-accent-hover transition-colors disabled:opacity-50"
+            disabled={selectedNumbers.size === 0 || isLoading}
+            onClick={handleContinue}
+          >
+            Continue
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,210 @@
+import { useState } from 'react';
+import { Trash2 } from 'lucide-react';
+import Button from '../ui/Button';
+import Dropdown, { OptionItem } from '../ui/Dropdown';
+
+export interface ControlPoint {
+  imageA: number;
+  imageB: number;
+  xA: number;
+  xB: number;
+  yA: number;
+  yB: number;
+}
+
+export interface ControlPointImage {
+  height: number;
+  keypoints: Array<[number, number]>;
+  path: string;
+  width: number;
+}
+
+export interface ControlPointRequest {
+  images: Array<ControlPointImage>;
+  matchedPairs: Array<{ imageA: number; imageB: number; inliers: number }>;
+  message: string;
+}
+
+interface ControlPointEditorProps {
+  onCancel(): void;
+  onStitch(controlPoints: Array<ControlPoint>): void;
+  request: ControlPointRequest;
+  thumbnails: Record<string, string>;
+}
+
+const MIN_POINTS_PER_PAIR = 4;
+const SNAP_DISTANCE = 0.02;
+
+function snapToKeypoint(image: ControlPointImage, x: number, y: number): [number, number] {
+  const maxDistance = SNAP_DISTANCE * Math.max(image.width, image.height);
+  let best: [number, number] = [x, y];
+  let bestDistance = maxDistance;
+  for (const [kx, ky] of image.keypoints) {
+    const distance = Math.hypot(kx - x, ky - y);
+    if (distance < bestDistance) {
+      bestDistance = distance;
+      best = [kx, ky];
+    }
+  }
+  return best;
+}
+
+export default function ControlPointEditor({ onCancel, onStitch, request, thumbnails }: ControlPointEditorProps) {
+  const [imageA, setImageA] = useState(0);
+  const [imageB, setImageB] = useState(Math.min(1, request.images.length - 1));
+  const [controlPoints, setControlPoints] = useState<Array<ControlPoint>>([]);
+  const [pendingPoint, setPendingPoint] = useState<[number, number] | null>(null);
+
+  const imageOptions: Array<OptionItem> = request.images.map((image: ControlPointImage, index: number) => ({
+    label: image.path.split(/[\\/]/).pop() || image.path,
+    value: index,
+  }));
+
+  const pairPoints = controlPoints.filter(
+    (cp: ControlPoint) =>
+      (cp.imageA === imageA && cp.imageB === imageB) || (cp.imageA === imageB && cp.imageB === imageA),
+  );
+  const pointsOn = (index: number): Array<[number, number]> =>
+    pairPoints.map((cp: ControlPoint) => (cp.imageA === index ? [cp.xA, cp.yA] : [cp.xB, cp.yB]));
+
+  const hasIncompletePair = () => {
+    const counts: Record<string, number> = {};
+    controlPoints.forEach((cp: ControlPoint) => {
+      const key = [cp.imageA, cp.imageB].sort().join('-');
+      counts[key] = (counts[key] || 0) + 1;
+    });
+    return Object.values(counts).some((count: number) => count < MIN_POINTS_PER_PAIR);
+  };
+
+  const handleImageClick = (e: any, index: number) => {
+    if (imageA === imageB) {
+      return;
+    }
+    const image = request.images[index];
+    const rect = e.currentTarget.getBoundingClientRect();
+    const [x, y] = snapToKeypoint(
+      image,
+      ((e.clientX - rect.left) / rect.width) * image.width,
+      ((e.clientY - rect.top) / rect.height) * image.height,
+    );
+
+    if (index === imageA) {
+      setPendingPoint([x, y]);
+    } else if (pendingPoint) {
+      setControlPoints((prev: Array<ControlPoint>) => [
+        ...prev,
+        { imageA, imageB, xA: pendingPoint[0], xB: x, yA: pendingPoint[1], yB: y },
+      ]);
+      setPendingPoint(null);
+    }
+  };
+
+  const renderImage = (index: number, isSource: boolean) => {
+    const image = request.images[index];
+    const points = pointsOn(index);
+    return (
+      <div
+        className="relative w-full bg-bg-primary rounded-md overflow-hidden cursor-crosshair"
+        onClick={(e: any) => handleImageClick(e, index)}
+        style={{ aspectRatio: `${image.width} / ${image.height}` }}
+      >
+        {thumbnails[image.path] && (
+          <img
+            alt={image.path}
+            className="w-full h-full object-fill select-none"
+            draggable={false}
+            src={thumbnails[image.path]}
+          />
+        )}
+        {image.keypoints.map(([x, y]: [number, number], i: number) => (
+          <div
+            className="absolute w-1 h-1 rounded-full bg-white/40 pointer-events-none"
+            key={`kp-${i}`}
+            style={{ left: `${(x / image.width) * 100}%`, top: `${(y / image.height) * 100}%` }}
+          />
+        ))}
+        {points.map(([x, y]: [number, number], i: number) => (
+          <div
+            className="absolute -translate-x-1/2 -translate-y-1/2 w-4 h-4 rounded-full bg-accent text-[9px] leading-4 text-center text-black font-bold pointer-events-none"
+            key={`cp-${i}`}
+            style={{ left: `${(x / image.width) * 100}%`, top: `${(y / image.height) * 100}%` }}
+          >
+            {i + 1}
+          </div>
+        ))}
+        {isSource && pendingPoint && (
+          <div
+            className="absolute -translate-x-1/2 -translate-y-1/2 w-4 h-4 rounded-full border-2 border-accent animate-pulse pointer-events-none"
+            style={{
+              left: `${(pendingPoint[0] / image.width) * 100}%`,
+              top: `${(pendingPoint[1] / image.height) * 100}%`,
+            }}
+          />
+        )}
+      </div>
+    );
+  };
+
+  return (
+    <div className="flex flex-col gap-4">
+      <div>
+        <h3 className="text-lg font-semibold text-text-primary mb-1">Align Manually</h3>
+        <p className="text-sm text-text-secondary">
+          {request.message} Click a point in the left image, then the same point in the right image. Add at least{' '}
+          {MIN_POINTS_PER_PAIR} points per pair.
+        </p>
+      </div>
+      <div className="grid grid-cols-2 gap-3">
+        <div className="flex flex-col gap-2">
+          <Dropdown
+            onChange={(value: number) => {
+              setImageA(value);
+              setPendingPoint(null);
+            }}
+            options={imageOptions}
+            value={imageA as any}
+          />
+          {renderImage(imageA, true)}
+        </div>
+        <div className="flex flex-col gap-2">
+          <Dropdown
+            onChange={(value: number) => {
+              setImageB(value);
+              setPendingPoint(null);
+            }}
+            options={imageOptions}
+            value={imageB as any}
+          />
+          {renderImage(imageB, false)}
+        </div>
+      </div>
+      <div className="flex items-center justify-between text-sm text-text-secondary">
+        <span>
+          {pairPoints.length} {pairPoints.length === 1 ? 'point' : 'points'} for this pair, {controlPoints.length}{' '}
+          total
+        </span>
+        <button
+          className="flex items-center gap-1 hover:text-text-primary transition-colors disabled:opacity-50"
+          disabled={pairPoints.length === 0}
+          onClick={() =>
+            setControlPoints((prev: Array<ControlPoint>) => prev.filter((cp: ControlPoint) => !pairPoints.includes(cp)))
+          }
+        >
+          <Trash2 size={14} />
+          Clear Pair
+        </button>
+      </div>
+      <div className="flex justify-end gap-3">
+        <button
+          className="px-4 py-2 rounded-md text-text-secondary hover:bg-card-active transition-colors"
+          onClick={onCancel}
+        >
+          Cancel
+        </button>
+        <Button disabled={controlPoints.length === 0 || hasIncompletePair()} onClick={() => onStitch(controlPoints)}>
+          Stitch with Control Points
+        </Button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,180 @@
+import { useState, useEffect, useCallback } from 'react';
+import { XCircle, Loader2, Save } from 'lucide-react';
+import Button from '../ui/Button';
+
+export interface MergeModalState {
+  error: string | null;
+  finalImageBase64: string | null;
+  isOpen: boolean;
+  progressMessage: string | null;
+  sourcePaths: Array<string>;
+}
+
+export const CLOSED_MERGE_MODAL_STATE: MergeModalState = {
+  error: null,
+  finalImageBase64: null,
+  isOpen: false,
+  progressMessage: '',
+  sourcePaths: [],
+};
+
+interface MergeModalProps {
+  error: string | null;
+  failedTitle: string;
+  finalImageBase64: string | null;
+  isOpen: boolean;
+  onClose(): void;
+  onOpenFile(path: string): void;
+  onSave(): Promise<string>;
+  progressMessage: string | null;
+  progressTitle: string;
+}
+
+export default function MergeModal({
+  error,
+  failedTitle,
+  finalImageBase64,
+  isOpen,
+  onClose,
+  onOpenFile,
+  onSave,
+  progressMessage,
+  progressTitle,
+}: MergeModalProps) {
+  const [isSaving, setIsSaving] = useState(false);
+  const [isMounted, setIsMounted] = useState(false);
+  const [show, setShow] = useState(false);
+
+  useEffect(() => {
+    if (isOpen) {
+      setIsMounted(true);
+      const timer = setTimeout(() => setShow(true), 10);
+      return () => clearTimeout(timer);
+    } else {
+      setShow(false);
+      const timer = setTimeout(() => {
+        setIsMounted(false);
+        setIsSaving(false);
+      }, 300);
+      return () => clearTimeout(timer);
+    }
+  }, [isOpen]);
+
+  const handleClose = useCallback(() => {
+    if (isSaving) {
+      return;
+    }
+    onClose();
+  }, [onClose, isSaving]);
+
+  const handleSave = async () => {
+    setIsSaving(true);
+    let path: string;
+    try {
+      path = await onSave();
+    } catch (e) {
+      setIsSaving(false);
+      return;
+    }
+    setIsSaving(false);
+    onOpenFile(path);
+    onClose();
+  };
+
+  const handleKeyDown = useCallback(
+    (e: any) => {
+      if (e.key === 'Escape') {
+        handleClose();
+      }
+    },
+    [handleClose],
+  );
+
+  const renderContent = () => {
+    if (error) {
+      return (
+        <>
+          <XCircle className="w-16 h-16 text-red-500 mx-auto mb-4" />
+          <h3 className="text-lg font-semibold text-text-primary mb-2 text-center">{failedTitle}</h3>
+          <p className="text-sm text-text-secondary text-center p-2 rounded-md max-h-40 overflow-y-auto">
+            {String(error)}
+          </p>
+        </>
+      );
+    }
+
+    if (finalImageBase64) {
+      return (
+        <div className="w-full bg-bg-primary rounded-md overflow-hidden border border-surface">
+          <img src={finalImageBase64} alt="Merged Result" className="w-full h-full object-contain" />
+        </div>
+      );
+    }
+
+    return (
+      <>
+        <div className="w-16 h-16 mx-auto mb-4">
+          <Loader2 className="w-16 h-16 text-accent animate-spin" />
+        </div>
+        <h3 className="text-lg font-semibold text-text-primary mb-2 text-center">{progressTitle}</h3>
+        <p className="text-sm text-text-secondary text-center min-h-[1.25rem]">{progressMessage}</p>
+      </>
+    );
+  };
+
+  const renderButtons = () => {
+    if (error) {
+      return (
+        <Button onClick={handleClose} className="w-full">
+          Close
+        </Button>
+      );
+    }
+    if (finalImageBase64) {
+      return (
+        <>
+          <button
+            onClick={handleClose}
+            className="px-4 py-2 rounded-md text-text-secondary hover:bg-card-active transition-colors"
+          >
+            Cancel
+          </button>
+          <Button onClick={handleSave} disabled={isSaving}>
+            {isSaving ? <Loader2 className="animate-spin mr-2" /> : <Save size={16} className="mr-2" />}
+            {isSaving ? 'Saving...' : 'Save & Edit'}
+          </Button>
+        </>
+      );
+    }
+    return null;
+  };
+
+  if (!isMounted) {
+    return null;
+  }
+
+  return (
+    <div
+      className={`fixed inset-0 flex items-center justify-center z-50 bg-black/30 backdrop-blur-sm transition-opacity duration-300 ease-in-out ${
+        show ? 'opacity-100' : 'opacity-0'
+      }`}
+      onClick={handleClose}
+      role="dialog"
+      aria-modal="true"
+    >
+      <div
+        className={`bg-surface rounded-lg shadow-xl p-6 w-full max-w-xl transform transition-all duration-300 ease-out ${
+          show ? 'scale-100 opacity-100 translate-y-0' : 'scale-95 opacity-0 -translate-y-4'
+        }`}
+        onClick={(e) => e.stopPropagation()}
+        onKeyDown={handleKeyDown}
+        tabIndex={-1}
+      >
+        <div className="flex flex-col">
+          {renderContent()}
+          <div className="mt-8 flex justify-end gap-3">{renderButtons()}</div>
+        </div>
+      </div>
+    </div>
+  );
+}
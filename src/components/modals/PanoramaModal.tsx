@@ -1,28 +1,103 @@
 import { useState, useEffect, useCallback } from 'react';
-import { CheckCircle, XCircle, Loader2, Save } from 'lucide-react';
+import { open } from '@tauri-apps/plugin-dialog';
+import { XCircle, Loader2, Save, Crosshair, FolderOpen } from 'lucide-react';
 import Button from '../ui/Button';
+import ControlPointEditor, { ControlPoint, ControlPointRequest } from './ControlPointEditor';
+import Dropdown, { OptionItem } from '../ui/Dropdown';
+import Switch from '../ui/Switch';
+
+export enum PanoramaBlendMode {
+  Feather = 'feather',
+  MultiBand = 'multiBand',
+}
+
+export enum PanoramaBoundary {
+  Crop = 'crop',
+  Fill = 'fill',
+  Keep = 'keep',
+}
+
+export enum PanoramaOutputFormat {
+  Dng = 'dng',
+  Jpeg = 'jpeg',
+  Png = 'png',
+  Tiff = 'tiff',
+}
+
+export enum PanoramaProjection {
+  Cylindrical = 'cylindrical',
+  Equirectangular = 'equirectangular',
+  Rectilinear = 'rectilinear',
+  Spherical = 'spherical',
+}
+
+export interface PanoramaOptions {
+  blendMode: PanoramaBlendMode;
+  boundary: PanoramaBoundary;
+  exposureCompensation: boolean;
+  projection: PanoramaProjection;
+}
+
+export const DEFAULT_PANORAMA_OPTIONS: PanoramaOptions = {
+  blendMode: PanoramaBlendMode.Feather,
+  boundary: PanoramaBoundary.Keep,
+  exposureCompensation: true,
+  projection: PanoramaProjection.Rectilinear,
+};
+
+const BOUNDARY_OPTIONS: Array<OptionItem> = [
+  { label: 'Keep Edges', value: PanoramaBoundary.Keep },
+  { label: 'Auto Crop', value: PanoramaBoundary.Crop },
+  { label: 'Fill Edges', value: PanoramaBoundary.Fill },
+];
+
+const OUTPUT_FORMAT_OPTIONS: Array<OptionItem> = [
+  { label: 'TIFF (16-bit)', value: PanoramaOutputFormat.Tiff },
+  { label: 'DNG', value: PanoramaOutputFormat.Dng },
+  { label: 'JPEG', value: PanoramaOutputFormat.Jpeg },
+  { label: 'PNG', value: PanoramaOutputFormat.Png },
+];
+
+const PROJECTION_OPTIONS: Array<OptionItem> = [
+  { label: 'Rectilinear', value: PanoramaProjection.Rectilinear },
+  { label: 'Cylindrical', value: PanoramaProjection.Cylindrical },
+  { label: 'Spherical', value: PanoramaProjection.Spherical },
+  { label: '360° Equirectangular', value: PanoramaProjection.Equirectangular },
+];
 
 interface PanoramaModalProps {
+  controlPointRequest: ControlPointRequest | null;
   error: string | null;
   finalImageBase64: string | null;
   isOpen: boolean;
   onClose(): void;
   onOpenFile(path: string): void;
-  onSave(): Promise<string>;
+  onOptionsChange(options: PanoramaOptions): void;
+  onSave(outputFormat: PanoramaOutputFormat, outputDir: string | null): Promise<string>;
+  onStitchWithControlPoints(controlPoints: Array<ControlPoint>): void;
+  options: PanoramaOptions;
   progressMessage: string | null;
+  thumbnails: Record<string, string>;
 }
 
 export default function PanoramaModal({
+  controlPointRequest,
   error,
   finalImageBase64,
   isOpen,
   onClose,
   onOpenFile,
+  onOptionsChange,
   onSave,
+  onStitchWithControlPoints,
+  options,
   progressMessage,
+  thumbnails,
 }: PanoramaModalProps) {
+  const [isEditingControlPoints, setIsEditingControlPoints] = useState(false);
   const [isSaving, setIsSaving] = useState(false);
-  const [savedPath, setSavedPath] = useState<string | null>(null);
+  const [outputFormat, setOutputFormat] = useState<PanoramaOutputFormat>(PanoramaOutputFormat.Tiff);
+  const [outputDir, setOutputDir] = useState<string | null>(null);
   const [isMounted, setIsMounted] = useState(false);
   const [show, setShow] = useState(false);
 
@@ -35,8 +110,8 @@ export default function PanoramaModal({
       setShow(false);
       const timer = setTimeout(() => {
         setIsMounted(false);
+        setIsEditingControlPoints(false);
         setIsSaving(false);
-        setSavedPath(null);
       }, 300);
       return () => clearTimeout(timer);
     }
@@ -51,20 +126,26 @@ export default function PanoramaModal({
 
   const handleSave = async () => {
     setIsSaving(true);
+    let path: string;
     try {
-      const path = await onSave();
-      setSavedPath(path);
+      path = await onSave(outputFormat, outputDir);
     } catch (e) {
-      // Error handling can be added here if needed
-    } finally {
       setIsSaving(false);
+      return;
     }
+    setIsSaving(false);
+    onOpenFile(path);
+    onClose();
   };
 
-  const handleOpen = () => {
-    if (savedPath) {
-      onOpenFile(savedPath);
-      handleClose();
+  const handleChooseDestination = async () => {
+    try {
+      const folder = await open({ directory: true, multiple: false, title: 'Select Panorama Destination' });
+      if (typeof folder === 'string') {
+        setOutputDir(folder);
+      }
+    } catch (err) {
+      console.error('Failed to select panorama destination:', err);
     }
   };
 
@@ -78,6 +159,20 @@ export default function PanoramaModal({
   );
 
   const renderContent = () => {
+    if (isEditingControlPoints && controlPointRequest) {
+      return (
+        <ControlPointEditor
+          onCancel={() => setIsEditingControlPoints(false)}
+          onStitch={(controlPoints: Array<ControlPoint>) => {
+            setIsEditingControlPoints(false);
+            onStitchWithControlPoints(controlPoints);
+          }}
+          request={controlPointRequest}
+          thumbnails={thumbnails}
+        />
+      );
+    }
+
     if (error) {
       return (
         <>
@@ -93,15 +188,69 @@ export default function PanoramaModal({
     if (finalImageBase64) {
       return (
         <>
-          {savedPath && (
-            <>
-              <CheckCircle className="w-16 h-16 mx-auto mb-4" />
-              <h3 className="text-lg font-semibold text-text-primary mb-4 text-center">Panorama Saved!</h3>
-            </>
-          )}
           <div className="w-full bg-bg-primary rounded-md overflow-hidden border border-surface">
             <img src={finalImageBase64} alt="Stitched Panorama" className="w-full h-full object-contain" />
           </div>
+          <div className="mt-4 space-y-3">
+            <div className="flex items-center justify-between gap-4">
+              <span className="text-sm text-text-secondary">Projection</span>
+              <Dropdown
+                className="w-40"
+                onChange={(projection: PanoramaProjection) => onOptionsChange({ ...options, projection })}
+                options={PROJECTION_OPTIONS}
+                value={options.projection}
+              />
+            </div>
+            <div className="flex items-center justify-between gap-4">
+              <span className="text-sm text-text-secondary">Edges</span>
+              <Dropdown
+                className="w-40"
+                onChange={(boundary: PanoramaBoundary) => onOptionsChange({ ...options, boundary })}
+                options={BOUNDARY_OPTIONS}
+                value={options.boundary}
+              />
+            </div>
+            <Switch
+              checked={options.exposureCompensation}
+              disabled={isSaving}
+              label="Exposure Compensation"
+              onChange={(exposureCompensation: boolean) => onOptionsChange({ ...options, exposureCompensation })}
+            />
+            <Switch
+              checked={options.blendMode === PanoramaBlendMode.MultiBand}
+              disabled={isSaving}
+              label="High Quality Blending"
+              onChange={(checked: boolean) =>
+                onOptionsChange({
+                  ...options,
+                  blendMode: checked ? PanoramaBlendMode.MultiBand : PanoramaBlendMode.Feather,
+                })
+              }
+            />
+            <div className="flex items-center justify-between gap-4 pt-3 border-t border-surface">
+              <span className="text-sm text-text-secondary">Format</span>
+              <Dropdown
+                className="w-40"
+                onChange={(format: PanoramaOutputFormat) => setOutputFormat(format)}
+                options={OUTPUT_FORMAT_OPTIONS}
+                value={outputFormat}
+              />
+            </div>
+            <div className="flex items-center justify-between gap-4">
+              <span className="text-sm text-text-secondary">Destination</span>
+              <button
+                className="flex items-center gap-2 min-w-0 text-sm text-text-primary hover:text-accent transition-colors disabled:opacity-50"
+                disabled={isSaving}
+                onClick={handleChooseDestination}
+                title={outputDir || 'Next to the first source image'}
+              >
+                <span className="truncate max-w-[14rem]">
+                  {outputDir ? outputDir.split(/[\\/]/).pop() : 'Next to Source'}
+                </span>
+                <FolderOpen size={16} className="shrink-0" />
+              </button>
+            </div>
+          </div>
         </>
       );
     }
@@ -118,14 +267,10 @@ export default function PanoramaModal({
   };
 
   const renderButtons = () => {
-    if (error) {
-      return (
-        <Button onClick={handleClose} className="w-full">
-          Close
-        </Button>
-      );
+    if (isEditingControlPoints) {
+      return null;
     }
-    if (savedPath) {
+    if (error && controlPointRequest) {
       return (
         <>
           <button
@@ -134,10 +279,20 @@ export default function PanoramaModal({
           >
             Close
           </button>
-          <Button onClick={handleOpen}>Open in Editor</Button>
+          <Button onClick={() => setIsEditingControlPoints(true)}>
+            <Crosshair size={16} className="mr-2" />
+            Align Manually
+          </Button>
         </>
       );
     }
+    if (error) {
+      return (
+        <Button onClick={handleClose} className="w-full">
+          Close
+        </Button>
+      );
+    }
     if (finalImageBase64) {
       return (
         <>
@@ -149,7 +304,7 @@ export default function PanoramaModal({
           </button>
           <Button onClick={handleSave} disabled={isSaving}>
             {isSaving ? <Loader2 className="animate-spin mr-2" /> : <Save size={16} className="mr-2" />}
-            {isSaving ? 'Saving...' : 'Save Panorama'}
+            {isSaving ? 'Saving...' : 'Save & Edit'}
           </Button>
         </>
       );
@@ -171,7 +326,9 @@ export default function PanoramaModal({
       aria-modal="true"
     >
       <div
-        className={`bg-surface rounded-lg shadow-xl p-6 w-full max-w-xl transform transition-all duration-300 ease-out ${
+        className={`bg-surface rounded-lg shadow-xl p-6 w-full ${
+          isEditingControlPoints ? 'max-w-4xl' : 'max-w-xl'
+        } transform transition-all duration-300 ease-out ${
           show ? 'scale-100 opacity-100 translate-y-0' : 'scale-95 opacity-0 -translate-y-4'
         }`}
         onClick={(e) => e.stopPropagation()}
@@ -180,7 +337,7 @@ export default function PanoramaModal({
       >
         <div className="flex flex-col">
           {renderContent()}
-          <div className="mt-8 flex justify-end gap-3">{renderButtons()}</div>
+          {!isEditingControlPoints && <div className="mt-8 flex justify-end gap-3">{renderButtons()}</div>}
         </div>
       </div>
     </div>
@@ -0,0 +1,171 @@
+import { useState, useEffect, useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Loader2, Search, User } from 'lucide-react';
+import { Invokes } from '../ui/AppProperties';
+
+interface FaceCluster {
+  faceCount: number;
+  id: number;
+  name: string | null;
+  sampleFace: [number, number, number, number];
+  samplePath: string;
+}
+
+interface PeopleModalProps {
+  isOpen: boolean;
+  onClose(): void;
+  onShowPerson(name: string): void;
+  thumbnails: Record<string, string>;
+}
+
+function faceCropStyle(thumbnail: string | undefined, [x1, y1, x2, y2]: [number, number, number, number]) {
+  if (!thumbnail) {
+    return {};
+  }
+  const width = Math.max(x2 - x1, 0.01);
+  const height = Math.max(y2 - y1, 0.01);
+  const position = (start: number, size: number) => (size >= 1 ? 0 : (start / (1 - size)) * 100);
+  return {
+    backgroundImage: `url(${thumbnail})`,
+    backgroundPosition: `${position(x1, width)}% ${position(y1, height)}%`,
+    backgroundSize: `${100 / width}% ${100 / height}%`,
+  };
+}
+
+export default function PeopleModal({ isOpen, onClose, onShowPerson, thumbnails }: PeopleModalProps) {
+  const [clusters, setClusters] = useState<Array<FaceCluster>>([]);
+  const [names, setNames] = useState<Record<number, string>>({});
+  const [isLoading, setIsLoading] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [isMounted, setIsMounted] = useState(false);
+  const [show, setShow] = useState(false);
+
+  const loadClusters = useCallback(async () => {
+    setIsLoading(true);
+    setError(null);
+    try {
+      const result: Array<FaceCluster> = await invoke(Invokes.GetFaceClusters);
+      setClusters(result);
+      setNames(Object.fromEntries(result.map((cluster: FaceCluster) => [cluster.id, cluster.name || ''])));
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setIsLoading(false);
+    }
+  }, []);
+
+  useEffect(() => {
+    if (isOpen) {
+      setIsMounted(true);
+      loadClusters();
+      const timer = setTimeout(() => setShow(true), 10);
+      return () => clearTimeout(timer);
+    } else {
+      setShow(false);
+      const timer = setTimeout(() => setIsMounted(false), 300);
+      return () => clearTimeout(timer);
+    }
+  }, [isOpen, loadClusters]);
+
+  const handleSaveName = async (cluster: FaceCluster) => {
+    const name = (names[cluster.id] || '').trim();
+    if (name === (cluster.name || '')) {
+      return;
+    }
+    try {
+      await invoke(Invokes.NameFaceCluster, { clusterId: cluster.id, name: name || null });
+      await loadClusters();
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  if (!isMounted) {
+    return null;
+  }
+
+  return (
+    <div
+      aria-modal="true"
+      className={`
+        fixed inset-0 flex items-center justify-center z-50
+        bg-black/30 backdrop-blur-sm
+        transition-opacity duration-300 ease-in-out
+        ${show ? 'opacity-100' : 'opacity-0'}
+      `}
+      onClick={onClose}
+      role="dialog"
+    >
+      <div
+        className={`
+          bg-surface rounded-lg shadow-xl p-6 w-full max-w-2xl max-h-[80vh] flex flex-col
+          transform transition-all duration-300 ease-out
+          ${show ? 'scale-100 opacity-100 translate-y-0' : 'scale-95 opacity-0 -translate-y-4'}
+        `}
+        onClick={(e: any) => e.stopPropagation()}
+      >
+        <h3 className="text-lg font-semibold text-text-primary mb-4">People</h3>
+        {error && <p className="text-sm text-red-400 mb-3">{error}</p>}
+        <div className="flex-1 overflow-y-auto">
+          {isLoading && clusters.length === 0 ? (
+            <div className="flex items-center justify-center py-10 text-text-secondary">
+              <Loader2 className="w-5 h-5 animate-spin mr-2" />
+              Loading faces...
+            </div>
+          ) : clusters.length === 0 ? (
+            <div className="flex flex-col items-center justify-center py-10 text-text-secondary text-center">
+              <User className="w-10 h-10 mb-3" />
+              <p className="text-sm">No faces found yet. Run "Scan for Faces" on a folder to group people.</p>
+            </div>
+          ) : (
+            <div className="grid grid-cols-3 gap-3">
+              {clusters.map((cluster: FaceCluster) => (
+                <div className="bg-bg-primary rounded-md p-2 flex flex-col gap-2" key={cluster.id}>
+                  <div
+                    className="aspect-square rounded-md bg-surface bg-no-repeat"
+                    style={faceCropStyle(thumbnails[cluster.samplePath], cluster.sampleFace)}
+                  />
+                  <input
+                    className="w-full bg-surface text-text-primary text-sm border border-border rounded-md px-2 py-1 focus:outline-none focus:ring-2 focus:ring-accent"
+                    onBlur={() => handleSaveName(cluster)}
+                    onChange={(e: any) => setNames((prev) => ({ ...prev, [cluster.id]: e.target.value }))}
+                    onKeyDown={(e: any) => {
+                      if (e.key === 'Enter') {
+                        e.target.blur();
+                      }
+                    }}
+                    placeholder="Add a name..."
+                    type="text"
+                    value={names[cluster.id] ?? ''}
+                  />
+                  <div className="flex items-center justify-between text-xs text-text-secondary">
+                    <span>
+                      {cluster.faceCount} {cluster.faceCount === 1 ? 'photo' : 'photos'}
+                    </span>
+                    {cluster.name && (
+                      <button
+                        className="flex items-center gap-1 hover:text-text-primary transition-colors"
+                        onClick={() => onShowPerson(cluster.name as string)}
+                      >
+                        <Search className="w-3 h-3" />
+                        Show
+                      </button>
+                    )}
+                  </div>
+                </div>
+              ))}
+            </div>
+          )}
+        </div>
+        <div className="flex justify-end gap-3 mt-5">
+          <button
+            className="px-4 py-2 rounded-md text-text-secondary hover:bg-surface transition-colors"
+            onClick={onClose}
+          >
+            Close
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
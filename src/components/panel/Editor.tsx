@@ -7,13 +7,24 @@ import { invoke } from '@tauri-apps/api/core';
 import debounce from 'lodash.debounce';
 import { AnimatePresence } from 'framer-motion';
 import { ImageDimensions, useImageRenderSize } from '../../hooks/useImageRenderSize';
-import { Adjustments, AiPatch, Coord, MaskContainer } from '../../utils/adjustments';
+import { Adjustments, AiPatch, Coord, MaskContainer, SpotEdit } from '../../utils/adjustments';
 import FullScreenViewer from './editor/FullScreenViewer';
 import EditorToolbar from './editor/EditorToolbar';
 import ImageCanvas from './editor/ImageCanvas';
 import Waveform from './editor/Waveform';
-import { Mask, SubMask } from './right/Masks';
-import { BrushSettings, Invokes, Panel, SelectedImage, TransformState, WaveformData } from '../ui/AppProperties';
+import { Mask, SamPoint, SubMask } from './right/Masks';
+import { SpotSettings } from './right/SpotRemovalPanel';
+import {
+  BrushSettings,
+  Invokes,
+  Panel,
+  RegionPreview,
+  SelectedImage,
+  TransformState,
+  WaveformData,
+} from '../ui/AppProperties';
+
+const REGION_MARGIN = 64;
 
 interface EditorProps {
   activeAiPatchContainerId: string | null;
@@ -25,6 +36,8 @@ interface EditorProps {
   brushSettings: BrushSettings | null;
   canRedo: boolean;
   canUndo: boolean;
+  clippingOverlayUrl: string | null;
+  comparisonUrl: string | null;
   finalPreviewUrl: string | null;
   fullScreenUrl: string | null;
   isAdjusting: boolean;
@@ -34,10 +47,11 @@ interface EditorProps {
   isMaskControlHovered: boolean;
   isStraightenActive: boolean;
   isWaveformVisible: boolean;
+  isWbPickerActive: boolean;
   onBackToLibrary(): void;
   onCloseWaveform(): void;
   onContextMenu(event: any): void;
-  onGenerateAiMask(subMaskId: string, startPoint: Coord, endPoint: Coord): void;
+  onGenerateAiMask(subMaskId: string, startPoint: Coord, endPoint: Coord, points?: Array<SamPoint>): void;
   onQuickErase(subMaskId: string | null, startPoint: Coord, endpoint: Coord): void;
   onRedo(): void;
   onSelectAiSubMask(id: string | null): void;
@@ -46,12 +60,14 @@ interface EditorProps {
   onToggleFullScreen(): void;
   onToggleWaveform(): void;
   onUndo(): void;
+  onWhiteBalancePick(x: number, y: number): void;
   onZoomed(state: TransformState): void;
   renderedRightPanel: Panel | null;
   selectedImage: SelectedImage;
   setAdjustments(adjustments: Partial<Adjustments>): void;
   setShowOriginal(show: any): void;
   showOriginal: boolean;
+  spotSettings: SpotSettings;
   targetZoom: number;
   thumbnails: Record<string, string>;
   transformWrapperRef: any;
@@ -63,8 +79,6 @@ interface EditorProps {
   onInitialFitScale?(scale: number): void;
   originalSize?: ImageDimensions;
   isFullResolution?: boolean;
-  fullResolutionUrl?: string | null;
-  isLoadingFullRes?: boolean;
 }
 
 export default function Editor({
@@ -77,6 +91,8 @@ export default function Editor({
   brushSettings,
   canRedo,
   canUndo,
+  clippingOverlayUrl,
+  comparisonUrl,
   finalPreviewUrl,
   fullScreenUrl,
   isAdjusting,
@@ -86,6 +102,7 @@ export default function Editor({
   isMaskControlHovered,
   isStraightenActive,
   isWaveformVisible,
+  isWbPickerActive,
   onBackToLibrary,
   onCloseWaveform,
   onContextMenu,
@@ -98,11 +115,13 @@ export default function Editor({
   onToggleFullScreen,
   onToggleWaveform,
   onUndo,
+  onWhiteBalancePick,
   onZoomed,
   selectedImage,
   setAdjustments,
   setShowOriginal,
   showOriginal,
+  spotSettings,
   targetZoom,
   thumbnails,
   transformWrapperRef,
@@ -114,15 +133,18 @@ export default function Editor({
   onInitialFitScale,
   originalSize,
   isFullResolution,
-  fullResolutionUrl,
-  isLoadingFullRes,
 }: EditorProps) {
   const [crop, setCrop] = useState<Crop | null>(null);
   const prevCropParams = useRef<any>(null);
   const [isMaskHovered, setIsMaskHovered] = useState(false);
   const [isLoaderVisible, setIsLoaderVisible] = useState(false);
   const [maskOverlayUrl, setMaskOverlayUrl] = useState<string | null>(null);
+  const [isFocusPeakingVisible, setIsFocusPeakingVisible] = useState(false);
+  const [focusPeakingUrl, setFocusPeakingUrl] = useState<string | null>(null);
   const [transformState, setTransformState] = useState<TransformState>({ scale: 1, positionX: 0, positionY: 0 });
+  const [regionPreview, setRegionPreview] = useState<RegionPreview | null>(null);
+  const [isRenderingRegion, setIsRenderingRegion] = useState(false);
+  const regionRequestRef = useRef<any>(null);
   const imageContainerRef = useRef<HTMLImageElement>(null);
   const isInitialMount = useRef(true);
   const transformStateRef = useRef<TransformState>(transformState);
@@ -140,6 +162,15 @@ export default function Editor({
     };
   }, [maskOverlayUrl]);
 
+  useEffect(() => {
+    const currentUrl = regionPreview?.url;
+    return () => {
+      if (currentUrl) {
+        URL.revokeObjectURL(currentUrl);
+      }
+    };
+  }, [regionPreview]);
+
   useEffect(() => {
     if (!transformWrapperRef.current) {
       return;
@@ -200,6 +231,7 @@ export default function Editor({
   const isCropping = activeRightPanel === Panel.Crop;
   const isMasking = activeRightPanel === Panel.Masks;
   const isAiEditing = activeRightPanel === Panel.Ai;
+  const isSpotRemoving = activeRightPanel === Panel.SpotRemoval;
 
   const hasDisplayableImage = finalPreviewUrl || selectedImage.originalUrl || selectedImage.thumbnailUrl;
   const showSpinner = isLoading && !hasDisplayableImage;
@@ -253,6 +285,104 @@ export default function Editor({
     }
   }, [imageRenderSize.scale, onInitialFitScale]);
 
+  const visualAdjustmentsKey = useMemo(() => {
+    const { rating, sectionVisibility, ...visualAdjustments } = adjustments;
+    return JSON.stringify(visualAdjustments);
+  }, [adjustments]);
+
+  const debouncedRenderRegion = useCallback(
+    debounce((currentAdjustments: Adjustments, region: Omit<RegionPreview, 'url'>) => {
+      if (regionRequestRef.current) {
+        regionRequestRef.current.cancelled = true;
+      }
+      const request = { cancelled: false };
+      regionRequestRef.current = request;
+      setIsRenderingRegion(true);
+
+      invoke(Invokes.RenderRegion, { jsAdjustments: currentAdjustments, ...region })
+        .then((imageData: Uint8Array) => {
+          if (!request.cancelled) {
+            const blob = new Blob([imageData], { type: 'image/jpeg' });
+            setRegionPreview({ ...region, url: URL.createObjectURL(blob) });
+          }
+        })
+        .catch((error: any) => {
+          if (!request.cancelled) {
+            console.error('Failed to render full resolution region:', error);
+          }
+        })
+        .finally(() => {
+          if (!request.cancelled) {
+            setIsRenderingRegion(false);
+          }
+        });
+    }, 250),
+    [],
+  );
+
+  const isRegionRenderingActive = !!isFullResolution && !isCropping && !showOriginal;
+
+  useEffect(() => {
+    setRegionPreview(null);
+  }, [selectedImage?.path, visualAdjustmentsKey]);
+
+  useEffect(() => {
+    const container = imageContainerRef.current;
+    if (!isRegionRenderingActive || !container || !croppedDimensions || !imageRenderSize.scale) {
+      debouncedRenderRegion.cancel();
+      if (regionRequestRef.current) {
+        regionRequestRef.current.cancelled = true;
+      }
+      setIsRenderingRegion(false);
+      setRegionPreview(null);
+      return;
+    }
+
+    const { scale, positionX, positionY } = transformState;
+    const toImageX = (x: number) => ((x - positionX) / scale - imageRenderSize.offsetX) / imageRenderSize.scale;
+    const toImageY = (y: number) => ((y - positionY) / scale - imageRenderSize.offsetY) / imageRenderSize.scale;
+
+    const left = Math.max(0, Math.floor(toImageX(0)) - REGION_MARGIN);
+    const top = Math.max(0, Math.floor(toImageY(0)) - REGION_MARGIN);
+    const right = Math.min(croppedDimensions.width, Math.ceil(toImageX(container.clientWidth)) + REGION_MARGIN);
+    const bottom = Math.min(croppedDimensions.height, Math.ceil(toImageY(container.clientHeight)) + REGION_MARGIN);
+    if (right <= left || bottom <= top) {
+      return;
+    }
+
+    debouncedRenderRegion(adjustments, { x: left, y: top, width: right - left, height: bottom - top });
+  }, [
+    isRegionRenderingActive,
+    transformState,
+    imageRenderSize,
+    croppedDimensions,
+    visualAdjustmentsKey,
+    debouncedRenderRegion,
+  ]);
+
+  useEffect(() => {
+    if (!isFocusPeakingVisible || !finalPreviewUrl) {
+      setFocusPeakingUrl(null);
+      return;
+    }
+    let isCancelled = false;
+    invoke(Invokes.GenerateFocusPeaking)
+      .then((dataUrl: any) => {
+        if (!isCancelled) {
+          setFocusPeakingUrl(dataUrl);
+        }
+      })
+      .catch((e) => {
+        console.error('Failed to generate focus peaking:', e);
+        if (!isCancelled) {
+          setFocusPeakingUrl(null);
+        }
+      });
+    return () => {
+      isCancelled = true;
+    };
+  }, [isFocusPeakingVisible, finalPreviewUrl]);
+
   const debouncedGenerateMaskOverlay = useCallback(
     debounce(async (maskDef, renderSize) => {
       if (!maskDef || !maskDef.visible || renderSize.width === 0) {
@@ -433,7 +563,7 @@ export default function Editor({
   const toggleShowOriginal = useCallback(() => setShowOriginal((prev: boolean) => !prev), [setShowOriginal]);
 
   const doubleClickProps: any = useMemo(() => {
-    if (isCropping || isMasking || isAiEditing) {
+    if (isCropping || isMasking || isAiEditing || isSpotRemoving) {
       return {
         disabled: true,
       };
@@ -443,7 +573,7 @@ export default function Editor({
       animationType: 'easeOut',
       mode: transformState.scale >= 2 ? 'reset' : 'zoomIn',
     };
-  }, [isCropping, isMasking, isAiEditing, transformState.scale]);
+  }, [isCropping, isMasking, isAiEditing, isSpotRemoving, transformState.scale]);
 
   if (!selectedImage) {
     return (
@@ -469,16 +599,33 @@ export default function Editor({
     return null;
   }, [adjustments.masks, adjustments.aiPatches, activeMaskId, activeAiSubMaskId, isMasking, isAiEditing]);
 
+  const handleAddSpotEdit = useCallback(
+    (edit: SpotEdit) => {
+      setAdjustments((prev: Adjustments) => ({ ...prev, spotEdits: [...(prev.spotEdits || []), edit] }));
+    },
+    [setAdjustments],
+  );
+
   const isPanningDisabled =
     isMaskHovered ||
     isCropping ||
+    isSpotRemoving ||
     (isMasking && (activeSubMask?.type === Mask.Brush || activeSubMask?.type === Mask.AiSubject)) ||
     (isAiEditing &&
       (activeSubMask?.type === Mask.Brush ||
         activeSubMask?.type === Mask.AiSubject ||
         activeSubMask?.type === Mask.QuickEraser));
 
-  const waveFormData: WaveformData = waveform || { blue: [], green: [], height: 0, luma: [], red: [], width: 0 };
+  const waveFormData: WaveformData = waveform || {
+    blue: [],
+    green: [],
+    height: 0,
+    luma: [],
+    parade: { blue: [], channelWidth: 0, green: [], height: 0, red: [] },
+    red: [],
+    vectorscope: { counts: [], size: 0 },
+    width: 0,
+  };
 
   return (
     <>
@@ -498,18 +645,20 @@ export default function Editor({
         <EditorToolbar
           canRedo={canRedo}
           canUndo={canUndo}
+          isFocusPeakingVisible={isFocusPeakingVisible}
           isFullScreenLoading={isFullScreenLoading}
           isLoading={isLoading}
           isWaveformVisible={isWaveformVisible}
           onBackToLibrary={onBackToLibrary}
           onRedo={onRedo}
+          onToggleFocusPeaking={() => setIsFocusPeakingVisible((visible) => !visible)}
           onToggleFullScreen={onToggleFullScreen}
           onToggleShowOriginal={toggleShowOriginal}
           onToggleWaveform={onToggleWaveform}
           onUndo={onUndo}
           selectedImage={selectedImage}
           showOriginal={showOriginal}
-          isLoadingFullRes={isLoadingFullRes}
+          isLoadingFullRes={isRenderingRegion}
         />
 
         <div
@@ -555,8 +704,11 @@ export default function Editor({
                 activeMaskId={activeMaskId}
                 adjustments={adjustments}
                 brushSettings={brushSettings}
+                clippingOverlayUrl={clippingOverlayUrl}
+                comparisonUrl={comparisonUrl}
                 crop={crop}
                 finalPreviewUrl={finalPreviewUrl}
+                focusPeakingUrl={focusPeakingUrl}
                 handleCropComplete={handleCropComplete}
                 imageRenderSize={imageRenderSize}
                 isAdjusting={isAdjusting}
@@ -564,23 +716,26 @@ export default function Editor({
                 isCropping={isCropping}
                 isMaskControlHovered={isMaskControlHovered}
                 isMasking={isMasking}
+                isSpotRemoving={isSpotRemoving}
                 isStraightenActive={isStraightenActive}
+                isWbPickerActive={isWbPickerActive}
                 maskOverlayUrl={maskOverlayUrl}
+                onAddSpotEdit={handleAddSpotEdit}
                 onGenerateAiMask={onGenerateAiMask}
                 onQuickErase={onQuickErase}
                 onSelectAiSubMask={onSelectAiSubMask}
                 onSelectMask={onSelectMask}
                 onStraighten={onStraighten}
+                onWhiteBalancePick={onWhiteBalancePick}
+                regionPreview={regionPreview}
                 selectedImage={selectedImage}
                 setCrop={handleCropChange}
                 setIsMaskHovered={setIsMaskHovered}
                 showOriginal={showOriginal}
+                spotSettings={spotSettings}
                 transformedOriginalUrl={transformedOriginalUrl}
                 uncroppedAdjustedPreviewUrl={uncroppedAdjustedPreviewUrl}
                 updateSubMask={updateSubMask}
-                fullResolutionUrl={fullResolutionUrl}
-                isFullResolution={isFullResolution}
-                isLoadingFullRes={isLoadingFullRes}
               />
             </TransformComponent>
           </TransformWrapper>
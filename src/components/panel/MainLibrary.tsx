@@ -3,17 +3,21 @@ import { getVersion } from '@tauri-apps/api/app';
 import { invoke } from '@tauri-apps/api/core';
 import {
   AlertTriangle,
+  Ban,
   Check,
+  Flag,
   Folder,
   FolderInput,
   Home,
   Image as ImageIcon,
+  Layers,
   Loader2,
   RefreshCw,
   Settings,
   SlidersHorizontal,
   Star as StarIcon,
   Search,
+  Video,
 } from 'lucide-react';
 import { motion, AnimatePresence } from 'framer-motion';
 import { FixedSizeGrid as Grid } from 'react-window';
@@ -23,9 +27,14 @@ import SettingsPanel from './SettingsPanel';
 import { ThemeProps, THEMES, DEFAULT_THEME_ID } from '../../utils/themes';
 import {
   AppSettings,
+  ExifFilterOptions as ExifFilterOptionsData,
   FilterCriteria,
+  FlagStatus,
+  hasExifFilter,
   ImageFile,
+  ImageFlag,
   Invokes,
+  StackInfo,
   Progress,
   RawStatus,
   SortCriteria,
@@ -33,6 +42,7 @@ import {
   SupportedTypes,
   ThumbnailSize,
   ThumbnailAspectRatio,
+  VideoMetadata,
 } from '../ui/AppProperties';
 import { Color, COLOR_LABELS } from '../../utils/adjustments';
 import { ImportState, Status } from './right/ExportImportProperties';
@@ -51,6 +61,19 @@ interface DropdownMenuProps {
   contentClassName: string;
 }
 
+interface ExifFilterOptionsProps extends FilterOptionProps {
+  currentFolderPath: string | null;
+}
+
+interface ExifRangeInputProps {
+  label: string;
+  max?: number | string | null;
+  min?: number | string | null;
+  onChange(min: number | string | null, max: number | string | null): void;
+  placeholder: [number | string, number | string] | null;
+  type: string;
+}
+
 interface FilterOptionProps {
   filterCriteria: FilterCriteria;
   setFilterCriteria(criteria: any): void;
@@ -116,13 +139,16 @@ interface SortOptionsProps {
 
 interface ThumbnailProps {
   data: any;
+  flag?: ImageFlag | null;
   isActive: boolean;
   isSelected: boolean;
+  isVideo: boolean;
   onContextMenu(e: any): void;
   onImageClick(path: string, event: any): void;
   onImageDoubleClick(path: string): void;
   path: string;
   rating: number;
+  stack?: StackInfo | null;
   tags: Array<string>;
   aspectRatio: ThumbnailAspectRatio;
 }
@@ -149,6 +175,7 @@ interface ThumbnailAspectRatioProps {
 }
 
 interface ViewOptionsProps {
+  currentFolderPath: string | null;
   filterCriteria: FilterCriteria;
   onSelectSize(size: ThumbnailSize): any;
   onSelectAspectRatio(aspectRatio: ThumbnailAspectRatio): any;
@@ -177,6 +204,13 @@ const ratingFilterOptions: Array<KeyValueLabel> = [
   { value: 5, label: '5 only' },
 ];
 
+const flagStatusOptions: Array<KeyValueLabel> = [
+  { key: FlagStatus.All, label: 'All Images' },
+  { key: FlagStatus.Picked, label: 'Picked' },
+  { key: FlagStatus.Rejected, label: 'Rejected' },
+  { key: FlagStatus.Unflagged, label: 'Unflagged' },
+];
+
 const rawStatusOptions: Array<KeyValueLabel> = [
   { key: RawStatus.All, label: 'All Types' },
   { key: RawStatus.RawOnly, label: 'RAW Only' },
@@ -436,6 +470,135 @@ function ThumbnailAspectRatioOptions({ selectedAspectRatio, onSelectAspectRatio
   );
 }
 
+function ExifRangeInput({ label, max, min, onChange, placeholder, type }: ExifRangeInputProps) {
+  const parse = (value: string) => {
+    if (value === '') {
+      return null;
+    }
+    return type === 'number' ? Number(value) : value;
+  };
+
+  return (
+    <div className="flex items-center gap-2 px-3 py-1">
+      <span className="w-20 text-sm text-text-secondary">{label}</span>
+      <input
+        className="w-full text-sm bg-bg-primary border border-border-color rounded px-2 py-1 outline-none focus:ring-1 focus:ring-accent text-text-primary"
+        onChange={(e: any) => onChange(parse(e.target.value), max ?? null)}
+        placeholder={placeholder ? String(placeholder[0]) : 'Min'}
+        type={type}
+        value={min ?? ''}
+      />
+      <span className="text-text-secondary">–</span>
+      <input
+        className="w-full text-sm bg-bg-primary border border-border-color rounded px-2 py-1 outline-none focus:ring-1 focus:ring-accent text-text-primary"
+        onChange={(e: any) => onChange(min ?? null, parse(e.target.value))}
+        placeholder={placeholder ? String(placeholder[1]) : 'Max'}
+        type={type}
+        value={max ?? ''}
+      />
+    </div>
+  );
+}
+
+function ExifFilterOptions({ currentFolderPath, filterCriteria, setFilterCriteria }: ExifFilterOptionsProps) {
+  const [options, setOptions] = useState<ExifFilterOptionsData | null>(null);
+
+  useEffect(() => {
+    if (!currentFolderPath) {
+      setOptions(null);
+      return;
+    }
+    invoke(Invokes.GetFilterOptions, { folder: currentFolderPath })
+      .then((result: any) => setOptions(result))
+      .catch((err) => console.error('Failed to load EXIF filter options:', err));
+  }, [currentFolderPath]);
+
+  const toggleValue = (key: 'cameraModels' | 'lensModels', value: string) => {
+    setFilterCriteria((prev: FilterCriteria) => {
+      const current = prev[key] || [];
+      const next = current.includes(value) ? current.filter((v: string) => v !== value) : [...current, value];
+      return { ...prev, [key]: next };
+    });
+  };
+
+  const setRange = (minKey: keyof FilterCriteria, maxKey: keyof FilterCriteria) => (min: any, max: any) => {
+    setFilterCriteria((prev: FilterCriteria) => ({ ...prev, [minKey]: min, [maxKey]: max }));
+  };
+
+  const renderValueList = (title: string, key: 'cameraModels' | 'lensModels', values: Array<string>) => {
+    if (values.length === 0) {
+      return null;
+    }
+    return (
+      <div>
+        <div className="px-3 py-2 text-xs font-semibold text-text-secondary uppercase">{title}</div>
+        <div className="max-h-32 overflow-y-auto">
+          {values.map((value: string) => {
+            const isSelected = (filterCriteria[key] || []).includes(value);
+            return (
+              <button
+                className={`w-full text-left px-3 py-1.5 text-sm rounded-md flex items-center justify-between transition-colors duration-150 ${
+                  isSelected
+                    ? 'bg-card-active text-text-primary font-semibold'
+                    : 'text-text-primary hover:bg-bg-primary'
+                }`}
+                key={value}
+                onClick={() => toggleValue(key, value)}
+                role="menuitem"
+              >
+                <span className="truncate">{value}</span>
+                {isSelected && <Check size={16} />}
+              </button>
+            );
+          })}
+        </div>
+      </div>
+    );
+  };
+
+  return (
+    <div className="space-y-2">
+      {renderValueList('Filter by Camera', 'cameraModels', options?.cameraModels || [])}
+      {renderValueList('Filter by Lens', 'lensModels', options?.lensModels || [])}
+      <div>
+        <div className="px-3 py-2 text-xs font-semibold text-text-secondary uppercase">Filter by Exposure</div>
+        <ExifRangeInput
+          label="Focal (mm)"
+          max={filterCriteria.focalLengthMax}
+          min={filterCriteria.focalLengthMin}
+          onChange={setRange('focalLengthMin', 'focalLengthMax')}
+          placeholder={options?.focalLengthRange || null}
+          type="number"
+        />
+        <ExifRangeInput
+          label="ISO"
+          max={filterCriteria.isoMax}
+          min={filterCriteria.isoMin}
+          onChange={setRange('isoMin', 'isoMax')}
+          placeholder={options?.isoRange || null}
+          type="number"
+        />
+        <ExifRangeInput
+          label="Aperture"
+          max={filterCriteria.apertureMax}
+          min={filterCriteria.apertureMin}
+          onChange={setRange('apertureMin', 'apertureMax')}
+          placeholder={options?.apertureRange || null}
+          type="number"
+        />
+        <ExifRangeInput
+          label="Date"
+          max={filterCriteria.captureDateTo}
+          min={filterCriteria.captureDateFrom}
+          onChange={setRange('captureDateFrom', 'captureDateTo')}
+          placeholder={null}
+          type="date"
+        />
+      </div>
+    </div>
+  );
+}
+
 function FilterOptions({ filterCriteria, setFilterCriteria }: FilterOptionProps) {
   const handleRatingFilterChange = (rating: number | undefined) => {
     setFilterCriteria((prev: Partial<FilterCriteria>) => ({ ...prev, rating }));
@@ -445,6 +608,10 @@ function FilterOptions({ filterCriteria, setFilterCriteria }: FilterOptionProps)
     setFilterCriteria((prev: Partial<FilterCriteria>) => ({ ...prev, rawStatus }));
   };
 
+  const handleFlagStatusChange = (flagStatus: FlagStatus | undefined) => {
+    setFilterCriteria((prev: Partial<FilterCriteria>) => ({ ...prev, flagStatus }));
+  };
+
   return (
     <>
       <div className="space-y-4">
@@ -494,6 +661,28 @@ function FilterOptions({ filterCriteria, setFilterCriteria }: FilterOptionProps)
             );
           })}
         </div>
+
+        <div>
+          <div className="px-3 py-2 text-xs font-semibold text-text-secondary uppercase">Filter by Flag</div>
+          {flagStatusOptions.map((option: KeyValueLabel) => {
+            const isSelected = (filterCriteria.flagStatus || FlagStatus.All) === option.key;
+            return (
+              <button
+                className={`w-full text-left px-3 py-2 text-sm rounded-md flex items-center justify-between transition-colors duration-150 ${
+                  isSelected
+                    ? 'bg-card-active text-text-primary font-semibold'
+                    : 'text-text-primary hover:bg-bg-primary'
+                }`}
+                key={option.key}
+                onClick={() => handleFlagStatusChange(option.key as FlagStatus)}
+                role="menuitem"
+              >
+                <span>{option.label}</span>
+                {isSelected && <Check size={16} />}
+              </button>
+            );
+          })}
+        </div>
       </div>
       <div className="py-2"></div>
       <ColorFilterOptions filterCriteria={filterCriteria} setFilterCriteria={setFilterCriteria} />
@@ -526,6 +715,7 @@ function SortOptions({ sortCriteria, setSortCriteria }: SortOptionsProps) {
 }
 
 function ViewOptionsDropdown({
+  currentFolderPath,
   filterCriteria,
   onSelectSize,
   onSelectAspectRatio,
@@ -538,7 +728,9 @@ function ViewOptionsDropdown({
   const isFilterActive =
     filterCriteria.rating > 0 ||
     (filterCriteria.rawStatus && filterCriteria.rawStatus !== RawStatus.All) ||
-    (filterCriteria.colors && filterCriteria.colors.length > 0);
+    (filterCriteria.colors && filterCriteria.colors.length > 0) ||
+    (filterCriteria.flagStatus && filterCriteria.flagStatus !== FlagStatus.All) ||
+    hasExifFilter(filterCriteria);
 
   return (
     <DropdownMenu
@@ -561,8 +753,14 @@ function ViewOptionsDropdown({
             />
           </div>
         </div>
-        <div className="w-2/4 p-2 border-r border-border-color">
+        <div className="w-2/4 p-2 border-r border-border-color max-h-[70vh] overflow-y-auto">
           <FilterOptions filterCriteria={filterCriteria} setFilterCriteria={setFilterCriteria} />
+          <div className="py-2"></div>
+          <ExifFilterOptions
+            currentFolderPath={currentFolderPath}
+            filterCriteria={filterCriteria}
+            setFilterCriteria={setFilterCriteria}
+          />
         </div>
         <div className="w-1/4 p-2">
           <SortOptions sortCriteria={sortCriteria} setSortCriteria={setSortCriteria} />
@@ -572,19 +770,51 @@ function ViewOptionsDropdown({
   );
 }
 
+function formatVideoDetails(meta: VideoMetadata): string {
+  const parts = [];
+  if (meta.durationSeconds !== null) {
+    const total = Math.round(meta.durationSeconds);
+    parts.push(`${Math.floor(total / 60)}:${String(total % 60).padStart(2, '0')}`);
+  }
+  if (meta.width && meta.height) {
+    parts.push(`${meta.width}×${meta.height}`);
+  }
+  if (meta.frameRate) {
+    parts.push(`${Math.round(meta.frameRate * 100) / 100} fps`);
+  }
+  if (meta.videoCodec) {
+    parts.push(meta.videoCodec.toUpperCase());
+  }
+  return parts.join(' · ');
+}
+
 function Thumbnail({
   data,
+  flag,
   isActive,
   isSelected,
+  isVideo,
   onContextMenu,
   onImageClick,
   onImageDoubleClick,
   path,
   rating,
+  stack,
   tags,
   aspectRatio,
 }: ThumbnailProps) {
   const [isLoaded, setIsLoaded] = useState(false);
+  const [videoDetails, setVideoDetails] = useState<string | null>(null);
+
+  const loadVideoDetails = () => {
+    if (!isVideo || videoDetails !== null) {
+      return;
+    }
+    setVideoDetails('');
+    invoke(Invokes.GetVideoMetadata, { path })
+      .then((meta: any) => setVideoDetails(formatVideoDetails(meta)))
+      .catch((err) => console.error('Failed to read video metadata:', err));
+  };
 
   useEffect(() => {
     if (data) {
@@ -601,7 +831,7 @@ function Thumbnail({
   const colorLabel = COLOR_LABELS.find((c: Color) => c.name === colorTag);
 
   const imageClasses = `w-full h-full group-hover:scale-[1.02] transition ease-in-out duration-300 ${
-    isLoaded ? 'opacity-100' : 'opacity-0'
+    isLoaded ? (flag === ImageFlag.Reject ? 'opacity-40' : 'opacity-100') : 'opacity-0'
   }`;
 
   return (
@@ -613,7 +843,8 @@ function Thumbnail({
       }}
       onContextMenu={onContextMenu}
       onDoubleClick={() => onImageDoubleClick(path)}
-      title={path.split(/[\\/]/).pop()}
+      onMouseEnter={loadVideoDetails}
+      title={[path.split(/[\\/]/).pop(), videoDetails].filter(Boolean).join('\n')}
     >
       {data ? (
         <>
@@ -638,6 +869,36 @@ function Thumbnail({
           <ImageIcon className="text-text-secondary animate-pulse" />
         </div>
       )}
+      {(flag || stack || isVideo) && (
+        <div className="absolute top-1.5 left-1.5 flex items-center gap-1">
+          {isVideo && (
+            <div className="bg-bg-primary/50 rounded-full p-1 text-text-primary backdrop-blur-sm" title="Video">
+              <Video size={12} />
+            </div>
+          )}
+          {flag && (
+            <div
+              className="bg-bg-primary/50 rounded-full p-1 text-text-primary backdrop-blur-sm"
+              title={flag === ImageFlag.Pick ? 'Picked' : 'Rejected'}
+            >
+              {flag === ImageFlag.Pick ? (
+                <Flag size={12} className="text-accent fill-accent" />
+              ) : (
+                <Ban size={12} className="text-red-400" />
+              )}
+            </div>
+          )}
+          {stack && (
+            <div
+              className="bg-bg-primary/50 rounded-full px-1.5 py-0.5 text-xs text-text-primary flex items-center gap-1 backdrop-blur-sm"
+              title={`Stack of ${stack.count} images`}
+            >
+              <Layers size={12} />
+              <span>{stack.count}</span>
+            </div>
+          )}
+        </div>
+      )}
       {(colorLabel || rating > 0) && (
         <div className="absolute top-1.5 right-1.5 bg-bg-primary/50 rounded-full px-1.5 py-0.5 text-xs text-text-primary flex items-center gap-1 backdrop-blur-sm">
           {colorLabel && (
@@ -674,6 +935,7 @@ const Cell = ({ columnIndex, rowIndex, style, data }: CellProps) => {
     onImageDoubleClick,
     thumbnails,
     thumbnailAspectRatio,
+    videoExtensions,
   } = data;
   const index = rowIndex * columnCount + columnIndex;
   if (index >= imageList.length) {
@@ -681,6 +943,7 @@ const Cell = ({ columnIndex, rowIndex, style, data }: CellProps) => {
   }
 
   const imageFile = imageList[index];
+  const extension = imageFile.path.split('.').pop()?.toLowerCase() || '';
 
   return (
     <div style={style}>
@@ -693,13 +956,16 @@ const Cell = ({ columnIndex, rowIndex, style, data }: CellProps) => {
       >
         <Thumbnail
           data={thumbnails[imageFile.path]}
+          flag={imageFile.flag}
           isActive={activePath === imageFile.path}
           isSelected={multiSelectedPaths.includes(imageFile.path)}
+          isVideo={videoExtensions.includes(extension)}
           onContextMenu={(e: any) => onContextMenu(e, imageFile.path)}
           onImageClick={onImageClick}
           onImageDoubleClick={onImageDoubleClick}
           path={imageFile.path}
           rating={imageRatings?.[imageFile.path] || 0}
+          stack={imageFile.stack}
           tags={imageFile.tags}
           aspectRatio={thumbnailAspectRatio}
         />
@@ -928,6 +1194,7 @@ export default function MainLibrary({
             setSearchQuery={setSearchQuery}
           />
           <ViewOptionsDropdown
+            currentFolderPath={currentFolderPath}
             filterCriteria={filterCriteria}
             onSelectSize={onThumbnailSizeChange}
             onSelectAspectRatio={onThumbnailAspectRatioChange}
@@ -985,6 +1252,7 @@ export default function MainLibrary({
                     onImageDoubleClick,
                     thumbnails,
                     thumbnailAspectRatio,
+                    videoExtensions: supportedTypes?.video || [],
                   }}
                   key={`${sortCriteria.key}-${sortCriteria.order}-${filterCriteria.rating}-${
                     filterCriteria.rawStatus || RawStatus.All
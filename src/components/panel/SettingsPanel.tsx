@@ -1,6 +1,8 @@
 import { useEffect, useState } from 'react';
-import { ArrowLeft, Trash2, Wifi, WifiOff } from 'lucide-react';
+import { ArrowLeft, FileDown, FileUp, FolderOpen, FolderPlus, Plus, Send, Trash2, Upload, Wifi, WifiOff } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { open } from '@tauri-apps/plugin-dialog';
 import { relaunch } from '@tauri-apps/plugin-process';
 import Button from '../ui/Button';
 import ConfirmModal from '../modals/ConfirmModal';
@@ -8,7 +10,17 @@ import Dropdown, { OptionItem } from '../ui/Dropdown';
 import Switch from '../ui/Switch';
 import Input from '../ui/Input';
 import { ThemeProps, THEMES, DEFAULT_THEME_ID } from '../../utils/themes';
-import { Invokes } from '../ui/AppProperties';
+import {
+  ComfyUiProfile,
+  ComfyUiProfileHealth,
+  DisplayProfileInfo,
+  HdrPreviewMode,
+  Invokes,
+  PublishService,
+  UploadKind,
+  UploadTarget,
+  WatchFolder,
+} from '../ui/AppProperties';
 
 interface ConfirmModalState {
   confirmText: string;
@@ -22,6 +34,7 @@ interface ConfirmModalState {
 interface DataActionItemProps {
   buttonAction(): void;
   buttonText: string;
+  children?: any;
   description: any;
   disabled?: boolean;
   icon: any;
@@ -55,6 +68,40 @@ interface TestStatus {
   testing: boolean;
 }
 
+const DEFAULT_HTTP_API_PORT = 8741;
+
+const DEVELOP_SOURCE_OPTIONS: Array<OptionItem> = [
+  { value: 'darktable', label: 'darktable (.xmp)' },
+  { value: 'captureOne', label: 'Capture One (.cos)' },
+];
+
+const UPLOAD_KIND_OPTIONS: Array<OptionItem> = [
+  { value: UploadKind.Sftp, label: 'SFTP' },
+  { value: UploadKind.S3, label: 'S3' },
+  { value: UploadKind.WebDav, label: 'WebDAV' },
+];
+
+const UPLOAD_FIELDS: Record<UploadKind, Array<{ key: string; placeholder: string; type?: string }>> = {
+  [UploadKind.Sftp]: [
+    { key: 'host', placeholder: 'Host' },
+    { key: 'port', placeholder: 'Port (22)', type: 'number' },
+    { key: 'username', placeholder: 'Username' },
+    { key: 'remoteDir', placeholder: 'Remote folder' },
+  ],
+  [UploadKind.S3]: [
+    { key: 'endpoint', placeholder: 'Endpoint, e.g. https://s3.eu-central-1.amazonaws.com' },
+    { key: 'region', placeholder: 'Region' },
+    { key: 'bucket', placeholder: 'Bucket' },
+    { key: 'prefix', placeholder: 'Key prefix (optional)' },
+    { key: 'accessKeyId', placeholder: 'Access key ID' },
+    { key: 'secretAccessKey', placeholder: 'Secret access key', type: 'password' },
+  ],
+  [UploadKind.WebDav]: [
+    { key: 'url', placeholder: 'Folder URL' },
+    { key: 'username', placeholder: 'Username (optional)' },
+    { key: 'password', placeholder: 'Password (optional)', type: 'password' },
+  ],
+};
 const EXECUTE_TIMEOUT = 3000;
 
 const resolutions: Array<OptionItem> = [
@@ -65,6 +112,34 @@ const resolutions: Array<OptionItem> = [
   { value: 3840, label: '3840px' },
 ];
 
+const hdrPreviewModes: Array<OptionItem> = [
+  { value: HdrPreviewMode.Off, label: 'Off' },
+  { value: HdrPreviewMode.Pq, label: 'PQ (HDR10)' },
+  { value: HdrPreviewMode.Hlg, label: 'HLG' },
+];
+
+const prefetchCounts: Array<OptionItem> = [
+  { value: 0, label: 'Off' },
+  { value: 1, label: '1 image' },
+  { value: 2, label: '2 images' },
+  { value: 3, label: '3 images' },
+];
+
+const memoryBudgets: Array<OptionItem> = [
+  { value: 2048, label: '2 GB' },
+  { value: 4096, label: '4 GB' },
+  { value: 8192, label: '8 GB' },
+  { value: 16384, label: '16 GB' },
+];
+
+const exportWorkerCounts: Array<OptionItem> = [
+  { value: 0, label: 'Auto' },
+  { value: 1, label: '1 image' },
+  { value: 2, label: '2 images' },
+  { value: 4, label: '4 images' },
+  { value: 8, label: '8 images' },
+];
+
 const KeybindItem = ({ keys, description }: KeybindItemProps) => (
   <div className="flex justify-between items-center py-2">
     <span className="text-text-secondary text-sm">{description}</span>
@@ -92,6 +167,7 @@ const SettingItem = ({ children, description, label }: SettingItemProps) => (
 const DataActionItem = ({
   buttonAction,
   buttonText,
+  children,
   description,
   disabled = false,
   icon,
@@ -102,6 +178,7 @@ const DataActionItem = ({
   <div className="pb-6 border-b border-border-color last:border-b-0 last:pb-0">
     <h3 className="font-medium text-text-primary mb-1">{title}</h3>
     <p className="text-xs text-text-secondary mb-3">{description}</p>
+    {children && <div className="mb-3">{children}</div>}
     <Button variant="destructive" onClick={buttonAction} disabled={isProcessing || disabled}>
       {icon}
       {isProcessing ? 'Processing...' : buttonText}
@@ -126,6 +203,27 @@ export default function SettingsPanel({
   const [isClearingTags, setIsClearingTags] = useState(false);
   const [tagsClearMessage, setTagsClearMessage] = useState('');
 
+  const [isImportingXmp, setIsImportingXmp] = useState(false);
+  const [xmpImportMessage, setXmpImportMessage] = useState('');
+
+  const [isWritingXmp, setIsWritingXmp] = useState(false);
+  const [xmpWriteMessage, setXmpWriteMessage] = useState('');
+
+  const [isImportingLightroom, setIsImportingLightroom] = useState(false);
+  const [lightroomImportMessage, setLightroomImportMessage] = useState('');
+  const [includeLightroomDevelop, setIncludeLightroomDevelop] = useState(false);
+  const [isImportingDevelop, setIsImportingDevelop] = useState(false);
+  const [developImportMessage, setDevelopImportMessage] = useState('');
+  const [developSource, setDevelopSource] = useState('darktable');
+
+  const [presetOptions, setPresetOptions] = useState<Array<OptionItem>>([]);
+  const [albumOptions, setAlbumOptions] = useState<Array<OptionItem>>([]);
+  const [exportPresetOptions, setExportPresetOptions] = useState<Array<OptionItem>>([]);
+  const [publishMessages, setPublishMessages] = useState<Record<string, string>>({});
+  const [profileHealth, setProfileHealth] = useState<Record<string, ComfyUiProfileHealth>>({});
+  const [isCheckingProfiles, setIsCheckingProfiles] = useState(false);
+  const [displayProfiles, setDisplayProfiles] = useState<Array<DisplayProfileInfo>>([]);
+
   const [confirmModalState, setConfirmModalState] = useState<ConfirmModalState>({
     confirmText: 'Confirm',
     confirmVariant: 'primary',
@@ -142,6 +240,13 @@ export default function SettingsPanel({
   });
 
   const [comfyUiAddress, setComfyUiAddress] = useState<string>(appSettings?.comfyuiAddress || '');
+  const [httpApiPort, setHttpApiPort] = useState<string>(String(appSettings?.httpApiPort || DEFAULT_HTTP_API_PORT));
+  const [customEffect, setCustomEffect] = useState<string>(appSettings?.customEffectShader || '');
+  const [customEffectStatus, setCustomEffectStatus] = useState<TestStatus>({
+    message: '',
+    success: null,
+    testing: false,
+  });
 
   useEffect(() => {
     if (appSettings?.comfyuiAddress !== comfyUiAddress) {
@@ -149,7 +254,217 @@ export default function SettingsPanel({
     }
   }, [appSettings?.comfyuiAddress]);
 
+  useEffect(() => {
+    invoke(Invokes.LoadPresets)
+      .then((items: any) => {
+        const presets = items.flatMap((item: any) => (item.preset ? [item.preset] : item.folder?.children || []));
+        setPresetOptions([
+          { value: '', label: 'No preset' },
+          ...presets.map((preset: any) => ({ value: preset.id, label: preset.name })),
+        ]);
+      })
+      .catch((err) => console.error('Failed to load presets:', err));
+    invoke(Invokes.LoadAlbums)
+      .then((albums: any) => setAlbumOptions(albums.map((album: any) => ({ value: album.id, label: album.name }))))
+      .catch((err) => console.error('Failed to load albums:', err));
+    invoke(Invokes.LoadExportPresets)
+      .then((presets: any) =>
+        setExportPresetOptions(presets.map((preset: any) => ({ value: preset.id, label: preset.name }))),
+      )
+      .catch((err) => console.error('Failed to load export presets:', err));
+    invoke(Invokes.ListDisplayProfiles)
+      .then((profiles: any) => setDisplayProfiles(profiles))
+      .catch((err) => console.error('Failed to list display profiles:', err));
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen('lightroom-import-progress', (event: any) => {
+      const { current, total } = event.payload;
+      setLightroomImportMessage(`Importing ${current} of ${total} images...`);
+    });
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen('publish-complete', (event: any) => {
+      const { serviceId, published, failed } = event.payload;
+      setPublishMessages((prev) => ({
+        ...prev,
+        [serviceId]: `${published} images published` + (failed > 0 ? `, ${failed} failed.` : '.'),
+      }));
+    });
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
   const effectiveRootPath = rootPath || appSettings?.lastRootPath;
+  const watchFolders: Array<WatchFolder> = appSettings?.watchFolders || [];
+
+  const updateWatchFolders = (folders: Array<WatchFolder>) => {
+    onSettingsChange({ ...appSettings, watchFolders: folders });
+  };
+
+  const updateWatchFolder = (index: number, changes: Partial<WatchFolder>) => {
+    updateWatchFolders(watchFolders.map((folder, i) => (i === index ? { ...folder, ...changes } : folder)));
+  };
+
+  const uploadTargets: Array<UploadTarget> = appSettings?.uploadTargets || [];
+
+  const updateUploadTargets = (targets: Array<UploadTarget>) => {
+    onSettingsChange({ ...appSettings, uploadTargets: targets });
+  };
+
+  const updateUploadTarget = (index: number, changes: Partial<UploadTarget>) => {
+    updateUploadTargets(uploadTargets.map((target, i) => (i === index ? { ...target, ...changes } : target)));
+  };
+
+  const updateUploadField = (index: number, field: { key: string; type?: string }, value: string) => {
+    const target = uploadTargets[index];
+    const parsed = field.type === 'number' ? parseInt(value) || null : value;
+    updateUploadTarget(index, { destination: { ...target.destination, [field.key]: parsed } });
+  };
+
+  const handleAddUploadTarget = () => {
+    updateUploadTargets([
+      ...uploadTargets,
+      { destination: { kind: UploadKind.WebDav }, id: crypto.randomUUID(), name: `Upload ${uploadTargets.length + 1}` },
+    ]);
+  };
+
+  const comfyUiProfiles: Array<ComfyUiProfile> = appSettings?.comfyuiProfiles || [];
+
+  const updateComfyUiProfiles = (profiles: Array<ComfyUiProfile>) => {
+    onSettingsChange({ ...appSettings, comfyuiProfiles: profiles });
+  };
+
+  const updateComfyUiProfile = (index: number, changes: Partial<ComfyUiProfile>) => {
+    updateComfyUiProfiles(comfyUiProfiles.map((profile, i) => (i === index ? { ...profile, ...changes } : profile)));
+  };
+
+  const handleAddComfyUiProfile = () => {
+    updateComfyUiProfiles([
+      ...comfyUiProfiles,
+      { address: '', id: crypto.randomUUID(), name: `Server ${comfyUiProfiles.length + 1}`, workflowsDir: null },
+    ]);
+  };
+
+  const handleSelectWorkflowsDir = async (index: number) => {
+    const dir = await open({ directory: true, multiple: false, title: 'Select Workflows Folder' });
+    if (typeof dir === 'string') {
+      updateComfyUiProfile(index, { workflowsDir: dir });
+    }
+  };
+
+  const handleSelectDisplayProfile = async () => {
+    const profilePath = await open({
+      filters: [{ name: 'ICC Profile', extensions: ['icc', 'icm'] }],
+      multiple: false,
+      title: 'Select Display Profile',
+    });
+    if (typeof profilePath === 'string') {
+      onSettingsChange({ ...appSettings, displayProfilePath: profilePath });
+    }
+  };
+
+  const displayProfileOptions: Array<OptionItem> = [
+    { value: '', label: 'None (sRGB)' },
+    ...displayProfiles.map((profile) => ({ value: profile.path, label: profile.description })),
+  ];
+  const displayProfilePath = appSettings?.displayProfilePath || '';
+  if (displayProfilePath && !displayProfiles.some((profile) => profile.path === displayProfilePath)) {
+    displayProfileOptions.push({ value: displayProfilePath, label: displayProfilePath.split(/[\\/]/).pop() ?? displayProfilePath });
+  }
+
+  const handleCheckProfiles = async () => {
+    setIsCheckingProfiles(true);
+    try {
+      const health: Array<ComfyUiProfileHealth> = await invoke(Invokes.CheckComfyuiProfiles);
+      setProfileHealth(Object.fromEntries(health.map((h) => [h.id, h])));
+    } catch (err) {
+      console.error('Failed to check ComfyUI servers:', err);
+    } finally {
+      setIsCheckingProfiles(false);
+    }
+  };
+
+  const renderProfileStatus = (id: string) => {
+    const health = profileHealth[id];
+    if (!health) {
+      return null;
+    }
+    return (
+      <span className={`text-xs flex items-center gap-1 ${health.online ? 'text-green-400' : 'text-red-400'}`}>
+        {health.online ? <Wifi size={14} /> : <WifiOff size={14} />}
+        {health.online ? `${health.latencyMs} ms` : 'Offline'}
+      </span>
+    );
+  };
+
+  const publishServices: Array<PublishService> = appSettings?.publishServices || [];
+
+  const updatePublishServices = (services: Array<PublishService>) => {
+    onSettingsChange({ ...appSettings, publishServices: services });
+  };
+
+  const updatePublishService = (index: number, changes: Partial<PublishService>) => {
+    updatePublishServices(publishServices.map((service, i) => (i === index ? { ...service, ...changes } : service)));
+  };
+
+  const handleAddPublishService = () => {
+    updatePublishServices([
+      ...publishServices,
+      {
+        albumId: null,
+        authToken: '',
+        id: crypto.randomUUID(),
+        name: `Publish ${publishServices.length + 1}`,
+        presetId: null,
+        url: '',
+      },
+    ]);
+  };
+
+  const handlePublish = async (service: PublishService) => {
+    setPublishMessages((prev) => ({ ...prev, [service.id]: 'Checking for changes...' }));
+    try {
+      const count: any = await invoke(Invokes.PublishToService, { serviceId: service.id });
+      setPublishMessages((prev) => ({
+        ...prev,
+        [service.id]: count > 0 ? `Exporting ${count} new or changed images...` : 'Everything is up to date.',
+      }));
+    } catch (err) {
+      console.error('Failed to publish:', err);
+      setPublishMessages((prev) => ({ ...prev, [service.id]: `Error: ${err}` }));
+    }
+  };
+
+  const handleAddWatchFolder = async () => {
+    const source = await open({ directory: true, multiple: false, title: 'Select Folder to Watch' });
+    if (typeof source !== 'string') {
+      return;
+    }
+    const destination = await open({ directory: true, multiple: false, title: 'Select Import Destination' });
+    if (typeof destination !== 'string') {
+      return;
+    }
+    updateWatchFolders([
+      ...watchFolders,
+      {
+        destination,
+        importSettings: {
+          dateFolderFormat: 'YYYY/MM-DD',
+          deleteAfterImport: false,
+          filenameTemplate: '{original_filename}',
+          organizeByDate: false,
+        },
+        presetId: null,
+        source,
+      },
+    ]);
+  };
 
   const executeClearSidecars = async () => {
     setIsClearing(true);
@@ -211,6 +526,117 @@ export default function SettingsPanel({
     });
   };
 
+  const handleImportXmp = async () => {
+    setIsImportingXmp(true);
+    setXmpImportMessage('Reading XMP sidecars...');
+    try {
+      const count = await invoke(Invokes.ImportXmpSidecars, { rootPath: effectiveRootPath });
+      setXmpImportMessage(`${count} images updated from XMP sidecars.`);
+      onLibraryRefresh();
+    } catch (err) {
+      console.error('Failed to import XMP sidecars:', err);
+      setXmpImportMessage(`Error: ${err}`);
+    } finally {
+      setTimeout(() => {
+        setIsImportingXmp(false);
+        setXmpImportMessage('');
+      }, EXECUTE_TIMEOUT);
+    }
+  };
+
+  const handleWriteXmp = async () => {
+    setIsWritingXmp(true);
+    setXmpWriteMessage('Writing XMP sidecars...');
+    try {
+      const count = await invoke(Invokes.WriteXmpSidecars, { rootPath: effectiveRootPath });
+      setXmpWriteMessage(`${count} XMP sidecars written.`);
+    } catch (err) {
+      console.error('Failed to write XMP sidecars:', err);
+      setXmpWriteMessage(`Error: ${err}`);
+    } finally {
+      setTimeout(() => {
+        setIsWritingXmp(false);
+        setXmpWriteMessage('');
+      }, EXECUTE_TIMEOUT);
+    }
+  };
+
+  const handleImportLightroom = async () => {
+    const catalogPath = await open({
+      filters: [{ name: 'Lightroom Catalog', extensions: ['lrcat'] }],
+      multiple: false,
+      title: 'Select Lightroom Catalog',
+    });
+    if (typeof catalogPath !== 'string') {
+      return;
+    }
+    setIsImportingLightroom(true);
+    setLightroomImportMessage('Reading Lightroom catalog...');
+    try {
+      const summary: any = await invoke(Invokes.ImportLightroomCatalog, {
+        catalogPath,
+        includeDevelop: includeLightroomDevelop,
+      });
+      setLightroomImportMessage(
+        `${summary.images} images updated, ${summary.albums} albums created` +
+          (summary.missing > 0 ? `, ${summary.missing} images not found on disk.` : '.'),
+      );
+      onLibraryRefresh();
+    } catch (err) {
+      console.error('Failed to import Lightroom catalog:', err);
+      setLightroomImportMessage(`Error: ${err}`);
+    } finally {
+      setTimeout(() => {
+        setIsImportingLightroom(false);
+        setLightroomImportMessage('');
+      }, EXECUTE_TIMEOUT);
+    }
+  };
+
+  const handleImportDevelop = async () => {
+    setIsImportingDevelop(true);
+    setDevelopImportMessage('Converting edits...');
+    try {
+      const summary: any = await invoke(Invokes.ImportDevelopSettings, {
+        rootPath: effectiveRootPath,
+        source: developSource,
+      });
+      const operations = Array.from(new Set(summary.unconverted.flatMap((u: any) => u.operations)));
+      setDevelopImportMessage(
+        `${summary.updated} images updated` +
+          (operations.length > 0
+            ? `, ${summary.unconverted.length} with unconverted edits: ${operations.join(', ')}.`
+            : '.'),
+      );
+      onLibraryRefresh();
+    } catch (err) {
+      console.error('Failed to import develop settings:', err);
+      setDevelopImportMessage(`Error: ${err}`);
+    } finally {
+      setTimeout(() => {
+        setIsImportingDevelop(false);
+        setDevelopImportMessage('');
+      }, EXECUTE_TIMEOUT);
+    }
+  };
+
+  const handleToggleHttpApi = (enabled: boolean) => {
+    onSettingsChange({
+      ...appSettings,
+      enableHttpApi: enabled,
+      httpApiToken: appSettings?.httpApiToken || crypto.randomUUID(),
+    });
+  };
+
+  const handleHttpApiPortBlur = () => {
+    const port = parseInt(httpApiPort);
+    if (port > 0 && port < 65536) {
+      onSettingsChange({ ...appSettings, httpApiPort: port });
+    } else {
+      setHttpApiPort(String(appSettings?.httpApiPort || DEFAULT_HTTP_API_PORT));
+    }
+  };
+
   const executeSetTransparent = async (transparent: boolean) => {
     onSettingsChange({ ...appSettings, transparent });
     await relaunch();
@@ -275,6 +701,22 @@ export default function SettingsPanel({
     }
   };
 
+  const handleApplyCustomEffect = async () => {
+    if (!customEffect.trim()) {
+      onSettingsChange({ ...appSettings, customEffectShader: null });
+      setCustomEffectStatus({ testing: false, message: 'Custom effect removed.', success: true });
+      return;
+    }
+    setCustomEffectStatus({ testing: true, message: 'Compiling...', success: null });
+    try {
+      await invoke(Invokes.ValidateCustomEffect, { source: customEffect });
+      onSettingsChange({ ...appSettings, customEffectShader: customEffect });
+      setCustomEffectStatus({ testing: false, message: 'Custom effect applied.', success: true });
+    } catch (err) {
+      setCustomEffectStatus({ testing: false, message: String(err), success: false });
+    }
+  };
+
   const closeConfirmModal = () => {
     setConfirmModalState({ ...confirmModalState, isOpen: false });
   };
@@ -330,6 +772,96 @@ export default function SettingsPanel({
                 />
               </SettingItem>
 
+              <SettingItem
+                description="Keeps .xmp sidecars up to date with ratings, color labels and flags set in RapidRAW, so other applications such as Lightroom or darktable see them."
+                label="XMP Sidecars"
+              >
+                <Switch
+                  checked={appSettings?.writeXmpSidecars ?? false}
+                  id="xmp-sidecars-toggle"
+                  label="Write Ratings and Labels to XMP"
+                  onChange={(checked) => onSettingsChange({ ...appSettings, writeXmpSidecars: checked })}
+                />
+              </SettingItem>
+
+              <SettingItem
+                description="Detects isolated hot and dead pixels in RAW files from their neighbours. This can also remove stars and small highlights in long exposures; pixels mapped from a dark frame are always repaired."
+                label="Hot Pixels"
+              >
+                <Switch
+                  checked={appSettings?.autoHotPixelRemoval ?? false}
+                  id="auto-hot-pixel-toggle"
+                  label="Remove Hot Pixels Automatically"
+                  onChange={(checked) => onSettingsChange({ ...appSettings, autoHotPixelRemoval: checked })}
+                />
+              </SettingItem>
+
+              <SettingItem
+                description="New files appearing in a watched folder, e.g. from a tethered camera, are imported automatically into the destination with these import settings."
+                label="Watch Folders"
+              >
+                <div className="space-y-3">
+                  {watchFolders.map((folder, index) => (
+                    <div className="p-3 bg-bg-primary rounded-md space-y-2" key={`${folder.source}-${index}`}>
+                      <div className="flex items-start justify-between gap-2">
+                        <div className="text-xs min-w-0">
+                          <p className="text-text-primary truncate" title={folder.source}>
+                            {folder.source}
+                          </p>
+                          <p className="text-text-secondary truncate" title={folder.destination}>
+                            to {folder.destination}
+                          </p>
+                        </div>
+                        <button
+                          className="p-1 text-text-secondary hover:text-red-400"
+                          onClick={() => updateWatchFolders(watchFolders.filter((_, i) => i !== index))}
+                          title="Remove watch folder"
+                        >
+                          <Trash2 size={14} />
+                        </button>
+                      </div>
+                      <Input
+                        className="h-8 text-xs"
+                        onChange={(e: any) =>
+                          updateWatchFolder(index, {
+                            importSettings: { ...folder.importSettings, filenameTemplate: e.target.value },
+                          })
+                        }
+                        onKeyDown={(e: any) => e.stopPropagation()}
+                        placeholder="Filename template"
+                        type="text"
+                        value={folder.importSettings.filenameTemplate}
+                      />
+                      <Switch
+                        checked={folder.importSettings.organizeByDate}
+                        label="Organize into date folders"
+                        onChange={(checked) =>
+                          updateWatchFolder(index, { importSettings: { ...folder.importSettings, organizeByDate: checked } })
+                        }
+                      />
+                      <Switch
+                        checked={folder.importSettings.deleteAfterImport}
+                        label="Move files to trash after import"
+                        onChange={(checked) =>
+                          updateWatchFolder(index, {
+                            importSettings: { ...folder.importSettings, deleteAfterImport: checked },
+                          })
+                        }
+                      />
+                      <Dropdown
+                        onChange={(value: string) => updateWatchFolder(index, { presetId: value || null })}
+                        options={presetOptions}
+                        value={folder.presetId || ''}
+                      />
+                    </div>
+                  ))}
+                  <Button onClick={handleAddWatchFolder}>
+                    <FolderPlus size={16} />
+                    Add Watch Folder
+                  </Button>
+                </div>
+              </SettingItem>
+
               <SettingItem
                 description="Enables or disables transparency effects for the application window. Relaunch required."
                 label="Window Effects"
@@ -352,6 +884,102 @@ export default function SettingsPanel({
                   value={appSettings?.editorPreviewResolution || 1920}
                 />
               </SettingItem>
+
+              <SettingItem
+                description="The ICC profile of your monitor. Previews are converted from sRGB to this profile so colors display accurately on wide-gamut and calibrated screens."
+                label="Display Profile"
+              >
+                <div className="flex items-center gap-2">
+                  <div className="flex-grow">
+                    <Dropdown
+                      onChange={(value: string) =>
+                        onSettingsChange({ ...appSettings, displayProfilePath: value || null })
+                      }
+                      options={displayProfileOptions}
+                      value={displayProfilePath}
+                    />
+                  </div>
+                  <button
+                    className="p-2 text-text-secondary hover:text-text-primary"
+                    onClick={handleSelectDisplayProfile}
+                    title="Select profile file"
+                  >
+                    <FolderOpen size={16} />
+                  </button>
+                </div>
+              </SettingItem>
+
+              <SettingItem
+                description="Shows highlights brighter than white in the editor preview. Requires an HDR-capable display with HDR enabled in the operating system."
+                label="HDR Preview"
+              >
+                <Dropdown
+                  onChange={(value: HdrPreviewMode) => onSettingsChange({ ...appSettings, hdrPreview: value })}
+                  options={hdrPreviewModes}
+                  value={appSettings?.hdrPreview ?? HdrPreviewMode.Off}
+                />
+              </SettingItem>
+
+              <SettingItem
+                description="Experimental. A WGSL snippet defining fn custom_effect(color: vec3<f32>, uv: vec2<f32>) -> vec3<f32>, applied after LUTs with the Custom Effect slider in the Effects panel. Colors are display sRGB, uv runs from 0 to 1 across the image. Attributes and loops aren't allowed."
+                label="Custom Effect"
+              >
+                <div className="space-y-2">
+                  <textarea
+                    className="w-full h-40 p-2 font-mono text-xs bg-bg-primary text-text-primary rounded-md border border-border-color resize-y"
+                    onChange={(e: any) => setCustomEffect(e.target.value)}
+                    onKeyDown={(e: any) => e.stopPropagation()}
+                    placeholder={'fn custom_effect(color: vec3<f32>, uv: vec2<f32>) -> vec3<f32> {\n    return vec3<f32>(1.0) - color;\n}'}
+                    spellCheck={false}
+                    value={customEffect}
+                  />
+                  <div className="flex items-center gap-2">
+                    <Button disabled={customEffectStatus.testing} onClick={handleApplyCustomEffect}>
+                      {customEffectStatus.testing ? 'Compiling...' : 'Validate & Apply'}
+                    </Button>
+                    {customEffectStatus.message && !customEffectStatus.testing && (
+                      <span
+                        className={`text-sm ${customEffectStatus.success ? 'text-green-400' : 'text-red-400'} break-all`}
+                      >
+                        {customEffectStatus.message}
+                      </span>
+                    )}
+                  </div>
+                </div>
+              </SettingItem>
+
+              <SettingItem
+                description="Decodes the images next to the one being edited in the background, so switching images is faster. Uses more memory."
+                label="Preload Adjacent Images"
+              >
+                <Dropdown
+                  onChange={(value: number) => onSettingsChange({ ...appSettings, editorPrefetchCount: value })}
+                  options={prefetchCounts}
+                  value={appSettings?.editorPrefetchCount ?? 1}
+                />
+              </SettingItem>
+
+              <SettingItem
+                description="Upper limit for decoded images and previews kept in memory. Older cached images are released when the limit is reached."
+                label="Memory Budget"
+              >
+                <Dropdown
+                  onChange={(value: number) => onSettingsChange({ ...appSettings, memoryBudgetMb: value })}
+                  options={memoryBudgets}
+                  value={appSettings?.memoryBudgetMb ?? 4096}
+                />
+              </SettingItem>
+
+              <SettingItem
+                description="Number of images processed at the same time during batch export. Auto picks a value based on your CPU and memory budget."
+                label="Parallel Exports"
+              >
+                <Dropdown
+                  onChange={(value: number) => onSettingsChange({ ...appSettings, exportWorkerCount: value })}
+                  options={exportWorkerCounts}
+                  value={appSettings?.exportWorkerCount ?? 0}
+                />
+              </SettingItem>
             </div>
           </div>
 
@@ -393,6 +1021,243 @@ export default function SettingsPanel({
                   </p>
                 )}
               </SettingItem>
+
+              <SettingItem
+                description="Additional ComfyUI servers, tried in order when the address above is offline. A workflows folder can replace the built-in workflow files of the same name; they must keep the node IDs of the originals."
+                label="Fallback ComfyUI Servers"
+              >
+                <div className="space-y-3">
+                  {comfyUiProfiles.map((profile, index) => (
+                    <div className="p-3 bg-bg-primary rounded-md space-y-2" key={profile.id}>
+                      <div className="flex items-center gap-2">
+                        <Input
+                          className="h-8 text-xs flex-grow"
+                          onChange={(e: any) => updateComfyUiProfile(index, { name: e.target.value })}
+                          onKeyDown={(e: any) => e.stopPropagation()}
+                          placeholder="Name"
+                          type="text"
+                          value={profile.name}
+                        />
+                        {renderProfileStatus(profile.id)}
+                        <button
+                          className="p-1 text-text-secondary hover:text-red-400"
+                          onClick={() => updateComfyUiProfiles(comfyUiProfiles.filter((_, i) => i !== index))}
+                          title="Remove server"
+                        >
+                          <Trash2 size={14} />
+                        </button>
+                      </div>
+                      <Input
+                        className="h-8 text-xs"
+                        onChange={(e: any) => updateComfyUiProfile(index, { address: e.target.value })}
+                        onKeyDown={(e: any) => e.stopPropagation()}
+                        placeholder="192.168.1.20:8188"
+                        type="text"
+                        value={profile.address}
+                      />
+                      <div className="flex items-center gap-2">
+                        <p className="text-xs text-text-secondary truncate flex-grow" title={profile.workflowsDir ?? ''}>
+                          {profile.workflowsDir || 'Built-in workflows'}
+                        </p>
+                        <button
+                          className="p-1 text-text-secondary hover:text-text-primary"
+                          onClick={() => handleSelectWorkflowsDir(index)}
+                          title="Select workflows folder"
+                        >
+                          <FolderOpen size={14} />
+                        </button>
+                        {profile.workflowsDir && (
+                          <button
+                            className="p-1 text-text-secondary hover:text-red-400"
+                            onClick={() => updateComfyUiProfile(index, { workflowsDir: null })}
+                            title="Use built-in workflows"
+                          >
+                            <Trash2 size={14} />
+                          </button>
+                        )}
+                      </div>
+                    </div>
+                  ))}
+                  <div className="flex items-center gap-2">
+                    <Button onClick={handleAddComfyUiProfile}>
+                      <Plus size={16} />
+                      Add Server
+                    </Button>
+                    <Button disabled={isCheckingProfiles} onClick={handleCheckProfiles}>
+                      {isCheckingProfiles ? 'Checking...' : 'Check All Servers'}
+                    </Button>
+                    {profileHealth.default && (
+                      <span className="text-xs text-text-secondary flex items-center gap-2">
+                        Main address: {renderProfileStatus('default')}
+                      </span>
+                    )}
+                  </div>
+                </div>
+              </SettingItem>
+
+              <SettingItem
+                description="Lets scripts, Stream Decks and other tools on this computer list folders, fetch thumbnails, set ratings and export with a preset. Requests must send the token as a Bearer token or a 'token' query parameter."
+                label="Local HTTP API"
+              >
+                <div className="space-y-3">
+                  <Switch
+                    checked={appSettings?.enableHttpApi ?? false}
+                    id="http-api-toggle"
+                    label="Enable HTTP and WebSocket API on localhost"
+                    onChange={handleToggleHttpApi}
+                  />
+                  {appSettings?.enableHttpApi && (
+                    <>
+                      <div className="flex items-center gap-2">
+                        <span className="text-sm text-text-secondary w-16">Port</span>
+                        <Input
+                          className="w-32"
+                          id="http-api-port"
+                          onBlur={handleHttpApiPortBlur}
+                          onChange={(e: any) => setHttpApiPort(e.target.value)}
+                          onKeyDown={(e: any) => e.stopPropagation()}
+                          type="number"
+                          value={httpApiPort}
+                        />
+                      </div>
+                      <div className="flex items-center gap-2">
+                        <span className="text-sm text-text-secondary w-16">Token</span>
+                        <code className="flex-grow font-mono text-xs bg-bg-primary p-2 rounded break-all border border-border-color">
+                          {appSettings?.httpApiToken}
+                        </code>
+                        <Button
+                          className="w-32"
+                          onClick={() => onSettingsChange({ ...appSettings, httpApiToken: crypto.randomUUID() })}
+                        >
+                          Regenerate
+                        </Button>
+                      </div>
+                    </>
+                  )}
+                </div>
+              </SettingItem>
+
+              <SettingItem
+                description="Destinations that exports can upload to after encoding. SFTP uses your system's ssh client and keys."
+                label="Upload Targets"
+              >
+                <div className="space-y-3">
+                  {uploadTargets.map((target, index) => (
+                    <div className="p-3 bg-bg-primary rounded-md space-y-2" key={target.id}>
+                      <div className="flex items-center gap-2">
+                        <Input
+                          className="h-8 text-xs flex-grow"
+                          onChange={(e: any) => updateUploadTarget(index, { name: e.target.value })}
+                          onKeyDown={(e: any) => e.stopPropagation()}
+                          placeholder="Name"
+                          type="text"
+                          value={target.name}
+                        />
+                        <Dropdown
+                          onChange={(kind: UploadKind) => updateUploadTarget(index, { destination: { kind } })}
+                          options={UPLOAD_KIND_OPTIONS}
+                          value={target.destination.kind}
+                        />
+                        <button
+                          className="p-1 text-text-secondary hover:text-red-400"
+                          onClick={() => updateUploadTargets(uploadTargets.filter((_, i) => i !== index))}
+                          title="Remove upload target"
+                        >
+                          <Trash2 size={14} />
+                        </button>
+                      </div>
+                      {UPLOAD_FIELDS[target.destination.kind].map((field) => (
+                        <Input
+                          className="h-8 text-xs"
+                          key={field.key}
+                          onChange={(e: any) => updateUploadField(index, field, e.target.value)}
+                          onKeyDown={(e: any) => e.stopPropagation()}
+                          placeholder={field.placeholder}
+                          type={field.type || 'text'}
+                          value={target.destination[field.key] ?? ''}
+                        />
+                      ))}
+                    </div>
+                  ))}
+                  <Button onClick={handleAddUploadTarget}>
+                    <Upload size={16} />
+                    Add Upload Target
+                  </Button>
+                </div>
+              </SettingItem>
+
+              <SettingItem
+                description="Links an album to a webhook. Publishing exports new and changed images with an export preset and posts each one with its metadata as multipart form data."
+                label="Publish Services"
+              >
+                <div className="space-y-3">
+                  {publishServices.map((service, index) => (
+                    <div className="p-3 bg-bg-primary rounded-md space-y-2" key={service.id}>
+                      <div className="flex items-center gap-2">
+                        <Input
+                          className="h-8 text-xs flex-grow"
+                          onChange={(e: any) => updatePublishService(index, { name: e.target.value })}
+                          onKeyDown={(e: any) => e.stopPropagation()}
+                          placeholder="Name"
+                          type="text"
+                          value={service.name}
+                        />
+                        <button
+                          className="p-1 text-text-secondary hover:text-red-400"
+                          onClick={() => updatePublishServices(publishServices.filter((_, i) => i !== index))}
+                          title="Remove publish service"
+                        >
+                          <Trash2 size={14} />
+                        </button>
+                      </div>
+                      <Input
+                        className="h-8 text-xs"
+                        onChange={(e: any) => updatePublishService(index, { url: e.target.value })}
+                        onKeyDown={(e: any) => e.stopPropagation()}
+                        placeholder="https://example.com/webhook"
+                        type="text"
+                        value={service.url}
+                      />
+                      <Input
+                        className="h-8 text-xs"
+                        onChange={(e: any) => updatePublishService(index, { authToken: e.target.value })}
+                        onKeyDown={(e: any) => e.stopPropagation()}
+                        placeholder="Bearer token (optional)"
+                        type="password"
+                        value={service.authToken ?? ''}
+                      />
+                      <div className="flex items-center gap-2">
+                        <Dropdown
+                          onChange={(albumId: string) => updatePublishService(index, { albumId })}
+                          options={albumOptions}
+                          placeholder="Album"
+                          value={service.albumId ?? ''}
+                        />
+                        <Dropdown
+                          onChange={(presetId: string) => updatePublishService(index, { presetId })}
+                          options={exportPresetOptions}
+                          placeholder="Export preset"
+                          value={service.presetId ?? ''}
+                        />
+                        <Button
+                          className="w-32"
+                          disabled={!service.url || !service.albumId || !service.presetId}
+                          onClick={() => handlePublish(service)}
+                        >
+                          Publish
+                        </Button>
+                      </div>
+                      {publishMessages[service.id] && (
+                        <p className="text-xs text-text-secondary">{publishMessages[service.id]}</p>
+                      )}
+                    </div>
+                  ))}
+                  <Button onClick={handleAddPublishService}>
+                    <Send size={16} />
+                    Add Publish Service
+                  </Button>
+                </div>
+              </SettingItem>
             </div>
           </div>
 
@@ -429,6 +1294,57 @@ export default function SettingsPanel({
                 title="Clear All Tags"
               />
 
+              <DataActionItem
+                buttonAction={handleImportXmp}
+                buttonText="Import XMP Sidecars"
+                description="Reads ratings, color labels and keywords from Lightroom or darktable .xmp sidecars in the current root folder. Existing RapidRAW ratings and labels are kept."
+                disabled={!effectiveRootPath}
+                icon={<FileDown size={16} className="mr-2" />}
+                isProcessing={isImportingXmp}
+                message={xmpImportMessage}
+                title="Import XMP Sidecars"
+              />
+
+              <DataActionItem
+                buttonAction={handleWriteXmp}
+                buttonText="Write XMP Sidecars"
+                description="Writes ratings, color labels and tags of all edited images in the current root folder to .xmp sidecars."
+                disabled={!effectiveRootPath}
+                icon={<FileUp size={16} className="mr-2" />}
+                isProcessing={isWritingXmp}
+                message={xmpWriteMessage}
+                title="Write XMP Sidecars"
+              />
+
+              <DataActionItem
+                buttonAction={handleImportLightroom}
+                buttonText="Import Lightroom Catalog"
+                description="Migrates ratings, flags, color labels, keywords and collections from a Lightroom Classic .lrcat catalog. Collections become albums. Existing RapidRAW ratings and labels are kept."
+                icon={<FileDown size={16} className="mr-2" />}
+                isProcessing={isImportingLightroom}
+                message={lightroomImportMessage}
+                title="Import Lightroom Catalog"
+              >
+                <Switch
+                  checked={includeLightroomDevelop}
+                  label="Also import basic develop settings for unedited images"
+                  onChange={setIncludeLightroomDevelop}
+                />
+              </DataActionItem>
+
+              <DataActionItem
+                buttonAction={handleImportDevelop}
+                buttonText="Import Edits"
+                description="Converts crop, exposure, white balance and rating from darktable or Capture One sidecars in the current root folder. Only images without RapidRAW edits are changed; edits that can't be converted are listed."
+                disabled={!effectiveRootPath}
+                icon={<FileDown size={16} className="mr-2" />}
+                isProcessing={isImportingDevelop}
+                message={developImportMessage}
+                title="Import darktable / Capture One Edits"
+              >
+                <Dropdown onChange={setDevelopSource} options={DEVELOP_SOURCE_OPTIONS} value={developSource} />
+              </DataActionItem>
+
               <DataActionItem
                 buttonAction={handleClearCache}
                 buttonText="Clear Thumbnail Cache"
@@ -472,6 +1388,8 @@ export default function SettingsPanel({
                   <KeybindItem keys={['↑', '↓']} description="Zoom out / Zoom in" />
                   <KeybindItem keys={['F']} description="Toggle fullscreen" />
                   <KeybindItem keys={['B']} description="Show original (before/after)" />
+                  <KeybindItem keys={['J']} description="Toggle clipping warnings" />
+                  <KeybindItem keys={['Y']} description="Cycle compare view (split / side by side / off)" />
                   <KeybindItem keys={['R']} description="Toggle Crop panel" />
                   <KeybindItem keys={['M']} description="Toggle Masks panel" />
                   <KeybindItem keys={['I']} description="Toggle Metadata panel" />
@@ -1,17 +1,19 @@
 import { memo, useState, useEffect, useRef } from 'react';
-import { Eye, EyeOff, ArrowLeft, Maximize, Loader2, Undo, Redo, Waves } from 'lucide-react';
+import { Eye, EyeOff, ArrowLeft, Focus, Maximize, Loader2, Undo, Redo, Waves } from 'lucide-react';
 import clsx from 'clsx';
 import { SelectedImage } from '../../ui/AppProperties';
 
 interface EditorToolbarProps {
   canRedo: boolean;
   canUndo: boolean;
+  isFocusPeakingVisible: boolean;
   isFullScreenLoading: boolean;
   isWaveformVisible: boolean;
   isLoading: boolean;
   isLoadingFullRes?: boolean;
   onBackToLibrary(): void;
   onRedo(): void;
+  onToggleFocusPeaking(): void;
   onToggleFullScreen(): void;
   onToggleShowOriginal(): void;
   onToggleWaveform(): void;
@@ -24,12 +26,14 @@ const EditorToolbar = memo(
   ({
     canRedo,
     canUndo,
+    isFocusPeakingVisible,
     isFullScreenLoading,
     isLoading,
     isLoadingFullRes,
     isWaveformVisible,
     onBackToLibrary,
     onRedo,
+    onToggleFocusPeaking,
     onToggleFullScreen,
     onToggleShowOriginal,
     onToggleWaveform,
@@ -137,6 +141,18 @@ const EditorToolbar = memo(
           >
             <Redo size={20} />
           </button>
+          <button
+            className={clsx(
+              'p-2 rounded-full transition-colors',
+              isFocusPeakingVisible
+                ? 'bg-accent text-button-text hover:bg-accent/90 hover:text-button-text'
+                : 'bg-surface hover:bg-card-active text-text-primary',
+            )}
+            onClick={onToggleFocusPeaking}
+            title="Toggle Focus Peaking"
+          >
+            <Focus size={20} />
+          </button>
           <button
             className={clsx(
               'p-2 rounded-full transition-colors',
@@ -4,9 +4,11 @@ import 'react-image-crop/dist/ReactCrop.css';
 import { Stage, Layer, Ellipse, Line, Transformer, Group, Circle, Rect } from 'react-konva';
 import { PercentCrop, Crop } from 'react-image-crop';
 import clsx from 'clsx';
-import { Adjustments, AiPatch, Coord, MaskContainer } from '../../../utils/adjustments';
-import { Mask, SubMask, SubMaskMode, ToolType } from '../right/Masks';
-import { BrushSettings, SelectedImage } from '../../ui/AppProperties';
+import { v4 as uuidv4 } from 'uuid';
+import { Adjustments, AiPatch, Coord, MaskContainer, SpotEdit, SpotEditType } from '../../../utils/adjustments';
+import { Mask, SamPoint, SubMask, SubMaskMode, ToolType } from '../right/Masks';
+import { SpotSettings } from '../right/SpotRemovalPanel';
+import { BrushSettings, RegionPreview, SelectedImage } from '../../ui/AppProperties';
 import { RenderSize } from '../../../hooks/useImageRenderSize';
 
 interface CursorPreview {
@@ -29,8 +31,11 @@ interface ImageCanvasProps {
   activeMaskId: string | null;
   adjustments: Adjustments;
   brushSettings: BrushSettings | null;
+  clippingOverlayUrl: string | null;
+  comparisonUrl: string | null;
   crop: Crop | null;
   finalPreviewUrl: string | null;
+  focusPeakingUrl: string | null;
   handleCropComplete(c: Crop, cp: PercentCrop): void;
   imageRenderSize: RenderSize;
   isAdjusting: boolean;
@@ -38,23 +43,26 @@ interface ImageCanvasProps {
   isCropping: boolean;
   isMaskControlHovered: boolean;
   isMasking: boolean;
+  isSpotRemoving: boolean;
   isStraightenActive: boolean;
+  isWbPickerActive: boolean;
   maskOverlayUrl: string | null;
-  onGenerateAiMask(id: string | null, start: Coord, end: Coord): void;
+  onAddSpotEdit(edit: SpotEdit): void;
+  onGenerateAiMask(id: string | null, start: Coord, end: Coord, points?: Array<SamPoint>): void;
   onQuickErase(subMaskId: string | null, startPoint: Coord, endpoint: Coord): void;
   onSelectAiSubMask(id: string | null): void;
   onSelectMask(id: string | null): void;
   onStraighten(val: number): void;
+  onWhiteBalancePick(x: number, y: number): void;
+  regionPreview: RegionPreview | null;
   selectedImage: SelectedImage;
   setCrop(crop: Crop, perfentCrop: PercentCrop): void;
   setIsMaskHovered(isHovered: boolean): void;
   showOriginal: boolean;
+  spotSettings: SpotSettings;
   transformedOriginalUrl: string | null;
   uncroppedAdjustedPreviewUrl: string | null;
   updateSubMask(id: string | null, subMask: Partial<SubMask>): void;
-  fullResolutionUrl?: string | null;
-  isFullResolution?: boolean;
-  isLoadingFullRes?: boolean;
 }
 
 interface ImageLayer {
@@ -76,6 +84,28 @@ interface MaskOverlay {
 
 const ORIGINAL_LAYER = 'original';
 
+// Maps a point on the uncropped canvas to normalized coordinates on the unedited image by undoing
+// the fine rotation, the flips and the 90° steps, in reverse of the order they are applied.
+function canvasToSourcePoint(point: Coord, adjustments: Adjustments, width: number, height: number): Coord {
+  const steps = adjustments.orientationSteps || 0;
+  const [w, h] = steps % 2 === 1 ? [height, width] : [width, height];
+  const angle = (-(adjustments.rotation || 0) * Math.PI) / 180;
+  const dx = point.x - w / 2;
+  const dy = point.y - h / 2;
+  let u = (w / 2 + dx * Math.cos(angle) - dy * Math.sin(angle)) / w;
+  let v = (h / 2 + dx * Math.sin(angle) + dy * Math.cos(angle)) / h;
+  if (adjustments.flipHorizontal) {
+    u = 1 - u;
+  }
+  if (adjustments.flipVertical) {
+    v = 1 - v;
+  }
+  for (let i = 0; i < steps; i++) {
+    [u, v] = [v, 1 - u];
+  }
+  return { x: Math.min(Math.max(u, 0), 1), y: Math.min(Math.max(v, 0), 1) };
+}
+
 function linesIntersect(eraserLine: DrawnLine, drawnLine: DrawnLine) {
   const threshold = eraserLine.brushSize / 2 + drawnLine.brushSize / 2;
   for (const p1 of eraserLine.points) {
@@ -463,8 +493,11 @@ const ImageCanvas = memo(
     activeMaskId,
     adjustments,
     brushSettings,
+    clippingOverlayUrl,
+    comparisonUrl,
     crop,
     finalPreviewUrl,
+    focusPeakingUrl,
     handleCropComplete,
     imageRenderSize,
     isAdjusting,
@@ -472,23 +505,26 @@ const ImageCanvas = memo(
     isCropping,
     isMaskControlHovered,
     isMasking,
+    isSpotRemoving,
     isStraightenActive,
+    isWbPickerActive,
     maskOverlayUrl,
+    onAddSpotEdit,
     onGenerateAiMask,
     onQuickErase,
     onSelectAiSubMask,
     onSelectMask,
     onStraighten,
+    onWhiteBalancePick,
+    regionPreview,
     selectedImage,
     setCrop,
     setIsMaskHovered,
     showOriginal,
+    spotSettings,
     transformedOriginalUrl,
     uncroppedAdjustedPreviewUrl,
     updateSubMask,
-    fullResolutionUrl,
-    isFullResolution,
-    isLoadingFullRes,
   }: ImageCanvasProps) => {
     const [isCropViewVisible, setIsCropViewVisible] = useState(false);
     const [layers, setLayers] = useState<Array<ImageLayer>>([]);
@@ -502,6 +538,7 @@ const ImageCanvas = memo(
     const [cursorPreview, setCursorPreview] = useState<CursorPreview>({ x: 0, y: 0, visible: false });
     const [straightenLine, setStraightenLine] = useState<any>(null);
     const isStraightening = useRef(false);
+    const [cloneSource, setCloneSource] = useState<Coord | null>(null);
 
     const activeContainer = useMemo(() => {
       if (isMasking) {
@@ -537,7 +574,8 @@ const ImageCanvas = memo(
     const isAiSubjectActive =
       (isMasking || isAiEditing) &&
       (activeSubMask?.type === Mask.AiSubject || activeSubMask?.type === Mask.QuickEraser);
-    const isToolActive = isBrushActive || isAiSubjectActive;
+    const isToolActive = isBrushActive || isAiSubjectActive || isSpotRemoving;
+    const isCloning = isSpotRemoving && spotSettings.type === SpotEditType.Clone;
 
     const sortedSubMasks = useMemo(() => {
       if (!activeContainer) {
@@ -553,15 +591,12 @@ const ImageCanvas = memo(
       const { path: currentImagePath, originalUrl, thumbnailUrl } = selectedImage;
       const imageChanged = currentImagePath !== imagePathRef.current;
 
-      const currentPreviewUrl = showOriginal
-        ? transformedOriginalUrl
-        : isFullResolution && !isLoadingFullRes && fullResolutionUrl
-        ? fullResolutionUrl
-        : finalPreviewUrl;
+      const currentPreviewUrl = showOriginal ? transformedOriginalUrl : finalPreviewUrl;
 
       if (imageChanged) {
         imagePathRef.current = currentImagePath;
         latestEditedUrlRef.current = null;
+        setCloneSource(null);
         const initialUrl = thumbnailUrl || originalUrl;
         if (initialUrl) {
           setLayers([{ id: initialUrl, url: initialUrl, opacity: 1 }]);
@@ -598,19 +633,14 @@ const ImageCanvas = memo(
             if (prev.length === 0) {
               return [{ id: initialUrl, url: initialUrl, opacity: 1 }];
             }
-            return prev;
+            if (prev.some((l) => l.id === initialUrl)) {
+              return prev;
+            }
+            return [...prev, { id: initialUrl, url: initialUrl, opacity: 0 }];
           });
         }
       }
-    }, [
-      selectedImage,
-      finalPreviewUrl,
-      fullResolutionUrl,
-      transformedOriginalUrl,
-      showOriginal,
-      isFullResolution,
-      isLoadingFullRes,
-    ]);
+    }, [selectedImage, finalPreviewUrl, transformedOriginalUrl, showOriginal]);
 
     useEffect(() => {
       const layerToFadeIn = layers.find((l: ImageLayer) => l.opacity === 0);
@@ -646,6 +676,19 @@ const ImageCanvas = memo(
 
     const handleMouseDown = useCallback(
       (e: any) => {
+        if (isWbPickerActive) {
+          const pos = e.target.getStage().getPointerPosition();
+          if (pos) {
+            const canvasPoint = {
+              x: pos.x / imageRenderSize.scale + (adjustments.crop?.x || 0),
+              y: pos.y / imageRenderSize.scale + (adjustments.crop?.y || 0),
+            };
+            const point = canvasToSourcePoint(canvasPoint, adjustments, selectedImage.width, selectedImage.height);
+            onWhiteBalancePick(point.x, point.y);
+          }
+          return;
+        }
+
         if (isToolActive) {
           e.evt.preventDefault();
           isDrawing.current = true;
@@ -655,10 +698,24 @@ const ImageCanvas = memo(
             return;
           }
 
+          if (isCloning && e.evt.altKey) {
+            isDrawing.current = false;
+            setCloneSource({
+              x: pos.x / imageRenderSize.scale + (adjustments.crop?.x || 0),
+              y: pos.y / imageRenderSize.scale + (adjustments.crop?.y || 0),
+            });
+            return;
+          }
+
           const toolType = isAiSubjectActive ? ToolType.AiSeletor : ToolType.Brush;
 
+          let brushSize = isBrushActive && brushSettings?.size ? brushSettings.size : 2;
+          if (isSpotRemoving) {
+            brushSize = spotSettings.size;
+          }
+
           const newLine: DrawnLine = {
-            brushSize: isBrushActive && brushSettings?.size ? brushSettings.size : 2,
+            brushSize,
             points: [pos],
             tool: toolType,
           };
@@ -675,7 +732,24 @@ const ImageCanvas = memo(
           }
         }
       },
-      [isBrushActive, isAiSubjectActive, brushSettings, onSelectMask, onSelectAiSubMask, isMasking, isAiEditing],
+      [
+        isBrushActive,
+        isAiSubjectActive,
+        isSpotRemoving,
+        isCloning,
+        isWbPickerActive,
+        brushSettings,
+        spotSettings.size,
+        imageRenderSize.scale,
+        adjustments,
+        onWhiteBalancePick,
+        selectedImage.width,
+        selectedImage.height,
+        onSelectMask,
+        onSelectAiSubMask,
+        isMasking,
+        isAiEditing,
+      ],
     );
 
     const handleMouseMove = useCallback(
@@ -712,7 +786,7 @@ const ImageCanvas = memo(
       [isToolActive],
     );
 
-    const handleMouseUp = useCallback(() => {
+    const handleMouseUp = useCallback((e?: any) => {
       if (!isDrawing.current || !currentLine.current) {
         return;
       }
@@ -733,9 +807,41 @@ const ImageCanvas = memo(
 
       const activeId = isMasking ? activeMaskId : activeAiSubMaskId;
 
+      if (isSpotRemoving) {
+        const points = line.points.map((p: Coord) => ({ x: p.x / scale + cropX, y: p.y / scale + cropY }));
+        const brushSize = spotSettings.size / scale;
+        let sourceOffset: Coord | null = null;
+        if (isCloning) {
+          const source = cloneSource ?? { x: points[0].x + brushSize * 1.5, y: points[0].y };
+          sourceOffset = { x: source.x - points[0].x, y: source.y - points[0].y };
+        }
+        onAddSpotEdit({
+          brushSize,
+          feather: spotSettings.feather / 100,
+          id: uuidv4(),
+          opacity: spotSettings.opacity,
+          points,
+          sourceOffset,
+          type: spotSettings.type,
+          visible: true,
+        });
+        return;
+      }
+
       if (activeSubMask?.type === Mask.AiSubject || activeSubMask?.type === Mask.QuickEraser) {
         const points = line.points;
-        if (points.length > 1) {
+        if (points.length === 1 && activeSubMask.type === Mask.AiSubject && onGenerateAiMask) {
+          const params = activeSubMask.parameters || {};
+          const clickPoint: SamPoint = {
+            positive: !e?.evt?.altKey,
+            x: points[0].x / scale + cropX,
+            y: points[0].y / scale + cropY,
+          };
+          const hasBox = params.startX !== undefined && params.endX !== undefined;
+          const startPoint = hasBox ? { x: params.startX, y: params.startY } : clickPoint;
+          const endPoint = hasBox ? { x: params.endX, y: params.endY } : clickPoint;
+          onGenerateAiMask(activeId, startPoint, endPoint, [...(params.points || []), clickPoint]);
+        } else if (points.length > 1) {
           const xs = points.map((p: Coord) => p.x);
           const ys = points.map((p: Coord) => p.y);
           const minX = Math.min(...xs);
@@ -789,10 +895,14 @@ const ImageCanvas = memo(
       activeSubMask,
       adjustments.crop,
       brushSettings,
+      cloneSource,
       imageRenderSize.scale,
       isAiEditing,
       isBrushActive,
+      isCloning,
       isMasking,
+      isSpotRemoving,
+      onAddSpotEdit,
       onGenerateAiMask,
       onQuickErase,
       updateSubMask,
@@ -978,6 +1088,40 @@ const ImageCanvas = memo(
                   }}
                 />
               ))}
+              {regionPreview && !showOriginal && (
+                <img
+                  alt="Full Resolution Region"
+                  className="absolute pointer-events-none"
+                  src={regionPreview.url}
+                  style={{
+                    height: `${regionPreview.height * imageRenderSize.scale}px`,
+                    left: `${imageRenderSize.offsetX + regionPreview.x * imageRenderSize.scale}px`,
+                    top: `${imageRenderSize.offsetY + regionPreview.y * imageRenderSize.scale}px`,
+                    width: `${regionPreview.width * imageRenderSize.scale}px`,
+                  }}
+                />
+              )}
+              {clippingOverlayUrl && !showOriginal && (
+                <img
+                  alt="Clipping Overlay"
+                  className="absolute inset-0 w-full h-full object-contain pointer-events-none animate-pulse"
+                  src={clippingOverlayUrl}
+                />
+              )}
+              {focusPeakingUrl && (
+                <img
+                  alt="Focus Peaking"
+                  className="absolute inset-0 w-full h-full object-contain pointer-events-none"
+                  src={focusPeakingUrl}
+                />
+              )}
+              {comparisonUrl && (
+                <img
+                  alt="Comparison"
+                  className="absolute inset-0 w-full h-full object-contain pointer-events-none bg-bg-secondary"
+                  src={comparisonUrl}
+                />
+              )}
               {(isMasking || isAiEditing) && maskOverlayUrl && (
                 <img
                   alt="Mask Overlay"
@@ -1005,7 +1149,7 @@ const ImageCanvas = memo(
             onMouseMove={handleMouseMove}
             onMouseUp={handleMouseUp}
             style={{
-              cursor: isToolActive ? 'crosshair' : 'default',
+              cursor: isToolActive || isWbPickerActive ? 'crosshair' : 'default',
               left: `${imageRenderSize.offsetX}px`,
               opacity: showOriginal ? 0 : 1,
               pointerEvents: showOriginal ? 'none' : 'auto',
@@ -1044,6 +1188,28 @@ const ImageCanvas = memo(
                   tension={0.5}
                 />
               )}
+              {isSpotRemoving && cursorPreview.visible && (
+                <Circle
+                  listening={false}
+                  perfectDrawEnabled={false}
+                  radius={spotSettings.size / 2}
+                  stroke="#0ea5e9"
+                  strokeWidth={1}
+                  x={cursorPreview.x}
+                  y={cursorPreview.y}
+                />
+              )}
+              {isCloning && cloneSource && (
+                <Circle
+                  dash={[4, 4]}
+                  listening={false}
+                  radius={spotSettings.size / 2}
+                  stroke="#facc15"
+                  strokeWidth={1}
+                  x={(cloneSource.x - (adjustments.crop?.x || 0)) * imageRenderSize.scale}
+                  y={(cloneSource.y - (adjustments.crop?.y || 0)) * imageRenderSize.scale}
+                />
+              )}
               {isBrushActive && cursorPreview.visible && (
                 <Circle
                   listening={false}
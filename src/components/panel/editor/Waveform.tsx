@@ -2,7 +2,7 @@ import { useState, useEffect, useRef } from 'react';
 import Draggable from 'react-draggable';
 import { X, Waves } from 'lucide-react';
 import { motion } from 'framer-motion';
-import { WaveformData } from '../../ui/AppProperties';
+import { ParadeData, VectorscopeData, WaveformData } from '../../ui/AppProperties';
 import { DisplayMode } from '../../../utils/adjustments';
 
 interface LumaWaveformProps {
@@ -22,6 +22,10 @@ interface MaxValues {
   width: number;
 }
 
+interface ParadeProps {
+  parade: ParadeData;
+}
+
 interface RgbWaveformProps {
   blueData: Array<number>;
   greenData: Array<number>;
@@ -31,11 +35,23 @@ interface RgbWaveformProps {
   width: number;
 }
 
+interface VectorscopeProps {
+  vectorscope: VectorscopeData;
+}
+
 interface WaveformProps {
   onClose(): void;
   waveformData: WaveformData;
 }
 
+const PARADE_COLORS = [
+  [255, 0, 0],
+  [0, 255, 0],
+  [0, 0, 255],
+];
+// Angle of the skin tone line, counterclockwise from the Cb axis.
+const SKIN_TONE_ANGLE = (123 * Math.PI) / 180;
+
 const LumaWaveformDisplay = ({ data, width, height, maxVal, color }: LumaWaveformProps) => {
   const canvasRef = useRef<HTMLCanvasElement>(null);
 
@@ -112,11 +128,109 @@ const RgbWaveformDisplay = ({ redData, greenData, blueData, width, height, maxVa
   return <canvas ref={canvasRef} width={width} height={height} className="absolute inset-0" />;
 };
 
+const ParadeDisplay = ({ parade }: ParadeProps) => {
+  const canvasRef = useRef<HTMLCanvasElement>(null);
+  const { red, green, blue, channelWidth, height } = parade;
+  const width = channelWidth * 3;
+
+  useEffect(() => {
+    if (!red || !canvasRef.current || !width || !height) {
+      return;
+    }
+
+    const ctx = canvasRef.current.getContext('2d');
+    if (!ctx) {
+      return;
+    }
+    ctx.clearRect(0, 0, width, height);
+
+    const imageData = ctx.createImageData(width, height);
+    const pixels = imageData.data;
+
+    [red, green, blue].forEach((data, channel) => {
+      const maxVal = Math.max(...data);
+      const scale = maxVal > 0 ? 255 / Math.log(1 + maxVal) : 0;
+      const color = PARADE_COLORS[channel];
+
+      for (let i = 0; i < data.length; i++) {
+        if (data[i] > 0) {
+          const row = Math.floor(i / channelWidth);
+          const col = channel * channelWidth + (i % channelWidth);
+          const pixelIndex = (row * width + col) * 4;
+
+          pixels[pixelIndex] = color[0];
+          pixels[pixelIndex + 1] = color[1];
+          pixels[pixelIndex + 2] = color[2];
+          pixels[pixelIndex + 3] = Math.log(1 + data[i]) * scale;
+        }
+      }
+    });
+    ctx.putImageData(imageData, 0, 0);
+  }, [red, green, blue, channelWidth, width, height]);
+
+  return <canvas ref={canvasRef} width={width} height={height} className="absolute inset-0" />;
+};
+
+const VectorscopeDisplay = ({ vectorscope }: VectorscopeProps) => {
+  const canvasRef = useRef<HTMLCanvasElement>(null);
+  const { counts, size } = vectorscope;
+
+  useEffect(() => {
+    if (!counts || !canvasRef.current || !size) {
+      return;
+    }
+
+    const ctx = canvasRef.current.getContext('2d');
+    if (!ctx) {
+      return;
+    }
+    ctx.clearRect(0, 0, size, size);
+
+    const imageData = ctx.createImageData(size, size);
+    const pixels = imageData.data;
+
+    const maxVal = Math.max(...counts);
+    const scale = maxVal > 0 ? 255 / Math.log(1 + maxVal) : 0;
+
+    for (let i = 0; i < counts.length; i++) {
+      if (counts[i] > 0) {
+        const cb = (i % size) / (size - 1) - 0.5;
+        const cr = 0.5 - Math.floor(i / size) / (size - 1);
+        const r = 0.5 + 1.5748 * cr;
+        const b = 0.5 + 1.8556 * cb;
+        const g = (0.5 - 0.2126 * r - 0.0722 * b) / 0.7152;
+        const pixelIndex = i * 4;
+
+        pixels[pixelIndex] = Math.min(255, Math.max(0, r * 255 + 64));
+        pixels[pixelIndex + 1] = Math.min(255, Math.max(0, g * 255 + 64));
+        pixels[pixelIndex + 2] = Math.min(255, Math.max(0, b * 255 + 64));
+        pixels[pixelIndex + 3] = Math.log(1 + counts[i]) * scale;
+      }
+    }
+    ctx.putImageData(imageData, 0, 0);
+
+    const center = size / 2;
+    ctx.strokeStyle = 'rgba(255, 255, 255, 0.25)';
+    ctx.lineWidth = 1;
+    ctx.beginPath();
+    ctx.arc(center, center, center - 1, 0, 2 * Math.PI);
+    ctx.moveTo(0, center);
+    ctx.lineTo(size, center);
+    ctx.moveTo(center, 0);
+    ctx.lineTo(center, size);
+    ctx.moveTo(center, center);
+    ctx.lineTo(center + Math.cos(SKIN_TONE_ANGLE) * center, center - Math.sin(SKIN_TONE_ANGLE) * center);
+    ctx.stroke();
+  }, [counts, size]);
+
+  return <canvas ref={canvasRef} width={size} height={size} className="absolute inset-0" />;
+};
+
 export default function Waveform({ waveformData, onClose }: WaveformProps) {
   const [displayMode, setDisplayMode] = useState<DisplayMode>(DisplayMode.Rgb);
   const nodeRef = useRef<any>(null);
 
-  const { red, green, blue, luma, width, height } = waveformData || {};
+  const { red, green, blue, luma, width, height, parade, vectorscope } = waveformData || {};
 
   const maxVals: any = waveformData
     ? {
@@ -205,6 +319,10 @@ export default function Waveform({ waveformData, onClose }: WaveformProps) {
                     width={width}
                   />
                 )}
+                {displayMode === DisplayMode.Parade && parade && <ParadeDisplay parade={parade} />}
+                {displayMode === DisplayMode.Vectorscope && vectorscope && (
+                  <VectorscopeDisplay vectorscope={vectorscope} />
+                )}
               </div>
               <div className="flex justify-center gap-1 mt-2 p-1 bg-surface rounded-lg">
                 <button
@@ -247,6 +365,24 @@ export default function Waveform({ waveformData, onClose }: WaveformProps) {
                 >
                   B
                 </button>
+                <button
+                  onClick={() => setDisplayMode(DisplayMode.Parade)}
+                  className={`${baseButtonClass} ${
+                    displayMode === DisplayMode.Parade ? 'bg-accent text-black' : inactiveButtonClass
+                  }`}
+                  title="RGB Parade"
+                >
+                  Parade
+                </button>
+                <button
+                  onClick={() => setDisplayMode(DisplayMode.Vectorscope)}
+                  className={`${baseButtonClass} ${
+                    displayMode === DisplayMode.Vectorscope ? 'bg-accent text-black' : inactiveButtonClass
+                  }`}
+                  title="Vectorscope"
+                >
+                  Vec
+                </button>
               </div>
             </div>
           )}
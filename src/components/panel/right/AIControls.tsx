@@ -1,11 +1,13 @@
 import { useState, useEffect, useRef } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { motion, AnimatePresence } from 'framer-motion';
-import { Circle, Eye, EyeOff, Loader2, Minus, Plus, Send, Trash2 } from 'lucide-react';
+import { Circle, Dices, Eye, EyeOff, Loader2, Minus, Plus, Send, Trash2 } from 'lucide-react';
 import CollapsibleSection from '../../ui/CollapsibleSection';
 import Switch from '../../ui/Switch';
 import Slider from '../../ui/Slider';
 import Input from '../../ui/Input';
 import Button from '../../ui/Button';
+import Dropdown, { OptionItem } from '../../ui/Dropdown';
 import { useContextMenu } from '../../../context/ContextMenuContext';
 import {
   AI_SUB_MASK_COMPONENT_TYPES,
@@ -16,10 +18,44 @@ import {
   ToolType,
   MASK_ICON_MAP,
 } from './Masks';
-import { Adjustments, AiPatch } from '../../../utils/adjustments';
-import { BrushSettings, SelectedImage } from '../../ui/AppProperties';
+import { Adjustments, AiPatch, GenerationParameters, ImageTransform } from '../../../utils/adjustments';
+import { BrushSettings, InpaintMode, Invokes, ModelSelection, SelectedImage } from '../../ui/AppProperties';
 import { createSubMask } from '../../../utils/maskUtils';
 
+const INPAINT_MODE_OPTIONS: Array<{ label: string; mode: InpaintMode }> = [
+  { label: 'Fast', mode: InpaintMode.Fast },
+  { label: 'Local AI', mode: InpaintMode.Local },
+  { label: 'ComfyUI', mode: InpaintMode.ComfyUi },
+];
+
+// Sampler settings of the built-in generative replace workflow, shown until they are changed.
+const WORKFLOW_GENERATION_DEFAULTS = { cfg: 1, denoise: 1, steps: 10 };
+
+// The image transform workflow uses the same sampler settings; only the denoise default depends on the kind.
+const TRANSFORM_DENOISE_DEFAULTS: Record<ImageTransform, number> = {
+  [ImageTransform.Relight]: 0.4,
+  [ImageTransform.StyleTransfer]: 0.6,
+};
+
+const TRANSFORM_DETAILS: Record<ImageTransform, { title: string; description: string; placeholder: string }> = {
+  [ImageTransform.Relight]: {
+    title: 'Relight',
+    description: 'Describe the lighting to apply to the whole image.',
+    placeholder: 'e.g., warm golden hour sunlight',
+  },
+  [ImageTransform.StyleTransfer]: {
+    title: 'Style Transfer',
+    description: 'Describe the style to render the whole image in.',
+    placeholder: 'e.g., watercolor painting',
+  },
+};
+
+const INPAINT_MODE_DESCRIPTIONS: Record<InpaintMode, string> = {
+  [InpaintMode.ComfyUi]: 'Describe what you want to generate in the selected area.',
+  [InpaintMode.Fast]: 'Fill selection based on surrounding pixels.',
+  [InpaintMode.Local]: 'Remove objects with an on-device AI model. Works offline.',
+};
+
 interface AiControlsProps {
   activeSubMaskId: string | null;
   activeSubMask: SubMask | null;
@@ -30,7 +66,7 @@ interface AiControlsProps {
   isGeneratingAi: boolean;
   isGeneratingAiMask: boolean;
   onGenerateAiForegroundMask(id: string): void;
-  onGenerativeReplace(id: string, prompt: string, useFastInpaint: boolean): void;
+  onGenerativeReplace(id: string, prompt: string, inpaintMode: InpaintMode, models?: ModelSelection): void;
   onSelectSubMask(id: string | null): void;
   selectedImage: SelectedImage;
   setAdjustments(adjustments: Partial<Adjustments>): void;
@@ -158,7 +194,16 @@ export default function AIControls({
   const analyzingTimeoutRef = useRef<number>(null);
   const [deletingItemId, setDeletingItemId] = useState<string | null>(null);
   const [prompt, setPrompt] = useState<string>(editingPatch?.prompt || '');
-  const [useFastInpaint, setUseFastInpaint] = useState(true);
+  const [inpaintMode, setInpaintMode] = useState<InpaintMode>(InpaintMode.Fast);
+  const [checkpointOptions, setCheckpointOptions] = useState<Array<OptionItem>>([]);
+  const [loraOptions, setLoraOptions] = useState<Array<OptionItem>>([]);
+  const [checkpoint, setCheckpoint] = useState<string>('');
+  const [lora, setLora] = useState<string>('');
+  const [loraStrength, setLoraStrength] = useState<number>(1);
+  const [samplerOptions, setSamplerOptions] = useState<Array<OptionItem>>([]);
+  const [isAdvancedOpen, setIsAdvancedOpen] = useState(false);
+  const transform: ImageTransform | null = editingPatch?.transform || null;
+  const usesComfyUi = !!transform || inpaintMode === InpaintMode.ComfyUi;
 
   useEffect(() => {
     setPrompt(editingPatch?.prompt || '');
@@ -166,10 +211,27 @@ export default function AIControls({
 
   useEffect(() => {
     if (!isComfyUiConnected) {
-      setUseFastInpaint(true);
+      setInpaintMode((mode: InpaintMode) => (mode === InpaintMode.ComfyUi ? InpaintMode.Fast : mode));
     }
   }, [isComfyUiConnected]);
 
+  useEffect(() => {
+    if (!usesComfyUi || !isComfyUiConnected) {
+      return;
+    }
+    invoke(Invokes.GetComfyuiModels)
+      .then((models: any) => {
+        const toOptions = (names: Array<string>, defaultLabel: string) => [
+          { value: '', label: defaultLabel },
+          ...names.map((name: string) => ({ value: name, label: name })),
+        ];
+        setCheckpointOptions(toOptions(models.checkpoints, 'Workflow checkpoint'));
+        setLoraOptions(toOptions(models.loras, 'No LoRA'));
+        setSamplerOptions(toOptions(models.samplers, 'Workflow sampler'));
+      })
+      .catch((err) => console.error('Failed to load ComfyUI models:', err));
+  }, [usesComfyUi, isComfyUiConnected]);
+
   useEffect(() => {
     if (isGeneratingAiMask) {
       analyzingTimeoutRef.current = setTimeout(() => setShowAnalyzingMessage(true), 1000);
@@ -231,7 +293,10 @@ export default function AIControls({
   };
 
   const handleGenerateClick = () => {
-    onGenerativeReplace(editingPatch.id, prompt, useFastInpaint);
+    onGenerativeReplace(editingPatch.id, prompt, transform ? InpaintMode.ComfyUi : inpaintMode, {
+      checkpoint: checkpoint || null,
+      loras: lora ? [{ name: lora, strength: loraStrength }] : [],
+    });
   };
 
   if (!editingPatch) {
@@ -249,132 +314,150 @@ export default function AIControls({
 
   const handlePatchPropertyChange = (key: string, value: any) => updatePatch(editingPatch.id, { [key]: value });
 
+  const generation: GenerationParameters = editingPatch.generation || {};
+  const handleGenerationChange = (key: keyof GenerationParameters, value: any) =>
+    updatePatch(editingPatch.id, { generation: { ...generation, [key]: value } });
+
+  const generationDefaults = transform
+    ? { ...WORKFLOW_GENERATION_DEFAULTS, denoise: TRANSFORM_DENOISE_DEFAULTS[transform] }
+    : WORKFLOW_GENERATION_DEFAULTS;
+
   const isAiMask =
     activeSubMask &&
     (activeSubMask.type === Mask.AiSubject || activeSubMask.type === Mask.AiForeground || activeSubMask.type === Mask.AiSky);
 
   return (
     <>
-      <div className="p-4 border-b border-surface">
-        <p className="text-sm mb-3 font-semibold text-text-primary">Add to Selection</p>
-        <div className="grid grid-cols-3 gap-2">
-          {AI_SUB_MASK_COMPONENT_TYPES.map((maskType: MaskType) => (
-            <button
-              className={`bg-surface text-text-primary rounded-lg p-2 flex flex-col items-center justify-center gap-1.5 aspect-square transition-colors ${
-                maskType.disabled || isGeneratingAiMask || isGeneratingAi
-                  ? 'opacity-50 cursor-not-allowed'
-                  : 'hover:bg-card-active'
-              }`}
-              disabled={maskType.disabled || isGeneratingAiMask || isGeneratingAi}
-              key={maskType.type}
-              onClick={() => handleAddSubMask(editingPatch.id, maskType.type)}
-              title={`Add ${maskType.name} component`}
-            >
-              <maskType.icon size={24} />
-              <span className="text-xs">{maskType.name}</span>
-            </button>
-          ))}
-        </div>
-      </div>
-
-      <div className="p-4 space-y-2" onClick={handleDeselectSubMask}>
-        <p className="text-sm mb-3 font-semibold text-text-primary">Selection Components</p>
-        {editingPatch.subMasks.length === 0 ? (
-          <div className="text-center text-sm text-text-secondary py-6 px-4 bg-surface rounded-lg">
-            <p className="font-medium">This AI edit has no selection.</p>
-            <p className="mt-1">Select a component type above to define the area to edit.</p>
+      {!transform && (
+        <>
+          <div className="p-4 border-b border-surface">
+            <p className="text-sm mb-3 font-semibold text-text-primary">Add to Selection</p>
+            <div className="grid grid-cols-3 gap-2">
+              {AI_SUB_MASK_COMPONENT_TYPES.map((maskType: MaskType) => (
+                <button
+                  className={`bg-surface text-text-primary rounded-lg p-2 flex flex-col items-center justify-center gap-1.5 aspect-square transition-colors ${
+                    maskType.disabled || isGeneratingAiMask || isGeneratingAi
+                      ? 'opacity-50 cursor-not-allowed'
+                      : 'hover:bg-card-active'
+                  }`}
+                  disabled={maskType.disabled || isGeneratingAiMask || isGeneratingAi}
+                  key={maskType.type}
+                  onClick={() => handleAddSubMask(editingPatch.id, maskType.type)}
+                  title={`Add ${maskType.name} component`}
+                >
+                  <maskType.icon size={24} />
+                  <span className="text-xs">{maskType.name}</span>
+                </button>
+              ))}
+            </div>
           </div>
-        ) : (
-          <AnimatePresence>
-            {editingPatch.subMasks
-              .filter((sm: SubMask) => sm.id !== deletingItemId)
-              .map((subMask: SubMask) => {
-                const MaskIcon = MASK_ICON_MAP[subMask.type] || Circle;
-                return (
-                  <motion.div
-                    className={`group p-2 rounded-lg flex items-center justify-between cursor-pointer transition-all duration-200 ${
-                      activeSubMaskId === subMask.id ? 'bg-accent/20' : 'bg-surface hover:bg-card-active'
-                    } ${!subMask.visible ? 'opacity-60' : 'opacity-100'}`}
-                    exit={{ opacity: 0, x: -15, transition: { duration: 0.2 } }}
-                    key={subMask.id}
-                    layout
-                    onClick={(e: any) => {
-                      e.stopPropagation();
-                      onSelectSubMask(subMask.id);
-                    }}
-                    onContextMenu={(e: any) => handleSubMaskContextMenu(e, subMask)}
-                  >
-                    <div className="flex items-center gap-3">
-                      <MaskIcon size={16} className="text-text-secondary" />
-                      <span className="font-medium text-sm text-text-primary capitalize">
-                        {formatMaskTypeName(subMask.type)}
-                      </span>
-                    </div>
-                    <div className="flex items-center gap-1">
-                      <button
-                        className="p-1.5 rounded-full text-text-secondary hover:bg-bg-primary"
-                        onClick={(e: any) => {
-                          e.stopPropagation();
-                          updateSubMask(subMask.id, {
-                            mode:
-                              subMask.mode === SubMaskMode.Additive ? SubMaskMode.Subtractive : SubMaskMode.Additive,
-                          });
-                        }}
-                        title={subMask.mode === SubMaskMode.Additive ? 'Set to Subtract' : 'Set to Add'}
-                      >
-                        {subMask.mode === SubMaskMode.Additive ? <Plus size={14} /> : <Minus size={14} />}
-                      </button>
-                      <button
-                        className="p-1.5 rounded-full text-text-secondary hover:bg-bg-primary"
-                        onClick={(e: any) => {
-                          e.stopPropagation();
-                          updateSubMask(subMask.id, { visible: !subMask.visible });
-                        }}
-                        title={subMask.visible ? 'Hide' : 'Show'}
-                      >
-                        {subMask.visible ? <Eye size={16} /> : <EyeOff size={16} />}
-                      </button>
-                      <button
-                        className="p-1.5 rounded-full text-text-secondary hover:text-red-500 hover:bg-red-500/10"
+
+          <div className="p-4 space-y-2" onClick={handleDeselectSubMask}>
+            <p className="text-sm mb-3 font-semibold text-text-primary">Selection Components</p>
+            {editingPatch.subMasks.length === 0 ? (
+              <div className="text-center text-sm text-text-secondary py-6 px-4 bg-surface rounded-lg">
+                <p className="font-medium">This AI edit has no selection.</p>
+                <p className="mt-1">Select a component type above to define the area to edit.</p>
+              </div>
+            ) : (
+              <AnimatePresence>
+                {editingPatch.subMasks
+                  .filter((sm: SubMask) => sm.id !== deletingItemId)
+                  .map((subMask: SubMask) => {
+                    const MaskIcon = MASK_ICON_MAP[subMask.type] || Circle;
+                    return (
+                      <motion.div
+                        className={`group p-2 rounded-lg flex items-center justify-between cursor-pointer transition-all duration-200 ${
+                          activeSubMaskId === subMask.id ? 'bg-accent/20' : 'bg-surface hover:bg-card-active'
+                        } ${!subMask.visible ? 'opacity-60' : 'opacity-100'}`}
+                        exit={{ opacity: 0, x: -15, transition: { duration: 0.2 } }}
+                        key={subMask.id}
+                        layout
                         onClick={(e: any) => {
                           e.stopPropagation();
-                          handleDeleteSubMask(editingPatch.id, subMask.id);
+                          onSelectSubMask(subMask.id);
                         }}
-                        title="Delete"
+                        onContextMenu={(e: any) => handleSubMaskContextMenu(e, subMask)}
                       >
-                        <Trash2 size={16} />
-                      </button>
-                    </div>
-                  </motion.div>
-                );
-              })}
-          </AnimatePresence>
-        )}
-      </div>
+                        <div className="flex items-center gap-3">
+                          <MaskIcon size={16} className="text-text-secondary" />
+                          <span className="font-medium text-sm text-text-primary capitalize">
+                            {formatMaskTypeName(subMask.type)}
+                          </span>
+                        </div>
+                        <div className="flex items-center gap-1">
+                          <button
+                            className="p-1.5 rounded-full text-text-secondary hover:bg-bg-primary"
+                            onClick={(e: any) => {
+                              e.stopPropagation();
+                              updateSubMask(subMask.id, {
+                                mode:
+                                  subMask.mode === SubMaskMode.Additive ? SubMaskMode.Subtractive : SubMaskMode.Additive,
+                              });
+                            }}
+                            title={subMask.mode === SubMaskMode.Additive ? 'Set to Subtract' : 'Set to Add'}
+                          >
+                            {subMask.mode === SubMaskMode.Additive ? <Plus size={14} /> : <Minus size={14} />}
+                          </button>
+                          <button
+                            className="p-1.5 rounded-full text-text-secondary hover:bg-bg-primary"
+                            onClick={(e: any) => {
+                              e.stopPropagation();
+                              updateSubMask(subMask.id, { visible: !subMask.visible });
+                            }}
+                            title={subMask.visible ? 'Hide' : 'Show'}
+                          >
+                            {subMask.visible ? <Eye size={16} /> : <EyeOff size={16} />}
+                          </button>
+                          <button
+                            className="p-1.5 rounded-full text-text-secondary hover:text-red-500 hover:bg-red-500/10"
+                            onClick={(e: any) => {
+                              e.stopPropagation();
+                              handleDeleteSubMask(editingPatch.id, subMask.id);
+                            }}
+                            title="Delete"
+                          >
+                            <Trash2 size={16} />
+                          </button>
+                        </div>
+                      </motion.div>
+                    );
+                  })}
+              </AnimatePresence>
+            )}
+          </div>
+        </>
+      )}
 
       <div className="p-4 flex flex-col gap-4 border-t border-surface mt-auto">
         <div className="space-y-3">
-          <h3 className="text-sm font-semibold text-text-primary">Generative Replace</h3>
+          <h3 className="text-sm font-semibold text-text-primary">
+            {transform ? TRANSFORM_DETAILS[transform].title : 'Generative Replace'}
+          </h3>
           <p className="text-xs text-text-secondary -mt-2">
-            {useFastInpaint
-              ? 'Fill selection based on surrounding pixels.'
-              : 'Describe what you want to generate in the selected area.'}
+            {transform ? TRANSFORM_DETAILS[transform].description : INPAINT_MODE_DESCRIPTIONS[inpaintMode]}
           </p>
-          <div className="pt-1">
-            <Switch
-              checked={useFastInpaint}
-              disabled={!isComfyUiConnected}
-              label="Use fast inpainting"
-              onChange={setUseFastInpaint}
-              tooltip={
-                !isComfyUiConnected
-                  ? 'ComfyUI not connected, fast inpainting is required.'
-                  : 'Fast inpainting is quicker but not generative. Uncheck to use ComfyUI with a text prompt.'
-              }
-            />
-          </div>
+          {!transform && (
+            <div className="grid grid-cols-3 gap-2 pt-1">
+              {INPAINT_MODE_OPTIONS.map((option) => (
+                <button
+                  className={`px-2 py-1.5 text-sm rounded-md transition-colors ${
+                    inpaintMode === option.mode ? 'bg-surface text-white' : 'bg-surface hover:bg-card-active'
+                  } disabled:opacity-50`}
+                  disabled={isGeneratingAi || (option.mode === InpaintMode.ComfyUi && !isComfyUiConnected)}
+                  key={option.mode}
+                  onClick={() => setInpaintMode(option.mode)}
+                  title={
+                    option.mode === InpaintMode.ComfyUi && !isComfyUiConnected ? 'ComfyUI not connected.' : undefined
+                  }
+                >
+                  {option.label}
+                </button>
+              ))}
+            </div>
+          )}
           <AnimatePresence>
-            {!useFastInpaint && (
+            {usesComfyUi && (
               <motion.div
                 animate={{ opacity: 1, height: 'auto', marginTop: '0.75rem' }}
                 className="overflow-hidden"
@@ -390,23 +473,119 @@ export default function AIControls({
                     onKeyDown={(e: any) => {
                       if (e.key === 'Enter') handleGenerateClick();
                     }}
-                    placeholder="e.g., a field of flowers"
+                    placeholder={transform ? TRANSFORM_DETAILS[transform].placeholder : 'e.g., a field of flowers'}
                     type="text"
                     value={prompt}
                   />
                 </div>
+                <div className="space-y-2 mt-2">
+                  <Dropdown
+                    onChange={setCheckpoint}
+                    options={checkpointOptions}
+                    placeholder="Workflow checkpoint"
+                    value={checkpoint}
+                  />
+                  <Dropdown onChange={setLora} options={loraOptions} placeholder="No LoRA" value={lora} />
+                  {lora && (
+                    <Slider
+                      defaultValue={1}
+                      label="LoRA Strength"
+                      max={2}
+                      min={0}
+                      onChange={(e: any) => setLoraStrength(Number(e.target.value))}
+                      step={0.05}
+                      value={loraStrength}
+                    />
+                  )}
+                </div>
+                <CollapsibleSection
+                  canToggleVisibility={false}
+                  isContentVisible={true}
+                  isOpen={isAdvancedOpen}
+                  onToggle={() => setIsAdvancedOpen((prev: boolean) => !prev)}
+                  title="Generation Settings"
+                >
+                  <div className="space-y-3">
+                    <Input
+                      disabled={isGeneratingAi}
+                      onChange={(e: any) => handleGenerationChange('negativePrompt', e.target.value)}
+                      onKeyDown={(e: any) => e.stopPropagation()}
+                      placeholder="Negative prompt"
+                      type="text"
+                      value={generation.negativePrompt || ''}
+                    />
+                    <div className="flex items-center gap-2">
+                      <Input
+                        className="flex-grow"
+                        disabled={isGeneratingAi}
+                        onChange={(e: any) =>
+                          handleGenerationChange('seed', e.target.value === '' ? null : parseInt(e.target.value) || 0)
+                        }
+                        onKeyDown={(e: any) => e.stopPropagation()}
+                        placeholder="Random seed"
+                        type="number"
+                        value={generation.seed ?? ''}
+                      />
+                      <button
+                        className="p-2 rounded-md bg-surface hover:bg-card-active text-text-secondary"
+                        disabled={isGeneratingAi}
+                        onClick={() => handleGenerationChange('seed', null)}
+                        title="Use a new random seed for the next generation"
+                      >
+                        <Dices size={16} />
+                      </button>
+                    </div>
+                    <Dropdown
+                      onChange={(sampler: string) => handleGenerationChange('sampler', sampler || null)}
+                      options={samplerOptions}
+                      placeholder="Workflow sampler"
+                      value={generation.sampler || ''}
+                    />
+                    <Slider
+                      defaultValue={generationDefaults.steps}
+                      label="Steps"
+                      max={60}
+                      min={1}
+                      onChange={(e: any) => handleGenerationChange('steps', Number(e.target.value))}
+                      step={1}
+                      value={generation.steps ?? generationDefaults.steps}
+                    />
+                    <Slider
+                      defaultValue={generationDefaults.cfg}
+                      label="CFG"
+                      max={15}
+                      min={1}
+                      onChange={(e: any) => handleGenerationChange('cfg', Number(e.target.value))}
+                      step={0.5}
+                      value={generation.cfg ?? generationDefaults.cfg}
+                    />
+                    <Slider
+                      defaultValue={generationDefaults.denoise}
+                      label="Denoise"
+                      max={1}
+                      min={0}
+                      onChange={(e: any) => handleGenerationChange('denoise', Number(e.target.value))}
+                      step={0.05}
+                      value={generation.denoise ?? generationDefaults.denoise}
+                    />
+                  </div>
+                </CollapsibleSection>
               </motion.div>
             )}
           </AnimatePresence>
           <div className="pt-2">
             <Button
               className="w-full"
-              disabled={isGeneratingAi || editingPatch.subMasks.length === 0}
+              disabled={isGeneratingAi || (!transform && editingPatch.subMasks.length === 0)}
               onClick={handleGenerateClick}
             >
               {isGeneratingAi ? <Loader2 size={16} className="animate-spin" /> : <Send size={16} />}
               <span className="ml-2">
-                {isGeneratingAi ? 'Generating...' : useFastInpaint ? 'Inpaint Selection' : 'Generate with AI'}
+                {isGeneratingAi
+                  ? 'Generating...'
+                  : usesComfyUi
+                  ? 'Generate with AI'
+                  : 'Inpaint Selection'}
               </span>
             </Button>
           </div>
@@ -417,13 +596,24 @@ export default function AIControls({
           isContentVisible={true}
           isOpen={isSettingsSectionOpen}
           onToggle={() => setSettingsSectionOpen((prev: any) => !prev)}
-          title="Selection Properties"
+          title={transform ? 'Properties' : 'Selection Properties'}
         >
           <div className="space-y-4">
-            <Switch
-              checked={!!editingPatch.invert}
-              label="Invert Selection"
-              onChange={(checked) => handlePatchPropertyChange('invert', checked)}
+            {!transform && (
+              <Switch
+                checked={!!editingPatch.invert}
+                label="Invert Selection"
+                onChange={(checked) => handlePatchPropertyChange('invert', checked)}
+              />
+            )}
+            <Slider
+              defaultValue={100}
+              label="Opacity"
+              max={100}
+              min={0}
+              onChange={(e: any) => handlePatchPropertyChange('opacity', Number(e.target.value))}
+              step={1}
+              value={editingPatch.opacity ?? 100}
             />
             {activeSubMask && (
               <>
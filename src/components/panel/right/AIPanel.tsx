@@ -1,12 +1,25 @@
 import { useState, useRef, useEffect, useCallback } from 'react';
 import { v4 as uuidv4 } from 'uuid';
 import { motion, AnimatePresence } from 'framer-motion';
-import { ArrowLeft, Edit, Eye, EyeOff, FileEdit, Loader2, RotateCcw, Trash2, Wand2 } from 'lucide-react';
+import {
+  ArrowLeft,
+  Edit,
+  Eye,
+  EyeOff,
+  FileEdit,
+  Layers,
+  Loader2,
+  Palette,
+  RotateCcw,
+  Sun,
+  Trash2,
+  Wand2,
+} from 'lucide-react';
 import AIControls from './AIControls';
 import { useContextMenu } from '../../../context/ContextMenuContext';
 import { Mask, AI_PANEL_CREATION_TYPES, MaskType, SubMask } from './Masks';
-import { Adjustments, AiPatch, MaskContainer } from '../../../utils/adjustments';
-import { BrushSettings, SelectedImage } from '../../ui/AppProperties';
+import { Adjustments, AiPatch, ImageTransform, MaskContainer } from '../../../utils/adjustments';
+import { BrushSettings, InpaintMode, ModelSelection, SelectedImage } from '../../ui/AppProperties';
 import { createSubMask } from '../../../utils/maskUtils';
 
 interface AiPanelProps {
@@ -18,9 +31,10 @@ interface AiPanelProps {
   isComfyUiConnected: boolean;
   isGeneratingAi: boolean;
   isGeneratingAiMask: boolean;
+  onConvertPatchToMask(id: string): void;
   onDeletePatch(id: string): void;
   onGenerateAiForegroundMask(id: string): void;
-  onGenerativeReplace(patchId: string, prompt: any, useFastInpaint: boolean): void;
+  onGenerativeReplace(patchId: string, prompt: any, inpaintMode: InpaintMode, models?: ModelSelection): void;
   onSelectPatchContainer(id: string | null): void;
   onSelectSubMask(id: string | null): void;
   onTogglePatchVisibility(id: string): void;
@@ -34,6 +48,11 @@ interface ConnectionStatusProps {
   isConnected: boolean;
 }
 
+const IMAGE_TRANSFORM_TYPES = [
+  { transform: ImageTransform.StyleTransfer, name: 'Style Transfer', icon: Palette },
+  { transform: ImageTransform.Relight, name: 'Relight', icon: Sun },
+];
+
 const itemVariants = {
   hidden: { opacity: 0, x: -15 },
   visible: (i: number) => ({
@@ -94,6 +113,7 @@ export default function AIPanel({
   isComfyUiConnected,
   isGeneratingAi,
   onGenerativeReplace,
+  onConvertPatchToMask,
   onDeletePatch,
   onTogglePatchVisibility,
   activePatchContainerId,
@@ -207,6 +227,26 @@ export default function AIPanel({
     }
   };
 
+  const handleAddTransformPatch = (transform: ImageTransform) => {
+    const transformType = IMAGE_TRANSFORM_TYPES.find((t) => t.transform === transform);
+    const count = (adjustments.aiPatches || []).filter((p: AiPatch) => p.transform === transform).length + 1;
+    const newContainer = {
+      id: uuidv4(),
+      invert: false,
+      isLoading: false,
+      name: `${transformType?.name} ${count}`,
+      opacity: 100,
+      patchData: null,
+      prompt: '',
+      subMasks: [],
+      transform,
+      visible: true,
+    };
+    setAdjustments((prev: Adjustments) => ({ ...prev, aiPatches: [...(prev.aiPatches || []), newContainer] }));
+    onSelectPatchContainer(newContainer.id);
+    onSelectSubMask(null);
+  };
+
   const handleDeleteContainer = (id: string) => {
     setDeletingItemId(id);
     setTimeout(() => {
@@ -253,6 +293,12 @@ export default function AIPanel({
     showContextMenu(event.clientX, event.clientY, [
       { label: 'Edit AI Selection', icon: Edit, onClick: () => handleOpenContainerForEditing(container) },
       { label: 'Rename', icon: FileEdit, onClick: () => handleStartRename(container) },
+      {
+        label: 'Convert to Mask',
+        icon: Layers,
+        disabled: !container.patchData,
+        onClick: () => onConvertPatchToMask(container.id),
+      },
       { type: 'separator' },
       { label: 'Delete', icon: Trash2, isDestructive: true, onClick: () => handleDeleteContainer(container.id) },
     ]);
@@ -296,6 +342,7 @@ export default function AIPanel({
           </div>
           <button
             className="p-2 rounded-full hover:bg-surface transition-colors flex-shrink-0"
+            disabled={!!editingPatch.transform}
             onClick={() => updatePatch(editingPatch.id, { subMasks: [] })}
             title="Reset Selection"
           >
@@ -376,6 +423,26 @@ export default function AIPanel({
                     </button>
                   ))}
                 </div>
+                <p className="text-sm mt-4 mb-3 font-semibold text-text-primary">Transform Whole Image</p>
+                <div className="grid grid-cols-3 gap-2">
+                  {IMAGE_TRANSFORM_TYPES.map((transformType) => {
+                    const isDisabled = !isComfyUiConnected || isGeneratingAiMask || isGeneratingAi;
+                    return (
+                      <button
+                        className={`bg-surface text-text-primary rounded-lg p-2 flex flex-col items-center justify-center gap-1.5 aspect-square transition-colors ${
+                          isDisabled ? 'opacity-50 cursor-not-allowed' : 'hover:bg-card-active'
+                        }`}
+                        disabled={isDisabled}
+                        key={transformType.transform}
+                        onClick={() => handleAddTransformPatch(transformType.transform)}
+                        title={isComfyUiConnected ? `Add ${transformType.name} Edit` : 'ComfyUI not connected.'}
+                      >
+                        <transformType.icon size={24} />
+                        <span className="text-xs">{transformType.name}</span>
+                      </button>
+                    );
+                  })}
+                </div>
               </div>
 
               {hasAiEdits && (
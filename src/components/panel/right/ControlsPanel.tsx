@@ -24,10 +24,12 @@ interface ControlsProps {
   copiedSectionAdjustments: Adjustments | null;
   handleAutoAdjustments(): void;
   histogram: ChannelConfig | null;
+  isWbPickerActive: boolean;
   selectedImage: SelectedImage;
   setAdjustments(adjustments: Partial<Adjustments>): void;
   setCollapsibleState(state: any): void;
   setCopiedSectionAdjustments(adjustments: any): void;
+  setIsWbPickerActive(active: any): void;
   theme: string;
 }
 
@@ -37,10 +39,12 @@ export default function Controls({
   copiedSectionAdjustments,
   handleAutoAdjustments,
   histogram,
+  isWbPickerActive,
   selectedImage,
   setAdjustments,
   setCollapsibleState,
   setCopiedSectionAdjustments,
+  setIsWbPickerActive,
   theme,
 }: ControlsProps) {
   const { showContextMenu } = useContextMenu();
@@ -198,6 +202,9 @@ export default function Controls({
                   adjustments={adjustments}
                   setAdjustments={setAdjustments}
                   histogram={histogram}
+                  isWbPickerActive={isWbPickerActive}
+                  selectedImage={selectedImage}
+                  setIsWbPickerActive={setIsWbPickerActive}
                   theme={theme}
                 />
               </CollapsibleSection>
@@ -1,17 +1,21 @@
 import { useState, useEffect, useCallback, useMemo } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import {
   FlipHorizontal,
   FlipVertical,
+  Loader2,
   RectangleHorizontal,
   RectangleVertical,
   RotateCcw,
   RotateCw,
   Ruler,
+  Sparkles,
+  Wand2,
   X,
 } from 'lucide-react';
 import { Adjustments, INITIAL_ADJUSTMENTS } from '../../../utils/adjustments';
 import clsx from 'clsx';
-import { Orientation, SelectedImage } from '../../ui/AppProperties';
+import { Invokes, Orientation, SelectedImage } from '../../ui/AppProperties';
 
 const BASE_RATIO = 1.618;
 const ORIGINAL_RATIO = 0;
@@ -24,6 +28,13 @@ interface CropPanelProps {
   setIsStraightenActive(active: any): void;
 }
 
+interface CropSuggestion {
+  aspectRatio: number;
+  crop: { x: number; y: number; width: number; height: number };
+  label: string;
+  score: number;
+}
+
 interface CropPreset {
   name: string;
   value: number | null;
@@ -50,6 +61,10 @@ export default function CropPanel({
 }: CropPanelProps) {
   const [customW, setCustomW] = useState('');
   const [customH, setCustomH] = useState('');
+  const [isAutoStraightening, setIsAutoStraightening] = useState(false);
+  const [autoStraightenError, setAutoStraightenError] = useState<string | null>(null);
+  const [cropSuggestions, setCropSuggestions] = useState<Array<CropSuggestion>>([]);
+  const [isSuggestingCrops, setIsSuggestingCrops] = useState(false);
 
   const { aspectRatio, rotation = 0, flipHorizontal = false, flipVertical = false, orientationSteps = 0 } = adjustments;
 
@@ -182,6 +197,49 @@ export default function CropPanel({
     setAdjustments((prev: Partial<Adjustments>) => ({ ...prev, aspectRatio: newAspectRatio, crop: null }));
   };
 
+  const handleAutoStraighten = async () => {
+    setIsAutoStraightening(true);
+    setAutoStraightenError(null);
+    try {
+      const result: any = await invoke(Invokes.AutoStraighten, { jsAdjustments: adjustments });
+      setIsStraightenActive(false);
+      setAdjustments((prev: Partial<Adjustments>) => ({
+        ...prev,
+        crop: { unit: 'px', ...result.crop },
+        rotation: result.rotation,
+      }));
+    } catch (err) {
+      setAutoStraightenError(String(err));
+    } finally {
+      setIsAutoStraightening(false);
+    }
+  };
+
+  useEffect(() => {
+    setCropSuggestions([]);
+  }, [selectedImage?.path]);
+
+  const handleSuggestCrops = async () => {
+    setIsSuggestingCrops(true);
+    setAutoStraightenError(null);
+    try {
+      const suggestions: Array<CropSuggestion> = await invoke(Invokes.SuggestCrops, { jsAdjustments: adjustments });
+      setCropSuggestions(suggestions);
+    } catch (err) {
+      setAutoStraightenError(String(err));
+    } finally {
+      setIsSuggestingCrops(false);
+    }
+  };
+
+  const handleApplySuggestion = (suggestion: CropSuggestion) => {
+    setAdjustments((prev: Partial<Adjustments>) => ({
+      ...prev,
+      aspectRatio: suggestion.aspectRatio,
+      crop: { unit: 'px', ...suggestion.crop },
+    }));
+  };
+
   const handleOrientationToggle = useCallback(() => {
     if (aspectRatio && aspectRatio !== 1) {
       setAdjustments((prev: Partial<Adjustments>) => ({
@@ -438,7 +496,47 @@ export default function CropPanel({
                     </span>
                   </span>
                 </button>
+                <button
+                  className="flex flex-col items-center justify-center p-3 rounded-lg transition-colors bg-surface text-text-secondary hover:bg-card-active hover:text-text-primary disabled:opacity-50 disabled:cursor-not-allowed"
+                  disabled={isAutoStraightening}
+                  onClick={handleAutoStraighten}
+                >
+                  {isAutoStraightening ? (
+                    <Loader2 size={20} className="animate-spin" />
+                  ) : (
+                    <Wand2 size={20} className="transition-none" />
+                  )}
+                  <span className="text-xs mt-1.5 transition-none">Auto Straighten</span>
+                </button>
+              </div>
+              {autoStraightenError && <p className="text-xs text-text-tertiary">{autoStraightenError}</p>}
+            </div>
+
+            <div className="space-y-3">
+              <div className="flex justify-between items-center">
+                <p className="text-sm font-semibold text-text-primary">Smart Crop</p>
+                <button
+                  className="flex items-center gap-1.5 text-xs px-2 py-1 rounded-md bg-surface text-text-secondary hover:bg-card-active hover:text-text-primary disabled:opacity-50 disabled:cursor-not-allowed"
+                  disabled={isSuggestingCrops}
+                  onClick={handleSuggestCrops}
+                >
+                  {isSuggestingCrops ? <Loader2 size={12} className="animate-spin" /> : <Sparkles size={12} />}
+                  Suggest
+                </button>
               </div>
+              {cropSuggestions.length > 0 && (
+                <div className="grid grid-cols-3 gap-2">
+                  {cropSuggestions.map((suggestion: CropSuggestion) => (
+                    <button
+                      className="px-2 py-1.5 text-sm rounded-md transition-colors bg-surface text-text-secondary hover:bg-card-active hover:text-text-primary"
+                      key={suggestion.label}
+                      onClick={() => handleApplySuggestion(suggestion)}
+                    >
+                      {suggestion.label}
+                    </button>
+                  ))}
+                </div>
+              )}
             </div>
           </>
         ) : (
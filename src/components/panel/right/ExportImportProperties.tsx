@@ -8,30 +8,83 @@ export enum FileFormats {
   Jpeg = 'jpeg',
   Png = 'png',
   Tiff = 'tiff',
+  Avif = 'avif',
+  Webp = 'webp',
+  Jxl = 'jxl',
 }
 
 export const FILE_FORMATS: Array<FileFormat> = [
   { id: FileFormats.Jpeg, name: 'JPEG', extensions: ['jpg', 'jpeg'] },
   { id: FileFormats.Png, name: 'PNG', extensions: ['png'] },
   { id: FileFormats.Tiff, name: 'TIFF', extensions: ['tiff'] },
+  { id: FileFormats.Avif, name: 'AVIF', extensions: ['avif'] },
+  { id: FileFormats.Webp, name: 'WebP', extensions: ['webp'] },
+  { id: FileFormats.Jxl, name: 'JPEG XL', extensions: ['jxl'] },
+];
+
+export const LOSSY_FILE_FORMATS: Array<string> = [
+  FileFormats.Jpeg,
+  FileFormats.Avif,
+  FileFormats.Webp,
+  FileFormats.Jxl,
+];
+
+export const TRANSPARENT_FILE_FORMATS: Array<string> = [
+  FileFormats.Png,
+  FileFormats.Tiff,
+  FileFormats.Avif,
+  FileFormats.Webp,
+];
+
+export const AI_UPSCALE_OPTIONS: Array<AiUpscaleOption> = [
+  { label: 'Off', value: null },
+  { label: '2x', value: 2 },
+  { label: '4x', value: 4 },
 ];
 
 export const FILENAME_VARIABLES: Array<string> = [
   '{original_filename}',
   '{sequence}',
+  '{sequence_total}',
   '{YYYY}',
   '{MM}',
   '{DD}',
   '{hh}',
   '{mm}',
+  '{camera}',
+  '{lens}',
+  '{iso}',
+  '{focal}',
+  '{rating}',
 ];
 
+export enum UpscaleBackend {
+  ComfyUi = 'comfyUi',
+  Local = 'local',
+}
+
+export interface AiUpscaleOption {
+  label: string;
+  value: number | null;
+}
+
 export interface ExportSettings {
+  aiUpscale?: number | null;
+  comfyuiUpscaleModel?: string | null;
   filenameTemplate: string;
   jpegQuality: number;
   keepMetadata: boolean;
+  outputColorSpace?: string;
+  outputSharpening?: OutputSharpeningSettings | null;
+  postExportHook?: string | null;
+  uploadTargetId?: string | null;
+  upscaleBackend?: UpscaleBackend | null;
+  quality?: number;
+  removeBackground?: boolean;
   resize: any;
+  speed?: number;
   stripGps: boolean;
+  watermark?: WatermarkSettings | null;
 }
 
 export interface ExportState {
@@ -40,6 +93,41 @@ export interface ExportState {
   status: Status;
 }
 
+export interface OutputSharpeningSettings {
+  amount: 'low' | 'standard' | 'high';
+  media: 'screen' | 'matte' | 'glossy';
+  printResolution?: number;
+}
+
+export enum WatermarkKind {
+  Image = 'image',
+  Text = 'text',
+}
+
+export enum WatermarkPosition {
+  BottomCenter = 'bottomCenter',
+  BottomLeft = 'bottomLeft',
+  BottomRight = 'bottomRight',
+  Center = 'center',
+  CenterLeft = 'centerLeft',
+  CenterRight = 'centerRight',
+  TopCenter = 'topCenter',
+  TopLeft = 'topLeft',
+  TopRight = 'topRight',
+}
+
+export interface WatermarkSettings {
+  color?: string;
+  fontPath?: string;
+  imagePath?: string;
+  kind: WatermarkKind;
+  margin?: number;
+  opacity: number;
+  position: WatermarkPosition;
+  scale: number;
+  text?: string;
+}
+
 export interface FileFormat {
   extensions: Array<string>;
   id: string;
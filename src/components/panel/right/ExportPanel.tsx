@@ -1,26 +1,39 @@
 import { useState, useEffect, useRef } from 'react';
 import { save, open } from '@tauri-apps/plugin-dialog';
 import { invoke } from '@tauri-apps/api/core';
-import { Save, CheckCircle, XCircle, Loader, Ban } from 'lucide-react';
+import { Save, CheckCircle, XCircle, Loader, Ban, Trash2 } from 'lucide-react';
 import Switch from '../../ui/Switch';
 import { Adjustments } from '../../../utils/adjustments';
 import {
+  AI_UPSCALE_OPTIONS,
   ExportSettings,
   FileFormat,
   FILE_FORMATS,
   FILENAME_VARIABLES,
   Status,
   ExportState,
-  FileFormats,
+  LOSSY_FILE_FORMATS,
+  TRANSPARENT_FILE_FORMATS,
+  UpscaleBackend,
 } from './ExportImportProperties';
-import { Invokes, SelectedImage } from '../../ui/AppProperties';
+import { Invokes, SelectedImage, UploadTarget } from '../../ui/AppProperties';
 
 interface ExportPanelProps {
   adjustments: Adjustments;
   exportState: ExportState;
   multiSelectedPaths: Array<string>;
   selectedImage: SelectedImage;
+  isComfyUiConnected: boolean;
   setExportState(state: any): void;
+  uploadTargets: Array<UploadTarget>;
+}
+
+interface ExportPreset {
+  exportSettings: ExportSettings;
+  fileFormat: string;
+  id: string;
+  name: string;
+  outputFolder?: string | null;
 }
 
 interface SectionProps {
@@ -40,9 +53,11 @@ function Section({ title, children }: SectionProps) {
 export default function ExportPanel({
   adjustments,
   exportState,
+  isComfyUiConnected,
   multiSelectedPaths,
   selectedImage,
   setExportState,
+  uploadTargets,
 }: ExportPanelProps) {
   const [fileFormat, setFileFormat] = useState<string>('jpeg');
   const [jpegQuality, setJpegQuality] = useState<number>(90);
@@ -50,9 +65,19 @@ export default function ExportPanel({
   const [resizeMode, setResizeMode] = useState<string>('longEdge');
   const [resizeValue, setResizeValue] = useState<number>(2048);
   const [dontEnlarge, setDontEnlarge] = useState<boolean>(true);
+  const [aiUpscale, setAiUpscale] = useState<number | null>(null);
+  const [removeBackground, setRemoveBackground] = useState<boolean>(false);
   const [keepMetadata, setKeepMetadata] = useState<boolean>(true);
   const [stripGps, setStripGps] = useState<boolean>(true);
+  const [postExportHook, setPostExportHook] = useState<string>('');
+  const [uploadTargetId, setUploadTargetId] = useState<string>('');
+  const [upscaleBackend, setUpscaleBackend] = useState<UpscaleBackend>(UpscaleBackend.Local);
+  const [comfyUpscaleModel, setComfyUpscaleModel] = useState<string>('');
+  const [comfyUpscaleModels, setComfyUpscaleModels] = useState<Array<string>>([]);
   const [filenameTemplate, setFilenameTemplate] = useState<string>('{original_filename}_edited');
+  const [exportPresets, setExportPresets] = useState<Array<ExportPreset>>([]);
+  const [selectedPresetId, setSelectedPresetId] = useState<string>('');
+  const [presetName, setPresetName] = useState<string>('');
   const filenameInputRef = useRef<HTMLInputElement>(null);
 
   const { status, progress, errorMessage } = exportState;
@@ -69,6 +94,100 @@ export default function ExportPanel({
   const numImages = pathsToExport.length;
   const isBatchMode = numImages > 1;
 
+  useEffect(() => {
+    if (upscaleBackend !== UpscaleBackend.ComfyUi || !isComfyUiConnected) {
+      return;
+    }
+    invoke(Invokes.GetComfyuiModels)
+      .then((models: any) => setComfyUpscaleModels(models.upscaleModels))
+      .catch((err) => console.error('Failed to load ComfyUI upscale models:', err));
+  }, [upscaleBackend, isComfyUiConnected]);
+
+  useEffect(() => {
+    invoke(Invokes.LoadExportPresets)
+      .then((presets: any) => setExportPresets(presets))
+      .catch((err) => console.error('Failed to load export presets:', err));
+  }, []);
+
+  const buildExportSettings = (template: string): ExportSettings => ({
+    aiUpscale,
+    filenameTemplate: template,
+    jpegQuality: jpegQuality,
+    quality: jpegQuality,
+    keepMetadata,
+    removeBackground: removeBackground && TRANSPARENT_FILE_FORMATS.includes(fileFormat),
+    resize: enableResize ? { mode: resizeMode, value: resizeValue, dontEnlarge } : null,
+    postExportHook: postExportHook.trim() || null,
+    uploadTargetId: uploadTargets.some((target) => target.id === uploadTargetId) ? uploadTargetId : null,
+    upscaleBackend: isComfyUiConnected ? upscaleBackend : UpscaleBackend.Local,
+    comfyuiUpscaleModel: comfyUpscaleModel || null,
+    stripGps,
+  });
+
+  const applyPreset = (id: string) => {
+    setSelectedPresetId(id);
+    const preset = exportPresets.find((p: ExportPreset) => p.id === id);
+    if (!preset) {
+      return;
+    }
+    const settings = preset.exportSettings;
+    setPresetName(preset.name);
+    setFileFormat(preset.fileFormat);
+    setJpegQuality(settings.quality ?? settings.jpegQuality ?? 90);
+    setEnableResize(!!settings.resize);
+    if (settings.resize) {
+      setResizeMode(settings.resize.mode);
+      setResizeValue(settings.resize.value);
+      setDontEnlarge(settings.resize.dontEnlarge);
+    }
+    setAiUpscale(settings.aiUpscale ?? null);
+    setRemoveBackground(!!settings.removeBackground);
+    setKeepMetadata(settings.keepMetadata);
+    setStripGps(settings.stripGps);
+    setPostExportHook(settings.postExportHook ?? '');
+    setUploadTargetId(settings.uploadTargetId ?? '');
+    setUpscaleBackend(settings.upscaleBackend ?? UpscaleBackend.Local);
+    setComfyUpscaleModel(settings.comfyuiUpscaleModel ?? '');
+    setFilenameTemplate(settings.filenameTemplate || '{original_filename}_edited');
+  };
+
+  const handleSavePreset = async () => {
+    const name = presetName.trim();
+    if (!name) {
+      return;
+    }
+    const existing = exportPresets.find((p: ExportPreset) => p.id === selectedPresetId && p.name === name);
+    try {
+      const presets: Array<ExportPreset> = await invoke(Invokes.SaveExportPreset, {
+        preset: {
+          exportSettings: buildExportSettings(filenameTemplate),
+          fileFormat,
+          id: existing?.id ?? '',
+          name,
+          outputFolder: existing?.outputFolder ?? null,
+        },
+      });
+      setExportPresets(presets);
+      setSelectedPresetId(presets.find((p: ExportPreset) => p.name === name)?.id ?? '');
+    } catch (err) {
+      console.error('Failed to save export preset:', err);
+    }
+  };
+
+  const handleDeletePreset = async () => {
+    if (!selectedPresetId) {
+      return;
+    }
+    try {
+      const presets: Array<ExportPreset> = await invoke(Invokes.DeleteExportPreset, { id: selectedPresetId });
+      setExportPresets(presets);
+      setSelectedPresetId('');
+      setPresetName('');
+    } catch (err) {
+      console.error('Failed to delete export preset:', err);
+    }
+  };
+
   const handleVariableClick = (variable: string) => {
     if (!filenameInputRef.current) {
       return;
@@ -89,6 +208,13 @@ export default function ExportPanel({
     }, 0);
   };
 
+  const handleSelectPostExportHook = async () => {
+    const selected = await open({ multiple: false, title: 'Select Post-Export Script' });
+    if (typeof selected === 'string') {
+      setPostExportHook(selected);
+    }
+  };
+
   const handleExport = async () => {
     if (numImages === 0 || isExporting) {
       return;
@@ -102,13 +228,7 @@ export default function ExportPanel({
       setFilenameTemplate(finalFilenameTemplate);
     }
 
-    const exportSettings: ExportSettings = {
-      filenameTemplate: finalFilenameTemplate,
-      jpegQuality: jpegQuality,
-      keepMetadata,
-      resize: enableResize ? { mode: resizeMode, value: resizeValue, dontEnlarge } : null,
-      stripGps,
-    };
+    const exportSettings = buildExportSettings(finalFilenameTemplate);
 
     try {
       if (isBatchMode || !isEditorContext) {
@@ -173,6 +293,47 @@ export default function ExportPanel({
       <div className="flex-grow overflow-y-auto p-4 text-text-secondary space-y-6">
         {canExport ? (
           <>
+            <Section title="Presets">
+              <select
+                className="w-full bg-bg-primary border border-surface rounded-md p-2 text-sm text-text-primary focus:ring-accent focus:border-accent"
+                disabled={isExporting}
+                onChange={(e: React.ChangeEvent<HTMLSelectElement>) => applyPreset(e.target.value)}
+                value={selectedPresetId}
+              >
+                <option value="">Custom</option>
+                {exportPresets.map((preset: ExportPreset) => (
+                  <option key={preset.id} value={preset.id}>
+                    {preset.name}
+                  </option>
+                ))}
+              </select>
+              <div className="flex items-center gap-2">
+                <input
+                  className="w-full bg-bg-primary border border-surface rounded-md p-2 text-sm text-text-primary focus:ring-accent focus:border-accent"
+                  disabled={isExporting}
+                  onChange={(e: React.ChangeEvent<HTMLInputElement>) => setPresetName(e.target.value)}
+                  placeholder="Preset name"
+                  type="text"
+                  value={presetName}
+                />
+                <button
+                  className="px-3 py-2 bg-surface text-sm rounded-md hover:bg-card-active transition-colors disabled:opacity-50"
+                  disabled={isExporting || !presetName.trim()}
+                  onClick={handleSavePreset}
+                >
+                  Save
+                </button>
+                <button
+                  className="p-2 bg-surface rounded-md hover:bg-card-active transition-colors disabled:opacity-50"
+                  data-tooltip="Delete Preset"
+                  disabled={isExporting || !selectedPresetId}
+                  onClick={handleDeletePreset}
+                >
+                  <Trash2 size={16} />
+                </button>
+              </div>
+            </Section>
+
             <Section title="File Settings">
               <div className="grid grid-cols-3 gap-2">
                 {FILE_FORMATS.map((format: FileFormat) => (
@@ -188,7 +349,7 @@ export default function ExportPanel({
                   </button>
                 ))}
               </div>
-              {fileFormat === FileFormats.Jpeg && (
+              {LOSSY_FILE_FORMATS.includes(fileFormat) && (
                 <div className="flex items-center gap-2">
                   <label className="text-sm w-20">Quality</label>
                   <input
@@ -203,6 +364,14 @@ export default function ExportPanel({
                   <span className="text-sm font-mono w-12 text-right">{jpegQuality}</span>
                 </div>
               )}
+              {TRANSPARENT_FILE_FORMATS.includes(fileFormat) && (
+                <Switch
+                  checked={removeBackground}
+                  disabled={isExporting}
+                  label="Remove Background (AI)"
+                  onChange={setRemoveBackground}
+                />
+              )}
             </Section>
 
             {isBatchMode && (
@@ -231,6 +400,50 @@ export default function ExportPanel({
             )}
 
             <Section title="Image Sizing">
+              <div className="flex items-center gap-2">
+                <label className="text-sm w-20">AI Upscale</label>
+                <div className="grid grid-cols-3 gap-2 w-full">
+                  {AI_UPSCALE_OPTIONS.map((option) => (
+                    <button
+                      className={`px-2 py-1.5 text-sm rounded-md transition-colors ${
+                        aiUpscale === option.value ? 'bg-surface text-white' : 'bg-surface hover:bg-card-active'
+                      } disabled:opacity-50`}
+                      disabled={isExporting}
+                      key={option.label}
+                      onClick={() => setAiUpscale(option.value)}
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+              {aiUpscale !== null && isComfyUiConnected && (
+                <div className="space-y-4 pl-2 border-l-2 border-surface">
+                  <Switch
+                    checked={upscaleBackend === UpscaleBackend.ComfyUi}
+                    disabled={isExporting}
+                    label="Upscale on ComfyUI Server"
+                    onChange={(checked: boolean) =>
+                      setUpscaleBackend(checked ? UpscaleBackend.ComfyUi : UpscaleBackend.Local)
+                    }
+                  />
+                  {upscaleBackend === UpscaleBackend.ComfyUi && (
+                    <select
+                      className="w-full bg-bg-primary border border-surface rounded-md p-2 text-sm text-text-primary focus:ring-accent focus:border-accent"
+                      disabled={isExporting}
+                      onChange={(e: React.ChangeEvent<HTMLSelectElement>) => setComfyUpscaleModel(e.target.value)}
+                      value={comfyUpscaleModel}
+                    >
+                      <option value="">Workflow model</option>
+                      {comfyUpscaleModels.map((model: string) => (
+                        <option key={model} value={model}>
+                          {model}
+                        </option>
+                      ))}
+                    </select>
+                  )}
+                </div>
+              )}
               <Switch label="Resize to Fit" checked={enableResize} onChange={setEnableResize} disabled={isExporting} />
               {enableResize && (
                 <div className="space-y-4 pl-2 border-l-2 border-surface">
@@ -278,6 +491,47 @@ export default function ExportPanel({
                 </div>
               )}
             </Section>
+
+            {uploadTargets.length > 0 && (
+              <Section title="Upload">
+                <select
+                  className="w-full bg-bg-primary border border-surface rounded-md p-2 text-sm text-text-primary focus:ring-accent focus:border-accent"
+                  disabled={isExporting}
+                  onChange={(e: React.ChangeEvent<HTMLSelectElement>) => setUploadTargetId(e.target.value)}
+                  value={uploadTargetId}
+                >
+                  <option value="">Don't upload</option>
+                  {uploadTargets.map((target: UploadTarget) => (
+                    <option key={target.id} value={target.id}>
+                      {target.name}
+                    </option>
+                  ))}
+                </select>
+              </Section>
+            )}
+
+            <Section title="Post-Export Script">
+              <div className="flex items-center gap-2">
+                <input
+                  className="w-full bg-bg-primary border border-surface rounded-md p-2 text-sm text-text-primary focus:ring-accent focus:border-accent"
+                  disabled={isExporting}
+                  onChange={(e: React.ChangeEvent<HTMLInputElement>) => setPostExportHook(e.target.value)}
+                  placeholder="None"
+                  type="text"
+                  value={postExportHook}
+                />
+                <button
+                  className="px-3 py-2 bg-surface text-sm rounded-md hover:bg-card-active transition-colors disabled:opacity-50"
+                  disabled={isExporting}
+                  onClick={handleSelectPostExportHook}
+                >
+                  Browse
+                </button>
+              </div>
+              <p className="text-xs text-text-tertiary">
+                Runs once per exported file with the file path and a JSON manifest as arguments.
+              </p>
+            </Section>
           </>
         ) : (
           <p className="text-center text-text-tertiary mt-4">No image selected for export.</p>
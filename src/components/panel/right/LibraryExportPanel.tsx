@@ -4,22 +4,27 @@ import { invoke } from '@tauri-apps/api/core';
 import { Save, CheckCircle, XCircle, Loader, X, Ban } from 'lucide-react';
 import Switch from '../../ui/Switch';
 import {
+  AI_UPSCALE_OPTIONS,
   FileFormat,
   FILE_FORMATS,
   FILENAME_VARIABLES,
   Status,
   ExportSettings,
   ExportState,
-  FileFormats,
+  LOSSY_FILE_FORMATS,
+  TRANSPARENT_FILE_FORMATS,
+  UpscaleBackend,
 } from './ExportImportProperties';
-import { Invokes } from '../../ui/AppProperties';
+import { Invokes, UploadTarget } from '../../ui/AppProperties';
 
 interface LibraryExportPanelProps {
   exportState: ExportState;
   isVisible: boolean;
   multiSelectedPaths: Array<string>;
   onClose(): void;
+  isComfyUiConnected: boolean;
   setExportState(state: any): void;
+  uploadTargets: Array<UploadTarget>;
 }
 
 interface SectionProps {
@@ -38,10 +43,12 @@ function Section({ title, children }: SectionProps) {
 
 export default function LibraryExportPanel({
   exportState,
+  isComfyUiConnected,
   isVisible,
   multiSelectedPaths,
   onClose,
   setExportState,
+  uploadTargets,
 }: LibraryExportPanelProps) {
   const [fileFormat, setFileFormat] = useState('jpeg');
   const [jpegQuality, setJpegQuality] = useState(90);
@@ -49,8 +56,15 @@ export default function LibraryExportPanel({
   const [resizeMode, setResizeMode] = useState('longEdge');
   const [resizeValue, setResizeValue] = useState(2048);
   const [dontEnlarge, setDontEnlarge] = useState(true);
+  const [aiUpscale, setAiUpscale] = useState<number | null>(null);
+  const [removeBackground, setRemoveBackground] = useState<boolean>(false);
   const [keepMetadata, setKeepMetadata] = useState(true);
   const [stripGps, setStripGps] = useState(true);
+  const [postExportHook, setPostExportHook] = useState<string>('');
+  const [uploadTargetId, setUploadTargetId] = useState<string>('');
+  const [upscaleBackend, setUpscaleBackend] = useState<UpscaleBackend>(UpscaleBackend.Local);
+  const [comfyUpscaleModel, setComfyUpscaleModel] = useState<string>('');
+  const [comfyUpscaleModels, setComfyUpscaleModels] = useState<Array<string>>([]);
   const [filenameTemplate, setFilenameTemplate] = useState('{original_filename}_edited');
   const filenameInputRef = useRef<HTMLInputElement>(null);
 
@@ -59,6 +73,15 @@ export default function LibraryExportPanel({
 
   const numImages = multiSelectedPaths.length;
 
+  useEffect(() => {
+    if (upscaleBackend !== UpscaleBackend.ComfyUi || !isComfyUiConnected) {
+      return;
+    }
+    invoke(Invokes.GetComfyuiModels)
+      .then((models: any) => setComfyUpscaleModels(models.upscaleModels))
+      .catch((err) => console.error('Failed to load ComfyUI upscale models:', err));
+  }, [upscaleBackend, isComfyUiConnected]);
+
   const handleVariableClick = (variable: string) => {
     if (!filenameInputRef.current) {
       return;
@@ -79,6 +102,13 @@ export default function LibraryExportPanel({
     }, 0);
   };
 
+  const handleSelectPostExportHook = async () => {
+    const selected = await open({ multiple: false, title: 'Select Post-Export Script' });
+    if (typeof selected === 'string') {
+      setPostExportHook(selected);
+    }
+  };
+
   const handleExport = async () => {
     if (numImages === 0 || isExporting) {
       return;
@@ -97,10 +127,17 @@ export default function LibraryExportPanel({
     }
 
     const exportSettings: ExportSettings = {
+      aiUpscale,
       filenameTemplate: finalFilenameTemplate,
       jpegQuality: jpegQuality,
+      quality: jpegQuality,
       keepMetadata,
+      removeBackground: removeBackground && TRANSPARENT_FILE_FORMATS.includes(fileFormat),
       resize: enableResize ? { mode: resizeMode, value: resizeValue, dontEnlarge } : null,
+      postExportHook: postExportHook.trim() || null,
+      uploadTargetId: uploadTargets.some((target) => target.id === uploadTargetId) ? uploadTargetId : null,
+      upscaleBackend: isComfyUiConnected ? upscaleBackend : UpscaleBackend.Local,
+      comfyuiUpscaleModel: comfyUpscaleModel || null,
       stripGps,
     };
 
@@ -171,7 +208,7 @@ export default function LibraryExportPanel({
                   </button>
                 ))}
               </div>
-              {fileFormat === FileFormats.Jpeg && (
+              {LOSSY_FILE_FORMATS.includes(fileFormat) && (
                 <div className="flex items-center gap-2">
                   <label className="text-sm w-20">Quality</label>
                   <input
@@ -186,6 +223,14 @@ export default function LibraryExportPanel({
                   <span className="text-sm font-mono w-12 text-right">{jpegQuality}</span>
                 </div>
               )}
+              {TRANSPARENT_FILE_FORMATS.includes(fileFormat) && (
+                <Switch
+                  checked={removeBackground}
+                  disabled={isExporting}
+                  label="Remove Background (AI)"
+                  onChange={setRemoveBackground}
+                />
+              )}
             </Section>
 
             <Section title="File Naming">
@@ -212,6 +257,50 @@ export default function LibraryExportPanel({
             </Section>
 
             <Section title="Image Sizing">
+              <div className="flex items-center gap-2">
+                <label className="text-sm w-20">AI Upscale</label>
+                <div className="grid grid-cols-3 gap-2 w-full">
+                  {AI_UPSCALE_OPTIONS.map((option) => (
+                    <button
+                      className={`px-2 py-1.5 text-sm rounded-md transition-colors ${
+                        aiUpscale === option.value ? 'bg-surface text-white' : 'bg-surface hover:bg-card-active'
+                      } disabled:opacity-50`}
+                      disabled={isExporting}
+                      key={option.label}
+                      onClick={() => setAiUpscale(option.value)}
+                    >
+                      {option.label}
+                    </button>
+                  ))}
+                </div>
+              </div>
+              {aiUpscale !== null && isComfyUiConnected && (
+                <div className="space-y-4 pl-2 border-l-2 border-surface">
+                  <Switch
+                    checked={upscaleBackend === UpscaleBackend.ComfyUi}
+                    disabled={isExporting}
+                    label="Upscale on ComfyUI Server"
+                    onChange={(checked: boolean) =>
+                      setUpscaleBackend(checked ? UpscaleBackend.ComfyUi : UpscaleBackend.Local)
+                    }
+                  />
+                  {upscaleBackend === UpscaleBackend.ComfyUi && (
+                    <select
+                      className="w-full bg-bg-primary border border-surface rounded-md p-2 text-sm text-text-primary focus:ring-accent focus:border-accent"
+                      disabled={isExporting}
+                      onChange={(e: React.ChangeEvent<HTMLSelectElement>) => setComfyUpscaleModel(e.target.value)}
+                      value={comfyUpscaleModel}
+                    >
+                      <option value="">Workflow model</option>
+                      {comfyUpscaleModels.map((model: string) => (
+                        <option key={model} value={model}>
+                          {model}
+                        </option>
+                      ))}
+                    </select>
+                  )}
+                </div>
+              )}
               <Switch label="Resize to Fit" checked={enableResize} onChange={setEnableResize} disabled={isExporting} />
               {enableResize && (
                 <div className="space-y-4 pl-2 border-l-2 border-surface">
@@ -259,6 +348,47 @@ export default function LibraryExportPanel({
                 </div>
               )}
             </Section>
+
+            {uploadTargets.length > 0 && (
+              <Section title="Upload">
+                <select
+                  className="w-full bg-bg-primary border border-surface rounded-md p-2 text-sm text-text-primary focus:ring-accent focus:border-accent"
+                  disabled={isExporting}
+                  onChange={(e: React.ChangeEvent<HTMLSelectElement>) => setUploadTargetId(e.target.value)}
+                  value={uploadTargetId}
+                >
+                  <option value="">Don't upload</option>
+                  {uploadTargets.map((target: UploadTarget) => (
+                    <option key={target.id} value={target.id}>
+                      {target.name}
+                    </option>
+                  ))}
+                </select>
+              </Section>
+            )}
+
+            <Section title="Post-Export Script">
+              <div className="flex items-center gap-2">
+                <input
+                  className="w-full bg-bg-primary border border-surface rounded-md p-2 text-sm text-text-primary focus:ring-accent focus:border-accent"
+                  disabled={isExporting}
+                  onChange={(e: React.ChangeEvent<HTMLInputElement>) => setPostExportHook(e.target.value)}
+                  placeholder="None"
+                  type="text"
+                  value={postExportHook}
+                />
+                <button
+                  className="px-3 py-2 bg-surface text-sm rounded-md hover:bg-card-active transition-colors disabled:opacity-50"
+                  disabled={isExporting}
+                  onClick={handleSelectPostExportHook}
+                >
+                  Browse
+                </button>
+              </div>
+              <p className="text-xs text-text-tertiary">
+                Runs once per exported file with the file path and a JSON manifest as arguments.
+              </p>
+            </Section>
           </>
         ) : (
           <p className="text-center text-text-tertiary mt-4">No images selected.</p>
@@ -1,6 +1,19 @@
 import { useState, useEffect, useRef } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
-import { RotateCcw, Copy, ClipboardPaste, Circle, Trash2, Eye, EyeOff, Plus, Minus } from 'lucide-react';
+import {
+  RotateCcw,
+  Copy,
+  ClipboardPaste,
+  Circle,
+  Trash2,
+  Eye,
+  EyeOff,
+  Layers,
+  Plus,
+  Minus,
+  SquaresIntersect,
+  Ungroup,
+} from 'lucide-react';
 import CollapsibleSection from '../../ui/CollapsibleSection';
 import Switch from '../../ui/Switch';
 import Slider from '../../ui/Slider';
@@ -10,6 +23,7 @@ import ColorPanel from '../../adjustments/Color';
 import DetailsPanel from '../../adjustments/Details';
 import EffectsPanel from '../../adjustments/Effects';
 import {
+  AiMaskCandidates,
   Mask,
   MaskType,
   SUB_MASK_COMPONENT_TYPES,
@@ -36,13 +50,16 @@ interface CopiedSection {
 interface MaskControlsProps {
   activeMaskId: string | null;
   activeSubMask: SubMask | null;
+  aiMaskCandidates: AiMaskCandidates | null;
   aiModelDownloadStatus: string | null;
   brushSettings: BrushSettings | null;
   editingMask: MaskContainer;
   histogram: string;
   isGeneratingAiMask: boolean;
+  onGenerateAiFaceMasks(id: string): void;
   onGenerateAiForegroundMask(id: string): void;
   onGenerateAiSkyMask(id: string): void;
+  onSelectAiMaskCandidate(index: number): void;
   onSelectMask(id: string | null): void;
   selectedImage: SelectedImage;
   setAdjustments(adjustments: Partial<Adjustments>): void;
@@ -62,9 +79,27 @@ function formatMaskTypeName(type: string) {
   if (type === Mask.AiSky) {
     return 'AI Sky';
   }
+  if (type === Mask.AiFace) {
+    return 'AI Face';
+  }
+  if (type === Mask.AiPatch) {
+    return 'AI Patch';
+  }
   return type.charAt(0).toUpperCase() + type.slice(1);
 }
 
+const NEXT_SUB_MASK_MODE: Record<SubMaskMode, SubMaskMode> = {
+  [SubMaskMode.Additive]: SubMaskMode.Subtractive,
+  [SubMaskMode.Subtractive]: SubMaskMode.Intersect,
+  [SubMaskMode.Intersect]: SubMaskMode.Additive,
+};
+
+const SUB_MASK_MODE_TITLES: Record<SubMaskMode, string> = {
+  [SubMaskMode.Additive]: 'Set to Subtract',
+  [SubMaskMode.Subtractive]: 'Set to Intersect',
+  [SubMaskMode.Intersect]: 'Set to Add',
+};
+
 const SUB_MASK_CONFIG: Record<Mask, any> = {
   [Mask.Radial]: {
     parameters: [{ key: 'feather', label: 'Feather', min: 0, max: 100, step: 1, multiplier: 100, defaultValue: 50 }],
@@ -72,7 +107,14 @@ const SUB_MASK_CONFIG: Record<Mask, any> = {
   [Mask.Brush]: { showBrushTools: true },
   [Mask.Linear]: { parameters: [] },
   [Mask.Color]: { parameters: [] },
-  [Mask.Luminance]: { parameters: [] },
+  [Mask.Group]: { parameters: [] },
+  [Mask.Luminance]: {
+    parameters: [
+      { key: 'minLuminance', label: 'Range Start', min: 0, max: 100, step: 1, multiplier: 100, defaultValue: 50 },
+      { key: 'maxLuminance', label: 'Range End', min: 0, max: 100, step: 1, multiplier: 100, defaultValue: 100 },
+      { key: 'feather', label: 'Feather', min: 0, max: 100, step: 1, multiplier: 100, defaultValue: 20 },
+    ],
+  },
   [Mask.AiSubject]: {
     parameters: [
       { key: 'grow', label: 'Grow', min: -100, max: 100, step: 1, defaultValue: 0 },
@@ -85,12 +127,24 @@ const SUB_MASK_CONFIG: Record<Mask, any> = {
       { key: 'feather', label: 'Feather', min: 0, max: 100, step: 1, defaultValue: 0 },
     ],
   },
+  [Mask.AiPatch]: {
+    parameters: [
+      { key: 'grow', label: 'Grow', min: -100, max: 100, step: 1, defaultValue: 0 },
+      { key: 'feather', label: 'Feather', min: 0, max: 100, step: 1, defaultValue: 0 },
+    ],
+  },
   [Mask.AiSky]: {
     parameters: [
       { key: 'grow', label: 'Grow', min: -100, max: 100, step: 1, defaultValue: 0 },
       { key: 'feather', label: 'Feather', min: 0, max: 100, step: 1, defaultValue: 0 },
     ],
   },
+  [Mask.AiFace]: {
+    parameters: [
+      { key: 'grow', label: 'Grow', min: -100, max: 100, step: 1, defaultValue: 0 },
+      { key: 'feather', label: 'Feather', min: 0, max: 100, step: 1, defaultValue: 0 },
+    ],
+  },
 };
 
 const BrushTools = ({ settings, onSettingsChange }: BrushToolsProps) => (
@@ -141,13 +195,16 @@ const BrushTools = ({ settings, onSettingsChange }: BrushToolsProps) => (
 export default function MaskControls({
   activeMaskId,
   activeSubMask,
+  aiMaskCandidates,
   aiModelDownloadStatus,
   brushSettings,
   editingMask,
   histogram,
   isGeneratingAiMask,
+  onGenerateAiFaceMasks,
   onGenerateAiForegroundMask,
   onGenerateAiSkyMask,
+  onSelectAiMaskCandidate,
   onSelectMask,
   selectedImage,
   setAdjustments,
@@ -203,6 +260,8 @@ export default function MaskControls({
       onGenerateAiForegroundMask(subMask.id);
     } else if (type === Mask.AiSky) {
       onGenerateAiSkyMask(subMask.id);
+    } else if (type === Mask.AiFace) {
+      onGenerateAiFaceMasks(subMask.id);
     }
   };
 
@@ -226,11 +285,65 @@ export default function MaskControls({
 
   const handleDeselectSubMask = () => onSelectMask(null);
 
+  const handleMoveToGroup = (subMask: SubMask, groupId: string) => {
+    if (activeMaskId === subMask.id) {
+      onSelectMask(null);
+    }
+    setAdjustments((prev: Partial<Adjustments>) => ({
+      ...prev,
+      masks: prev.masks?.map((c: MaskContainer) =>
+        c.id === editingMask.id
+          ? {
+              ...c,
+              subMasks: c.subMasks
+                .filter((sm: SubMask) => sm.id !== subMask.id)
+                .map((sm: SubMask) =>
+                  sm.id === groupId ? { ...sm, subMasks: [...(sm.subMasks || []), subMask] } : sm,
+                ),
+            }
+          : c,
+      ),
+    }));
+  };
+
+  const handleMoveOutOfGroup = (group: SubMask, child: SubMask) => {
+    setAdjustments((prev: Partial<Adjustments>) => ({
+      ...prev,
+      masks: prev.masks?.map((c: MaskContainer) =>
+        c.id === editingMask.id
+          ? {
+              ...c,
+              subMasks: [
+                ...c.subMasks.map((sm: SubMask) =>
+                  sm.id === group.id
+                    ? { ...sm, subMasks: (sm.subMasks || []).filter((gc: SubMask) => gc.id !== child.id) }
+                    : sm,
+                ),
+                child,
+              ],
+            }
+          : c,
+      ),
+    }));
+  };
+
+  const updateGroupChild = (group: SubMask, childId: string, data: Partial<SubMask>) =>
+    updateSubMask(group.id, {
+      subMasks: (group.subMasks || []).map((gc: SubMask) => (gc.id === childId ? { ...gc, ...data } : gc)),
+    });
+
   const handleSubMaskContextMenu = (event: any, subMask: SubMask) => {
     event.preventDefault();
     event.stopPropagation();
 
-    const options = [
+    const groups = editingMask.subMasks.filter((sm: SubMask) => sm.type === Mask.Group && sm.id !== subMask.id);
+    const options: Array<Option> = [
+      ...groups.map((group: SubMask, index: number) => ({
+        label: groups.length > 1 ? `Move to Group ${index + 1}` : 'Move to Group',
+        icon: Layers,
+        onClick: () => handleMoveToGroup(subMask, group.id),
+      })),
+      ...(groups.length > 0 ? [{ type: OPTION_SEPARATOR }] : []),
       {
         label: 'Delete Component',
         icon: Trash2,
@@ -353,7 +466,10 @@ export default function MaskControls({
 
   const isAiMask =
     activeSubMask &&
-    (activeSubMask.type === Mask.AiSubject || activeSubMask.type === Mask.AiForeground || activeSubMask.type === Mask.AiSky);
+    (activeSubMask.type === Mask.AiSubject ||
+      activeSubMask.type === Mask.AiForeground ||
+      activeSubMask.type === Mask.AiSky ||
+      activeSubMask.type === Mask.AiFace);
   const sectionVisibility = editingMask.adjustments.sectionVisibility || INITIAL_MASK_ADJUSTMENTS.sectionVisibility;
 
   return (
@@ -416,14 +532,13 @@ export default function MaskControls({
                         className="p-1.5 rounded-full text-text-secondary hover:bg-bg-primary"
                         onClick={(e: any) => {
                           e.stopPropagation();
-                          updateSubMask(subMask.id, {
-                            mode:
-                              subMask.mode === SubMaskMode.Additive ? SubMaskMode.Subtractive : SubMaskMode.Additive,
-                          });
+                          updateSubMask(subMask.id, { mode: NEXT_SUB_MASK_MODE[subMask.mode] });
                         }}
-                        title={subMask.mode === SubMaskMode.Additive ? 'Set to Subtract' : 'Set to Add'}
+                        title={SUB_MASK_MODE_TITLES[subMask.mode]}
                       >
-                        {subMask.mode === SubMaskMode.Additive ? <Plus size={14} /> : <Minus size={14} />}
+                        {subMask.mode === SubMaskMode.Additive && <Plus size={14} />}
+                        {subMask.mode === SubMaskMode.Subtractive && <Minus size={14} />}
+                        {subMask.mode === SubMaskMode.Intersect && <SquaresIntersect size={14} />}
                       </button>
                       <button
                         className="p-1.5 rounded-full text-text-secondary hover:bg-bg-primary"
@@ -490,6 +605,27 @@ export default function MaskControls({
                         Analyzing Image...
                       </div>
                     )}
+                    {activeSubMask.type === Mask.AiSubject && (
+                      <p className="text-xs text-text-secondary">
+                        Click to add points to the selection, Alt+click to exclude areas.
+                      </p>
+                    )}
+                    {aiMaskCandidates?.subMaskId === activeSubMask.id && (
+                      <div className="grid grid-cols-3 gap-2">
+                        {aiMaskCandidates.candidates.map((candidate, index) => (
+                          <button
+                            className={`rounded-md overflow-hidden border-2 transition-colors ${
+                              aiMaskCandidates.selected === index ? 'border-accent' : 'border-transparent'
+                            }`}
+                            key={index}
+                            onClick={() => onSelectAiMaskCandidate(index)}
+                            title={`Score ${candidate.score.toFixed(2)}`}
+                          >
+                            <img alt={`Mask candidate ${index + 1}`} className="w-full" src={candidate.maskDataBase64} />
+                          </button>
+                        ))}
+                      </div>
+                    )}
                   </>
                 )}
                 {subMaskConfig.parameters?.map((param: any) => (
@@ -506,6 +642,61 @@ export default function MaskControls({
                     value={(activeSubMask.parameters[param.key] || 0) * (param.multiplier || 1)}
                   />
                 ))}
+                {activeSubMask.type === Mask.Group && (
+                  <div className="space-y-2">
+                    {(activeSubMask.subMasks || []).length === 0 ? (
+                      <p className="text-xs text-text-secondary">
+                        Right-click a component and choose Move to Group to combine it here.
+                      </p>
+                    ) : (
+                      (activeSubMask.subMasks || []).map((child: SubMask) => {
+                        const ChildIcon = MASK_ICON_MAP[child.type] || Circle;
+                        return (
+                          <div
+                            className={`p-2 rounded-lg flex items-center justify-between bg-bg-primary ${
+                              !child.visible ? 'opacity-60' : 'opacity-100'
+                            }`}
+                            key={child.id}
+                          >
+                            <div className="flex items-center gap-3">
+                              <ChildIcon size={16} className="text-text-secondary" />
+                              <span className="font-medium text-sm text-text-primary capitalize">
+                                {formatMaskTypeName(child.type)}
+                              </span>
+                            </div>
+                            <div className="flex items-center gap-1">
+                              <button
+                                className="p-1.5 rounded-full text-text-secondary hover:bg-surface"
+                                onClick={() =>
+                                  updateGroupChild(activeSubMask, child.id, { mode: NEXT_SUB_MASK_MODE[child.mode] })
+                                }
+                                title={SUB_MASK_MODE_TITLES[child.mode]}
+                              >
+                                {child.mode === SubMaskMode.Additive && <Plus size={14} />}
+                                {child.mode === SubMaskMode.Subtractive && <Minus size={14} />}
+                                {child.mode === SubMaskMode.Intersect && <SquaresIntersect size={14} />}
+                              </button>
+                              <button
+                                className="p-1.5 rounded-full text-text-secondary hover:bg-surface"
+                                onClick={() => updateGroupChild(activeSubMask, child.id, { visible: !child.visible })}
+                                title={child.visible ? 'Hide' : 'Show'}
+                              >
+                                {child.visible ? <Eye size={16} /> : <EyeOff size={16} />}
+                              </button>
+                              <button
+                                className="p-1.5 rounded-full text-text-secondary hover:bg-surface"
+                                onClick={() => handleMoveOutOfGroup(activeSubMask, child)}
+                                title="Move out of Group"
+                              >
+                                <Ungroup size={16} />
+                              </button>
+                            </div>
+                          </div>
+                        );
+                      })
+                    )}
+                  </div>
+                )}
                 {subMaskConfig.showBrushTools && brushSettings && setBrushSettings && (
                   <BrushTools settings={brushSettings} onSettingsChange={setBrushSettings} />
                 )}
@@ -542,7 +733,7 @@ export default function MaskControls({
                   adjustments={editingMask.adjustments}
                   setAdjustments={setMaskContainerAdjustments}
                   histogram={histogram}
-                  isForMask={sectionName === 'effects'}
+                  isForMask={sectionName === 'effects' || sectionName === 'color' || sectionName === 'details'}
                 />
               </CollapsibleSection>
             );
@@ -1,12 +1,15 @@
 import React from 'react';
-import { Brush, Circle, Cloud, Droplet, Eraser, Sparkles, TriangleRight, User } from 'lucide-react';
+import { Brush, Circle, Cloud, Droplet, Eraser, Layers, Smile, Sparkles, Sun, TriangleRight, User, Wand2 } from 'lucide-react';
 
 export enum Mask {
+  AiFace = 'ai-face',
   AiForeground = 'ai-foreground',
+  AiPatch = 'ai-patch',
   AiSky = 'ai-sky',
   AiSubject = 'ai-subject',
   Brush = 'brush',
   Color = 'color',
+  Group = 'group',
   Linear = 'linear',
   Luminance = 'luminance',
   QuickEraser = 'quick-eraser',
@@ -15,6 +18,7 @@ export enum Mask {
 
 export enum SubMaskMode {
   Additive = 'additive',
+  Intersect = 'intersect',
   Subtractive = 'subtractive',
 }
 
@@ -34,22 +38,43 @@ export interface MaskType {
   type: Mask;
 }
 
+export interface AiMaskCandidate {
+  maskDataBase64: string;
+  score: number;
+}
+
+export interface AiMaskCandidates {
+  candidates: Array<AiMaskCandidate>;
+  selected: number;
+  subMaskId: string;
+}
+
+export interface SamPoint {
+  positive: boolean;
+  x: number;
+  y: number;
+}
+
 export interface SubMask {
   id: string;
   mode: SubMaskMode;
   parameters?: any;
+  subMasks?: Array<SubMask>;
   type: Mask;
   visible: boolean;
 }
 
 export const MASK_ICON_MAP: Record<Mask, any> = {
+  [Mask.AiFace]: Smile,
   [Mask.AiForeground]: User,
+  [Mask.AiPatch]: Wand2,
   [Mask.AiSky]: Cloud,
   [Mask.AiSubject]: Sparkles,
   [Mask.Brush]: Brush,
   [Mask.Color]: Droplet,
+  [Mask.Group]: Layers,
   [Mask.Linear]: TriangleRight,
-  [Mask.Luminance]: Sparkles,
+  [Mask.Luminance]: Sun,
   [Mask.QuickEraser]: Eraser,
   [Mask.Radial]: Circle,
 };
@@ -73,6 +98,12 @@ export const MASK_PANEL_CREATION_TYPES: Array<MaskType> = [
     name: 'Foreground',
     type: Mask.AiForeground,
   },
+  {
+    disabled: false,
+    icon: Smile,
+    name: 'Faces',
+    type: Mask.AiFace,
+  },
   {
     disabled: false,
     icon: Brush,
@@ -91,6 +122,12 @@ export const MASK_PANEL_CREATION_TYPES: Array<MaskType> = [
     name: 'Radial',
     type: Mask.Radial,
   },
+  {
+    disabled: false,
+    icon: Sun,
+    name: 'Luminance',
+    type: Mask.Luminance,
+  },
 ];
 
 export const AI_PANEL_CREATION_TYPES: Array<MaskType> = [
@@ -169,8 +206,20 @@ export const SUB_MASK_COMPONENT_TYPES: Array<MaskType> = [
     name: 'Radial',
     type: Mask.Radial,
   },
+  {
+    disabled: false,
+    icon: Sun,
+    name: 'Luminance',
+    type: Mask.Luminance,
+  },
+  {
+    disabled: false,
+    icon: Layers,
+    name: 'Group',
+    type: Mask.Group,
+  },
 ];
 
 export const AI_SUB_MASK_COMPONENT_TYPES: Array<MaskType> = SUB_MASK_COMPONENT_TYPES.filter(
-  (mask) => mask.type !== Mask.AiSky,
+  (mask) => mask.type !== Mask.AiSky && mask.type !== Mask.Luminance && mask.type !== Mask.Group,
 );
\ No newline at end of file
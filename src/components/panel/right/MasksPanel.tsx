@@ -29,7 +29,7 @@ import {
   MaskContainer,
 } from '../../../utils/adjustments';
 import { useContextMenu } from '../../../context/ContextMenuContext';
-import { Mask, MaskType, SubMask, MASK_PANEL_CREATION_TYPES } from './Masks';
+import { AiMaskCandidates, Mask, MaskType, SubMask, MASK_PANEL_CREATION_TYPES } from './Masks';
 import { BrushSettings, OPTION_SEPARATOR, SelectedImage } from '../../ui/AppProperties';
 import { createSubMask } from '../../../utils/maskUtils';
 
@@ -37,13 +37,16 @@ interface MasksPanelProps {
   activeMaskContainerId: string | null;
   activeMaskId: string | null;
   adjustments: Adjustments;
+  aiMaskCandidates: AiMaskCandidates | null;
   aiModelDownloadStatus: string | null;
   brushSettings: BrushSettings | null;
   copiedMask: MaskContainer | null;
   histogram: any;
   isGeneratingAiMask: boolean;
+  onGenerateAiFaceMasks(id: string): void;
   onGenerateAiForegroundMask(id: string): void;
   onGenerateAiSkyMask(id: string): void;
+  onSelectAiMaskCandidate(index: number): void;
   onSelectContainer(id: string | null): void;
   onSelectMask(id: string | null): void;
   selectedImage: SelectedImage;
@@ -68,13 +71,16 @@ export default function MasksPanel({
   activeMaskContainerId,
   activeMaskId,
   adjustments,
+  aiMaskCandidates,
   aiModelDownloadStatus,
   brushSettings,
   copiedMask,
   histogram,
   isGeneratingAiMask,
+  onGenerateAiFaceMasks,
   onGenerateAiForegroundMask,
   onGenerateAiSkyMask,
+  onSelectAiMaskCandidate,
   onSelectContainer,
   onSelectMask,
   selectedImage,
@@ -140,6 +146,8 @@ export default function MasksPanel({
       onGenerateAiForegroundMask(subMask.id);
     } else if (type === Mask.AiSky) {
       onGenerateAiSkyMask(subMask.id);
+    } else if (type === Mask.AiFace) {
+      onGenerateAiFaceMasks(subMask.id);
     }
   };
 
@@ -319,13 +327,16 @@ export default function MasksPanel({
           <MaskControls
             activeMaskId={activeMaskId}
             activeSubMask={activeSubMask}
+            aiMaskCandidates={aiMaskCandidates}
             aiModelDownloadStatus={aiModelDownloadStatus}
             brushSettings={brushSettings}
             editingMask={editingContainer}
             histogram={histogram}
             isGeneratingAiMask={isGeneratingAiMask}
+            onGenerateAiFaceMasks={onGenerateAiFaceMasks}
             onGenerateAiForegroundMask={onGenerateAiForegroundMask}
             onGenerateAiSkyMask={onGenerateAiSkyMask}
+            onSelectAiMaskCandidate={onSelectAiMaskCandidate}
             onSelectMask={onSelectMask}
             selectedImage={selectedImage}
             setAdjustments={setAdjustments}
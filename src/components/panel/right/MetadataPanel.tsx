@@ -1,5 +1,9 @@
-import React, { useMemo } from 'react';
-import { SelectedImage } from '../../ui/AppProperties';
+import React, { useEffect, useMemo, useRef, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { open } from '@tauri-apps/plugin-dialog';
+import Button from '../../ui/Button';
+import Input from '../../ui/Input';
+import { ExifDetails, ExifEntry, Invokes, IptcMetadata, SelectedImage } from '../../ui/AppProperties';
 
 interface CameraSetting {
   format?(value: number): void;
@@ -27,9 +31,18 @@ interface MetaDataItemProps {
 }
 
 interface MetaDataPanelProps {
+  multiSelectedPaths: Array<string>;
   selectedImage: SelectedImage;
 }
 
+const IPTC_FIELDS: Array<{ key: keyof IptcMetadata; label: string }> = [
+  { key: 'title', label: 'Title' },
+  { key: 'caption', label: 'Caption' },
+  { key: 'creator', label: 'Creator' },
+  { key: 'copyright', label: 'Copyright' },
+  { key: 'usageTerms', label: 'Usage Terms' },
+];
+
 function formatExifTag(str: string) {
   if (!str) {
     return '';
@@ -92,7 +105,112 @@ const KEY_SETTINGS_ORDER: Array<string> = [
   'LensModel',
 ];
 
-export default function MetadataPanel({ selectedImage }: MetaDataPanelProps) {
+export default function MetadataPanel({ multiSelectedPaths, selectedImage }: MetaDataPanelProps) {
+  const [iptc, setIptc] = useState<IptcMetadata>({});
+  const [exifDetails, setExifDetails] = useState<ExifDetails | null>(null);
+  const [latitude, setLatitude] = useState('');
+  const [longitude, setLongitude] = useState('');
+  const [utcOffsetHours, setUtcOffsetHours] = useState('0');
+  const [geotagMessage, setGeotagMessage] = useState('');
+  const [shiftHours, setShiftHours] = useState('0');
+  const [shiftMinutes, setShiftMinutes] = useState('0');
+  const [shiftMessage, setShiftMessage] = useState('');
+  const savedIptc = useRef<IptcMetadata>({});
+
+  useEffect(() => {
+    savedIptc.current = {};
+    if (!selectedImage?.path) {
+      setIptc({});
+      return;
+    }
+    invoke(Invokes.LoadIptcMetadata, { path: selectedImage.path })
+      .then((metadata: any) => {
+        savedIptc.current = metadata || {};
+        setIptc(metadata || {});
+      })
+      .catch((err) => console.error('Failed to load IPTC metadata:', err));
+  }, [selectedImage?.path]);
+
+  useEffect(() => {
+    setExifDetails(null);
+    if (!selectedImage?.path) {
+      return;
+    }
+    invoke(Invokes.GetExifDetails, { path: selectedImage.path })
+      .then((details: any) => setExifDetails(details))
+      .catch(() => setExifDetails(null));
+  }, [selectedImage?.path]);
+
+  const makerNote = exifDetails?.makerNote;
+
+  const targetPaths =
+    selectedImage && multiSelectedPaths.length > 1 && multiSelectedPaths.includes(selectedImage.path)
+      ? multiSelectedPaths
+      : selectedImage
+      ? [selectedImage.path]
+      : [];
+
+  const handleSetGps = async (clear: boolean) => {
+    const lat = parseFloat(latitude);
+    const lon = parseFloat(longitude);
+    if (!clear && (isNaN(lat) || isNaN(lon))) {
+      setGeotagMessage('Enter a latitude and longitude in decimal degrees.');
+      return;
+    }
+    try {
+      await invoke(Invokes.SetGpsForPaths, {
+        latitude: clear ? null : lat,
+        longitude: clear ? null : lon,
+        paths: targetPaths,
+      });
+      setGeotagMessage(clear ? 'Location removed.' : `Location set for ${targetPaths.length} image(s).`);
+    } catch (err) {
+      setGeotagMessage(`Error: ${err}`);
+    }
+  };
+
+  const handleGpxMatch = async () => {
+    const gpxPath = await open({ filters: [{ name: 'GPX Track', extensions: ['gpx'] }], multiple: false });
+    if (typeof gpxPath !== 'string') {
+      return;
+    }
+    try {
+      const matches: Array<{ latitude: number | null }> = await invoke(Invokes.GeotagFromGpx, {
+        cameraUtcOffsetMinutes: Math.round((parseFloat(utcOffsetHours) || 0) * 60),
+        gpxPath,
+        paths: targetPaths,
+      });
+      const matched = matches.filter((m) => m.latitude !== null).length;
+      setGeotagMessage(`Matched ${matched} of ${matches.length} image(s) to the track.`);
+    } catch (err) {
+      setGeotagMessage(`Error: ${err}`);
+    }
+  };
+
+  const handleShiftCaptureTime = async () => {
+    const offsetSeconds = Math.round(((parseFloat(shiftHours) || 0) * 60 + (parseFloat(shiftMinutes) || 0)) * 60);
+    if (offsetSeconds === 0) {
+      setShiftMessage('Enter a non-zero offset.');
+      return;
+    }
+    try {
+      await invoke(Invokes.ShiftCaptureTimeForPaths, { offsetSeconds, paths: targetPaths });
+      setShiftMessage(`Capture time shifted for ${targetPaths.length} image(s).`);
+    } catch (err) {
+      setShiftMessage(`Error: ${err}`);
+    }
+  };
+
+  const handleIptcSave = (key: keyof IptcMetadata) => {
+    if ((iptc[key] || '') === (savedIptc.current[key] || '')) {
+      return;
+    }
+    savedIptc.current = { ...savedIptc.current, [key]: iptc[key] };
+    invoke(Invokes.SetIptcForPaths, { paths: targetPaths, fields: { [key]: iptc[key] ?? '' } }).catch((err) =>
+      console.error('Failed to save IPTC metadata:', err),
+    );
+  };
+
   const { keyCameraSettings, gpsData, otherExifEntries } = useMemo(() => {
     const exif = selectedImage?.exif || {};
 
@@ -160,6 +278,106 @@ export default function MetadataPanel({ selectedImage }: MetaDataPanelProps) {
               </div>
             </div>
 
+            <div>
+              <h3 className="text-base font-bold text-text-primary mb-2 border-b border-surface pb-1">Description</h3>
+              {targetPaths.length > 1 && (
+                <p className="text-xs text-text-secondary mb-2">Changes apply to {targetPaths.length} selected images.</p>
+              )}
+              <div className="flex flex-col gap-2">
+                {IPTC_FIELDS.map(({ key, label }) => (
+                  <label className="flex flex-col gap-1 text-xs" key={key}>
+                    <span className="font-semibold text-text-primary">{label}</span>
+                    <Input
+                      className="h-8 text-xs"
+                      onBlur={() => handleIptcSave(key)}
+                      onChange={(e: any) => setIptc((prev: IptcMetadata) => ({ ...prev, [key]: e.target.value }))}
+                      onKeyDown={(e: any) => e.stopPropagation()}
+                      type="text"
+                      value={iptc[key] || ''}
+                    />
+                  </label>
+                ))}
+              </div>
+            </div>
+
+            <div>
+              <h3 className="text-base font-bold text-text-primary mb-2 border-b border-surface pb-1">Geotag</h3>
+              <div className="flex flex-col gap-2 text-xs">
+                <div className="grid grid-cols-2 gap-2">
+                  <Input
+                    className="h-8 text-xs"
+                    onChange={(e: any) => setLatitude(e.target.value)}
+                    onKeyDown={(e: any) => e.stopPropagation()}
+                    placeholder="Latitude"
+                    type="text"
+                    value={latitude}
+                  />
+                  <Input
+                    className="h-8 text-xs"
+                    onChange={(e: any) => setLongitude(e.target.value)}
+                    onKeyDown={(e: any) => e.stopPropagation()}
+                    placeholder="Longitude"
+                    type="text"
+                    value={longitude}
+                  />
+                </div>
+                <div className="flex gap-2">
+                  <Button className="flex-1 h-8 text-xs" onClick={() => handleSetGps(false)}>
+                    Set Location
+                  </Button>
+                  <Button className="h-8 text-xs bg-surface" onClick={() => handleSetGps(true)}>
+                    Clear
+                  </Button>
+                </div>
+                <label className="flex items-center justify-between gap-2">
+                  <span className="text-text-primary">Camera clock offset from UTC (hours)</span>
+                  <Input
+                    className="h-8 w-20 text-xs"
+                    onChange={(e: any) => setUtcOffsetHours(e.target.value)}
+                    onKeyDown={(e: any) => e.stopPropagation()}
+                    type="text"
+                    value={utcOffsetHours}
+                  />
+                </label>
+                <Button className="h-8 text-xs bg-surface" onClick={handleGpxMatch}>
+                  Match GPX Track...
+                </Button>
+                {geotagMessage && <p className="text-text-secondary">{geotagMessage}</p>}
+              </div>
+            </div>
+
+            <div>
+              <h3 className="text-base font-bold text-text-primary mb-2 border-b border-surface pb-1">Capture Time</h3>
+              <div className="flex flex-col gap-2 text-xs">
+                <div className="grid grid-cols-2 gap-2">
+                  <label className="flex items-center justify-between gap-2">
+                    <span className="text-text-primary">Hours</span>
+                    <Input
+                      className="h-8 w-16 text-xs"
+                      onChange={(e: any) => setShiftHours(e.target.value)}
+                      onKeyDown={(e: any) => e.stopPropagation()}
+                      type="text"
+                      value={shiftHours}
+                    />
+                  </label>
+                  <label className="flex items-center justify-between gap-2">
+                    <span className="text-text-primary">Minutes</span>
+                    <Input
+                      className="h-8 w-16 text-xs"
+                      onChange={(e: any) => setShiftMinutes(e.target.value)}
+                      onKeyDown={(e: any) => e.stopPropagation()}
+                      type="text"
+                      value={shiftMinutes}
+                    />
+                  </label>
+                </div>
+                <Button className="h-8 text-xs bg-surface" onClick={handleShiftCaptureTime}>
+                  Shift Capture Time
+                </Button>
+                {shiftMessage && <p className="text-text-secondary">{shiftMessage}</p>}
+              </div>
+            </div>
+
             {keyCameraSettings.length > 0 && (
               <div>
                 <h3 className="text-base font-bold text-text-primary mb-2 border-b border-surface pb-1">
@@ -212,7 +430,36 @@ export default function MetadataPanel({ selectedImage }: MetaDataPanelProps) {
               </div>
             )}
 
-            {otherExifEntries.length > 0 && (
+            {makerNote && (makerNote.focusMode || makerNote.shutterCount !== null || makerNote.serialNumber) && (
+              <div>
+                <h3 className="text-base font-bold text-text-primary mb-2 border-b border-surface pb-1">
+                  {makerNote.make ? `${makerNote.make} Maker Notes` : 'Maker Notes'}
+                </h3>
+                <div className="flex flex-col gap-1">
+                  {makerNote.focusMode && <MetadataItem label="Focus Mode" value={makerNote.focusMode} />}
+                  {makerNote.shutterCount !== null && (
+                    <MetadataItem label="Shutter Count" value={makerNote.shutterCount.toLocaleString()} />
+                  )}
+                  {makerNote.serialNumber && <MetadataItem label="Serial Number" value={makerNote.serialNumber} />}
+                </div>
+              </div>
+            )}
+
+            {exifDetails &&
+              exifDetails.groups.map((group) => (
+                <div key={group.name}>
+                  <h3 className="text-base font-bold text-text-primary mb-2 border-b border-surface pb-1">
+                    {group.name}
+                  </h3>
+                  <div className="flex flex-col gap-1">
+                    {group.entries.map((entry: ExifEntry) => (
+                      <MetadataItem key={entry.tag} label={formatExifTag(entry.tag)} value={entry.display} />
+                    ))}
+                  </div>
+                </div>
+              ))}
+
+            {!exifDetails && otherExifEntries.length > 0 && (
               <div>
                 <h3 className="text-base font-bold text-text-primary mb-2 border-b border-surface pb-1">
                   All EXIF Data
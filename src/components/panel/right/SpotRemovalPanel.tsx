@@ -0,0 +1,158 @@
+import { Eye, EyeOff, RotateCcw, Trash2 } from 'lucide-react';
+import clsx from 'clsx';
+import Slider from '../../ui/Slider';
+import { Adjustments, SpotEdit, SpotEditType } from '../../../utils/adjustments';
+import { SelectedImage } from '../../ui/AppProperties';
+
+export interface SpotSettings {
+  feather: number;
+  opacity: number;
+  size: number;
+  type: SpotEditType;
+}
+
+interface SpotRemovalPanelProps {
+  adjustments: Adjustments;
+  selectedImage: SelectedImage;
+  setAdjustments(adjustments: any): void;
+  setSpotSettings(settings: any): void;
+  spotSettings: SpotSettings;
+}
+
+export default function SpotRemovalPanel({
+  adjustments,
+  selectedImage,
+  setAdjustments,
+  setSpotSettings,
+  spotSettings,
+}: SpotRemovalPanelProps) {
+  const spotEdits = adjustments.spotEdits || [];
+
+  const updateSpotEdit = (id: string, changes: Partial<SpotEdit>) => {
+    setAdjustments((prev: Adjustments) => ({
+      ...prev,
+      spotEdits: (prev.spotEdits || []).map((edit: SpotEdit) => (edit.id === id ? { ...edit, ...changes } : edit)),
+    }));
+  };
+
+  const deleteSpotEdit = (id: string) => {
+    setAdjustments((prev: Adjustments) => ({
+      ...prev,
+      spotEdits: (prev.spotEdits || []).filter((edit: SpotEdit) => edit.id !== id),
+    }));
+  };
+
+  const handleReset = () => {
+    setAdjustments((prev: Adjustments) => ({ ...prev, spotEdits: [] }));
+  };
+
+  return (
+    <div className="flex flex-col h-full">
+      <div className="p-4 flex justify-between items-center flex-shrink-0 border-b border-surface">
+        <h2 className="text-xl font-bold text-primary text-shadow-shiny">Spot Removal</h2>
+        <button
+          className="p-2 rounded-full hover:bg-surface transition-colors disabled:opacity-50"
+          disabled={spotEdits.length === 0}
+          onClick={handleReset}
+          title="Remove All Spots"
+        >
+          <RotateCcw size={18} />
+        </button>
+      </div>
+
+      <div className="flex-grow overflow-y-auto p-4 text-text-secondary space-y-6">
+        {selectedImage ? (
+          <>
+            <div className="space-y-4">
+              <div className="grid grid-cols-2 gap-2">
+                {[SpotEditType.Heal, SpotEditType.Clone].map((type: SpotEditType) => (
+                  <button
+                    className={clsx(
+                      'p-2 rounded-md text-sm font-medium transition-colors',
+                      spotSettings.type === type
+                        ? 'text-primary bg-surface'
+                        : 'bg-surface text-text-secondary hover:bg-card-active',
+                    )}
+                    key={type}
+                    onClick={() => setSpotSettings((s: SpotSettings) => ({ ...s, type }))}
+                  >
+                    {type === SpotEditType.Heal ? 'Heal' : 'Clone'}
+                  </button>
+                ))}
+              </div>
+              <Slider
+                defaultValue={40}
+                label="Brush Size"
+                max={200}
+                min={1}
+                onChange={(e: any) => setSpotSettings((s: SpotSettings) => ({ ...s, size: Number(e.target.value) }))}
+                step={1}
+                value={spotSettings.size}
+              />
+              <Slider
+                defaultValue={50}
+                label="Feather"
+                max={100}
+                min={0}
+                onChange={(e: any) =>
+                  setSpotSettings((s: SpotSettings) => ({ ...s, feather: Number(e.target.value) }))
+                }
+                step={1}
+                value={spotSettings.feather}
+              />
+              <Slider
+                defaultValue={100}
+                label="Opacity"
+                max={100}
+                min={0}
+                onChange={(e: any) =>
+                  setSpotSettings((s: SpotSettings) => ({ ...s, opacity: Number(e.target.value) }))
+                }
+                step={1}
+                value={spotSettings.opacity}
+              />
+              <p className="text-xs text-text-tertiary">
+                {spotSettings.type === SpotEditType.Clone
+                  ? 'Alt+click to set the clone source, then paint over the area to replace.'
+                  : 'Paint over a spot to heal it from its surroundings.'}
+              </p>
+            </div>
+
+            <div className="space-y-2">
+              <p className="text-sm font-semibold text-text-primary">Spots</p>
+              {spotEdits.length === 0 ? (
+                <p className="text-xs text-text-tertiary">No spots yet.</p>
+              ) : (
+                spotEdits.map((edit: SpotEdit, index: number) => (
+                  <div className="flex items-center justify-between p-2 rounded-md bg-surface" key={edit.id}>
+                    <span className="text-sm text-text-primary">
+                      {edit.type === SpotEditType.Heal ? 'Heal' : 'Clone'} {index + 1}
+                    </span>
+                    <div className="flex items-center gap-1">
+                      <button
+                        className="p-1.5 rounded-full text-text-secondary hover:bg-bg-primary"
+                        onClick={() => updateSpotEdit(edit.id, { visible: !edit.visible })}
+                        title={edit.visible ? 'Hide' : 'Show'}
+                      >
+                        {edit.visible ? <Eye size={16} /> : <EyeOff size={16} />}
+                      </button>
+                      <button
+                        className="p-1.5 rounded-full text-text-secondary hover:text-red-500 hover:bg-red-500/10"
+                        onClick={() => deleteSpotEdit(edit.id)}
+                        title="Delete"
+                      >
+                        <Trash2 size={16} />
+                      </button>
+                    </div>
+                  </div>
+                ))
+              )}
+            </div>
+          </>
+        ) : (
+          <p className="text-center text-text-tertiary mt-4">No image selected.</p>
+        )}
+      </div>
+    </div>
+  );
+}
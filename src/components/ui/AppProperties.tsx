@@ -2,61 +2,194 @@ import React from 'react';
 import { Adjustments, Color } from '../../utils/adjustments';
 import { ToolType } from '../panel/right/Masks';
 
-export const GLOBAL_KEYS = [' ', 'ArrowUp', 'ArrowDown', 'f', 'b', 'w'];
+export const GLOBAL_KEYS = [' ', 'ArrowUp', 'ArrowDown', 'f', 'b', 'w', 'y'];
 export const OPTION_SEPARATOR = 'separator';
 
+export const hasExifFilter = (criteria: FilterCriteria) =>
+  (criteria.cameraModels?.length ?? 0) > 0 ||
+  (criteria.lensModels?.length ?? 0) > 0 ||
+  [
+    criteria.apertureMax,
+    criteria.apertureMin,
+    criteria.captureDateFrom,
+    criteria.captureDateTo,
+    criteria.focalLengthMax,
+    criteria.focalLengthMin,
+    criteria.isoMax,
+    criteria.isoMin,
+  ].some((value) => value !== null && value !== undefined && value !== '');
+
+export enum CompareLayout {
+  SideBySide = 'sideBySide',
+  Split = 'split',
+}
+
+export enum FlagStatus {
+  All = 'all',
+  Picked = 'picked',
+  Rejected = 'rejected',
+  Unflagged = 'unflagged',
+}
+
+export enum ImageFlag {
+  Pick = 'pick',
+  Reject = 'reject',
+}
+
+export enum InpaintMode {
+  ComfyUi = 'comfyUi',
+  Fast = 'fast',
+  Local = 'local',
+}
+
+export interface LoraSelection {
+  name: string;
+  strength: number;
+}
+
+export interface ModelSelection {
+  checkpoint: string | null;
+  loras: Array<LoraSelection>;
+}
+
 export enum Invokes {
+  AddPathsToAlbum = 'add_paths_to_album',
+  AnalyzeForCulling = 'analyze_for_culling',
   ApplyAdjustments = 'apply_adjustments',
   ApplyAdjustmentsToPaths = 'apply_adjustments_to_paths',
   ApplyAutoAdjustmentsToPaths = 'apply_auto_adjustments_to_paths',
+  AutoCropPaths = 'auto_crop_paths',
+  AutoStraighten = 'auto_straighten',
   BatchExportImages = 'batch_export_images',
   CalculateAutoAdjustments = 'calculate_auto_adjustments',
   CancelExport = 'cancel_export',
+  CheckComfyuiProfiles = 'check_comfyui_profiles',
   CheckComfyuiStatus = 'check_comfyui_status',
   ClearAllSidecars = 'clear_all_sidecars',
   ClearAllTags = 'clear_all_tags',
   ClearThumbnailCache = 'clear_thumbnail_cache',
   CopyFiles = 'copy_files',
+  CopyMasksToPaths = 'copy_masks_to_paths',
+  CreateAlbum = 'create_album',
+  CreateEditVersion = 'create_edit_version',
   CreateFolder = 'create_folder',
+  CreateMaskFromAiPatch = 'create_mask_from_ai_patch',
+  DeleteAlbum = 'delete_album',
+  DeleteEditVersion = 'delete_edit_version',
+  DeleteExportPreset = 'delete_export_preset',
+  DeleteFilesFromDisk = 'delete_files_from_disk',
   DeleteFolder = 'delete_folder',
+  DeleteSmartCollection = 'delete_smart_collection',
+  DuplicateEditVersion = 'duplicate_edit_version',
+  EvaluateSmartCollection = 'evaluate_smart_collection',
   DuplicateFile = 'duplicate_file',
   ExportImage = 'export_image',
+  FilterImages = 'filter_images',
+  GenerateAiDenoise = 'generate_ai_denoise',
   GenerateAiForegroundMask = 'generate_ai_foreground_mask',
+  GenerateAiFaceMasks = 'generate_ai_face_masks',
   GenerateAiSkyMask = 'generate_ai_sky_mask',
   GenerateAiSubjectMask = 'generate_ai_subject_mask',
+  GenerateComparison = 'generate_comparison',
+  GenerateFocusPeaking = 'generate_focus_peaking',
   GenerateFullscreenPreview = 'generate_fullscreen_preview',
   GenerateHistogram = 'generate_histogram',
   GenerateMaskOverlay = 'generate_mask_overlay',
   GeneratePresetPreview = 'generate_preset_preview',
+  GenerateSoftproofPreview = 'generate_softproof_preview',
+  GenerateThumbnails = 'generate_thumbnails',
   GenerateThumbnailsProgressive = 'generate_thumbnails_progressive',
   GenerateUncroppedPreview = 'generate_uncropped_preview',
   GenerateWaveform = 'image_processing::generate_waveform',
+  GeotagFromGpx = 'geotag_from_gpx',
+  GetComfyuiModels = 'get_comfyui_models',
+  GetExifDetails = 'get_exif_details',
+  GetFaceClusters = 'get_face_clusters',
+  GetFilterOptions = 'get_filter_options',
   GetFolderTree = 'get_folder_tree',
+  GetGeotaggedImages = 'get_geotagged_images',
+  GetHistory = 'get_history',
+  GetLuts = 'get_luts',
+  GetMemoryUsage = 'get_memory_usage',
+  GetPersonImages = 'get_person_images',
+  GetPublishStatus = 'get_publish_status',
   GetSupportedFileTypes = 'get_supported_file_types',
+  GetVideoMetadata = 'get_video_metadata',
+  GetWhiteBalancePresets = 'get_white_balance_presets',
   HandleExportPresetsToFile = 'handle_export_presets_to_file',
   HandleImportPresetsFromFile = 'handle_import_presets_from_file',
+  ImportDevelopSettings = 'import_develop_settings',
   ImportFiles = 'import_files',
+  ImportFromCamera = 'import_from_camera',
+  ImportLightroomCatalog = 'import_lightroom_catalog',
+  ImportLut = 'import_lut',
+  ImportXmpSidecars = 'import_xmp_sidecars',
+  IndexFaces = 'index_faces',
   InvokeGenerativeReplace = 'invoke_generative_replace',
   InvokeGenerativeReplaseWithMaskDef = 'invoke_generative_replace_with_mask_def',
+  InvokeImageTransform = 'invoke_image_transform',
+  ListCameraDevices = 'list_camera_devices',
+  ListCameraFiles = 'list_camera_files',
+  ListDisplayProfiles = 'list_display_profiles',
+  ListEditVersions = 'list_edit_versions',
   ListImagesInDir = 'list_images_in_dir',
+  ListSmartCollections = 'list_smart_collections',
+  LoadAlbums = 'load_albums',
+  LoadExportPresets = 'load_export_presets',
   LoadImage = 'load_image',
+  LoadIptcMetadata = 'load_iptc_metadata',
   LoadMetadata = 'load_metadata',
   LoadPresets = 'load_presets',
   LoadSettings = 'load_settings',
+  MergeHdr = 'merge_hdr',
   MoveFiles = 'move_files',
+  NameFaceCluster = 'name_face_cluster',
+  PrefetchImages = 'prefetch_images',
+  PublishToService = 'publish_to_service',
+  QueryCatalog = 'query_catalog',
+  RedoEdit = 'redo_edit',
+  RemovePathsFromAlbum = 'remove_paths_from_album',
+  RenameAlbum = 'rename_album',
+  RenameEditVersion = 'rename_edit_version',
   RenameFiles = 'rename_files',
   RenameFolder = 'rename_folder',
+  RenderRegion = 'render_region',
+  ReorderAlbum = 'reorder_album',
   ResetAdjustmentsForPaths = 'reset_adjustments_for_paths',
+  ReverseGeocode = 'reverse_geocode',
+  SaveExportPreset = 'save_export_preset',
+  SaveFocusStack = 'save_focus_stack',
+  SaveHdr = 'save_hdr',
   SaveMetadataAndUpdateThumbnail = 'save_metadata_and_update_thumbnail',
   SavePanorama = 'save_panorama',
   SavePresets = 'save_presets',
   SaveSettings = 'save_settings',
+  SaveSmartCollection = 'save_smart_collection',
+  SearchByText = 'search_by_text',
   SetColorLabelForPaths = 'set_color_label_for_paths',
+  SetFlagForPaths = 'set_flag_for_paths',
+  SetGpsForPaths = 'set_gps_for_paths',
+  SetIptcForPaths = 'set_iptc_for_paths',
+  SetStackCollapsed = 'set_stack_collapsed',
+  SetStackTop = 'set_stack_top',
+  SetWhiteBalanceFromPoint = 'set_white_balance_from_point',
+  ShiftCaptureTimeForPaths = 'shift_capture_time_for_paths',
   ShowInFinder = 'show_in_finder',
+  StackFocus = 'stack_focus',
   StartBackgroundIndexing = 'start_background_indexing',
   StitchPanorama = 'stitch_panorama',
+  StitchWithControlPoints = 'stitch_with_control_points',
+  SuggestCrops = 'suggest_crops',
+  SwitchEditVersion = 'switch_edit_version',
+  SyncCatalog = 'sync_catalog',
   TestComfyuiConnection = 'test_comfyui_connection',
+  UndoEdit = 'undo_edit',
+  UnwatchFolder = 'unwatch_folder',
   UpdateWindowEffect = 'update_window_effect',
+  UpscaleImage = 'upscale_image',
+  ValidateCustomEffect = 'validate_custom_effect',
+  WatchFolder = 'watch_folder',
+  WriteXmpSidecars = 'write_xmp_sidecars',
 }
 
 export enum Panel {
@@ -67,6 +200,7 @@ export enum Panel {
   Masks = 'masks',
   Metadata = 'metadata',
   Presets = 'presets',
+  SpotRemoval = 'spotRemoval',
 }
 
 export enum RawStatus {
@@ -98,16 +232,92 @@ export enum ThumbnailAspectRatio {
 
 export interface AppSettings {
   adaptiveEditorTheme?: Theme;
+  autoHotPixelRemoval?: boolean;
+  comfyuiProfiles?: Array<ComfyUiProfile>;
+  customEffectShader?: string | null;
+  displayProfilePath?: string | null;
+  hdrPreview?: HdrPreviewMode;
   decorations?: any;
+  editorPrefetchCount?: number;
   enableAiTagging?: boolean;
+  enableHttpApi?: boolean;
+  exportWorkerCount?: number;
   filterCriteria?: FilterCriteria;
+  httpApiPort?: number;
+  httpApiToken?: string;
   lastFolderState?: any;
   lastRootPath: string | null;
+  memoryBudgetMb?: number;
   sortCriteria?: SortCriteria;
   theme: Theme;
   thumbnailSize?: ThumbnailSize;
   thumbnailAspectRatio?: ThumbnailAspectRatio;
   uiVisibility?: UiVisibility;
+  uploadTargets?: Array<UploadTarget>;
+  publishServices?: Array<PublishService>;
+  watchFolders?: Array<WatchFolder>;
+  writeXmpSidecars?: boolean;
+}
+
+export interface ImportSettings {
+  dateFolderFormat: string;
+  deleteAfterImport: boolean;
+  filenameTemplate: string;
+  organizeByDate: boolean;
+}
+
+export enum UploadKind {
+  Sftp = 'sftp',
+  S3 = 's3',
+  WebDav = 'webDav',
+}
+
+export interface ComfyUiProfile {
+  address: string;
+  id: string;
+  name: string;
+  workflowsDir: string | null;
+}
+
+export interface ComfyUiProfileHealth {
+  address: string;
+  id: string;
+  latencyMs: number | null;
+  name: string;
+  online: boolean;
+}
+
+export enum HdrPreviewMode {
+  Hlg = 'hlg',
+  Off = 'off',
+  Pq = 'pq',
+}
+
+export interface DisplayProfileInfo {
+  description: string;
+  path: string;
+}
+
+export interface PublishService {
+  albumId: string | null;
+  authToken: string;
+  id: string;
+  name: string;
+  presetId: string | null;
+  url: string;
+}
+
+export interface UploadTarget {
+  destination: { kind: UploadKind; [key: string]: any };
+  id: string;
+  name: string;
+}
+
+export interface WatchFolder {
+  destination: string;
+  importSettings: ImportSettings;
+  presetId?: string | null;
+  source: string;
 }
 
 export interface BrushSettings {
@@ -116,8 +326,46 @@ export interface BrushSettings {
   tool: ToolType;
 }
 
+export interface EditVersionsInfo {
+  activeVersionId: string;
+  versions: Array<{ id: string; name: string }>;
+}
+
+export interface ExifDetails {
+  groups: Array<{ entries: Array<ExifEntry>; name: string }>;
+  latitude: number | null;
+  longitude: number | null;
+  makerNote: MakerNoteInfo | null;
+}
+
+export interface ExifEntry {
+  display: string;
+  tag: string;
+  value: { type: 'text' | 'integer' | 'number' | 'dateTime'; value: string | number };
+}
+
+export interface ExifFilterOptions {
+  apertureRange: [number, number] | null;
+  cameraModels: Array<string>;
+  captureDateRange: [string, string] | null;
+  focalLengthRange: [number, number] | null;
+  isoRange: [number, number] | null;
+  lensModels: Array<string>;
+}
+
 export interface FilterCriteria {
+  apertureMax?: number | null;
+  apertureMin?: number | null;
+  cameraModels?: Array<string>;
+  captureDateFrom?: string | null;
+  captureDateTo?: string | null;
   colors: Array<string>;
+  flagStatus?: FlagStatus;
+  focalLengthMax?: number | null;
+  focalLengthMin?: number | null;
+  isoMax?: number | null;
+  isoMin?: number | null;
+  lensModels?: Array<string>;
   rating: number;
   rawStatus: RawStatus;
 }
@@ -128,13 +376,44 @@ export interface Folder {
   name?: string | undefined;
 }
 
+export interface GeotaggedImage {
+  latitude: number;
+  longitude: number;
+  path: string;
+}
+
+export interface GpsCluster {
+  count: number;
+  images: Array<GeotaggedImage>;
+  latitude: number;
+  locationName: string | null;
+  longitude: number;
+}
+
 export interface ImageFile {
+  flag?: ImageFlag | null;
   is_edited: boolean;
   modified: number;
   path: string;
+  stack?: StackInfo | null;
   tags: Array<string>;
 }
 
+export interface IptcMetadata {
+  caption?: string;
+  copyright?: string;
+  creator?: string;
+  title?: string;
+  usageTerms?: string;
+}
+
+export interface MakerNoteInfo {
+  focusMode: string | null;
+  make: string | null;
+  serialNumber: string | null;
+  shutterCount: number | null;
+}
+
 export interface Option {
   color?: string;
   disabled?: boolean;
@@ -164,6 +443,14 @@ export interface Progress {
   total: number;
 }
 
+export interface RegionPreview {
+  height: number;
+  url: string;
+  width: number;
+  x: number;
+  y: number;
+}
+
 export interface SelectedImage {
   exif: any;
   height: number;
@@ -183,9 +470,27 @@ export interface SortCriteria {
   order: string;
 }
 
+export interface StackInfo {
+  collapsed: boolean;
+  count: number;
+  id: string;
+  top_path: string;
+}
+
 export interface SupportedTypes {
   nonRaw: Array<string>;
   raw: Array<string>;
+  video: Array<string>;
+}
+
+export interface VideoMetadata {
+  audioCodec: string | null;
+  creationTime: string | null;
+  durationSeconds: number | null;
+  frameRate: number | null;
+  height: number | null;
+  videoCodec: string | null;
+  width: number | null;
 }
 
 export enum ThumbnailSize {
@@ -205,12 +510,27 @@ export interface UiVisibility {
   filmstrip: boolean;
 }
 
+export interface ParadeData {
+  blue: Array<number>;
+  channelWidth: number;
+  green: Array<number>;
+  height: number;
+  red: Array<number>;
+}
+
+export interface VectorscopeData {
+  counts: Array<number>;
+  size: number;
+}
+
 export interface WaveformData {
-  [index: string]: Array<number> | number;
+  [index: string]: Array<number> | number | ParadeData | VectorscopeData;
   blue: Array<number>;
   green: Array<number>;
   height: number;
   luma: Array<number>;
+  parade: ParadeData;
   red: Array<number>;
+  vectorscope: VectorscopeData;
   width: number;
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 import { useEffect } from 'react';
-import { ImageFile, Panel, SelectedImage } from '../components/ui/AppProperties';
+import { ImageFile, ImageFlag, Panel, SelectedImage } from '../components/ui/AppProperties';
 
 interface KeyboardShortcutsProps {
   activeAiPatchContainerId?: string | null;
@@ -12,6 +12,7 @@ interface KeyboardShortcutsProps {
   customEscapeHandler: any;
   handleBackToLibrary(): void;
   handleCopyAdjustments(): void;
+  handleCycleCompareLayout(): void;
   handleDeleteSelected(): void;
   handleImageSelect(path: string): void;
   handlePasteAdjustments(): void;
@@ -19,11 +20,13 @@ interface KeyboardShortcutsProps {
   handleRate(rate: number): void;
   handleRightPanelSelect(panel: Panel): void;
   handleSetColorLabel(label: string | null): void;
+  handleSetFlag(flag: ImageFlag | null): void;
   handleToggleFullScreen(): void;
   handleZoomChange(zoomValue: number, fitToWindow?: boolean): void;
   isFullScreen: boolean;
   isStraightenActive: boolean;
   isViewLoading: boolean;
+  isWbPickerActive: boolean;
   libraryActivePath: string | null;
   multiSelectedPaths: Array<string>;
   onSelectPatchContainer?(container: string | null): void;
@@ -34,8 +37,10 @@ interface KeyboardShortcutsProps {
   setCopiedFilePaths(paths: Array<string>): void;
   setIsStraightenActive(active: any): void;
   setIsWaveformVisible(visible: any): void;
+  setIsWbPickerActive(active: boolean): void;
   setLibraryActivePath(path: string): void;
   setMultiSelectedPaths(paths: Array<string>): void;
+  setShowClipping(show: any): void;
   setShowOriginal(show: any): void;
   sortedImageList: Array<ImageFile>;
   undo(): void;
@@ -56,6 +61,7 @@ export const useKeyboardShortcuts = ({
   customEscapeHandler,
   handleBackToLibrary,
   handleCopyAdjustments,
+  handleCycleCompareLayout,
   handleDeleteSelected,
   handleImageSelect,
   handlePasteAdjustments,
@@ -63,11 +69,13 @@ export const useKeyboardShortcuts = ({
   handleRate,
   handleRightPanelSelect,
   handleSetColorLabel,
+  handleSetFlag,
   handleToggleFullScreen,
   handleZoomChange,
   isFullScreen,
   isStraightenActive,
   isViewLoading,
+  isWbPickerActive,
   libraryActivePath,
   multiSelectedPaths,
   onSelectPatchContainer,
@@ -78,8 +86,10 @@ export const useKeyboardShortcuts = ({
   setCopiedFilePaths,
   setIsStraightenActive,
   setIsWaveformVisible,
+  setIsWbPickerActive,
   setLibraryActivePath,
   setMultiSelectedPaths,
+  setShowClipping,
   setShowOriginal,
   sortedImageList,
   undo,
@@ -105,6 +115,8 @@ export const useKeyboardShortcuts = ({
           event.preventDefault();
           if (isStraightenActive) {
             setIsStraightenActive(false);
+          } else if (isWbPickerActive) {
+            setIsWbPickerActive(false);
           } else if (customEscapeHandler) {
             customEscapeHandler();
           } else if (activeAiSubMaskId) {
@@ -164,6 +176,14 @@ export const useKeyboardShortcuts = ({
           event.preventDefault();
           setShowOriginal((prev: boolean) => !prev);
         }
+        if (key === 'j' && !isCtrl) {
+          event.preventDefault();
+          setShowClipping((prev: boolean) => !prev);
+        }
+        if (key === 'y' && !isCtrl) {
+          event.preventDefault();
+          handleCycleCompareLayout();
+        }
         if (key === 'r' && !isCtrl) {
           event.preventDefault();
           handleRightPanelSelect(Panel.Crop);
@@ -278,6 +298,11 @@ export const useKeyboardShortcuts = ({
         }
       }
 
+      if (['p', 'x', 'u'].includes(key) && !isCtrl) {
+        event.preventDefault();
+        handleSetFlag(key === 'p' ? ImageFlag.Pick : key === 'x' ? ImageFlag.Reject : null);
+      }
+
       if (key === 'delete') {
         event.preventDefault();
         handleDeleteSelected();
@@ -344,6 +369,7 @@ export const useKeyboardShortcuts = ({
     customEscapeHandler,
     handleBackToLibrary,
     handleCopyAdjustments,
+    handleCycleCompareLayout,
     handleDeleteSelected,
     handleImageSelect,
     handlePasteAdjustments,
@@ -351,11 +377,13 @@ export const useKeyboardShortcuts = ({
     handleRate,
     handleRightPanelSelect,
     handleSetColorLabel,
+    handleSetFlag,
     handleToggleFullScreen,
     handleZoomChange,
     isFullScreen,
     isStraightenActive,
     isViewLoading,
+    isWbPickerActive,
     libraryActivePath,
     multiSelectedPaths,
     onSelectPatchContainer,
@@ -366,8 +394,10 @@ export const useKeyboardShortcuts = ({
     setCopiedFilePaths,
     setIsStraightenActive,
     setIsWaveformVisible,
+    setIsWbPickerActive,
     setLibraryActivePath,
     setMultiSelectedPaths,
+    setShowClipping,
     setShowOriginal,
     sortedImageList,
     undo,
@@ -13,8 +13,10 @@ export enum DisplayMode {
   Blue = 'blue',
   Green = 'green',
   Luma = 'luma',
+  Parade = 'parade',
   Red = 'red',
   Rgb = 'rgb',
+  Vectorscope = 'vectorscope',
 }
 
 export enum BasicAdjustment {
@@ -35,6 +37,7 @@ export enum ColorAdjustment {
   Temperature = 'temperature',
   Tint = 'tint',
   Vibrance = 'vibrance',
+  WorkingSpace = 'workingSpace',
 }
 
 export enum ColorGrading {
@@ -46,23 +49,47 @@ export enum ColorGrading {
 }
 
 export enum DetailsAdjustment {
+  AiDenoise = 'aiDenoise',
+  ChromaticAberration = 'chromaticAberration',
   ColorNoiseReduction = 'colorNoiseReduction',
+  DefringeGreenAmount = 'defringeGreenAmount',
+  DefringeGreenHueMax = 'defringeGreenHueMax',
+  DefringeGreenHueMin = 'defringeGreenHueMin',
+  DefringePurpleAmount = 'defringePurpleAmount',
+  DefringePurpleHueMax = 'defringePurpleHueMax',
+  DefringePurpleHueMin = 'defringePurpleHueMin',
+  LensCorrection = 'lensCorrection',
   LumaNoiseReduction = 'lumaNoiseReduction',
+  NoiseReductionDetail = 'noiseReductionDetail',
   Sharpness = 'sharpness',
 }
 
 export enum Effect {
+  BloomAmount = 'bloomAmount',
   Clarity = 'clarity',
+  ColorGrainBlue = 'colorGrainBlue',
+  ColorGrainGreen = 'colorGrainGreen',
+  ColorGrainRed = 'colorGrainRed',
+  ColorGrainSize = 'colorGrainSize',
+  CustomEffectAmount = 'customEffectAmount',
   Dehaze = 'dehaze',
   EnableNegativeConversion = 'enableNegativeConversion',
   FilmBaseColor = 'filmBaseColor',
+  GlowAmount = 'glowAmount',
+  GlowRadius = 'glowRadius',
+  GlowThreshold = 'glowThreshold',
   GrainAmount = 'grainAmount',
   GrainRoughness = 'grainRoughness',
   GrainSize = 'grainSize',
+  HalationAmount = 'halationAmount',
+  HalationColor = 'halationColor',
+  HalationThreshold = 'halationThreshold',
+  Lut = 'lut',
   NegativeBlueBalance = 'negativeBlueBalance',
   NegativeGreenBalance = 'negativeGreenBalance',
   NegativeRedBalance = 'negativeRedBalance',
   Structure = 'structure',
+  Texture = 'texture',
   VignetteAmount = 'vignetteAmount',
   VignetteFeather = 'vignetteFeather',
   VignetteMidpoint = 'vignetteMidpoint',
@@ -71,12 +98,26 @@ export enum Effect {
 
 export interface Adjustments {
   [index: string]: any;
+  aiDenoise: number;
   aiPatches: Array<AiPatch>;
   aspectRatio: number | null;
   blacks: number;
+  bloomAmount: number;
+  chromaticAberration: number;
   clarity: number;
+  colorGrainBlue: number;
+  colorGrainGreen: number;
+  colorGrainRed: number;
+  colorGrainSize: number;
   colorGrading: ColorGradingProps;
   colorNoiseReduction: number;
+  customEffectAmount: number;
+  defringeGreenAmount: number;
+  defringeGreenHueMax: number;
+  defringeGreenHueMin: number;
+  defringePurpleAmount: number;
+  defringePurpleHueMax: number;
+  defringePurpleHueMin: number;
   contrast: number;
   curves: Curves;
   crop: Crop | null;
@@ -86,16 +127,26 @@ export interface Adjustments {
   filmBaseColor: string;
   flipHorizontal: boolean;
   flipVertical: boolean;
+  glowAmount: number;
+  glowRadius: number;
+  glowThreshold: number;
   grainAmount: number;
   grainRoughness: number;
   grainSize: number;
+  halationAmount: number;
+  halationColor: string;
+  halationThreshold: number;
   highlights: number;
   hsl: Hsl;
+  lensBlur: LensBlur;
+  lensCorrection: LensCorrection;
   lumaNoiseReduction: number;
+  lut: LutAdjustment | null;
   masks: Array<MaskContainer>;
   negativeBlueBalance: number;
   negativeGreenBalance: number;
   negativeRedBalance: number;
+  noiseReductionDetail: number;
   orientationSteps: number;
   rating: number;
   rotation: number;
@@ -103,8 +154,10 @@ export interface Adjustments {
   sectionVisibility: SectionVisibility;
   shadows: number;
   sharpness: number;
+  spotEdits: Array<SpotEdit>;
   structure: number;
   temperature: number;
+  texture: number;
   tint: number;
   vibrance: number;
   vignetteAmount: number;
@@ -112,15 +165,33 @@ export interface Adjustments {
   vignetteMidpoint: number;
   vignetteRoundness: number;
   whites: number;
+  workingSpace: WorkingSpace;
+}
+
+export interface GenerationParameters {
+  cfg?: number | null;
+  denoise?: number | null;
+  negativePrompt?: string;
+  sampler?: string | null;
+  seed?: number | null;
+  steps?: number | null;
+}
+
+export enum ImageTransform {
+  Relight = 'relight',
+  StyleTransfer = 'styleTransfer',
 }
 
 export interface AiPatch {
+  generation?: GenerationParameters;
   id: string;
   isLoading: boolean;
   name: string;
+  opacity?: number;
   patchData: any | null;
   prompt: string;
   subMasks: Array<SubMask>;
+  transform?: ImageTransform | null;
   visible: boolean;
 }
 
@@ -169,16 +240,57 @@ interface Hsl {
   yellows: HueSatLum;
 }
 
+export enum LensBlurSource {
+  DepthMap = 'depthMap',
+  Radial = 'radial',
+}
+
+export interface LensBlur {
+  amount: number;
+  bladeCount: number;
+  bladeRotation: number;
+  centerX: number;
+  centerY: number;
+  depthMaskId: string | null;
+  feather: number;
+  focalDepth: number;
+  focusRadius: number;
+  focusRange: number;
+  highlightBoost: number;
+  source: LensBlurSource;
+}
+
+export interface LensCorrection {
+  chromaticAberration: boolean;
+  distortion: boolean;
+  enabled: boolean;
+  vignetting: boolean;
+}
+
+export interface LutAdjustment {
+  id: string;
+  intensity: number;
+}
+
 export interface MaskAdjustments {
   [index: string]: any;
   blacks: number;
   clarity: number;
   colorGrading: ColorGradingProps;
   colorNoiseReduction: number;
+  defringeGreenAmount: number;
+  defringeGreenHueMax: number;
+  defringeGreenHueMin: number;
+  defringePurpleAmount: number;
+  defringePurpleHueMax: number;
+  defringePurpleHueMin: number;
   contrast: number;
   curves: Curves;
   dehaze: number;
   exposure: number;
+  glowAmount: number;
+  glowRadius: number;
+  glowThreshold: number;
   highlights: number;
   hsl: Hsl;
   id?: string;
@@ -189,6 +301,7 @@ export interface MaskAdjustments {
   sharpness: number;
   structure: number;
   temperature: number;
+  texture: number;
   tint: number;
   vibrance: number;
   whites: number;
@@ -222,6 +335,28 @@ export interface SectionVisibility {
   effects: boolean;
 }
 
+export enum WorkingSpace {
+  ProPhoto = 'proPhoto',
+  Rec2020 = 'rec2020',
+  Srgb = 'srgb',
+}
+
+export enum SpotEditType {
+  Clone = 'clone',
+  Heal = 'heal',
+}
+
+export interface SpotEdit {
+  brushSize: number;
+  feather: number;
+  id: string;
+  opacity: number;
+  points: Array<Coord>;
+  sourceOffset: Coord | null;
+  type: SpotEditType;
+  visible: boolean;
+}
+
 export const COLOR_LABELS: Array<Color> = [
   { name: 'red', color: '#ef4444' },
   { name: 'yellow', color: '#facc15' },
@@ -243,6 +378,12 @@ export const INITIAL_MASK_ADJUSTMENTS: MaskAdjustments = {
   clarity: 0,
   colorGrading: { ...INITIAL_COLOR_GRADING },
   colorNoiseReduction: 0,
+  defringeGreenAmount: 0,
+  defringeGreenHueMax: 160,
+  defringeGreenHueMin: 80,
+  defringePurpleAmount: 0,
+  defringePurpleHueMax: 320,
+  defringePurpleHueMin: 260,
   contrast: 0,
   curves: {
     blue: [
@@ -264,6 +405,9 @@ export const INITIAL_MASK_ADJUSTMENTS: MaskAdjustments = {
   },
   dehaze: 0,
   exposure: 0,
+  glowAmount: 0,
+  glowRadius: 50,
+  glowThreshold: 50,
   highlights: 0,
   hsl: {
     aquas: { hue: 0, saturation: 0, luminance: 0 },
@@ -288,6 +432,7 @@ export const INITIAL_MASK_ADJUSTMENTS: MaskAdjustments = {
   sharpness: 0,
   structure: 0,
   temperature: 0,
+  texture: 0,
   tint: 0,
   vibrance: 0,
   whites: 0,
@@ -303,12 +448,26 @@ export const INITIAL_MASK_CONTAINER: MaskContainer = {
 };
 
 export const INITIAL_ADJUSTMENTS: Adjustments = {
+  aiDenoise: 0,
   aiPatches: [],
   aspectRatio: null,
   blacks: 0,
+  bloomAmount: 0,
+  chromaticAberration: 0,
   clarity: 0,
+  colorGrainBlue: 0,
+  colorGrainGreen: 0,
+  colorGrainRed: 0,
+  colorGrainSize: 25,
   colorGrading: { ...INITIAL_COLOR_GRADING },
   colorNoiseReduction: 0,
+  customEffectAmount: 0,
+  defringeGreenAmount: 0,
+  defringeGreenHueMax: 160,
+  defringeGreenHueMin: 80,
+  defringePurpleAmount: 0,
+  defringePurpleHueMax: 320,
+  defringePurpleHueMin: 260,
   contrast: 0,
   crop: null,
   curves: {
@@ -335,9 +494,15 @@ export const INITIAL_ADJUSTMENTS: Adjustments = {
   filmBaseColor: '#ff8800',
   flipHorizontal: false,
   flipVertical: false,
+  glowAmount: 0,
+  glowRadius: 50,
+  glowThreshold: 50,
   grainAmount: 0,
   grainRoughness: 50,
   grainSize: 25,
+  halationAmount: 0,
+  halationColor: '#ff3c14',
+  halationThreshold: 80,
   highlights: 0,
   hsl: {
     aquas: { hue: 0, saturation: 0, luminance: 0 },
@@ -349,11 +514,33 @@ export const INITIAL_ADJUSTMENTS: Adjustments = {
     reds: { hue: 0, saturation: 0, luminance: 0 },
     yellows: { hue: 0, saturation: 0, luminance: 0 },
   },
+  lensBlur: {
+    amount: 0,
+    bladeCount: 0,
+    bladeRotation: 0,
+    centerX: 0.5,
+    centerY: 0.5,
+    depthMaskId: null,
+    feather: 30,
+    focalDepth: 100,
+    focusRadius: 20,
+    focusRange: 10,
+    highlightBoost: 50,
+    source: LensBlurSource.Radial,
+  },
+  lensCorrection: {
+    chromaticAberration: true,
+    distortion: true,
+    enabled: false,
+    vignetting: true,
+  },
   lumaNoiseReduction: 0,
+  lut: null,
   masks: [],
   negativeBlueBalance: 0,
   negativeGreenBalance: 0,
   negativeRedBalance: 0,
+  noiseReductionDetail: 50,
   orientationSteps: 0,
   rating: 0,
   rotation: 0,
@@ -367,8 +554,10 @@ export const INITIAL_ADJUSTMENTS: Adjustments = {
   },
   shadows: 0,
   sharpness: 0,
+  spotEdits: [],
   structure: 0,
   temperature: 0,
+  texture: 0,
   tint: 0,
   vibrance: 0,
   vignetteAmount: 0,
@@ -376,6 +565,7 @@ export const INITIAL_ADJUSTMENTS: Adjustments = {
   vignetteMidpoint: 50,
   vignetteRoundness: 0,
   whites: 0,
+  workingSpace: WorkingSpace.Srgb,
 };
 
 export const normalizeLoadedAdjustments = (loadedAdjustments: Adjustments): any => {
@@ -419,6 +609,8 @@ export const normalizeLoadedAdjustments = (loadedAdjustments: Adjustments): any
     colorGrading: { ...INITIAL_ADJUSTMENTS.colorGrading, ...(loadedAdjustments.colorGrading || {}) },
     hsl: { ...INITIAL_ADJUSTMENTS.hsl, ...(loadedAdjustments.hsl || {}) },
     curves: { ...INITIAL_ADJUSTMENTS.curves, ...(loadedAdjustments.curves || {}) },
+    lensBlur: { ...INITIAL_ADJUSTMENTS.lensBlur, ...(loadedAdjustments.lensBlur || {}) },
+    lensCorrection: { ...INITIAL_ADJUSTMENTS.lensCorrection, ...(loadedAdjustments.lensCorrection || {}) },
     masks: normalizedMasks,
     aiPatches: normalizedAiPatches,
     sectionVisibility: {
@@ -430,30 +622,53 @@ export const normalizeLoadedAdjustments = (loadedAdjustments: Adjustments): any
 
 export const COPYABLE_ADJUSTMENT_KEYS: Array<string> = [
   BasicAdjustment.Blacks,
+  Effect.BloomAmount,
+  DetailsAdjustment.ChromaticAberration,
   Effect.Clarity,
+  Effect.ColorGrainBlue,
+  Effect.ColorGrainGreen,
+  Effect.ColorGrainRed,
+  Effect.ColorGrainSize,
   ColorAdjustment.ColorGrading,
   DetailsAdjustment.ColorNoiseReduction,
+  Effect.CustomEffectAmount,
+  DetailsAdjustment.DefringeGreenAmount,
+  DetailsAdjustment.DefringeGreenHueMax,
+  DetailsAdjustment.DefringeGreenHueMin,
+  DetailsAdjustment.DefringePurpleAmount,
+  DetailsAdjustment.DefringePurpleHueMax,
+  DetailsAdjustment.DefringePurpleHueMin,
   BasicAdjustment.Contrast,
   'curves',
   Effect.Dehaze,
   Effect.EnableNegativeConversion,
   BasicAdjustment.Exposure,
   Effect.FilmBaseColor,
+  Effect.GlowAmount,
+  Effect.GlowRadius,
+  Effect.GlowThreshold,
   Effect.GrainAmount,
   Effect.GrainRoughness,
   Effect.GrainSize,
+  Effect.HalationAmount,
+  Effect.HalationColor,
+  Effect.HalationThreshold,
   BasicAdjustment.Highlights,
   ColorAdjustment.Hsl,
+  DetailsAdjustment.LensCorrection,
   DetailsAdjustment.LumaNoiseReduction,
+  Effect.Lut,
   Effect.NegativeBlueBalance,
   Effect.NegativeGreenBalance,
   Effect.NegativeRedBalance,
+  DetailsAdjustment.NoiseReductionDetail,
   ColorAdjustment.Saturation,
   'sectionVisibility',
   BasicAdjustment.Shadows,
   DetailsAdjustment.Sharpness,
   Effect.Structure,
   ColorAdjustment.Temperature,
+  Effect.Texture,
   ColorAdjustment.Tint,
   ColorAdjustment.Vibrance,
   Effect.VignetteAmount,
@@ -461,6 +676,7 @@ export const COPYABLE_ADJUSTMENT_KEYS: Array<string> = [
   Effect.VignetteMidpoint,
   Effect.VignetteRoundness,
   BasicAdjustment.Whites,
+  ColorAdjustment.WorkingSpace,
 ];
 
 export const ADJUSTMENT_SECTIONS: Sections = {
@@ -480,20 +696,48 @@ export const ADJUSTMENT_SECTIONS: Sections = {
     ColorAdjustment.Vibrance,
     ColorAdjustment.Hsl,
     ColorAdjustment.ColorGrading,
+    ColorAdjustment.WorkingSpace,
+  ],
+  details: [
+    DetailsAdjustment.Sharpness,
+    DetailsAdjustment.LumaNoiseReduction,
+    DetailsAdjustment.ColorNoiseReduction,
+    DetailsAdjustment.NoiseReductionDetail,
+    DetailsAdjustment.ChromaticAberration,
+    DetailsAdjustment.DefringePurpleAmount,
+    DetailsAdjustment.DefringePurpleHueMin,
+    DetailsAdjustment.DefringePurpleHueMax,
+    DetailsAdjustment.DefringeGreenAmount,
+    DetailsAdjustment.DefringeGreenHueMin,
+    DetailsAdjustment.DefringeGreenHueMax,
+    DetailsAdjustment.LensCorrection,
   ],
-  details: [DetailsAdjustment.Sharpness, DetailsAdjustment.LumaNoiseReduction, DetailsAdjustment.ColorNoiseReduction],
   effects: [
+    Effect.BloomAmount,
     Effect.Clarity,
+    Effect.ColorGrainBlue,
+    Effect.ColorGrainGreen,
+    Effect.ColorGrainRed,
+    Effect.ColorGrainSize,
+    Effect.CustomEffectAmount,
     Effect.Dehaze,
     Effect.EnableNegativeConversion,
     Effect.FilmBaseColor,
+    Effect.GlowAmount,
+    Effect.GlowRadius,
+    Effect.GlowThreshold,
     Effect.GrainAmount,
     Effect.GrainRoughness,
     Effect.GrainSize,
+    Effect.HalationAmount,
+    Effect.HalationColor,
+    Effect.HalationThreshold,
+    Effect.Lut,
     Effect.NegativeBlueBalance,
     Effect.NegativeGreenBalance,
     Effect.NegativeRedBalance,
     Effect.Structure,
+    Effect.Texture,
     Effect.VignetteAmount,
     Effect.VignetteFeather,
     Effect.VignetteMidpoint,
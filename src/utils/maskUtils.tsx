@@ -30,6 +30,12 @@ export const createSubMask = (type: Mask, imageDimensions: ImageDimensions) => {
       return { ...common, parameters: { maskDataBase64: null, grow: 0, feather: 0 } };
     case Mask.AiForeground:
       return { ...common, parameters: { maskDataBase64: null, grow: 0, feather: 0 } };
+    case Mask.AiFace:
+      return { ...common, parameters: { maskDataBase64: null, grow: 0, feather: 0 } };
+    case Mask.Luminance:
+      return { ...common, parameters: { minLuminance: 0.5, maxLuminance: 1, feather: 0.2 } };
+    case Mask.Group:
+      return { ...common, parameters: {}, subMasks: [] };
     case Mask.QuickEraser:
       return { ...common, parameters: { maskDataBase64: null, grow: 50, feather: 50 } };
     default: